@@ -10,12 +10,19 @@ use bb_challenge::{
     data_provider::{
         enumerator::Enumerator,
         enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        DataProvider,
     },
     decider::{
-        decider_engine, decider_halt_long::DeciderHaltLong, decider_halt_macro::DeciderHaltMacro,
-        decider_result::result_max_steps_known, Decider, DeciderConfig, DeciderStandard,
+        batch_stepper::{run_batch_halts, BATCH_STEPPER_STEP_LIMIT},
+        decider_cycler::DeciderCycler,
+        decider_engine,
+        decider_halt_long::DeciderHaltLong,
+        decider_halt_macro::DeciderHaltMacro,
+        decider_result::result_max_steps_known,
+        Decider, DeciderConfig, DeciderStandard,
     },
     machine_binary::{MachineId, NotableMachineBinary},
+    sim_core::{run_u128, CoreRunResult},
     status::MachineStatus,
     unused::{decider_halt_128::DeciderHalt128, decider_halt_64::DeciderHalt64},
 };
@@ -25,10 +32,15 @@ const MEASUREMENT_TIME_MS: u64 = 2000;
 const BENCH_GENERATOR_BATCH_SIZE_REQUEST_FULL: usize = 500_000;
 const BENCH_GENERATOR_BATCH_SIZE_REQUEST_REDUCED: usize = 1_000_000;
 const GENERATOR_LIMIT: u64 = 50_000_000;
+// The first BB5 machines are mostly eliminated by the pre-decider, so start in the middle.
+const DECIDER_BB5_START_ID: u64 = 8_000_000_000_000;
+const DECIDER_BB5_LIMIT: u64 = 10_000_000;
 
 criterion_group!(
     benches,
     benchmark_tape_type,
+    benchmark_decider_bb5,
+    benchmark_batch_stepper,
     // benchmark_enumerator,
     // benchmark_decider_gen_bb3,
     // benchmark_decider_gen_bb4,
@@ -114,7 +126,7 @@ fn benchmark_decider_gen_bb3(c: &mut Criterion) {
                 &dc_cycler,
                 &config,
                 EnumeratorType::EnumeratorFullBackward,
-                CoreUsage::MultiCore,
+                CoreUsage::MultiCore(0),
             )
         })
     });
@@ -128,7 +140,7 @@ fn benchmark_decider_gen_bb3(c: &mut Criterion) {
                     &dc_cycler,
                     &config,
                     EnumeratorType::EnumeratorReducedForward,
-                    CoreUsage::MultiCore,
+                    CoreUsage::MultiCore(0),
                 )
             })
         },
@@ -143,7 +155,7 @@ fn benchmark_decider_gen_bb3(c: &mut Criterion) {
     //                 &dc_cycler,
     //                 &config,
     //                 EnumeratorType::EnumeratorReducedBackward,
-    //                 CoreUsage::MultiCore,
+    //                 CoreUsage::MultiCore(0),
     //             )
     //         })
     //     },
@@ -212,7 +224,7 @@ fn benchmark_decider_gen_bb4(c: &mut Criterion) {
                 &dc_cycler,
                 &config,
                 EnumeratorType::EnumeratorFullBackward,
-                CoreUsage::MultiCore,
+                CoreUsage::MultiCore(0),
             )
         })
     });
@@ -226,7 +238,7 @@ fn benchmark_decider_gen_bb4(c: &mut Criterion) {
                     &dc_cycler,
                     &config,
                     EnumeratorType::EnumeratorReducedForward,
-                    CoreUsage::MultiCore,
+                    CoreUsage::MultiCore(0),
                 )
             })
         },
@@ -235,6 +247,112 @@ fn benchmark_decider_gen_bb4(c: &mut Criterion) {
     group.finish();
 }
 
+/// Hold and Bouncer decider on BB5 machines, which mainly measures the step loop.
+fn benchmark_decider_bb5(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Bench Decider BB5");
+    let config = Config::builder(5)
+        .enumerator_reduced_batch_size_request(BENCH_GENERATOR_BATCH_SIZE_REQUEST_REDUCED)
+        .start_from_id(DECIDER_BB5_START_ID)
+        .machine_limit(DECIDER_BB5_LIMIT)
+        .step_limit_decider_halt(1_000)
+        .step_limit_decider_bouncer(1_000)
+        .build();
+    let dc_hold: DeciderConfig<'_> = DeciderStandard::Hold.decider_config(&config);
+    let dc_bouncer: DeciderConfig<'_> = DeciderStandard::Bouncer128.decider_config(&config);
+
+    group.warm_up_time(Duration::from_millis(WARM_UP_TIME_MS));
+    group.sample_size(10);
+
+    group.bench_function("Decider Hold (Enumerator Reduced) Single BB5", |b| {
+        b.iter(|| {
+            bench_decider_data_provider_gen(
+                &dc_hold,
+                &config,
+                EnumeratorType::EnumeratorReducedForward,
+                CoreUsage::SingleCore,
+            )
+        })
+    });
+    group.bench_function("Decider Bouncer (Enumerator Reduced) Single BB5", |b| {
+        b.iter(|| {
+            bench_decider_data_provider_gen(
+                &dc_bouncer,
+                &config,
+                EnumeratorType::EnumeratorReducedForward,
+                CoreUsage::SingleCore,
+            )
+        })
+    });
+
+    group.finish();
+}
+
+/// Lockstep batch stepper compared to the scalar run of the core for the same steps, and the cycler on each
+/// machine compared to the batch stepper deciding the machines halting early followed by the cycler. \
+/// The lanes only run on wide registers, e.g. RUSTFLAGS="-C target-cpu=native" cargo bench.
+fn benchmark_batch_stepper(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Bench Batch Stepper");
+    let config = Config::builder(5)
+        .start_from_id(DECIDER_BB5_START_ID)
+        .step_limit_decider_cycler(BATCH_STEPPER_STEP_LIMIT as _)
+        .build();
+    let mut enumerator = EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+    let machines = enumerator.machine_batch_next().unwrap().machines;
+
+    group.warm_up_time(Duration::from_millis(WARM_UP_TIME_MS));
+    group.sample_size(10);
+
+    group.bench_function("Core run each machine BB5", |b| {
+        b.iter(|| {
+            machines
+                .iter()
+                .filter(|m| {
+                    let transitions = m.machine().transitions_all().map(|t| t.transition);
+                    matches!(
+                        run_u128(&transitions, BATCH_STEPPER_STEP_LIMIT),
+                        CoreRunResult::Halt(_)
+                    )
+                })
+                .count()
+        })
+    });
+    group.bench_function("Batch stepper BB5", |b| {
+        b.iter(|| {
+            run_batch_halts(&machines, BATCH_STEPPER_STEP_LIMIT)
+                .iter()
+                .filter(|s| **s != MachineStatus::NoDecision)
+                .count()
+        })
+    });
+    group.bench_function("Cycler each machine BB5", |b| {
+        b.iter(|| {
+            let mut cycler = DeciderCycler::new(&config);
+            machines
+                .iter()
+                .map(|m| cycler.decide_machine(m))
+                .filter(|s| matches!(s, MachineStatus::DecidedHaltField(_, _)))
+                .count()
+        })
+    });
+    group.bench_function("Batch stepper and cycler BB5", |b| {
+        b.iter(|| {
+            let mut cycler = DeciderCycler::new(&config);
+            let statuses = run_batch_halts(&machines, BATCH_STEPPER_STEP_LIMIT);
+            machines
+                .iter()
+                .zip(statuses)
+                .map(|(m, s)| match s {
+                    MachineStatus::NoDecision => cycler.decide_machine(m),
+                    s => s,
+                })
+                .filter(|s| matches!(s, MachineStatus::DecidedHaltField(_, _)))
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
 fn benchmark_tape_type(c: &mut Criterion) {
     // let input = aoc_file_reader::read_file(FILENAME_PART_1);
     // machine_bb5_max.step_limit = 50_000_000;
@@ -459,13 +577,18 @@ fn bench_decider_data_provider_gen(
                 None,
             )
         }
-        CoreUsage::MultiCore => {
+        CoreUsage::MultiCore(_) | CoreUsage::Percent(_) => {
             decider_engine::batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                 &vec![dc_cycler],
                 enumerator,
                 None,
             )
         }
+        CoreUsage::Pipelined => decider_engine::batch_run_decider_chain_pipelined_reporting(
+            &vec![dc_cycler],
+            enumerator,
+            None,
+        ),
     };
     // println!("{}", result);
     let n_states = config.n_states();
@@ -0,0 +1,133 @@
+//! This example is a minimal worker for low memory devices like a Raspberry Pi. \
+//! It decides a range of the bb_challenge seed file with small batches on a single thread and prints only the
+//! counts as TOML, so the results of several devices can be collected and added up.
+//!
+//! Build it without the html reports and the decider timers and with the small 'embedded' profile:
+//! 'cargo build --example embedded_worker --no-default-features --profile embedded'.
+//! The number formatting uses the built-in 'en' locale (see [user_locale](bb_challenge::config::user_locale)),
+//! so no system locale data is needed. The deciders run on the 128-bit tape, which needs 16 bytes per machine,
+//! a smaller tape would not reduce the memory of the small batches.
+//!
+//! Worker protocol:
+//! - Each worker runs 'embedded_worker <seed file> <first machine id> <number of machines>' and writes the
+//!   TOML result (range and counters) to stdout, e.g. into a file per range.
+//! - The collector runs 'embedded_worker merge <result files>'. The ranges must not overlap and must not
+//!   have gaps, then the counters are added and the total result is printed.
+
+use bb_challenge::{
+    checkpoint::CheckpointCounters,
+    config::Config,
+    data_provider::BBChallengeSeedFileProvider,
+    decider::{
+        decider_engine::batch_run_decider_chain_data_provider_single_thread,
+        decider_result::DeciderResultStats, DeciderStandard,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// Small batches keep the memory low.
+const BATCH_SIZE: usize = 1_000;
+const N_STATES: usize = 5;
+
+/// Result of a worker for its range of machines.
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkerResult {
+    first_id: u64,
+    num_machines: u64,
+    counters: CheckpointCounters,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let status = match args.get(1).map(String::as_str) {
+        Some("merge") if args.len() > 2 => merge(&args[2..]),
+        Some(_) if args.len() == 4 => work(&args[1], &args[2], &args[3]),
+        _ => {
+            eprintln!("Usage: embedded_worker <seed file> <first machine id> <number of machines>");
+            eprintln!("       embedded_worker merge <result files>");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = status {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+/// Decides the range of machines and prints the [WorkerResult].
+fn work(file_path: &str, first_id: &str, num_machines: &str) -> Result<(), String> {
+    let (Ok(first_id), Ok(num_machines)) = (first_id.parse::<u64>(), num_machines.parse::<u64>())
+    else {
+        return Err(format!("Invalid machine range: {first_id} {num_machines}"));
+    };
+
+    let config = Config::builder(N_STATES)
+        .num_threads(1)
+        .step_limit_decider_cycler(1_500)
+        .step_limit_decider_bouncer(5_000)
+        // counts only
+        .limit_machines_undecided(0)
+        .build();
+    let deciders = [
+        DeciderStandard::Cycler.decider_config(&config),
+        DeciderStandard::Bouncer128.decider_config(&config),
+    ];
+    let data_provider = BBChallengeSeedFileProvider::try_new(
+        file_path,
+        BATCH_SIZE,
+        Some(first_id..first_id + num_machines),
+    )
+    .map_err(|e| e.to_string())?;
+
+    let result = batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider);
+    let worker_result = WorkerResult {
+        first_id,
+        num_machines,
+        counters: result.checkpoint_counters(),
+    };
+    print!(
+        "{}",
+        toml::to_string(&worker_result).expect("worker result is valid TOML")
+    );
+
+    Ok(())
+}
+
+/// Reads the [WorkerResult] files, checks that the ranges are contiguous and prints the total result.
+fn merge(file_paths: &[String]) -> Result<(), String> {
+    let mut worker_results = Vec::with_capacity(file_paths.len());
+    for file_path in file_paths {
+        let text = std::fs::read_to_string(file_path).map_err(|e| format!("{file_path}: {e}"))?;
+        let worker_result: WorkerResult =
+            toml::from_str(&text).map_err(|e| format!("{file_path}: {e}"))?;
+        worker_results.push(worker_result);
+    }
+    worker_results.sort_by_key(|r| r.first_id);
+
+    let config = Config::new_default(N_STATES);
+    let mut total = DeciderResultStats::new(&config);
+    let mut next_id = worker_results[0].first_id;
+    for r in worker_results.iter() {
+        if r.first_id != next_id {
+            return Err(format!(
+                "Ranges are not contiguous: expected first id {next_id}, found {}",
+                r.first_id
+            ));
+        }
+        next_id = r.first_id + r.num_machines;
+        total.add_result(&DeciderResultStats::from_checkpoint(
+            &config,
+            &r.counters,
+            Vec::new(),
+            Vec::new(),
+        ));
+    }
+    println!(
+        "Machines {}..{next_id} from {} workers",
+        worker_results[0].first_id,
+        worker_results.len()
+    );
+    println!("{total}");
+
+    Ok(())
+}
@@ -0,0 +1,334 @@
+//! Behavioral analysis of machines independent of a decider. \
+//! [traces_equivalent] checks if two machines run identically for a number of steps, only the state names
+//! and possibly the directions (mirrored machine) may differ. This verifies canonicalization
+//! (e.g. the normalized machine must behave like the original) and allows to cluster holdouts into
+//! behavioral equivalence classes with [trace_equivalence_classes]. \
+//! [decide_halt_siblings] decides the siblings of a machine, which only differ in the field of the halt transition.
+//! This shows how close a non-halting machine or holdout is to halting behavior. \
+//! [transition_stats] compares the transition tables of the undecided machines with the decided ones. \
+//! [symbol_frequency] flags likely bouncers and counters by the density of the written ones for triage.
+//! # Example
+//! ```
+//! use bb_challenge::{analysis::traces_equivalent, machine_binary::MachineBinary};
+//!
+//! let m1 = MachineBinary::try_from_standard_tm_text_format("1RB1LC_1LA1RB_1LB---").unwrap();
+//! let m2 = MachineBinary::try_from_standard_tm_text_format("1RC1LB_1LC---_1LA1RC").unwrap();
+//! assert!(traces_equivalent(&m1, &m2, 1000));
+//! ```
+
+pub mod symbol_frequency;
+pub mod transition_stats;
+
+use std::fmt::Display;
+
+use crate::{
+    config::{StepBig, MAX_STATES},
+    decider::{decider_engine::decide_machines_statuses, decider_result::EndReason, DeciderConfig},
+    machine_binary::{MachineBinary, MachineId, TRANSITION_TABLE_BINARY_DEFAULT},
+    machine_runner::MachineRunner,
+    status::MachineStatus,
+};
+
+/// Relabeling which maps the trace of the first machine onto the trace of the second machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceRelabeling {
+    /// State of the second machine for each state of the first machine (A = 1), 0 if the state was not reached.
+    pub states: [u8; MAX_STATES + 1],
+    /// True if the second machine moves in the opposite direction.
+    pub is_mirrored: bool,
+}
+
+/// Returns the relabeling if both machines produce identical head/state/symbol traces for the given
+/// number of steps up to renaming the states and mirroring the direction, otherwise None. \
+/// Machines halting within the steps must halt in the same step.
+pub fn trace_relabeling(
+    m1: &MachineBinary,
+    m2: &MachineBinary,
+    steps: StepBig,
+) -> Option<TraceRelabeling> {
+    let mut relabeling = TraceRelabeling {
+        states: [0; MAX_STATES + 1],
+        is_mirrored: false,
+    };
+    // inverse mapping to keep the relabeling bijective
+    let mut states_inverse = [0u8; MAX_STATES + 1];
+    let mut is_direction_known = false;
+    let mut r1 = MachineRunner::new(m1);
+    let mut r2 = MachineRunner::new(m2);
+    for (s1, s2) in r1.steps().zip(r2.steps()).take(steps as usize) {
+        match (relabeling.states[s1.state], states_inverse[s2.state]) {
+            (0, 0) => {
+                relabeling.states[s1.state] = s2.state as u8;
+                states_inverse[s2.state] = s1.state as u8;
+            }
+            (a, b) if a as usize == s2.state && b as usize == s1.state => {}
+            _ => return None,
+        }
+        if s1.read != s2.read || s1.is_halt() != s2.is_halt() {
+            return None;
+        }
+        if s1.is_halt() {
+            break;
+        }
+        if s1.symbol_written() != s2.symbol_written() {
+            return None;
+        }
+        let is_mirrored = s1.head_delta != s2.head_delta;
+        if !is_direction_known {
+            relabeling.is_mirrored = is_mirrored;
+            is_direction_known = true;
+        } else if relabeling.is_mirrored != is_mirrored {
+            return None;
+        }
+    }
+
+    Some(relabeling)
+}
+
+/// Returns true if both machines produce identical head/state/symbol traces for the given number of steps
+/// up to renaming the states and mirroring the direction, see [trace_relabeling].
+pub fn traces_equivalent(m1: &MachineBinary, m2: &MachineBinary, steps: StepBig) -> bool {
+    trace_relabeling(m1, m2, steps).is_some()
+}
+
+/// Groups the machines into classes of machines with equivalent traces for the given number of steps. \
+/// The first machine of each class is its representative, classes and machines keep the input order.
+pub fn trace_equivalence_classes(machines: &[MachineId], steps: StepBig) -> Vec<Vec<MachineId>> {
+    let mut classes: Vec<Vec<MachineId>> = Vec::new();
+    for m in machines.iter() {
+        match classes
+            .iter_mut()
+            .find(|c| traces_equivalent(c[0].machine(), m.machine(), steps))
+        {
+            Some(class) => class.push(*m),
+            None => classes.push(vec![*m]),
+        }
+    }
+
+    classes
+}
+
+/// Returns the siblings of the machine: for each other used field the machine with the halt transition
+/// relocated to that field, which receives the transition of that field in exchange. \
+/// Returns the array id of the relocated halt transition and the sibling. The machine must have exactly one
+/// halt (or undefined) transition, otherwise there are no siblings.
+pub fn halt_siblings(machine: &MachineBinary) -> Vec<(usize, MachineBinary)> {
+    let n_states = machine.n_states();
+    let last = n_states * 2 + 2;
+    let halt_ids: Vec<usize> = (2..last)
+        .filter(|id| machine.transition_array_id(*id).is_halt())
+        .collect();
+    let [halt_id] = halt_ids[..] else {
+        return Vec::new();
+    };
+
+    let mut siblings = Vec::with_capacity(last - 3);
+    for id in (2..last).filter(|id| *id != halt_id) {
+        let mut transitions = machine.transitions_all();
+        transitions.swap(id, halt_id);
+        // the stored metadata (self-ref, features) is not valid for the sibling
+        transitions[0] = TRANSITION_TABLE_BINARY_DEFAULT[0];
+        transitions[1] = TRANSITION_TABLE_BINARY_DEFAULT[1];
+        siblings.push((id, MachineBinary::new_with_n_states(transitions, n_states)));
+    }
+
+    siblings
+}
+
+/// Sibling of [HaltSiblingFamily] with its decided status.
+#[derive(Debug, Clone, Copy)]
+pub struct HaltSibling {
+    /// Array id of the field holding the halt transition.
+    pub halt_array_id: usize,
+    pub machine: MachineBinary,
+    pub status: MachineStatus,
+}
+
+/// Result of [decide_halt_siblings].
+#[derive(Debug, Clone)]
+pub struct HaltSiblingFamily {
+    pub machine: MachineId,
+    pub siblings: Vec<HaltSibling>,
+}
+
+impl HaltSiblingFamily {
+    pub fn num_halt(&self) -> usize {
+        self.siblings
+            .iter()
+            .filter(|s| is_halt_status(&s.status))
+            .count()
+    }
+
+    pub fn num_non_halt(&self) -> usize {
+        self.siblings
+            .iter()
+            .filter(|s| matches!(s.status, MachineStatus::DecidedNonHalt(_)))
+            .count()
+    }
+
+    pub fn num_undecided(&self) -> usize {
+        self.siblings.len() - self.num_halt() - self.num_non_halt()
+    }
+
+    /// Family outcome: true if all siblings are decided as non-halting.
+    pub fn is_all_non_halt(&self) -> bool {
+        self.num_non_halt() == self.siblings.len()
+    }
+
+    /// Halting sibling with the most steps, None if no sibling halts.
+    pub fn halt_max_steps(&self) -> Option<&HaltSibling> {
+        self.siblings
+            .iter()
+            .filter(|s| is_halt_status(&s.status))
+            .max_by_key(|s| s.status.steps())
+    }
+}
+
+impl Display for HaltSiblingFamily {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Halt siblings of {}: {} siblings, halt: {}, non-halt: {}, undecided: {}",
+            self.machine.to_standard_tm_text_format(),
+            self.siblings.len(),
+            self.num_halt(),
+            self.num_non_halt(),
+            self.num_undecided()
+        )?;
+        for s in self.siblings.iter() {
+            writeln!(
+                f,
+                "   Halt in {}: {}, {}",
+                MachineBinary::array_id_to_field_name(s.halt_array_id),
+                s.machine.to_standard_tm_text_format(),
+                s.status
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn is_halt_status(status: &MachineStatus) -> bool {
+    matches!(
+        status,
+        MachineStatus::DecidedHalt(_)
+            | MachineStatus::DecidedHaltField(_, _)
+            | MachineStatus::DecidedHaltDetail(_, _, _)
+    )
+}
+
+/// Decides all [halt_siblings] of the machine as one group with the decider chain. \
+/// Usually called for a machine decided as non-halting or for a holdout.
+pub fn decide_halt_siblings(
+    decider_configs: &[DeciderConfig],
+    machine: &MachineId,
+) -> Result<HaltSiblingFamily, EndReason> {
+    let siblings = halt_siblings(machine.machine());
+    let machines: Vec<MachineId> = siblings
+        .iter()
+        .map(|(_, m)| MachineId::new_no_id(*m))
+        .collect();
+    let statuses = if machines.is_empty() {
+        Vec::new()
+    } else {
+        decide_machines_statuses(decider_configs, &machines)?
+    };
+
+    Ok(HaltSiblingFamily {
+        machine: *machine,
+        siblings: siblings
+            .into_iter()
+            .zip(statuses)
+            .map(|((halt_array_id, machine), status)| HaltSibling {
+                halt_array_id,
+                machine,
+                status,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Config, decider::DeciderStandard, machine_binary::NotableMachineBinary};
+
+    fn machine(tm: &str) -> MachineBinary {
+        MachineBinary::try_from_standard_tm_text_format(tm).unwrap()
+    }
+
+    #[test]
+    fn traces_equivalent_relabeled_and_mirrored() {
+        // BB3 Max, states B and C swapped, and mirrored
+        let bb3 = NotableMachineBinary::BB3Max.machine();
+        let tm = bb3.to_standard_tm_text_format();
+        let parts: Vec<&str> = tm.split('_').collect();
+        let swapped = format!("{}_{}_{}", parts[0], parts[2], parts[1])
+            .replace('B', "x")
+            .replace('C', "B")
+            .replace('x', "C");
+        let mirrored = tm.replace('L', "x").replace('R', "L").replace('x', "R");
+
+        let relabeling = trace_relabeling(&bb3, &machine(&swapped), 100).unwrap();
+        assert!(!relabeling.is_mirrored);
+        assert_eq!(relabeling.states[1..4], [1, 3, 2]);
+        let relabeling = trace_relabeling(&bb3, &machine(&mirrored), 100).unwrap();
+        assert!(relabeling.is_mirrored);
+        assert_eq!(relabeling.states[1..4], [1, 2, 3]);
+        assert!(traces_equivalent(&bb3, &bb3, 100));
+    }
+
+    #[test]
+    fn traces_equivalent_differs() {
+        // same for the first step, then one writes 0 instead of 1
+        let m1 = machine("1RB1LB_1LA---");
+        let m2 = machine("1RB1LB_0LA---");
+        assert!(traces_equivalent(&m1, &m2, 1));
+        assert!(!traces_equivalent(&m1, &m2, 2));
+        // undefined and halt transition behave the same
+        let bb2 = machine("1RB1LB_1LA1RZ");
+        assert!(traces_equivalent(&bb2, &m1, 100));
+        assert!(!traces_equivalent(&bb2, &m2, 100));
+
+        let classes = trace_equivalence_classes(
+            &[
+                MachineId::new(1, m1),
+                MachineId::new(2, m2),
+                MachineId::new(3, m1),
+            ],
+            10,
+        );
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[0].len(), 2);
+        assert_eq!(classes[0][1].id(), 3);
+    }
+
+    #[test]
+    fn halt_siblings_decided_as_family() {
+        // BB2 Max with halt in B1
+        let bb2 = MachineId::try_from("1RB1LB_1LA1RZ").unwrap();
+        let siblings = halt_siblings(bb2.machine());
+        assert_eq!(siblings.len(), 3);
+        assert_eq!(siblings[0].0, 2);
+        assert_eq!(siblings[0].1.to_standard_tm_text_format(), "1RZ1LB_1LA1RB");
+        assert_eq!(siblings[2].1.to_standard_tm_text_format(), "1RB1LB_1RZ1LA");
+        // no single halt transition
+        assert!(halt_siblings(&machine("1RB---_1LA---")).is_empty());
+
+        let config = Config::new_default(2);
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let family = decide_halt_siblings(&deciders, &bb2).unwrap();
+        assert_eq!(family.siblings.len(), 3);
+        assert_eq!(family.siblings[0].status.steps(), Some(1));
+        assert_eq!(
+            family.num_halt() + family.num_non_halt() + family.num_undecided(),
+            3
+        );
+        assert!(!family.is_all_non_halt());
+        assert!(family.halt_max_steps().is_some());
+        assert!(family.to_string().contains("Halt in A0: 1RZ1LB_1LA1RB"));
+    }
+}
@@ -0,0 +1,215 @@
+//! Cheap triage of undecided machines by the frequency of the written ones, without deciding them. \
+//! The machine runs for a number of steps, split into windows. At the end of each window the density of ones
+//! on the visited tape is measured. Bouncers and counters fill the growing tape with a stable pattern, so their
+//! density converges while the tape grows. Such machines are flagged as [TriageFlag::LikelyBouncerCounter]. \
+//! The flag is a heuristic for the order in which holdouts are examined and for the classification report of the
+//! remaining machines, it is never a decision.
+
+use std::fmt::Display;
+
+use num_format::ToFormattedString;
+
+use crate::{
+    config::{user_locale, StepBig},
+    machine_binary::MachineBinary,
+    machine_info::MachineInfo,
+    machine_runner::MachineRunner,
+};
+
+/// Steps run per machine, enough for a few sweeps of typical BB5 bouncers.
+pub const TRIAGE_STEPS_DEFAULT: StepBig = 20_000;
+/// Number of windows the steps are split into.
+const NUM_WINDOWS: usize = 8;
+/// The density of the last half of the windows may differ by this much to count as converged.
+const DENSITY_TOLERANCE: f64 = 0.02;
+
+/// Triage result of a machine, in the order of [triage_order].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TriageFlag {
+    /// The machine halted within the triage steps, e.g. because the deciders ran with a lower step limit.
+    Halted,
+    /// The density of ones converges on a growing tape, typical for bouncers and counters.
+    LikelyBouncerCounter,
+    Unclassified,
+}
+
+impl Display for TriageFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriageFlag::Halted => write!(f, "halted"),
+            TriageFlag::LikelyBouncerCounter => write!(f, "likely bouncer/counter"),
+            TriageFlag::Unclassified => write!(f, "unclassified"),
+        }
+    }
+}
+
+/// Density of the written ones of a machine, see [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolFrequency {
+    /// Ones per visited cell at the end of each window, empty if the machine halted.
+    pub densities: Vec<f64>,
+    /// Visited cells at the end of each window.
+    pub tape_sizes: Vec<u64>,
+    pub flag: TriageFlag,
+}
+
+impl SymbolFrequency {
+    /// Runs the machine for the given steps and measures the density of ones in each window.
+    pub fn from_machine(machine: &MachineBinary, steps: StepBig) -> Self {
+        let window_size = (steps as usize / NUM_WINDOWS).max(1);
+        let mut densities = Vec::with_capacity(NUM_WINDOWS);
+        let mut tape_sizes = Vec::with_capacity(NUM_WINDOWS);
+        let mut runner = MachineRunner::new(machine);
+        let mut num_ones: i64 = 0;
+        let (mut head_pos, mut head_pos_min, mut head_pos_max) = (0i64, 0, 0);
+        for (i, step) in runner.steps().take(window_size * NUM_WINDOWS).enumerate() {
+            if step.is_halt() {
+                return Self {
+                    densities: Vec::new(),
+                    tape_sizes: Vec::new(),
+                    flag: TriageFlag::Halted,
+                };
+            }
+            num_ones += (step.symbol_written() == 1) as i64 - (step.read == 1) as i64;
+            head_pos += step.head_delta as i64;
+            head_pos_min = head_pos_min.min(head_pos);
+            head_pos_max = head_pos_max.max(head_pos);
+            if (i + 1) % window_size == 0 {
+                let tape_size = (head_pos_max - head_pos_min + 1) as u64;
+                tape_sizes.push(tape_size);
+                densities.push(num_ones as f64 / tape_size.max(1) as f64);
+            }
+        }
+        let flag = Self::flag(&densities, &tape_sizes);
+
+        Self {
+            densities,
+            tape_sizes,
+            flag,
+        }
+    }
+
+    /// Flags the machine if the density of the last half of the windows converges while the tape grows.
+    fn flag(densities: &[f64], tape_sizes: &[u64]) -> TriageFlag {
+        let half = densities.len() / 2;
+        if half == 0 || tape_sizes[densities.len() - 1] <= tape_sizes[half - 1] {
+            return TriageFlag::Unclassified;
+        }
+        let last = &densities[half..];
+        let min = last.iter().copied().fold(f64::MAX, f64::min);
+        let max = last.iter().copied().fold(f64::MIN, f64::max);
+        if max - min <= DENSITY_TOLERANCE {
+            TriageFlag::LikelyBouncerCounter
+        } else {
+            TriageFlag::Unclassified
+        }
+    }
+}
+
+/// Number of machines per [TriageFlag], returned by [triage_order].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TriageReport {
+    pub steps: StepBig,
+    pub num_halted: u64,
+    pub num_likely_bouncer_counter: u64,
+    pub num_unclassified: u64,
+}
+
+impl TriageReport {
+    fn add(&mut self, flag: TriageFlag) {
+        match flag {
+            TriageFlag::Halted => self.num_halted += 1,
+            TriageFlag::LikelyBouncerCounter => self.num_likely_bouncer_counter += 1,
+            TriageFlag::Unclassified => self.num_unclassified += 1,
+        }
+    }
+}
+
+impl Display for TriageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        writeln!(
+            f,
+            "Triage by symbol frequency ({} steps, no decision):",
+            self.steps.to_formatted_string(&locale)
+        )?;
+        writeln!(
+            f,
+            "  {}: {}",
+            TriageFlag::Halted,
+            self.num_halted.to_formatted_string(&locale)
+        )?;
+        writeln!(
+            f,
+            "  {}: {}",
+            TriageFlag::LikelyBouncerCounter,
+            self.num_likely_bouncer_counter.to_formatted_string(&locale)
+        )?;
+        writeln!(
+            f,
+            "  {}: {}",
+            TriageFlag::Unclassified,
+            self.num_unclassified.to_formatted_string(&locale)
+        )
+    }
+}
+
+/// Sorts the machines for triage: halting machines first, then the likely bouncers and counters, then the
+/// unclassified machines. Machines with the same flag keep their order.
+pub fn triage_order(machines: &mut [MachineInfo], steps: StepBig) -> TriageReport {
+    let mut report = TriageReport {
+        steps,
+        ..Default::default()
+    };
+    let mut flagged: Vec<(TriageFlag, MachineInfo)> = machines
+        .iter()
+        .map(|m| {
+            let flag = SymbolFrequency::from_machine(&m.machine(), steps).flag;
+            report.add(flag);
+            (flag, *m)
+        })
+        .collect();
+    flagged.sort_by_key(|(flag, _)| *flag);
+    for (m, (_, m_flagged)) in machines.iter_mut().zip(flagged) {
+        *m = m_flagged;
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{machine_binary::NotableMachineBinary, status::MachineStatus};
+
+    #[test]
+    fn symbol_frequency_triage_order() {
+        let machine = |tm| MachineBinary::try_from_standard_tm_text_format(tm).unwrap();
+        let bouncer = machine("1RB0LB_1LA0LC_---1RD_0RA0RA");
+        let frequency = SymbolFrequency::from_machine(&bouncer, 10_000);
+        assert_eq!(frequency.flag, TriageFlag::LikelyBouncerCounter);
+        assert_eq!(frequency.densities.len(), NUM_WINDOWS);
+        assert!(frequency.tape_sizes.windows(2).all(|w| w[0] < w[1]));
+
+        // stays on a few cells
+        let bounded = machine("1RB1LA_0LA0RB");
+        let status = MachineStatus::NoDecision;
+        let mut machines = [
+            MachineInfo::new(bounded, status),
+            MachineInfo::new(bouncer, status),
+            MachineInfo::new(NotableMachineBinary::BB4Max.machine(), status),
+        ];
+        let report = triage_order(&mut machines, 10_000);
+        assert_eq!(
+            (
+                report.num_halted,
+                report.num_likely_bouncer_counter,
+                report.num_unclassified
+            ),
+            (1, 1, 1)
+        );
+        assert_eq!(machines[1].machine(), bouncer);
+        assert_eq!(machines[2].machine(), bounded);
+        assert!(report.to_string().contains("likely bouncer/counter: 1"));
+    }
+}
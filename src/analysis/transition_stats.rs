@@ -0,0 +1,298 @@
+//! Structure metrics of the transition tables of a machine population, used to compare the undecided machines
+//! against the decided ones. This gives a quantitative base for the question which decider to build next,
+//! e.g. if the undecided machines mostly write 1 and move right in A0, or have a low entropy in some field.
+//!
+//! For each field (state and read symbol) [TransitionStats] counts
+//! - the (write, direction) pairs 0L, 0R, 1L, 1R and halt/undefined ('---'),
+//! - the full transitions including the next state, and the Shannon entropy (bits) of both distributions.
+//!
+//! [TransitionStatsComparison::write_csv] exports one line per field and population (undecided, decided)
+//! and a line with the difference undecided - decided.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    config::{MAX_STATES, NUM_FIELDS},
+    decider::decider_result::DeciderResultStats,
+    machine_binary::MachineBinary,
+    machine_info::MachineInfo,
+    output_layout::create_dir,
+    transition_binary::TransitionBinary,
+};
+
+pub const TRANSITION_STATS_FILE_NAME: &str = "transition_stats.csv";
+
+/// Names of the (write, direction) pairs, the last one is halt or undefined.
+pub const WRITE_DIR_NAMES: [&str; NUM_WRITE_DIR] = ["0L", "0R", "1L", "1R", "---"];
+const NUM_WRITE_DIR: usize = 5;
+const WRITE_DIR_HALT: usize = NUM_WRITE_DIR - 1;
+/// Number of different transitions: (write, direction) pairs times next state plus halt.
+const NUM_TRANSITION_VALUES: usize = WRITE_DIR_HALT * MAX_STATES + 1;
+
+/// Transition counts of a machine population per field, see [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransitionStats {
+    num_machines: u64,
+    /// Highest number of states of the machines, only these fields are reported.
+    n_states: usize,
+    /// Count of each transition value per field (array id).
+    counts: [[u64; NUM_TRANSITION_VALUES]; NUM_FIELDS],
+}
+
+impl Default for TransitionStats {
+    fn default() -> Self {
+        Self {
+            num_machines: 0,
+            n_states: 0,
+            counts: [[0; NUM_TRANSITION_VALUES]; NUM_FIELDS],
+        }
+    }
+}
+
+impl TransitionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_machines<'a>(machines: impl IntoIterator<Item = &'a MachineBinary>) -> Self {
+        let mut stats = Self::new();
+        for machine in machines {
+            stats.add(machine);
+        }
+        stats
+    }
+
+    pub fn from_machine_infos(machines: &[MachineInfo]) -> Self {
+        let mut stats = Self::new();
+        for mi in machines.iter() {
+            stats.add(&mi.machine());
+        }
+        stats
+    }
+
+    /// Adds the transition table of the machine.
+    pub fn add(&mut self, machine: &MachineBinary) {
+        let n_states = machine.n_states();
+        self.n_states = self.n_states.max(n_states);
+        self.num_machines += 1;
+        for array_id in 2..n_states * 2 + 2 {
+            let value = transition_value(&machine.transition_array_id(array_id));
+            self.counts[array_id][value] += 1;
+        }
+    }
+
+    pub fn num_machines(&self) -> u64 {
+        self.num_machines
+    }
+
+    pub fn n_states(&self) -> usize {
+        self.n_states
+    }
+
+    /// Array ids of the reported fields, starting with 2 for A0.
+    pub fn field_ids(&self) -> std::ops::Range<usize> {
+        2..self.n_states * 2 + 2
+    }
+
+    /// Count of each (write, direction) pair in the field, in the order of [WRITE_DIR_NAMES].
+    pub fn write_dir_counts(&self, array_id: usize) -> [u64; NUM_WRITE_DIR] {
+        let mut counts = [0; NUM_WRITE_DIR];
+        for (value, count) in self.counts[array_id].iter().enumerate() {
+            counts[value / MAX_STATES] += count;
+        }
+        counts
+    }
+
+    /// Share (0.0 - 1.0) of each (write, direction) pair in the field, in the order of [WRITE_DIR_NAMES].
+    pub fn write_dir_shares(&self, array_id: usize) -> [f64; NUM_WRITE_DIR] {
+        let counts = self.write_dir_counts(array_id);
+        let total: u64 = counts.iter().sum();
+        counts.map(|c| share(c, total))
+    }
+
+    /// Shannon entropy in bits of the full transitions (write, direction and next state) in the field.
+    pub fn entropy(&self, array_id: usize) -> f64 {
+        entropy(&self.counts[array_id])
+    }
+
+    /// Shannon entropy in bits of the (write, direction) pairs in the field.
+    pub fn entropy_write_dir(&self, array_id: usize) -> f64 {
+        entropy(&self.write_dir_counts(array_id))
+    }
+}
+
+/// [TransitionStats] of the undecided machines compared against the decided machines.
+#[derive(Debug, Clone)]
+pub struct TransitionStatsComparison {
+    pub undecided: TransitionStats,
+    pub decided: TransitionStats,
+}
+
+impl TransitionStatsComparison {
+    /// Stats of the recorded machines of the result. Returns None if the result did not record
+    /// decided and undecided machines, see Config::limit_machines_decided and Config::limit_machines_undecided.
+    pub fn from_result(result: &DeciderResultStats) -> Option<Self> {
+        Some(Self {
+            undecided: TransitionStats::from_machine_infos(result.machines_undecided()?),
+            decided: TransitionStats::from_machine_infos(result.machines_decided()?),
+        })
+    }
+
+    /// Csv lines including the header, see [module documentation](self).
+    pub fn csv_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "field,population,machines,entropy,entropy_write_dir,{}",
+            WRITE_DIR_NAMES.join(",")
+        )];
+        let n_states = self.undecided.n_states().max(self.decided.n_states());
+        for array_id in 2..n_states * 2 + 2 {
+            let field = MachineBinary::array_id_to_field_name(array_id);
+            let mut values = Vec::new();
+            for (population, stats) in [("undecided", &self.undecided), ("decided", &self.decided)]
+            {
+                let v = FieldValues::new(stats, array_id);
+                lines.push(format!(
+                    "{field},{population},{},{}",
+                    stats.num_machines(),
+                    v.to_csv()
+                ));
+                values.push(v);
+            }
+            let difference = values[0].difference(&values[1]);
+            lines.push(format!(
+                "{field},difference,{},{}",
+                self.undecided.num_machines() as i64 - self.decided.num_machines() as i64,
+                difference.to_csv()
+            ));
+        }
+        lines
+    }
+
+    /// Writes the csv file, the directory is created if required.
+    pub fn write_csv(&self, file_path: &Path) -> io::Result<()> {
+        if let Some(dir) = file_path.parent() {
+            create_dir(dir)?;
+        }
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        for line in self.csv_lines() {
+            writeln!(writer, "{line}")?;
+        }
+        writer.flush()
+    }
+}
+
+/// Metrics of one field of one population, a line of the csv.
+struct FieldValues {
+    entropy: f64,
+    entropy_write_dir: f64,
+    shares: [f64; NUM_WRITE_DIR],
+}
+
+impl FieldValues {
+    fn new(stats: &TransitionStats, array_id: usize) -> Self {
+        Self {
+            entropy: stats.entropy(array_id),
+            entropy_write_dir: stats.entropy_write_dir(array_id),
+            shares: stats.write_dir_shares(array_id),
+        }
+    }
+
+    fn difference(&self, other: &Self) -> Self {
+        let mut shares = self.shares;
+        for (s, o) in shares.iter_mut().zip(other.shares) {
+            *s -= o;
+        }
+        Self {
+            entropy: self.entropy - other.entropy,
+            entropy_write_dir: self.entropy_write_dir - other.entropy_write_dir,
+            shares,
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        let shares: Vec<String> = self.shares.iter().map(|s| format!("{s:.4}")).collect();
+        format!(
+            "{:.4},{:.4},{}",
+            self.entropy,
+            self.entropy_write_dir,
+            shares.join(",")
+        )
+    }
+}
+
+/// Index of the transition in the counts: (write, direction) pair * MAX_STATES + next state - 1, halt is last.
+fn transition_value(tr: &TransitionBinary) -> usize {
+    if tr.is_halt() || tr.is_undefined() {
+        return NUM_TRANSITION_VALUES - 1;
+    }
+    let write_dir = tr.symbol_usize() * 2 + tr.is_dir_right() as usize;
+    write_dir * MAX_STATES + tr.state() as usize - 1
+}
+
+fn share(count: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+/// Shannon entropy in bits of the distribution given by the counts.
+fn entropy(counts: &[u64]) -> f64 {
+    let total: u64 = counts.iter().sum();
+    counts
+        .iter()
+        .filter(|c| **c > 0)
+        .map(|c| share(*c, total))
+        // fold from 0.0, as the sum of -0.0 (a single value) would print as '-0'
+        .fold(0.0, |sum, p| sum - p * p.log2())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine(tm: &str) -> MachineBinary {
+        MachineBinary::try_from_standard_tm_text_format(tm).unwrap()
+    }
+
+    #[test]
+    fn transition_stats_entropy_and_csv() {
+        let undecided = [machine("1RB1LB_1LA0RB"), machine("1RB0LB_1LA0RB")];
+        let decided = [machine("1RB1LB_1LA1RZ"), machine("1RB1LB_1LA---")];
+        let comparison = TransitionStatsComparison {
+            undecided: TransitionStats::from_machines(undecided.iter()),
+            decided: TransitionStats::from_machines(decided.iter()),
+        };
+        let u = &comparison.undecided;
+        assert_eq!((u.num_machines(), u.n_states()), (2, 2));
+        // A0 identical in all machines, A1 differs in the undecided machines
+        assert_eq!(u.entropy(2), 0.0);
+        assert_eq!(u.write_dir_counts(3), [1, 0, 1, 0, 0]);
+        assert_eq!(u.entropy(3), 1.0);
+        assert_eq!(
+            comparison.decided.write_dir_shares(5),
+            [0.0, 0.0, 0.0, 0.0, 1.0]
+        );
+
+        let lines = comparison.csv_lines();
+        // header and 3 lines for each of the 4 fields
+        assert_eq!(lines.len(), 13);
+        assert_eq!(
+            lines[0],
+            "field,population,machines,entropy,entropy_write_dir,0L,0R,1L,1R,---"
+        );
+        assert_eq!(
+            lines[10],
+            "B1,undecided,2,0.0000,0.0000,0.0000,1.0000,0.0000,0.0000,0.0000"
+        );
+        assert_eq!(
+            lines[12],
+            "B1,difference,0,0.0000,0.0000,0.0000,1.0000,0.0000,0.0000,-1.0000"
+        );
+    }
+}
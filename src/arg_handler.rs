@@ -1,12 +1,48 @@
 //! This crate provides an argument handler, which may be used to support typical arguments, e.g.
-//! '-m 1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA'. See below in the help_string().
+//! '-m 1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA'. See below in the help_string(). \
+//! The subcommands `enumerate`, `decide`, `decide-file`, `decide-one`, `verify`, `export`, `resume-undecided`,
+//! `report` and `render` run the library without writing Rust code, e.g.
+//! `decide -s 4 --deciders cycler:1500,bouncer:20000,hold:1000000 -o result.txt`.
+//! `verify -s 4 -d cycler,hold` cross-checks the verdicts of two deciders.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
 
 use crate::{
-    config::CONFIG_TOML,
-    data_provider::bb_file_reader::BBFileReader,
+    checkpoint::Checkpoint,
+    config::{Config, ConfigBuilder, CoreUsage, StepBig, CONFIG_TOML},
+    config_file::ConfigFile,
+    data_provider::{
+        bb_file_reader::BBFileReader,
+        enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        text_file::{check_text_file, read_text_file, DataProviderTextFile},
+        DataProvider,
+    },
+    decider::{
+        decider_compare::cross_check_verdicts,
+        decider_engine::DeciderPipeline,
+        decider_halt_long::DeciderHaltLong,
+        decider_result::{DeciderResultStats, EndReason},
+        Decider, DeciderStandard,
+    },
+    machine_binary::MachineId,
     machine_generic::{MachineGeneric, NotableMachine},
+    results_store::ResultsStore,
+    resume_undecided::{
+        read_undecided_list, resume_undecided, run_undecided_list, UndecidedListFormat,
+    },
+    space_time::SpaceTimeDiagram,
+    status::MachineStatus,
+    stop_conditions::StopConditions,
+    tape::{tape_utils::VecU32Ext, Tape},
 };
 
+/// Steps of the render subcommand without --steps.
+const RENDER_NUM_STEPS_DEFAULT: u64 = 1_000;
+
 /// This is the return value of the argument handler
 #[non_exhaustive]
 pub enum ArgValue {
@@ -24,6 +60,52 @@ pub fn help_string() -> String {
     s.push_str("-h, --help:                  This help text\n");
     s.push_str("-m, --machine <transitions>: Run machine, e.g. '-m 1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA' or '-m 1RB2LB1RZ_2LA2RB1LB'\n");
     s.push_str("-n, --file-number <number>:  Read machine no (e.g. 42) from bb_challenge file and run it.\n");
+    s.push_str("-t, --halt-tape <transitions> [file]: Run machine until it halts and print the final tape (ones and RLE), optionally save it to file.\n");
+    s.push_str("--stop-undecided <number>:   Stop the run after this many undecided machines (see StopConditions).\n");
+    s.push_str("--stop-halt-steps <steps>:   Stop the run when a machine halting after this many steps is found.\n");
+    s.push_str("--stop-time <seconds>:       Stop the run after this wall-clock time.\n");
+    s.push_str("--resume <file>:             Resume the run from this checkpoint file (see Config::resume_from).\n");
+    s.push_str("--threads <number>:          Use this many threads instead of the detected cores (see Config::num_threads).\n");
+    s.push_str("--cores <value>:             Core usage: single, single-enumerator, multi or pipelined (see CoreUsage),\n");
+    s.push_str("                             or multi with a number of threads (e.g. 6) or percent of the cores (e.g. 75%).\n");
+    s.push_str("--config <file>:             Read the config values from this TOML file (see ConfigFile), other options override them.\n");
+    s.push_str("\nSubcommands:\n");
+    s.push_str("enumerate -s <n_states>:     Enumerate the machines (reduced), -o writes them to a text file.\n");
+    s.push_str("decide -s <n_states>:        Run the decider chain on the enumerated machines.\n");
+    s.push_str("decide-file <file>:          Run the decider chain on the machines of a text file (one machine per line).\n");
+    s.push_str("decide-one <machine>:        Run the decider chain on a single machine, e.g. 1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA.\n");
+    s.push_str("                             Prints the status, steps and ones on tape, --html writes the HTML trace.\n");
+    s.push_str("verify <file>:               Check the machines of a text file against their annotated status.\n");
+    s.push_str("verify -s <n_states> -d <a,b>: Run the two deciders independently and report contradicting verdicts.\n");
+    s.push_str("export -s <n_states> [file]: Write the machines left undecided (of the enumeration or file) to the -o text file.\n");
+    s.push_str("resume-undecided <file>:     Run the decider chain on the machines of an undecided list (text, JSON or bbchallenge\n");
+    s.push_str("                             index) and merge the verdicts into the --previous checkpoint result. -o writes the\n");
+    s.push_str("                             machines still undecided, so the list can be resumed again with other deciders.\n");
+    s.push_str(
+        "report:                      Print the best machines found in the runs (results store).\n",
+    );
+    s.push_str("render <machine>:            Write the space-time diagram (one row per step) as PNG, or SVG if -o ends with .svg.\n");
+    s.push_str("                             Default file <machine>.png, --from-step and --steps select the steps.\n");
+    s.push_str("Options of the subcommands:\n");
+    s.push_str("-s, --states <n_states>:     Number of states, for files read from the first machine if not given.\n");
+    s.push_str("--machine-limit <number>:    Enumerate only this many machines, 0 for all.\n");
+    s.push_str("-d, --deciders <chain>:      Decider chain with step limits, e.g. 'cycler:1500,bouncer:20000,hold'. Deciders: cycler,\n");
+    s.push_str("                             bouncer, bouncer-proof, backward, ctl, counter, hold, hold-naive, rep-wl,\n");
+    s.push_str("                             translated-cycler.\n");
+    s.push_str("                             Without limit the config default is used, without chain the standard chain.\n");
+    s.push_str("-o, --output <file>:         Write the output into this file.\n");
+    s.push_str("--html:                      Write the HTML trace of the deciders (feature enable_html_reports).\n");
+    s.push_str("--quiet:                     No progress output (batches, machines/s, ETA on stderr) during the run.\n");
+    s.push_str(
+        "--dashboard:                 Live terminal dashboard during the run (feature tui).\n",
+    );
+    s.push_str("--verdicts:                  Write the verdict of each machine reaching a decider into verdicts.jsonl.\n");
+    s.push_str("--sqlite:                    Write machines, verdicts and run metadata into results.sqlite (feature sqlite).\n");
+    s.push_str("--previous <file>:           Checkpoint file with the result of the run which exported the undecided list.\n");
+    s.push_str("--format <format>:           Format of the undecided list: text, json or index, default by file extension.\n");
+    s.push_str("--from-step <step>:          First step of the render subcommand, default 0.\n");
+    s.push_str("--steps <number>:            Number of steps (rows) of the render subcommand, default 1000.\n");
+    s.push_str("The options --threads, --cores, --resume and --stop-* are also accepted.\n");
     s
 }
 
@@ -34,6 +116,13 @@ pub fn standard_args(args: &[String]) -> ArgValue {
         return ArgValue::None;
     }
 
+    if let Some(subcommand) = Subcommand::from_arg(&args[1]) {
+        return match SubcommandArgs::try_from_args(&args[2..]) {
+            Ok(sub_args) => subcommand.run(&sub_args, args),
+            Err(e) => ArgValue::Error(e),
+        };
+    }
+
     // match on first argument if second is optional
     match args[1].as_str() {
         "-h" | "--help" => {
@@ -89,6 +178,15 @@ pub fn standard_args(args: &[String]) -> ArgValue {
                 }
             }
 
+            "-t" | "--halt-tape" => return halt_tape(&args[2], None),
+
+            // No valid argument
+            _ => {}
+        },
+
+        4 => match args[1].as_str() {
+            "-t" | "--halt-tape" => return halt_tape(&args[2], Some(&args[3])),
+
             // No valid argument
             _ => {}
         },
@@ -104,11 +202,670 @@ pub fn standard_args(args: &[String]) -> ArgValue {
     ArgValue::None
 }
 
+/// Reads the thread count override `--threads <number>` from the command line arguments, None if not given.
+/// Other arguments are ignored.
+pub fn num_threads_from_args(args: &[String]) -> Result<Option<usize>, String> {
+    let Some(pos) = args.iter().position(|a| a == "--threads") else {
+        return Ok(None);
+    };
+    let Some(value) = args.get(pos + 1) else {
+        return Err("Missing value for --threads".to_string());
+    };
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(Some(n)),
+        _ => Err(format!("Invalid value for --threads: {value}")),
+    }
+}
+
+/// Reads the core usage `--cores <value>` from the command line arguments and sets it on the builder.
+/// The value is the name of a [CoreUsage], a number of threads ([CoreUsage::MultiCore]) or a percentage
+/// of the detected cores ([CoreUsage::Percent]). Other arguments are ignored.
+pub fn core_usage_from_args(
+    args: &[String],
+    builder: ConfigBuilder,
+) -> Result<ConfigBuilder, String> {
+    let Some(pos) = args.iter().position(|a| a == "--cores") else {
+        return Ok(builder);
+    };
+    let Some(value) = args.get(pos + 1) else {
+        return Err("Missing value for --cores".to_string());
+    };
+    match CoreUsage::try_from(value.as_str()) {
+        Ok(core_usage) => Ok(builder.core_usage(core_usage)),
+        Err(_) => Err(format!("Invalid value for --cores: {value}")),
+    }
+}
+
+/// Reads the checkpoint file to resume from `--resume <file>` from the command line arguments, None if not given.
+/// Other arguments are ignored.
+pub fn resume_from_args(args: &[String]) -> Result<Option<PathBuf>, String> {
+    let Some(pos) = args.iter().position(|a| a == "--resume") else {
+        return Ok(None);
+    };
+    match args.get(pos + 1) {
+        Some(value) if !value.starts_with('-') => Ok(Some(PathBuf::from(value))),
+        _ => Err("Missing value for --resume".to_string()),
+    }
+}
+
+/// Subcommands of the command line, see [help_string].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subcommand {
+    Enumerate,
+    Decide,
+    DecideFile,
+    DecideOne,
+    Verify,
+    Export,
+    ResumeUndecided,
+    Report,
+    Render,
+}
+
+impl Subcommand {
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        match arg {
+            "enumerate" => Some(Subcommand::Enumerate),
+            "decide" => Some(Subcommand::Decide),
+            "decide-file" => Some(Subcommand::DecideFile),
+            "decide-one" => Some(Subcommand::DecideOne),
+            "verify" => Some(Subcommand::Verify),
+            "export" => Some(Subcommand::Export),
+            "resume-undecided" => Some(Subcommand::ResumeUndecided),
+            "report" => Some(Subcommand::Report),
+            "render" => Some(Subcommand::Render),
+            _ => None,
+        }
+    }
+
+    /// Runs the subcommand, args are all command line arguments for the general options, e.g. --threads.
+    pub fn run(&self, sub_args: &SubcommandArgs, args: &[String]) -> ArgValue {
+        let r = match self {
+            Subcommand::Enumerate => enumerate(sub_args, args),
+            Subcommand::Decide => decide(sub_args, args),
+            Subcommand::DecideFile => decide_file(sub_args, args),
+            Subcommand::DecideOne => decide_one(sub_args, args),
+            Subcommand::Verify => verify(sub_args, args),
+            Subcommand::Export => export(sub_args, args),
+            Subcommand::ResumeUndecided => resume(sub_args, args),
+            Subcommand::Report => report(sub_args),
+            Subcommand::Render => render(sub_args),
+        };
+        match r {
+            Ok(()) => ArgValue::Done,
+            Err(e) => ArgValue::Error(e),
+        }
+    }
+}
+
+/// Options of the subcommands, see [help_string].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SubcommandArgs {
+    pub n_states: Option<usize>,
+    pub machine_limit: Option<u64>,
+    /// Decider chain, without step limit the limit of the config is used.
+    pub deciders: Vec<(DeciderStandard, Option<StepBig>)>,
+    pub output: Option<PathBuf>,
+    /// Argument without option name, e.g. the file of decide-file or the machine of decide-one.
+    pub file: Option<PathBuf>,
+    /// Write the HTML trace of the deciders.
+    pub html: bool,
+    /// No progress output during the run.
+    pub quiet: bool,
+    /// Live terminal dashboard during the run.
+    pub dashboard: bool,
+    /// Write the verdict log.
+    pub verdicts: bool,
+    /// Write the SQLite result store.
+    pub sqlite: bool,
+    /// First step of the rendered space-time diagram.
+    pub first_step: Option<u64>,
+    /// Number of steps of the rendered space-time diagram.
+    pub num_steps: Option<u64>,
+    /// Checkpoint file with the previous result of resume-undecided.
+    pub previous: Option<PathBuf>,
+    /// Format of the undecided list of resume-undecided, by file extension if not given.
+    pub list_format: Option<UndecidedListFormat>,
+    /// Values of the config file (--config), overridden by the options.
+    pub config_file: Option<ConfigFile>,
+}
+
+impl SubcommandArgs {
+    /// Reads the options from the arguments after the subcommand.
+    pub fn try_from_args(args: &[String]) -> Result<Self, String> {
+        let mut sub_args = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg.as_str();
+            if !name.starts_with('-') {
+                if sub_args.file.is_some() {
+                    return Err(format!("Unexpected argument: {name}"));
+                }
+                sub_args.file = Some(PathBuf::from(name));
+                continue;
+            }
+            if name == "--html" {
+                sub_args.html = true;
+                continue;
+            }
+            if name == "--quiet" {
+                sub_args.quiet = true;
+                continue;
+            }
+            if name == "--dashboard" {
+                sub_args.dashboard = true;
+                continue;
+            }
+            if name == "--verdicts" {
+                sub_args.verdicts = true;
+                continue;
+            }
+            if name == "--sqlite" {
+                sub_args.sqlite = true;
+                continue;
+            }
+            let Some(value) = iter.next() else {
+                return Err(format!("Missing value for {name}"));
+            };
+            let number = || {
+                value
+                    .replace('_', "")
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid value for {name}: {value}"))
+            };
+            match name {
+                "-s" | "--states" => sub_args.n_states = Some(number()? as usize),
+                "--machine-limit" => sub_args.machine_limit = Some(number()?),
+                "--from-step" => sub_args.first_step = Some(number()?),
+                "--steps" => sub_args.num_steps = Some(number()?),
+                "-d" | "--deciders" => sub_args.deciders = deciders_from_arg(value)?,
+                "-o" | "--output" => sub_args.output = Some(PathBuf::from(value)),
+                "--previous" => sub_args.previous = Some(PathBuf::from(value)),
+                "--format" => {
+                    sub_args.list_format = Some(match value.as_str() {
+                        "text" => UndecidedListFormat::Text,
+                        "json" => UndecidedListFormat::Json,
+                        "index" => UndecidedListFormat::BBChallengeIndex,
+                        _ => return Err(format!("Invalid value for {name}: {value}")),
+                    })
+                }
+                "--config" => {
+                    sub_args.config_file = Some(ConfigFile::read(value).map_err(|e| e.to_string())?)
+                }
+                // general options, see config
+                "--threads" | "--cores" | "--resume" | "--stop-undecided" | "--stop-halt-steps"
+                | "--stop-time" => {}
+                _ => return Err(format!("Unknown option: {name}")),
+            }
+        }
+        Ok(sub_args)
+    }
+
+    /// Config for n_states with the options of the subcommand and the general options of all arguments.
+    /// The values of the config file (--config) are overridden by the command line options.
+    pub fn config(&self, n_states: usize, args: &[String]) -> Result<Config, String> {
+        let mut builder = match &self.config_file {
+            Some(config_file) => config_file.builder(n_states),
+            None => Config::builder(n_states),
+        };
+        let machine_limit = self
+            .machine_limit
+            .or(self.config_file.as_ref().and_then(|f| f.machine_limit));
+        builder = builder.machine_limit(machine_limit.unwrap_or(0));
+        let stop_conditions = StopConditions::try_from_args(args)?;
+        if !stop_conditions.is_empty() {
+            builder = builder.stop_conditions(stop_conditions);
+        }
+        if self.html {
+            builder = builder.write_html_file(true);
+        }
+        if self.quiet {
+            builder = builder.progress_quiet(true);
+        }
+        if self.dashboard {
+            builder = builder.dashboard(true);
+        }
+        if self.verdicts {
+            builder = builder.write_verdict_log(true);
+        }
+        if self.sqlite {
+            builder = builder.write_results_sqlite(true);
+        }
+        if let Some(num_threads) = num_threads_from_args(args)? {
+            builder = builder.num_threads(num_threads);
+        }
+        builder = core_usage_from_args(args, builder)?;
+        if let Some(resume_from) = resume_from_args(args)? {
+            builder = builder.resume_from(resume_from);
+        }
+        Ok(builder.build())
+    }
+
+    /// The decider chain of the options, the standard chain if no deciders are given.
+    pub fn pipeline(&self, config: &Config) -> DeciderPipeline {
+        if self.deciders.is_empty() {
+            return DeciderPipeline::new_standard(config);
+        }
+        self.deciders.iter().fold(
+            DeciderPipeline::new(config),
+            |pipeline, (decider, step_limit)| {
+                pipeline.with_stage(
+                    *decider,
+                    step_limit.unwrap_or_else(|| decider.step_limit(config)),
+                )
+            },
+        )
+    }
+
+    /// Number of states of the option or of the config file.
+    fn n_states_required(&self) -> Result<usize, String> {
+        self.n_states
+            .or(self.config_file.as_ref().and_then(|f| f.n_states))
+            .ok_or_else(|| "Missing number of states, e.g. -s 4".to_string())
+    }
+
+    fn file_required(&self) -> Result<&PathBuf, String> {
+        self.file
+            .as_ref()
+            .ok_or_else(|| "Missing file argument".to_string())
+    }
+
+    /// Number of states of the option or of the first machine of the file.
+    fn n_states_or_from_file(&self, file: &PathBuf) -> Result<usize, String> {
+        if let Ok(n_states) = self.n_states_required() {
+            return Ok(n_states);
+        }
+        let machines = read_text_file(file).map_err(|e| e.to_string())?;
+        machines
+            .first()
+            .map(|m| m.machine.n_states())
+            .ok_or_else(|| format!("No machine in {}", file.display()))
+    }
+}
+
+/// Reads the decider chain, e.g. 'cycler:1500,bouncer:20000,hold'.
+pub fn deciders_from_arg(value: &str) -> Result<Vec<(DeciderStandard, Option<StepBig>)>, String> {
+    value
+        .split(',')
+        .map(|part| {
+            let (name, step_limit) = match part.split_once(':') {
+                Some((name, step_limit)) => (name, Some(step_limit)),
+                None => (part, None),
+            };
+            let decider = match name.trim() {
+                "cycler" => DeciderStandard::Cycler,
+                "bouncer" => DeciderStandard::Bouncer128,
+                "bouncer-proof" => DeciderStandard::BouncerProof,
+                "backward" => DeciderStandard::BackwardReasoning,
+                "ctl" => DeciderStandard::ClosedTapeLanguage,
+                "counter" => DeciderStandard::Counter,
+                "hold" => DeciderStandard::Hold,
+                "hold-naive" => DeciderStandard::HoldNaive,
+                "rep-wl" => DeciderStandard::RepWL,
+                "translated-cycler" => DeciderStandard::TranslatedCycler,
+                name => DeciderStandard::from_decider_name(name)
+                    .ok_or_else(|| format!("Unknown decider: {name}"))?,
+            };
+            let step_limit = match step_limit {
+                Some(s) => Some(
+                    s.trim()
+                        .replace('_', "")
+                        .parse::<StepBig>()
+                        .map_err(|_| format!("Invalid step limit for {name}: {s}"))?,
+                ),
+                None => None,
+            };
+            Ok((decider, step_limit))
+        })
+        .collect()
+}
+
+/// Prints the text and writes it into the output file, if given.
+fn output(text: &str, sub_args: &SubcommandArgs) -> Result<(), String> {
+    print!("{text}");
+    if let Some(file_path) = &sub_args.output {
+        std::fs::write(file_path, text)
+            .map_err(|e| format!("File {} could not be written: {e}", file_path.display()))?;
+    }
+    Ok(())
+}
+
+/// Writes the machines with id in Standard TM Text Format, readable by [DataProviderTextFile].
+fn write_machines(
+    writer: &mut impl Write,
+    machines: &[MachineId],
+    comment: impl Fn(usize) -> Option<String>,
+) -> io::Result<()> {
+    for (i, m) in machines.iter().enumerate() {
+        match comment(i) {
+            Some(c) => writeln!(
+                writer,
+                "{} {} # {c}",
+                m.id(),
+                m.to_standard_tm_text_format()
+            )?,
+            None => writeln!(writer, "{} {}", m.id(), m.to_standard_tm_text_format())?,
+        }
+    }
+    Ok(())
+}
+
+fn enumerate(sub_args: &SubcommandArgs, args: &[String]) -> Result<(), String> {
+    let config = sub_args.config(sub_args.n_states_required()?, args)?;
+    let mut enumerator =
+        EnumeratorBinary::try_new(EnumeratorType::EnumeratorReducedForward, &config)?;
+    let mut writer = match &sub_args.output {
+        Some(file_path) => Some(BufWriter::new(
+            File::create(file_path).map_err(|e| e.to_string())?,
+        )),
+        None => None,
+    };
+    let mut num_machines = 0;
+    loop {
+        let batch = enumerator.machine_batch_next().map_err(|e| e.to_string())?;
+        num_machines += batch.machines.len() as u64;
+        if let Some(writer) = writer.as_mut() {
+            write_machines(writer, &batch.machines, |_| None).map_err(|e| e.to_string())?;
+        }
+        if matches!(
+            batch.end_reason,
+            EndReason::IsLastBatch | EndReason::NoMoreData
+        ) {
+            break;
+        }
+    }
+    if let Some(mut writer) = writer {
+        writer.flush().map_err(|e| e.to_string())?;
+    }
+    println!(
+        "BB{}: {num_machines} machines enumerated of {} machines",
+        config.n_states(),
+        enumerator.num_machines_to_process()
+    );
+    Ok(())
+}
+
+fn decide(sub_args: &SubcommandArgs, args: &[String]) -> Result<(), String> {
+    let config = sub_args.config(sub_args.n_states_required()?, args)?;
+    let pipeline = sub_args.pipeline(&config);
+    let result = pipeline.run(
+        EnumeratorType::EnumeratorReducedForward,
+        config.core_usage(),
+    );
+    if let EndReason::Error(_, e) = &result.end_reason {
+        return Err(e.clone());
+    }
+    output(
+        &format!("{pipeline}\n{}\n", result.to_string_with_duration()),
+        sub_args,
+    )
+}
+
+fn decide_file(sub_args: &SubcommandArgs, args: &[String]) -> Result<(), String> {
+    let file = sub_args.file_required()?;
+    let config = sub_args.config(sub_args.n_states_or_from_file(file)?, args)?;
+    let pipeline = sub_args.pipeline(&config);
+    let data_provider = DataProviderTextFile::try_new(file).map_err(|e| e.to_string())?;
+    let result = pipeline.run_data_provider(data_provider, CoreUsage::SingleCore);
+    output(
+        &format!("{pipeline}\n{}\n", result.to_string_with_duration()),
+        sub_args,
+    )
+}
+
+/// Runs the decider chain on the machine given in Standard TM Text Format and prints the deciding stage,
+/// status, steps and ones on the tape after these steps.
+fn decide_one(sub_args: &SubcommandArgs, args: &[String]) -> Result<(), String> {
+    let Some(tm_text_format) = sub_args.file.as_ref().and_then(|f| f.to_str()) else {
+        return Err("Missing machine, e.g. 1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA".to_string());
+    };
+    let machine = MachineId::try_from(tm_text_format).map_err(|e| e.to_string())?;
+    let config = sub_args.config(machine.n_states(), args)?;
+    let pipeline = sub_args.pipeline(&config);
+    let (decider, status) = pipeline.decide_single_machine(&machine);
+
+    let mut s = format!(
+        "Machine: {}\nDeciders: {pipeline}\nDecided by: {}\nStatus: {status}\n",
+        machine.to_standard_tm_text_format(),
+        decider.map_or("none (undecided)", |d| d.decider_id().name),
+    );
+    if let Some(steps) = status.steps() {
+        // rerun the halt decider up to the steps to get the tape
+        let config_tape = Config::builder_from_config(
+            &DeciderStandard::Hold.config_with_step_limit(&config, steps),
+        )
+        .write_html_file(false)
+        .build();
+        let mut decider_halt = DeciderHaltLong::new(&config_tape);
+        decider_halt.decide_machine(&machine);
+        s.push_str(&format!(
+            "Steps: {steps}\nOnes on tape: {}\n",
+            decider_halt.tape().count_ones()
+        ));
+    }
+    if config.write_html_file() {
+        s.push_str(&format!(
+            "HTML trace written to {}\n",
+            config.output_layout().run_path(&config).display()
+        ));
+    }
+    for warning in config.warnings() {
+        s.push_str(&format!("Warning: {warning}\n"));
+    }
+    output(&s, sub_args)
+}
+
+fn verify(sub_args: &SubcommandArgs, args: &[String]) -> Result<(), String> {
+    let Some(file) = &sub_args.file else {
+        return verify_deciders(sub_args, args);
+    };
+    let config = sub_args.config(sub_args.n_states_or_from_file(file)?, args)?;
+    let pipeline = sub_args.pipeline(&config);
+    let check = check_text_file(&pipeline.decider_configs(), file).map_err(|e| e.to_string())?;
+    output(&check.to_string(), sub_args)?;
+    if check.mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} mismatches", check.mismatches.len()))
+    }
+}
+
+/// Runs the two deciders of the options independently on the enumerated machines and reports the machines
+/// with contradicting verdicts, see [cross_check_verdicts].
+fn verify_deciders(sub_args: &SubcommandArgs, args: &[String]) -> Result<(), String> {
+    if sub_args.deciders.len() != 2 {
+        return Err("verify without file requires two deciders, e.g. -d cycler,hold".to_string());
+    }
+    let config = sub_args.config(sub_args.n_states_required()?, args)?;
+    let pipeline = sub_args.pipeline(&config);
+    let decider_configs = pipeline.decider_configs();
+    let enumerator = EnumeratorBinary::try_new(EnumeratorType::EnumeratorReducedForward, &config)?;
+    let check = cross_check_verdicts(&decider_configs[0], &decider_configs[1], enumerator)
+        .map_err(|e| e.to_string())?;
+    output(&check.to_string(), sub_args)?;
+    if check.has_mismatches() {
+        Err(format!("{} mismatches", check.mismatches.len()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Runs the decider chain and writes all undecided machines into the output file, which can be
+/// read again with decide-file.
+fn export(sub_args: &SubcommandArgs, args: &[String]) -> Result<(), String> {
+    let Some(file_path) = &sub_args.output else {
+        return Err("Missing output file, e.g. -o undecided.txt".to_string());
+    };
+    let n_states = match &sub_args.file {
+        Some(file) => sub_args.n_states_or_from_file(file)?,
+        None => sub_args.n_states_required()?,
+    };
+    // record all undecided machines
+    let config = Config::builder_from_config(&sub_args.config(n_states, args)?)
+        .limit_machines_undecided(usize::MAX)
+        .build();
+    let pipeline = sub_args.pipeline(&config);
+    let result = match &sub_args.file {
+        Some(file) => pipeline.run_data_provider(
+            DataProviderTextFile::try_new(file).map_err(|e| e.to_string())?,
+            CoreUsage::SingleCore,
+        ),
+        None => pipeline.run(
+            EnumeratorType::EnumeratorReducedForward,
+            config.core_usage(),
+        ),
+    };
+    if let EndReason::Error(_, e) = &result.end_reason {
+        return Err(e.clone());
+    }
+    let num_machines = write_undecided_machines(file_path, &result)?;
+    println!(
+        "{pipeline}\n{num_machines} undecided machines written to {}",
+        file_path.display()
+    );
+    Ok(())
+}
+
+/// Writes the recorded undecided machines of the result with their status as comment, see [write_machines].
+/// Returns the number of machines written.
+fn write_undecided_machines(
+    file_path: &Path,
+    result: &DeciderResultStats,
+) -> Result<usize, String> {
+    let undecided = result.machines_undecided().cloned().unwrap_or_default();
+    let machines: Vec<MachineId> = undecided
+        .iter()
+        .map(|m| MachineId::new(m.id(), m.machine()))
+        .collect();
+    let mut writer = BufWriter::new(File::create(file_path).map_err(|e| e.to_string())?);
+    write_machines(&mut writer, &machines, |i| {
+        Some(undecided[i].status().to_string())
+    })
+    .and_then(|_| writer.flush())
+    .map_err(|e| e.to_string())?;
+    Ok(machines.len())
+}
+
+/// Runs the decider chain on the machines of the undecided list and prints the result merged into the
+/// previous result, see [resume_undecided]. The machines still undecided are written into the output file.
+fn resume(sub_args: &SubcommandArgs, args: &[String]) -> Result<(), String> {
+    let file = sub_args.file_required()?;
+    let format = sub_args
+        .list_format
+        .unwrap_or_else(|| UndecidedListFormat::from_file_path(file));
+    let previous = match &sub_args.previous {
+        Some(file_path) => Some(Checkpoint::read(file_path).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    let n_states = match (sub_args.n_states, &previous) {
+        (Some(n_states), _) => n_states,
+        (None, Some(checkpoint)) => checkpoint.n_states,
+        (None, None) => sub_args.n_states_or_from_file(file)?,
+    };
+    // record all undecided machines
+    let config = Config::builder_from_config(&sub_args.config(n_states, args)?)
+        .limit_machines_undecided(usize::MAX)
+        .build();
+    let machines = read_undecided_list(file, format, &config).map_err(|e| e.to_string())?;
+    let pipeline = sub_args.pipeline(&config);
+    let result = match previous {
+        Some(checkpoint) => {
+            let mut result = checkpoint.result(&config).map_err(|e| e.to_string())?;
+            resume_undecided(&pipeline.decider_configs(), &machines, &mut result)?;
+            result
+        }
+        None => run_undecided_list(&pipeline.decider_configs(), &machines)?,
+    };
+    println!("{pipeline}\n{}", result.to_string_with_duration());
+    if let Some(file_path) = &sub_args.output {
+        let num_machines = write_undecided_machines(file_path, &result)?;
+        println!(
+            "{num_machines} undecided machines written to {}",
+            file_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Prints the best machines per n_states of the results store.
+fn report(sub_args: &SubcommandArgs) -> Result<(), String> {
+    let store = ResultsStore::from_config(&Config::new_default(sub_args.n_states.unwrap_or(5)));
+    let bounds = store.bb_lower_bounds().map_err(|e| e.to_string())?;
+    let text: String = bounds.iter().map(|b| format!("{b}\n")).collect();
+    output(&text, sub_args)
+}
+
+/// Writes the space-time diagram of the machine into the output file, see [SpaceTimeDiagram].
+fn render(sub_args: &SubcommandArgs) -> Result<(), String> {
+    let Some(tm_text_format) = sub_args.file.as_ref().and_then(|f| f.to_str()) else {
+        return Err("Missing machine, e.g. 1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA".to_string());
+    };
+    let machine = MachineId::try_from(tm_text_format).map_err(|e| e.to_string())?;
+    let diagram = SpaceTimeDiagram::new(
+        machine.machine(),
+        sub_args.first_step.unwrap_or(0),
+        sub_args.num_steps.unwrap_or(RENDER_NUM_STEPS_DEFAULT),
+    );
+    let file_path = sub_args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("{}.png", machine.file_name())));
+    diagram
+        .write_file(&file_path)
+        .map_err(|e| format!("File {} could not be written: {e}", file_path.display()))?;
+    println!(
+        "Space-time diagram of steps {} to {} ({} cells wide) written to {}",
+        diagram.first_step(),
+        diagram.first_step() + diagram.num_rows() as u64 - 1,
+        diagram.width(),
+        file_path.display()
+    );
+    Ok(())
+}
+
+/// Runs the machine with the halt decider and prints the final tape (number of ones and run length encoded).
+/// If a file path is given, the output is also written into that file.
+fn halt_tape(tm_text_format: &str, file_path: Option<&str>) -> ArgValue {
+    let machine = match MachineId::try_from(tm_text_format) {
+        Ok(machine) => machine,
+        Err(e) => return ArgValue::Error(e.to_string()),
+    };
+    let config = Config::new_default(machine.n_states());
+    let mut decider = DeciderHaltLong::new(&config);
+    let status = decider.decide_machine(&machine);
+    match status {
+        MachineStatus::DecidedHalt(_) | MachineStatus::DecidedHaltField(_, _) => {}
+        _ => {
+            return ArgValue::Error(format!(
+                "Machine {} did not halt: {status}",
+                machine.to_standard_tm_text_format()
+            ))
+        }
+    }
+
+    let tape = decider.tape();
+    let s = format!(
+        "Machine: {}\n{status}\nOnes on tape: {}\nTape (RLE): {}\n",
+        machine.to_standard_tm_text_format(),
+        tape.count_ones(),
+        tape.tape_long_clean().to_rle_string()
+    );
+    print!("{s}");
+    if let Some(file_path) = file_path {
+        if let Err(e) = std::fs::write(file_path, &s) {
+            return ArgValue::Error(format!("File {file_path} could not be written: {e}"));
+        }
+    }
+
+    ArgValue::Done
+}
+
 #[cfg(test)]
 mod tests {
     use crate::machine_generic::{TransitionGeneric, B};
 
     use super::*;
+    use std::path::Path;
 
     #[test]
     fn test_machine_2x2_6_4() {
@@ -128,4 +885,294 @@ mod tests {
         let tm_format = table.to_standard_tm_text_format();
         assert_eq!(text, tm_format);
     }
+
+    #[test]
+    fn test_halt_tape_bb3_max() {
+        let file_path = std::env::temp_dir().join("bb_challenge_halt_tape_bb3_max.txt");
+        let args = vec![
+            "path".to_string(),
+            "-t".to_string(),
+            "1RB1RZ_1LB0RC_1LC1LA".to_string(),
+            file_path.to_string_lossy().to_string(),
+        ];
+        let r = standard_args(&args);
+        assert!(matches!(r, ArgValue::Done));
+        let s = std::fs::read_to_string(&file_path).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+        assert!(s.contains("Ones on tape: 5"));
+        assert!(s.contains("Tape (RLE): 1^5"));
+    }
+
+    #[test]
+    fn num_threads_override() {
+        let args: Vec<String> = ["path", "--stop-time", "60", "--threads", "3"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let num_threads = num_threads_from_args(&args).unwrap();
+        assert_eq!(num_threads, Some(3));
+        assert_eq!(num_threads_from_args(&args[..3]), Ok(None));
+        assert!(num_threads_from_args(&args[..4]).is_err());
+
+        let config = Config::builder(4)
+            .cpu_utilization(1)
+            .num_threads(num_threads.unwrap())
+            .build();
+        assert_eq!(config.num_threads_effective(), 3);
+        assert_eq!(Config::new_default(4).num_threads(), 0);
+    }
+
+    #[test]
+    fn core_usage_override() {
+        let to_args = |value: &str| -> Vec<String> {
+            ["path", "--threads", "3", "--cores", value]
+                .iter()
+                .map(|a| a.to_string())
+                .collect()
+        };
+        let config = |value: &str| {
+            core_usage_from_args(&to_args(value), Config::builder(4).num_threads(3))
+                .map(|builder| builder.build())
+        };
+        assert_eq!(Config::new_default(4).core_usage(), CoreUsage::MultiCore(0));
+        assert_eq!(config("multi").unwrap().num_threads_effective(), 3);
+
+        let single = config("single").unwrap();
+        assert_eq!(single.core_usage(), CoreUsage::SingleCore);
+        assert_eq!(single.num_threads_effective(), 1);
+        assert_eq!(
+            config("single-enumerator").unwrap().core_usage(),
+            CoreUsage::SingleCoreEnumeratorMultiCoreDecider
+        );
+        assert_eq!(config("pipelined").unwrap().num_threads_effective(), 3);
+
+        let multi = config("5").unwrap();
+        assert_eq!(multi.core_usage(), CoreUsage::MultiCore(5));
+        assert_eq!(multi.num_threads_effective(), 5);
+        assert_eq!(multi.core_usage().to_string(), "5");
+        let percent = config("50%").unwrap();
+        assert_eq!(percent.core_usage(), CoreUsage::Percent(50));
+        assert_eq!(
+            percent.num_threads_effective(),
+            crate::utils::num_cpus_percentage(50)
+        );
+        assert!(config("0").is_err());
+        assert!(config("200%").is_err());
+        assert!(config("many").is_err());
+        assert!(core_usage_from_args(&to_args("5")[..4], Config::builder(4)).is_err());
+    }
+
+    #[test]
+    fn resume_from_file() {
+        let args: Vec<String> = ["path", "--resume", "checkpoint.toml", "--threads", "3"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let resume_from = resume_from_args(&args).unwrap();
+        assert_eq!(resume_from, Some(PathBuf::from("checkpoint.toml")));
+        assert_eq!(resume_from_args(&args[..1]), Ok(None));
+        assert!(resume_from_args(&args[..2]).is_err());
+
+        let config = Config::builder(4).resume_from(resume_from.unwrap()).build();
+        assert_eq!(config.resume_from(), Some(Path::new("checkpoint.toml")));
+    }
+
+    #[test]
+    fn subcommand_args_deciders() {
+        let args: Vec<String> = [
+            "-s",
+            "4",
+            "--threads",
+            "2",
+            "-d",
+            "cycler:1_500,hold",
+            "--dashboard",
+            "--verdicts",
+            "file.txt",
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        let sub_args = SubcommandArgs::try_from_args(&args).unwrap();
+        assert_eq!(sub_args.n_states, Some(4));
+        assert_eq!(sub_args.file, Some(PathBuf::from("file.txt")));
+        assert_eq!(
+            sub_args.deciders,
+            vec![
+                (DeciderStandard::Cycler, Some(1500)),
+                (DeciderStandard::Hold, None)
+            ]
+        );
+        let config = sub_args.config(4, &args).unwrap();
+        assert!(config.dashboard());
+        assert!(config.write_verdict_log());
+        let pipeline = sub_args.pipeline(&config);
+        assert_eq!(
+            pipeline.stages(),
+            &[
+                (DeciderStandard::Cycler, 1500),
+                (DeciderStandard::Hold, config.step_limit_decider_halt())
+            ]
+        );
+        assert!(deciders_from_arg("cycler:many").is_err());
+        assert!(SubcommandArgs::try_from_args(&args[..1]).is_err());
+    }
+
+    #[test]
+    fn subcommand_config_file_override() {
+        let file_path = std::env::temp_dir().join("bb_challenge_subcommand_config_file.toml");
+        std::fs::write(
+            &file_path,
+            "n_states = 3\nstep_limit_decider_cycler = 300\nnum_threads = 2\n",
+        )
+        .unwrap();
+        let file = file_path.to_string_lossy().to_string();
+        let args: Vec<String> = ["--config", &file, "--threads", "3"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let sub_args = SubcommandArgs::try_from_args(&args).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+        let config = sub_args
+            .config(sub_args.n_states_required().unwrap(), &args)
+            .unwrap();
+        assert_eq!(config.n_states(), 3);
+        assert_eq!(config.step_limit_decider_cycler(), 300);
+        // command line overrides the file
+        assert_eq!(config.num_threads(), 3);
+    }
+
+    #[test]
+    fn subcommand_export_decide_file_bb3() {
+        let file_path = std::env::temp_dir().join("bb_challenge_export_bb3.txt");
+        let file = file_path.to_string_lossy().to_string();
+        // machines left undecided by the cycler
+        let args: Vec<String> = ["path", "export", "-s", "3", "-d", "cycler:100", "-o", &file]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert!(matches!(standard_args(&args), ArgValue::Done));
+        let machines = read_text_file(&file_path).unwrap();
+        assert!(!machines.is_empty());
+
+        let args: Vec<String> = ["path", "decide-file", &file]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert!(matches!(standard_args(&args), ArgValue::Done));
+        std::fs::remove_file(&file_path).unwrap();
+
+        let args: Vec<String> = ["path", "decide"].iter().map(|a| a.to_string()).collect();
+        assert!(matches!(standard_args(&args), ArgValue::Error(_)));
+    }
+
+    #[test]
+    fn subcommand_resume_undecided_bb3() {
+        let dir = std::env::temp_dir();
+        let file_path = dir.join("bb_challenge_resume_undecided_cli_bb3.txt");
+        let remaining_path = dir.join("bb_challenge_resume_undecided_cli_bb3_remaining.txt");
+        let file = file_path.to_string_lossy().to_string();
+        let remaining = remaining_path.to_string_lossy().to_string();
+        // machines left undecided by the halt decider, then resumed with the cycler
+        let args: Vec<String> = ["path", "export", "-s", "3", "-d", "hold", "-o", &file]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert!(matches!(standard_args(&args), ArgValue::Done));
+        let args: Vec<String> = [
+            "path",
+            "resume-undecided",
+            &file,
+            "--format",
+            "text",
+            "-d",
+            "cycler",
+            "-o",
+            &remaining,
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        assert!(matches!(standard_args(&args), ArgValue::Done));
+        let num_exported = read_text_file(&file_path).unwrap().len();
+        let num_remaining = read_text_file(&remaining_path).unwrap().len();
+        std::fs::remove_file(&file_path).unwrap();
+        std::fs::remove_file(&remaining_path).unwrap();
+        assert!(num_remaining < num_exported);
+
+        let sub_args = SubcommandArgs::try_from_args(&args[2..]).unwrap();
+        assert_eq!(sub_args.list_format, Some(UndecidedListFormat::Text));
+        assert!(
+            SubcommandArgs::try_from_args(&["--format".to_string(), "csv".to_string()]).is_err()
+        );
+    }
+
+    #[test]
+    fn subcommand_verify_deciders_bb3() {
+        let args: Vec<String> = ["path", "verify", "-s", "3", "-d", "cycler,hold"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert!(matches!(standard_args(&args), ArgValue::Done));
+        // one decider is not enough
+        let args: Vec<String> = ["path", "verify", "-s", "3", "-d", "cycler"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert!(matches!(standard_args(&args), ArgValue::Error(_)));
+    }
+
+    #[test]
+    fn subcommand_decide_one_bb3_max() {
+        let file_path = std::env::temp_dir().join("bb_challenge_decide_one_bb3_max.txt");
+        let args: Vec<String> = [
+            "path",
+            "decide-one",
+            "1RB1RZ_1LB0RC_1LC1LA",
+            "-o",
+            &file_path.to_string_lossy(),
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        assert!(matches!(standard_args(&args), ArgValue::Done));
+        let s = std::fs::read_to_string(&file_path).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+        assert!(s.contains("Decided by: Decider Cycler"), "{s}");
+        assert!(s.contains("Steps: 21\nOnes on tape: 5"), "{s}");
+
+        let args: Vec<String> = ["path", "decide-one", "1RB1RZ_1LB0"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert!(matches!(standard_args(&args), ArgValue::Error(_)));
+    }
+
+    #[test]
+    fn subcommand_render_bb3_max() {
+        let file_path = std::env::temp_dir().join("bb_challenge_render_bb3_max.svg");
+        let args: Vec<String> = [
+            "path",
+            "render",
+            "1RB1RZ_1LB0RC_1LC1LA",
+            "--from-step",
+            "5",
+            "--steps",
+            "10",
+            "-o",
+            &file_path.to_string_lossy(),
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        assert!(matches!(standard_args(&args), ArgValue::Done));
+        let svg = std::fs::read_to_string(&file_path).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+        let machine = MachineId::try_from("1RB1RZ_1LB0RC_1LC1LA").unwrap();
+        assert_eq!(
+            svg,
+            SpaceTimeDiagram::new(machine.machine(), 5, 10).to_svg(1)
+        );
+        assert!(svg.contains("height=\"10\""));
+    }
 }
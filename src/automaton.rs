@@ -0,0 +1,215 @@
+//! Finite automata over the binary tape alphabet {0, 1}, used to describe regular sets of half-tapes,
+//! e.g. by the [DeciderCTL]. \
+//! An [Nfa] is built by adding transitions, [Nfa::to_dfa] converts it with the subset construction
+//! into a [Dfa], which checks words in linear time.
+//! # Example
+//! ```
+//! use bb_challenge::automaton::Nfa;
+//!
+//! // words ending with 1
+//! let mut nfa = Nfa::new(2);
+//! nfa.add_start(0);
+//! nfa.add_transition(0, 0, 0);
+//! nfa.add_transition(0, 1, 0);
+//! nfa.add_transition(0, 1, 1);
+//! nfa.set_accepting(1, true);
+//! assert!(nfa.to_dfa().accepts(&[0, 1, 1]));
+//! ```
+//!
+//! [DeciderCTL]: crate::decider::decider_ctl::DeciderCTL
+
+use std::collections::HashMap;
+
+/// Symbols of the alphabet, same as the tape cells.
+pub const NUM_SYMBOLS: usize = 2;
+
+/// Nondeterministic finite automaton, the states are numbered 0..num_states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nfa {
+    /// Target states for each state and symbol.
+    transitions: Vec<[Vec<usize>; NUM_SYMBOLS]>,
+    start: Vec<usize>,
+    accepting: Vec<bool>,
+}
+
+impl Nfa {
+    /// Creates an automaton without start state and transitions.
+    pub fn new(num_states: usize) -> Self {
+        Self {
+            transitions: vec![Default::default(); num_states],
+            start: Vec::new(),
+            accepting: vec![false; num_states],
+        }
+    }
+
+    pub fn num_states(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn add_start(&mut self, state: usize) {
+        if !self.start.contains(&state) {
+            self.start.push(state);
+        }
+    }
+
+    pub fn set_accepting(&mut self, state: usize, is_accepting: bool) {
+        self.accepting[state] = is_accepting;
+    }
+
+    /// Adds the transition, returns false if it already existed.
+    pub fn add_transition(&mut self, from: usize, symbol: u8, to: usize) -> bool {
+        let targets = &mut self.transitions[from][symbol as usize];
+        if targets.contains(&to) {
+            return false;
+        }
+        targets.push(to);
+        true
+    }
+
+    pub fn has_transition(&self, from: usize, symbol: u8, to: usize) -> bool {
+        self.transitions[from][symbol as usize].contains(&to)
+    }
+
+    pub fn targets(&self, from: usize, symbol: u8) -> &[usize] {
+        &self.transitions[from][symbol as usize]
+    }
+
+    /// Number of transitions over all states and symbols.
+    pub fn num_transitions(&self) -> usize {
+        self.transitions
+            .iter()
+            .map(|t| t.iter().map(Vec::len).sum::<usize>())
+            .sum()
+    }
+
+    pub fn accepts(&self, word: &[u8]) -> bool {
+        let mut states = self.start_set();
+        for symbol in word {
+            states = self.step(&states, *symbol);
+        }
+        self.is_accepting_set(&states)
+    }
+
+    /// Converts the automaton into a [Dfa] with the subset construction. Only reachable sets become states,
+    /// a missing transition rejects the word.
+    pub fn to_dfa(&self) -> Dfa {
+        let start = self.start_set();
+        let mut ids = HashMap::from([(start.clone(), 0)]);
+        let mut sets = vec![start];
+        let mut dfa = Dfa {
+            transitions: Vec::new(),
+            accepting: Vec::new(),
+        };
+        let mut i = 0;
+        while i < sets.len() {
+            let mut targets = [None; NUM_SYMBOLS];
+            for (symbol, target) in targets.iter_mut().enumerate() {
+                let next = self.step(&sets[i], symbol as u8);
+                if next.iter().any(|s| *s) {
+                    let id = *ids.entry(next.clone()).or_insert_with(|| {
+                        sets.push(next);
+                        sets.len() - 1
+                    });
+                    *target = Some(id);
+                }
+            }
+            dfa.transitions.push(targets);
+            dfa.accepting.push(self.is_accepting_set(&sets[i]));
+            i += 1;
+        }
+        dfa
+    }
+
+    fn start_set(&self) -> Vec<bool> {
+        let mut states = vec![false; self.num_states()];
+        for s in self.start.iter() {
+            states[*s] = true;
+        }
+        states
+    }
+
+    fn step(&self, states: &[bool], symbol: u8) -> Vec<bool> {
+        let mut next = vec![false; self.num_states()];
+        for (from, _) in states.iter().enumerate().filter(|(_, is_set)| **is_set) {
+            for to in self.targets(from, symbol) {
+                next[*to] = true;
+            }
+        }
+        next
+    }
+
+    fn is_accepting_set(&self, states: &[bool]) -> bool {
+        states
+            .iter()
+            .zip(self.accepting.iter())
+            .any(|(is_set, is_accepting)| *is_set && *is_accepting)
+    }
+}
+
+/// Deterministic finite automaton, state 0 is the start state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dfa {
+    /// Target state for each state and symbol, None rejects the word.
+    transitions: Vec<[Option<usize>; NUM_SYMBOLS]>,
+    accepting: Vec<bool>,
+}
+
+impl Dfa {
+    pub fn num_states(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn next(&self, state: usize, symbol: u8) -> Option<usize> {
+        self.transitions[state][symbol as usize]
+    }
+
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accepting[state]
+    }
+
+    pub fn accepts(&self, word: &[u8]) -> bool {
+        let mut state = 0;
+        for symbol in word {
+            match self.next(state, *symbol) {
+                Some(s) => state = s,
+                None => return false,
+            }
+        }
+        self.is_accepting(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nfa_to_dfa_accepts_same_words() {
+        // words with a 1 at the second last position, the DFA needs 4 states
+        let mut nfa = Nfa::new(3);
+        nfa.add_start(0);
+        nfa.add_transition(0, 0, 0);
+        nfa.add_transition(0, 1, 0);
+        nfa.add_transition(0, 1, 1);
+        nfa.add_transition(1, 0, 2);
+        nfa.add_transition(1, 1, 2);
+        nfa.set_accepting(2, true);
+        assert!(!nfa.add_transition(0, 1, 1));
+        assert_eq!(nfa.num_transitions(), 5);
+
+        let dfa = nfa.to_dfa();
+        assert_eq!(dfa.num_states(), 4);
+        for word in [
+            &[][..],
+            &[1],
+            &[1, 0],
+            &[0, 1, 1],
+            &[1, 1, 0, 0],
+            &[0, 0, 1, 0],
+        ] {
+            assert_eq!(dfa.accepts(word), nfa.accepts(word), "{word:?}");
+        }
+        assert!(dfa.accepts(&[0, 0, 1, 0]));
+        assert!(!dfa.accepts(&[1, 0, 0]));
+    }
+}
@@ -0,0 +1,548 @@
+//! Proof certificates of non-halting machines, which are checked with [verify_certificate] independent of
+//! the decider which found them. \
+//! A decider emits the certificate of the last machine with [Decider::proof_certificate], if it supports it:
+//! - [CycleCertificate]: Start step and period of a cycle, used for the cycler and the translated cycler.
+//!   The verifier runs the machine to the start step and one period further and checks that the tape read
+//!   in the period repeats, shifted by the head movement of the period.
+//! - [BouncerCertificate]: Formula tape with repeating words and shift rules of the bouncer proof.
+//! - [CtlCertificate]: Automata of both half-tapes and the local contexts of the closed tape language.
+//!   The verifier checks that the set is closed under the transitions and contains no halt transition.
+//!
+//! All certificates are written as text with [Display] and read with [Certificate::from_text], one entry
+//! per line, lines starting with '#' are comments.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     certificate::{verify_certificate, Certificate},
+//!     config::Config,
+//!     decider::{decider_cycler::DeciderCycler, Decider},
+//!     machine_binary::MachineId,
+//! };
+//! let machine = MachineId::try_from("1RB---_1LC0RC_0LD1LC_1RA0RA").unwrap();
+//! let mut decider = DeciderCycler::new(&Config::new_default(4));
+//! decider.decide_machine(&machine);
+//! let certificate = decider.proof_certificate().unwrap();
+//! let text = certificate.to_string();
+//! assert!(verify_certificate(&Certificate::from_text(&text).unwrap()).is_ok());
+//! ```
+//!
+//! [Decider::proof_certificate]: crate::decider::Decider::proof_certificate
+
+use std::{collections::HashSet, fmt::Display};
+
+use crate::{
+    automaton::Nfa,
+    decider::{
+        decider_bouncer_proof::BouncerCertificate,
+        decider_ctl::{LocalContext, CONTEXT_LEN_MAX},
+    },
+    format::standard_tm,
+    machine_binary::{MachineId, State, Symbol},
+    machine_runner::MachineRunner,
+    status::{MachineStatus, NonHaltReason},
+};
+
+const CYCLE_HEADER: &str = "cycle certificate";
+const CTL_HEADER: &str = "ctl certificate";
+
+/// Certificate of a non-halting machine, see [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Certificate {
+    Cycle(CycleCertificate),
+    Bouncer(BouncerCertificate),
+    ClosedTapeLanguage(CtlCertificate),
+}
+
+impl Certificate {
+    pub fn machine(&self) -> &MachineId {
+        match self {
+            Certificate::Cycle(c) => &c.machine,
+            Certificate::Bouncer(c) => &c.machine,
+            Certificate::ClosedTapeLanguage(c) => &c.machine,
+        }
+    }
+
+    /// Reads any certificate written with [Display], the type is identified by the header line.
+    pub fn from_text(text: &str) -> Result<Self, &'static str> {
+        match content_lines(text).next() {
+            Some(CYCLE_HEADER) => Ok(Certificate::Cycle(CycleCertificate::from_text(text)?)),
+            Some(CTL_HEADER) => Ok(Certificate::ClosedTapeLanguage(CtlCertificate::from_text(
+                text,
+            )?)),
+            Some(_) => Ok(Certificate::Bouncer(BouncerCertificate::from_text(text)?)),
+            None => Err("Certificate: empty text"),
+        }
+    }
+}
+
+impl Display for Certificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Certificate::Cycle(c) => c.fmt(f),
+            Certificate::Bouncer(c) => c.fmt(f),
+            Certificate::ClosedTapeLanguage(c) => c.fmt(f),
+        }
+    }
+}
+
+/// Checks the proof of the certificate, Ok if the machine never halts.
+pub fn verify_certificate(certificate: &Certificate) -> Result<(), &'static str> {
+    match certificate {
+        Certificate::Cycle(c) => c.verify(),
+        Certificate::Bouncer(c) => c.verify(),
+        Certificate::ClosedTapeLanguage(c) => c.verify(),
+    }
+}
+
+/// The machine repeats the steps from start_step to start_step + period forever, possibly shifted on the tape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CycleCertificate {
+    pub machine: MachineId,
+    pub start_step: u64,
+    pub period: u64,
+}
+
+impl CycleCertificate {
+    /// Creates the certificate from the status of the cycler or translated cycler, None for other results.
+    pub fn from_status(machine: &MachineId, status: &MachineStatus) -> Option<Self> {
+        let (steps, period) = match status {
+            MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(steps, period))
+            | MachineStatus::DecidedNonHalt(NonHaltReason::TranslatedCycler(steps, period, _)) => {
+                (*steps as u64, *period as u64)
+            }
+            _ => return None,
+        };
+        Some(Self {
+            machine: *machine,
+            start_step: steps.checked_sub(period)?,
+            period,
+        })
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, &'static str> {
+        let mut machine = None;
+        let mut start_step = None;
+        let mut period = None;
+        for (key, value) in key_values(text, CYCLE_HEADER)? {
+            match key {
+                "machine" => machine = Some(MachineId::try_from(value)?),
+                "start_step" => {
+                    start_step = Some(
+                        value
+                            .parse()
+                            .map_err(|_| "Cycle certificate: invalid start step")?,
+                    )
+                }
+                "period" => {
+                    period = Some(
+                        value
+                            .parse()
+                            .map_err(|_| "Cycle certificate: invalid period")?,
+                    )
+                }
+                _ => return Err("Cycle certificate: unknown line"),
+            }
+        }
+        Ok(Self {
+            machine: machine.ok_or("Cycle certificate: machine missing")?,
+            start_step: start_step.ok_or("Cycle certificate: start step missing")?,
+            period: period.ok_or("Cycle certificate: period missing")?,
+        })
+    }
+
+    /// Checks that the machine reaches the same state after the period and the cells read during the period
+    /// are identical, moved by the shift of the head. With a shift, the cells in front of the head up to the
+    /// end of the tape need to be identical as well, as the next periods read them.
+    pub fn verify(&self) -> Result<(), &'static str> {
+        if self.period == 0 {
+            return Err("Cycle certificate: period is 0");
+        }
+        let mut runner = MachineRunner::new(self.machine.machine());
+        for _ in 0..self.start_step {
+            runner.step();
+        }
+        let start = runner.clone();
+        // positions read in the period, relative to the head at the start step
+        let (mut min, mut max) = (0, 0);
+        for _ in 0..self.period {
+            runner.step();
+            let pos = runner.head_pos() - start.head_pos();
+            min = min.min(pos);
+            max = max.max(pos);
+        }
+        if runner.is_halted() {
+            return Err("Cycle certificate: the machine halts");
+        }
+        if runner.state() != start.state() {
+            return Err("Cycle certificate: the state differs after the period");
+        }
+        let shift = runner.head_pos() - start.head_pos();
+        let is_repeated = match shift.signum() {
+            0 => {
+                let len = (max - min + 1) as usize;
+                let window = |r: &MachineRunner| {
+                    let mut cells = r.cells_from_head(min, true);
+                    cells.resize(len, 0);
+                    cells
+                };
+                window(&start) == window(&runner)
+            }
+            1 => start.cells_from_head(min, true) == runner.cells_from_head(min, true),
+            _ => start.cells_from_head(max, false) == runner.cells_from_head(max, false),
+        };
+        if !is_repeated {
+            return Err("Cycle certificate: the tape differs after the period");
+        }
+        Ok(())
+    }
+}
+
+impl Display for CycleCertificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{CYCLE_HEADER}")?;
+        writeln!(f, "machine {}", self.machine.to_standard_tm_text_format())?;
+        writeln!(f, "start_step {}", self.start_step)?;
+        writeln!(f, "period {}", self.period)
+    }
+}
+
+/// Closed tape language of the [DeciderCTL]: The automata of the left and right half-tape, read from the
+/// blank end towards the head, whose states are the last context_len cells read, and all local contexts
+/// of the reachable configurations.
+///
+/// [DeciderCTL]: crate::decider::decider_ctl::DeciderCTL
+#[derive(Debug, Clone, PartialEq)]
+pub struct CtlCertificate {
+    pub machine: MachineId,
+    pub context_len: usize,
+    pub left: Nfa,
+    pub right: Nfa,
+    pub contexts: Vec<LocalContext>,
+}
+
+impl CtlCertificate {
+    pub fn from_text(text: &str) -> Result<Self, &'static str> {
+        let mut machine = None;
+        let mut context_len = None;
+        let mut transitions = Vec::new();
+        let mut contexts = Vec::new();
+        for (key, value) in key_values(text, CTL_HEADER)? {
+            let numbers = || {
+                value
+                    .split(' ')
+                    .map(|v| v.parse::<u32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| "CTL certificate: invalid number")
+            };
+            match key {
+                "machine" => machine = Some(MachineId::try_from(value)?),
+                "context_len" => {
+                    let len = value
+                        .parse()
+                        .map_err(|_| "CTL certificate: invalid context length")?;
+                    if !(1..=CONTEXT_LEN_MAX).contains(&len) {
+                        return Err("CTL certificate: context length out of range");
+                    }
+                    context_len = Some(len);
+                }
+                "left" | "right" => {
+                    let &[from, symbol, to] = numbers()?.as_slice() else {
+                        return Err("CTL certificate: invalid transition");
+                    };
+                    transitions.push((key == "left", from, symbol, to));
+                }
+                "context" => {
+                    let parts: Vec<&str> = value.split(' ').collect();
+                    let &[left, state, symbol, right] = parts.as_slice() else {
+                        return Err("CTL certificate: invalid context");
+                    };
+                    let number =
+                        |v: &str| v.parse().map_err(|_| "CTL certificate: invalid context");
+                    contexts.push(LocalContext {
+                        left: number(left)?,
+                        state: standard_tm::state_from_char(*state.as_bytes().first().unwrap_or(&0))
+                            .ok_or("CTL certificate: invalid state")?
+                            as u8,
+                        symbol: number(symbol)? as u8,
+                        right: number(right)?,
+                    });
+                }
+                _ => return Err("CTL certificate: unknown line"),
+            }
+        }
+        let context_len = context_len.ok_or("CTL certificate: context length missing")?;
+        let num_states = 1 << context_len;
+        let mut left = Nfa::new(num_states);
+        let mut right = Nfa::new(num_states);
+        for nfa in [&mut left, &mut right] {
+            // same as the decider: starts at the blank end, all half-tapes read are accepted
+            nfa.add_start(0);
+            (0..num_states).for_each(|state| nfa.set_accepting(state, true));
+        }
+        for (is_left, from, symbol, to) in transitions {
+            if from >= num_states as u32 || to >= num_states as u32 || symbol > 1 {
+                return Err("CTL certificate: transition out of range");
+            }
+            let nfa = if is_left { &mut left } else { &mut right };
+            nfa.add_transition(from as usize, symbol as u8, to as usize);
+        }
+        Ok(Self {
+            machine: machine.ok_or("CTL certificate: machine missing")?,
+            context_len,
+            left,
+            right,
+            contexts,
+        })
+    }
+
+    /// Checks that the set of local contexts contains the start, is closed under the transitions of the
+    /// machine and uses no halt transition. Each automaton transition must append the cell to the context,
+    /// so the symbol under the head is the last cell of the context.
+    pub fn verify(&self) -> Result<(), &'static str> {
+        let mask = (1u32 << self.context_len) - 1;
+        for nfa in [&self.left, &self.right] {
+            if nfa.num_states() != 1 << self.context_len {
+                return Err("CTL certificate: automaton size differs from the context length");
+            }
+            for from in 0..nfa.num_states() {
+                for symbol in 0..2 {
+                    for &to in nfa.targets(from, symbol) {
+                        if to as u32 != ((from as u32) << 1 | symbol as u32) & mask {
+                            return Err("CTL certificate: transition does not match the context");
+                        }
+                    }
+                }
+            }
+            if !nfa.has_transition(0, 0, 0) {
+                return Err("CTL certificate: blank half-tape missing");
+            }
+        }
+        let known: HashSet<LocalContext> = self.contexts.iter().copied().collect();
+        let start = LocalContext {
+            left: 0,
+            state: 1,
+            symbol: 0,
+            right: 0,
+        };
+        if !known.contains(&start) {
+            return Err("CTL certificate: start context missing");
+        }
+
+        let m = self.machine.machine();
+        for c in self.contexts.iter() {
+            if c.left > mask
+                || c.right > mask
+                || c.symbol > 1
+                || c.state == 0
+                || c.state as usize > self.machine.n_states()
+            {
+                return Err("CTL certificate: context out of range");
+            }
+            let tr = m.transition(State::new(c.state as usize), Symbol::new(c.symbol as usize));
+            if tr.is_halt() {
+                return Err("CTL certificate: a halt transition is reachable");
+            }
+            let write = tr.is_symbol_one() as u8;
+            let (from, to, from_nfa, to_nfa) = if tr.is_dir_right() {
+                (c.left, c.right, &self.left, &self.right)
+            } else {
+                (c.right, c.left, &self.right, &self.left)
+            };
+            let from_next = ((from << 1) | write as u32) & mask;
+            if !from_nfa.has_transition(from as usize, write, from_next as usize) {
+                return Err("CTL certificate: written cell missing in the automaton");
+            }
+            let symbol = (to & 1) as u8;
+            for y in 0..2 {
+                let to_next = (to >> 1) | (y << (self.context_len - 1));
+                if !to_nfa.has_transition(to_next as usize, symbol, to as usize) {
+                    continue;
+                }
+                let (left, right) = if tr.is_dir_right() {
+                    (from_next, to_next)
+                } else {
+                    (to_next, from_next)
+                };
+                let next = LocalContext {
+                    left,
+                    state: tr.state() as u8,
+                    symbol,
+                    right,
+                };
+                if !known.contains(&next) {
+                    return Err("CTL certificate: the set is not closed");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for CtlCertificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{CTL_HEADER}")?;
+        writeln!(f, "machine {}", self.machine.to_standard_tm_text_format())?;
+        writeln!(f, "context_len {}", self.context_len)?;
+        writeln!(f, "# automaton transitions: side from symbol to")?;
+        for (side, nfa) in [("left", &self.left), ("right", &self.right)] {
+            for from in 0..nfa.num_states() {
+                for symbol in 0..2 {
+                    for to in nfa.targets(from, symbol) {
+                        writeln!(f, "{side} {from} {symbol} {to}")?;
+                    }
+                }
+            }
+        }
+        writeln!(f, "# local contexts: left state symbol right")?;
+        for c in self.contexts.iter() {
+            writeln!(
+                f,
+                "context {} {} {} {}",
+                c.left,
+                standard_tm::state_to_char(c.state as usize),
+                c.symbol,
+                c.right
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Lines without comments and blank lines.
+fn content_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Key and value of the lines after the header.
+fn key_values<'a>(
+    text: &'a str,
+    header: &str,
+) -> Result<impl Iterator<Item = (&'a str, &'a str)>, &'static str> {
+    let mut lines = content_lines(text);
+    if lines.next() != Some(header) {
+        return Err("Certificate: header missing");
+    }
+    let pairs = lines
+        .map(|line| line.split_once(' ').ok_or("Certificate: invalid line"))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(pairs.into_iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::{
+            enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            DataProvider,
+        },
+        decider::{
+            decider_bouncer_proof::DeciderBouncerProof, decider_ctl::DeciderCTL,
+            decider_cycler::DeciderCycler, decider_translated_cycler::DeciderTranslatedCycler,
+            Decider,
+        },
+    };
+
+    /// Decides the machine, checks the certificate and its text and returns it.
+    fn certificate(mut decider: impl Decider, tm: &str) -> Certificate {
+        let machine = MachineId::try_from(tm).unwrap();
+        let status = decider.decide_machine(&machine);
+        assert!(
+            matches!(status, MachineStatus::DecidedNonHalt(_)),
+            "{status}"
+        );
+        let certificate = decider.proof_certificate().unwrap();
+        assert_eq!(verify_certificate(&certificate), Ok(()));
+        let read = Certificate::from_text(&certificate.to_string()).unwrap();
+        assert_eq!(read, certificate);
+        certificate
+    }
+
+    #[test]
+    fn certificate_cycle() {
+        let config = Config::new_default(5);
+        let Certificate::Cycle(cycle) =
+            certificate(DeciderCycler::new(&config), "1RB---_1LC0RC_0LD1LC_1RA0RA")
+        else {
+            panic!("cycle certificate expected");
+        };
+        let wrong = CycleCertificate {
+            period: cycle.period + 1,
+            ..cycle
+        };
+        assert!(wrong.verify().is_err());
+
+        for tm in ["1RB---_1LC0RB_1RD0LB_1RA0RE_0LD1RB", "1LB---_0LA1RA"] {
+            let Certificate::Cycle(cycle) = certificate(DeciderTranslatedCycler::new(&config), tm)
+            else {
+                panic!("cycle certificate expected");
+            };
+            let wrong = CycleCertificate {
+                period: cycle.period - 1,
+                ..cycle
+            };
+            assert!(wrong.verify().is_err(), "{tm}");
+        }
+
+        // BB4 Max halts
+        let machine = MachineId::try_from("1RB1LB_1LA0LC_1RZ1LD_1RD0RA").unwrap();
+        let halts = CycleCertificate {
+            machine,
+            start_step: 100,
+            period: 10,
+        };
+        assert!(halts.verify().is_err());
+    }
+
+    /// Every machine of BB3 decided by the cyclers has a valid certificate.
+    #[test]
+    fn certificate_cycle_all_bb3() {
+        let config = Config::builder(3).machine_limit(0).build();
+        let mut cycler = DeciderCycler::new(&config);
+        let mut translated = DeciderTranslatedCycler::new(&config);
+        let mut data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let mut num_verified = 0;
+        for _ in 0..data_provider.num_batches() {
+            let batch = data_provider.machine_batch_next().unwrap();
+            for machine in batch.machines.iter() {
+                for status in [
+                    cycler.decide_machine(machine),
+                    translated.decide_machine(machine),
+                ] {
+                    let Some(c) = CycleCertificate::from_status(machine, &status) else {
+                        continue;
+                    };
+                    assert_eq!(c.verify(), Ok(()), "{machine} {status}");
+                    num_verified += 1;
+                }
+            }
+        }
+        assert!(num_verified > 0);
+    }
+
+    #[test]
+    fn certificate_ctl() {
+        let config = Config::new_default(4);
+        let Certificate::ClosedTapeLanguage(mut ctl) =
+            certificate(DeciderCTL::new(&config), "1RB1LC_0RD0RA_0LA---_1RC0RA")
+        else {
+            panic!("ctl certificate expected");
+        };
+        ctl.contexts.pop();
+        assert!(ctl.verify().is_err());
+    }
+
+    #[test]
+    fn certificate_bouncer() {
+        let config = Config::new_default(4);
+        let certificate = certificate(
+            DeciderBouncerProof::new(&config),
+            "1RB0LB_1LA0LC_---1RD_0RA0RA",
+        );
+        assert!(matches!(certificate, Certificate::Bouncer(_)));
+    }
+}
@@ -0,0 +1,296 @@
+//! Export of proof certificates as S-expressions, so the non-halting results can be checked by a formal
+//! verification (e.g. Coq or Lean) without parsing the text format of [Certificate]. \
+//! The [CertificateExporter] writes one file per certificate type into the certificates directory of the
+//! [OutputLayout](crate::output_layout::OutputLayout): 'cycler.sexp', 'bouncer.sexp' and 'ctl.sexp'.
+//! Each line is one certificate, lines starting with ';' are comments.
+//!
+//! Format: Machines are strings in the Standard TM text format, states are letters (A = first state),
+//! symbols are 0 or 1, words are strings of symbols (e.g. "1010") and directions are L or R.
+//! ```text
+//! (cycler (machine "1RB---_1LC0RC_0LD1LC_1RA0RA") (start-step 64) (period 26))
+//! (bouncer (machine "1RB0LB_1LA0LC_---1RD_0RA0RA") (start-step 13)
+//!     (formula (left (repeat "1010" 1) (symbol 1)) (head B 0) (right))
+//!     (induction-steps 12 24)
+//!     (rules (rule B L "1010" "" "0101" 4) (rule A R "0101" "" "1010" 8)))
+//! (ctl (machine "1RB1LC_0RD0RA_0LA---_1RC0RA") (context-len 3)
+//!     (left (0 0 0) (0 1 1) ...) (right (0 0 0) ...)
+//!     (contexts (0 A 0 0) (1 B 0 0) ...))
+//! ```
+//! (line breaks only for readability)
+//! - cycler: After start-step steps, the machine repeats the next period steps forever, possibly shifted,
+//!   see [CycleCertificate].
+//! - bouncer: Formula tape F(n) with repeaters (word, c) repeated n + c times, reached at start-step for n = 0.
+//!   The induction step F(n) -> F(n+1) takes `a*n + b` steps (induction-steps a b) using the shift rules
+//!   (state, direction, word, context, new word, steps), see [BouncerCertificate].
+//! - ctl: Automaton transitions (from symbol to) of the left and right half-tape, read from the blank end,
+//!   and the local contexts (left state symbol right) of the closed set, see [CtlCertificate].
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use crate::{
+    automaton::Nfa,
+    certificate::{Certificate, CtlCertificate, CycleCertificate},
+    config::Config,
+    decider::{
+        decider_bouncer_proof::{BouncerCertificate, FormulaItem, ShiftRule},
+        Decider,
+    },
+    format::standard_tm,
+    machine_binary::MachineId,
+    status::MachineStatus,
+};
+
+pub const FILE_EXTENSION: &str = "sexp";
+
+/// Writes certificates into one file per certificate type, see [module documentation](self).
+#[derive(Debug)]
+pub struct CertificateExporter {
+    dir: PathBuf,
+    /// Open files by certificate type, created with the first certificate of the type.
+    writers: HashMap<&'static str, BufWriter<File>>,
+    num_exported: u64,
+}
+
+impl CertificateExporter {
+    /// Exporter writing into the directory, existing files are overwritten.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            writers: HashMap::new(),
+            num_exported: 0,
+        }
+    }
+
+    /// Exporter writing into the certificates directory of the output layout of the config.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(config.output_layout().certificates_path(config))
+    }
+
+    /// File of the certificate type, e.g. '<dir>/cycler.sexp'.
+    pub fn file_path(&self, certificate_type: &str) -> PathBuf {
+        self.dir
+            .join(format!("{certificate_type}.{FILE_EXTENSION}"))
+    }
+
+    pub fn num_exported(&self) -> u64 {
+        self.num_exported
+    }
+
+    /// Writes the certificate as one line into the file of its type.
+    pub fn export(&mut self, certificate: &Certificate) -> io::Result<()> {
+        let certificate_type = certificate_type(certificate);
+        if !self.writers.contains_key(certificate_type) {
+            crate::output_layout::create_dir(&self.dir)?;
+            let mut writer = BufWriter::new(File::create(self.file_path(certificate_type))?);
+            writeln!(
+                writer,
+                "; {certificate_type} certificates of bb_challenge, one s-expression per line"
+            )?;
+            self.writers.insert(certificate_type, writer);
+        }
+        let writer = self.writers.get_mut(certificate_type).unwrap();
+        writeln!(writer, "{}", to_sexpr(certificate))?;
+        self.num_exported += 1;
+        Ok(())
+    }
+
+    /// Runs the decider on the machines and exports the certificates of the machines decided as non-halting.
+    /// Returns the number of certificates, machines decided by a decider without certificates are skipped.
+    pub fn export_decided(
+        &mut self,
+        decider: &mut impl Decider,
+        machines: &[MachineId],
+    ) -> io::Result<usize> {
+        let mut num_exported = 0;
+        for machine in machines.iter() {
+            if !matches!(
+                decider.decide_machine(machine),
+                MachineStatus::DecidedNonHalt(_)
+            ) {
+                continue;
+            }
+            if let Some(certificate) = decider.proof_certificate() {
+                self.export(&certificate)?;
+                num_exported += 1;
+            }
+        }
+        Ok(num_exported)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        for writer in self.writers.values_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Name of the certificate type, which is also the file name and the first atom of the S-expression.
+pub fn certificate_type(certificate: &Certificate) -> &'static str {
+    match certificate {
+        Certificate::Cycle(_) => "cycler",
+        Certificate::Bouncer(_) => "bouncer",
+        Certificate::ClosedTapeLanguage(_) => "ctl",
+    }
+}
+
+/// The certificate as S-expression in one line, see [module documentation](self).
+pub fn to_sexpr(certificate: &Certificate) -> String {
+    let machine = format!(
+        "(machine \"{}\")",
+        certificate.machine().to_standard_tm_text_format()
+    );
+    let body = match certificate {
+        Certificate::Cycle(c) => cycle_sexpr(c),
+        Certificate::Bouncer(c) => bouncer_sexpr(c),
+        Certificate::ClosedTapeLanguage(c) => ctl_sexpr(c),
+    };
+    format!("({} {machine} {body})", certificate_type(certificate))
+}
+
+fn cycle_sexpr(c: &CycleCertificate) -> String {
+    format!("(start-step {}) (period {})", c.start_step, c.period)
+}
+
+fn bouncer_sexpr(c: &BouncerCertificate) -> String {
+    let rules: Vec<String> = c.rules.iter().map(rule_sexpr).collect();
+    format!(
+        "(start-step {}) (formula (left{}) (head {} {}) (right{})) (induction-steps {} {}) (rules{})",
+        c.start_step,
+        items_sexpr(&c.formula.left),
+        state(c.formula.state),
+        c.formula.head,
+        items_sexpr(&c.formula.right),
+        c.steps_n,
+        c.steps_const,
+        prefixed(&rules)
+    )
+}
+
+fn ctl_sexpr(c: &CtlCertificate) -> String {
+    let transitions = |nfa: &Nfa| {
+        let mut list = Vec::new();
+        for from in 0..nfa.num_states() {
+            for symbol in 0..2 {
+                for to in nfa.targets(from, symbol) {
+                    list.push(format!("({from} {symbol} {to})"));
+                }
+            }
+        }
+        list
+    };
+    let contexts: Vec<String> = c
+        .contexts
+        .iter()
+        .map(|c| format!("({} {} {} {})", c.left, state(c.state), c.symbol, c.right))
+        .collect();
+    format!(
+        "(context-len {}) (left{}) (right{}) (contexts{})",
+        c.context_len,
+        prefixed(&transitions(&c.left)),
+        prefixed(&transitions(&c.right)),
+        prefixed(&contexts)
+    )
+}
+
+fn rule_sexpr(rule: &ShiftRule) -> String {
+    format!(
+        "(rule {} {} {} {} {} {})",
+        state(rule.state),
+        if rule.is_dir_right {
+            standard_tm::DIR_RIGHT_CHAR
+        } else {
+            standard_tm::DIR_LEFT_CHAR
+        },
+        word(&rule.word),
+        word(&rule.context),
+        word(&rule.word_new),
+        rule.steps
+    )
+}
+
+fn items_sexpr(items: &[FormulaItem]) -> String {
+    let items: Vec<String> = items
+        .iter()
+        .map(|item| match item {
+            FormulaItem::Symbol(s) => format!("(symbol {s})"),
+            FormulaItem::Repeat(w, c) => format!("(repeat {} {c})", word(w)),
+        })
+        .collect();
+    prefixed(&items)
+}
+
+/// The elements each with a leading blank, so an empty list gives e.g. '(right)'.
+fn prefixed(list: &[String]) -> String {
+    list.iter().map(|e| format!(" {e}")).collect()
+}
+
+fn state(state: u8) -> char {
+    standard_tm::state_to_char(state as usize)
+}
+
+fn word(symbols: &[u8]) -> String {
+    let w: String = symbols.iter().map(|s| s.to_string()).collect();
+    format!("\"{w}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decider::{decider_ctl::DeciderCTL, decider_cycler::DeciderCycler};
+
+    #[test]
+    fn certificate_export_files() {
+        let dir = std::env::temp_dir().join("bb_challenge_certificate_export_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = Config::new_default(4);
+        let machines: Vec<MachineId> = [
+            "1RB---_1LC0RC_0LD1LC_1RA0RA",
+            "1RB1LC_0RD0RA_0LA---_1RC0RA",
+            // BB4 Max halts
+            "1RB1LB_1LA0LC_1RZ1LD_1RD0RA",
+        ]
+        .iter()
+        .map(|tm| MachineId::try_from(*tm).unwrap())
+        .collect();
+
+        let mut exporter = CertificateExporter::new(&dir);
+        let n = exporter
+            .export_decided(&mut DeciderCycler::new(&config), &machines)
+            .unwrap();
+        assert_eq!(n, 1);
+        let n = exporter
+            .export_decided(&mut DeciderCTL::new(&config), &machines)
+            .unwrap();
+        // the cycler is also a closed tape language
+        assert_eq!(n, 2);
+        exporter.flush().unwrap();
+        assert_eq!(exporter.num_exported(), 3);
+
+        let cycler = std::fs::read_to_string(exporter.file_path("cycler")).unwrap();
+        let lines: Vec<&str> = cycler.lines().filter(|l| !l.starts_with(';')).collect();
+        assert_eq!(
+            lines,
+            ["(cycler (machine \"1RB---_1LC0RC_0LD1LC_1RA0RA\") (start-step 64) (period 26))"]
+        );
+        let ctl = std::fs::read_to_string(exporter.file_path("ctl")).unwrap();
+        let lines: Vec<&str> = ctl.lines().filter(|l| !l.starts_with(';')).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines
+            .iter()
+            .all(|l| l.starts_with("(ctl (machine ") && l.contains("(contexts (0 A 0 0)")));
+        // balanced brackets
+        let depth = lines[1].chars().fold(0, |d, c| match c {
+            '(' => d + 1,
+            ')' => d - 1,
+            _ => d,
+        });
+        assert_eq!(depth, 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,553 @@
+//! Checkpoints to resume long enumeration runs, e.g. after a crash or a stop by [StopConditions]. \
+//! If [Config::checkpoint_interval] is set, the engine writes a checkpoint every this many batches and at the end
+//! of the run, see [OutputLayout::checkpoint_file_path]. It contains the counters of the result, the machines with
+//! max steps, the undecided machines (up to [Config::limit_machines_undecided]) and the position of the data provider
+//! as the number of the next batch. The batches of a threaded run finish out of order, so a checkpoint only contains
+//! the batches before the first unfinished batch. The file is first written to a temporary file, synced and then
+//! renamed, and each checkpoint is logged as 'checkpoint_written' in the [EventLog]. \
+//! A run with [Config::resume_from] (command line `--resume <file>`) starts with the result of the checkpoint and
+//! continues with its next batch, so the counters are the same as in an uninterrupted run. This requires the same
+//! n_states, data provider and batch size and a data provider which can skip batches, see
+//! [DataProvider::skip_to_batch]. Durations, decider stats and the tape memory only cover the resumed part.
+//! # Example
+//! ```
+//! use std::time::Duration;
+//!
+//! use bb_challenge::{config::Config, stop_conditions::StopConditions};
+//!
+//! // first run, stopped after one hour
+//! let config = Config::builder(5)
+//!     .checkpoint_interval(10)
+//!     .stop_conditions(StopConditions { duration: Some(Duration::from_secs(3600)), ..Default::default() })
+//!     .build();
+//! // next run, continues where the first run stopped
+//! let config = Config::builder(5)
+//!     .checkpoint_interval(10)
+//!     .resume_from(config.output_layout().checkpoint_file_path(&config))
+//!     .build();
+//! ```
+//!
+//! [StopConditions]: crate::stop_conditions::StopConditions
+//! [OutputLayout::checkpoint_file_path]: crate::output_layout::OutputLayout::checkpoint_file_path
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, StepBig},
+    data_provider::DataProvider,
+    decider::{
+        decider_result::{
+            DeciderResultStats, NonHaltCount, PreDeciderCount, UndecidedCount, WarningKind,
+        },
+        DeciderConfig,
+    },
+    event_log::{log_or_warn, EventLog, RunEvent},
+    machine_binary::{MachineBinary, MachineId},
+    machine_info::MachineInfo,
+    output_layout::create_dir,
+    status::{MachineStatus, StatusCode},
+};
+
+/// Counters of a result as stored in a [Checkpoint].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointCounters {
+    pub num_processed_total: u64,
+    pub num_evaluated: u64,
+    pub num_halt: u64,
+    pub num_not_max: u64,
+    pub num_undecided: u64,
+    pub num_not_max_too_many_halt_transitions: u64,
+    pub num_not_max_not_all_states_used: u64,
+    pub num_step_budget_exhausted: u64,
+    pub num_steps_simulated: u64,
+    pub steps_max: StepBig,
+    pub num_machines_steps_max: usize,
+    pub pre_decider_count: PreDeciderCount,
+    /// Machines eliminated by the pre-decider of the first decider. None in checkpoints written before
+    /// this counter existed, then it is derived from the other counters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_eliminated_evaluated: Option<u64>,
+    pub non_halt_count: NonHaltCount,
+    pub undecided_count: UndecidedCount,
+}
+
+/// Machine of a [Checkpoint] with its status code. The values of the halt and undecided status (e.g. the steps)
+/// are stored, any other status is restored with 0 values, see [MachineStatus::from_code].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointMachine {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    /// Machine in standard TM text format.
+    pub machine: String,
+    pub status: StatusCode,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<u64>,
+}
+
+impl CheckpointMachine {
+    pub fn from_machine_info(machine: &MachineInfo) -> Self {
+        let values = match machine.status() {
+            MachineStatus::DecidedHalt(steps) => vec![steps as u64],
+            MachineStatus::DecidedHaltField(steps, field) => vec![steps as u64, field as u64],
+            MachineStatus::DecidedHaltDetail(steps, tape_size, ones) => {
+                vec![steps as u64, tape_size as u64, ones as u64]
+            }
+            MachineStatus::Undecided(_, steps, tape_size) => vec![steps as u64, tape_size as u64],
+            _ => Vec::new(),
+        };
+        Self {
+            id: machine.has_id().then(|| machine.id()),
+            machine: machine.to_standard_tm_text_format(),
+            status: machine.status().as_code(),
+            values,
+        }
+    }
+
+    pub fn to_machine_info(&self) -> io::Result<MachineInfo> {
+        let machine = MachineBinary::try_from_standard_tm_text_format(&self.machine)
+            .map_err(|e| invalid_data(format!("{}: {e}", self.machine)))?;
+        let value = |i: usize| self.values.get(i).copied().unwrap_or_default();
+        let status = match MachineStatus::from_code(self.status)
+            .ok_or_else(|| invalid_data(format!("Unknown status code {}", self.status)))?
+        {
+            MachineStatus::DecidedHalt(_) => MachineStatus::DecidedHalt(value(0) as StepBig),
+            MachineStatus::DecidedHaltField(_, _) => {
+                MachineStatus::DecidedHaltField(value(0) as StepBig, value(1) as usize)
+            }
+            MachineStatus::DecidedHaltDetail(_, _, _) => MachineStatus::DecidedHaltDetail(
+                value(0) as StepBig,
+                value(1) as u32,
+                value(2) as u32,
+            ),
+            MachineStatus::Undecided(reason, _, _) => {
+                MachineStatus::Undecided(reason, value(0) as StepBig, value(1) as u32)
+            }
+            status => status,
+        };
+        Ok(match self.id {
+            Some(id) => MachineInfo::new_m_id(MachineId::new(id, machine), status),
+            None => MachineInfo::new(machine, status),
+        })
+    }
+}
+
+/// Content of a checkpoint file (TOML), see [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Local time when the checkpoint was written.
+    pub time: String,
+    pub n_states: usize,
+    /// Name of the data provider, a run is only resumed with the same data provider.
+    pub data_provider: String,
+    pub batch_size: usize,
+    pub num_batches: usize,
+    /// All batches before this batch are contained, the resumed run starts with this batch.
+    pub next_batch_no: usize,
+    pub counters: CheckpointCounters,
+    pub machines_max_steps: Vec<CheckpointMachine>,
+    pub machines_undecided: Vec<CheckpointMachine>,
+}
+
+impl Checkpoint {
+    pub fn read(file_path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = fs::read_to_string(file_path)?;
+        ::toml::from_str(&text).map_err(|e| invalid_data(e.to_string()))
+    }
+
+    /// Writes the checkpoint atomically: temporary file, sync, rename.
+    pub fn write(&self, file_path: &Path) -> io::Result<()> {
+        if let Some(dir) = file_path.parent() {
+            create_dir(dir)?;
+        }
+        let text = ::toml::to_string(self).map_err(|e| invalid_data(e.to_string()))?;
+        let file_path_tmp = file_path.with_extension("toml.tmp");
+        let mut file = File::create(&file_path_tmp)?;
+        file.write_all(text.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&file_path_tmp, file_path)
+    }
+
+    /// True if all batches of the run are contained.
+    pub fn is_complete(&self) -> bool {
+        self.next_batch_no >= self.num_batches
+    }
+
+    /// The result of the contained batches with the limits of the config.
+    pub fn result(&self, config: &Config) -> io::Result<DeciderResultStats> {
+        let machines = |list: &[CheckpointMachine]| {
+            list.iter()
+                .map(CheckpointMachine::to_machine_info)
+                .collect::<io::Result<Vec<_>>>()
+        };
+        Ok(DeciderResultStats::from_checkpoint(
+            config,
+            &self.counters,
+            machines(&self.machines_max_steps)?,
+            machines(&self.machines_undecided)?,
+        ))
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Batch of the run which has not been added to the checkpoint result yet.
+#[derive(Debug)]
+struct PendingBatch {
+    result: DeciderResultStats,
+    is_finished: bool,
+}
+
+/// Writer of the checkpoints of a run, see [module documentation](self).
+#[derive(Debug)]
+pub struct Checkpointer {
+    file_path: PathBuf,
+    /// Write a checkpoint every this many batches. 0: The run is only resumed.
+    interval: usize,
+    /// Configs of the decider chain for the limits of the results.
+    configs: Vec<Config>,
+    data_provider: String,
+    batch_size: usize,
+    num_batches: usize,
+    /// Result of all batches before next_batch_no.
+    result: DeciderResultStats,
+    next_batch_no: usize,
+    next_batch_no_written: usize,
+    /// Started batches with their result so far, by batch no.
+    pending: BTreeMap<usize, PendingBatch>,
+}
+
+impl Checkpointer {
+    /// Creates the checkpointer if [Config::checkpoint_interval] or [Config::resume_from] is set. \
+    /// When resuming, the result of the checkpoint is added to the result and the data provider
+    /// skips to the next batch of the checkpoint.
+    /// # Panics
+    /// If the checkpoint cannot be read, does not fit to the run or the data provider cannot skip batches.
+    pub(crate) fn start_run(
+        decider_configs: &[DeciderConfig],
+        data_provider: &mut impl DataProvider,
+        result: &mut DeciderResultStats,
+    ) -> Option<Self> {
+        let config: &Config = decider_configs.first()?.config();
+        if config.checkpoint_interval() == 0 && config.resume_from().is_none() {
+            return None;
+        }
+        let configs: Vec<Config> = decider_configs
+            .iter()
+            .map(|dc| dc.config().clone())
+            .collect();
+        let mut checkpointer = Self {
+            file_path: config.output_layout().checkpoint_file_path(config),
+            interval: config.checkpoint_interval(),
+            result: new_result(&configs),
+            configs,
+            data_provider: data_provider.name().to_string(),
+            batch_size: data_provider.batch_size(),
+            num_batches: data_provider.num_batches(),
+            next_batch_no: 0,
+            next_batch_no_written: 0,
+            pending: BTreeMap::new(),
+        };
+        if let Some(file_path) = config.resume_from() {
+            let result_checkpoint = checkpointer
+                .resume(file_path, data_provider)
+                .unwrap_or_else(|e| panic!("Checkpoint {}: {e}", file_path.display()));
+            result.add_result(&result_checkpoint);
+            checkpointer.result.add_result(&result_checkpoint);
+        }
+        Some(checkpointer)
+    }
+
+    /// Reads the checkpoint, checks that it fits to this run and skips the data provider to its next batch.
+    fn resume(
+        &mut self,
+        file_path: &Path,
+        data_provider: &mut impl DataProvider,
+    ) -> io::Result<DeciderResultStats> {
+        let checkpoint = Checkpoint::read(file_path)?;
+        if checkpoint.n_states != self.configs[0].n_states()
+            || checkpoint.data_provider != self.data_provider
+            || checkpoint.batch_size != self.batch_size
+            || checkpoint.num_batches != self.num_batches
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "BB{} '{}' with {} batches of {} machines does not fit to this run: \
+                    BB{} '{}' with {} batches of {} machines.",
+                    checkpoint.n_states,
+                    checkpoint.data_provider,
+                    checkpoint.num_batches,
+                    checkpoint.batch_size,
+                    self.configs[0].n_states(),
+                    self.data_provider,
+                    self.num_batches,
+                    self.batch_size
+                ),
+            ));
+        }
+        if checkpoint.is_complete() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The run of this checkpoint is already complete.",
+            ));
+        }
+        if !data_provider.skip_to_batch(checkpoint.next_batch_no) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "The data provider '{}' cannot skip batches.",
+                    self.data_provider
+                ),
+            ));
+        }
+        self.next_batch_no = checkpoint.next_batch_no;
+        self.next_batch_no_written = checkpoint.next_batch_no;
+
+        checkpoint.result(&self.configs[0])
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// First batch which is not contained in the checkpoint. The run starts with this batch when resumed.
+    pub fn next_batch_no(&self) -> usize {
+        self.next_batch_no
+    }
+
+    /// Adds the machines eliminated by the data provider in this batch.
+    pub fn add_pre_decider_count(
+        &mut self,
+        batch_no: usize,
+        n_states: usize,
+        pre_decider_count: &PreDeciderCount,
+    ) {
+        if self.interval == 0 {
+            return;
+        }
+        let section = self
+            .pending_batch(batch_no)
+            .result
+            .n_states_section_mut(n_states);
+        section.add_pre_decider_count(pre_decider_count);
+        section.add_total(pre_decider_count.num_total());
+    }
+
+    /// Adds the result of the deciders for this batch.
+    pub fn add_result(&mut self, batch_no: usize, result: &DeciderResultStats) {
+        if self.interval == 0 {
+            return;
+        }
+        self.pending_batch(batch_no).result.add_result(result);
+    }
+
+    /// Marks the batch as finished and writes a checkpoint if interval batches have been added since the
+    /// last checkpoint. Returns the file path if a checkpoint was written.
+    pub fn batch_finished(&mut self, batch_no: usize) -> io::Result<Option<PathBuf>> {
+        if self.interval == 0 {
+            return Ok(None);
+        }
+        self.pending_batch(batch_no).is_finished = true;
+        // add the finished batches in order
+        while let Some(entry) = self.pending.first_entry() {
+            if *entry.key() != self.next_batch_no || !entry.get().is_finished {
+                break;
+            }
+            self.result.add_result(&entry.remove().result);
+            self.next_batch_no += 1;
+        }
+        if self.next_batch_no >= self.next_batch_no_written + self.interval {
+            return self.write().map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Writes the last checkpoint of the run, if batches have been added since the last checkpoint.
+    pub fn run_end(&mut self) -> io::Result<Option<PathBuf>> {
+        if self.interval == 0 || self.next_batch_no == self.next_batch_no_written {
+            return Ok(None);
+        }
+        self.write().map(Some)
+    }
+
+    fn write(&mut self) -> io::Result<PathBuf> {
+        let machines = |list: Option<&Vec<MachineInfo>>| {
+            list.map(|machines| {
+                machines
+                    .iter()
+                    .map(CheckpointMachine::from_machine_info)
+                    .collect()
+            })
+            .unwrap_or_default()
+        };
+        let checkpoint = Checkpoint {
+            time: Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z").to_string(),
+            n_states: self.configs[0].n_states(),
+            data_provider: self.data_provider.clone(),
+            batch_size: self.batch_size,
+            num_batches: self.num_batches,
+            next_batch_no: self.next_batch_no,
+            counters: self.result.checkpoint_counters(),
+            machines_max_steps: machines(self.result.machines_max_steps()),
+            machines_undecided: machines(self.result.machines_undecided()),
+        };
+        checkpoint.write(&self.file_path)?;
+        self.next_batch_no_written = self.next_batch_no;
+        Ok(self.file_path.clone())
+    }
+
+    fn pending_batch(&mut self, batch_no: usize) -> &mut PendingBatch {
+        self.pending
+            .entry(batch_no)
+            .or_insert_with(|| PendingBatch {
+                result: new_result(&self.configs),
+                is_finished: false,
+            })
+    }
+}
+
+/// Empty result with the limits of all configs, like the result of the engine.
+fn new_result(configs: &[Config]) -> DeciderResultStats {
+    let mut result = DeciderResultStats::new(&configs[0]);
+    for config in configs.iter() {
+        result.enhance_machines_un_decided(config);
+    }
+    result
+}
+
+/// Runs the checkpoint function, logs a written checkpoint in the event log and adds a warning to the result
+/// if the checkpoint cannot be written. Does nothing if there is no checkpointer.
+pub(crate) fn checkpoint_or_warn(
+    checkpointer: &mut Option<Checkpointer>,
+    event_log: &mut Option<EventLog>,
+    result: &mut DeciderResultStats,
+    f: impl FnOnce(&mut Checkpointer) -> io::Result<Option<PathBuf>>,
+) {
+    let Some(c) = checkpointer.as_mut() else {
+        return;
+    };
+    match f(c) {
+        Ok(Some(file_path)) => log_or_warn(event_log, result, |log, _| {
+            log.log(&RunEvent::CheckpointWritten {
+                path: file_path.display().to_string(),
+            })
+        }),
+        Ok(None) => {}
+        Err(e) => {
+            let file_path = c.file_path().to_path_buf();
+            result.add_warning(WarningKind::Checkpoint, || {
+                format!("{}: {e}", file_path.display())
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::batch_run_decider_chain_threaded_data_provider_single_thread_reporting,
+            DeciderStandard,
+        },
+        event_log::read_event_log,
+        output_layout::{OutputLayout, EVENT_LOG_FILE_NAME},
+        stop_conditions::StopConditions,
+    };
+
+    fn run(config: &Config, num_threads: usize) -> DeciderResultStats {
+        let config = Config::builder_from_config(config)
+            .num_threads(num_threads)
+            .build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Bouncer128.decider_config(&config),
+        ];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
+            &deciders,
+            data_provider,
+            None,
+        )
+    }
+
+    #[test]
+    fn checkpoint_resume_same_as_full_run() {
+        let dir = std::env::temp_dir().join("bb_challenge_checkpoint_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = Config::builder(4)
+            .machine_limit(0)
+            .enumerator_reduced_batch_size_request(50_000)
+            .limit_machines_undecided(10_000)
+            .output_layout(OutputLayout::new(&dir))
+            .build();
+        let result_full = run(&config, 1);
+
+        // first run stops early, the checkpoint is written at the end
+        let config_stop = Config::builder_from_config(&config)
+            .checkpoint_interval(3)
+            .write_event_log(true)
+            .stop_conditions(StopConditions {
+                num_undecided: result_full.num_undecided() / 2,
+                ..Default::default()
+            })
+            .build();
+        let result_stop = run(&config_stop, 4);
+        let file_path = config.output_layout().checkpoint_file_path(&config);
+        let checkpoint = Checkpoint::read(&file_path).unwrap();
+        assert!(!file_path.with_extension("toml.tmp").exists());
+        assert!(checkpoint.next_batch_no > 0 && !checkpoint.is_complete());
+        assert!(checkpoint.counters.num_processed_total <= result_stop.num_processed_total());
+        let lines = read_event_log(dir.join(EVENT_LOG_FILE_NAME)).unwrap();
+        assert!(lines.iter().any(|l| l.event == "checkpoint_written"));
+
+        // second run continues with the next batch
+        let config_resume = Config::builder_from_config(&config)
+            .resume_from(&file_path)
+            .build();
+        let result_resume = run(&config_resume, 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            result_resume.checkpoint_counters(),
+            result_full.checkpoint_counters()
+        );
+        let ids = |result: &DeciderResultStats| {
+            result
+                .machines_undecided_sorted()
+                .unwrap()
+                .machines()
+                .iter()
+                .map(|m| m.id())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&result_resume), ids(&result_full));
+    }
+
+    #[test]
+    fn checkpoint_machine_status_values() {
+        let machine = MachineId::try_from("1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA").unwrap();
+        let status = MachineStatus::Undecided(
+            crate::status::UndecidedReason::TapeLimitLeftBoundReached,
+            12_345,
+            678,
+        );
+        let cm =
+            CheckpointMachine::from_machine_info(&MachineInfo::new(*machine.machine(), status));
+        let m = cm.to_machine_info().unwrap();
+        assert_eq!(m.status(), status);
+        assert!(!m.has_id());
+        assert_eq!(m.to_standard_tm_text_format(), cm.machine);
+    }
+}
@@ -1,12 +1,35 @@
 //! This crate contains the Config struct which is used to configure a decider run.
 // TODO doc function, the doc is on the fields
 
-use std::{fmt::Display, sync::LazyLock, time::SystemTime};
-
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, LazyLock,
+    },
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Local, Utc};
 use hashbrown::HashMap;
 use num_format::ToFormattedString;
 
-use crate::toml::ConfigToml;
+use crate::{
+    config_file::ConfigFile,
+    decider::{
+        decider_result::{result_max_steps_known, ResultDisplay, ResultVerbosity},
+        pre_decider::LOOKAHEAD_STEPS_MAX,
+    },
+    macro_machine::BLOCK_SIZE_MAX,
+    output_layout::OutputLayout,
+    stop_conditions::StopConditions,
+    throttle::Throttle,
+    toml::ConfigToml,
+    transition_binary::TransitionOrder,
+    utils::num_cpus_percentage,
+};
 
 // File path, can always be passed as parameter.
 // pub const PATH_RESULT_HTML: &str = "../bb_result/";
@@ -27,9 +50,21 @@ const BATCH_SIZE_FILE: usize = 200;
 /// Default tape size limit (number cells) if not changed in working machine.
 const TAPE_SIZE_LIMIT_U32_BLOCKS_DEFAULT: u32 = 625; // 20.000 cells
 const CPU_UTILIZATION_DEFAULT: usize = 100;
+const PIPELINE_QUEUE_DEPTH_DEFAULT: usize = 4;
+const LIMIT_MACHINES_HIGH_STEPS_DEFAULT: usize = 10_000;
 
 const ENUMERATOR_FULL_BATCH_SIZE_RECOMMENDATION: usize = 500_000;
+/// Recommended steps for [ConfigBuilder::enumerator_lookahead_steps].
+pub const ENUMERATOR_LOOKAHEAD_STEPS_RECOMMENDATION: usize = 20;
+/// Upper bound of the default for [ConfigBuilder::enumerator_tnf_step_limit].
+const ENUMERATOR_TNF_STEP_LIMIT_MAX_DEFAULT: StepBig = 1_000;
 const WRITE_HTML_LINE_LIMIT: u32 = 10_000;
+const STEP_LIMIT_DECIDER_REP_WL_DEFAULT: StepSmall = 10_000;
+const STEP_LIMIT_DECIDER_TRANSLATED_CYCLER_DEFAULT: StepSmall = 10_000;
+const STEP_LIMIT_DECIDER_BACKWARD_REASONING_DEFAULT: StepSmall = 300;
+const STEP_LIMIT_DECIDER_CTL_DEFAULT: StepSmall = 10_000;
+const STEP_LIMIT_DECIDER_COUNTER_DEFAULT: StepSmall = 10_000;
+const STEP_LIMIT_DECIDER_BOUNCER_PROOF_DEFAULT: StepSmall = 10_000;
 
 /// Read config.toml only once
 // https://blog.logrocket.com/how-use-lazy-initialization-pattern-rust-1-80/
@@ -61,11 +96,132 @@ pub const NUM_FIELDS: usize = (MAX_STATES + 1) * 2;
 pub(crate) const MAX_STATES_GENERIC: usize = 10;
 pub(crate) const MAX_SYMBOLS_GENERIC: usize = 10;
 
-/// This is used to define the CPU usage during enumerator and decider run.
+/// This is used to define the CPU usage during enumerator and decider run. \
+/// The run functions take the core usage as parameter, the runs started from the config (e.g. the CLI) use
+/// [Config::core_usage]. The number of threads of the multi core runs is [Self::num_threads], which is
+/// given by [CoreUsage::MultiCore] or [CoreUsage::Percent], otherwise [ConfigBuilder::num_threads] or
+/// [ConfigBuilder::cpu_utilization] of the config are used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CoreUsage {
+    /// Enumerator and deciders run in the calling thread.
     SingleCore,
+    /// The enumerator runs on one core, the deciders on the others.
     SingleCoreEnumeratorMultiCoreDecider,
-    MultiCore,
+    /// Each thread enumerates and decides its own batches, with this number of threads.
+    /// 0 uses the threads of the config.
+    MultiCore(usize),
+    /// Same as MultiCore with this percentage of the detected cores, e.g. 75 -> 6 of 8 cores used. 1-150 allowed.
+    Percent(usize),
+    /// The data provider runs in its own thread and passes the batches through a bounded queue
+    /// to the decider threads, see [Config::pipeline_queue_depth].
+    Pipelined,
+}
+
+impl CoreUsage {
+    /// Number of threads of a run with this core usage and the config.
+    pub fn num_threads(&self, config: &Config) -> usize {
+        match self {
+            CoreUsage::SingleCore => 1,
+            CoreUsage::MultiCore(n) if *n > 0 => *n,
+            CoreUsage::Percent(percent) => num_cpus_percentage(*percent),
+            _ if config.num_threads > 0 => config.num_threads,
+            _ => num_cpus_percentage(config.cpu_utilization_percent),
+        }
+    }
+}
+
+/// MultiCore with the threads of the config.
+impl Default for CoreUsage {
+    fn default() -> Self {
+        CoreUsage::MultiCore(0)
+    }
+}
+
+/// Same text as read by [TryFrom], e.g. 'single', '6' or '75%'.
+impl Display for CoreUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoreUsage::SingleCore => write!(f, "single"),
+            CoreUsage::SingleCoreEnumeratorMultiCoreDecider => write!(f, "single-enumerator"),
+            CoreUsage::MultiCore(0) => write!(f, "multi"),
+            CoreUsage::MultiCore(n) => write!(f, "{n}"),
+            CoreUsage::Percent(percent) => write!(f, "{percent}%"),
+            CoreUsage::Pipelined => write!(f, "pipelined"),
+        }
+    }
+}
+
+/// Reads the name ('single', 'single-enumerator', 'multi' or 'pipelined'), a number of threads (MultiCore)
+/// or a percentage of the detected cores (Percent, e.g. '75%').
+impl TryFrom<&str> for CoreUsage {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "single" => Ok(CoreUsage::SingleCore),
+            "single-enumerator" => Ok(CoreUsage::SingleCoreEnumeratorMultiCoreDecider),
+            "multi" => Ok(CoreUsage::MultiCore(0)),
+            "pipelined" => Ok(CoreUsage::Pipelined),
+            _ => match value.strip_suffix('%') {
+                Some(percent) => match percent.parse::<usize>() {
+                    Ok(p) if p > 0 && p <= 150 => Ok(CoreUsage::Percent(p)),
+                    _ => Err(format!("Unknown core usage: {value}")),
+                },
+                None => match value.parse::<usize>() {
+                    Ok(n) if n > 0 => Ok(CoreUsage::MultiCore(n)),
+                    _ => Err(format!("Unknown core usage: {value}")),
+                },
+            },
+        }
+    }
+}
+
+/// Memory shared by all worker threads for the tapes of the re-runs with a larger tape size limit,
+/// see [Config::tape_memory_budget_bytes]. The clones of a [Config] share the budget.
+#[derive(Debug, Default)]
+pub struct TapeMemoryBudget {
+    /// 0: unlimited.
+    budget_bytes: u64,
+    reserved_bytes: AtomicU64,
+}
+
+impl TapeMemoryBudget {
+    pub fn new(budget_bytes: u64) -> Self {
+        Self {
+            budget_bytes,
+            reserved_bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// Reserves the bytes for a tape. Returns false if the budget would be exceeded, then nothing is reserved.
+    /// Each successful reservation must be released with [Self::release].
+    pub fn try_reserve(&self, bytes: u64) -> bool {
+        if self.budget_bytes == 0 {
+            return true;
+        }
+        self.reserved_bytes
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |reserved| {
+                reserved
+                    .checked_add(bytes)
+                    .filter(|total| *total <= self.budget_bytes)
+            })
+            .is_ok()
+    }
+
+    pub fn release(&self, bytes: u64) {
+        if self.budget_bytes > 0 {
+            self.reserved_bytes.fetch_sub(bytes, Ordering::AcqRel);
+        }
+    }
+
+    pub fn budget_bytes(&self) -> u64 {
+        self.budget_bytes
+    }
+
+    pub fn reserved_bytes(&self) -> u64 {
+        self.reserved_bytes.load(Ordering::Acquire)
+    }
 }
 
 // TODO make config reference with lifetime,
@@ -92,19 +248,68 @@ pub struct Config {
     n_states: usize,
     /// This is the decider halt step limit. If this many steps are walked, then exit undecided.
     step_limit_decider_halt: StepBig,
+    /// Block size of the macro machine of the DeciderHaltMacro, see [MacroMachine](crate::macro_machine::MacroMachine).
+    /// 0: detected for each machine.
+    macro_block_size: usize,
     /// Search step limit for cycles. The loop size can be close to the max step size,
     /// but requires twice as many steps as the loop can only be identified if a repeated loop is found.
     step_limit_decider_cycler: StepSmall,
     /// Search step limit for bouncer.
     step_limit_decider_bouncer: StepSmall,
+    /// Maximum number of abstract configurations the RepWL decider searches per parameter set.
+    step_limit_decider_rep_wl: StepSmall,
+    /// Search step limit for translated cycles.
+    step_limit_decider_translated_cycler: StepSmall,
+    /// Maximum number of steps the backward reasoning decider goes back from a halt transition.
+    step_limit_decider_backward_reasoning: StepSmall,
+    /// Maximum number of local contexts the CTL decider searches per context length.
+    step_limit_decider_ctl: StepSmall,
+    /// Step limit of the counter decider to identify the counter.
+    step_limit_decider_counter: StepSmall,
+    /// Step limit of the bouncer prover to find the records of the bouncer.
+    step_limit_decider_bouncer_proof: StepSmall,
+    /// Shared step budget for each machine over all deciders of a chain. Each decider runs
+    /// with the lower of its own step limit and the steps left in the budget. 0: no shared budget.
+    step_budget_chain: StepBig,
+    /// Wall-clock time limit per machine and decider in milliseconds. A machine exceeding it is undecided with
+    /// [UndecidedReason::TimeLimit](crate::status::UndecidedReason::TimeLimit), see
+    /// [machine_time_limit](crate::decider::machine_time_limit). 0: no time limit.
+    time_limit_machine_ms: u64,
+    /// Undecided machines carry a [DeciderHint](crate::decider::decider_hint::DeciderHint) of the decider to the
+    /// next decider of the chain, e.g. the cycler tells the bouncer when the machine starts to sweep.
+    decider_hints: bool,
+    /// Assertion mode: The engine checks the invariants of the result counters after each batch is added
+    /// and ends the run with [EndReason::InvariantViolated](crate::decider::decider_result::EndReason::InvariantViolated)
+    /// on a violation. This catches accounting errors early, but costs some time.
+    check_invariants: bool,
+    /// Diagnostic mode: [run_decider_chain_gen](crate::decider::decider_engine::run_decider_chain_gen) also runs
+    /// each decider of the chain on all machines and compares the halt steps, see
+    /// [compare_halt_steps](crate::decider::decider_compare::compare_halt_steps). A mismatch is added as
+    /// warning to the result. This runs the machines once more for each decider.
+    compare_halt_steps: bool,
+    /// Upper step limit for the re-runs a decider requests for an undecided machine, see
+    /// [Decider::needs_retry](crate::decider::Decider::needs_retry). The tape size of a re-run is capped by
+    /// the tape size limit escalation. 0: no re-runs.
+    decider_retry_step_limit_max: StepBig,
     /// The init value determines if machines with less steps are recorded.
     /// This can be updated as previous batch runs max can be used as init value for next batches,
     /// reducing updates because a new machine with higher max steps was found.
     steps_min: StepBig,
     /// Tape Size Limit recalculated in full u32 blocks, e.g. 100 -> 4.
     tape_size_limit_u32_blocks: u32,
+    /// Upper tape size limit in u32 blocks for the automatic tape limit escalation. A machine which is
+    /// undecided because of the tape size limit is retried immediately with a doubled tape size limit
+    /// until this cap is reached. 0: no escalation.
+    tape_size_limit_escalation_u32_blocks: u32,
+    /// Memory for the tapes of all re-runs with a tape size limit above [Config::tape_size_limit_cells]
+    /// (tape limit escalation and retries), shared by all worker threads. A re-run which does not fit is skipped
+    /// and counted. 0: unlimited.
+    tape_memory_budget: Arc<TapeMemoryBudget>,
     /// For data provider: Return max this many machines.
     machines_limit: IdNormalized,
+    /// For enumerators: First machine id to enumerate, rounded down to the first machine of its block of
+    /// (4n+1)^2 permutations of state A. The machines_limit counts from this id. 0: start with the first machine.
+    start_from_id: IdNormalized,
     // Ids from bb_challenge file (start, end exclusive). If None then all.
     file_id_range: Option<std::ops::Range<IdNormalized>>,
     /// batch size for operation
@@ -116,20 +321,76 @@ pub struct Config {
     enumerator_full_batch_size_request: usize,
     /// Specific to the EnumeratorReduced: desired batch_size. One needs to test different sizes for max performance.
     enumerator_reduced_batch_size_request: usize,
+    /// Specific to the EnumeratorReduced: Each machine passing the pre-decider is simulated for this many steps
+    /// inside the enumerator. Machines which halt or repeat a configuration within these steps are counted
+    /// in the pre-decider count and not passed to the deciders. 0: no lookahead.
+    enumerator_lookahead_steps: usize,
+    /// Specific to the EnumeratorTNF: Each machine of the tree is simulated for this many steps. If an undefined
+    /// transition is reached within these steps, the tree is extended there, otherwise the machine is a leaf.
+    enumerator_tnf_step_limit: StepBig,
+    /// Order of the transitions of one field in the EnumeratorFull, which defines the machine ids,
+    /// see [TransitionOrder]. The EnumeratorReduced always uses the default order.
+    transition_order: TransitionOrder,
     /// This many decided machines are stored in the ResultDecider. If full, the decider exits.
     /// This is mainly for individual ResultDeciders to further process machines with certain characteristics.
     limit_machines_decided: usize,
     /// This many undecided machines are stored in the ResultDecider. If full, the decider exits.
     /// This is mainly to find machines to further analyze.
     limit_machines_undecided: usize,
+    /// All halting machines with at least this many steps are recorded, not only the machines with max steps.
+    /// 0 to disable. This keeps high but not champion machines, e.g. for BB5 all machines with 100_000+ steps.
+    high_steps_threshold: StepBig,
+    /// This many machines with high steps are stored, the machines with the lowest steps are dropped
+    /// and counted as overflow if more are found.
+    limit_machines_high_steps: usize,
+    /// Counts-only mode for long runs: No machines are recorded (max steps, high steps, decided and undecided
+    /// machines, html output), only the fixed-size counters of the result. The memory stays flat over the run.
+    counts_only: bool,
+    /// Run mode for the runs started from the config, see [CoreUsage].
+    core_usage: CoreUsage,
     /// CPU utilization in percent, e.g. 75 -> 6 of 8 cores used. 0-150 allowed.
     cpu_utilization_percent: usize,
+    /// Number of threads of the threaded runs independent of the detected cores, e.g. for reproducible
+    /// benchmarks on different computers. 0: derived from the cpu utilization.
+    num_threads: usize,
+    /// Background mode: worker threads run with low OS priority and sleep between batches, see [Throttle].
+    /// None to run at full speed.
+    throttle: Option<Throttle>,
+    /// Ends an exploratory run early, e.g. after a number of undecided machines, see [StopConditions].
+    stop_conditions: StopConditions,
+    /// Pipelined run: Number of batches waiting in the queue between the data provider thread and the
+    /// decider threads. The data provider blocks when the queue is full, which keeps the memory bounded.
+    pipeline_queue_depth: usize,
     /// Additional config e.g. for deciders using this library.
     config_key_value_pair: HashMap<String, String>,
     /// Creation time of this Config. Used for file names.
     creation_time: SystemTime,
     /// When set to false UTC is used instead, but this may be confusing to the user.
     use_local_time: bool,
+    /// Directory layout for html files, reports, certificates and checkpoints.
+    output_layout: OutputLayout,
+    /// Verbosity and number of machines shown when the result is displayed.
+    result_display: ResultDisplay,
+    /// Writes the timestamped events of a run (new max steps, progress, stages) into the event log of the output layout.
+    write_event_log: bool,
+    /// Writes the verdict of each machine reaching a decider into the verdict log of the output layout,
+    /// see [VerdictLog](crate::verdict_log::VerdictLog).
+    write_verdict_log: bool,
+    /// Writes the machines reaching a decider, their verdicts and the run metadata into the SQLite database of the
+    /// output layout (feature 'sqlite'), see [results_store_sqlite](crate::results_store_sqlite).
+    write_results_sqlite: bool,
+    /// No progress output of the standard reporter during a run, see [Reporter::from_config](crate::reporter::Reporter::from_config).
+    progress_quiet: bool,
+    /// Shows the live terminal dashboard during a run (feature 'tui'), see [Dashboard](crate::dashboard::Dashboard).
+    dashboard: bool,
+    /// Writes a counters-only snapshot of the result every this many batches into a fixed file of the output layout,
+    /// see [StatsSnapshot](crate::stats_snapshot::StatsSnapshot). 0: no snapshot.
+    stats_snapshot_interval: usize,
+    /// Writes a checkpoint every this many batches into the checkpoint file of the output layout,
+    /// see [Checkpoint](crate::checkpoint::Checkpoint). 0: no checkpoint.
+    checkpoint_interval: usize,
+    /// Checkpoint file from which the run is resumed, see [Checkpoint](crate::checkpoint::Checkpoint).
+    resume_from: Option<PathBuf>,
     /// Outputs decider steps into an html file
     write_html_file: bool,
     /// Outputs decider steps into an html file only for undecided machines
@@ -143,6 +404,9 @@ pub struct Config {
     write_html_line_limit: u32,
     /// reduces 128 bit tape_shifted to 64 bits, which can be printed on a landscape page
     write_html_tape_shifted_64_bit: bool,
+    /// Options which could not be applied when building the config, e.g. html output without the feature.
+    /// They are added to the result warnings instead of being printed.
+    warnings: Vec<String>,
     // / config.toml, only loaded on demand as this would require disk operation and slows down config creation
     // config_toml: Option<ConfigToml>,
 }
@@ -158,30 +422,83 @@ impl Config {
         ConfigBuilder::new_config(config.clone())
     }
 
+    /// Reads the config from a TOML file, which must contain n_states, see [ConfigFile].
+    pub fn from_file(file_path: impl AsRef<Path>) -> io::Result<Config> {
+        let config_file = ConfigFile::read(&file_path)?;
+        let Some(n_states) = config_file.n_states else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Config file {}: n_states is missing",
+                    file_path.as_ref().display()
+                ),
+            ));
+        };
+        Ok(config_file.builder(n_states).build())
+    }
+
     /// Default values for testing purposes. Better use builder.
     pub fn new_default(n_states: usize) -> Config {
         let step_limit = Self::step_limit_decider_halt_default(n_states);
         Self {
             n_states,
             step_limit_decider_halt: step_limit,
+            macro_block_size: 0,
             steps_min: if n_states == 1 { 0 } else { 2 },
             // TODO depending on n_states
             tape_size_limit_u32_blocks: TAPE_SIZE_LIMIT_U32_BLOCKS_DEFAULT,
+            tape_size_limit_escalation_u32_blocks: 0,
+            tape_memory_budget: Arc::new(TapeMemoryBudget::new(0)),
             machines_limit: Self::enumerate_limit_default(n_states),
+            start_from_id: 0,
+            warnings: Vec::new(),
             enumerator_first_rotate_field_front: false,
             enumerator_full_batch_size_request: ENUMERATOR_FULL_BATCH_SIZE_RECOMMENDATION,
             enumerator_reduced_batch_size_request:
                 Self::enumerator_reduced_batch_size_request_recommendation(n_states),
+            enumerator_lookahead_steps: 0,
+            enumerator_tnf_step_limit: step_limit.min(ENUMERATOR_TNF_STEP_LIMIT_MAX_DEFAULT),
+            transition_order: TransitionOrder::DirectionMajor,
             file_id_range: None,
             batch_size: BATCH_SIZE_FILE,
             limit_machines_decided: 0,
             limit_machines_undecided: 0,
+            high_steps_threshold: 0,
+            limit_machines_high_steps: LIMIT_MACHINES_HIGH_STEPS_DEFAULT,
+            counts_only: false,
+            core_usage: CoreUsage::default(),
             cpu_utilization_percent: CPU_UTILIZATION_DEFAULT,
+            num_threads: 0,
+            throttle: None,
+            stop_conditions: StopConditions::default(),
+            pipeline_queue_depth: PIPELINE_QUEUE_DEPTH_DEFAULT,
             config_key_value_pair: HashMap::new(),
             creation_time: SystemTime::now(),
             use_local_time: true,
+            output_layout: OutputLayout::default(),
+            result_display: ResultDisplay::default(),
+            write_event_log: false,
+            write_verdict_log: false,
+            write_results_sqlite: false,
+            progress_quiet: false,
+            dashboard: false,
+            stats_snapshot_interval: 0,
+            checkpoint_interval: 0,
+            resume_from: None,
             step_limit_decider_bouncer: Self::step_limit_bouncer_default(n_states),
             step_limit_decider_cycler: Self::step_limit_cycler_default(n_states),
+            step_limit_decider_rep_wl: STEP_LIMIT_DECIDER_REP_WL_DEFAULT,
+            step_limit_decider_translated_cycler: STEP_LIMIT_DECIDER_TRANSLATED_CYCLER_DEFAULT,
+            step_limit_decider_backward_reasoning: STEP_LIMIT_DECIDER_BACKWARD_REASONING_DEFAULT,
+            step_limit_decider_ctl: STEP_LIMIT_DECIDER_CTL_DEFAULT,
+            step_limit_decider_counter: STEP_LIMIT_DECIDER_COUNTER_DEFAULT,
+            step_limit_decider_bouncer_proof: STEP_LIMIT_DECIDER_BOUNCER_PROOF_DEFAULT,
+            step_budget_chain: 0,
+            time_limit_machine_ms: 0,
+            decider_hints: false,
+            check_invariants: false,
+            compare_halt_steps: false,
+            decider_retry_step_limit_max: 0,
             write_html_file: false,
             write_html_file_undecided: false,
             write_html_step_start: 0,
@@ -261,14 +578,51 @@ impl Config {
         self.config_key_value_pair.get(key)
     }
 
+    /// Run mode for the runs started from the config, e.g. by the CLI.
+    pub fn core_usage(&self) -> CoreUsage {
+        self.core_usage
+    }
+
     pub fn cpu_utilization_percent(&self) -> usize {
         self.cpu_utilization_percent
     }
 
+    /// Configured number of threads, 0 if derived from the cpu utilization.
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    /// Number of threads used by the threaded runs, see [CoreUsage::num_threads]: 1 for [CoreUsage::SingleCore],
+    /// the threads of [CoreUsage::MultiCore] or [CoreUsage::Percent], [Self::num_threads] if set,
+    /// otherwise [Self::cpu_utilization_percent] of the detected cores.
+    pub fn num_threads_effective(&self) -> usize {
+        self.core_usage.num_threads(self)
+    }
+
+    /// Background mode of the worker threads, None if they run at full speed.
+    pub fn throttle(&self) -> Option<&Throttle> {
+        self.throttle.as_ref()
+    }
+
+    pub fn stop_conditions(&self) -> &StopConditions {
+        &self.stop_conditions
+    }
+
     pub fn creation_time(&self) -> SystemTime {
         self.creation_time
     }
 
+    /// Creation time formatted for file names, e.g. '20250101_120000'. Uses local time or UTC depending on use_local_time.
+    pub fn creation_time_to_string(&self) -> String {
+        if self.use_local_time {
+            let datetime_local: DateTime<Local> = self.creation_time.into();
+            datetime_local.format("%Y%m%d_%H%M%S").to_string()
+        } else {
+            let datetime_utc: DateTime<Utc> = self.creation_time.into();
+            datetime_utc.format("%Y%m%d_%H%M%S").to_string()
+        }
+    }
+
     pub fn file_id_range(&self) -> Option<std::ops::Range<IdNormalized>> {
         self.file_id_range.clone()
     }
@@ -298,6 +652,20 @@ impl Config {
         self.enumerator_reduced_batch_size_request
     }
 
+    /// Steps of the inline simulation in the EnumeratorReduced, 0 if not used.
+    pub fn enumerator_lookahead_steps(&self) -> usize {
+        self.enumerator_lookahead_steps
+    }
+
+    /// Steps of the simulation of each machine in the EnumeratorTNF.
+    pub fn enumerator_tnf_step_limit(&self) -> StepBig {
+        self.enumerator_tnf_step_limit
+    }
+
+    pub fn transition_order(&self) -> TransitionOrder {
+        self.transition_order
+    }
+
     pub fn enumerator_reduced_batch_size_request_recommendation(n_states: usize) -> usize {
         match n_states {
             1 | 2 => 10_000,
@@ -314,6 +682,20 @@ impl Config {
         self.limit_machines_undecided
     }
 
+    /// Halting machines with at least this many steps are recorded, 0 if disabled.
+    pub fn high_steps_threshold(&self) -> StepBig {
+        self.high_steps_threshold
+    }
+
+    pub fn limit_machines_high_steps(&self) -> usize {
+        self.limit_machines_high_steps
+    }
+
+    /// True if no machines are recorded, only the counters, see [ConfigBuilder::counts_only].
+    pub fn counts_only(&self) -> bool {
+        self.counts_only
+    }
+
     // pub fn set_limit_machines_undecided(&mut self, limit: usize) {
     //     self.limit_machines_undecided = limit;
     // }
@@ -326,6 +708,20 @@ impl Config {
         self.n_states
     }
 
+    /// Number of batches the pipelined run holds between data provider and deciders, at least 1.
+    pub fn pipeline_queue_depth(&self) -> usize {
+        self.pipeline_queue_depth
+    }
+
+    pub fn start_from_id(&self) -> IdNormalized {
+        self.start_from_id
+    }
+
+    /// Options which could not be applied when the config was built.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
     pub fn steps_min(&self) -> StepBig {
         self.steps_min
     }
@@ -337,10 +733,54 @@ impl Config {
     //     }
     // }
 
+    /// Shared step budget per machine over the decider chain, 0 if each decider only uses its own limit.
+    pub fn step_budget_chain(&self) -> StepBig {
+        self.step_budget_chain
+    }
+
+    /// Time limit per machine and decider in milliseconds, 0 if the machines are only limited by steps.
+    pub fn time_limit_machine_ms(&self) -> u64 {
+        self.time_limit_machine_ms
+    }
+
+    /// Time limit per machine and decider, None if the machines are only limited by steps.
+    pub fn time_limit_machine(&self) -> Option<Duration> {
+        if self.time_limit_machine_ms == 0 {
+            None
+        } else {
+            Some(Duration::from_millis(self.time_limit_machine_ms))
+        }
+    }
+
+    /// Deciders pass hints for the undecided machines to the next decider of the chain.
+    pub fn decider_hints(&self) -> bool {
+        self.decider_hints
+    }
+
+    /// The engine checks the invariants of the result counters after each batch.
+    pub fn check_invariants(&self) -> bool {
+        self.check_invariants
+    }
+
+    /// The decider chain is checked for halt step mismatches of its deciders.
+    pub fn compare_halt_steps(&self) -> bool {
+        self.compare_halt_steps
+    }
+
+    /// Upper step limit for the re-runs a decider requests, 0 if re-runs are disabled.
+    pub fn decider_retry_step_limit_max(&self) -> StepBig {
+        self.decider_retry_step_limit_max
+    }
+
     pub fn step_limit_decider_halt(&self) -> StepBig {
         self.step_limit_decider_halt
     }
 
+    /// Block size of the macro machine, 0 if detected for each machine.
+    pub fn macro_block_size(&self) -> usize {
+        self.macro_block_size
+    }
+
     pub fn step_limit_decider_bouncer(&self) -> StepSmall {
         self.step_limit_decider_bouncer
     }
@@ -349,6 +789,30 @@ impl Config {
         self.step_limit_decider_cycler
     }
 
+    pub fn step_limit_decider_rep_wl(&self) -> StepSmall {
+        self.step_limit_decider_rep_wl
+    }
+
+    pub fn step_limit_decider_translated_cycler(&self) -> StepSmall {
+        self.step_limit_decider_translated_cycler
+    }
+
+    pub fn step_limit_decider_backward_reasoning(&self) -> StepSmall {
+        self.step_limit_decider_backward_reasoning
+    }
+
+    pub fn step_limit_decider_ctl(&self) -> StepSmall {
+        self.step_limit_decider_ctl
+    }
+
+    pub fn step_limit_decider_counter(&self) -> StepSmall {
+        self.step_limit_decider_counter
+    }
+
+    pub fn step_limit_decider_bouncer_proof(&self) -> StepSmall {
+        self.step_limit_decider_bouncer_proof
+    }
+
     pub fn tape_size_limit_cells(&self) -> u32 {
         self.tape_size_limit_u32_blocks * 32
     }
@@ -357,10 +821,71 @@ impl Config {
         self.tape_size_limit_u32_blocks
     }
 
+    pub fn tape_size_limit_escalation_u32_blocks(&self) -> u32 {
+        self.tape_size_limit_escalation_u32_blocks
+    }
+
+    pub fn tape_memory_budget_bytes(&self) -> u64 {
+        self.tape_memory_budget.budget_bytes()
+    }
+
+    pub fn tape_memory_budget(&self) -> &TapeMemoryBudget {
+        &self.tape_memory_budget
+    }
+
     pub fn use_local_time(&self) -> bool {
         self.use_local_time
     }
 
+    pub fn output_layout(&self) -> &OutputLayout {
+        &self.output_layout
+    }
+
+    pub fn result_display(&self) -> ResultDisplay {
+        self.result_display
+    }
+
+    pub fn write_event_log(&self) -> bool {
+        self.write_event_log
+    }
+
+    pub fn write_verdict_log(&self) -> bool {
+        self.write_verdict_log
+    }
+
+    pub fn write_results_sqlite(&self) -> bool {
+        self.write_results_sqlite
+    }
+
+    /// The deciders record the verdict of each machine, because the verdict log or the SQLite store is written.
+    pub fn record_verdicts(&self) -> bool {
+        self.write_verdict_log || self.write_results_sqlite
+    }
+
+    pub fn progress_quiet(&self) -> bool {
+        self.progress_quiet
+    }
+
+    /// Dashboard requested, it is only shown if the feature 'tui' is enabled.
+    pub fn dashboard(&self) -> bool {
+        self.dashboard
+    }
+
+    /// Batches between two stats snapshots, 0 if no snapshot is written.
+    pub fn stats_snapshot_interval(&self) -> usize {
+        self.stats_snapshot_interval
+    }
+
+    /// Batches between two checkpoints, 0 if no checkpoint is written.
+    pub fn checkpoint_interval(&self) -> usize {
+        self.checkpoint_interval
+    }
+
+    /// Checkpoint file from which the run is resumed, None for a new run.
+    pub fn resume_from(&self) -> Option<&Path> {
+        self.resume_from.as_deref()
+    }
+
     pub fn write_html_file(&self) -> bool {
         self.write_html_file
     }
@@ -412,16 +937,54 @@ pub struct ConfigBuilder {
     enumerator_first_rotate_field_front: Option<bool>,
     enumerator_batch_size_request_full: Option<usize>,
     enumerator_batch_size_request_reduced: Option<usize>,
+    enumerator_lookahead_steps: Option<usize>,
+    enumerator_tnf_step_limit: Option<StepBig>,
+    transition_order: Option<TransitionOrder>,
     step_limit_decider_halt: Option<StepBig>,
+    macro_block_size: Option<usize>,
     step_limit_decider_bouncer: Option<StepSmall>,
     step_limit_decider_cycler: Option<StepSmall>,
+    step_limit_decider_rep_wl: Option<StepSmall>,
+    step_limit_decider_translated_cycler: Option<StepSmall>,
+    step_limit_decider_backward_reasoning: Option<StepSmall>,
+    step_limit_decider_ctl: Option<StepSmall>,
+    step_limit_decider_counter: Option<StepSmall>,
+    step_limit_decider_bouncer_proof: Option<StepSmall>,
+    step_budget_chain: Option<StepBig>,
+    time_limit_machine_ms: Option<u64>,
+    decider_hints: Option<bool>,
+    check_invariants: Option<bool>,
+    compare_halt_steps: Option<bool>,
+    decider_retry_step_limit_max: Option<StepBig>,
     tape_size_limit_u32_blocks: Option<u32>,
+    tape_size_limit_escalation_u32_blocks: Option<u32>,
+    tape_memory_budget_bytes: Option<u64>,
     machines_limit: Option<u64>,
+    start_from_id: Option<IdNormalized>,
     limit_machines_decided: Option<usize>,
     limit_machines_undecided: Option<usize>,
+    high_steps_threshold: Option<StepBig>,
+    limit_machines_high_steps: Option<usize>,
+    counts_only: Option<bool>,
+    core_usage: Option<CoreUsage>,
     cpu_utilization_percent: Option<usize>,
+    num_threads: Option<usize>,
+    throttle: Option<Option<Throttle>>,
+    stop_conditions: Option<StopConditions>,
+    pipeline_queue_depth: Option<usize>,
     config_key_value_pair: Option<HashMap<String, String>>,
     use_local_time: Option<bool>,
+    output_layout: Option<OutputLayout>,
+    result_verbosity: Option<ResultVerbosity>,
+    result_display_limits: Option<(usize, usize)>,
+    write_event_log: Option<bool>,
+    write_verdict_log: Option<bool>,
+    write_results_sqlite: Option<bool>,
+    progress_quiet: Option<bool>,
+    dashboard: Option<bool>,
+    stats_snapshot_interval: Option<usize>,
+    checkpoint_interval: Option<usize>,
+    resume_from: Option<PathBuf>,
     write_html_file: Option<bool>,
     write_html_file_undecided: Option<bool>,
     write_html_step_start: Option<StepBig>,
@@ -449,11 +1012,35 @@ impl ConfigBuilder {
         self
     }
 
+    /// Run mode for the runs started from the config, see [CoreUsage].
+    pub fn core_usage(mut self, core_usage: CoreUsage) -> Self {
+        self.core_usage = Some(core_usage);
+        self
+    }
+
     pub fn cpu_utilization(mut self, percent: usize) -> Self {
         self.cpu_utilization_percent = Some(percent);
         self
     }
 
+    /// Fixed number of threads, which overrides the cpu utilization. 0 to derive it from the cpu utilization.
+    pub fn num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// Runs the worker threads in the throttled background mode, see [Throttle]. None to run at full speed.
+    pub fn throttle(mut self, throttle: Option<Throttle>) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Ends the run early when one of the conditions is met, see [StopConditions].
+    pub fn stop_conditions(mut self, stop_conditions: StopConditions) -> Self {
+        self.stop_conditions = Some(stop_conditions);
+        self
+    }
+
     pub fn file_id_range(mut self, file_id_range: std::ops::Range<IdNormalized>) -> Self {
         self.file_id_range = Some(file_id_range);
         self
@@ -474,6 +1061,32 @@ impl ConfigBuilder {
         self
     }
 
+    /// Simulates each machine of the EnumeratorReduced for this many steps inside the enumerator, which decides
+    /// the machines halting or cycling within a few steps without the batch overhead of the deciders.
+    /// [ENUMERATOR_LOOKAHEAD_STEPS_RECOMMENDATION] is a good value, 0 to disable. \
+    /// These machines are only counted, so the steps are capped below the max steps of n_states (e.g. 5 for BB2)
+    /// and at [LOOKAHEAD_STEPS_MAX].
+    pub fn enumerator_lookahead_steps(mut self, steps: usize) -> Self {
+        self.enumerator_lookahead_steps = Some(steps);
+        self
+    }
+
+    /// The EnumeratorTNF simulates each machine of the tree this many steps to find the undefined transitions
+    /// to extend. The default is the halt step limit, but at most 1,000. \
+    /// A machine running longer without reaching an undefined transition is returned as leaf. If a decider
+    /// finds it halting later, the machines extending it are not enumerated.
+    pub fn enumerator_tnf_step_limit(mut self, step_limit: StepBig) -> Self {
+        self.enumerator_tnf_step_limit = Some(step_limit);
+        self
+    }
+
+    /// Order of the transitions in the EnumeratorFull, e.g. to compare the machine ids with other
+    /// published enumerations, see [TransitionOrder].
+    pub fn transition_order(mut self, order: TransitionOrder) -> Self {
+        self.transition_order = Some(order);
+        self
+    }
+
     pub fn limit_machines_decided(mut self, value: usize) -> Self {
         self.limit_machines_decided = Some(value);
         self
@@ -484,16 +1097,95 @@ impl ConfigBuilder {
         self
     }
 
+    /// Records all halting machines with at least this many steps (0 to disable), see [Config::high_steps_threshold].
+    pub fn high_steps_threshold(mut self, steps: StepBig) -> Self {
+        self.high_steps_threshold = Some(steps);
+        self
+    }
+
+    /// Max number of recorded machines with high steps, see [Config::high_steps_threshold].
+    pub fn limit_machines_high_steps(mut self, value: usize) -> Self {
+        self.limit_machines_high_steps = Some(value);
+        self
+    }
+
+    /// Counts-only mode: Disables all machine recording (limit_machines_decided, limit_machines_undecided,
+    /// high_steps_threshold, html output and the machines with max steps), only the totals are counted.
+    /// This maximizes the throughput and keeps the memory flat over arbitrarily long runs.
+    pub fn counts_only(mut self, value: bool) -> Self {
+        self.counts_only = Some(value);
+        self
+    }
+
     pub fn machine_limit(mut self, enumerate_limit: u64) -> Self {
         self.machines_limit = Some(enumerate_limit);
         self
     }
 
+    /// Number of batches the pipelined run ([CoreUsage::Pipelined]) holds in its queue. Values below 1 are set to 1.
+    pub fn pipeline_queue_depth(mut self, depth: usize) -> Self {
+        self.pipeline_queue_depth = Some(depth);
+        self
+    }
+
+    /// Enumerators start with this machine id (rounded down to the first machine of its block of
+    /// (4n+1)^2 permutations of state A) and enumerate onward. The machine_limit counts from here.
+    pub fn start_from_id(mut self, id: IdNormalized) -> Self {
+        self.start_from_id = Some(id);
+        self
+    }
+
+    pub fn step_budget_chain(mut self, step_budget: StepBig) -> Self {
+        self.step_budget_chain = Some(step_budget);
+        self
+    }
+
+    /// Ends a machine which runs longer than this in one decider as undecided, 0 for no time limit.
+    pub fn time_limit_machine_ms(mut self, time_limit_ms: u64) -> Self {
+        self.time_limit_machine_ms = Some(time_limit_ms);
+        self
+    }
+
+    /// Deciders pass hints for the undecided machines to the next decider of the chain, see
+    /// [DeciderHint](crate::decider::decider_hint::DeciderHint).
+    pub fn decider_hints(mut self, decider_hints: bool) -> Self {
+        self.decider_hints = Some(decider_hints);
+        self
+    }
+
+    /// Assertion mode: The engine checks the invariants of the result counters after each batch and
+    /// ends the run on a violation, see [Config::check_invariants].
+    pub fn check_invariants(mut self, check_invariants: bool) -> Self {
+        self.check_invariants = Some(check_invariants);
+        self
+    }
+
+    /// Diagnostic mode: The halt steps of the deciders of the chain are compared after the run,
+    /// see [Config::compare_halt_steps].
+    pub fn compare_halt_steps(mut self, compare_halt_steps: bool) -> Self {
+        self.compare_halt_steps = Some(compare_halt_steps);
+        self
+    }
+
+    /// Allows the deciders to re-run undecided machines with a larger step limit up to this limit,
+    /// see [Config::decider_retry_step_limit_max]. 0: no re-runs.
+    pub fn decider_retry_step_limit_max(mut self, step_limit: StepBig) -> Self {
+        self.decider_retry_step_limit_max = Some(step_limit);
+        self
+    }
+
     pub fn step_limit_decider_halt(mut self, step_limit: StepBig) -> Self {
         self.step_limit_decider_halt = Some(step_limit);
         self
     }
 
+    /// Block size of the macro machine of the DeciderHaltMacro, up to [BLOCK_SIZE_MAX](crate::macro_machine::BLOCK_SIZE_MAX).
+    /// 0 to detect the block size for each machine.
+    pub fn macro_block_size(mut self, block_size: usize) -> Self {
+        self.macro_block_size = Some(block_size);
+        self
+    }
+
     pub fn step_limit_decider_bouncer(mut self, step_limit: StepSmall) -> Self {
         self.step_limit_decider_bouncer = Some(step_limit);
         self
@@ -504,17 +1196,130 @@ impl ConfigBuilder {
         self
     }
 
+    pub fn step_limit_decider_rep_wl(mut self, step_limit: StepSmall) -> Self {
+        self.step_limit_decider_rep_wl = Some(step_limit);
+        self
+    }
+
+    pub fn step_limit_decider_translated_cycler(mut self, step_limit: StepSmall) -> Self {
+        self.step_limit_decider_translated_cycler = Some(step_limit);
+        self
+    }
+
+    pub fn step_limit_decider_backward_reasoning(mut self, step_limit: StepSmall) -> Self {
+        self.step_limit_decider_backward_reasoning = Some(step_limit);
+        self
+    }
+
+    pub fn step_limit_decider_ctl(mut self, step_limit: StepSmall) -> Self {
+        self.step_limit_decider_ctl = Some(step_limit);
+        self
+    }
+
+    pub fn step_limit_decider_counter(mut self, step_limit: StepSmall) -> Self {
+        self.step_limit_decider_counter = Some(step_limit);
+        self
+    }
+
+    pub fn step_limit_decider_bouncer_proof(mut self, step_limit: StepSmall) -> Self {
+        self.step_limit_decider_bouncer_proof = Some(step_limit);
+        self
+    }
+
     pub fn tape_size_limit_cells(mut self, tape_size_limit_cells: u32) -> Self {
         let t = tape_size_limit_cells.div_ceil(32);
         self.tape_size_limit_u32_blocks = Some(t);
         self
     }
 
+    /// Cap for the automatic tape limit escalation in cells, 0 to turn it off.
+    pub fn tape_size_limit_escalation_cells(mut self, tape_size_limit_cells_max: u32) -> Self {
+        let t = tape_size_limit_cells_max.div_ceil(32);
+        self.tape_size_limit_escalation_u32_blocks = Some(t);
+        self
+    }
+
+    /// Memory for the tapes of the re-runs with a larger tape size limit of all worker threads in bytes,
+    /// see [Config::tape_memory_budget_bytes]. 0: unlimited.
+    pub fn tape_memory_budget_bytes(mut self, budget_bytes: u64) -> Self {
+        self.tape_memory_budget_bytes = Some(budget_bytes);
+        self
+    }
+
     pub fn use_local_time(mut self, value_false_is_utc: bool) -> Self {
         self.use_local_time = Some(value_false_is_utc);
         self
     }
 
+    pub fn output_layout(mut self, output_layout: OutputLayout) -> Self {
+        self.output_layout = Some(output_layout);
+        self
+    }
+
+    /// Level of detail when the result is displayed: Summary, Normal or Full.
+    pub fn result_verbosity(mut self, verbosity: ResultVerbosity) -> Self {
+        self.result_verbosity = Some(verbosity);
+        self
+    }
+
+    /// Number of machines with max steps and undecided machines shown in the result (Normal verbosity).
+    pub fn result_display_limits(
+        mut self,
+        limit_machines_max_steps: usize,
+        limit_machines_undecided: usize,
+    ) -> Self {
+        self.result_display_limits = Some((limit_machines_max_steps, limit_machines_undecided));
+        self
+    }
+
+    pub fn write_event_log(mut self, value: bool) -> Self {
+        self.write_event_log = Some(value);
+        self
+    }
+
+    /// Writes one record per machine reaching a decider into the verdict log, flushed after each batch.
+    pub fn write_verdict_log(mut self, value: bool) -> Self {
+        self.write_verdict_log = Some(value);
+        self
+    }
+
+    /// Writes the machines, verdicts and run metadata into the SQLite database, requires the feature 'sqlite'.
+    pub fn write_results_sqlite(mut self, value: bool) -> Self {
+        self.write_results_sqlite = Some(value);
+        self
+    }
+
+    pub fn progress_quiet(mut self, value: bool) -> Self {
+        self.progress_quiet = Some(value);
+        self
+    }
+
+    /// Shows the live terminal dashboard instead of the progress lines, requires the feature 'tui'.
+    pub fn dashboard(mut self, value: bool) -> Self {
+        self.dashboard = Some(value);
+        self
+    }
+
+    /// Writes a counters-only stats snapshot every this many batches, so an aborted run loses at most
+    /// these batches of statistics. 0 to disable.
+    pub fn stats_snapshot_interval(mut self, batches: usize) -> Self {
+        self.stats_snapshot_interval = Some(batches);
+        self
+    }
+
+    /// Writes a checkpoint every this many batches and at the end of the run, so the run can be resumed
+    /// with [Self::resume_from]. 0 to disable.
+    pub fn checkpoint_interval(mut self, batches: usize) -> Self {
+        self.checkpoint_interval = Some(batches);
+        self
+    }
+
+    /// Resumes the run from this checkpoint file, see [Checkpoint](crate::checkpoint::Checkpoint).
+    pub fn resume_from(mut self, file_path: impl Into<PathBuf>) -> Self {
+        self.resume_from = Some(file_path.into());
+        self
+    }
+
     pub fn write_html_file(mut self, value: bool) -> Self {
         self.write_html_file = Some(value);
         self
@@ -541,24 +1346,73 @@ impl ConfigBuilder {
     }
 
     pub fn build(self) -> Config {
-        #[allow(unused_mut)]
         let mut config = Config {
             n_states: self.config.n_states,
             batch_size: self.batch_size.unwrap_or(self.config.batch_size),
             step_limit_decider_halt: self
                 .step_limit_decider_halt
                 .unwrap_or(self.config.step_limit_decider_halt),
+            macro_block_size: self
+                .macro_block_size
+                .unwrap_or(self.config.macro_block_size)
+                .min(BLOCK_SIZE_MAX),
             step_limit_decider_bouncer: self
                 .step_limit_decider_bouncer
                 .unwrap_or(self.config.step_limit_decider_bouncer),
             step_limit_decider_cycler: self
                 .step_limit_decider_cycler
                 .unwrap_or(self.config.step_limit_decider_cycler),
+            step_limit_decider_rep_wl: self
+                .step_limit_decider_rep_wl
+                .unwrap_or(self.config.step_limit_decider_rep_wl),
+            step_limit_decider_translated_cycler: self
+                .step_limit_decider_translated_cycler
+                .unwrap_or(self.config.step_limit_decider_translated_cycler),
+            step_limit_decider_backward_reasoning: self
+                .step_limit_decider_backward_reasoning
+                .unwrap_or(self.config.step_limit_decider_backward_reasoning),
+            step_limit_decider_ctl: self
+                .step_limit_decider_ctl
+                .unwrap_or(self.config.step_limit_decider_ctl),
+            step_limit_decider_counter: self
+                .step_limit_decider_counter
+                .unwrap_or(self.config.step_limit_decider_counter),
+            step_limit_decider_bouncer_proof: self
+                .step_limit_decider_bouncer_proof
+                .unwrap_or(self.config.step_limit_decider_bouncer_proof),
+            step_budget_chain: self
+                .step_budget_chain
+                .unwrap_or(self.config.step_budget_chain),
+            time_limit_machine_ms: self
+                .time_limit_machine_ms
+                .unwrap_or(self.config.time_limit_machine_ms),
+            decider_hints: self.decider_hints.unwrap_or(self.config.decider_hints),
+            check_invariants: self
+                .check_invariants
+                .unwrap_or(self.config.check_invariants),
+            compare_halt_steps: self
+                .compare_halt_steps
+                .unwrap_or(self.config.compare_halt_steps),
+            decider_retry_step_limit_max: self
+                .decider_retry_step_limit_max
+                .unwrap_or(self.config.decider_retry_step_limit_max),
             steps_min: self.config.steps_min,
             tape_size_limit_u32_blocks: self
                 .tape_size_limit_u32_blocks
                 .unwrap_or(self.config.tape_size_limit_u32_blocks),
+            tape_size_limit_escalation_u32_blocks: self
+                .tape_size_limit_escalation_u32_blocks
+                .unwrap_or(self.config.tape_size_limit_escalation_u32_blocks),
+            // a config built from another config shares its budget
+            tape_memory_budget: match self.tape_memory_budget_bytes {
+                Some(budget_bytes) if budget_bytes != self.config.tape_memory_budget_bytes() => {
+                    Arc::new(TapeMemoryBudget::new(budget_bytes))
+                }
+                _ => Arc::clone(&self.config.tape_memory_budget),
+            },
             machines_limit: self.machines_limit.unwrap_or(self.config.machines_limit),
+            start_from_id: self.start_from_id.unwrap_or(self.config.start_from_id),
+            warnings: Vec::new(),
             enumerator_first_rotate_field_front: self
                 .enumerator_first_rotate_field_front
                 .unwrap_or(self.config.enumerator_first_rotate_field_front),
@@ -568,6 +1422,23 @@ impl ConfigBuilder {
             enumerator_reduced_batch_size_request: self
                 .enumerator_batch_size_request_reduced
                 .unwrap_or(self.config.enumerator_reduced_batch_size_request()),
+            enumerator_lookahead_steps: {
+                let steps = self
+                    .enumerator_lookahead_steps
+                    .unwrap_or(self.config.enumerator_lookahead_steps)
+                    .min(LOOKAHEAD_STEPS_MAX);
+                // the halting machines are not recorded, the max steps machines must reach the deciders
+                match self.config.n_states {
+                    1..=3 => steps.min(result_max_steps_known(self.config.n_states) as usize - 1),
+                    _ => steps,
+                }
+            },
+            enumerator_tnf_step_limit: self
+                .enumerator_tnf_step_limit
+                .unwrap_or(self.config.enumerator_tnf_step_limit),
+            transition_order: self
+                .transition_order
+                .unwrap_or(self.config.transition_order),
             file_id_range: if self.config.file_id_range.is_some() {
                 self.config.file_id_range
             } else {
@@ -579,14 +1450,57 @@ impl ConfigBuilder {
             limit_machines_undecided: self
                 .limit_machines_undecided
                 .unwrap_or(self.config.limit_machines_undecided),
+            high_steps_threshold: self
+                .high_steps_threshold
+                .unwrap_or(self.config.high_steps_threshold),
+            limit_machines_high_steps: self
+                .limit_machines_high_steps
+                .unwrap_or(self.config.limit_machines_high_steps),
+            counts_only: self.counts_only.unwrap_or(self.config.counts_only),
+            core_usage: self.core_usage.unwrap_or(self.config.core_usage),
             cpu_utilization_percent: self
                 .cpu_utilization_percent
                 .unwrap_or(self.config.cpu_utilization_percent),
+            num_threads: self.num_threads.unwrap_or(self.config.num_threads),
+            throttle: self.throttle.unwrap_or(self.config.throttle),
+            stop_conditions: self.stop_conditions.unwrap_or(self.config.stop_conditions),
+            pipeline_queue_depth: self
+                .pipeline_queue_depth
+                .unwrap_or(self.config.pipeline_queue_depth)
+                .max(1),
             config_key_value_pair: self
                 .config_key_value_pair
                 .unwrap_or(self.config.config_key_value_pair),
             creation_time: SystemTime::now(),
             use_local_time: self.use_local_time.unwrap_or(self.config.use_local_time),
+            output_layout: self.output_layout.unwrap_or(self.config.output_layout),
+            result_display: {
+                let mut display = self.config.result_display;
+                if let Some(verbosity) = self.result_verbosity {
+                    display.verbosity = verbosity;
+                }
+                if let Some((max_steps, undecided)) = self.result_display_limits {
+                    display.limit_machines_max_steps = max_steps;
+                    display.limit_machines_undecided = undecided;
+                }
+                display
+            },
+            write_event_log: self.write_event_log.unwrap_or(self.config.write_event_log),
+            write_verdict_log: self
+                .write_verdict_log
+                .unwrap_or(self.config.write_verdict_log),
+            write_results_sqlite: self
+                .write_results_sqlite
+                .unwrap_or(self.config.write_results_sqlite),
+            progress_quiet: self.progress_quiet.unwrap_or(self.config.progress_quiet),
+            dashboard: self.dashboard.unwrap_or(self.config.dashboard),
+            stats_snapshot_interval: self
+                .stats_snapshot_interval
+                .unwrap_or(self.config.stats_snapshot_interval),
+            checkpoint_interval: self
+                .checkpoint_interval
+                .unwrap_or(self.config.checkpoint_interval),
+            resume_from: self.resume_from.or(self.config.resume_from),
             write_html_file: self.write_html_file.unwrap_or(self.config.write_html_file),
             write_html_file_undecided: self
                 .write_html_file
@@ -603,14 +1517,32 @@ impl ConfigBuilder {
             // config_toml: None,
         };
 
+        if config.counts_only {
+            config.limit_machines_decided = 0;
+            config.limit_machines_undecided = 0;
+            config.high_steps_threshold = 0;
+            config.write_html_file = false;
+            config.write_html_file_undecided = false;
+        }
+
         #[cfg(not(feature = "enable_html_reports"))]
         if config.write_html_file {
-            println!(
-                "WARNING: feature 'enable_html_reports' is not enabled, cannot write HTML files."
+            config.warnings.push(
+                "feature 'enable_html_reports' is not enabled, cannot write HTML files."
+                    .to_string(),
             );
             config.write_html_file = false;
         }
 
+        #[cfg(not(feature = "sqlite"))]
+        if config.write_results_sqlite {
+            config.warnings.push(
+                "feature 'sqlite' is not enabled, cannot write the SQLite result store."
+                    .to_string(),
+            );
+            config.write_results_sqlite = false;
+        }
+
         config
     }
 }
@@ -625,6 +1557,57 @@ impl Display for Config {
             self.step_limit_decider_cycler.to_formatted_string(&locale),
             self.step_limit_decider_bouncer.to_formatted_string(&locale)
         )?;
+        if self.step_budget_chain > 0 {
+            write!(
+                f,
+                "Step Budget Chain: {}, ",
+                self.step_budget_chain.to_formatted_string(&locale)
+            )?;
+        }
+        if self.time_limit_machine_ms > 0 {
+            write!(
+                f,
+                "Time Limit Machine: {} ms, ",
+                self.time_limit_machine_ms.to_formatted_string(&locale)
+            )?;
+        }
+        if self.tape_size_limit_escalation_u32_blocks > 0 {
+            write!(
+                f,
+                "Tape Limit Escalation up to: {} cells, ",
+                (self.tape_size_limit_escalation_u32_blocks as u64 * 32)
+                    .to_formatted_string(&locale)
+            )?;
+        }
+        if self.tape_memory_budget_bytes() > 0 {
+            write!(
+                f,
+                "Tape Memory Budget: {} bytes, ",
+                self.tape_memory_budget_bytes().to_formatted_string(&locale)
+            )?;
+        }
+        if self.start_from_id > 0 {
+            write!(
+                f,
+                "Start Id: {}, ",
+                self.start_from_id.to_formatted_string(&locale)
+            )?;
+        }
+        if self.counts_only {
+            write!(f, "Counts Only, ")?;
+        }
+        if self.core_usage != CoreUsage::default() {
+            write!(f, "Core Usage: {}, ", self.core_usage)?;
+        }
+        if self.num_threads > 0 {
+            write!(f, "Threads: {}, ", self.num_threads)?;
+        }
+        if self.transition_order != TransitionOrder::default() {
+            write!(f, "Transition Order: {}, ", self.transition_order)?;
+        }
+        if !self.stop_conditions.is_empty() {
+            write!(f, "Stop at: {}, ", self.stop_conditions)?;
+        }
         writeln!(
             f,
             "Limit Machines: {}, File Id Range: {}",
@@ -0,0 +1,330 @@
+//! Configuration file (TOML) for the values of [Config], so long running experiments can be reproduced and
+//! shared. \
+//! All keys are optional and named like the [ConfigBuilder] functions, a missing key keeps the default of
+//! [Config::new_default]. Unknown keys are an error, so a typo does not silently run with the default.
+//! [ConfigFile::builder] returns a builder with the values of the file, so values set on the builder afterwards,
+//! e.g. from the command line (`--config <file>`), override the file. [ConfigFile::from_config] writes the values
+//! of a config, e.g. next to the result of a run.
+//! # Example
+//! ```toml
+//! n_states = 5
+//! step_limit_decider_cycler = 1500
+//! step_limit_decider_bouncer = 20000
+//! step_limit_decider_halt = 50000000
+//! tape_size_limit_cells = 20000
+//! write_html_file = false
+//! stop_time_secs = 3600
+//! ```
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, ConfigBuilder, CoreUsage, IdNormalized, StepBig, StepSmall},
+    stop_conditions::StopConditions,
+};
+
+/// Values of a configuration file, see [module documentation](self).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub n_states: Option<usize>,
+    pub step_limit_decider_halt: Option<StepBig>,
+    pub step_limit_decider_cycler: Option<StepSmall>,
+    pub step_limit_decider_bouncer: Option<StepSmall>,
+    pub step_limit_decider_rep_wl: Option<StepSmall>,
+    pub step_limit_decider_translated_cycler: Option<StepSmall>,
+    pub step_limit_decider_backward_reasoning: Option<StepSmall>,
+    pub step_limit_decider_ctl: Option<StepSmall>,
+    pub step_limit_decider_counter: Option<StepSmall>,
+    pub step_limit_decider_bouncer_proof: Option<StepSmall>,
+    pub step_budget_chain: Option<StepBig>,
+    pub time_limit_machine_ms: Option<u64>,
+    pub macro_block_size: Option<usize>,
+    pub decider_hints: Option<bool>,
+    pub check_invariants: Option<bool>,
+    pub compare_halt_steps: Option<bool>,
+    pub decider_retry_step_limit_max: Option<StepBig>,
+    pub tape_size_limit_cells: Option<u32>,
+    pub tape_size_limit_escalation_cells: Option<u32>,
+    pub tape_memory_budget_bytes: Option<u64>,
+    pub machine_limit: Option<u64>,
+    pub start_from_id: Option<IdNormalized>,
+    pub batch_size: Option<usize>,
+    pub enumerator_full_batch_size_request: Option<usize>,
+    pub enumerator_reduced_batch_size_request: Option<usize>,
+    pub enumerator_lookahead_steps: Option<usize>,
+    pub enumerator_tnf_step_limit: Option<StepBig>,
+    pub limit_machines_decided: Option<usize>,
+    pub limit_machines_undecided: Option<usize>,
+    pub high_steps_threshold: Option<StepBig>,
+    pub limit_machines_high_steps: Option<usize>,
+    pub counts_only: Option<bool>,
+    /// Core usage as text, e.g. 'single', '6' or '75%', see [CoreUsage::try_from].
+    #[serde(with = "core_usage_text")]
+    pub core_usage: Option<CoreUsage>,
+    pub cpu_utilization: Option<usize>,
+    pub num_threads: Option<usize>,
+    pub pipeline_queue_depth: Option<usize>,
+    pub use_local_time: Option<bool>,
+    pub write_event_log: Option<bool>,
+    pub write_verdict_log: Option<bool>,
+    pub write_results_sqlite: Option<bool>,
+    pub progress_quiet: Option<bool>,
+    pub dashboard: Option<bool>,
+    pub stats_snapshot_interval: Option<usize>,
+    pub checkpoint_interval: Option<usize>,
+    pub resume_from: Option<PathBuf>,
+    /// See [StopConditions::num_undecided](crate::stop_conditions::StopConditions::num_undecided).
+    pub stop_undecided: Option<u64>,
+    /// See [StopConditions::halt_steps](crate::stop_conditions::StopConditions::halt_steps).
+    pub stop_halt_steps: Option<StepBig>,
+    /// See [StopConditions::duration](crate::stop_conditions::StopConditions::duration).
+    pub stop_time_secs: Option<u64>,
+    pub write_html_file: Option<bool>,
+    pub write_html_file_undecided: Option<bool>,
+    pub write_html_step_start: Option<StepBig>,
+    pub write_html_line_limit: Option<u32>,
+    pub write_html_tape_shifted_64_bit: Option<bool>,
+}
+
+impl ConfigFile {
+    pub fn read(file_path: impl AsRef<Path>) -> io::Result<Self> {
+        let file_path = file_path.as_ref();
+        let text = fs::read_to_string(file_path)?;
+        ::toml::from_str(&text).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Config file {}: {e}", file_path.display()),
+            )
+        })
+    }
+
+    pub fn write(&self, file_path: impl AsRef<Path>) -> io::Result<()> {
+        let text = ::toml::to_string(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        fs::write(file_path, text)
+    }
+
+    /// All values of the config, so the file reproduces it.
+    pub fn from_config(config: &Config) -> Self {
+        let stop_conditions = config.stop_conditions();
+        Self {
+            n_states: Some(config.n_states()),
+            step_limit_decider_halt: Some(config.step_limit_decider_halt()),
+            step_limit_decider_cycler: Some(config.step_limit_decider_cycler()),
+            step_limit_decider_bouncer: Some(config.step_limit_decider_bouncer()),
+            step_limit_decider_rep_wl: Some(config.step_limit_decider_rep_wl()),
+            step_limit_decider_translated_cycler: Some(
+                config.step_limit_decider_translated_cycler(),
+            ),
+            step_limit_decider_backward_reasoning: Some(
+                config.step_limit_decider_backward_reasoning(),
+            ),
+            step_limit_decider_ctl: Some(config.step_limit_decider_ctl()),
+            step_limit_decider_counter: Some(config.step_limit_decider_counter()),
+            step_limit_decider_bouncer_proof: Some(config.step_limit_decider_bouncer_proof()),
+            step_budget_chain: Some(config.step_budget_chain()),
+            time_limit_machine_ms: Some(config.time_limit_machine_ms()),
+            macro_block_size: Some(config.macro_block_size()),
+            decider_hints: Some(config.decider_hints()),
+            check_invariants: Some(config.check_invariants()),
+            compare_halt_steps: Some(config.compare_halt_steps()),
+            decider_retry_step_limit_max: Some(config.decider_retry_step_limit_max()),
+            tape_size_limit_cells: Some(config.tape_size_limit_cells()),
+            tape_size_limit_escalation_cells: Some(
+                config.tape_size_limit_escalation_u32_blocks() * 32,
+            ),
+            tape_memory_budget_bytes: Some(config.tape_memory_budget_bytes()),
+            machine_limit: Some(config.machines_limit()),
+            start_from_id: Some(config.start_from_id()),
+            batch_size: Some(config.batch_size()),
+            enumerator_full_batch_size_request: Some(config.enumerator_full_batch_size_request()),
+            enumerator_reduced_batch_size_request: Some(
+                config.enumerator_reduced_batch_size_request(),
+            ),
+            enumerator_lookahead_steps: Some(config.enumerator_lookahead_steps()),
+            enumerator_tnf_step_limit: Some(config.enumerator_tnf_step_limit()),
+            limit_machines_decided: Some(config.limit_machines_decided()),
+            limit_machines_undecided: Some(config.limit_machines_undecided()),
+            high_steps_threshold: Some(config.high_steps_threshold()),
+            limit_machines_high_steps: Some(config.limit_machines_high_steps()),
+            counts_only: Some(config.counts_only()),
+            core_usage: Some(config.core_usage()),
+            cpu_utilization: Some(config.cpu_utilization_percent()),
+            num_threads: Some(config.num_threads()),
+            pipeline_queue_depth: Some(config.pipeline_queue_depth()),
+            use_local_time: Some(config.use_local_time()),
+            write_event_log: Some(config.write_event_log()),
+            write_verdict_log: Some(config.write_verdict_log()),
+            write_results_sqlite: Some(config.write_results_sqlite()),
+            progress_quiet: Some(config.progress_quiet()),
+            dashboard: Some(config.dashboard()),
+            stats_snapshot_interval: Some(config.stats_snapshot_interval()),
+            checkpoint_interval: Some(config.checkpoint_interval()),
+            resume_from: config.resume_from().map(Path::to_path_buf),
+            stop_undecided: Some(stop_conditions.num_undecided),
+            stop_halt_steps: Some(stop_conditions.halt_steps),
+            stop_time_secs: stop_conditions.duration.map(|d| d.as_secs()),
+            write_html_file: Some(config.write_html_file()),
+            write_html_file_undecided: Some(config.write_html_file_undecided()),
+            write_html_step_start: Some(config.write_html_step_start()),
+            write_html_line_limit: Some(config.write_html_line_limit()),
+            write_html_tape_shifted_64_bit: Some(config.write_html_tape_shifted_64_bit()),
+        }
+    }
+
+    /// Builder for n_states with the values of the file set. Values set on the builder afterwards override the
+    /// file values. n_states of the file is not used, so it can be overridden too.
+    pub fn builder(&self, n_states: usize) -> ConfigBuilder {
+        let mut builder = Config::builder(n_states);
+        // sets the builder value for each key of the file
+        macro_rules! apply {
+            ($($key:ident),* $(,)?) => {
+                $(
+                    if let Some(value) = self.$key.clone() {
+                        builder = builder.$key(value);
+                    }
+                )*
+            };
+        }
+        apply!(
+            step_limit_decider_halt,
+            step_limit_decider_cycler,
+            step_limit_decider_bouncer,
+            step_limit_decider_rep_wl,
+            step_limit_decider_translated_cycler,
+            step_limit_decider_backward_reasoning,
+            step_limit_decider_ctl,
+            step_limit_decider_counter,
+            step_limit_decider_bouncer_proof,
+            step_budget_chain,
+            time_limit_machine_ms,
+            macro_block_size,
+            decider_hints,
+            check_invariants,
+            compare_halt_steps,
+            decider_retry_step_limit_max,
+            tape_size_limit_cells,
+            tape_size_limit_escalation_cells,
+            tape_memory_budget_bytes,
+            machine_limit,
+            start_from_id,
+            batch_size,
+            enumerator_full_batch_size_request,
+            enumerator_reduced_batch_size_request,
+            enumerator_lookahead_steps,
+            enumerator_tnf_step_limit,
+            limit_machines_decided,
+            limit_machines_undecided,
+            high_steps_threshold,
+            limit_machines_high_steps,
+            counts_only,
+            core_usage,
+            cpu_utilization,
+            num_threads,
+            pipeline_queue_depth,
+            use_local_time,
+            write_event_log,
+            write_verdict_log,
+            write_results_sqlite,
+            progress_quiet,
+            dashboard,
+            stats_snapshot_interval,
+            checkpoint_interval,
+            resume_from,
+            write_html_file,
+            write_html_file_undecided,
+            write_html_step_start,
+            write_html_line_limit,
+            write_html_tape_shifted_64_bit,
+        );
+        if self.stop_undecided.is_some()
+            || self.stop_halt_steps.is_some()
+            || self.stop_time_secs.is_some()
+        {
+            builder = builder.stop_conditions(StopConditions {
+                num_undecided: self.stop_undecided.unwrap_or_default(),
+                halt_steps: self.stop_halt_steps.unwrap_or_default(),
+                duration: self.stop_time_secs.map(Duration::from_secs),
+            });
+        }
+
+        builder
+    }
+}
+
+/// Reads and writes the [CoreUsage] as text, so the file does not depend on the feature 'serde'.
+mod core_usage_text {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::config::CoreUsage;
+
+    pub fn serialize<S: Serializer>(
+        core_usage: &Option<CoreUsage>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match core_usage {
+            Some(core_usage) => serializer.serialize_some(&core_usage.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<CoreUsage>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| CoreUsage::try_from(text.as_str()).map_err(D::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_file_round_trip() {
+        let text = "n_states = 4\nstep_limit_decider_cycler = 1_500\ntape_size_limit_cells = 640\n\
+                    stop_time_secs = 60\ncore_usage = \"single-enumerator\"\n";
+        let config_file: ConfigFile = ::toml::from_str(text).unwrap();
+        let config = config_file.builder(4).build();
+        assert_eq!(config.step_limit_decider_cycler(), 1500);
+        assert_eq!(config.tape_size_limit_cells(), 640);
+        assert_eq!(
+            config.core_usage(),
+            CoreUsage::SingleCoreEnumeratorMultiCoreDecider
+        );
+        assert_eq!(
+            config.stop_conditions().duration,
+            Some(Duration::from_secs(60))
+        );
+        // not in the file
+        assert_eq!(
+            config.step_limit_decider_halt(),
+            Config::new_default(4).step_limit_decider_halt()
+        );
+        // builder overrides the file
+        let config_override = config_file.builder(4).step_limit_decider_cycler(99).build();
+        assert_eq!(config_override.step_limit_decider_cycler(), 99);
+
+        let file_path = std::env::temp_dir().join("bb_challenge_config_file_round_trip.toml");
+        ConfigFile::from_config(&config).write(&file_path).unwrap();
+        let config_read = Config::from_file(&file_path).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+        assert_eq!(
+            ConfigFile::from_config(&config_read),
+            ConfigFile::from_config(&config)
+        );
+
+        assert!(::toml::from_str::<ConfigFile>("step_limit_cycler = 1").is_err());
+        let percent: ConfigFile = ::toml::from_str("core_usage = \"75%\"").unwrap();
+        assert_eq!(percent.core_usage, Some(CoreUsage::Percent(75)));
+        assert!(::toml::from_str::<ConfigFile>("core_usage = \"many\"").is_err());
+    }
+}
@@ -0,0 +1,447 @@
+//! Live terminal dashboard for long runs, enabled with [Config::dashboard] and the feature 'tui'. \
+//! The decider threads send a [DashboardEvent] at the start and the end of each batch into a channel.
+//! A separate UI thread collects the events in a [DashboardState] and redraws the screen in place about
+//! twice a second: the throughput of each thread with its current batch, the undecided machines after each
+//! decider of the chain and the memory usage of the process. The decider threads never wait for the UI. \
+//! Without the feature 'tui' no dashboard is started and the [DashboardSender] of the engine does nothing.
+//! # Example
+//! ```text
+//! BB4 run 00:01:23, batch 212 / 1.000, 1.234.567.890 / 6.975.757.441 machines (17.7%), memory 412 MB
+//!
+//! Thread  Batch  Batches       Machines  Machines/s
+//!      0    213       27    156.234.876   1.882.345
+//!      1    214       26    151.099.412   1.820.474
+//!
+//! Decider                  Machines     Decided   Undecided
+//! Decider Cycler        987.654.321 987.000.000     654.321
+//! Decider Bouncer           654.321     650.000       4.321
+//! ```
+
+use std::{
+    sync::mpsc::{Receiver, RecvTimeoutError, Sender},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use num_format::ToFormattedString;
+
+use crate::{
+    config::{self, Config},
+    decider::{
+        decider_result::{DeciderResultStats, DeciderStats},
+        DeciderConfig,
+    },
+    reporter::format_duration_hhmmss_ms,
+};
+
+/// Time between two redraws of the dashboard.
+pub const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Event sent from a decider thread to the UI thread.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DashboardEvent {
+    /// The decider thread (worker) started to decide the batch.
+    BatchStarted { worker: usize, batch_no: usize },
+    /// The decider thread finished the batch, with the stats of each decider of the chain for this batch.
+    BatchFinished {
+        worker: usize,
+        batch_no: usize,
+        num_machines: u64,
+        duration: Duration,
+        decider_stats: Vec<DeciderStats>,
+    },
+}
+
+/// Sending end of the event channel, cloned into each decider thread. The default sender sends nothing,
+/// which is used if no dashboard is shown. Send errors are ignored, the run does not depend on the UI.
+#[derive(Debug, Clone, Default)]
+pub struct DashboardSender {
+    sender: Option<Sender<DashboardEvent>>,
+}
+
+impl DashboardSender {
+    pub fn batch_started(&self, worker: usize, batch_no: usize) {
+        self.send(DashboardEvent::BatchStarted { worker, batch_no });
+    }
+
+    pub fn batch_finished(
+        &self,
+        worker: usize,
+        batch_no: usize,
+        result: &DeciderResultStats,
+        duration: Duration,
+    ) {
+        if self.sender.is_none() {
+            return;
+        }
+        self.send(DashboardEvent::BatchFinished {
+            worker,
+            batch_no,
+            num_machines: result.num_processed_total(),
+            duration,
+            decider_stats: result.decider_stats().to_vec(),
+        });
+    }
+
+    fn send(&self, event: DashboardEvent) {
+        if let Some(sender) = self.sender.as_ref() {
+            sender.send(event).unwrap_or_default();
+        }
+    }
+}
+
+/// Counters of one decider thread.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct WorkerState {
+    /// Batch currently decided, None while the thread waits.
+    pub batch_no: Option<usize>,
+    pub num_batches: u64,
+    pub num_machines: u64,
+    /// Time spent deciding, so the throughput excludes the waiting time.
+    pub duration: Duration,
+}
+
+impl WorkerState {
+    pub fn machines_per_sec(&self) -> f64 {
+        let secs = self.duration.as_secs_f64();
+        if secs == 0.0 {
+            0.0
+        } else {
+            self.num_machines as f64 / secs
+        }
+    }
+}
+
+/// Everything the dashboard shows, updated by the events. Independent of the terminal, so the output is
+/// available as text lines, see [DashboardState::lines].
+#[derive(Debug, Clone)]
+pub struct DashboardState {
+    n_states: usize,
+    num_batches: usize,
+    num_machines_total: u64,
+    start: Instant,
+    pub workers: Vec<WorkerState>,
+    pub batches_done: usize,
+    pub machines_processed: u64,
+    /// Sum of the stats of each decider of the chain.
+    pub decider_stats: Vec<DeciderStats>,
+}
+
+impl DashboardState {
+    pub fn new(
+        n_states: usize,
+        num_batches: usize,
+        num_machines_total: u64,
+        num_threads: usize,
+    ) -> Self {
+        Self {
+            n_states,
+            num_batches,
+            num_machines_total,
+            start: Instant::now(),
+            workers: vec![WorkerState::default(); num_threads.max(1)],
+            batches_done: 0,
+            machines_processed: 0,
+            decider_stats: Vec::new(),
+        }
+    }
+
+    pub fn apply(&mut self, event: DashboardEvent) {
+        match event {
+            DashboardEvent::BatchStarted { worker, batch_no } => {
+                self.worker_mut(worker).batch_no = Some(batch_no);
+            }
+            DashboardEvent::BatchFinished {
+                worker,
+                num_machines,
+                duration,
+                decider_stats,
+                ..
+            } => {
+                let w = self.worker_mut(worker);
+                w.batch_no = None;
+                w.num_batches += 1;
+                w.num_machines += num_machines;
+                w.duration += duration;
+                self.batches_done += 1;
+                self.machines_processed += num_machines;
+                for stats in decider_stats.iter() {
+                    match self
+                        .decider_stats
+                        .iter_mut()
+                        .find(|s| s.decider_name == stats.decider_name)
+                    {
+                        Some(s) => s.add_self(stats),
+                        None => self.decider_stats.push(stats.clone()),
+                    }
+                }
+            }
+        }
+    }
+
+    fn worker_mut(&mut self, worker: usize) -> &mut WorkerState {
+        if worker >= self.workers.len() {
+            self.workers.resize(worker + 1, WorkerState::default());
+        }
+        &mut self.workers[worker]
+    }
+
+    /// The dashboard as text lines, see [module documentation](self).
+    pub fn lines(&self) -> Vec<String> {
+        let locale = config::user_locale();
+        let percent = if self.num_machines_total == 0 {
+            0.0
+        } else {
+            self.machines_processed as f64 / self.num_machines_total as f64 * 100.0
+        };
+        let memory = match memory_usage_bytes() {
+            Some(bytes) => format!("{} MB", (bytes / 1_000_000).to_formatted_string(&locale)),
+            None => "-".to_string(),
+        };
+        let mut lines = vec![
+            format!(
+                "BB{} run {}, batch {} / {}, {} / {} machines ({percent:.1}%), memory {memory}",
+                self.n_states,
+                format_duration_hhmmss_ms(self.start.elapsed(), false),
+                self.batches_done.to_formatted_string(&locale),
+                self.num_batches.to_formatted_string(&locale),
+                self.machines_processed.to_formatted_string(&locale),
+                self.num_machines_total.to_formatted_string(&locale),
+            ),
+            String::new(),
+            format!(
+                "{:>6} {:>6} {:>8} {:>14} {:>11}",
+                "Thread", "Batch", "Batches", "Machines", "Machines/s"
+            ),
+        ];
+        for (i, w) in self.workers.iter().enumerate() {
+            lines.push(format!(
+                "{i:>6} {:>6} {:>8} {:>14} {:>11}",
+                w.batch_no.map_or("-".to_string(), |b| (b + 1).to_string()),
+                w.num_batches.to_formatted_string(&locale),
+                w.num_machines.to_formatted_string(&locale),
+                (w.machines_per_sec() as u64).to_formatted_string(&locale),
+            ));
+        }
+        lines.push(String::new());
+        lines.push(format!(
+            "{:<20} {:>14} {:>14} {:>14}",
+            "Decider", "Machines", "Decided", "Undecided"
+        ));
+        for s in self.decider_stats.iter() {
+            lines.push(format!(
+                "{:<20} {:>14} {:>14} {:>14}",
+                s.decider_name,
+                s.num_machines.to_formatted_string(&locale),
+                s.num_decided.to_formatted_string(&locale),
+                (s.num_machines - s.num_decided).to_formatted_string(&locale),
+            ));
+        }
+        lines
+    }
+}
+
+/// The running dashboard with its UI thread, see [module documentation](self).
+#[derive(Debug)]
+pub struct Dashboard {
+    sender: Sender<DashboardEvent>,
+    ui_thread: JoinHandle<()>,
+}
+
+impl Dashboard {
+    /// Starts the UI thread, which draws the state to stdout.
+    #[cfg(feature = "tui")]
+    pub fn start(state: DashboardState) -> Self {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let ui_thread = std::thread::spawn(move || run_ui(state, receiver, terminal::draw));
+        Self { sender, ui_thread }
+    }
+
+    /// Starts the dashboard if it is requested in the config of the first decider and the feature 'tui'
+    /// is enabled.
+    pub(crate) fn start_run(
+        decider_configs: &[DeciderConfig],
+        num_batches: usize,
+        num_machines: u64,
+        num_threads: usize,
+    ) -> Option<Self> {
+        let config: &Config = decider_configs.first()?.config();
+        if !config.dashboard() {
+            return None;
+        }
+        #[cfg(feature = "tui")]
+        {
+            Some(Self::start(DashboardState::new(
+                config.n_states(),
+                num_batches,
+                num_machines,
+                num_threads,
+            )))
+        }
+        #[cfg(not(feature = "tui"))]
+        {
+            let _ = (num_batches, num_machines, num_threads);
+            None
+        }
+    }
+
+    pub fn sender(&self) -> DashboardSender {
+        DashboardSender {
+            sender: Some(self.sender.clone()),
+        }
+    }
+
+    /// Draws the final state and ends the UI thread. The dashboard stays on the screen.
+    /// The senders of the decider threads must have been dropped before.
+    pub fn finish(self) {
+        drop(self.sender);
+        self.ui_thread.join().unwrap_or_default();
+    }
+}
+
+/// Loop of the UI thread: applies the events and draws the state every [REFRESH_INTERVAL] until all
+/// senders are dropped. The draw function allows other front ends than the terminal.
+pub fn run_ui(
+    mut state: DashboardState,
+    receiver: Receiver<DashboardEvent>,
+    mut draw: impl FnMut(&DashboardState),
+) {
+    let mut last_draw = Instant::now();
+    draw(&state);
+    loop {
+        match receiver.recv_timeout(REFRESH_INTERVAL) {
+            Ok(event) => state.apply(event),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if last_draw.elapsed() >= REFRESH_INTERVAL {
+            draw(&state);
+            last_draw = Instant::now();
+        }
+    }
+    draw(&state);
+}
+
+/// Resident memory of the process from /proc (Linux only).
+pub fn memory_usage_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kb = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(feature = "tui")]
+mod terminal {
+    use std::io::{self, Write};
+
+    use crossterm::{
+        cursor::{MoveTo, MoveToNextLine},
+        queue,
+        style::Print,
+        terminal::{Clear, ClearType},
+    };
+
+    use super::DashboardState;
+
+    /// Draws the state from the top of the screen, overwriting the previous output.
+    pub(super) fn draw(state: &DashboardState) {
+        // a failed draw (e.g. no terminal) only loses this frame
+        let _ = draw_lines(&state.lines());
+    }
+
+    fn draw_lines(lines: &[String]) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        queue!(out, MoveTo(0, 0))?;
+        for line in lines.iter() {
+            queue!(
+                out,
+                Print(line),
+                Clear(ClearType::UntilNewLine),
+                MoveToNextLine(1)
+            )?;
+        }
+        queue!(out, Clear(ClearType::FromCursorDown))?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_provider::{
+            enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            DataProvider,
+        },
+        decider::{decider_engine, DeciderStandard},
+    };
+
+    #[test]
+    fn dashboard_state_from_events() {
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .batch_size(2_000)
+            .build();
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let num_batches = data_provider.num_batches();
+        let num_machines = data_provider.num_machines_to_process();
+        let decider_configs = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Bouncer128.decider_config(&config),
+        ];
+        let result = decider_engine::batch_run_decider_chain_data_provider_single_thread_reporting(
+            &decider_configs,
+            data_provider,
+            None,
+        );
+
+        // replay the run as two workers
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let dashboard_sender = DashboardSender {
+            sender: Some(sender),
+        };
+        dashboard_sender.batch_started(1, 0);
+        dashboard_sender.batch_finished(1, 0, &result, Duration::from_secs(2));
+        dashboard_sender.batch_started(0, 1);
+        drop(dashboard_sender);
+        let mut draws = 0;
+        let mut state = DashboardState::new(3, num_batches, num_machines, 2);
+        run_ui(state.clone(), receiver, |s| {
+            draws += 1;
+            state = s.clone();
+        });
+        assert!(draws >= 2);
+        assert_eq!(state.batches_done, 1);
+        assert_eq!(state.machines_processed, result.num_processed_total());
+        assert_eq!(state.workers[0].batch_no, Some(1));
+        assert_eq!(state.workers[1].batch_no, None);
+        assert_eq!(
+            state.workers[1].machines_per_sec(),
+            result.num_processed_total() as f64 / 2.0
+        );
+        assert_eq!(state.decider_stats, result.decider_stats());
+        let undecided = state.decider_stats[1].num_machines - state.decider_stats[1].num_decided;
+        assert_eq!(undecided, result.num_undecided());
+
+        let lines = state.lines();
+        assert!(lines[0].starts_with("BB3 run "));
+        assert!(lines[0].contains(&format!("batch 1 / {num_batches}")));
+        assert!(lines.iter().any(|l| l.starts_with("Decider Cycler")));
+    }
+
+    #[test]
+    fn dashboard_not_started_without_config() {
+        let config = Config::new_default(3);
+        let decider_configs = [DeciderStandard::Cycler.decider_config(&config)];
+        assert!(Dashboard::start_run(&decider_configs, 1, 1, 1).is_none());
+        // the default sender ignores the events
+        DashboardSender::default().batch_started(0, 0);
+    }
+}
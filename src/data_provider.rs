@@ -1,16 +1,30 @@
 // TODO Doc ; Write a data provider which returns the machines in batches, e.g. enumerator, file reader
+#[cfg(all(feature = "mmap", unix))]
+pub mod bb_file_mmap;
 pub mod bb_file_reader;
 pub mod bb_file_shrink;
+pub mod bb_seed_file;
+pub mod bb_undecided_index;
+pub mod bbformat;
+pub mod data_provider_composite;
 pub mod enumerator;
 pub mod enumerator_binary;
+pub mod enumerator_generic;
 pub mod enumerator_tnf;
+pub mod machine_spool;
+pub mod text_file;
 // pub mod enumerator_binary_reverse;
 
+pub use bb_seed_file::BBChallengeSeedFileProvider;
+
 use std::fmt::Display;
 
 use crate::decider::decider_result::{EndReason, PreDeciderCount};
 use crate::machine_binary::MachineId;
-use crate::{decider::pre_decider::PreDeciderRun, machine_info::MachineInfo};
+use crate::{
+    decider::pre_decider::{PreDeciderRun, PreDeciderTimer},
+    machine_info::MachineInfo,
+};
 
 // Returning DataProviderBatch in a box degrades performance.
 pub type ResultDataProvider = Result<DataProviderBatch, Box<DataProviderError>>;
@@ -37,6 +51,12 @@ pub trait DataProvider {
     /// or data reader as less data is stored and moved in memory.
     // TODO possibly not required because of option, but may be clearer for developer
     fn requires_pre_decider_check(&self) -> PreDeciderRun;
+
+    /// Continues with this batch (count starts at 0) on the next call of machine_batch_next, e.g. to resume a run
+    /// from a [Checkpoint](crate::checkpoint::Checkpoint). Returns false if the data provider cannot skip batches.
+    fn skip_to_batch(&mut self, _batch_no: usize) -> bool {
+        false
+    }
 }
 
 pub trait DataProviderThreaded: DataProvider + std::marker::Send {
@@ -56,12 +76,16 @@ pub trait DataProviderThreaded: DataProvider + std::marker::Send {
 pub struct DataProviderBatch {
     /// Current batch no, first batch is 0.
     pub batch_no: usize,
+    /// Number of states of the machines in this batch. 0: n_states of the config.
+    pub n_states: usize,
     /// Machines for Decider
     // TODO this could be a trait MachineWork which may not have an id. This could speed up the enumeration and deciders a tiny bit.
     // Generally, enumeration is not the bottleneck. Expected gain <1%.
     pub machines: Vec<MachineId>,
     /// Info if machines have been eliminated from the full list. For statistics only.
     pub pre_decider_count: Option<PreDeciderCount>,
+    /// Duration of the pre-decider checks in the data provider, this is part of the data provider duration.
+    pub pre_decider_timer: PreDeciderTimer,
     // TODO Possibly not used fully
     /// End reason of this batch. This can be an error or the info that this is the last batch.
     pub end_reason: EndReason,
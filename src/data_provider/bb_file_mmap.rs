@@ -0,0 +1,308 @@
+//! Memory mapped reading of the bb_challenge seed file (feature `mmap`, unix only). \
+//! The file is mapped into memory once and the machine records are read directly from the mapped pages,
+//! without a read buffer, a copy or a system call per batch. Each batch advises the OS to read the pages of
+//! the following batch ahead (madvise WILLNEED), so the IO of fast NVMe storage overlaps with the deciders. \
+//! [BBMmapDataProvider] implements [DataProviderThreaded], all threads share the same mapping and read their
+//! batches directly, so a single reader thread is not the bottleneck of the decider threads.
+
+use std::{fs::File, io, ops::Range, os::fd::AsRawFd, sync::Arc};
+
+use crate::{
+    data_provider::{
+        bb_file_reader::{BBFileReader, Header, BYTES_MACHINE},
+        DataProvider, DataProviderBatch, DataProviderThreaded, ResultDataProvider,
+    },
+    decider::{decider_result::EndReason, pre_decider::PreDeciderRun},
+    machine_binary::MachineId,
+};
+
+/// Read only memory mapping of a whole file. The mapping stays valid after the file is closed.
+#[derive(Debug)]
+struct MmapFile {
+    ptr: *const u8,
+    len: usize,
+}
+
+// SAFETY: The mapping is read only and not changed until it is unmapped in drop.
+unsafe impl Send for MmapFile {}
+unsafe impl Sync for MmapFile {}
+
+impl MmapFile {
+    fn open(file_path: &str) -> io::Result<Self> {
+        let file = File::open(file_path)?;
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Not enough bytes in header",
+            ));
+        }
+        // SAFETY: Maps the whole file read only, the result is checked for MAP_FAILED.
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let mmap = Self {
+            ptr: ptr as *const u8,
+            len,
+        };
+        mmap.advise(0..len, libc::MADV_SEQUENTIAL);
+
+        Ok(mmap)
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        // SAFETY: ptr and len describe the mapping, which lives as long as self.
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    /// Gives the OS a hint on the access of the bytes. Hints only change the performance, so errors are ignored.
+    fn advise(&self, range: Range<usize>, advice: libc::c_int) {
+        // SAFETY: sysconf has no memory safety requirements.
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as usize;
+        // madvise requires a page aligned start
+        let start = range.start.min(self.len) / page_size * page_size;
+        let end = range.end.min(self.len);
+        if start < end {
+            // SAFETY: The range lies within the mapping.
+            unsafe {
+                libc::madvise(
+                    self.ptr.add(start) as *mut libc::c_void,
+                    end - start,
+                    advice,
+                );
+            }
+        }
+    }
+}
+
+impl Drop for MmapFile {
+    fn drop(&mut self) {
+        // SAFETY: The mapping was created in open and is not used after this.
+        unsafe {
+            libc::munmap(self.ptr as *mut libc::c_void, self.len);
+        }
+    }
+}
+
+/// Memory mapped bb_challenge seed file, see [module documentation](self). Clones share the mapping.
+#[derive(Debug, Clone)]
+pub struct BBFileMmap {
+    mmap: Arc<MmapFile>,
+    header: Header,
+}
+
+impl BBFileMmap {
+    pub fn try_new(file_path: &str) -> io::Result<Self> {
+        let mmap = MmapFile::open(file_path)?;
+        let bytes = mmap.as_slice();
+        let header = Header::from_bytes(&bytes[..bytes.len().min(BYTES_MACHINE)])?;
+        if (bytes.len() as u64) < (header.num_undecided_machines + 1) * BYTES_MACHINE as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "{file_path}: Not enough bytes for {} machines",
+                    header.num_undecided_machines
+                ),
+            ));
+        }
+
+        Ok(Self {
+            mmap: Arc::new(mmap),
+            header,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn num_machines(&self) -> u64 {
+        self.header.num_undecided_machines
+    }
+
+    /// The 30 bytes of the machine in the file format, without copying. The id starts with 0.
+    pub fn record(&self, id: u64) -> &[u8; BYTES_MACHINE] {
+        assert!(id < self.num_machines(), "Machine id {id} not in file");
+        let pos = Self::file_pos(id);
+        self.mmap.as_slice()[pos..pos + BYTES_MACHINE]
+            .try_into()
+            .unwrap()
+    }
+
+    pub fn machine(&self, id: u64) -> MachineId {
+        MachineId::new(id, BBFileReader::machine_from_file_data(self.record(id)))
+    }
+
+    /// Returns the machines of the ids. These must be within the file.
+    pub fn machines(&self, ids: Range<u64>) -> Vec<MachineId> {
+        ids.map(|id| self.machine(id)).collect()
+    }
+
+    /// Advises the OS to read the records of these machines ahead.
+    pub fn prefetch(&self, ids: Range<u64>) {
+        let ids = ids.start.min(self.num_machines())..ids.end.min(self.num_machines());
+        self.mmap.advise(
+            Self::file_pos(ids.start)..Self::file_pos(ids.end),
+            libc::MADV_WILLNEED,
+        );
+    }
+
+    fn file_pos(id: u64) -> usize {
+        (id as usize + 1) * BYTES_MACHINE
+    }
+}
+
+/// Data provider for the memory mapped bb_challenge seed file, see [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct BBMmapDataProvider {
+    file: BBFileMmap,
+    batch_no: usize,
+    batch_size: usize,
+    id_start: u64,
+    id_end: u64,
+}
+
+impl BBMmapDataProvider {
+    /// Maps the file and returns the machines of the id range (all machines if None) in batches of batch_size.
+    pub fn try_new(
+        file_path: &str,
+        batch_size: usize,
+        id_range: Option<Range<u64>>,
+    ) -> io::Result<Self> {
+        let file = BBFileMmap::try_new(file_path)?;
+        let num_machines = file.num_machines();
+        let id_range = id_range.unwrap_or(0..num_machines);
+        let id_end = id_range.end.min(num_machines);
+        let id_start = id_range.start.min(id_end);
+        // reduce batch size to actually available machines
+        let batch_size = (id_end - id_start).min(batch_size as u64).max(1) as usize;
+        file.prefetch(id_start..id_start + batch_size as u64);
+
+        Ok(Self {
+            file,
+            batch_no: 0,
+            batch_size,
+            id_start,
+            id_end,
+        })
+    }
+
+    pub fn file(&self) -> &BBFileMmap {
+        &self.file
+    }
+
+    fn ids_in_batch(&self, batch_no: usize) -> Range<u64> {
+        let start = (self.id_start + batch_no as u64 * self.batch_size as u64).min(self.id_end);
+        start..(start + self.batch_size as u64).min(self.id_end)
+    }
+}
+
+impl DataProvider for BBMmapDataProvider {
+    fn name(&self) -> &str {
+        "BB Challenge File Mmap"
+    }
+
+    fn machine_batch_next(&mut self) -> ResultDataProvider {
+        let batch = self.batch_no(self.batch_no);
+        Ok(batch)
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    fn num_batches(&self) -> usize {
+        self.num_machines_to_process()
+            .div_ceil(self.batch_size as u64) as usize
+    }
+
+    fn num_machines_to_process(&self) -> u64 {
+        self.id_end - self.id_start
+    }
+
+    fn requires_pre_decider_check(&self) -> PreDeciderRun {
+        PreDeciderRun::RunNormalForward
+    }
+
+    fn skip_to_batch(&mut self, batch_no: usize) -> bool {
+        self.batch_no = batch_no;
+        true
+    }
+}
+
+impl DataProviderThreaded for BBMmapDataProvider {
+    fn new_from_data_provider(&self) -> Self {
+        self.clone()
+    }
+
+    fn batch_no(&mut self, batch_no: usize) -> DataProviderBatch {
+        let ids = self.ids_in_batch(batch_no);
+        // the next batch is read by the OS while this batch is decided
+        self.file.prefetch(self.ids_in_batch(batch_no + 1));
+        let mut batch = DataProviderBatch::new(batch_no);
+        if ids.end == self.id_end {
+            batch.end_reason = EndReason::IsLastBatch;
+        }
+        batch.machines = self.file.machines(ids);
+        self.batch_no = batch_no + 1;
+
+        batch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_provider::bb_file_reader::write_seed_file;
+
+    #[test]
+    fn mmap_data_provider_same_as_file_reader() {
+        let dir = std::env::temp_dir().join("bb_challenge_mmap_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("seed.bin");
+        write_seed_file(
+            &file_path,
+            &[
+                "1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA",
+                "1RB0RC_1LC1RA_1RD1LB_0LE---_1LA0RE",
+                "1RB---_0RC1LB_1LD0RE_1LA1LC_0LB0RA",
+                "1RB1RE_1LC1LD_1RA0LB_0RB---_1RC0RA",
+                "1RB0LD_1LC1RD_1LA1LC_---0RE_1RA0RB",
+            ],
+        );
+        let file_path = file_path.to_str().unwrap();
+        let mut reader = BBFileReader::try_new(file_path).unwrap();
+        let expected = reader.read_machine_range(1, 4).unwrap();
+
+        let mut data_provider = BBMmapDataProvider::try_new(file_path, 3, Some(1..10)).unwrap();
+        assert_eq!(data_provider.num_machines_to_process(), 4);
+        assert_eq!(data_provider.num_batches(), 2);
+        let mut machines = Vec::new();
+        loop {
+            let batch = data_provider.machine_batch_next().unwrap();
+            machines.extend(batch.machines);
+            if batch.end_reason == EndReason::IsLastBatch {
+                break;
+            }
+        }
+        // threads access the batches directly
+        let batch = data_provider.new_from_data_provider().batch_no(1);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(machines, expected);
+        assert_eq!(batch.machines[..], expected[3..]);
+        assert_eq!(
+            machines[0].to_standard_tm_text_format(),
+            "1RB0RC_1LC1RA_1RD1LB_0LE---_1LA0RE"
+        );
+    }
+}
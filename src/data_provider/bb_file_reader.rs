@@ -15,12 +15,11 @@ use crate::{
         DeciderConfig,
     },
     machine_binary::{
-        MachineBinary, MachineId, TransitionTableBinaryArray1D, TRANSITION_TABLE_BINARY_DEFAULT,
+        MachineBinary, MachineId, TransitionTableBinaryArray1D, BB_CHALLENGE_RECORD_SIZE,
     },
-    transition_binary::TransitionBinary,
 };
 
-const BYTES_MACHINE: usize = 30;
+pub(crate) const BYTES_MACHINE: usize = BB_CHALLENGE_RECORD_SIZE;
 const BATCH_SIZE: usize = 100_000;
 
 /// <https://bbchallenge.org/method#format>
@@ -33,6 +32,29 @@ pub struct Header {
     pub is_sorted: bool,
 }
 
+impl Header {
+    /// Reads the header from the first bytes of the file.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        if bytes.len() < 13 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Not enough bytes in header",
+            ));
+        };
+
+        Ok(Self {
+            num_undecided_machines_exceed_47m_steps: u32::from_be_bytes(
+                bytes[0..4].try_into().unwrap(),
+            ) as u64,
+            num_undecided_machines_exceed_12k_cells: u32::from_be_bytes(
+                bytes[4..8].try_into().unwrap(),
+            ) as u64,
+            num_undecided_machines: u32::from_be_bytes(bytes[8..12].try_into().unwrap()) as u64,
+            is_sorted: bytes[12] == 1,
+        })
+    }
+}
+
 // TODO use config.machine_limit if set
 #[derive(Debug)]
 pub struct BBFileReader {
@@ -62,25 +84,7 @@ impl BBFileReader {
     fn read_header(reader: &mut BufReader<File>) -> io::Result<Header> {
         let mut buffer: [u8; BYTES_MACHINE] = [0; BYTES_MACHINE];
         let bytes_read = reader.read(&mut buffer)?;
-        if bytes_read < 13 {
-            return Err(io::Error::new(
-                io::ErrorKind::UnexpectedEof,
-                "Not enough bytes in header",
-            ));
-        };
-
-        let header = Header {
-            num_undecided_machines_exceed_47m_steps: u32::from_be_bytes(
-                buffer[0..4].try_into().unwrap(),
-            ) as u64,
-            num_undecided_machines_exceed_12k_cells: u32::from_be_bytes(
-                buffer[4..8].try_into().unwrap(),
-            ) as u64,
-            num_undecided_machines: u32::from_be_bytes(buffer[8..12].try_into().unwrap()) as u64,
-            is_sorted: buffer[12] == 1,
-        };
-
-        Ok(header)
+        Header::from_bytes(&buffer[..bytes_read])
     }
 
     /// get single machine
@@ -88,15 +92,8 @@ impl BBFileReader {
     pub fn read_machine_single(machine_id: u64, file_path: &str) -> io::Result<MachineId> {
         // path of file in current folder
         // let mut file = BBFileReader::new(file_path)?;
-        let mut file;
-        let r = BBFileReader::try_new(file_path);
-        match r {
-            Ok(reader) => file = reader,
-            Err(e) => {
-                eprintln!("File not found: {file_path}");
-                return Err(e);
-            }
-        }
+        let mut file = BBFileReader::try_new(file_path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{file_path}: {e}")))?;
 
         // println!("\nHeader: {:?}", file.header);
         assert!(machine_id < file.header.num_undecided_machines);
@@ -118,8 +115,26 @@ impl BBFileReader {
         }
     }
 
-    // TODO multiple machines, ids in a vector
-    // pub fn read_machines(machine_ids: &[u64]) -> io::Result<Vec<TM>> {}
+    /// Reads the machines with the given ids, e.g. from the undecided index. The ids do not need to be sorted.
+    pub fn read_machines(&mut self, machine_ids: &[u64]) -> io::Result<Vec<MachineId>> {
+        let mut machines = Vec::with_capacity(machine_ids.len());
+        let mut buffer: [u8; BYTES_MACHINE] = [0; BYTES_MACHINE];
+        for &id in machine_ids {
+            if id >= self.header.num_undecided_machines {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Machine id {id} not in file ({} machines)",
+                        self.header.num_undecided_machines
+                    ),
+                ));
+            }
+            self.reader.seek(SeekFrom::Start(Self::file_pos(id)))?;
+            self.reader.read_exact(&mut buffer)?;
+            machines.push(MachineId::new(id, Self::machine_from_file_data(&buffer)));
+        }
+        Ok(machines)
+    }
 
     // id starts with 0
     // returns machines up to count
@@ -155,11 +170,7 @@ impl BBFileReader {
                     // };
                 }
             }
-            // This assumes the file is BB5, otherwise use new_eval_n_states
-            let machine = MachineBinary::new_with_n_states(
-                Self::file_data_array_into_transitions(&buffer),
-                5,
-            );
+            let machine = Self::machine_from_file_data(&buffer);
             machines.push(MachineId::new(first_id + machines.len() as u64, machine));
         }
         Ok(machines)
@@ -171,27 +182,13 @@ impl BBFileReader {
 
     /// Converts the transitions in the file format into transitions of the library.
     pub fn file_data_array_into_transitions(array: &[u8]) -> TransitionTableBinaryArray1D {
-        // assert!(array.len() == 30);
-        let mut transitions = TRANSITION_TABLE_BINARY_DEFAULT;
-        for i in 0..5 {
-            let p = i * 6;
-            transitions[(i + 1) * 2] =
-                TransitionBinary::try_new(array[p..p + 3].try_into().unwrap())
-                    .expect("File Data Error");
-            transitions[(i + 1) * 2 + 1] =
-                TransitionBinary::try_new(array[p + 3..p + 6].try_into().unwrap())
-                    .expect("File Data Error");
-        }
-
-        transitions
+        Self::machine_from_file_data(array).transitions
     }
 
     /// Creates a new machine from the bb_challenge file, one machine as array.
-    fn machine_from_file_data(array: &[u8]) -> MachineBinary {
-        // This assumes the file is BB5, otherwise use new_eval_n_states
-        // assert!(array.len() == 30);
-        let transitions = Self::file_data_array_into_transitions(array);
-        MachineBinary::new_with_n_states(transitions, 5)
+    pub(crate) fn machine_from_file_data(array: &[u8]) -> MachineBinary {
+        let record = array[..BYTES_MACHINE].try_into().unwrap();
+        MachineBinary::from_bb_challenge_record(record).expect("File Data Error")
     }
 
     pub fn header(&self) -> &Header {
@@ -425,10 +422,22 @@ pub fn run_deciders_bb_challenge_file(
     run_decider_chain_data_provider_single(decider_config, bb_file_reader, multi_core)
 }
 
+/// Writes the machines in the file format of the seed file.
+#[cfg(test)]
+pub(crate) fn write_seed_file(file_path: &std::path::Path, machines: &[&str]) {
+    let mut bytes = vec![0; BYTES_MACHINE];
+    bytes[8..12].copy_from_slice(&(machines.len() as u32).to_be_bytes());
+    for tm in machines {
+        let machine = MachineBinary::try_from_standard_tm_text_format(tm).unwrap();
+        bytes.extend(machine.to_bb_challenge_record());
+    }
+    std::fs::write(file_path, bytes).unwrap();
+}
+
 #[cfg(test)]
 #[allow(non_snake_case)]
 mod tests {
-    use crate::config::CONFIG_TOML;
+    use crate::{config::CONFIG_TOML, transition_binary::TransitionBinary};
 
     use super::*;
 
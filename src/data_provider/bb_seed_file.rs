@@ -0,0 +1,199 @@
+//! Data provider for the seed database of bbchallenge (all_5_states_undecided_machines_with_global_header),
+//! see <https://bbchallenge.org/method#format>. \
+//! Each machine is a 30-byte record after the 30-byte header. The machines are returned with their index in
+//! the file as id, so results can be matched with the published database. \
+//! The provider can be used by multiple threads, each thread reads its batches directly from the shared file.
+
+use std::{
+    io,
+    ops::Range,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    data_provider::{
+        bb_file_reader::{BBFileReader, Header, BYTES_MACHINE},
+        DataProvider, DataProviderBatch, DataProviderError, DataProviderThreaded,
+        ResultDataProvider,
+    },
+    decider::{decider_result::EndReason, pre_decider::PreDeciderRun},
+};
+
+/// Data provider for the bbchallenge seed database, see [module documentation](self). Clones share the file.
+#[derive(Debug, Clone)]
+pub struct BBChallengeSeedFileProvider {
+    reader: Arc<Mutex<BBFileReader>>,
+    header: Header,
+    batch_no: usize,
+    batch_size: usize,
+    id_start: u64,
+    id_end: u64,
+}
+
+impl BBChallengeSeedFileProvider {
+    /// Opens the file and returns the machines of the id range (all machines if None) in batches of batch_size.
+    /// Returns an error if the file holds less machines than its header states.
+    pub fn try_new(
+        file_path: &str,
+        batch_size: usize,
+        id_range: Option<Range<u64>>,
+    ) -> io::Result<Self> {
+        let reader = BBFileReader::try_new(file_path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{file_path}: {e}")))?;
+        let header = *reader.header();
+        let file_len = std::fs::metadata(file_path)?.len();
+        if file_len < (header.num_undecided_machines + 1) * BYTES_MACHINE as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "{file_path}: Not enough bytes for {} machines",
+                    header.num_undecided_machines
+                ),
+            ));
+        }
+        let id_range = id_range.unwrap_or(0..header.num_undecided_machines);
+        let id_end = id_range.end.min(header.num_undecided_machines);
+        let id_start = id_range.start.min(id_end);
+        // reduce batch size to actually available machines
+        let batch_size = (id_end - id_start).min(batch_size as u64).max(1) as usize;
+
+        Ok(Self {
+            reader: Arc::new(Mutex::new(reader)),
+            header,
+            batch_no: 0,
+            batch_size,
+            id_start,
+            id_end,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    fn ids_in_batch(&self, batch_no: usize) -> Range<u64> {
+        let start = (self.id_start + batch_no as u64 * self.batch_size as u64).min(self.id_end);
+        start..(start + self.batch_size as u64).min(self.id_end)
+    }
+
+    fn read_batch(&mut self, batch_no: usize) -> io::Result<DataProviderBatch> {
+        let ids = self.ids_in_batch(batch_no);
+        let mut batch = DataProviderBatch::new(batch_no);
+        if ids.end == self.id_end {
+            batch.end_reason = EndReason::IsLastBatch;
+        }
+        // a thread which panicked while reading did not leave the reader in an invalid state
+        let mut reader = self.reader.lock().unwrap_or_else(|e| e.into_inner());
+        batch.machines = reader.read_machine_range(ids.start, (ids.end - ids.start) as usize)?;
+        self.batch_no = batch_no + 1;
+
+        Ok(batch)
+    }
+}
+
+impl DataProvider for BBChallengeSeedFileProvider {
+    fn name(&self) -> &str {
+        "BB Challenge Seed File"
+    }
+
+    fn machine_batch_next(&mut self) -> ResultDataProvider {
+        let batch_no = self.batch_no;
+        self.read_batch(batch_no).map_err(|e| {
+            let mut batch = DataProviderBatch::new(batch_no);
+            batch.end_reason = EndReason::Error(0, e.to_string());
+            Box::new(DataProviderError {
+                name: self.name().to_string(),
+                batch: Some(batch),
+                msg: e.to_string(),
+                ..Default::default()
+            })
+        })
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    fn num_batches(&self) -> usize {
+        self.num_machines_to_process()
+            .div_ceil(self.batch_size as u64) as usize
+    }
+
+    fn num_machines_to_process(&self) -> u64 {
+        self.id_end - self.id_start
+    }
+
+    fn requires_pre_decider_check(&self) -> PreDeciderRun {
+        PreDeciderRun::RunNormalForward
+    }
+
+    fn skip_to_batch(&mut self, batch_no: usize) -> bool {
+        self.batch_no = batch_no;
+        true
+    }
+}
+
+impl DataProviderThreaded for BBChallengeSeedFileProvider {
+    fn new_from_data_provider(&self) -> Self {
+        self.clone()
+    }
+
+    /// A read error ends the batch with [EndReason::Error].
+    fn batch_no(&mut self, batch_no: usize) -> DataProviderBatch {
+        self.read_batch(batch_no).unwrap_or_else(|e| {
+            let mut batch = DataProviderBatch::new(batch_no);
+            batch.end_reason = EndReason::Error(0, e.to_string());
+            batch
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_provider::bb_file_reader::write_seed_file;
+
+    #[test]
+    fn seed_file_provider_ids_are_file_index() {
+        let dir = std::env::temp_dir().join("bb_challenge_seed_file_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("seed.bin");
+        let machines = [
+            "1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA",
+            "1RB0RC_1LC1RA_1RD1LB_0LE---_1LA0RE",
+            "1RB---_0RC1LB_1LD0RE_1LA1LC_0LB0RA",
+            "1RB1RE_1LC1LD_1RA0LB_0RB---_1RC0RA",
+            "1RB0LD_1LC1RD_1LA1LC_---0RE_1RA0RB",
+        ];
+        write_seed_file(&file_path, &machines);
+        let file_path_str = file_path.to_str().unwrap();
+
+        let mut data_provider =
+            BBChallengeSeedFileProvider::try_new(file_path_str, 2, Some(1..10)).unwrap();
+        assert_eq!(data_provider.header().num_undecided_machines, 5);
+        assert_eq!(data_provider.num_machines_to_process(), 4);
+        assert_eq!(data_provider.num_batches(), 2);
+        let mut ids = Vec::new();
+        loop {
+            let batch = data_provider.machine_batch_next().unwrap();
+            for m in batch.machines.iter() {
+                assert_eq!(m.to_standard_tm_text_format(), machines[m.id() as usize]);
+                ids.push(m.id());
+            }
+            if batch.end_reason == EndReason::IsLastBatch {
+                break;
+            }
+        }
+        assert_eq!(ids, [1, 2, 3, 4]);
+        // threads access the batches directly
+        let batch = data_provider.new_from_data_provider().batch_no(1);
+        assert_eq!(batch.machines[0].id(), 3);
+
+        // file shorter than the header states
+        let bytes = std::fs::read(&file_path).unwrap();
+        std::fs::write(&file_path, &bytes[..bytes.len() - 1]).unwrap();
+        let r = BBChallengeSeedFileProvider::try_new(file_path_str, 2, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(r.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
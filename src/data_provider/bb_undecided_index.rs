@@ -0,0 +1,424 @@
+//! Bulk verification of the bb_challenge undecided index with the decider chain of this crate. \
+//! The undecided index is a list of machine ids (u32 big-endian) into the bb_challenge seed file
+//! 'all_5_states_undecided_machines_with_global_header' of all BB5 machines which were not decided by bbchallenge.
+//! Both files need to be downloaded from bbchallenge.org, the paths are set in the config.toml.
+//!
+//! [verify_undecided_index] runs the decider chain exactly on these machines and reports which machines this
+//! crate decides, with the decider, the status and the proof [Certificate] of the non-halting machines,
+//! if the decider supports one. The report can be printed as Markdown table for discussion and the
+//! certificates exported for submission with [UndecidedIndexReport::export_certificates].
+//!
+//! [write_undecided_index_from_result] exports the machines left undecided by a run in the same format, so the
+//! results can be used with the bbchallenge tooling. The file has no header, as the files of bbchallenge.
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    certificate::{verify_certificate, Certificate, CycleCertificate},
+    certificate_export::{certificate_type, CertificateExporter},
+    config::{Config, CONFIG_TOML},
+    data_provider::bb_file_reader::BBFileReader,
+    decider::{
+        decider_bouncer_proof::DeciderBouncerProof,
+        decider_ctl::DeciderCTL,
+        decider_result::{BatchData, DeciderResultStats},
+        pre_decider::PreDeciderRun,
+        Decider, DeciderConfig,
+    },
+    machine_binary::MachineId,
+    status::{MachineStatus, NonHaltReason},
+};
+
+/// Number of machines read from the seed file and decided at once.
+const BATCH_SIZE: usize = 10_000;
+const BYTES_ID: usize = 4;
+
+/// A machine of the undecided index which was decided by this crate.
+#[derive(Debug, Clone)]
+pub struct IndexMachineDecided {
+    pub machine: MachineId,
+    pub status: MachineStatus,
+    /// Name of the decider which decided the machine.
+    pub decider_name: &'static str,
+    /// Verified proof certificate, None for halting machines and deciders without certificates.
+    pub certificate: Option<Certificate>,
+}
+
+/// Result of [verify_undecided_index].
+#[derive(Debug, Default)]
+pub struct UndecidedIndexReport {
+    /// Number of machines in the undecided index.
+    pub num_machines: u64,
+    /// Machines decided by this crate, in order of the index.
+    pub machines_decided: Vec<IndexMachineDecided>,
+    pub num_undecided: u64,
+    pub duration: Duration,
+}
+
+impl UndecidedIndexReport {
+    /// Number of decided machines with a certificate.
+    pub fn num_certificates(&self) -> usize {
+        self.machines_decided
+            .iter()
+            .filter(|m| m.certificate.is_some())
+            .count()
+    }
+
+    /// Decided machines as Markdown table. The certificate column names the certificate type,
+    /// the certificates themselves are written with [Self::export_certificates].
+    pub fn to_markdown_table(&self) -> String {
+        let mut s = String::from(
+            "| Id | Machine | Decider | Status | Certificate |\n|---:|---|---|---|---|\n",
+        );
+        for m in self.machines_decided.iter() {
+            s.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                m.machine.id(),
+                m.machine.to_standard_tm_text_format(),
+                m.decider_name,
+                m.status,
+                m.certificate.as_ref().map_or("-", certificate_type)
+            ));
+        }
+        s
+    }
+
+    /// Writes the certificates of the decided machines as S-expressions, see [CertificateExporter].
+    /// Returns the number of certificates written.
+    pub fn export_certificates(&self, exporter: &mut CertificateExporter) -> io::Result<usize> {
+        for certificate in self
+            .machines_decided
+            .iter()
+            .filter_map(|m| m.certificate.as_ref())
+        {
+            exporter.export(certificate)?;
+        }
+        exporter.flush()?;
+
+        Ok(self.num_certificates())
+    }
+}
+
+impl Display for UndecidedIndexReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "bb_challenge undecided index: {} machines, decided: {} ({} with certificate), still undecided: {}, duration: {:?}",
+            self.num_machines,
+            self.machines_decided.len(),
+            self.num_certificates(),
+            self.num_undecided,
+            self.duration
+        )?;
+        if !self.machines_decided.is_empty() {
+            writeln!(f)?;
+            write!(f, "{}", self.to_markdown_table())?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the undecided index, a list of u32 big-endian machine ids.
+pub fn read_undecided_index(file_path: &str) -> io::Result<Vec<u64>> {
+    let mut reader = BufReader::new(File::open(file_path)?);
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    if bytes.len() % BYTES_ID != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Undecided index {file_path} is not a list of u32 ids."),
+        ));
+    }
+    let ids = bytes
+        .chunks_exact(BYTES_ID)
+        .map(|b| u32::from_be_bytes(b.try_into().unwrap()) as u64)
+        .collect();
+
+    Ok(ids)
+}
+
+/// Writes the machine ids as undecided index, sorted and without duplicates, in u32 big-endian.
+/// Returns the number of ids written or an error if an id does not fit into u32.
+pub fn write_undecided_index(file_path: &str, ids: &[u64]) -> io::Result<usize> {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    if let Some(id) = ids.last().filter(|id| **id > u32::MAX as u64) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Machine id {id} does not fit into the undecided index."),
+        ));
+    }
+    let mut writer = BufWriter::new(File::create(file_path)?);
+    for id in ids.iter() {
+        writer.write_all(&(*id as u32).to_be_bytes())?;
+    }
+    writer.flush()?;
+
+    Ok(ids.len())
+}
+
+/// Writes the undecided machines of the result as undecided index, see [write_undecided_index]. \
+/// The ids are only indices into the seed file if the machines were read from it, e.g. with
+/// [BBChallengeSeedFileProvider](crate::data_provider::BBChallengeSeedFileProvider).
+/// All undecided machines need to be recorded, see [Config::limit_machines_undecided].
+pub fn write_undecided_index_from_result(
+    result: &DeciderResultStats,
+    file_path: &str,
+) -> io::Result<usize> {
+    let Some(machines) = result.machines_undecided() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The result has no recorded undecided machines.",
+        ));
+    };
+    if (machines.len() as u64) < result.num_undecided() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Only {} of {} undecided machines are recorded, increase limit_machines_undecided.",
+                machines.len(),
+                result.num_undecided()
+            ),
+        ));
+    }
+    let ids: Vec<u64> = machines.iter().map(|m| m.id()).collect();
+    write_undecided_index(file_path, &ids)
+}
+
+/// Runs [verify_undecided_index] with the file paths of the config.toml.
+pub fn verify_undecided_index_toml_paths(
+    decider_configs: &[DeciderConfig],
+) -> io::Result<UndecidedIndexReport> {
+    verify_undecided_index(
+        decider_configs,
+        CONFIG_TOML.bb_challenge_undecided_index_path(),
+        CONFIG_TOML.bb_challenge_filename_path(),
+    )
+}
+
+/// Runs the decider chain on all machines of the bb_challenge undecided index and
+/// reports the machines decided by this crate. Each decider runs on the machines left undecided by the
+/// previous deciders. Unlike the normal run, all decided machines are recorded regardless of limit_machines_decided.
+/// # Arguments
+/// - index_path: the undecided index (u32 big-endian ids)
+/// - seed_file_path: the bb_challenge seed file 'all_5_states_undecided_machines_with_global_header'
+pub fn verify_undecided_index(
+    decider_configs: &[DeciderConfig],
+    index_path: &str,
+    seed_file_path: &str,
+) -> io::Result<UndecidedIndexReport> {
+    assert!(!decider_configs.is_empty(), "No decider given");
+    let start = Instant::now();
+    let ids = read_undecided_index(index_path)?;
+    let mut reader = BBFileReader::try_new(seed_file_path)?;
+    // record all decided machines of a batch
+    let configs: Vec<Config> = decider_configs
+        .iter()
+        .map(|dc| {
+            Config::builder_from_config(dc.config())
+                .limit_machines_decided(BATCH_SIZE)
+                .build()
+        })
+        .collect();
+
+    let mut report = UndecidedIndexReport {
+        num_machines: ids.len() as u64,
+        ..Default::default()
+    };
+    let num_batches = ids.len().div_ceil(BATCH_SIZE);
+    for (batch_no, batch_ids) in ids.chunks(BATCH_SIZE).enumerate() {
+        let mut machines = reader.read_machines(batch_ids)?;
+        for (i, (dc, config)) in decider_configs.iter().zip(configs.iter()).enumerate() {
+            if machines.is_empty() {
+                break;
+            }
+            let mut batch_data = BatchData {
+                machines: &machines,
+                result_decided: DeciderResultStats::new(config),
+                machines_decided: Default::default(),
+                machines_undecided: Default::default(),
+                batch_no,
+                num_batches,
+                decider_id: dc.decider_id(),
+                config,
+                run_predecider: if i == 0 {
+                    PreDeciderRun::RunNormalForward
+                } else {
+                    PreDeciderRun::DoNotRun
+                },
+                step_budget: 0,
+                steps_used: Vec::new(),
+                hints: Vec::new(),
+            };
+            if let Err(e) = dc.f_decider()(&mut batch_data) {
+                return Err(io::Error::other(format!(
+                    "Decider {} failed: {e}",
+                    dc.decider_id().name
+                )));
+            }
+            let decider_name = dc.decider_id().name;
+            for (machine, status) in batch_data
+                .machines_decided
+                .machines
+                .iter()
+                .zip(batch_data.machines_decided.states.iter())
+            {
+                report.machines_decided.push(IndexMachineDecided {
+                    machine: *machine,
+                    status: *status,
+                    decider_name,
+                    certificate: certificate_of_decided(machine, status, config),
+                });
+            }
+            machines = batch_data.machines_undecided.machines;
+        }
+        report.num_undecided += machines.len() as u64;
+    }
+    report.duration = start.elapsed();
+
+    Ok(report)
+}
+
+/// Proof certificate of a machine decided as non-halting, verified independent of the decider.
+/// The bouncer proof and the closed tape language are run again, as the batch run does not keep the certificates.
+fn certificate_of_decided(
+    machine: &MachineId,
+    status: &MachineStatus,
+    config: &Config,
+) -> Option<Certificate> {
+    let certificate = match status {
+        MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(_, _))
+        | MachineStatus::DecidedNonHalt(NonHaltReason::TranslatedCycler(_, _, _)) => {
+            Certificate::Cycle(CycleCertificate::from_status(machine, status)?)
+        }
+        MachineStatus::DecidedNonHalt(NonHaltReason::BouncerProof(_, _)) => {
+            let mut decider = DeciderBouncerProof::new(config);
+            decider.decide_machine(machine);
+            decider.proof_certificate()?
+        }
+        MachineStatus::DecidedNonHalt(NonHaltReason::ClosedTapeLanguage(_)) => {
+            let mut decider = DeciderCTL::new(config);
+            decider.decide_machine(machine);
+            decider.proof_certificate()?
+        }
+        _ => return None,
+    };
+
+    verify_certificate(&certificate).ok().map(|_| certificate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decider::DeciderStandard, machine_binary::NotableMachineBinary,
+        transition_binary::TransitionBinary,
+    };
+
+    /// Writes a seed file in bb_challenge format with the given machines.
+    fn write_seed_file(path: &std::path::Path, machines: &[&str]) {
+        let mut bytes = vec![0u8; 30];
+        bytes[8..12].copy_from_slice(&(machines.len() as u32).to_be_bytes());
+        for m in machines {
+            for tr in m.split('_').flat_map(|s| [&s[0..3], &s[3..6]]) {
+                if tr == "---" {
+                    bytes.extend_from_slice(&[0, 0, 0]);
+                } else {
+                    let t = TransitionBinary::try_from(tr).unwrap();
+                    let dir = if t.is_dir_left() { 1 } else { 0 };
+                    bytes.extend_from_slice(&[t.symbol() as u8, dir, t.state() as u8]);
+                }
+            }
+        }
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn verify_undecided_index_reports_decided_machines() {
+        let dir = std::env::temp_dir().join("bb_challenge_undecided_index_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let seed_path = dir.join("seed");
+        let index_path = dir.join("index");
+        let bb5_max = NotableMachineBinary::BB5Max
+            .machine_id()
+            .to_standard_tm_text_format();
+        // machine 1 halts after 105 steps, machine 3 is a cycler
+        write_seed_file(
+            &seed_path,
+            &[
+                &bb5_max,
+                "1RB1LC_0LB1LA_1RD1LB_1RE0RD_0RA---",
+                &bb5_max,
+                "0RB0LC_1LA1RB_1RD---_0LA1RE_0RC1LD",
+            ],
+        );
+        let index: Vec<u8> = [2u32, 1, 3]
+            .iter()
+            .flat_map(|id| id.to_be_bytes())
+            .collect();
+        std::fs::write(&index_path, index).unwrap();
+
+        // BB5 Max is undecided with these limits
+        let config = Config::builder(5)
+            .step_limit_decider_cycler(1_000)
+            .step_limit_decider_halt(1_000)
+            .build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let report = verify_undecided_index(
+            &deciders,
+            index_path.to_str().unwrap(),
+            seed_path.to_str().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(report.num_machines, 3);
+        assert_eq!(report.num_undecided, 1);
+        assert_eq!(report.machines_decided.len(), 2);
+        let decided = &report.machines_decided[0];
+        assert_eq!(decided.machine.id(), 1);
+        assert!(matches!(
+            decided.status,
+            MachineStatus::DecidedHalt(105) | MachineStatus::DecidedHaltField(105, _)
+        ));
+        assert!(decided.certificate.is_none());
+        let cycler = &report.machines_decided[1];
+        assert_eq!(cycler.machine.id(), 3);
+        assert!(matches!(
+            cycler.certificate,
+            Some(Certificate::Cycle(CycleCertificate { period: 3, .. }))
+        ));
+        assert_eq!(report.num_certificates(), 1);
+        let table = report.to_markdown_table();
+        assert!(table.contains("| 1 | 1RB1LC_0LB1LA_1RD1LB_1RE0RD_0RA--- |"));
+        assert!(table.contains("| cycler |"));
+
+        let mut exporter = CertificateExporter::new(dir.join("certificates"));
+        assert_eq!(report.export_certificates(&mut exporter).unwrap(), 1);
+        let sexpr = std::fs::read_to_string(exporter.file_path("cycler")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(sexpr.contains("(machine \"0RB0LC_1LA1RB_1RD---_0LA1RE_0RC1LD\")"));
+    }
+
+    #[test]
+    fn write_undecided_index_sorted() {
+        let dir = std::env::temp_dir().join("bb_challenge_write_undecided_index_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let index_path = dir.join("index");
+        let index_path = index_path.to_str().unwrap();
+        assert_eq!(write_undecided_index(index_path, &[5, 1, 3, 1]).unwrap(), 3);
+        let ids = read_undecided_index(index_path).unwrap();
+        let r = write_undecided_index(index_path, &[1, u32::MAX as u64 + 1]);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(ids, [1, 3, 5]);
+        assert_eq!(r.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,574 @@
+//! Self-describing binary file format for machine dumps, e.g. undecided machines of a run. \
+//! The file carries its own n_states and machine count, so it can be read by later runs and versions
+//! and is validated on load. All numbers are little endian.
+//!
+//! Header (16 bytes):
+//! | Bytes | Content |
+//! |-------|---------|
+//! | 0-3   | magic 'BBTM' |
+//! | 4-5   | format version (u16), currently 1 |
+//! | 6     | n_states (u8) of all machines in the file |
+//! | 7     | flags (u8), bit 0: records contain the machine id |
+//! | 8-15  | number of machines (u64) |
+//!
+//! Each machine record is the machine id (u64, only with the id flag) followed by one byte per field
+//! A0, A1, B0, ... (2 * n_states bytes):
+//! - 0xFF: undefined '---'
+//! - bit 0: symbol to write, bit 1: direction (1 = right), bits 2-5: next state (1 = A, 0 = halt 'Z').
+//!
+//! This does not depend on the internal bit layout of [TransitionBinary], which may change.
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::{
+    config::MAX_STATES,
+    data_provider::{DataProvider, DataProviderBatch, DataProviderError, ResultDataProvider},
+    decider::{
+        decider_result::{BatchData, EndReason},
+        decider_result_worker::ResultWorker,
+        pre_decider::PreDeciderRun,
+    },
+    format::standard_tm::{self, TRANSITION_UNDEFINED},
+    machine_binary::{MachineBinary, MachineId},
+    output_layout::create_dir,
+    transition_binary::TransitionBinary,
+};
+
+pub const BBFORMAT_MAGIC: &[u8; 4] = b"BBTM";
+pub const BBFORMAT_VERSION: u16 = 1;
+pub const BBFORMAT_FILE_EXTENSION: &str = "bbtm";
+pub const BBFORMAT_HEADER_SIZE: usize = 16;
+const FLAG_HAS_ID: u8 = 0b0000_0001;
+const FIELD_UNDEFINED: u8 = 0xFF;
+const BATCH_SIZE: usize = 10_000;
+
+/// Header of a machine file, see [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BBFormatHeader {
+    pub version: u16,
+    pub n_states: usize,
+    /// The records contain the machine id.
+    pub has_id: bool,
+    pub num_machines: u64,
+}
+
+impl BBFormatHeader {
+    pub fn new(n_states: usize, has_id: bool) -> Self {
+        Self {
+            version: BBFORMAT_VERSION,
+            n_states,
+            has_id,
+            num_machines: 0,
+        }
+    }
+
+    /// Size of one machine record in bytes.
+    pub fn record_size(&self) -> usize {
+        if self.has_id {
+            8 + self.n_states * 2
+        } else {
+            self.n_states * 2
+        }
+    }
+
+    /// Expected file size in bytes.
+    pub fn file_size(&self) -> u64 {
+        BBFORMAT_HEADER_SIZE as u64 + self.num_machines * self.record_size() as u64
+    }
+
+    pub fn to_bytes(&self) -> [u8; BBFORMAT_HEADER_SIZE] {
+        let mut bytes = [0; BBFORMAT_HEADER_SIZE];
+        bytes[0..4].copy_from_slice(BBFORMAT_MAGIC);
+        bytes[4..6].copy_from_slice(&self.version.to_le_bytes());
+        bytes[6] = self.n_states as u8;
+        bytes[7] = if self.has_id { FLAG_HAS_ID } else { 0 };
+        bytes[8..16].copy_from_slice(&self.num_machines.to_le_bytes());
+        bytes
+    }
+
+    /// Reads and validates the header.
+    pub fn try_from_bytes(bytes: &[u8; BBFORMAT_HEADER_SIZE]) -> io::Result<Self> {
+        if &bytes[0..4] != BBFORMAT_MAGIC {
+            return Err(invalid_data(
+                "not a machine file, magic 'BBTM' missing".to_string(),
+            ));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != BBFORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported format version {version}, expected {BBFORMAT_VERSION}"
+            )));
+        }
+        let n_states = bytes[6] as usize;
+        if !(1..=MAX_STATES).contains(&n_states) {
+            return Err(invalid_data(format!(
+                "n_states {n_states} not supported, max {MAX_STATES}"
+            )));
+        }
+        if bytes[7] & !FLAG_HAS_ID != 0 {
+            return Err(invalid_data(format!("unknown flags {:#04x}", bytes[7])));
+        }
+        Ok(Self {
+            version,
+            n_states,
+            has_id: bytes[7] & FLAG_HAS_ID != 0,
+            num_machines: u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+        })
+    }
+}
+
+/// Writes machines of one n_states into a machine file. The number of machines in the header is
+/// updated in [BBFormatWriter::finish], which must be called.
+#[derive(Debug)]
+pub struct BBFormatWriter {
+    writer: BufWriter<File>,
+    header: BBFormatHeader,
+    record: Vec<u8>,
+}
+
+impl BBFormatWriter {
+    /// Creates a new file, an existing file is overwritten.
+    pub fn create(file_path: impl AsRef<Path>, n_states: usize, has_id: bool) -> io::Result<Self> {
+        if !(1..=MAX_STATES).contains(&n_states) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("n_states {n_states} not supported, max {MAX_STATES}"),
+            ));
+        }
+        let header = BBFormatHeader::new(n_states, has_id);
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        writer.write_all(&header.to_bytes())?;
+        Ok(Self {
+            writer,
+            header,
+            record: Vec::with_capacity(header.record_size()),
+        })
+    }
+
+    /// Opens the file to append machines, the file is created if it does not exist. \
+    /// An existing file must have the same n_states, id flag and a size matching its header.
+    pub fn append(file_path: impl AsRef<Path>, n_states: usize, has_id: bool) -> io::Result<Self> {
+        let file_path = file_path.as_ref();
+        if !file_path.exists() {
+            return Self::create(file_path, n_states, has_id);
+        }
+        let mut file = OpenOptions::new().read(true).write(true).open(file_path)?;
+        let header = read_header(&mut file, file_path)?;
+        if header.n_states != n_states || header.has_id != has_id {
+            return Err(invalid_data(format!(
+                "{}: file has n_states {} and id {}, cannot append n_states {n_states} and id {has_id}",
+                file_path.display(),
+                header.n_states,
+                header.has_id
+            )));
+        }
+        file.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            header,
+            record: Vec::with_capacity(header.record_size()),
+        })
+    }
+
+    pub fn header(&self) -> &BBFormatHeader {
+        &self.header
+    }
+
+    /// Writes the machine, which must have the n_states of the file.
+    pub fn write_machine(&mut self, machine: &MachineId) -> io::Result<()> {
+        if machine.n_states() != self.header.n_states {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "machine {} has n_states {}, file has {}",
+                    machine.to_standard_tm_text_format(),
+                    machine.n_states(),
+                    self.header.n_states
+                ),
+            ));
+        }
+        self.record.clear();
+        if self.header.has_id {
+            self.record.extend_from_slice(&machine.id().to_le_bytes());
+        }
+        for array_id in 2..self.header.n_states * 2 + 2 {
+            self.record.push(encode_transition(
+                &machine.machine().transition_array_id(array_id),
+            ));
+        }
+        self.writer.write_all(&self.record)?;
+        self.header.num_machines += 1;
+        Ok(())
+    }
+
+    pub fn write_machines(&mut self, machines: &[MachineId]) -> io::Result<()> {
+        for machine in machines.iter() {
+            self.write_machine(machine)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the number of machines into the header and flushes the file. Returns the number of machines.
+    pub fn finish(mut self) -> io::Result<u64> {
+        self.writer.flush()?;
+        let file = self.writer.get_mut();
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&self.header.to_bytes())?;
+        file.flush()?;
+        Ok(self.header.num_machines)
+    }
+}
+
+/// Reads the machines of a machine file in batches.
+#[derive(Debug)]
+pub struct BBFormatReader {
+    reader: BufReader<File>,
+    header: BBFormatHeader,
+    num_read: u64,
+}
+
+impl BBFormatReader {
+    /// Opens the file and validates the header and the file size.
+    pub fn open(file_path: impl AsRef<Path>) -> io::Result<Self> {
+        let file_path = file_path.as_ref();
+        let mut file = File::open(file_path)?;
+        let header = read_header(&mut file, file_path)?;
+        Ok(Self {
+            reader: BufReader::new(file),
+            header,
+            num_read: 0,
+        })
+    }
+
+    pub fn header(&self) -> &BBFormatHeader {
+        &self.header
+    }
+
+    /// Reads up to max_machines machines, an empty Vec if all machines have been read.
+    pub fn read_machines(&mut self, max_machines: usize) -> io::Result<Vec<MachineId>> {
+        let n = (self.header.num_machines - self.num_read).min(max_machines as u64) as usize;
+        let record_size = self.header.record_size();
+        let mut buffer = vec![0; n * record_size];
+        self.reader.read_exact(&mut buffer)?;
+        let mut machines = Vec::with_capacity(n);
+        for record in buffer.chunks_exact(record_size) {
+            machines.push(self.decode_record(record)?);
+        }
+        self.num_read += n as u64;
+        Ok(machines)
+    }
+
+    fn decode_record(&self, record: &[u8]) -> io::Result<MachineId> {
+        let (id, fields) = if self.header.has_id {
+            let (id, fields) = record.split_at(8);
+            (Some(u64::from_le_bytes(id.try_into().unwrap())), fields)
+        } else {
+            (None, record)
+        };
+        let mut machine = MachineBinary::new_default(self.header.n_states);
+        for (i, field) in fields.iter().enumerate() {
+            machine.transitions[i + 2] = decode_transition(*field, self.header.n_states)?;
+        }
+        Ok(MachineId::new_option_id(id, machine))
+    }
+}
+
+/// Reads all machines of a machine file.
+pub fn read_bbformat_file(file_path: impl AsRef<Path>) -> io::Result<Vec<MachineId>> {
+    let mut reader = BBFormatReader::open(file_path)?;
+    let num_machines = reader.header().num_machines as usize;
+    reader.read_machines(num_machines)
+}
+
+/// Writes the machines (all with the same n_states) into a new machine file. Returns the number of machines.
+pub fn write_bbformat_file(
+    file_path: impl AsRef<Path>,
+    n_states: usize,
+    machines: &[MachineId],
+) -> io::Result<u64> {
+    let has_id = machines.iter().all(|m| m.has_id());
+    let mut writer = BBFormatWriter::create(file_path, n_states, has_id)?;
+    writer.write_machines(machines)?;
+    writer.finish()
+}
+
+/// Data provider for the machines of a machine file, see [module documentation](self).
+#[derive(Debug)]
+pub struct DataProviderBBFormat {
+    reader: BBFormatReader,
+    batch_no: usize,
+    batch_size: usize,
+    run_pre_decider: PreDeciderRun,
+    name: String,
+}
+
+impl DataProviderBBFormat {
+    pub fn try_new(file_path: impl AsRef<Path>) -> io::Result<Self> {
+        let name = format!("Machine File: {}", file_path.as_ref().display());
+        Ok(Self {
+            reader: BBFormatReader::open(file_path)?,
+            batch_no: 0,
+            batch_size: BATCH_SIZE,
+            run_pre_decider: PreDeciderRun::DoNotRun,
+            name,
+        })
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Runs the pre-decider on the machines, e.g. if the file contains unfiltered machines.
+    pub fn with_pre_decider(mut self, run_pre_decider: PreDeciderRun) -> Self {
+        self.run_pre_decider = run_pre_decider;
+        self
+    }
+}
+
+impl DataProvider for DataProviderBBFormat {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn machine_batch_next(&mut self) -> ResultDataProvider {
+        let mut batch = DataProviderBatch::new(self.batch_no);
+        batch.n_states = self.reader.header().n_states;
+        batch.machines = self.reader.read_machines(self.batch_size).map_err(|e| {
+            Box::new(DataProviderError {
+                name: self.name.clone(),
+                msg: e.to_string(),
+                ..Default::default()
+            })
+        })?;
+        if self.reader.num_read == self.reader.header().num_machines {
+            batch.end_reason = EndReason::IsLastBatch;
+        }
+        self.batch_no += 1;
+
+        Ok(batch)
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    fn num_batches(&self) -> usize {
+        (self.reader.header().num_machines as usize)
+            .div_ceil(self.batch_size)
+            .max(1)
+    }
+
+    fn num_machines_to_process(&self) -> u64 {
+        self.reader.header().num_machines
+    }
+
+    fn requires_pre_decider_check(&self) -> PreDeciderRun {
+        self.run_pre_decider
+    }
+}
+
+/// Serializes the appends of all threads to the undecided machine files.
+static UNDECIDED_FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Result worker which appends the undecided machines of the decider to a machine file per n_states
+/// in the reports directory, e.g. '<time>_undecided_bb5 Cycler.bbtm'. See [DeciderConfig::new_with_worker].
+///
+/// [DeciderConfig::new_with_worker]: crate::decider::DeciderConfig::new_with_worker
+pub fn save_machines_undecided_bbformat(batch_data: &mut BatchData) -> ResultWorker {
+    let machines = &batch_data.machines_undecided.machines;
+    if machines.is_empty() {
+        return Ok(());
+    }
+    let config = batch_data.config;
+    let path = config.output_layout().reports_path(config);
+    create_dir(&path)?;
+    let _lock = UNDECIDED_FILE_LOCK
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let has_id = machines.iter().all(|m| m.has_id());
+    for n_states in 1..=MAX_STATES {
+        let mut writer: Option<BBFormatWriter> = None;
+        for m in machines.iter().filter(|m| m.n_states() == n_states) {
+            if writer.is_none() {
+                let file_path = undecided_file_path(
+                    path.clone(),
+                    &config.creation_time_to_string(),
+                    n_states,
+                    batch_data.decider_id.name,
+                );
+                writer = Some(BBFormatWriter::append(file_path, n_states, has_id)?);
+            }
+            writer.as_mut().unwrap().write_machine(m)?;
+        }
+        if let Some(writer) = writer {
+            writer.finish()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn undecided_file_path(path: PathBuf, time: &str, n_states: usize, decider_name: &str) -> PathBuf {
+    path.join(format!(
+        "{time}_undecided_bb{n_states} {decider_name}.{BBFORMAT_FILE_EXTENSION}"
+    ))
+}
+
+fn read_header(file: &mut File, file_path: &Path) -> io::Result<BBFormatHeader> {
+    let mut bytes = [0; BBFORMAT_HEADER_SIZE];
+    file.read_exact(&mut bytes)
+        .map_err(|e| invalid_data(format!("{}: header: {e}", file_path.display())))?;
+    let header = BBFormatHeader::try_from_bytes(&bytes)
+        .map_err(|e| invalid_data(format!("{}: {e}", file_path.display())))?;
+    let file_size = file.metadata()?.len();
+    if file_size != header.file_size() {
+        return Err(invalid_data(format!(
+            "{}: file size {file_size} does not match {} machines in the header (expected {} bytes)",
+            file_path.display(),
+            header.num_machines,
+            header.file_size()
+        )));
+    }
+    Ok(header)
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn encode_transition(tr: &TransitionBinary) -> u8 {
+    if tr.is_undefined() {
+        return FIELD_UNDEFINED;
+    }
+    tr.symbol() as u8 | (tr.is_dir_right() as u8) << 1 | (tr.state() as u8) << 2
+}
+
+fn decode_transition(field: u8, n_states: usize) -> io::Result<TransitionBinary> {
+    if field == FIELD_UNDEFINED {
+        return TransitionBinary::try_from(TRANSITION_UNDEFINED)
+            .map_err(|e| invalid_data(format!("transition: {e:?}")));
+    }
+    let state = (field >> 2) as usize;
+    if field >> 6 != 0 || state > n_states {
+        return Err(invalid_data(format!(
+            "invalid transition byte {field:#04x}"
+        )));
+    }
+    let text = [
+        b'0' + (field & 1),
+        standard_tm::dir_to_char(field & 2 != 0) as u8,
+        standard_tm::state_to_char(state) as u8,
+    ];
+    TransitionBinary::try_new(text).map_err(|e| invalid_data(format!("transition: {e:?}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_cycler::DeciderCycler,
+            decider_engine::batch_run_decider_chain_data_provider_single_thread_reporting, Decider,
+            DeciderConfig, DECIDER_CYCLER_ID,
+        },
+        output_layout::OutputLayout,
+    };
+
+    #[test]
+    fn bbformat_write_read_append() {
+        let dir = std::env::temp_dir().join("bb_challenge_bbformat_test");
+        create_dir(&dir).unwrap();
+        let path = dir.join("machines.bbtm");
+        let machines: Vec<MachineId> = [
+            "1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA",
+            "1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA",
+            "0RB0LC_1LA1RB_1RD---_0LA1RE_0RC1LD",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, tm)| {
+            MachineId::new(
+                i as u64 * 1000,
+                MachineBinary::try_from_standard_tm_text_format(tm).unwrap(),
+            )
+        })
+        .collect();
+
+        assert_eq!(write_bbformat_file(&path, 5, &machines[0..2]).unwrap(), 2);
+        let mut writer = BBFormatWriter::append(&path, 5, true).unwrap();
+        writer.write_machine(&machines[2]).unwrap();
+        assert_eq!(writer.finish().unwrap(), 3);
+        assert!(BBFormatWriter::append(&path, 4, true).is_err());
+
+        let read = read_bbformat_file(&path).unwrap();
+        assert_eq!(read.len(), 3);
+        for (m, r) in machines.iter().zip(read.iter()) {
+            assert_eq!(r.id(), m.id());
+            assert_eq!(
+                r.to_standard_tm_text_format(),
+                m.to_standard_tm_text_format()
+            );
+        }
+        let mut data_provider = DataProviderBBFormat::try_new(&path)
+            .unwrap()
+            .with_batch_size(2);
+        assert_eq!(data_provider.num_batches(), 2);
+        assert_eq!(
+            data_provider.machine_batch_next().unwrap().machines.len(),
+            2
+        );
+        let batch = data_provider.machine_batch_next().unwrap();
+        assert_eq!(batch.end_reason, EndReason::IsLastBatch);
+        assert_eq!(batch.n_states, 5);
+
+        // truncated file and wrong magic are detected
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        let e = BBFormatReader::open(&path).unwrap_err();
+        assert!(e.to_string().contains("does not match 3 machines"));
+        std::fs::write(&path, b"BBXX0000000000000000").unwrap();
+        let e = read_bbformat_file(&path).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(e.to_string().contains("magic"));
+    }
+
+    #[test]
+    fn bbformat_undecided_worker() {
+        let dir = std::env::temp_dir().join("bb_challenge_bbformat_worker_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .enumerator_reduced_batch_size_request(10_000)
+            .output_layout(OutputLayout::new(&dir))
+            .build();
+        let deciders = [DeciderConfig::new_with_worker(
+            &DECIDER_CYCLER_ID,
+            DeciderCycler::decider_run_batch,
+            save_machines_undecided_bbformat,
+            &config,
+        )];
+        let enumerator = EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &deciders, enumerator, None,
+        );
+        assert!(result.num_undecided() > 0);
+
+        let file_path = undecided_file_path(
+            config.output_layout().reports_path(&config),
+            &config.creation_time_to_string(),
+            3,
+            DECIDER_CYCLER_ID.name,
+        );
+        let machines = read_bbformat_file(&file_path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(machines.len() as u64, result.num_undecided());
+    }
+}
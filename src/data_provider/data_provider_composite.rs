@@ -0,0 +1,168 @@
+//! Data provider which returns the machines of several data providers one after the other,
+//! e.g. the enumerators for BB2 and BB3 in one run. \
+//! Each machine knows its n_states, so the deciders run all sizes and the result (DeciderResultStats)
+//! contains a section per n_states.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     data_provider::{
+//!         data_provider_composite::DataProviderComposite,
+//!         enumerator_binary::{EnumeratorBinary, EnumeratorType},
+//!     },
+//!     decider::{
+//!         decider_engine::batch_run_decider_chain_data_provider_single_thread, DeciderStandard,
+//!     },
+//! };
+//!
+//! let config_2 = Config::builder(2).machine_limit(0).build();
+//! let config_3 = Config::builder(3).machine_limit(0).build();
+//! let data_provider = DataProviderComposite::new(vec![
+//!     Box::new(EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config_2)),
+//!     Box::new(EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config_3)),
+//! ]);
+//! let dc = DeciderStandard::Cycler.decider_config(&config_3);
+//! let result = batch_run_decider_chain_data_provider_single_thread(&[dc], data_provider);
+//! ```
+//! The composite data provider cannot be used with [CoreUsage::MultiCore](crate::config::CoreUsage::MultiCore)
+//! as it does not implement DataProviderThreaded.
+
+use crate::{
+    data_provider::{DataProvider, ResultDataProvider},
+    decider::{decider_result::EndReason, pre_decider::PreDeciderRun},
+};
+
+pub struct DataProviderComposite {
+    data_providers: Vec<Box<dyn DataProvider>>,
+    /// Current data provider.
+    provider_no: usize,
+    /// Batch no over all data providers.
+    batch_no: usize,
+    name: String,
+}
+
+impl DataProviderComposite {
+    pub fn new(data_providers: Vec<Box<dyn DataProvider>>) -> Self {
+        assert!(!data_providers.is_empty(), "No data provider given");
+        let names: Vec<&str> = data_providers.iter().map(|dp| dp.name()).collect();
+        let name = format!("Composite: {}", names.join(", "));
+        Self {
+            data_providers,
+            provider_no: 0,
+            batch_no: 0,
+            name,
+        }
+    }
+}
+
+impl DataProvider for DataProviderComposite {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the next batch of the current data provider. The last batch of a data provider
+    /// switches to the next data provider, only the last batch of the last data provider ends the run.
+    fn machine_batch_next(&mut self) -> ResultDataProvider {
+        let mut data = self.data_providers[self.provider_no].machine_batch_next()?;
+        data.batch_no = self.batch_no;
+        self.batch_no += 1;
+        if data.n_states == 0 {
+            if let Some(m) = data.machines.first() {
+                data.n_states = m.n_states();
+            }
+        }
+        if matches!(
+            data.end_reason,
+            EndReason::IsLastBatch | EndReason::NoMoreData
+        ) && self.provider_no + 1 < self.data_providers.len()
+        {
+            self.provider_no += 1;
+            data.end_reason = EndReason::None;
+        }
+
+        Ok(data)
+    }
+
+    fn batch_size(&self) -> usize {
+        self.data_providers
+            .iter()
+            .map(|dp| dp.batch_size())
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn num_batches(&self) -> usize {
+        self.data_providers.iter().map(|dp| dp.num_batches()).sum()
+    }
+
+    fn num_machines_to_process(&self) -> u64 {
+        self.data_providers
+            .iter()
+            .map(|dp| dp.num_machines_to_process())
+            .sum()
+    }
+
+    /// Returns the most complete pre-decider check required by any data provider.
+    /// Running it on machines which passed the pre-decider already does not change the result.
+    fn requires_pre_decider_check(&self) -> PreDeciderRun {
+        let runs: Vec<PreDeciderRun> = self
+            .data_providers
+            .iter()
+            .map(|dp| dp.requires_pre_decider_check())
+            .collect();
+        if runs.contains(&PreDeciderRun::RunNormalForward) {
+            PreDeciderRun::RunNormalForward
+        } else if runs.contains(&PreDeciderRun::RunStartBRightOnly) {
+            PreDeciderRun::RunStartBRightOnly
+        } else {
+            PreDeciderRun::DoNotRun
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::batch_run_decider_chain_data_provider_single_thread,
+            decider_result::result_max_steps_known, DeciderStandard,
+        },
+    };
+
+    #[test]
+    fn data_provider_composite_result_per_n_states() {
+        let config_2 = Config::builder(2).machine_limit(0).build();
+        let config_3 = Config::builder(3).machine_limit(0).build();
+        let data_provider = DataProviderComposite::new(vec![
+            Box::new(EnumeratorBinary::new(
+                EnumeratorType::EnumeratorReducedForward,
+                &config_3,
+            )),
+            Box::new(EnumeratorBinary::new(
+                EnumeratorType::EnumeratorFullForward,
+                &config_2,
+            )),
+        ]);
+        let num_machines = data_provider.num_machines_to_process();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config_3),
+            DeciderStandard::Hold.decider_config(&config_3),
+        ];
+        let result = batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider);
+
+        assert_eq!(result.end_reason, EndReason::AllMachinesChecked);
+        assert_eq!(result.n_states(), 3);
+        assert_eq!(result.steps_max(), result_max_steps_known(3));
+        assert_eq!(result.num_processed_total(), num_machines);
+        let sections = result.n_states_sections();
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].n_states(), 2);
+        assert_eq!(sections[0].steps_max(), result_max_steps_known(2));
+        assert_eq!(sections[0].num_processed_total(), 9u64.pow(4));
+        let s = result.to_string();
+        assert!(s.contains("Result BB3") && s.contains("Result BB2"));
+    }
+}
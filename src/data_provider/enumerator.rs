@@ -1,3 +1,5 @@
+pub mod addressing;
+
 use crate::{
     config::MAX_STATES, data_provider::DataProvider, decider::decider_result::PreDeciderCount,
     machine_binary::MachineId, transition_binary::TRANSITION_BINARY_HALT,
@@ -0,0 +1,225 @@
+//! Addressing of batches by machine id for the enumerators with calculable ids ([EnumeratorBinary]). \
+//! The enumeration id is a number with base p = 4 * n_states + 1, each field of the transition table being one digit.
+//! The batches split the ids into equal ranges, only the last batch may be shorter:
+//! * first id of batch = batch_no * batch_size + first_id
+//! * batch of id = (id - first_id) / batch_size
+//!
+//! first_id is config.start_from_id rounded down to the first machine of its block of p^2 machines,
+//! as a batch always enumerates all permutations of state A. The batch size is a multiple of p^2.
+//!
+//! This allows to split a run into batch ranges (e.g. for several computers) or to continue a run
+//! from a machine id without enumerating the machines before.
+//! ```
+//! use bb_challenge::{config::Config, data_provider::{enumerator::addressing::BatchAddressing, enumerator_binary::EnumeratorType}};
+//! let config = Config::builder(4).enumerator_full_batch_size_request(10_000).machine_limit(0).build();
+//! let addressing = BatchAddressing::new(EnumeratorType::EnumeratorFullForward, &config).unwrap();
+//! let batch_no = addressing.batch_of_id(1_000_000).unwrap();
+//! assert!(addressing.ids_in_batch(batch_no).contains(&1_000_000));
+//! ```
+//!
+//! [EnumeratorBinary]: crate::data_provider::enumerator_binary::EnumeratorBinary
+
+use std::ops::Range;
+
+use crate::{
+    config::Config,
+    data_provider::{
+        enumerator::{num_turing_machine_permutations, Enumerator},
+        enumerator_binary::{EnumeratorBinary, EnumeratorType},
+    },
+};
+
+/// Id and batch number arithmetic of an enumeration, see [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchAddressing {
+    /// Id of the first machine, batch 0 starts here.
+    first_id: u64,
+    /// Number of machine ids in each batch.
+    batch_size: usize,
+    /// Id after the last machine to enumerate (exclusive).
+    limit_id: u64,
+    /// Number of machines counted for the progress of the run.
+    num_machines: u64,
+}
+
+impl BatchAddressing {
+    /// Addressing of the enumerator type for this config (n_states, start_from_id, machines_limit and batch size). \
+    /// Returns an error if the start id exceeds the number of machines or the enumerator does not support it.
+    pub fn new(enumerator_type: EnumeratorType, config: &Config) -> Result<Self, String> {
+        let n_states = config.n_states();
+        let n_machines = num_turing_machine_permutations(n_states) as u64;
+        let batch_size = EnumeratorBinary::calc_batch_size(
+            config.enumerator_full_batch_size_request(),
+            n_states,
+            n_machines as u128,
+        );
+        let limit = if config.machines_limit() > 0 {
+            config.machines_limit()
+        } else {
+            n_machines
+        };
+
+        match enumerator_type {
+            EnumeratorType::EnumeratorFullForward
+            | EnumeratorType::EnumeratorFullBackward
+            | EnumeratorType::EnumeratorReducedForward => {
+                // Seek to the start id. The enumeration of a batch always runs all permutations of
+                // state A (fields 2 and 3), so the start is the first machine of this block.
+                let permutations_a = (4 * n_states as u64 + 1).pow(2);
+                if config.start_from_id() >= n_machines {
+                    return Err(format!(
+                        "Start id {} exceeds the number of machines {n_machines}.",
+                        config.start_from_id()
+                    ));
+                }
+                let first_id = config.start_from_id() / permutations_a * permutations_a;
+                // the limit counts from the start id
+                let limit_id = first_id.saturating_add(limit).min(n_machines);
+                Ok(Self {
+                    first_id,
+                    batch_size,
+                    limit_id,
+                    num_machines: limit_id - first_id,
+                })
+            }
+            EnumeratorType::EnumeratorReducedBackward => {
+                if config.start_from_id() != 0 {
+                    return Err(
+                        "The EnumeratorReducedBackward does not support a start id.".to_string()
+                    );
+                }
+                // A0 is the highest digit, so A0 = 0RB and 1RB (permutations 2 and 3) are the ids
+                // from the jump to 0RB up to the end of 1RB, each 2n-1 fields below A0.
+                let ids_per_a0 = (4 * n_states as u64 + 1).pow(2 * n_states as u32 - 1);
+                let first_id = 2 * ids_per_a0;
+                let limit_id = first_id.saturating_add(limit).min(4 * ids_per_a0);
+                // a full run also counts the skipped A0 transitions
+                let num_machines = if limit_id == 4 * ids_per_a0 {
+                    n_machines
+                } else {
+                    limit_id - first_id
+                };
+                Ok(Self {
+                    first_id,
+                    batch_size,
+                    limit_id,
+                    num_machines,
+                })
+            }
+        }
+    }
+
+    /// Id of the first machine of batch 0.
+    pub fn first_id(&self) -> u64 {
+        self.first_id
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Id after the last machine to enumerate (exclusive).
+    pub fn limit_id(&self) -> u64 {
+        self.limit_id
+    }
+
+    /// Number of machines to process, which is the number of ids from first_id to limit_id. A full run of the
+    /// EnumeratorReducedBackward also counts the ids of the skipped A0 transitions, so it is the number of all machines.
+    pub fn num_machines(&self) -> u64 {
+        self.num_machines
+    }
+
+    /// Number of machines counted in [Self::num_machines], but not within the ids from first_id to limit_id.
+    pub fn num_ids_not_walked(&self) -> u64 {
+        self.num_machines - (self.limit_id - self.first_id)
+    }
+
+    /// Total number of batches.
+    pub fn num_batches(&self) -> usize {
+        (self.limit_id - self.first_id).div_ceil(self.batch_size as u64) as usize
+    }
+
+    /// Returns the batch number of the machine id or None if the id is not enumerated (before first_id or after the limit).
+    pub fn batch_of_id(&self, id: u64) -> Option<usize> {
+        if id < self.first_id || id >= self.limit_id {
+            return None;
+        }
+        Some(((id - self.first_id) / self.batch_size as u64) as usize)
+    }
+
+    /// Id of the first machine of the batch.
+    pub fn first_id_of_batch(&self, batch_no: usize) -> u64 {
+        batch_no as u64 * self.batch_size as u64 + self.first_id
+    }
+
+    /// Ids of the batch. The range is empty if the batch is beyond the limit.
+    pub fn ids_in_batch(&self, batch_no: usize) -> Range<u64> {
+        let start = self.first_id_of_batch(batch_no).min(self.limit_id);
+        let end = start
+            .saturating_add(self.batch_size as u64)
+            .min(self.limit_id);
+        start..end
+    }
+
+    /// Batches containing the ids, e.g. to run only a part of an enumeration.
+    pub fn batches_of_ids(&self, ids: Range<u64>) -> Range<usize> {
+        let start = ids.start.clamp(self.first_id, self.limit_id);
+        let end = ids.end.clamp(start, self.limit_id);
+        if start == end {
+            let batch_no = ((start - self.first_id) / self.batch_size as u64) as usize;
+            return batch_no..batch_no;
+        }
+        let batch_first = ((start - self.first_id) / self.batch_size as u64) as usize;
+        let batch_last = ((end - 1 - self.first_id) / self.batch_size as u64) as usize;
+        batch_first..batch_last + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_provider::{DataProvider, DataProviderThreaded};
+
+    #[test]
+    fn addressing_ids_match_enumerator_batches() {
+        let config = Config::builder(3)
+            .enumerator_full_batch_size_request(10_000)
+            .start_from_id(1_000_100)
+            .machine_limit(123_456)
+            .build();
+        let addressing =
+            BatchAddressing::new(EnumeratorType::EnumeratorFullForward, &config).unwrap();
+        // rounded down to the block of 13^2 machines
+        assert_eq!(addressing.first_id(), 999_973);
+        assert_eq!(addressing.limit_id(), 999_973 + 123_456);
+
+        let mut enumerator = EnumeratorBinary::new(EnumeratorType::EnumeratorFullForward, &config);
+        assert_eq!(addressing.num_batches(), enumerator.num_batches());
+        assert_eq!(
+            addressing.num_machines(),
+            enumerator.num_machines_to_process()
+        );
+        for batch_no in [0, 5, addressing.num_batches() - 1] {
+            let batch = enumerator.batch_no(batch_no);
+            let ids = addressing.ids_in_batch(batch_no);
+            // the full enumerator returns every id of the batch
+            assert_eq!(batch.machines.len() as u64, ids.end - ids.start);
+            assert_eq!(addressing.batch_of_id(ids.start), Some(batch_no));
+            assert_eq!(addressing.batch_of_id(ids.end - 1), Some(batch_no));
+        }
+        assert_eq!(addressing.batch_of_id(addressing.first_id() - 1), None);
+        assert_eq!(addressing.batch_of_id(addressing.limit_id()), None);
+        assert!(addressing.ids_in_batch(addressing.num_batches()).is_empty());
+
+        let batch_size = addressing.batch_size() as u64;
+        let first_id = addressing.first_id();
+        assert_eq!(
+            addressing.batches_of_ids(first_id + batch_size..first_id + 2 * batch_size + 1),
+            1..3
+        );
+        assert_eq!(
+            addressing.batches_of_ids(0..u64::MAX),
+            0..addressing.num_batches()
+        );
+    }
+}
@@ -22,24 +22,32 @@
 //! into several batches and addressing a batch directly (it is not required to create all the
 //! permutations up to that batch). This is used to parallelize permutation generation and
 //! subsequently the deciders to run in multiple threads.
-// TODO enumerationReducedReverse is broken
+//!
+//! The reduced enumerators only enumerate the machines with A0 = 0RB or 1RB. For the backward enumeration A0 is
+//! the highest digit of the id, so these machines are one contiguous id range starting at the jump to 0RB.
+//! The ids outside this range are counted as not enumerated in the last batch, so the counts of the forward and
+//! backward enumeration are the same.
 
 use crate::{
     config::{Config, NUM_FIELDS},
     data_provider::{
-        enumerator::{machines_for_n_states_1, num_turing_machine_permutations, Enumerator},
+        enumerator::{
+            addressing::BatchAddressing, machines_for_n_states_1, num_turing_machine_permutations,
+            Enumerator,
+        },
         DataProvider, DataProviderBatch, DataProviderThreaded, ResultDataProvider,
     },
     decider::{
         decider_result::{EndReason, PreDeciderCount},
         pre_decider::{
             check_not_all_states_used, check_only_right_direction, check_only_zero_writes,
-            check_simple_start_cycle, count_halt_transitions, PreDeciderRun,
+            check_simple_start_cycle, count_halt_transitions, run_lookahead, LookaheadResult,
+            PreDeciderRun, PreDeciderTimer,
         },
     },
     machine_binary::{MachineBinary, MachineId},
     status::PreDeciderReason,
-    transition_binary::{TransitionBinary, TRANSITIONS_FOR_A0},
+    transition_binary::{TransitionBinary, TransitionOrder, TRANSITIONS_FOR_A0},
 };
 
 // const BATCH_SIZE_REQUEST_SINGLE_THREAD_MAX: usize = 500_000;
@@ -51,8 +59,7 @@ pub enum EnumeratorType {
     EnumeratorFullForward,
     EnumeratorFullBackward,
     EnumeratorReducedForward,
-    EnumeratorReducedBackwardNotWorking,
-    EnumeratorTNF,
+    EnumeratorReducedBackward,
 }
 
 /// This enumerator creates all permutations of transition sets (Turing machine) possible for the given n_states,
@@ -60,11 +67,12 @@ pub enum EnumeratorType {
 /// The transition table is enumerated by permuting all transition permutations for field A0, then A1, then B0 and so on.
 pub struct EnumeratorBinary {
     /// Next id for the enumerated machine, starting with 0. \
-    /// The id is correct for the Full Enumerators and the ReverseForward, but indicates just a counter for ReverseBackward.
+    /// The id is the id of the full enumerator in the same direction, the backward ids differ from the forward ids.
     /// It can later be set like the full id with calc_id in the MachineInfo.
     // TODO id for larger BBx
     id_next: u64,
-    ids_skip_start: u64,
+    /// Id and batch number arithmetic. Batch 0 starts at config.start_from_id or the jump to 0RB of the reduced backward.
+    addressing: BatchAddressing,
     /// batch_no, increased for every call, batch 0 will show batch 1
     batch_no: usize,
     // batch_no_skip: usize,
@@ -74,15 +82,6 @@ pub struct EnumeratorBinary {
     /// For Reduced this is initially the number of Turing Machines to be created (using only 0RB and 1RB for field A0) and
     /// may later be reduced if more (tree) elements can be eliminated.
     n_machines: u64,
-    /// The number of machines to process if a limit is applied.
-    // TODO This is a bit unclear and needs to be clarified.
-    n_machines_to_process: u64,
-    /// The reduced actual batch size (number of Turing machines enumerated in each call).
-    batch_size: usize,
-    /// The given limit of machines to enumerate or (if smaller) the maximum number of machines for the number of states.
-    limit_id: u64,
-    /// The total number of batches to create all permutations.
-    num_batches: usize,
     /// The (4n+1) permutations for the transitions
     tr_permutations: Vec<TransitionBinary>,
     /// The (4n+1) permutations for the transitions per field. Can be reduced to eligible ones.
@@ -97,59 +96,67 @@ pub struct EnumeratorBinary {
     /// Sets if the first field A0 is rotated first (then A1, B0, B1, C0 etc.) or
     /// the last field (BB5: E1, then E0, D1, D0, C1 etc.)
     gen_type: EnumeratorType,
+    /// Steps of the inline simulation after the pre-decider, see [Config::enumerator_lookahead_steps].
+    lookahead_steps: usize,
 
     // reduced only
     id_batch_last: u64,
     pre_decider_count_batch: Option<PreDeciderCount>,
+    pre_decider_timer_batch: PreDeciderTimer,
     #[cfg(feature = "bb_enumerator_longest_skip_chain")]
     longest_skip_chain: Counter,
 }
 
 impl EnumeratorBinary {
-    /// Creates a new enumerator \
+    /// Creates a new enumerator.
+    /// # Panics
+    /// If the start id of the config is not valid for this enumerator, see [Self::try_new].
     pub fn new(enumeration_type: EnumeratorType, config: &Config) -> Self {
+        Self::try_new(enumeration_type, config).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a new enumerator. Returns an error if the start id of the config (see [Config::start_from_id])
+    /// exceeds the number of machines or the enumerator does not support a start id.
+    pub fn try_new(enumeration_type: EnumeratorType, config: &Config) -> Result<Self, String> {
         let n_states = config.n_states();
         assert!(n_states <= 7, "This enumerator can not create all permutations for {n_states} states as this would exceed u64:MAX permutations.");
 
         let n_fields = n_states * 2 + 2;
-        let tr_permutations = TransitionBinary::create_all_transition_permutations(n_states);
+        // the reduced enumerators rely on the positions of the default order, e.g. 0RB on pos 3
+        let transition_order = config.transition_order();
+        assert!(
+            transition_order == TransitionOrder::default()
+                || matches!(
+                    enumeration_type,
+                    EnumeratorType::EnumeratorFullForward | EnumeratorType::EnumeratorFullBackward
+                ),
+            "The transition order {transition_order} is only supported by the EnumeratorFull."
+        );
+        let tr_permutations = TransitionBinary::create_all_transition_permutations_ordered(
+            n_states,
+            transition_order,
+        );
         let mut transition_table = MachineBinary::new_default(n_states);
         // set all in set to the first variant
         transition_table.transitions[2..n_fields].fill(tr_permutations[0]);
 
+        // start id, limit and batch size
+        let addressing = BatchAddressing::new(enumeration_type, config)?;
+        let ids_skip_start = addressing.first_id();
+
         // special logic for reduced backward
-        let tr_permutations_field;
-        let ids_skip_start: u64;
         // let batch_no_skip;
         let n_machines = num_turing_machine_permutations(n_states) as u64;
-        let mut n_machines_to_process = n_machines;
-        let mut fields = [0; NUM_FIELDS];
-        match enumeration_type {
+        let fields = [0; NUM_FIELDS];
+        let tr_permutations_field = match enumeration_type {
             EnumeratorType::EnumeratorFullForward
             | EnumeratorType::EnumeratorFullBackward
-            | EnumeratorType::EnumeratorReducedForward => {
-                ids_skip_start = 0;
-                // batch_no_skip = 0;
-                tr_permutations_field = TR_PERMUTATIONS_FIELD_DEFAULT;
-            }
-            EnumeratorType::EnumeratorReducedBackwardNotWorking => {
-                // id must jump 2 to 0RB and then skips the whole tree
-                // permutations in a normal field: (4 * n_states + 1), e.g. 17 for BB4
-                // first field of 2n_states fields, so 2n-1 not created.
-                ids_skip_start = 2 * (4 * n_states as u64 + 1).pow(2 * n_states as u32 - 1);
-                tr_permutations_field =
-                    Self::create_all_transition_permutations_for_fields(n_states, &tr_permutations);
-                transition_table.transitions[2] = tr_permutations_field[2][2];
-                fields[2] = 2;
-                // number eliminated in A0: (4 * n_states as u64 + 1 - 2)
-                // This formula is correct but it will result in ids_skip_start because 2 are skipped and 2 are processed.
-                // n_machines_to_process -= (4 * n_states as u64 - 1)
-                //     * (4 * n_states as u64 + 1).pow(2 * n_states as u32 - 1);
-                n_machines_to_process = ids_skip_start;
-                // batch_no_skip = 0;
+            | EnumeratorType::EnumeratorReducedForward => TR_PERMUTATIONS_FIELD_DEFAULT,
+            // A0 is set by the start id
+            EnumeratorType::EnumeratorReducedBackward => {
+                Self::create_all_transition_permutations_for_fields(n_states, &tr_permutations)
             }
-            EnumeratorType::EnumeratorTNF => panic!("wrong call"),
-        }
+        };
         // if gen_type == EnumeratorType::EnumeratorReducedBackward {
         //     // id must jump 2 to 0RB and then skips the whole tree
         //     // permutations in a normal field: (4 * n_states + 1), e.g. 17 for BB4
@@ -168,41 +175,12 @@ impl EnumeratorBinary {
         //     tr_permutations_field = TR_PERMUTATIONS_FIELD_DEFAULT;
         // };
 
-        // limit
-        let limit_id = if config.machines_limit() > 0 {
-            let limit = config.machines_limit().min(n_machines);
-            if limit < n_machines {
-                if enumeration_type != EnumeratorType::EnumeratorReducedBackwardNotWorking {
-                    n_machines_to_process = limit;
-                    limit
-                } else {
-                    limit + ids_skip_start
-                }
-            } else {
-                limit
-            }
-        } else {
-            n_machines
-        };
-
-        // batch size
-        let batch_size = Self::calc_batch_size(
-            config.enumerator_full_batch_size_request(),
-            n_states,
-            n_machines as u128,
-        );
-        let num_batches = n_machines_to_process.div_ceil(batch_size as u64) as usize;
-
-        Self {
+        let mut enumerator = Self {
             id_next: ids_skip_start,
-            ids_skip_start,
+            addressing,
             batch_no: 0,
             // batch_no_skip,
             n_machines,
-            n_machines_to_process,
-            batch_size,
-            num_batches,
-            limit_id,
             tr_permutations,
             tr_permutations_field,
             machine: transition_table,
@@ -212,61 +190,73 @@ impl EnumeratorBinary {
                 EnumeratorType::EnumeratorFullForward
                 | EnumeratorType::EnumeratorReducedForward => 4,
                 EnumeratorType::EnumeratorFullBackward
-                | EnumeratorType::EnumeratorReducedBackwardNotWorking => n_fields - 3,
-                EnumeratorType::EnumeratorTNF => panic!("wrong call"),
+                | EnumeratorType::EnumeratorReducedBackward => n_fields - 3,
             },
             n_states,
             gen_type: enumeration_type,
+            lookahead_steps: config.enumerator_lookahead_steps(),
 
             id_batch_last: 0,
             pre_decider_count_batch: Default::default(),
+            pre_decider_timer_batch: Default::default(),
             #[cfg(feature = "bb_enumerator_longest_skip_chain")]
             longest_skip_chain: Default::default(),
+        };
+        if ids_skip_start > 0 {
+            // set the fields to the start id
+            enumerator.calc_batch_init(0);
         }
+
+        Ok(enumerator)
     }
 
+    /// Sets the enumerator to the first machine of the batch, so that the next call
+    /// of enumerate_permutation_batch_next returns exactly the same machines (and the same
+    /// pre-decider count) as the sequential enumeration would for this batch. \
+    /// The machine id is a number with base p = 4 * n_states + 1, each field being one digit:
+    /// * id = first id of the batch, see [BatchAddressing::first_id_of_batch]
+    /// * field value = (id / p^k) % p, with k the position of the field in enumeration order.
+    ///
+    /// Fields 2 and 3 (state A) are the two lowest digits and always 0 at the start of a batch, because
+    /// the batch size is a multiple of p^2. All other fields are reset first, so the result does not
+    /// depend on previously enumerated batches.
     fn calc_batch_init(&mut self, batch_no: usize) {
         self.batch_no = batch_no;
-        self.id_next = batch_no as u64 * self.batch_size as u64 + self.ids_skip_start;
+        self.id_next = self.addressing.first_id_of_batch(batch_no);
+        self.id_batch_last = 0;
+        self.pre_decider_count_batch = None;
+        self.pre_decider_timer_batch = Default::default();
         // fields 0 and 1 are unused
-        // fields 2 and 3 will be 0 as this is guaranteed by the batch size (status A always fully permuted)
         // calculating the remaining fields
         let permutations = (4 * self.n_states + 1) as u64;
         let mut remain = self.id_next / (permutations * permutations);
-        // reset transitions, only the used ones will be filled in the following loop, possibly redundant
+        // reset transitions, only the used ones will be filled in the following loop
         self.machine.transitions[2..self.n_states * 2 + 2].fill(self.tr_permutations[0]);
         match self.gen_type {
             EnumeratorType::EnumeratorFullForward | EnumeratorType::EnumeratorReducedForward => {
-                // field_no is always 4 or n-3
+                self.fields[4..self.n_fields].fill(0);
+                self.field_no = 4;
                 let mut i = 4;
-                loop {
-                    if remain > 0 {
-                        let m = remain % permutations;
-                        self.fields[i] = m as usize;
-                        self.machine.transitions[i] = self.tr_permutations[self.fields[i]];
-                        i += 1;
-                        remain = (remain - m) / permutations;
-                    } else {
-                        break;
-                    }
+                while remain > 0 {
+                    let m = remain % permutations;
+                    self.fields[i] = m as usize;
+                    self.machine.transitions[i] = self.tr_permutations[self.fields[i]];
+                    i += 1;
+                    remain /= permutations;
                 }
             }
-            EnumeratorType::EnumeratorFullBackward
-            | EnumeratorType::EnumeratorReducedBackwardNotWorking => {
+            EnumeratorType::EnumeratorFullBackward | EnumeratorType::EnumeratorReducedBackward => {
+                self.fields[2..self.n_fields - 2].fill(0);
+                self.field_no = self.n_fields - 3;
                 let mut i = self.n_fields - 3;
-                loop {
-                    if remain > 0 {
-                        let m = remain % permutations;
-                        self.fields[i] = m as usize;
-                        self.machine.transitions[i] = self.tr_permutations[self.fields[i]];
-                        i -= 1;
-                        remain = (remain - m) / permutations;
-                    } else {
-                        break;
-                    }
+                while remain > 0 {
+                    let m = remain % permutations;
+                    self.fields[i] = m as usize;
+                    self.machine.transitions[i] = self.tr_permutations[self.fields[i]];
+                    i -= 1;
+                    remain /= permutations;
                 }
             }
-            EnumeratorType::EnumeratorTNF => panic!("wrong call"),
         }
     }
 
@@ -278,7 +268,7 @@ impl EnumeratorBinary {
         // }
 
         // let mut is_last_batch = false;
-        let mut permutations = Vec::with_capacity(self.batch_size);
+        let mut permutations = Vec::with_capacity(self.addressing.batch_size());
         let num_tr_permutations = self.tr_permutations.len();
         // 'creation: loop {
         loop {
@@ -292,7 +282,7 @@ impl EnumeratorBinary {
                     let permutation = MachineId::new_no_id(self.machine);
                     permutations.push(permutation);
                     id += 1;
-                    if id == self.limit_id {
+                    if id == self.addressing.limit_id() {
                         // total maximum reached
                         self.id_next = id;
                         return (permutations, true);
@@ -329,7 +319,7 @@ impl EnumeratorBinary {
                     }
                 }
             }
-            if permutations.len() == self.batch_size {
+            if permutations.len() == self.addressing.batch_size() {
                 break;
             }
         }
@@ -347,7 +337,7 @@ impl EnumeratorBinary {
         // println!("Tr: {}", self.transition_table);
 
         // let mut is_last_batch = false;
-        let mut permutations = Vec::with_capacity(self.batch_size);
+        let mut permutations = Vec::with_capacity(self.addressing.batch_size());
         let num_tr_permutations = self.tr_permutations.len();
         let first = self.n_fields - 1;
         let third = self.n_fields - 3;
@@ -362,7 +352,7 @@ impl EnumeratorBinary {
                     let permutation = MachineId::new_no_id(self.machine);
                     permutations.push(permutation);
                     id += 1;
-                    if id == self.limit_id {
+                    if id == self.addressing.limit_id() {
                         // total maximum reached
                         self.id_next = id;
                         return (permutations, true);
@@ -399,7 +389,7 @@ impl EnumeratorBinary {
                     }
                 }
             }
-            if permutations.len() == self.batch_size {
+            if permutations.len() == self.addressing.batch_size() {
                 break;
             }
         }
@@ -416,10 +406,11 @@ impl EnumeratorBinary {
         //     return (Vec::new(), true);
         // }
         // println!("Enumerator reduced: is_next = {}", self.id_next);
-        self.id_batch_last = (self.id_next + self.batch_size as u64 - 1).min(self.limit_id - 1);
+        self.id_batch_last = self.addressing.ids_in_batch(self.batch_no).end - 1;
         let mut pre_decider_count_batch = PreDeciderCount::default();
+        let mut pre_decider_timer = PreDeciderTimer::default();
 
-        let mut permutations = Vec::with_capacity(self.batch_size);
+        let mut permutations = Vec::with_capacity(self.addressing.batch_size());
         let num_tr_permutations = self.tr_permutations.len();
         let ids_left_out = self.tr_permutations.len() as u64 - 2;
         let mut num_hold_a1;
@@ -447,7 +438,7 @@ impl EnumeratorBinary {
                         );
                     } else {
                         // run pre-decider check
-                        let check_pre = self.check_pre_decider();
+                        let check_pre = pre_decider_timer.measure(|| self.check_pre_decider());
                         #[cfg(feature = "bb_enumerator_longest_skip_chain")]
                         match check_pre {
                             PreDeciderReason::None => {
@@ -471,6 +462,7 @@ impl EnumeratorBinary {
                             PreDeciderReason::None => {
                                 let mut permutation = self.machine;
                                 permutation.has_self_referencing_transition_store_result();
+                                permutation.features_store_result();
                                 permutations.push(MachineId::new_no_id(permutation));
                                 #[cfg(feature = "bb_print_non_pre_perm")]
                                 println!(
@@ -501,32 +493,40 @@ impl EnumeratorBinary {
                             PreDeciderReason::WritesOnlyZero => {
                                 pre_decider_count_batch.num_writes_only_zero += 1;
                             }
+                            PreDeciderReason::LookaheadHalt => {
+                                pre_decider_count_batch.num_lookahead_halt += 1;
+                            }
+                            PreDeciderReason::LookaheadCycle => {
+                                pre_decider_count_batch.num_lookahead_cycle += 1;
+                            }
                         }
                     }
                     id += 1;
-                    if id == self.limit_id {
+                    if id == self.addressing.limit_id() {
                         // total maximum reached
                         self.id_next = id;
                         // not enumerated = size of batch - enumerated permutations - eliminated permutations
-                        pre_decider_count_batch.num_not_enumerated = self.limit_id
+                        pre_decider_count_batch.num_not_enumerated = self.addressing.limit_id()
                             - self.id_batch_start()
                             - permutations.len() as u64
                             - pre_decider_count_batch.num_total();
                         self.pre_decider_count_batch = Some(pre_decider_count_batch);
+                        self.pre_decider_timer_batch = pre_decider_timer;
                         return (permutations, true);
                     }
                 }
                 // adjust id back to normal count
                 id += ids_left_out;
-                if id >= self.limit_id {
+                if id >= self.addressing.limit_id() {
                     // total maximum reached
                     self.id_next = id;
                     // not enumerated = size of batch - enumerated permutations - eliminated permutations
-                    pre_decider_count_batch.num_not_enumerated = self.limit_id
+                    pre_decider_count_batch.num_not_enumerated = self.addressing.limit_id()
                         - self.id_batch_start()
                         - permutations.len() as u64
                         - pre_decider_count_batch.num_total();
                     self.pre_decider_count_batch = Some(pre_decider_count_batch);
+                    self.pre_decider_timer_batch = pre_decider_timer;
                     return (permutations, true);
                 }
             }
@@ -568,9 +568,11 @@ impl EnumeratorBinary {
             num_hold_other_lines = count_halt_transitions(tr3_used);
         }
 
-        pre_decider_count_batch.num_not_enumerated =
-            (self.batch_size - permutations.len()) as u64 - pre_decider_count_batch.num_total();
+        pre_decider_count_batch.num_not_enumerated = (self.addressing.batch_size()
+            - permutations.len()) as u64
+            - pre_decider_count_batch.num_total();
         self.pre_decider_count_batch = Some(pre_decider_count_batch);
+        self.pre_decider_timer_batch = pre_decider_timer;
 
         (permutations, false)
     }
@@ -582,10 +584,12 @@ impl EnumeratorBinary {
         // if self.id_next >= self.n_machines {
         //     return (Vec::new(), true);
         // }
-        self.id_batch_last = (self.id_next + self.batch_size as u64 - 1).min(self.limit_id - 1);
+        self.id_batch_last = (self.id_next + self.addressing.batch_size() as u64 - 1)
+            .min(self.addressing.limit_id() - 1);
         let mut pre_decider_count_batch = PreDeciderCount::default();
+        let mut pre_decider_timer = PreDeciderTimer::default();
 
-        let mut permutations = Vec::with_capacity(self.batch_size);
+        let mut permutations = Vec::with_capacity(self.addressing.batch_size());
         let first = self.n_fields - 1;
         let third = self.n_fields - 3;
         let mut num_hold_e0;
@@ -597,8 +601,6 @@ impl EnumeratorBinary {
             // Also the reduced number of stated for A0 remains 0RB and 1RB
             // permutations state E
             // loop all transitions for last state
-            // id must jump 2 to 0RB
-            // TODO
             let mut id = self.id_next;
             for v1 in self.tr_permutations_field[first - 1].iter() {
                 self.machine.transitions[first - 1] = *v1;
@@ -619,7 +621,7 @@ impl EnumeratorBinary {
                         );
                     } else {
                         // run pre-decider check
-                        let check_pre = self.check_pre_decider();
+                        let check_pre = pre_decider_timer.measure(|| self.check_pre_decider());
                         #[cfg(feature = "bb_enumerator_longest_skip_chain")]
                         match check_pre {
                             PreDeciderReason::None => {
@@ -643,6 +645,7 @@ impl EnumeratorBinary {
                             PreDeciderReason::None => {
                                 let mut permutation = self.machine;
                                 permutation.has_self_referencing_transition_store_result();
+                                permutation.features_store_result();
                                 permutations.push(MachineId::new_no_id(permutation));
                                 #[cfg(feature = "bb_print_non_pre_perm")]
                                 println!(
@@ -673,32 +676,29 @@ impl EnumeratorBinary {
                             PreDeciderReason::WritesOnlyZero => {
                                 pre_decider_count_batch.num_writes_only_zero += 1;
                             }
+                            PreDeciderReason::LookaheadHalt => {
+                                pre_decider_count_batch.num_lookahead_halt += 1;
+                            }
+                            PreDeciderReason::LookaheadCycle => {
+                                pre_decider_count_batch.num_lookahead_cycle += 1;
+                            }
                         }
                     }
                     id += 1;
-                    if id == self.limit_id {
-                        // total maximum reached
+                    if id == self.addressing.limit_id() {
+                        // total maximum reached, which is the end of A0 = 1RB for a full run
                         self.id_next = id;
-                        pre_decider_count_batch.num_not_enumerated = self.limit_id
+                        pre_decider_count_batch.num_not_enumerated = self.addressing.limit_id()
                             - self.id_batch_start()
                             - permutations.len() as u64
-                            - pre_decider_count_batch.num_total();
+                            - pre_decider_count_batch.num_total()
+                            + self.addressing.num_ids_not_walked();
                         self.pre_decider_count_batch = Some(pre_decider_count_batch);
+                        self.pre_decider_timer_batch = pre_decider_timer;
                         return (permutations, true);
                     }
                 }
-                // id += ids_left_out;
-                // if id >= self.limit {
-                //     // total maximum reached
-                //     self.id_next = id;
-                //     self.pre_decider_count_batch.num_not_enumerated = self.limit
-                //         - self.id_batch_start()
-                //         - permutations.len() as u64
-                //         - self.pre_decider_count_batch.num_total();
-                //     return (permutations, true);
-                // }
             }
-            // TODO subtract jump to 0RB
             self.id_next = id;
 
             // update line two, permutations state D (still separate for performance)
@@ -711,18 +711,8 @@ impl EnumeratorBinary {
                 self.fields[third] = 0;
                 self.machine.transitions[third] = self.tr_permutations_field[third][0];
                 'outer: loop {
+                    // A0 is never exhausted, the limit id is reached before
                     self.field_no -= 1;
-                    if self.field_no < 2 {
-                        // Ends here
-                        // add remaining ids for this batch
-                        self.id_next = self.id_batch_last + 1;
-                        pre_decider_count_batch.num_not_enumerated = self.limit_id
-                            - self.id_batch_start()
-                            - permutations.len() as u64
-                            - pre_decider_count_batch.num_total();
-                        self.pre_decider_count_batch = Some(pre_decider_count_batch);
-                        return (permutations, true);
-                    }
                     self.fields[self.field_no] += 1;
                     if self.fields[self.field_no] < self.tr_permutations_field[self.field_no].len()
                     {
@@ -750,9 +740,11 @@ impl EnumeratorBinary {
             num_hold_other_lines = count_halt_transitions(tr3_used);
         }
 
-        pre_decider_count_batch.num_not_enumerated =
-            (self.batch_size - permutations.len()) as u64 - pre_decider_count_batch.num_total();
+        pre_decider_count_batch.num_not_enumerated = (self.addressing.batch_size()
+            - permutations.len()) as u64
+            - pre_decider_count_batch.num_total();
         self.pre_decider_count_batch = Some(pre_decider_count_batch);
+        self.pre_decider_timer_batch = pre_decider_timer;
 
         (permutations, false)
     }
@@ -772,6 +764,13 @@ impl EnumeratorBinary {
         if check_simple_start_cycle(&self.machine) {
             return PreDeciderReason::SimpleStartCycle;
         }
+        if self.lookahead_steps > 0 {
+            match run_lookahead(&self.machine, self.lookahead_steps) {
+                LookaheadResult::Halt(_) => return PreDeciderReason::LookaheadHalt,
+                LookaheadResult::Cycle => return PreDeciderReason::LookaheadCycle,
+                LookaheadResult::Undecided => {}
+            }
+        }
 
         PreDeciderReason::None
     }
@@ -791,7 +790,11 @@ impl EnumeratorBinary {
     }
 
     fn id_batch_start(&self) -> u64 {
-        self.id_batch_last / self.batch_size as u64 * self.batch_size as u64
+        let batch_no = self
+            .addressing
+            .batch_of_id(self.id_batch_last)
+            .expect("id of batch within limit");
+        self.addressing.first_id_of_batch(batch_no)
     }
 }
 
@@ -815,10 +818,9 @@ impl Enumerator for EnumeratorBinary {
             EnumeratorType::EnumeratorReducedForward => {
                 self.enumerate_reduced_permutation_batch_next_forward()
             }
-            EnumeratorType::EnumeratorReducedBackwardNotWorking => {
+            EnumeratorType::EnumeratorReducedBackward => {
                 self.enumerate_reduced_permutation_batch_next_backward()
             }
-            EnumeratorType::EnumeratorTNF => panic!("wrong call"),
         };
         self.batch_no += 1;
 
@@ -827,7 +829,7 @@ impl Enumerator for EnumeratorBinary {
 
     /// The given limit of machines to enumerate or (if smaller) the maximum number of machines for the number of states.
     fn limit(&self) -> u64 {
-        self.limit_id
+        self.addressing.limit_id()
     }
 
     fn pre_decider_count(&self) -> PreDeciderCount {
@@ -869,24 +871,25 @@ impl DataProvider for EnumeratorBinary {
         Ok(DataProviderBatch {
             // batch no is already set to next batch
             batch_no: self.batch_no - 1,
+            n_states: self.n_states,
             machines,
             pre_decider_count: self.pre_decider_count_batch,
+            pre_decider_timer: self.pre_decider_timer_batch,
             end_reason,
         })
     }
 
     fn batch_size(&self) -> usize {
-        self.batch_size
+        self.addressing.batch_size()
     }
 
     /// The total number of batches to create all permutations.
     fn num_batches(&self) -> usize {
-        self.num_batches
+        self.addressing.num_batches()
     }
 
     fn num_machines_to_process(&self) -> u64 {
-        self.limit_id
-        // self.n_machines_to_process
+        self.addressing.num_machines()
     }
 
     fn requires_pre_decider_check(&self) -> PreDeciderRun {
@@ -895,11 +898,15 @@ impl DataProvider for EnumeratorBinary {
                 PreDeciderRun::RunStartBRightOnly
             }
             EnumeratorType::EnumeratorReducedForward
-            | EnumeratorType::EnumeratorReducedBackwardNotWorking => PreDeciderRun::DoNotRun,
-            EnumeratorType::EnumeratorTNF => panic!("wrong call"),
+            | EnumeratorType::EnumeratorReducedBackward => PreDeciderRun::DoNotRun,
         }
     }
 
+    fn skip_to_batch(&mut self, batch_no: usize) -> bool {
+        self.calc_batch_init(batch_no);
+        true
+    }
+
     // fn returns_pre_decider_count(&self) -> bool {
     //     false
     // }
@@ -920,14 +927,10 @@ impl DataProviderThreaded for EnumeratorBinary {
 
         Self {
             id_next: 0,
-            ids_skip_start: self.ids_skip_start,
+            addressing: self.addressing,
             batch_no: 0,
             // batch_no_skip: self.batch_no_skip,
             n_machines: self.n_machines,
-            n_machines_to_process: self.n_machines_to_process,
-            batch_size: self.batch_size,
-            limit_id: self.limit_id,
-            num_batches: self.num_batches,
             tr_permutations: self.tr_permutations.clone(),
             tr_permutations_field: self.tr_permutations_field.clone(),
             machine: transition_table,
@@ -937,14 +940,15 @@ impl DataProviderThreaded for EnumeratorBinary {
                 EnumeratorType::EnumeratorFullForward
                 | EnumeratorType::EnumeratorReducedForward => 4,
                 EnumeratorType::EnumeratorFullBackward
-                | EnumeratorType::EnumeratorReducedBackwardNotWorking => self.n_fields - 3,
-                EnumeratorType::EnumeratorTNF => panic!("wrong call"),
+                | EnumeratorType::EnumeratorReducedBackward => self.n_fields - 3,
             },
             n_states: self.n_states,
             gen_type: self.gen_type,
+            lookahead_steps: self.lookahead_steps,
 
             id_batch_last: 0,
             pre_decider_count_batch: Default::default(),
+            pre_decider_timer_batch: Default::default(),
             #[cfg(feature = "bb_enumerator_longest_skip_chain")]
             longest_skip_chain: Default::default(),
         }
@@ -958,26 +962,31 @@ impl DataProviderThreaded for EnumeratorBinary {
             EndReason::None
         };
         DataProviderBatch {
-            batch_no: self.batch_no,
+            // batch no is already set to next batch
+            batch_no: self.batch_no - 1,
+            n_states: self.n_states,
             machines,
             pre_decider_count: self.pre_decider_count_batch,
+            pre_decider_timer: self.pre_decider_timer_batch,
             end_reason,
         }
     }
 }
 
-/// Compares sequential batches with batch_no generation
+/// Compares the batches of the sequential enumeration with the direct access by batch_no for the reduced
+/// forward enumerator of BB3 and panics on the first difference.
+#[deprecated(
+    note = "Direct batch addressing is exact, see the tests of the enumerator and BatchAddressing."
+)]
 pub fn validate_next_with_batch_no() {
     let n_states = 3;
     let config = Config::builder(n_states)
         .enumerator_full_batch_size_request(100000)
-        // .enumerator_first_rotate_field_front(true)
         .build();
     let mut enumerator_next =
-        EnumeratorBinary::new(EnumeratorType::EnumeratorReducedBackwardNotWorking, &config);
+        EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
     let mut enumerator_batch_no =
-        EnumeratorBinary::new(EnumeratorType::EnumeratorReducedBackwardNotWorking, &config);
-    // let (_m_no, _is_finished) = enumerator_batch_no.enumerate_permutation_batch_no(484);
+        EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
 
     println!("Machines: {}", enumerator_next.n_machines);
 
@@ -985,41 +994,21 @@ pub fn validate_next_with_batch_no() {
     let mut counter = 0;
     loop {
         let (m_next, is_finished) = enumerator_next.enumerate_permutation_batch_next();
-        // delete known transition data to force update
-        for i in 2..8 {
-            enumerator_batch_no.machine.transitions[i].transition = 0;
-        }
         let (m_no, _is_finished) = enumerator_batch_no.enumerate_permutation_batch_no(batch_no);
-        println!(
-            "batch_no {}, size next{}, size batch_no {}",
-            batch_no + 1,
-            m_next.len(),
-            m_no.len()
-        );
-        assert_eq!(m_next.len(), m_no.len());
-
-        for (i, m) in m_next.iter().enumerate() {
-            let mv = &m_no[i];
+        assert_eq!(m_next.len(), m_no.len(), "batch_no {batch_no}");
+        for (m, mv) in m_next.iter().zip(m_no.iter()) {
             counter += 1;
             assert_eq!(m, mv);
         }
-
         if is_finished {
             println!(
                 "counted: {counter} of {} machines",
                 enumerator_next.n_machines
             );
-            // assert_eq!(counter, enumerator_next.n_machines);
             break;
         }
-
         batch_no += 1;
     }
-    // let result = batch_run_decider_chain_data_provider_single_thread(&vec![dc], enumerator);
-    // println!("{}", result);
-    // println!("{}", result.machines_max_steps_to_string(10));
-    // assert_eq!(result_max_steps_known(n_states), result.steps_max());
-    println!();
 }
 
 /// run this only in release mode from command line: \
@@ -1027,9 +1016,10 @@ pub fn validate_next_with_batch_no() {
 #[cfg(test)]
 mod tests {
     use crate::{
+        config::{CoreUsage, IdNormalized, ENUMERATOR_LOOKAHEAD_STEPS_RECOMMENDATION},
         decider::decider_engine::{
             batch_run_decider_chain_data_provider_single_thread,
-            batch_run_decider_chain_threaded_data_provider_multi_thread,
+            batch_run_decider_chain_threaded_data_provider_multi_thread, run_decider_gen,
         },
         decider::decider_result::result_max_steps_known,
         decider::DeciderStandard,
@@ -1101,6 +1091,30 @@ mod tests {
         }
     }
 
+    /// The n-th machine of the EnumeratorFull has the id n, also for other transition orders.
+    #[test]
+    #[cfg(not(feature = "normalized_id_reversed"))]
+    fn enumerator_full_transition_order_ids() {
+        let config = Config::builder(2)
+            .transition_order(TransitionOrder::SymbolMajor)
+            .machine_limit(0)
+            .build();
+        let mut g = EnumeratorBinary::new(EnumeratorType::EnumeratorFullForward, &config);
+        let (machines, _) = g.enumerate_permutation_batch_next();
+        assert_eq!(
+            machines[1].machine().transition_start(),
+            TRANSITIONS_FOR_A0[0]
+        );
+        for (id, m) in machines.iter().enumerate() {
+            assert_eq!(
+                m.machine()
+                    .normalized_id_calc_ordered(TransitionOrder::SymbolMajor),
+                id as IdNormalized
+            );
+        }
+        assert_ne!(machines[1].machine().normalized_id_calc(), 1);
+    }
+
     #[test]
     fn enumerator_full_direct_access_batch_no_all() {
         let config = Config::builder(3)
@@ -1130,6 +1144,200 @@ mod tests {
         // println!("m1: {}", m1);
     }
 
+    /// Direct access with batch_no must return exactly the batch of the sequential enumeration,
+    /// also when the batches are requested in reverse order (as threads may do).
+    #[test]
+    fn enumerator_reduced_forward_direct_access_batch_no_all_bb3() {
+        let config = Config::builder(3)
+            .enumerator_full_batch_size_request(10_000)
+            .machine_limit(0)
+            .build();
+        assert_direct_access_equals_sequential(EnumeratorType::EnumeratorReducedForward, &config);
+    }
+
+    #[test]
+    fn enumerator_reduced_forward_direct_access_batch_no_limit_bb3() {
+        let config = Config::builder(3)
+            .enumerator_full_batch_size_request(10_000)
+            .machine_limit(1_234_567)
+            .build();
+        assert_direct_access_equals_sequential(EnumeratorType::EnumeratorReducedForward, &config);
+    }
+
+    /// The backward enumeration returns the same machines and counts as the forward enumeration.
+    #[test]
+    fn enumerator_reduced_backward_same_as_forward_bb3() {
+        let config = Config::builder(3)
+            .enumerator_full_batch_size_request(10_000)
+            .machine_limit(0)
+            .build();
+        let run = |gen_type: EnumeratorType| {
+            let mut g = EnumeratorBinary::new(gen_type, &config);
+            let mut machines = Vec::new();
+            let mut count = PreDeciderCount::default();
+            loop {
+                let data = g.machine_batch_next().unwrap();
+                machines.extend(data.machines.iter().map(|m| m.to_standard_tm_text_format()));
+                count.add_self(&data.pre_decider_count.unwrap());
+                if data.end_reason == EndReason::IsLastBatch {
+                    machines.sort();
+                    return (machines, count, g.num_machines_to_process());
+                }
+            }
+        };
+        let forward = run(EnumeratorType::EnumeratorReducedForward);
+        let backward = run(EnumeratorType::EnumeratorReducedBackward);
+        assert_eq!(forward, backward);
+        assert_eq!(
+            backward.0.len() as u64 + backward.1.num_total(),
+            num_turing_machine_permutations(3) as u64
+        );
+
+        assert_direct_access_equals_sequential(EnumeratorType::EnumeratorReducedBackward, &config);
+        let config = Config::builder_from_config(&config)
+            .machine_limit(123_456)
+            .build();
+        assert_direct_access_equals_sequential(EnumeratorType::EnumeratorReducedBackward, &config);
+    }
+
+    #[test]
+    fn enumerator_full_forward_direct_access_batch_no_all_bb3() {
+        let config = Config::builder(3)
+            .enumerator_full_batch_size_request(10_000)
+            .machine_limit(0)
+            .build();
+        assert_direct_access_equals_sequential(EnumeratorType::EnumeratorFullForward, &config);
+    }
+
+    #[test]
+    fn enumerator_full_start_from_id_bb3() {
+        for gen_type in [
+            EnumeratorType::EnumeratorFullForward,
+            EnumeratorType::EnumeratorFullBackward,
+        ] {
+            let config = Config::builder(3)
+                .enumerator_full_batch_size_request(10_000)
+                .machine_limit(0)
+                .build();
+            let all = enumerate_all(gen_type, &config);
+            // 1_000_100 is rounded down to the first machine of its block of 13^2 machines
+            let config = Config::builder_from_config(&config)
+                .start_from_id(1_000_100)
+                .machine_limit(123_456)
+                .build();
+            let from_id = enumerate_all(gen_type, &config);
+            assert_eq!(all[999_973..1_123_429], from_id[..]);
+            assert_direct_access_equals_sequential(gen_type, &config);
+        }
+    }
+
+    #[test]
+    fn enumerator_reduced_forward_start_from_id_bb3() {
+        let config = Config::builder(3)
+            .enumerator_full_batch_size_request(10_000)
+            .machine_limit(0)
+            .build();
+        let all = enumerate_all(EnumeratorType::EnumeratorReducedForward, &config);
+        let config = Config::builder_from_config(&config)
+            .start_from_id(2_345_678)
+            .build();
+        let from_id = enumerate_all(EnumeratorType::EnumeratorReducedForward, &config);
+        assert!(!from_id.is_empty() && from_id.len() < all.len());
+        assert!(all.ends_with(&from_id));
+        assert_direct_access_equals_sequential(EnumeratorType::EnumeratorReducedForward, &config);
+
+        // a start id beyond the last machine is an error, not a panic
+        let config = Config::builder_from_config(&config)
+            .start_from_id(20_000_000)
+            .build();
+        let e = EnumeratorBinary::try_new(EnumeratorType::EnumeratorReducedForward, &config);
+        assert!(e.is_err());
+        let decider_config = DeciderStandard::Cycler.decider_config(&config);
+        let result = run_decider_gen(
+            decider_config,
+            EnumeratorType::EnumeratorReducedForward,
+            CoreUsage::SingleCore,
+        );
+        assert!(matches!(result.end_reason, EndReason::Error(20_000_000, _)));
+    }
+
+    #[test]
+    fn enumerator_reduced_lookahead_bb3() {
+        let run = |lookahead_steps: usize| {
+            let config = Config::builder(3)
+                .enumerator_reduced_batch_size_request(10_000)
+                .enumerator_lookahead_steps(lookahead_steps)
+                .machine_limit(0)
+                .build();
+            let deciders = [
+                DeciderStandard::Cycler.decider_config(&config),
+                DeciderStandard::Hold.decider_config(&config),
+            ];
+            let enumerator =
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+            batch_run_decider_chain_data_provider_single_thread(&deciders, enumerator)
+        };
+        let result = run(0);
+        // capped below the 21 steps of BB3
+        let result_lookahead = run(ENUMERATOR_LOOKAHEAD_STEPS_RECOMMENDATION + 5);
+        let count = result_lookahead.pre_decider_count();
+        assert!(count.num_lookahead_halt > 0 && count.num_lookahead_cycle > 0);
+        assert_eq!(
+            result_lookahead.num_evaluated() + count.num_lookahead_halt + count.num_lookahead_cycle,
+            result.num_evaluated()
+        );
+        assert_eq!(
+            result_lookahead.num_halt() + count.num_lookahead_halt,
+            result.num_halt()
+        );
+        assert_eq!(result_lookahead.num_undecided(), result.num_undecided());
+        assert_eq!(result_lookahead.steps_max(), result_max_steps_known(3));
+        assert_eq!(
+            result_lookahead.machines_max_steps().unwrap().len(),
+            result.machines_max_steps().unwrap().len()
+        );
+    }
+
+    fn enumerate_all(gen_type: EnumeratorType, config: &Config) -> Vec<MachineId> {
+        let mut g = EnumeratorBinary::new(gen_type, config);
+        let mut machines = Vec::new();
+        loop {
+            let data = g.machine_batch_next().unwrap();
+            machines.extend(data.machines);
+            if data.end_reason == EndReason::IsLastBatch {
+                return machines;
+            }
+        }
+    }
+
+    fn assert_direct_access_equals_sequential(gen_type: EnumeratorType, config: &Config) {
+        let mut batches = Vec::new();
+        let mut g = EnumeratorBinary::new(gen_type, config);
+        loop {
+            let data = g.machine_batch_next().unwrap();
+            let is_last = data.end_reason == EndReason::IsLastBatch;
+            batches.push(data);
+            if is_last {
+                break;
+            }
+        }
+        assert_eq!(batches.len(), g.num_batches());
+        let total: u64 = batches
+            .iter()
+            .map(|b| b.machines.len() as u64 + b.pre_decider_count.map_or(0, |p| p.num_total()))
+            .sum();
+        assert_eq!(total, g.num_machines_to_process());
+
+        let mut g = EnumeratorBinary::new(gen_type, config).new_from_data_provider();
+        for (batch_no, b1) in batches.iter().enumerate().rev() {
+            let b2 = g.batch_no(batch_no);
+            assert_eq!(b1.batch_no, b2.batch_no);
+            assert_eq!(b1.machines, b2.machines, "batch_no {batch_no}");
+            assert_eq!(b1.pre_decider_count, b2.pre_decider_count);
+            assert_eq!(b1.end_reason, b2.end_reason);
+        }
+    }
+
     fn run_test_decider_enumerator_full(n_states: usize) {
         let config = config_bench(n_states);
         let dc = DeciderStandard::Cycler.decider_config(&config);
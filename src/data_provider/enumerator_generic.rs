@@ -0,0 +1,142 @@
+//! Enumerator for machines with any number of states and symbols ([MachineGeneric]), e.g. BB(2,3), BB(2,4) or BB(3,3). \
+//! The binary machines with two symbols are enumerated much faster by
+//! [EnumeratorBinary](crate::data_provider::enumerator_binary::EnumeratorBinary), which also delivers the batches
+//! for the standard deciders. This enumerator is an [Iterator] and the machines are run by
+//! [DeciderGeneric](crate::decider::decider_generic::DeciderGeneric). \
+//! All transition tables are created with these reductions:
+//! - The first transition A0 writes 0 or 1 and moves right to B (0RB or 1RB). Mirrored machines, renamed states
+//!   and permuted symbols other than 0 are the same machine.
+//! - The table has exactly one halt transition '---'. Without halt transition the machine cannot halt, with more
+//!   than one it cannot be a max machine, see
+//!   [PreDeciderReason::NotExactlyOneHaltCondition](crate::status::PreDeciderReason::NotExactlyOneHaltCondition).
+//!
+//! Each machine has a number, which is also its id, so a machine can be created directly with [EnumeratorGeneric::machine].
+//! # Example
+//! ```
+//! use bb_challenge::data_provider::enumerator_generic::EnumeratorGeneric;
+//! // BB(2,3): 2 states, 3 symbols
+//! let enumerator = EnumeratorGeneric::new(2, 3);
+//! assert_eq!(enumerator.num_machines(), 207_360);
+//! assert_eq!(enumerator.count(), 207_360);
+//! ```
+
+use crate::{
+    config::{MAX_STATES_GENERIC, MAX_SYMBOLS_GENERIC},
+    machine_generic::{
+        MachineGeneric, StateType, SymbolType, TransitionGeneric, B, DIR_LEFT, DIR_RIGHT,
+        TRANSITION_HALT,
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct EnumeratorGeneric {
+    n_states: usize,
+    n_symbols: usize,
+    /// All transitions which do not halt.
+    transitions: Vec<TransitionGeneric>,
+    num_machines: u64,
+    machine_no_next: u64,
+}
+
+impl EnumeratorGeneric {
+    /// Creates the enumerator for machines with n_states (2 or more) and n_symbols (2 or more).
+    pub fn new(n_states: usize, n_symbols: usize) -> Self {
+        assert!(
+            (2..=MAX_STATES_GENERIC).contains(&n_states)
+                && (2..=MAX_SYMBOLS_GENERIC).contains(&n_symbols),
+            "The enumerator requires 2 to {MAX_STATES_GENERIC} states and 2 to {MAX_SYMBOLS_GENERIC} symbols."
+        );
+        let mut transitions = Vec::with_capacity(n_states * n_symbols * 2);
+        for state in 1..=n_states {
+            for symbol in 0..n_symbols {
+                for direction in [DIR_LEFT, DIR_RIGHT] {
+                    transitions.push(TransitionGeneric::new_fields(
+                        symbol as SymbolType,
+                        direction,
+                        state as StateType,
+                    ));
+                }
+            }
+        }
+        // A0 (2 options), halt field (all other fields) and all other fields
+        let n_fields = (n_states * n_symbols) as u64;
+        let num_machines = 2 * (n_fields - 1) * (transitions.len() as u64).pow(n_fields as u32 - 2);
+
+        Self {
+            n_states,
+            n_symbols,
+            transitions,
+            num_machines,
+            machine_no_next: 0,
+        }
+    }
+
+    /// Returns the machine with this number (0..num_machines), the number is set as id.
+    pub fn machine(&self, machine_no: u64) -> MachineGeneric {
+        assert!(
+            machine_no < self.num_machines,
+            "Machine number out of range"
+        );
+        let mut machine = MachineGeneric::new_undefined(self.n_states, self.n_symbols);
+        let mut rest = machine_no;
+        let n_fields = self.n_states * self.n_symbols;
+        machine.set_transition(
+            1,
+            0,
+            TransitionGeneric::new_fields((rest % 2) as SymbolType, DIR_RIGHT, B),
+        );
+        rest /= 2;
+        let field_halt = (rest % (n_fields as u64 - 1)) as usize + 1;
+        rest /= n_fields as u64 - 1;
+        let n_transitions = self.transitions.len() as u64;
+        for field in 1..n_fields {
+            let transition = if field == field_halt {
+                TRANSITION_HALT
+            } else {
+                let t = self.transitions[(rest % n_transitions) as usize];
+                rest /= n_transitions;
+                t
+            };
+            machine.set_transition(
+                (field / self.n_symbols) as StateType + 1,
+                (field % self.n_symbols) as SymbolType,
+                transition,
+            );
+        }
+        machine.id = Some(machine_no);
+
+        machine
+    }
+
+    /// Total number of machines enumerated.
+    pub fn num_machines(&self) -> u64 {
+        self.num_machines
+    }
+
+    pub fn n_states(&self) -> usize {
+        self.n_states
+    }
+
+    pub fn n_symbols(&self) -> usize {
+        self.n_symbols
+    }
+}
+
+impl Iterator for EnumeratorGeneric {
+    type Item = MachineGeneric;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.machine_no_next >= self.num_machines {
+            return None;
+        }
+        let machine = self.machine(self.machine_no_next);
+        self.machine_no_next += 1;
+
+        Some(machine)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = (self.num_machines - self.machine_no_next) as usize;
+        (n, Some(n))
+    }
+}
@@ -1,207 +1,208 @@
-#![allow(dead_code)]
-
-use std::{
-    collections::VecDeque,
-    fmt::Display,
-    fs::File,
-    io::{BufWriter, Write},
-};
+//! Enumerator in Tree Normal Form (TNF). \
+//! Instead of creating all permutations of the transition table, the machines are created as a tree:
+//! The root is the machine with all transitions undefined. Each machine is simulated until it reaches an
+//! undefined transition, which would be its halt. Only then this transition is extended by all allowed
+//! transitions, each giving a new machine, which continues the simulation at that point. \
+//! This way only transitions the machine actually reaches are defined and transitions never used are not
+//! permuted at all. Additionally these rules apply:
+//! - The first transition A0 moves right to B (0RB or 1RB), the mirrored machines are the same.
+//! - A new transition may only go to the states used so far or the next unused state, so the states are
+//!   numbered in the order they are reached.
+//! - A transition is only extended if another transition stays undefined, otherwise the machines could not halt.
+//!
+//! Returned are the machines reaching an undefined transition (these halt, all undefined transitions are '---')
+//! and the leaves, which run [Config::enumerator_tnf_step_limit] steps without reaching one. The leaves are
+//! left for the deciders. \
+//! This massively reduces the number of machines, e.g. for BB4 the tree has about 860,000 machines
+//! compared to 6,975,757,441 permutations. \
+//! The tree is enumerated depth first, so batch numbers cannot be addressed directly and the enumeration
+//! runs on a single thread.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     data_provider::{enumerator_tnf::EnumeratorTNF, DataProvider},
+//!     decider::decider_result::EndReason,
+//! };
+//! let config = Config::new_default(3);
+//! let mut enumerator = EnumeratorTNF::new(&config);
+//! let mut num_machines = 0;
+//! loop {
+//!     let batch = enumerator.machine_batch_next().unwrap();
+//!     num_machines += batch.machines.len();
+//!     if batch.end_reason == EndReason::IsLastBatch {
+//!         break;
+//!     }
+//! }
+//! assert_eq!(num_machines as u64, enumerator.num_machines_returned());
+//! ```
+
+use std::{collections::VecDeque, fmt::Display};
 
 use crate::{
-    config::{Config, CoreUsage, StepBig, MAX_STATES, NUM_FIELDS},
+    config::{Config, StepBig},
     data_provider::{
         enumerator::{num_turing_machine_permutations, Enumerator},
-        enumerator_binary::EnumeratorType,
         DataProvider, DataProviderBatch, ResultDataProvider,
     },
     decider::{
-        decider_bouncer_128::DeciderBouncer128,
-        decider_cycler_small::DeciderCyclerSmall,
-        decider_engine,
         decider_result::{EndReason, PreDeciderCount},
-        pre_decider::{check_no_halt_transition, moves_only_right_status, PreDeciderRun},
-        Decider, DeciderConfig, DeciderStandard,
+        pre_decider::PreDeciderRun,
     },
-    machine_binary::{MachineBinary, MachineId},
-    machine_info::MachineInfo,
-    status::MachineStatus,
-    transition_binary::{TransitionBinary, TRANSITION_BINARY_HALT, TRANSITION_BINARY_UNDEFINED},
+    machine_binary::{MachineBinary, MachineId, State, Symbol},
+    transition_binary::{TransitionBinary, TRANSITION_BINARY_UNDEFINED},
 };
 
-const NUM_TR_PERMUTATIONS: usize = MAX_STATES * 4 + 1;
-const PRE_CHECK: &str = "Pre-Check";
-const BOUNCER_MAX_STEPS: StepBig = 300;
-const CYCLER_MAX_STEPS: StepBig = 100;
-
-#[derive(Debug)]
-pub struct MachineForStack {
+/// Machine of the tree with the configuration where the simulation continues.
+#[derive(Debug, Clone)]
+struct TnfNode {
     machine: MachineBinary,
-    field_no: usize,
-    // max_state: usize,
+    tape: VecDeque<u8>,
+    head: usize,
+    state: usize,
+    steps: StepBig,
+}
+
+/// Result of the simulation of a [TnfNode].
+enum TnfRun {
+    /// The transition in this field is undefined.
+    Undefined(usize),
+    StepLimit,
+}
+
+impl TnfNode {
+    /// Simulates until an undefined transition is reached or the step limit. The configuration is kept
+    /// before the undefined transition, so the extended machines continue from there.
+    fn run(&mut self, step_limit: StepBig) -> TnfRun {
+        while self.steps < step_limit {
+            let symbol = self.tape[self.head] as usize;
+            let tr = self
+                .machine
+                .transition(State::new(self.state), Symbol::new(symbol));
+            if tr.is_undefined() {
+                return TnfRun::Undefined(self.state * 2 + symbol);
+            }
+            self.tape[self.head] = tr.symbol_usize() as u8;
+            if tr.is_dir_right() {
+                self.head += 1;
+                if self.head == self.tape.len() {
+                    self.tape.push_back(0);
+                }
+            } else if self.head == 0 {
+                self.tape.push_front(0);
+            } else {
+                self.head -= 1;
+            }
+            self.state = tr.state() as usize;
+            self.steps += 1;
+        }
+        TnfRun::StepLimit
+    }
 }
 
-// TODO If max one halt condition, then use pre-decider before using cycler. quicker decision
-// Could also be interesting for cases that result in Non-Halt with the given data, e.g. only 0 or only R.
 #[derive(Debug)]
 pub struct EnumeratorTNF {
     /// The number of states used for this enumerator.
     n_states: usize,
-    /// The total number of machines to be enumerated. This is the number of Turing Machines for that n.
+    /// The total number of Turing machines for that n, only for information.
     n_machines: u64,
-    /// The limit of machines to enumerate or (if smaller) the maximum number of machines for the number of states. \
-    /// For performance reasons the limit is checked only after each batch.
+    /// The limit of machines to return. For performance reasons the limit is checked only after each batch.
     limit: u64,
-
-    /// The (4n) permutations for the transitions, kept as array on the stack, so it is in level 1 cache.
-    tr_permutations: [TransitionBinary; NUM_TR_PERMUTATIONS],
-    /// The transition set for one Turning machine. Will be adjusted each round and copied to the machine permutation.
-    machine: MachineBinary,
-    machine_stack: Vec<MachineBinary>,
-    machines_for_stack: VecDeque<MachineForStack>,
-    /// Number of used fields in the transition table (including the 2 empty fields for dummy state 0).
-    n_fields: usize,
-    /// Stores the id of the current transition permutation for the corresponding transition field.
-    fields: [usize; NUM_FIELDS],
-    fields_max_state: [usize; NUM_FIELDS],
-    field_no: usize,
-    last_field_id: usize,
-
+    step_limit: StepBig,
+    batch_size: usize,
     /// Batch no, increased for every call, starting with 0.
     batch_no: usize,
-    /// The reduced actual batch size (number of Turing machines enumerated in each call).
-    batch_size: usize,
-    /// Instead of limit, which is not checked anyhow, use id of last batch to check if limit is reached.
-    batch_last: usize,
-
-    /// Currently known max number of steps. All machines with less steps will be disregarded.
-    /// This also means this cannot be used to find machines with maximum number of ones.
-    max_steps: StepBig,
-    /// list of machines having max_steps
-    // TODO return value
-    machines_max_steps: Vec<MachineInfo>,
-    undecided_count: usize,
-    undecided_multiple_halts_count: usize,
-    undecided_machines_multiple_halts: Vec<MachineBinary>,
-    tnf_machines_count: u64,
-
-    decider_cycler: DeciderCyclerSmall,
-    decider_bouncer: DeciderBouncer128,
-    #[cfg(feature = "enable_csv_for_tnf")]
-    csv: CsvWriter,
-    #[cfg(feature = "debug_enumerator")]
-    max_for_stack: usize,
+    /// Depth first stack of the machines still to simulate.
+    stack: Vec<TnfNode>,
+    num_nodes: u64,
+    num_machines_returned: u64,
+    num_leaves: u64,
 }
 
 impl EnumeratorTNF {
     pub fn new(config: &Config) -> Self {
         let n_states = config.n_states();
-        assert!(n_states <= 7, "This enumerator can not create all permutations for {n_states} states as this would exceed u64:MAX permutations.");
-
+        assert!(n_states <= 7, "This enumerator can not handle {n_states} states as this would exceed u64:MAX permutations.");
         let n_machines = num_turing_machine_permutations(n_states) as u64;
-        let limit = config.machines_limit().min(n_machines);
-
-        let n_fields = n_states * 2 + 2;
-        let tr_permutations = Self::create_all_transition_permutations();
         let mut machine = MachineBinary::new_default(n_states);
-        // set all in set to the first variant
-        machine.transitions[2..n_fields].fill(TRANSITION_BINARY_UNDEFINED);
-        let mut machine_stack = Vec::new();
-        // Fill machines for field 2
-        for i in 6..9 {
-            if tr_permutations[i].is_dir_right() {
-                machine.transitions[2] = tr_permutations[i];
-                machine_stack.push(machine);
-            }
-        }
-
-        #[cfg(feature = "enable_csv_for_tnf")]
-        let mut csv = CsvWriter::try_new(config).expect("File Error");
-        #[cfg(feature = "enable_csv_for_tnf")]
-        {
-            use crate::transition_binary::{TRANSITION_0RA_BINARY_FIRST, TRANSITION_1RA};
-
-            machine.transitions[2] = TRANSITION_BINARY_UNDEFINED;
-            csv.write_machine(&machine, &MachineStatus::DecidedHaltField(1, 2), PRE_CHECK);
-            machine.transitions[2] = TRANSITION_0RA_BINARY_FIRST;
-            csv.write_machine(&machine, &MachineStatus::DecidedHaltField(1, 2), PRE_CHECK);
-            machine.transitions[2] = TRANSITION_1RA;
-            csv.write_machine(&machine, &MachineStatus::DecidedHaltField(1, 2), PRE_CHECK);
-        }
-
-        let config = Config::builder_from_config(config)
-            .step_limit_decider_cycler(CYCLER_MAX_STEPS)
-            .step_limit_decider_bouncer(BOUNCER_MAX_STEPS)
-            .build();
+        machine.transitions
+            [2..MachineBinary::last_used_field_id_in_transition_array_exclusive(n_states)]
+            .fill(TRANSITION_BINARY_UNDEFINED);
 
         Self {
             n_states,
             n_machines,
-            limit,
-            tr_permutations,
-            machine,
-            machine_stack,
-            machines_for_stack: VecDeque::new(),
-            n_fields,
-            fields: [0; NUM_FIELDS],
-            fields_max_state: [0; NUM_FIELDS],
-            field_no: 4,
-            last_field_id: MachineBinary::last_used_field_id_in_transition_array_exclusive(
-                n_states,
-            ),
+            limit: config.machines_limit(),
+            step_limit: config.enumerator_tnf_step_limit(),
+            batch_size: config.enumerator_reduced_batch_size_request(),
             batch_no: 0,
-            batch_last: 0,
-            // TODO batch size
-            batch_size: 10_000,
-            // avoid to much useless storing of machines in the beginning
-            max_steps: if n_states < 4 { 6 } else { 100 },
-            machines_max_steps: Vec::new(),
-            undecided_count: 0,
-            undecided_multiple_halts_count: 0,
-            undecided_machines_multiple_halts: Vec::new(),
-            tnf_machines_count: 3,
-            decider_cycler: DeciderCyclerSmall::new(&config),
-            decider_bouncer: DeciderBouncer128::new(&config),
-
-            #[cfg(feature = "enable_csv_for_tnf")]
-            csv,
-            #[cfg(feature = "debug_enumerator")]
-            max_for_stack: 0,
+            stack: vec![TnfNode {
+                machine,
+                tape: VecDeque::from([0]),
+                head: 0,
+                state: 1,
+                steps: 0,
+            }],
+            num_nodes: 0,
+            num_machines_returned: 0,
+            num_leaves: 0,
         }
     }
 
-    /// This creates all transition permutations for one field, e.g. \
-    /// BB1: 0LA, 1LA, 0RA, 1RA \
-    /// BB2: 0LA, 1LA, 0RA, 1RA, 0LB, 1LB, 0RB, 1RB \
-    /// The enumeration is
-    /// * undefined
-    /// * each state first: A, B, C, ... (allowing to skip all following if an not yet allowed state is reached and also \
-    /// have the same order for all states. This can be made into a constant.)
-    /// * each direction next: L, R
-    /// * symbol last: 0, 1
-    /// * 1RZ is not part of this list, it is set for last transition.
-    ///
-    /// The number can be calculated by (4 * n_states + 1), e.g. 21 for BB5. \
-    /// Keep this order as it is required by TNF tree enumeration.
-    pub fn create_all_transition_permutations() -> [TransitionBinary; NUM_TR_PERMUTATIONS] {
-        let mut transitions = [TRANSITION_BINARY_UNDEFINED; NUM_TR_PERMUTATIONS];
+    /// Number of machines of the tree simulated so far.
+    pub fn num_nodes(&self) -> u64 {
+        self.num_nodes
+    }
 
-        // all to left
-        let mut i = 1;
-        for state in 1..=MAX_STATES as u8 {
-            for direction in [1u8, 0u8] {
-                for symbol in [0u8, 1u8] {
-                    // tr as symbol, direction, next state
-                    let tr = [symbol, direction, state as u8];
-                    transitions[i] = TransitionBinary::try_new(tr).unwrap();
-                    i += 1;
+    /// Number of machines returned so far.
+    pub fn num_machines_returned(&self) -> u64 {
+        self.num_machines_returned
+    }
+
+    /// Number of machines returned which did not reach an undefined transition within the step limit.
+    pub fn num_leaves(&self) -> u64 {
+        self.num_leaves
+    }
+
+    /// Returns the transitions allowed for the undefined field of the machine in TNF order.
+    fn transitions_for_field(
+        &self,
+        machine: &MachineBinary,
+        field_id: usize,
+    ) -> Vec<TransitionBinary> {
+        let last_field_id =
+            MachineBinary::last_used_field_id_in_transition_array_exclusive(self.n_states);
+        // A0 is reached first, its transition defines state B
+        if field_id == 2 {
+            let state = self.n_states.min(2) as u8;
+            let symbols: &[u8] = if cfg!(feature = "enumerator_1RB_only") {
+                &[1]
+            } else {
+                &[0, 1]
+            };
+            return symbols
+                .iter()
+                .map(|&symbol| TransitionBinary::try_new([symbol, b'R', state]).unwrap())
+                .collect();
+        }
+        let max_state_used = machine.transitions[2..last_field_id]
+            .iter()
+            .filter(|tr| !tr.is_undefined())
+            .map(|tr| tr.state() as usize)
+            .max()
+            .unwrap_or(1);
+        let state_max = (max_state_used + 1).min(self.n_states);
+        let mut transitions = Vec::with_capacity(state_max * 4);
+        for state in 1..=state_max as u8 {
+            for direction in [b'L', b'R'] {
+                for symbol in [0, 1] {
+                    transitions
+                        .push(TransitionBinary::try_new([symbol, direction, state]).unwrap());
                 }
             }
         }
-
         transitions
     }
-
-    fn moves_only_right() {}
 }
 
 impl Enumerator for EnumeratorTNF {
@@ -209,208 +210,58 @@ impl Enumerator for EnumeratorTNF {
         panic!("The TNF Enumerator does not support direct batch no access.")
     }
 
-    // TODO Pre-Check: No transition going to higher levels (to a stop field), e.g.  0RB0LA_1LA1RB_------_------
     fn enumerate_permutation_batch_next(&mut self) -> (Vec<MachineId>, bool) {
-        // if self.n_states == 1 {
-        //     self.batch_no = 1;
-        //     return (super::enumerator::machines_for_n_states_1(), true);
-        // }
-        let mut permutations = Vec::with_capacity(self.batch_size);
-        // Alternative to return machines for n=1
-        if self.n_states == 1 {
-            self.batch_no = 1;
-            self.machine.transitions[2] = TRANSITION_BINARY_HALT;
-            let m = MachineId::new_no_id(self.machine);
-            self.machines_max_steps.push(MachineInfo::from_machine(
-                self.machine,
-                MachineStatus::DecidedHalt(1),
-            ));
-            permutations.push(m);
-            return (permutations, true);
-        }
-
-        // let check = MachineBinary::try_from("1RB0LC_1LC0RC_1LA0LC_------").unwrap();
-
-        loop {
-            // After this if self.machine must be filled with the correct machine
-            if self.machine_stack.is_empty() {
-                match self.machines_for_stack.pop_front() {
-                    Some(mfs) => {
-                        // put first machine in self.machine
-                        self.machine = mfs.machine;
-                        let max_state = (self.machine.max_state_used(self.last_field_id) + 1)
-                            .min(self.n_states);
-                        let last_perm = max_state * 4 + 1;
-                        for i in 2..last_perm {
-                            self.machine.transitions[mfs.field_no] = self.tr_permutations[i];
-                            self.machine_stack.push(self.machine);
-                        }
-                        self.machine.transitions[mfs.field_no] = self.tr_permutations[1];
+        let last_field_id =
+            MachineBinary::last_used_field_id_in_transition_array_exclusive(self.n_states);
+        let mut machines = Vec::with_capacity(self.batch_size);
+        while machines.len() < self.batch_size {
+            let Some(mut node) = self.stack.pop() else {
+                break;
+            };
+            self.num_nodes += 1;
+            match node.run(self.step_limit) {
+                TnfRun::Undefined(field_id) => {
+                    // the root halts in the first step, it is not relevant
+                    if node.steps > 0 {
+                        machines.push(MachineId::new_no_id(node.machine));
                     }
-                    // all machines created
-                    None => break,
-                }
-            } else {
-                self.machine = self.machine_stack.remove(0);
-            }
-
-            // check machine
-            self.tnf_machines_count += 1;
-            let tr_used = &self.machine.transitions[2..self.last_field_id];
-            let mut status = moves_only_right_status(tr_used);
-            #[cfg(not(feature = "enumerator_1RB_only"))]
-            if status == MachineStatus::NoDecision && self.machine.transitions[2].is_symbol_zero() {
-                status = crate::decider::pre_decider::has_only_zero_writes_status(tr_used);
-            }
-            // check has halt in case not all states are used
-            // if self.machine == check {
-            //     println!();
-            // }
-            let max_state = self.machine.max_state_used(self.last_field_id);
-            if max_state < self.n_states {
-                let tr_used = &self.machine.transitions[2..max_state * 2 + 2];
-                if check_no_halt_transition(tr_used) {
-                    status = MachineStatus::DecidedNonHalt(
-                        crate::status::NonHaltReason::NoHaltTransition,
-                    );
-                }
-            }
-            if status == MachineStatus::NoDecision {
-                // use Check_Cycler_Small
-                let has_two_undefined = self.machine.has_at_least_two_undefined(self.last_field_id);
-                if !has_two_undefined {
-                    // only one undefined (halt), use pre-decider checks for Non-Halt identification
-                }
-
-                // TODO faster if working with MachineBinary for small cycler
-                let m_id = MachineId::new_no_id(self.machine);
-                status = self.decider_cycler.decide_machine(&m_id);
-
-                // debug output
-                #[cfg(all(debug_assertions, feature = "debug_enumerator"))]
-                println!(
-                    "{:>3} {}: {status}, Cycler ",
-                    self.tnf_machines_count, self.machine
-                );
-
-                if let MachineStatus::Undecided(_, _, _) = status {
-                    status = self.decider_bouncer.decide_machine(&m_id);
-                    #[cfg(all(debug_assertions, feature = "debug_enumerator"))]
-                    println!(
-                        "{:>3} {}: {status}, Bouncer ",
-                        self.tnf_machines_count, self.machine
-                    );
-                    #[cfg(feature = "enable_csv_for_tnf")]
-                    self.csv.write_machine(
-                        &self.machine,
-                        &status,
-                        DeciderBouncer128::decider_id().name,
-                    );
-                } else {
-                    #[cfg(feature = "enable_csv_for_tnf")]
-                    self.csv.write_machine(
-                        &self.machine,
-                        &status,
-                        DeciderCyclerSmall::decider_id().name,
-                    );
-                }
-            } else {
-                // debug output
-                #[cfg(feature = "enable_csv_for_tnf")]
-                {
-                    self.csv.write_machine(&self.machine, &status, PRE_CHECK);
-                }
-                #[cfg(all(debug_assertions, feature = "debug_enumerator"))]
-                println!(
-                    "{:>3} {}: {}, {PRE_CHECK}",
-                    self.tnf_machines_count, self.machine, status
-                );
-            }
-
-            #[cfg(all(debug_assertions, feature = "debug_enumerator"))]
-            if self.tnf_machines_count % 512 == 0 {
-                println!();
-            }
-
-            match status {
-                // Halts, machine is put on the stack and later expanded on the stop field with all permutations of that field.
-                MachineStatus::DecidedHaltField(steps, field_no) => {
-                    // println!("Result Cycler {count_m}: {} {status}", self.machine);
-                    if steps >= self.max_steps {
-                        if steps > self.max_steps {
-                            self.max_steps = steps;
-                            self.machines_max_steps.clear();
-                        }
-                        let mut m = self.machine;
-                        m.transitions[field_no] = TRANSITION_BINARY_HALT;
-                        self.machines_max_steps.push(MachineInfo::new(m, status));
-                    }
-                    // If only one undefined is left, then that one must be the halt condition.
-                    // Iterating would result in machines without halt condition.
-                    if self.machine.has_at_least_two_undefined(self.last_field_id) {
-                        #[cfg(all(debug_assertions, feature = "debug_enumerator"))]
-                        {
-                            let mut m = self.machine;
-                            m.transitions[field_no] = TRANSITION_BINARY_HALT;
-                            println!("to stack {}: {m}", self.machines_for_stack.len());
-                        }
-                        self.machines_for_stack.push_back(MachineForStack {
-                            machine: self.machine,
-                            field_no,
-                        });
-                        #[cfg(all(feature = "debug_enumerator", feature = "enable_csv_for_tnf"))]
+                    if node.machine.has_at_least_two_undefined(last_field_id) {
+                        // reversed, so the first transition is simulated next
+                        for tr in self
+                            .transitions_for_field(&node.machine, field_id)
+                            .into_iter()
+                            .rev()
                         {
-                            if self.max_for_stack < self.machines_for_stack.len() {
-                                self.max_for_stack = self.machines_for_stack.len();
-                                self.csv
-                                    .writeln(&format!("Max stack = {}", self.max_for_stack));
-                            }
+                            let mut child = node.clone();
+                            child.machine.transitions[field_id] = tr;
+                            self.stack.push(child);
                         }
                     }
                 }
-
-                // Non-Halt: In this case the machine is just irrelevant and the tree is cut here as it is not further pursued.
-                MachineStatus::DecidedNonHalt(_) => {}
-
-                MachineStatus::Undecided(_, _, _) => {
-                    self.undecided_count += 1;
-                    if self.machine.has_at_least_two_undefined(
-                        MachineBinary::last_used_field_id_in_transition_array_exclusive(max_state),
-                    ) {
-                        self.undecided_multiple_halts_count += 1;
-                        if self.undecided_machines_multiple_halts.len() < 10 {
-                            self.undecided_machines_multiple_halts.push(self.machine);
-                        }
-                        #[cfg(all(debug_assertions, feature = "debug_enumerator"))]
-                        {
-                            println!("Machine Undecided: {}", self.machine);
-                            println!("This is an erroneous situation, as the halt is not reached but could potentially, requiring more machines to create.");
-                            // todo!("Undecided with more than one halt condition.");
-                        }
-                    }
-                    // println!("Result Cycler {count_m}: {} {status}", self.machine);
-                    permutations.push(MachineId::new_no_id(self.machine))
+                TnfRun::StepLimit => {
+                    self.num_leaves += 1;
+                    machines.push(MachineId::new_no_id(node.machine));
                 }
-                _ => panic!("Status not allowed: {status}"),
             }
         }
-
-        println!("\nResult: \n{}", &self);
+        self.num_machines_returned += machines.len() as u64;
         self.batch_no += 1;
-        (permutations, true)
+        let is_last_batch = self.stack.is_empty() || self.num_machines_returned >= self.limit;
+
+        (machines, is_last_batch)
     }
 
     fn limit(&self) -> u64 {
         self.limit
     }
 
+    /// The pre-deciders are not used, the tree is cut by the rules of the TNF.
     fn pre_decider_count(&self) -> PreDeciderCount {
-        // PreDeciderCount::default();
-        todo!()
+        PreDeciderCount::default()
     }
 
     fn num_eliminated(&self) -> u64 {
-        todo!()
+        0
     }
 }
 
@@ -429,23 +280,24 @@ impl DataProvider for EnumeratorTNF {
         Ok(DataProviderBatch {
             // batch no is already set to next batch
             batch_no: self.batch_no - 1,
+            n_states: self.n_states,
             machines,
-            // pre_decider_count: self.pre_decider_count_batch,
-            // TODO count
             pre_decider_count: None,
+            pre_decider_timer: Default::default(),
             end_reason,
         })
     }
 
     fn batch_size(&self) -> usize {
-        todo!()
+        self.batch_size
     }
 
+    /// The number of batches is not known in advance, this returns the batches created so far.
     fn num_batches(&self) -> usize {
-        // TODO unclear what this controls
         self.batch_no
     }
 
+    /// The number of machines is not known in advance, this returns the limit.
     fn num_machines_to_process(&self) -> u64 {
         self.limit
     }
@@ -459,128 +311,77 @@ impl Display for EnumeratorTNF {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "n_states: {}", self.n_states)?;
         writeln!(f, "machines total: {}", self.n_machines)?;
-        writeln!(f, "machines in TNF tree: {}", self.tnf_machines_count)?;
-        writeln!(f, "limit: {}", self.limit)?;
-        let mut t = Vec::new();
-        for i in 0..self.n_states * 4 {
-            t.push(self.tr_permutations[i].to_string());
-        }
-        writeln!(f, "Transitions: {}", t.join(", "))?;
-        writeln!(f, "Max Steps: {}", self.max_steps)?;
-        for m in self.machines_max_steps.iter() {
-            writeln!(f, "Max Machine: {m}")?;
-        }
-        writeln!(f, "Undecided machines: {}", self.undecided_count)?;
+        writeln!(f, "machines in TNF tree: {}", self.num_nodes)?;
+        writeln!(f, "machines returned: {}", self.num_machines_returned)?;
         writeln!(
             f,
-            "Undecided machines which could stop and need more sub-machines: {}",
-            self.undecided_multiple_halts_count
+            "  leaves at step limit {}: {}",
+            self.step_limit, self.num_leaves
         )?;
-        for m in self.undecided_machines_multiple_halts.iter() {
-            writeln!(f, "check: {m}")?;
-        }
-
-        Ok(())
+        write!(f, "limit: {}", self.limit)
     }
 }
 
-const CSV_HEADER: &str = "machine,status,decider";
-#[derive(Debug)]
-
-pub struct CsvWriter {
-    n_states: usize,
-    /// Main path without sub directory
-    html_out_path: String,
-    // / Sub-dir. This is mandatory, the option is only to check if it is set.
-    // sub_dir: Option<String>,
-    /// full path, set_sub_dir to set this path, mandatory.
-    file_name: String,
-    buf_writer: BufWriter<File>,
-    count_line: u64,
-}
-
-impl CsvWriter {
-    pub fn try_new(config: &Config) -> Result<Self, std::io::Error> {
-        let n_states = config.n_states();
-        let file_name = format!("BB{n_states} enumeration.csv");
-        let html_out_path = config.config_toml().html_out_path().to_string();
-        let p = std::path::Path::new(&html_out_path).join(&file_name);
-        let file = File::create(&p)?;
-        let mut buf_writer = BufWriter::new(file);
-        writeln!(buf_writer, "{CSV_HEADER}")?;
-
-        Ok(Self {
-            n_states,
-            html_out_path,
-            file_name,
-            buf_writer,
-            count_line: 0,
-        })
-    }
-
-    pub fn writeln(&mut self, text: &str) {
-        self.count_line += 1;
-        writeln!(self.buf_writer, "{text}").expect("File write error");
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{
+        config::CoreUsage,
+        decider::{decider_engine, DeciderStandard},
+        machine_runner::MachineRunner,
+    };
+
+    fn enumerate_all(n_states: usize) -> (Vec<MachineId>, EnumeratorTNF) {
+        let config = Config::new_default(n_states);
+        let mut enumerator = EnumeratorTNF::new(&config);
+        let mut machines = Vec::new();
+        loop {
+            let (batch, is_last_batch) = enumerator.enumerate_permutation_batch_next();
+            machines.extend(batch);
+            if is_last_batch {
+                return (machines, enumerator);
+            }
+        }
     }
 
-    pub fn write_machine(
-        &mut self,
-        machine: &MachineBinary,
-        status: &MachineStatus,
-        decider_name: &str,
-    ) {
-        self.count_line += 1;
-        if self.count_line % 1024 * 128 == 0 {
-            println!("CSV file writing: line {}", self.count_line);
+    #[test]
+    fn enumerator_tnf_max_steps() {
+        for (n_states, steps_max) in [(2, 6), (3, 21), (4, 107)] {
+            let (machines, enumerator) = enumerate_all(n_states);
+            assert_eq!(machines.len() as u64, enumerator.num_machines_returned());
+            // each machine is returned once
+            let unique: HashSet<_> = machines
+                .iter()
+                .map(|m| m.to_standard_tm_text_format())
+                .collect();
+            assert_eq!(unique.len(), machines.len());
+            let mut max = 0;
+            for m in machines.iter() {
+                let mut runner = MachineRunner::new(m.machine());
+                let steps = runner.steps().take(200).count() as u64;
+                if runner.is_halted() {
+                    max = max.max(steps);
+                }
+            }
+            assert_eq!(max, steps_max, "BB{n_states}");
         }
-        let txt = format!("{machine},{status},{decider_name}");
-        writeln!(self.buf_writer, "{txt}").expect("File write error");
+        // 6,975,757,441 permutations
+        let (_, enumerator) = enumerate_all(4);
+        assert!(enumerator.num_nodes() < 1_000_000);
     }
-}
-
-pub fn test_enumerator_tnf_simple() {
-    let n_states = 4;
-    let config_1 = Config::builder(n_states)
-        // 10_000_000_000 for BB4
-        .machine_limit(1000_000_000_000)
-        // .limit_machines_undecided(200)
-        .write_html_file(true)
-        .build();
 
-    let tnf = EnumeratorTNF::new(&config_1);
-    println!("Enumerator {tnf}");
-}
-
-pub fn test_enumerator_tnf(n_states: usize) {
-    let config_1 = Config::builder(n_states)
-        // 10_000_000_000 for BB4
-        .machine_limit(1000_000_000_000)
-        .limit_machines_undecided(200)
-        .write_html_file_undecided(true)
-        .build();
-
-    // let dc_cycler_1 = DeciderStandard::Cycler.decider_config(&config_1);
-    // let decider_config = vec![
-    //     dc_cycler_1,
-    //     // dc_bouncer_1,
-    //     // dc_cycler_2,
-    //     // dc_hold,
-    // ];
-
-    let (config, config_cycler_2) = DeciderConfig::standard_config(&config_1);
-    let decider_config = DeciderStandard::standard_decider_for_config(&config, &config_cycler_2);
-
-    let decider_last = 4;
-    let result = decider_engine::run_decider_chain_gen(
-        &decider_config[0..decider_last],
-        EnumeratorType::EnumeratorTNF,
-        CoreUsage::SingleCore,
-    );
-
-    println!("\n{}", result.to_string_with_duration());
-    // if let Some(m_undecided) = result.machines_undecided_sorted() {
-    //     for m in m_undecided.iter().take(10) {
-    //         println!("Undecided {}", m);
-    //     }
-    // }
+    #[test]
+    fn enumerator_tnf_decider_chain() {
+        let config = Config::new_default(4);
+        let decider_config = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Bouncer128.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let result =
+            decider_engine::run_decider_chain_tnf(&decider_config, CoreUsage::MultiCore(0));
+        assert_eq!(107, result.steps_max());
+    }
 }
@@ -0,0 +1,209 @@
+//! Collects the machines left undecided by one stage of a run, so they can be the data provider of the next
+//! stage, see [run_decider_stages](crate::decider::decider_engine::run_decider_stages). \
+//! The machines are kept in memory up to the spill threshold. Above it all machines are written to a temporary
+//! text file (Standard TM Text Format with id, see [text_file](crate::data_provider::text_file)), which is
+//! read again by [DataProviderSpool] and deleted when the data provider is dropped.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    data_provider::{
+        text_file::DataProviderTextFile, DataProvider, DataProviderBatch, ResultDataProvider,
+    },
+    decider::{decider_result::EndReason, pre_decider::PreDeciderRun},
+    machine_binary::MachineId,
+};
+
+/// Machines kept in memory by default, about 100 MB for BB5.
+pub const SPILL_THRESHOLD_DEFAULT: usize = 1_000_000;
+const BATCH_SIZE: usize = 1000;
+
+/// Counter for unique file names of the spill files within this process.
+static SPILL_FILE_NO: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+pub struct MachineSpool {
+    machines: Vec<MachineId>,
+    spill_threshold: usize,
+    /// Spill file, once the threshold was exceeded.
+    spill: Option<(PathBuf, BufWriter<File>)>,
+    num_machines: u64,
+}
+
+impl MachineSpool {
+    /// Creates an empty spool which keeps up to spill_threshold machines in memory.
+    pub fn new(spill_threshold: usize) -> Self {
+        Self {
+            machines: Vec::new(),
+            spill_threshold,
+            spill: None,
+            num_machines: 0,
+        }
+    }
+
+    /// Adds the machines, writing all machines to the spill file once the threshold is exceeded.
+    pub fn push(&mut self, machines: &[MachineId]) -> io::Result<()> {
+        self.num_machines += machines.len() as u64;
+        if self.spill.is_none() && self.machines.len() + machines.len() > self.spill_threshold {
+            let path = std::env::temp_dir().join(format!(
+                "bb_challenge_spool_{}_{}.txt",
+                std::process::id(),
+                SPILL_FILE_NO.fetch_add(1, Ordering::Relaxed)
+            ));
+            let mut writer = BufWriter::new(File::create(&path)?);
+            write_machines(&mut writer, &self.machines)?;
+            self.machines = Vec::new();
+            self.spill = Some((path, writer));
+        }
+        match self.spill.as_mut() {
+            Some((_, writer)) => write_machines(writer, machines),
+            None => {
+                self.machines.extend_from_slice(machines);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn num_machines(&self) -> u64 {
+        self.num_machines
+    }
+
+    /// Returns true if the machines were written to the spill file.
+    pub fn is_spilled(&self) -> bool {
+        self.spill.is_some()
+    }
+
+    /// Returns the data provider for the collected machines.
+    pub fn into_data_provider(mut self) -> io::Result<DataProviderSpool> {
+        let source = match self.spill.take() {
+            Some((path, mut writer)) => {
+                writer.flush()?;
+                drop(writer);
+                let text_file = DataProviderTextFile::try_new(&path)?.with_batch_size(BATCH_SIZE);
+                SpoolSource::File(text_file, path)
+            }
+            None => SpoolSource::Memory(std::mem::take(&mut self.machines), 0),
+        };
+        Ok(DataProviderSpool {
+            num_machines: self.num_machines,
+            batch_no: 0,
+            source,
+        })
+    }
+}
+
+impl Drop for MachineSpool {
+    fn drop(&mut self) {
+        if let Some((path, _)) = &self.spill {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn write_machines(writer: &mut impl Write, machines: &[MachineId]) -> io::Result<()> {
+    for m in machines.iter() {
+        writeln!(writer, "{} {}", m.id(), m.to_standard_tm_text_format())?;
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+enum SpoolSource {
+    /// Machines and position of the next batch.
+    Memory(Vec<MachineId>, usize),
+    File(DataProviderTextFile, PathBuf),
+}
+
+/// Data provider of the machines of a [MachineSpool].
+#[derive(Debug)]
+pub struct DataProviderSpool {
+    num_machines: u64,
+    batch_no: usize,
+    source: SpoolSource,
+}
+
+impl DataProvider for DataProviderSpool {
+    fn name(&self) -> &str {
+        "Undecided machines of previous stage"
+    }
+
+    fn machine_batch_next(&mut self) -> ResultDataProvider {
+        let mut batch = match &mut self.source {
+            SpoolSource::Memory(machines, pos) => {
+                let mut batch = DataProviderBatch::new(self.batch_no);
+                let end = (*pos + BATCH_SIZE).min(machines.len());
+                batch.machines = machines[*pos..end].to_vec();
+                *pos = end;
+                if end == machines.len() {
+                    batch.end_reason = EndReason::IsLastBatch;
+                }
+                batch
+            }
+            SpoolSource::File(text_file, _) => text_file.machine_batch_next()?,
+        };
+        batch.batch_no = self.batch_no;
+        self.batch_no += 1;
+
+        Ok(batch)
+    }
+
+    fn batch_size(&self) -> usize {
+        BATCH_SIZE
+    }
+
+    fn num_batches(&self) -> usize {
+        (self.num_machines as usize).div_ceil(BATCH_SIZE).max(1)
+    }
+
+    fn num_machines_to_process(&self) -> u64 {
+        self.num_machines
+    }
+
+    fn requires_pre_decider_check(&self) -> PreDeciderRun {
+        PreDeciderRun::DoNotRun
+    }
+}
+
+impl Drop for DataProviderSpool {
+    fn drop(&mut self) {
+        if let SpoolSource::File(_, path) = &self.source {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_binary::NotableMachineBinary;
+
+    #[test]
+    fn machine_spool_spills_to_file() {
+        let machines: Vec<MachineId> = (0..2500)
+            .map(|id| MachineId::new(id, NotableMachineBinary::BB4Max.machine()))
+            .collect();
+        for spill_threshold in [10_000, 1000] {
+            let mut spool = MachineSpool::new(spill_threshold);
+            for chunk in machines.chunks(700) {
+                spool.push(chunk).unwrap();
+            }
+            assert_eq!(spool.is_spilled(), spill_threshold < machines.len());
+            let mut data_provider = spool.into_data_provider().unwrap();
+            assert_eq!(data_provider.num_batches(), 3);
+            let mut read = Vec::new();
+            loop {
+                let batch = data_provider.machine_batch_next().unwrap();
+                read.extend(batch.machines);
+                if matches!(batch.end_reason, EndReason::IsLastBatch) {
+                    break;
+                }
+            }
+            assert_eq!(read, machines);
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! Data provider reading machines from a text file, one machine per line in Standard TM Text Format. \
+//! A machine can be annotated with the expected status, which allows to keep test corpora in files instead of
+//! `Vec<(&str, &str)>` tuples in the test code. [check_text_file] runs a decider chain on the file and
+//! reports the machines which do not match their annotation.
+//!
+//! Line format: `[id] machine [expected] [# comment]`, empty lines and lines starting with '#' are skipped.
+//! - `id`: optional leading id column, e.g. the id in a list exported by another tool. Without id the line
+//!   number is used as id.
+//! - `halt` or `halt <steps>`: the machine must be decided as halting (after the given steps)
+//! - `non_halt`: the machine must be decided as non-halting
+//! - `undecided`: the machine must not be decided
+//! # Example
+//! ```text
+//! # BB4 Max
+//! 1RB1LB_1LA0LC_1RZ1LD_1RD0RA halt 107
+//! 1RB0LB_1LA0LC_---1RD_0RA0RA non_halt   # bouncer
+//! 7383 1RB0RC_1LC1RA_1RD1LB_0LE---_1LA0RE
+//! ```
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufRead, BufReader, Lines},
+    path::Path,
+};
+
+use crate::{
+    config::StepBig,
+    data_provider::{DataProvider, DataProviderBatch, DataProviderError, ResultDataProvider},
+    decider::{
+        decider_engine::decide_machines_statuses, decider_result::EndReason,
+        pre_decider::PreDeciderRun, DeciderConfig,
+    },
+    machine_binary::{MachineBinary, MachineId},
+    status::MachineStatus,
+};
+
+const BATCH_SIZE: usize = 1000;
+
+/// Expected status of an annotated machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedStatus {
+    /// Halts, optionally after the given steps.
+    Halt(Option<StepBig>),
+    NonHalt,
+    Undecided,
+}
+
+impl ExpectedStatus {
+    pub fn matches(&self, status: &MachineStatus) -> bool {
+        match (self, status) {
+            (
+                ExpectedStatus::Halt(steps),
+                MachineStatus::DecidedHalt(s)
+                | MachineStatus::DecidedHaltField(s, _)
+                | MachineStatus::DecidedHaltDetail(s, _, _),
+            ) => steps.is_none_or(|steps| steps == *s),
+            (ExpectedStatus::NonHalt, MachineStatus::DecidedNonHalt(_)) => true,
+            (ExpectedStatus::Undecided, MachineStatus::Undecided(_, _, _)) => true,
+            _ => false,
+        }
+    }
+}
+
+impl TryFrom<&str> for ExpectedStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let parts: Vec<&str> = value.split_whitespace().collect();
+        match parts.as_slice() {
+            ["halt"] => Ok(ExpectedStatus::Halt(None)),
+            ["halt", steps] => steps
+                .parse()
+                .map(|steps| ExpectedStatus::Halt(Some(steps)))
+                .map_err(|e| format!("halt steps '{steps}': {e}")),
+            ["non_halt"] => Ok(ExpectedStatus::NonHalt),
+            ["undecided"] => Ok(ExpectedStatus::Undecided),
+            _ => Err(format!("unknown expected status '{value}'")),
+        }
+    }
+}
+
+impl Display for ExpectedStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExpectedStatus::Halt(None) => write!(f, "halt"),
+            ExpectedStatus::Halt(Some(steps)) => write!(f, "halt {steps}"),
+            ExpectedStatus::NonHalt => write!(f, "non_halt"),
+            ExpectedStatus::Undecided => write!(f, "undecided"),
+        }
+    }
+}
+
+/// A machine of the text file, the id of the machine is the id of the id column or its line number.
+#[derive(Debug, Clone, Copy)]
+pub struct TextFileMachine {
+    pub machine: MachineId,
+    pub expected: Option<ExpectedStatus>,
+    pub line_no: u64,
+}
+
+/// Returns the text of the line without comment, empty if the line holds no machine.
+fn line_text(line: &str) -> &str {
+    match line.find('#') {
+        Some(pos) => &line[..pos],
+        None => line,
+    }
+    .trim()
+}
+
+/// Parses a line of the text file, None if the line holds no machine. Errors contain the line number.
+fn parse_line(line: &str, line_no: u64, file_path: &Path) -> io::Result<Option<TextFileMachine>> {
+    let mut text = line_text(line);
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let invalid = |e: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}, line {line_no}: {e}", file_path.display()),
+        )
+    };
+    // optional id column, a machine never consists of digits only
+    let mut id = line_no;
+    if let Some((first, rest)) = text.split_once(char::is_whitespace) {
+        if let Ok(id_column) = first.parse::<u64>() {
+            id = id_column;
+            text = rest.trim_start();
+        }
+    }
+    let (tm, expected) = match text.split_once(char::is_whitespace) {
+        Some((tm, expected)) => (
+            tm,
+            Some(ExpectedStatus::try_from(expected).map_err(invalid)?),
+        ),
+        None => (text, None),
+    };
+    let machine =
+        MachineBinary::try_from_standard_tm_text_format(tm).map_err(|e| invalid(e.to_string()))?;
+
+    Ok(Some(TextFileMachine {
+        machine: MachineId::new(id, machine),
+        expected,
+        line_no,
+    }))
+}
+
+/// Reads all machines of the text file. Errors contain the line number.
+pub fn read_text_file(file_path: impl AsRef<Path>) -> io::Result<Vec<TextFileMachine>> {
+    let file_path = file_path.as_ref();
+    let reader = BufReader::new(File::open(file_path)?);
+    let mut machines = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        if let Some(machine) = parse_line(&line?, i as u64 + 1, file_path)? {
+            machines.push(machine);
+        }
+    }
+
+    Ok(machines)
+}
+
+/// Data provider for the machines of a text file, see [module documentation](self).
+/// The machines are read batch by batch, so large lists exported from other tools are not held in memory.
+/// The pre-decider is not run, so the deciders see exactly the machines of the file.
+#[derive(Debug)]
+pub struct DataProviderTextFile {
+    lines: Lines<BufReader<File>>,
+    file_path: std::path::PathBuf,
+    line_no: u64,
+    num_machines: u64,
+    num_read: u64,
+    batch_no: usize,
+    batch_size: usize,
+    run_pre_decider: PreDeciderRun,
+    name: String,
+}
+
+impl DataProviderTextFile {
+    /// Opens the file and counts its machines, the machines are parsed when the batches are read.
+    pub fn try_new(file_path: impl AsRef<Path>) -> io::Result<Self> {
+        let file_path = file_path.as_ref();
+        let name = format!("Text File: {}", file_path.display());
+        let mut num_machines = 0;
+        for line in BufReader::new(File::open(file_path)?).lines() {
+            if !line_text(&line?).is_empty() {
+                num_machines += 1;
+            }
+        }
+        Ok(Self {
+            lines: BufReader::new(File::open(file_path)?).lines(),
+            file_path: file_path.to_path_buf(),
+            line_no: 0,
+            num_machines,
+            num_read: 0,
+            batch_no: 0,
+            batch_size: BATCH_SIZE,
+            run_pre_decider: PreDeciderRun::DoNotRun,
+            name,
+        })
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Runs the pre-decider on the machines, e.g. if the file contains unfiltered machines.
+    pub fn with_pre_decider(mut self, run_pre_decider: PreDeciderRun) -> Self {
+        self.run_pre_decider = run_pre_decider;
+        self
+    }
+
+    /// Reads the next machines up to the batch size. Returns true if the end of the file is reached.
+    fn read_machines(&mut self, machines: &mut Vec<MachineId>) -> io::Result<bool> {
+        while machines.len() < self.batch_size {
+            let Some(line) = self.lines.next() else {
+                return Ok(true);
+            };
+            self.line_no += 1;
+            if let Some(m) = parse_line(&line?, self.line_no, &self.file_path)? {
+                machines.push(m.machine);
+                self.num_read += 1;
+            }
+        }
+        Ok(self.num_read >= self.num_machines)
+    }
+}
+
+impl DataProvider for DataProviderTextFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn machine_batch_next(&mut self) -> ResultDataProvider {
+        let mut batch = DataProviderBatch::new(self.batch_no);
+        // n_states stays 0, the file may contain machines of different sizes
+        let mut machines = Vec::with_capacity(self.batch_size);
+        match self.read_machines(&mut machines) {
+            Ok(is_end) => {
+                if is_end {
+                    batch.end_reason = EndReason::IsLastBatch;
+                }
+            }
+            Err(e) => {
+                batch.end_reason = EndReason::Error(0, e.to_string());
+                return Err(Box::new(DataProviderError {
+                    name: self.name.clone(),
+                    batch: Some(batch),
+                    msg: e.to_string(),
+                    ..Default::default()
+                }));
+            }
+        }
+        batch.machines = machines;
+        self.batch_no += 1;
+
+        Ok(batch)
+    }
+
+    fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    fn num_batches(&self) -> usize {
+        (self.num_machines as usize)
+            .div_ceil(self.batch_size)
+            .max(1)
+    }
+
+    fn num_machines_to_process(&self) -> u64 {
+        self.num_machines
+    }
+
+    fn requires_pre_decider_check(&self) -> PreDeciderRun {
+        self.run_pre_decider
+    }
+}
+
+/// An annotated machine with another status than expected.
+#[derive(Debug, Clone)]
+pub struct ExpectationMismatch {
+    pub line_no: u64,
+    pub machine: MachineId,
+    pub expected: ExpectedStatus,
+    pub status: MachineStatus,
+}
+
+/// Result of [check_text_file].
+#[derive(Debug, Default)]
+pub struct TextFileCheck {
+    pub num_machines: usize,
+    /// Machines with an expected status.
+    pub num_annotated: usize,
+    pub mismatches: Vec<ExpectationMismatch>,
+}
+
+impl TextFileCheck {
+    /// Panics with the list of mismatches, for use in tests.
+    pub fn assert_ok(&self) {
+        assert!(self.mismatches.is_empty(), "{self}");
+    }
+}
+
+impl Display for TextFileCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Text file check: {} machines, {} annotated, {} mismatches",
+            self.num_machines,
+            self.num_annotated,
+            self.mismatches.len()
+        )?;
+        for m in self.mismatches.iter() {
+            writeln!(
+                f,
+                "   line {}: {}, expected {}, got {}",
+                m.line_no,
+                m.machine.to_standard_tm_text_format(),
+                m.expected,
+                m.status
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the decider chain on all machines of the text file and compares the status of each annotated machine
+/// with its expected status. Each decider runs on the machines left undecided by the previous deciders.
+pub fn check_text_file(
+    decider_configs: &[DeciderConfig],
+    file_path: impl AsRef<Path>,
+) -> io::Result<TextFileCheck> {
+    let file_machines = read_text_file(file_path)?;
+    let machines: Vec<MachineId> = file_machines.iter().map(|m| m.machine).collect();
+    let statuses = decide_machines_statuses(decider_configs, &machines)
+        .map_err(|e| io::Error::other(format!("Decider chain failed: {e}")))?;
+
+    let mut check = TextFileCheck {
+        num_machines: file_machines.len(),
+        ..Default::default()
+    };
+    for (fm, status) in file_machines.iter().zip(statuses) {
+        let Some(expected) = fm.expected else {
+            continue;
+        };
+        check.num_annotated += 1;
+        if !expected.matches(&status) {
+            check.mismatches.push(ExpectationMismatch {
+                line_no: fm.line_no,
+                machine: fm.machine,
+                expected,
+                status,
+            });
+        }
+    }
+
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        decider::{
+            decider_engine::batch_run_decider_chain_data_provider_single_thread, DeciderStandard,
+        },
+    };
+
+    const CORPUS_DECIDERS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test_corpus/deciders.txt");
+
+    #[test]
+    fn text_file_corpus_matches_expectations() {
+        let config = Config::new_default(5);
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Bouncer128.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let check = check_text_file(&deciders, CORPUS_DECIDERS).unwrap();
+        assert!(check.num_annotated > 0);
+        check.assert_ok();
+
+        let data_provider = DataProviderTextFile::try_new(CORPUS_DECIDERS).unwrap();
+        assert_eq!(
+            data_provider.num_machines_to_process(),
+            check.num_machines as u64
+        );
+        let result = batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider);
+        assert_eq!(result.end_reason, EndReason::AllMachinesChecked);
+    }
+
+    #[test]
+    fn text_file_reports_mismatch() {
+        let dir = std::env::temp_dir().join("bb_challenge_text_file_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("corpus.txt");
+        std::fs::write(
+            &path,
+            "# BB2 Max\n1RB1LB_1LA1RZ halt 7\n\n1RB1LB_1LA1RZ halt 6 # correct\n1RB1LB_1LA1RZ\n",
+        )
+        .unwrap();
+        let config = Config::new_default(2);
+        let deciders = [DeciderStandard::Hold.decider_config(&config)];
+        let check = check_text_file(&deciders, &path).unwrap();
+
+        assert_eq!((check.num_machines, check.num_annotated), (3, 2));
+        assert_eq!(check.mismatches.len(), 1);
+        assert_eq!(check.mismatches[0].line_no, 2);
+        assert_eq!(check.mismatches[0].status.steps(), Some(6));
+        assert!(check
+            .to_string()
+            .contains("line 2: 1RB1LB_1LA1RZ, expected halt 7"));
+
+        std::fs::write(&path, "1RB1LB_1LA1RZ halts\n").unwrap();
+        let e = read_text_file(&path).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(e
+            .to_string()
+            .contains("line 1: unknown expected status 'halts'"));
+    }
+
+    #[test]
+    fn text_file_data_provider_id_column() {
+        let dir = std::env::temp_dir().join("bb_challenge_text_file_id_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("holdouts.txt");
+        std::fs::write(
+            &path,
+            "# holdouts\n7383 1RB0RC_1LC1RA_1RD1LB_0LE---_1LA0RE\n\n1RB1LB_1LA1RZ halt 6\n\
+             42   1RB0LB_1LA0LC_---1RD_0RA0RA non_halt # bouncer\n",
+        )
+        .unwrap();
+        let file_machines = read_text_file(&path).unwrap();
+        let ids: Vec<u64> = file_machines.iter().map(|m| m.machine.id()).collect();
+        assert_eq!(ids, [7383, 4, 42]);
+        assert_eq!(file_machines[2].line_no, 5);
+        assert_eq!(file_machines[2].expected, Some(ExpectedStatus::NonHalt));
+
+        let mut data_provider = DataProviderTextFile::try_new(&path)
+            .unwrap()
+            .with_batch_size(2);
+        assert_eq!(data_provider.num_machines_to_process(), 3);
+        assert_eq!(data_provider.num_batches(), 2);
+        let batch = data_provider.machine_batch_next().unwrap();
+        assert_eq!(batch.machines.len(), 2);
+        assert_eq!(batch.end_reason, EndReason::None);
+        let batch = data_provider.machine_batch_next().unwrap();
+        assert_eq!(batch.machines[0].id(), 42);
+        assert_eq!(batch.end_reason, EndReason::IsLastBatch);
+
+        std::fs::write(&path, "1 1RB1LB_1LA1RZ\n2 1RB1LB_1LA1RZ halts\n").unwrap();
+        let mut data_provider = DataProviderTextFile::try_new(&path).unwrap();
+        let e = data_provider.machine_batch_next().unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(e.msg.contains("line 2"));
+    }
+}
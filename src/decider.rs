@@ -1,8 +1,17 @@
+pub mod batch_stepper;
+pub mod cycler_offload;
+#[cfg(feature = "gpu")]
+pub mod cycler_offload_gpu;
+pub mod decider_backward_reasoning;
 pub mod decider_bouncer_128;
+pub mod decider_bouncer_proof;
 // pub mod decider_bouncer_128_speed_up;
 // pub mod decider_bouncer_apex;
 pub mod pre_decider;
 // // pub mod decider_bouncer_v1; old decider with different logic, may contain some re-usable code
+pub mod decider_compare;
+pub mod decider_counter;
+pub mod decider_ctl;
 pub mod decider_cycler;
 pub mod decider_cycler_small;
 // pub mod decider_data;
@@ -11,29 +20,53 @@ pub mod decider_data_128;
 pub mod decider_data_long;
 pub mod decider_data_macro;
 pub mod decider_engine;
+#[cfg(feature = "async")]
+pub mod decider_engine_async;
+pub mod decider_generic;
 pub mod decider_halt_long;
 pub mod decider_halt_macro;
+pub mod decider_halt_poll;
+pub mod decider_hint;
+pub mod decider_hold_naive;
+pub mod decider_rep_wl;
 pub mod decider_result;
 pub mod decider_result_worker;
+pub mod decider_translated_cycler;
+pub mod machine_time_limit;
+pub mod scratch_buffer;
 pub mod step_record;
 
 use std::{fmt::Display, sync::Arc, time::Duration};
 
 use crate::{
-    config::Config,
+    certificate::Certificate,
+    config::{Config, StepBig, TapeMemoryBudget},
     decider::{
+        decider_backward_reasoning::DeciderBackwardReasoning,
         decider_bouncer_128::DeciderBouncer128,
+        decider_bouncer_proof::DeciderBouncerProof,
+        decider_counter::DeciderCounter,
+        decider_ctl::DeciderCTL,
         decider_cycler::DeciderCycler,
         decider_halt_long::DeciderHaltLong,
+        decider_hint::DeciderHint,
+        decider_hold_naive::DeciderHoldNaive,
+        decider_rep_wl::DeciderRepWL,
         decider_result::{
             BatchData, DeciderResultStats, EndReason, PreDeciderCount, ResultUnitEndReason,
+            WarningKind,
         },
         decider_result_worker::FnResultWorker,
-        pre_decider::{run_pre_decider_simple, run_pre_decider_strict, PreDeciderRun},
+        decider_translated_cycler::DeciderTranslatedCycler,
+        pre_decider::{
+            run_pre_decider_halt_transitions, run_pre_decider_simple, run_pre_decider_strict,
+            PreDeciderRun, PreDeciderTimer,
+        },
     },
+    html::HtmlWriteFailures,
     machine_binary::MachineId,
     machine_info::MachineInfo,
-    status::MachineStatus,
+    status::{MachineStatus, UndecidedReason},
 };
 // use crate::{
 //     decider::{
@@ -54,6 +87,11 @@ pub const DECIDER_HALT_ID: DeciderId = DeciderId {
     name: "Decider Halt",
     sub_dir: "halt",
 };
+pub const DECIDER_HOLD_NAIVE_ID: DeciderId = DeciderId {
+    id: 11,
+    name: "Decider Hold Naive",
+    sub_dir: "hold_naive",
+};
 pub const DECIDER_HALT_MACRO_ID: DeciderId = DeciderId {
     id: 15,
     name: "Decider Halt Macro",
@@ -69,11 +107,41 @@ pub const DECIDER_CYCLER_LONG_ID: DeciderId = DeciderId {
     name: "Decider Cycler Long",
     sub_dir: "cycler_long",
 };
+pub const DECIDER_TRANSLATED_CYCLER_ID: DeciderId = DeciderId {
+    id: 22,
+    name: "Decider Translated Cycler",
+    sub_dir: "translated_cycler",
+};
+pub const DECIDER_BACKWARD_REASONING_ID: DeciderId = DeciderId {
+    id: 25,
+    name: "Decider Backward Reasoning",
+    sub_dir: "backward_reasoning",
+};
 pub const DECIDER_BOUNCER_ID: DeciderId = DeciderId {
     id: 20,
     name: "Decider Bouncer",
     sub_dir: "bouncer",
 };
+pub const DECIDER_BOUNCER_PROOF_ID: DeciderId = DeciderId {
+    id: 23,
+    name: "Decider Bouncer Proof",
+    sub_dir: "bouncer_proof",
+};
+pub const DECIDER_REP_WL_ID: DeciderId = DeciderId {
+    id: 30,
+    name: "Decider RepWL",
+    sub_dir: "rep_wl",
+};
+pub const DECIDER_CTL_ID: DeciderId = DeciderId {
+    id: 40,
+    name: "Decider CTL",
+    sub_dir: "ctl",
+};
+pub const DECIDER_COUNTER_ID: DeciderId = DeciderId {
+    id: 45,
+    name: "Decider Counter",
+    sub_dir: "counter",
+};
 
 // This result gives a clear indication if an error occurred. It returns the data which has been processed so far.
 pub type ResultDecider = Result<DeciderResultStats, Box<DeciderError>>;
@@ -97,33 +165,135 @@ pub type FnDeciderRunBatchV2 = fn(&mut BatchData) -> ResultUnitEndReason;
 // }
 
 /// These are the provided deciders. This library should enable you to write your own decider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DeciderStandard {
+    BackwardReasoning,
     // BouncerV1,
     Bouncer128,
+    BouncerProof,
+    ClosedTapeLanguage,
+    Counter,
     Cycler,
     Hold,
+    /// Reference simulator without acceleration, see [DeciderHoldNaive].
+    HoldNaive,
+    RepWL,
+    TranslatedCycler,
 }
 
 impl DeciderStandard {
-    pub fn decider_caller(&self) -> DeciderCaller<'_> {
+    /// All standard deciders, e.g. to find a decider by its name.
+    pub const ALL: [DeciderStandard; 10] = [
+        DeciderStandard::BackwardReasoning,
+        DeciderStandard::Bouncer128,
+        DeciderStandard::BouncerProof,
+        DeciderStandard::ClosedTapeLanguage,
+        DeciderStandard::Counter,
+        DeciderStandard::Cycler,
+        DeciderStandard::Hold,
+        DeciderStandard::HoldNaive,
+        DeciderStandard::RepWL,
+        DeciderStandard::TranslatedCycler,
+    ];
+
+    pub fn decider_id(&self) -> &'static DeciderId {
+        self.decider_caller().decider_id()
+    }
+
+    /// Returns the decider with the name of its [DeciderId], e.g. 'Decider Cycler'.
+    pub fn from_decider_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.decider_id().name == name)
+    }
+
+    /// Name of the struct of the decider, e.g. 'DeciderCycler'.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DeciderStandard::BackwardReasoning => "DeciderBackwardReasoning",
+            DeciderStandard::Bouncer128 => "DeciderBouncer128",
+            DeciderStandard::BouncerProof => "DeciderBouncerProof",
+            DeciderStandard::ClosedTapeLanguage => "DeciderCTL",
+            DeciderStandard::Counter => "DeciderCounter",
+            DeciderStandard::Cycler => "DeciderCycler",
+            DeciderStandard::Hold => "DeciderHaltLong",
+            DeciderStandard::HoldNaive => "DeciderHoldNaive",
+            DeciderStandard::RepWL => "DeciderRepWL",
+            DeciderStandard::TranslatedCycler => "DeciderTranslatedCycler",
+        }
+    }
+
+    pub fn decide_single_machine(&self, machine: &MachineId, config: &Config) -> MachineStatus {
+        match self {
+            DeciderStandard::BackwardReasoning => {
+                DeciderBackwardReasoning::decide_single_machine(machine, config)
+            }
+            DeciderStandard::Bouncer128 => {
+                DeciderBouncer128::decide_single_machine(machine, config)
+            }
+            DeciderStandard::ClosedTapeLanguage => {
+                DeciderCTL::decide_single_machine(machine, config)
+            }
+            DeciderStandard::BouncerProof => {
+                DeciderBouncerProof::decide_single_machine(machine, config)
+            }
+            DeciderStandard::Counter => DeciderCounter::decide_single_machine(machine, config),
+            DeciderStandard::Cycler => DeciderCycler::decide_single_machine(machine, config),
+            DeciderStandard::Hold => DeciderHaltLong::decide_single_machine(machine, config),
+            DeciderStandard::HoldNaive => DeciderHoldNaive::decide_single_machine(machine, config),
+            DeciderStandard::RepWL => DeciderRepWL::decide_single_machine(machine, config),
+            DeciderStandard::TranslatedCycler => {
+                DeciderTranslatedCycler::decide_single_machine(machine, config)
+            }
+        }
+    }
+
+    pub fn decider_caller(&self) -> DeciderCaller<'static> {
         match self {
+            DeciderStandard::BackwardReasoning => DeciderCaller::new(
+                &DECIDER_BACKWARD_REASONING_ID,
+                DeciderBackwardReasoning::decider_run_batch,
+            ),
             // DeciderStandard::BouncerV1 => {
             //     DeciderCaller::new(&DECIDER_BOUNCER_ID, DeciderBouncerV1::decider_run_batch)
             // }
             DeciderStandard::Bouncer128 => {
                 DeciderCaller::new(&DECIDER_BOUNCER_ID, DeciderBouncer128::decider_run_batch)
             }
+            DeciderStandard::ClosedTapeLanguage => {
+                DeciderCaller::new(&DECIDER_CTL_ID, DeciderCTL::decider_run_batch)
+            }
+            DeciderStandard::BouncerProof => DeciderCaller::new(
+                &DECIDER_BOUNCER_PROOF_ID,
+                DeciderBouncerProof::decider_run_batch,
+            ),
+            DeciderStandard::Counter => {
+                DeciderCaller::new(&DECIDER_COUNTER_ID, DeciderCounter::decider_run_batch)
+            }
             DeciderStandard::Cycler => {
                 DeciderCaller::new(&DECIDER_CYCLER_ID, DeciderCycler::decider_run_batch)
             }
             DeciderStandard::Hold => {
                 DeciderCaller::new(&DECIDER_HALT_ID, DeciderHaltLong::decider_run_batch)
             }
+            DeciderStandard::HoldNaive => {
+                DeciderCaller::new(&DECIDER_HOLD_NAIVE_ID, DeciderHoldNaive::decider_run_batch)
+            }
+            DeciderStandard::RepWL => {
+                DeciderCaller::new(&DECIDER_REP_WL_ID, DeciderRepWL::decider_run_batch)
+            }
+            DeciderStandard::TranslatedCycler => DeciderCaller::new(
+                &DECIDER_TRANSLATED_CYCLER_ID,
+                DeciderTranslatedCycler::decider_run_batch,
+            ),
         }
     }
 
     pub fn decider_config<'a>(&self, config: &'a Config) -> DeciderConfig<'a> {
         match self {
+            DeciderStandard::BackwardReasoning => DeciderConfig::new(
+                &DECIDER_BACKWARD_REASONING_ID,
+                DeciderBackwardReasoning::decider_run_batch,
+                config,
+            ),
             // DeciderStandard::BouncerV1 => DeciderConfig::new(
             //     &DECIDER_BOUNCER_ID,
             //     DeciderBouncerV1::decider_run_batch,
@@ -134,15 +304,81 @@ impl DeciderStandard {
                 DeciderBouncer128::decider_run_batch,
                 config,
             ),
+            DeciderStandard::ClosedTapeLanguage => {
+                DeciderConfig::new(&DECIDER_CTL_ID, DeciderCTL::decider_run_batch, config)
+            }
+            DeciderStandard::BouncerProof => DeciderConfig::new(
+                &DECIDER_BOUNCER_PROOF_ID,
+                DeciderBouncerProof::decider_run_batch,
+                config,
+            ),
+            DeciderStandard::Counter => DeciderConfig::new(
+                &DECIDER_COUNTER_ID,
+                DeciderCounter::decider_run_batch,
+                config,
+            ),
             DeciderStandard::Cycler => {
                 DeciderConfig::new(&DECIDER_CYCLER_ID, DeciderCycler::decider_run_batch, config)
             }
             DeciderStandard::Hold => {
                 DeciderConfig::new(&DECIDER_HALT_ID, DeciderHaltLong::decider_run_batch, config)
             }
+            DeciderStandard::HoldNaive => DeciderConfig::new(
+                &DECIDER_HOLD_NAIVE_ID,
+                DeciderHoldNaive::decider_run_batch,
+                config,
+            ),
+            DeciderStandard::RepWL => {
+                DeciderConfig::new(&DECIDER_REP_WL_ID, DeciderRepWL::decider_run_batch, config)
+            }
+            DeciderStandard::TranslatedCycler => DeciderConfig::new(
+                &DECIDER_TRANSLATED_CYCLER_ID,
+                DeciderTranslatedCycler::decider_run_batch,
+                config,
+            ),
         }
     }
 
+    /// Step limit of this decider in the config, see [DeciderStandard::config_with_step_limit].
+    pub fn step_limit(&self, config: &Config) -> StepBig {
+        match self {
+            DeciderStandard::BackwardReasoning => config.step_limit_decider_backward_reasoning(),
+            DeciderStandard::Bouncer128 => config.step_limit_decider_bouncer(),
+            DeciderStandard::BouncerProof => config.step_limit_decider_bouncer_proof(),
+            DeciderStandard::ClosedTapeLanguage => config.step_limit_decider_ctl(),
+            DeciderStandard::Counter => config.step_limit_decider_counter(),
+            DeciderStandard::Cycler => config.step_limit_decider_cycler(),
+            DeciderStandard::Hold | DeciderStandard::HoldNaive => config.step_limit_decider_halt(),
+            DeciderStandard::RepWL => config.step_limit_decider_rep_wl(),
+            DeciderStandard::TranslatedCycler => config.step_limit_decider_translated_cycler(),
+        }
+    }
+
+    /// Returns a copy of the config with the step limit of this decider set, e.g. for a stage of a
+    /// [DeciderPipeline](crate::decider::decider_engine::DeciderPipeline). \
+    /// For the backward reasoning decider this is the search depth.
+    pub fn config_with_step_limit(&self, config: &Config, step_limit: StepBig) -> Config {
+        let builder = Config::builder_from_config(config);
+        match self {
+            DeciderStandard::BackwardReasoning => {
+                builder.step_limit_decider_backward_reasoning(step_limit)
+            }
+            DeciderStandard::Bouncer128 => builder.step_limit_decider_bouncer(step_limit),
+            DeciderStandard::BouncerProof => builder.step_limit_decider_bouncer_proof(step_limit),
+            DeciderStandard::ClosedTapeLanguage => builder.step_limit_decider_ctl(step_limit),
+            DeciderStandard::Counter => builder.step_limit_decider_counter(step_limit),
+            DeciderStandard::Cycler => builder.step_limit_decider_cycler(step_limit),
+            DeciderStandard::Hold | DeciderStandard::HoldNaive => {
+                builder.step_limit_decider_halt(step_limit)
+            }
+            DeciderStandard::RepWL => builder.step_limit_decider_rep_wl(step_limit),
+            DeciderStandard::TranslatedCycler => {
+                builder.step_limit_decider_translated_cycler(step_limit)
+            }
+        }
+        .build()
+    }
+
     pub fn standard_decider_for_config<'a>(
         config: &'a Config,
         config_cycler_2: &'a Config,
@@ -151,9 +387,21 @@ impl DeciderStandard {
         let dc_cycler_1 = DeciderStandard::Cycler.decider_config(config);
         let dc_bouncer_1 = DeciderStandard::Bouncer128.decider_config(config);
         let dc_cycler_2 = DeciderStandard::Cycler.decider_config(&config_cycler_2);
+        let dc_backward_reasoning = DeciderStandard::BackwardReasoning.decider_config(config);
+        let dc_translated_cycler = DeciderStandard::TranslatedCycler.decider_config(config);
         let dc_hold = DeciderStandard::Hold.decider_config(config);
+        // exhaustive search, only for the machines left by all other deciders
+        let dc_rep_wl = DeciderStandard::RepWL.decider_config(config);
 
-        let decider_config = vec![dc_cycler_1, dc_bouncer_1, dc_cycler_2, dc_hold];
+        let decider_config = vec![
+            dc_cycler_1,
+            dc_bouncer_1,
+            dc_cycler_2,
+            dc_backward_reasoning,
+            dc_translated_cycler,
+            dc_hold,
+            dc_rep_wl,
+        ];
 
         decider_config
     }
@@ -249,6 +497,19 @@ impl<'a> DeciderConfig<'a> {
         Arc::clone(&self.config)
     }
 
+    /// Same decider and result worker with a different config.
+    pub fn with_config<'b>(&self, config: &'b Config) -> DeciderConfig<'b>
+    where
+        'a: 'b,
+    {
+        DeciderConfig {
+            decider_id: self.decider_id,
+            f_decider_run_batch: self.f_decider_run_batch,
+            fo_result_worker: self.fo_result_worker,
+            config: Arc::new(config),
+        }
+    }
+
     pub fn decider_id(&self) -> &DeciderId {
         self.decider_id
     }
@@ -278,6 +539,10 @@ impl<'a> DeciderConfig<'a> {
     }
 }
 
+/// Name of a decider as stored in results, see [DeciderId::name]. \
+/// With the feature 'serde' a deserialized name is interned, as the results keep a &'static str.
+pub type DeciderName = &'static str;
+
 /// Decider identification. As only the function to run the decider is passed, the id can not be requested
 /// and needs to be part of the DeciderConfig.
 #[derive(Debug, Default, Clone, Copy)]
@@ -301,6 +566,14 @@ pub struct DeciderId {
 //     }
 // }
 
+/// Request of a decider to run an undecided machine again with larger limits, see [Decider::needs_retry].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct NeedsRetry {
+    pub suggested_step_limit: StepBig,
+    /// Tape size limit in u32 blocks, ignored by deciders without configurable tape size.
+    pub suggested_tape_blocks: u32,
+}
+
 pub trait Decider {
     // TODO into id, name struct
     fn decider_id() -> &'static DeciderId;
@@ -314,6 +587,59 @@ pub trait Decider {
     /// and use it for multiple machines.
     fn decide_single_machine(machine: &MachineId, config: &Config) -> MachineStatus;
 
+    /// Returns the step limit the decider uses for each machine.
+    fn step_limit(&self) -> StepBig;
+
+    /// Sets the step limit for the next machines. This is used to restrict the decider to the
+    /// remaining shared step budget of a machine (see [Config::step_budget_chain]).
+    fn set_step_limit(&mut self, step_limit: StepBig);
+
+    /// Returns the tape size limit in u32 blocks, 0 if the decider has no configurable tape size
+    /// (e.g. fixed 128-bit tape). Used for the tape limit escalation, see [Config::tape_size_limit_escalation_cells].
+    fn tape_size_limit_u32_blocks(&self) -> u32 {
+        0
+    }
+
+    /// Sets the tape size limit for the next machines. Does nothing for deciders without configurable tape size.
+    fn set_tape_size_limit_u32_blocks(&mut self, _tape_size_limit_u32_blocks: u32) {}
+
+    /// Memory of the growing tape in bytes after the last decided machine, None if the decider has no growing tape
+    /// (e.g. fixed 128-bit tape). Used for the tape memory high-water mark of the result, see [TapeMemoryStats](decider_result::TapeMemoryStats).
+    fn tape_memory_bytes(&self) -> Option<usize> {
+        None
+    }
+
+    /// Html files of this decider which could not be written, None if the decider writes no html. \
+    /// Write failures do not stop the decider, they are added to the result of the batch.
+    fn html_write_failures(&self) -> Option<&HtmlWriteFailures> {
+        None
+    }
+
+    /// Hint on the last decided machine for the next decider of the chain, None if the decider creates no hints.
+    /// Only called for undecided machines if [Config::decider_hints] is set.
+    fn hint(&self) -> Option<DeciderHint> {
+        None
+    }
+
+    /// Sets the hint of the previous deciders for the next machine, None if there is no hint.
+    /// Does nothing for deciders which do not use hints.
+    fn set_hint(&mut self, _hint: Option<&DeciderHint>) {}
+
+    /// Request to run the last machine again with larger limits, e.g. because it looks like a long cycle.
+    /// None if larger limits are not expected to decide the machine. \
+    /// Only called for undecided machines if [Config::decider_retry_step_limit_max] is set, the engine
+    /// caps the limits and counts the retries. This replaces extra chain stages of the same decider with
+    /// a higher step limit.
+    fn needs_retry(&self) -> Option<NeedsRetry> {
+        None
+    }
+
+    /// Certificate of the last machine if it was decided as non-halting, which can be checked independently
+    /// with [verify_certificate](crate::certificate::verify_certificate). None if the decider emits no certificates.
+    fn proof_certificate(&self) -> Option<Certificate> {
+        None
+    }
+
     fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason;
 }
 
@@ -325,17 +651,54 @@ pub fn decider_generic_run_batch(
     if batch_data.machines.is_empty() {
         return Err(EndReason::NoBatchData);
     }
+    if batch_data.step_budget > 0 {
+        return decider_generic_run_batch_step_budget(decider, batch_data);
+    }
 
     let limit_decided = batch_data.config.limit_machines_decided();
+    let rerun_limits = RerunLimits::new(&decider, batch_data.config);
+    let use_hints = batch_data.config.decider_hints();
+    // undecided machines are recorded by the chain, which knows if a later decider decides them
+    let record_verdicts = batch_data.config.record_verdicts();
+    let decider_name = batch_data.decider_id.name;
+    // steps run by the decider, as far as the status contains them
+    let mut num_steps = 0;
+    let mut pre_decider_timer = PreDeciderTimer::default();
     match batch_data.run_predecider {
         PreDeciderRun::DoNotRun => {
-            for machine in batch_data.machines.iter() {
-                let status = decider.decide_machine(machine);
+            for (i, machine) in batch_data.machines.iter().enumerate() {
+                let mut status = run_pre_decider_halt_transitions(machine.machine());
+                if status == MachineStatus::NoDecision {
+                    if use_hints {
+                        decider.set_hint(batch_data.hints.get(i));
+                    }
+                    status = decider.decide_machine(machine);
+                    if rerun_limits.is_active() {
+                        (status, _) = decide_machine_rerun(
+                            &mut decider,
+                            machine,
+                            status,
+                            &rerun_limits,
+                            StepBig::MAX,
+                            &mut batch_data.result_decided,
+                        );
+                    }
+                    num_steps += status.steps().unwrap_or_default() as u64;
+                    add_tape_memory(&decider, machine, &status, &mut batch_data.result_decided);
+                    if record_verdicts && !matches!(status, MachineStatus::Undecided(_, _, _)) {
+                        batch_data
+                            .result_decided
+                            .add_verdict(machine, decider_name, status);
+                    }
+                }
                 // This part is identical for all branches
                 match status {
                     MachineStatus::Undecided(_, _, _) => {
                         batch_data.machines_undecided.machines.push(*machine);
                         batch_data.machines_undecided.states.push(status);
+                        if use_hints {
+                            add_hint(decider.hint(), batch_data, i);
+                        }
                     }
                     _ => {
                         if limit_decided > 0
@@ -350,16 +713,37 @@ pub fn decider_generic_run_batch(
             }
         }
         PreDeciderRun::RunNormalForward => {
-            for machine in batch_data.machines.iter() {
-                let mut status = run_pre_decider_simple(machine.machine());
+            for (i, machine) in batch_data.machines.iter().enumerate() {
+                let mut status =
+                    pre_decider_timer.measure(|| run_pre_decider_simple(machine.machine()));
                 if status == MachineStatus::NoDecision {
                     status = decider.decide_machine(machine);
+                    if rerun_limits.is_active() {
+                        (status, _) = decide_machine_rerun(
+                            &mut decider,
+                            machine,
+                            status,
+                            &rerun_limits,
+                            StepBig::MAX,
+                            &mut batch_data.result_decided,
+                        );
+                    }
+                    num_steps += status.steps().unwrap_or_default() as u64;
+                    add_tape_memory(&decider, machine, &status, &mut batch_data.result_decided);
+                    if record_verdicts && !matches!(status, MachineStatus::Undecided(_, _, _)) {
+                        batch_data
+                            .result_decided
+                            .add_verdict(machine, decider_name, status);
+                    }
                 }
                 // This part is identical for all branches
                 match status {
                     MachineStatus::Undecided(_, _, _) => {
                         batch_data.machines_undecided.machines.push(*machine);
                         batch_data.machines_undecided.states.push(status);
+                        if use_hints {
+                            add_hint(decider.hint(), batch_data, i);
+                        }
                     }
                     _ => {
                         if limit_decided > 0
@@ -375,10 +759,28 @@ pub fn decider_generic_run_batch(
         }
 
         PreDeciderRun::RunStartBRightOnly => {
-            for machine in batch_data.machines.iter() {
-                let mut status = run_pre_decider_strict(machine.machine());
+            for (i, machine) in batch_data.machines.iter().enumerate() {
+                let mut status =
+                    pre_decider_timer.measure(|| run_pre_decider_strict(machine.machine()));
                 if status == MachineStatus::NoDecision {
                     status = decider.decide_machine(machine);
+                    if rerun_limits.is_active() {
+                        (status, _) = decide_machine_rerun(
+                            &mut decider,
+                            machine,
+                            status,
+                            &rerun_limits,
+                            StepBig::MAX,
+                            &mut batch_data.result_decided,
+                        );
+                    }
+                    num_steps += status.steps().unwrap_or_default() as u64;
+                    add_tape_memory(&decider, machine, &status, &mut batch_data.result_decided);
+                    if record_verdicts && !matches!(status, MachineStatus::Undecided(_, _, _)) {
+                        batch_data
+                            .result_decided
+                            .add_verdict(machine, decider_name, status);
+                    }
                 }
                 // This part is identical for all branches
                 // match_status(status, batch_data, machine, limit_decided);
@@ -386,6 +788,9 @@ pub fn decider_generic_run_batch(
                     MachineStatus::Undecided(_, _, _) => {
                         batch_data.machines_undecided.machines.push(*machine);
                         batch_data.machines_undecided.states.push(status);
+                        if use_hints {
+                            add_hint(decider.hint(), batch_data, i);
+                        }
                     }
                     _ => {
                         if limit_decided > 0
@@ -400,13 +805,354 @@ pub fn decider_generic_run_batch(
             }
         }
     }
+    if let Some(write_failures) = decider.html_write_failures() {
+        batch_data
+            .result_decided
+            .add_html_write_failures(write_failures);
+    }
+    batch_data.result_decided.add_steps_simulated(num_steps);
     batch_data
         .result_decided
-        .add_total(batch_data.machines.len() as u64);
+        .add_pre_decider_timer_decider(&pre_decider_timer);
+    batch_data
+        .result_decided
+        .add_total_machines(batch_data.machines);
 
     Ok(())
 }
 
+/// Adds the hint of the undecided machine with index i in the batch. If the decider has no hint,
+/// the hint of the previous deciders is passed on.
+#[inline(always)]
+fn add_hint(hint: Option<DeciderHint>, batch_data: &mut BatchData, i: usize) {
+    let hint = hint
+        .or_else(|| batch_data.hints.get(i).copied())
+        .unwrap_or_default();
+    batch_data.machines_undecided.hints.push(hint);
+}
+
+/// Records the tape memory the decider used for the machine, see [Decider::tape_memory_bytes].
+#[inline(always)]
+fn add_tape_memory(
+    decider: &impl Decider,
+    machine: &MachineId,
+    status: &MachineStatus,
+    result: &mut DeciderResultStats,
+) {
+    if let Some(bytes) = decider.tape_memory_bytes() {
+        let is_decided = !matches!(status, MachineStatus::Undecided(_, _, _));
+        result.add_tape_memory(machine, bytes, is_decided);
+    }
+}
+
+/// Same as [decider_generic_run_batch], but each machine only runs for the steps left in the
+/// shared step budget of the decider chain. Kept separate to not slow down the normal run. \
+/// Undecided machines report the steps used by all deciders so far, so the next decider knows the remaining budget.
+fn decider_generic_run_batch_step_budget(
+    mut decider: impl Decider,
+    batch_data: &mut BatchData,
+) -> ResultUnitEndReason {
+    let limit_decided = batch_data.config.limit_machines_decided();
+    let step_limit_decider = decider.step_limit();
+    let rerun_limits = RerunLimits::new(&decider, batch_data.config);
+    let use_hints = batch_data.config.decider_hints();
+    let record_verdicts = batch_data.config.record_verdicts();
+    let decider_name = batch_data.decider_id.name;
+    let mut num_steps = 0;
+    let mut pre_decider_timer = PreDeciderTimer::default();
+    for (i, machine) in batch_data.machines.iter().enumerate() {
+        let mut hint = None;
+        let steps_used = batch_data.steps_used.get(i).copied().unwrap_or_default();
+        let steps_left = batch_data.step_budget.saturating_sub(steps_used);
+        let mut status = match batch_data.run_predecider {
+            PreDeciderRun::DoNotRun => run_pre_decider_halt_transitions(machine.machine()),
+            PreDeciderRun::RunNormalForward => {
+                pre_decider_timer.measure(|| run_pre_decider_simple(machine.machine()))
+            }
+            PreDeciderRun::RunStartBRightOnly => {
+                pre_decider_timer.measure(|| run_pre_decider_strict(machine.machine()))
+            }
+        };
+        if status == MachineStatus::NoDecision {
+            if steps_left == 0 {
+                // budget already used up by previous deciders
+                status = MachineStatus::Undecided(UndecidedReason::StepLimit, steps_used, 0);
+            } else {
+                decider.set_step_limit(step_limit_decider.min(steps_left));
+                if use_hints {
+                    decider.set_hint(batch_data.hints.get(i));
+                }
+                status = decider.decide_machine(machine);
+                // the replaced runs of the escalation and the retries are charged to the budget
+                let mut steps_left_run = steps_left;
+                if rerun_limits.is_active() {
+                    (status, steps_left_run) = decide_machine_rerun(
+                        &mut decider,
+                        machine,
+                        status,
+                        &rerun_limits,
+                        steps_left,
+                        &mut batch_data.result_decided,
+                    );
+                }
+                let steps_used = steps_used.saturating_add(steps_left - steps_left_run);
+                let steps_left = steps_left_run;
+                num_steps += status.steps().unwrap_or_default() as u64;
+                add_tape_memory(&decider, machine, &status, &mut batch_data.result_decided);
+                if record_verdicts && !matches!(status, MachineStatus::Undecided(_, _, _)) {
+                    batch_data
+                        .result_decided
+                        .add_verdict(machine, decider_name, status);
+                }
+                if use_hints {
+                    hint = decider.hint();
+                }
+                if let MachineStatus::Undecided(reason, steps, tape_size) = status {
+                    if reason == UndecidedReason::StepLimit && steps >= steps_left {
+                        batch_data.result_decided.add_step_budget_exhausted();
+                    }
+                    status = MachineStatus::Undecided(
+                        reason,
+                        steps_used.saturating_add(steps),
+                        tape_size,
+                    );
+                }
+            }
+        }
+        match status {
+            MachineStatus::Undecided(_, _, _) => {
+                batch_data.machines_undecided.machines.push(*machine);
+                batch_data.machines_undecided.states.push(status);
+                if use_hints {
+                    add_hint(hint, batch_data, i);
+                }
+            }
+            _ => {
+                if limit_decided > 0 && batch_data.machines_decided.machines.len() < limit_decided {
+                    batch_data.machines_decided.machines.push(*machine);
+                    batch_data.machines_decided.states.push(status);
+                }
+                batch_data.result_decided.add(machine, &status);
+            }
+        }
+    }
+    decider.set_step_limit(step_limit_decider);
+    if let Some(write_failures) = decider.html_write_failures() {
+        batch_data
+            .result_decided
+            .add_html_write_failures(write_failures);
+    }
+    batch_data.result_decided.add_steps_simulated(num_steps);
+    batch_data
+        .result_decided
+        .add_pre_decider_timer_decider(&pre_decider_timer);
+    batch_data
+        .result_decided
+        .add_total_machines(batch_data.machines);
+
+    Ok(())
+}
+
+/// Limits of the re-runs of an undecided machine, see [decide_machine_rerun].
+#[derive(Debug, Clone, Copy)]
+struct RerunLimits<'a> {
+    /// Maximum tape size limit in u32 blocks of the tape limit escalation and the retries.
+    tape_limit_max: u32,
+    tape_limit_escalation: bool,
+    /// Maximum step limit of the retries, 0 if the retries are off.
+    retry_step_limit_max: StepBig,
+    /// Memory shared by all worker threads for the tapes of the re-runs with a larger tape size limit.
+    tape_memory_budget: &'a TapeMemoryBudget,
+}
+
+impl<'a> RerunLimits<'a> {
+    fn new(decider: &impl Decider, config: &'a Config) -> Self {
+        let tape_limit_max = config.tape_size_limit_escalation_u32_blocks();
+        Self {
+            tape_limit_max,
+            tape_limit_escalation: tape_limit_max > decider.tape_size_limit_u32_blocks(),
+            retry_step_limit_max: config.decider_retry_step_limit_max(),
+            tape_memory_budget: config.tape_memory_budget(),
+        }
+    }
+
+    #[inline(always)]
+    fn is_active(&self) -> bool {
+        self.tape_limit_escalation || self.retry_step_limit_max > 0
+    }
+}
+
+/// Runs an undecided machine again with larger limits: first the tape limit escalation, then the retries
+/// requested by the decider. The steps of each replaced run are taken from steps_left, the step budget of the
+/// machine (StepBig::MAX without budget).
+/// # Returns
+/// The status of the last run and the steps left for it.
+#[inline]
+fn decide_machine_rerun(
+    decider: &mut impl Decider,
+    machine: &MachineId,
+    status: MachineStatus,
+    limits: &RerunLimits<'_>,
+    steps_left: StepBig,
+    result: &mut DeciderResultStats,
+) -> (MachineStatus, StepBig) {
+    let mut status = status;
+    let mut steps_left = steps_left;
+    if limits.tape_limit_escalation {
+        status = decide_machine_tape_limit_escalation(
+            decider,
+            machine,
+            status,
+            limits,
+            &mut steps_left,
+            result,
+        );
+    }
+    if limits.retry_step_limit_max > 0 {
+        status = decide_machine_retry(decider, machine, status, limits, &mut steps_left, result);
+    }
+
+    (status, steps_left)
+}
+
+/// Retries a machine which is undecided because of the tape size limit with a doubled
+/// tape size limit until it is decided or the tape_limit_max (u32 blocks) of the limits is reached. \
+/// Only deciders with a long tape are escalated. A TapeSizeLimit with a tape size of 128 cells or less
+/// is a limit of the 128-bit tape (e.g. in the cycler) and would not change with a larger tape. \
+/// The steps of each replaced run are taken from steps_left, the step budget of the machine (StepBig::MAX
+/// without budget). The retries run with at most the steps left and end when the budget is used up. \
+/// The tape of each retry is reserved in the tape memory budget, a retry which does not fit is skipped.
+fn decide_machine_tape_limit_escalation(
+    decider: &mut impl Decider,
+    machine: &MachineId,
+    status: MachineStatus,
+    limits: &RerunLimits<'_>,
+    steps_left: &mut StepBig,
+    result: &mut DeciderResultStats,
+) -> MachineStatus {
+    let tape_limit_max = limits.tape_limit_max;
+    let tape_limit_decider = decider.tape_size_limit_u32_blocks();
+    if tape_limit_decider == 0 || tape_limit_max <= tape_limit_decider {
+        return status;
+    }
+    let step_limit_decider = decider.step_limit();
+    let mut status = status;
+    let mut tape_limit = tape_limit_decider;
+    while let MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, steps, tape_size) = status {
+        if tape_size <= 128 || tape_limit >= tape_limit_max {
+            break;
+        }
+        let steps_left_retry = steps_left.saturating_sub(steps);
+        if steps_left_retry == 0 {
+            break;
+        }
+        let tape_limit_retry = tape_limit.saturating_mul(2).min(tape_limit_max);
+        let tape_bytes = tape_bytes(tape_limit_retry);
+        if !limits.tape_memory_budget.try_reserve(tape_bytes) {
+            result.add_tape_memory_budget_skip();
+            break;
+        }
+        *steps_left = steps_left_retry;
+        tape_limit = tape_limit_retry;
+        decider.set_tape_size_limit_u32_blocks(tape_limit);
+        decider.set_step_limit(step_limit_decider.min(*steps_left));
+        result.add_tape_limit_escalation();
+        status = decider.decide_machine(machine);
+        limits.tape_memory_budget.release(tape_bytes);
+    }
+    decider.set_step_limit(step_limit_decider);
+    if let MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, _, tape_size) = status {
+        if tape_size > 128 && tape_limit >= tape_limit_max {
+            result.add_warning(WarningKind::TapeLimitEscalationCap, || {
+                format!(
+                    "{}: tape size limit of {tape_limit_max} u32 blocks reached",
+                    machine.to_standard_tm_text_format()
+                )
+            });
+        }
+    }
+    decider.set_tape_size_limit_u32_blocks(tape_limit_decider);
+
+    status
+}
+
+/// Runs an undecided machine again as long as the decider requests larger limits, see [Decider::needs_retry].
+/// The step limit is capped by retry_step_limit_max, the tape size limit by tape_limit_max (u32 blocks) of the
+/// limits.
+/// A retry is only run if it raises a limit, so the retries end at the latest when both caps are reached. \
+/// Like the tape limit escalation the replaced runs are charged to steps_left, the step limit of a retry
+/// is capped by the steps left and a tape above the tape size limit of the decider is reserved in the
+/// tape memory budget.
+fn decide_machine_retry(
+    decider: &mut impl Decider,
+    machine: &MachineId,
+    status: MachineStatus,
+    limits: &RerunLimits<'_>,
+    steps_left: &mut StepBig,
+    result: &mut DeciderResultStats,
+) -> MachineStatus {
+    let step_limit_max = limits.retry_step_limit_max;
+    let tape_limit_max = limits.tape_limit_max;
+    let step_limit_decider = decider.step_limit();
+    let tape_limit_decider = decider.tape_size_limit_u32_blocks();
+    let mut step_limit = step_limit_decider;
+    let mut tape_limit = tape_limit_decider;
+    let mut status = status;
+    while let MachineStatus::Undecided(_, steps, _) = status {
+        let Some(retry) = decider.needs_retry() else {
+            break;
+        };
+        let steps_left_retry = steps_left.saturating_sub(steps);
+        if steps_left_retry == 0 {
+            break;
+        }
+        let step_limit_retry = retry
+            .suggested_step_limit
+            .min(step_limit_max)
+            .max(step_limit)
+            .min(steps_left_retry);
+        // deciders without configurable tape size keep 0
+        let tape_limit_retry = if tape_limit_decider == 0 {
+            0
+        } else {
+            retry
+                .suggested_tape_blocks
+                .min(tape_limit_max)
+                .max(tape_limit)
+        };
+        if step_limit_retry <= step_limit && tape_limit_retry == tape_limit {
+            break;
+        }
+        let tape_bytes = if tape_limit_retry > tape_limit_decider {
+            tape_bytes(tape_limit_retry)
+        } else {
+            0
+        };
+        if !limits.tape_memory_budget.try_reserve(tape_bytes) {
+            result.add_tape_memory_budget_skip();
+            break;
+        }
+        step_limit = step_limit_retry;
+        tape_limit = tape_limit_retry;
+        *steps_left = steps_left_retry;
+        decider.set_step_limit(step_limit);
+        decider.set_tape_size_limit_u32_blocks(tape_limit);
+        result.add_decider_retry();
+        status = decider.decide_machine(machine);
+        limits.tape_memory_budget.release(tape_bytes);
+    }
+    decider.set_step_limit(step_limit_decider);
+    decider.set_tape_size_limit_u32_blocks(tape_limit_decider);
+
+    status
+}
+
+/// Bytes of a tape with the tape size limit in u32 blocks.
+#[inline]
+fn tape_bytes(tape_limit_u32_blocks: u32) -> u64 {
+    tape_limit_u32_blocks as u64 * size_of::<u32>() as u64
+}
+
 // Works, but even with inline 20-30% performance decrease
 // #[inline(always)]
 // fn match_status(
@@ -435,11 +1181,14 @@ pub struct ThreadResultDataProvider {
     pub batch_no: usize,
     pub machines: Vec<MachineId>,
     pub pre_decider_count: Option<PreDeciderCount>,
+    pub pre_decider_timer: PreDeciderTimer,
     pub duration: Duration,
 }
 
 pub struct ThreadResultDecider {
     pub batch_no: usize,
+    /// Number of the decider thread, see [Dashboard](crate::dashboard::Dashboard).
+    pub worker: usize,
     pub result: DeciderResultStats,
     pub duration: Duration,
 }
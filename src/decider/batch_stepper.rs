@@ -0,0 +1,251 @@
+//! Experimental batch stepper, which runs [BATCH_STEPPER_LANES] machines in lockstep for their first steps.
+//! Within the first steps most machines stay on a few cells, so the u128 tape of each machine fits
+//! into two u64 registers and all lanes execute the same instructions: read the head, write the symbol and
+//! shift the tape. Only the lookup of the transition is a gather from the transition tables. \
+//! The step is identical to [step_u128](crate::sim_core::step_u128) of the core, a lane stops when its machine
+//! halts or a 1 would be shifted out of the tape. Lanes whose machine halts are decided, all others
+//! are left to the decider.
+//!
+//! The lanes are plain arrays, which the compiler vectorizes on stable Rust. The feature 'simd' uses
+//! std::simd (portable_simd) instead, which requires a nightly compiler.
+//!
+//! The benchmark 'Bench Batch Stepper' compares the stepper with the scalar [run_u128](crate::sim_core::run_u128)
+//! and with the cycler. The lanes need wide registers, e.g. RUSTFLAGS="-C target-cpu=native":
+//! With AVX-512 the stepper runs the first steps of a BB5 batch about a third faster than the scalar run,
+//! with the default x86-64 target it is slower. As pre-pass of the cycler there is no gain yet (a few percent
+//! slower), since the machines halting early are cheap for the cycler too. Therefore no decider uses the stepper.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     decider::batch_stepper::{run_batch_halts, BATCH_STEPPER_STEP_LIMIT},
+//!     machine_binary::{MachineId, NotableMachineBinary},
+//! };
+//!
+//! let machines = [
+//!     MachineId::new_no_id(NotableMachineBinary::BB2MaxAronson.machine()),
+//!     MachineId::new_no_id(NotableMachineBinary::BB3Max.machine()),
+//! ];
+//!
+//! let statuses = run_batch_halts(&machines, BATCH_STEPPER_STEP_LIMIT);
+//! assert_eq!(statuses.len(), machines.len());
+//! ```
+
+use crate::{
+    config::{StepBig, NUM_FIELDS},
+    machine_binary::MachineId,
+    sim_core::{ARRAY_ID_START, FILTER_DIR, FILTER_STATE, TO_RIGHT},
+    status::MachineStatus,
+};
+
+/// Number of machines run in lockstep.
+pub const BATCH_STEPPER_LANES: usize = 8;
+/// Steps run by the stepper. The machines of the reduced enumeration halting at all mostly halt within these
+/// steps, all other lanes run until the step limit, so a higher limit costs more than it decides.
+pub const BATCH_STEPPER_STEP_LIMIT: StepBig = 32;
+/// Cells of the u128 tape, the decider must allow at least these cells to get the same result.
+pub const BATCH_STEPPER_TAPE_CELLS: u32 = 128;
+
+/// Fields per lane in the tables, a power of two so the array id can be masked instead of bounds checked.
+const TABLE_STRIDE: usize = NUM_FIELDS.next_power_of_two();
+const TABLE_LEN: usize = BATCH_STEPPER_LANES * TABLE_STRIDE;
+/// Head position (bit 63) in the low u64 of the tape.
+const HEAD_BIT: u64 = 1 << 63;
+
+/// Transition tables of the lanes, lane i uses the fields i * TABLE_STRIDE.., see
+/// [MachineBinary::transitions](crate::machine_binary::MachineBinary::transitions).
+type LaneTables = [u64; TABLE_LEN];
+/// Steps and array id of the halting transition per lane, None if the lane did not halt.
+type LaneHalts = [Option<(StepBig, usize)>; BATCH_STEPPER_LANES];
+
+/// Runs the machines in lockstep for up to step_limit steps.
+/// # Returns
+/// For each machine [MachineStatus::DecidedHaltField] if it halts within the step limit, otherwise
+/// [MachineStatus::NoDecision].
+pub fn run_batch_halts(machines: &[MachineId], step_limit: StepBig) -> Vec<MachineStatus> {
+    let mut statuses = Vec::with_capacity(machines.len());
+    run_batch_halts_into(machines, step_limit, &mut statuses);
+
+    statuses
+}
+
+/// Same as [run_batch_halts], but the statuses are appended to the given Vec.
+pub fn run_batch_halts_into(
+    machines: &[MachineId],
+    step_limit: StepBig,
+    statuses: &mut Vec<MachineStatus>,
+) {
+    let mut tables = [0; TABLE_LEN];
+    for chunk in machines.chunks(BATCH_STEPPER_LANES) {
+        let mut active = [false; BATCH_STEPPER_LANES];
+        for (lane, machine) in chunk.iter().enumerate() {
+            for (field, tr) in machine.machine().transitions.iter().enumerate() {
+                tables[lane * TABLE_STRIDE + field] = tr.transition as u64;
+            }
+            active[lane] = true;
+        }
+        let halts = run_lanes(&tables, active, step_limit);
+        statuses.extend(halts.iter().take(chunk.len()).map(|halt| match halt {
+            Some((steps, array_id)) => MachineStatus::DecidedHaltField(*steps, *array_id),
+            None => MachineStatus::NoDecision,
+        }));
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+fn run_lanes(
+    tables: &LaneTables,
+    active: [bool; BATCH_STEPPER_LANES],
+    step_limit: StepBig,
+) -> LaneHalts {
+    // all lane values are u64 and the flags masks (all bits set for true), so the loop over the lanes
+    // compiles to vector instructions
+    let mut active = active.map(|a| if a { u64::MAX } else { 0 });
+    let mut halt_step = [0u64; BATCH_STEPPER_LANES];
+    let mut halt_id = [0u64; BATCH_STEPPER_LANES];
+    // u128 tape as high and low u64, the head is bit 63 of low
+    let mut high = [0u64; BATCH_STEPPER_LANES];
+    let mut low = [0u64; BATCH_STEPPER_LANES];
+    let mut array_id = [ARRAY_ID_START as u64; BATCH_STEPPER_LANES];
+    let mut tr = [0u64; BATCH_STEPPER_LANES];
+    for step_no in 1..=step_limit as u64 {
+        // gather, inactive lanes keep their last valid array id
+        for lane in 0..BATCH_STEPPER_LANES {
+            tr[lane] = tables[lane * TABLE_STRIDE + (array_id[lane] as usize & (TABLE_STRIDE - 1))];
+        }
+        let mut any_active = 0;
+        for lane in 0..BATCH_STEPPER_LANES {
+            let is_halt = mask(tr[lane] & FILTER_STATE as u64 == 0) & active[lane];
+            halt_step[lane] |= step_no & is_halt;
+            halt_id[lane] |= array_id[lane] & is_halt;
+            let l = (low[lane] & !HEAD_BIT) | ((tr[lane] & 1) << 63);
+            let h = high[lane];
+            let is_right = mask(tr[lane] & FILTER_DIR as u64 == TO_RIGHT as u64);
+            let is_bound = mask(((h >> 63) & is_right) | (l & 1 & !is_right) != 0);
+            active[lane] &= !(is_halt | is_bound);
+            any_active |= active[lane];
+            high[lane] = (((h << 1) | (l >> 63)) & is_right) | ((h >> 1) & !is_right);
+            low[lane] = ((l << 1) & is_right) | (((l >> 1) | (h << 63)) & !is_right);
+            let next_id = (tr[lane] & FILTER_STATE as u64) + (low[lane] >> 63);
+            array_id[lane] = (next_id & active[lane]) | (array_id[lane] & !active[lane]);
+        }
+        if any_active == 0 {
+            break;
+        }
+    }
+
+    core::array::from_fn(|lane| {
+        (halt_step[lane] > 0).then_some((halt_step[lane] as StepBig, halt_id[lane] as usize))
+    })
+}
+
+/// Mask with all bits set for true.
+#[cfg(not(feature = "simd"))]
+#[inline(always)]
+fn mask(flag: bool) -> u64 {
+    (flag as u64).wrapping_neg()
+}
+
+#[cfg(feature = "simd")]
+fn run_lanes(
+    tables: &LaneTables,
+    active: [bool; BATCH_STEPPER_LANES],
+    step_limit: StepBig,
+) -> LaneHalts {
+    use std::simd::{
+        cmp::{SimdPartialEq, SimdPartialOrd},
+        num::SimdUint,
+        Mask, Select, Simd,
+    };
+    type Lanes = Simd<u64, BATCH_STEPPER_LANES>;
+
+    let mut halts = [None; BATCH_STEPPER_LANES];
+    let lane_offset = Simd::from_array(core::array::from_fn(|lane| lane * TABLE_STRIDE));
+    let filter_state = Lanes::splat(FILTER_STATE as u64);
+    let zero = Lanes::splat(0);
+    let one = Lanes::splat(1);
+    let shift_head = Lanes::splat(63);
+    let mut active = Mask::<i64, BATCH_STEPPER_LANES>::from_array(active);
+    // u128 tape as high and low u64, the head is bit 63 of low
+    let mut high = zero;
+    let mut low = zero;
+    let mut array_id = Simd::<usize, BATCH_STEPPER_LANES>::splat(ARRAY_ID_START);
+    for step_no in 1..=step_limit {
+        let tr = Lanes::gather_select(tables, active.cast::<isize>(), lane_offset + array_id, zero);
+        let is_halt = active & (tr & filter_state).simd_eq(zero);
+        if is_halt.any() {
+            for lane in 0..BATCH_STEPPER_LANES {
+                if is_halt.test(lane) {
+                    halts[lane] = Some((step_no, array_id[lane]));
+                }
+            }
+            active &= !is_halt;
+        }
+        low = (low & Lanes::splat(!HEAD_BIT)) | ((tr & one) << shift_head);
+        let is_right =
+            (tr & Lanes::splat(FILTER_DIR as u64)).simd_eq(Lanes::splat(TO_RIGHT as u64));
+        let is_bound = is_right.select(high >> shift_head, low & one).simd_gt(zero);
+        active &= !is_bound;
+        if !active.any() {
+            break;
+        }
+        (high, low) = (
+            is_right.select((high << one) | (low >> shift_head), high >> one),
+            is_right.select(low << one, (low >> one) | (high << shift_head)),
+        );
+        let next_id = (tr & filter_state) + (low >> shift_head);
+        array_id = active
+            .cast::<isize>()
+            .select(next_id.cast::<usize>(), array_id);
+    }
+
+    halts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::{
+            enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            DataProvider,
+        },
+        decider::{decider_cycler::DeciderCycler, Decider},
+        machine_binary::NotableMachineBinary,
+        sim_core::{run_u128, CoreRunResult},
+    };
+
+    #[test]
+    fn batch_halts_match_core_run() {
+        let config = Config::new_default(4);
+        let mut enumerator =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let mut machines = enumerator.machine_batch_next().unwrap().machines;
+        machines.truncate(2_003);
+        machines.push(MachineId::new_no_id(NotableMachineBinary::BB4Max.machine()));
+
+        let statuses = run_batch_halts(&machines, 120);
+        assert_eq!(statuses.len(), machines.len());
+        let mut num_halts = 0;
+        for (machine, status) in machines.iter().zip(statuses.iter()) {
+            let transitions = machine.machine().transitions_all().map(|t| t.transition);
+            match run_u128(&transitions, 120) {
+                CoreRunResult::Halt(steps) => {
+                    num_halts += 1;
+                    assert!(
+                        matches!(status, MachineStatus::DecidedHaltField(s, _) if *s == steps),
+                        "{machine}: {status:?}"
+                    );
+                }
+                _ => assert_eq!(*status, MachineStatus::NoDecision, "{machine}"),
+            }
+        }
+        assert!(num_halts > 0);
+        // same status as the cycler
+        let config_cycler = Config::builder(4).step_limit_decider_cycler(200).build();
+        assert_eq!(
+            *statuses.last().unwrap(),
+            DeciderCycler::decide_single_machine(machines.last().unwrap(), &config_cycler)
+        );
+    }
+}
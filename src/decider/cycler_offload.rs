@@ -0,0 +1,196 @@
+//! Offload of the bounded cycler check to an accelerator, e.g. the GPU. \
+//! Each machine of the enumeration is checked independently of all others, so huge batches can run
+//! in parallel on thousands of cores. The check is [run_u128](crate::sim_core::run_u128) of the no-std core:
+//! the machine runs on the 128-bit tape and cycles are found by comparing with a saved configuration (Brent),
+//! so no memory per machine is needed except the transition table. \
+//! The verdict of each machine is one of:
+//! - [MachineStatus::DecidedHalt] with the steps, if the machine halts within the step limit.
+//! - [MachineStatus::DecidedNonHalt] with [NonHaltReason::Cycler] (steps, period), if a configuration repeats.
+//! - [MachineStatus::Undecided] with [UndecidedReason::StepLimit] or [UndecidedReason::TapeSizeLimit],
+//!   the machine must be run by the decider chain.
+//!
+//! The check finds the cyclers later than the [DeciderCycler](crate::decider::decider_cycler::DeciderCycler)
+//! and only those staying within 128 cells, so it is a filter in front of the chain, not a replacement.
+//!
+//! The backends implement [CyclerOffload]:
+//! - [CyclerOffloadCpu] runs the core on the host. It is the reference for the results of all other backends.
+//! - CyclerOffloadGpu (feature 'gpu') runs the same loop as WGSL compute shader with wgpu, see
+//!   cycler_offload_gpu.
+//!
+//! The accelerators exchange plain u32 words with the host: the transition tables of the machines
+//! (see [pack_transition_tables]) and [RESULT_WORDS] result words per machine (see [status_from_result]).
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     decider::cycler_offload::{CyclerOffload, CyclerOffloadCpu},
+//!     machine_binary::{MachineId, NotableMachineBinary},
+//! };
+//!
+//! let machines = [MachineId::new_no_id(NotableMachineBinary::BB4Max.machine())];
+//!
+//! let mut backend = CyclerOffloadCpu;
+//! let statuses = backend.check_batch(&machines, 1000).unwrap();
+//! assert_eq!(statuses.len(), machines.len());
+//! ```
+
+use crate::{
+    config::{StepBig, NUM_FIELDS},
+    machine_binary::MachineId,
+    sim_core::{run_u128, CoreRunResult},
+    status::{MachineStatus, NonHaltReason, UndecidedReason},
+};
+
+/// Fields per machine in the packed transition tables, a power of two so the accelerator can index with shifts.
+pub const TABLE_STRIDE: usize = NUM_FIELDS.next_power_of_two();
+/// Result words per machine: result kind, steps, period of the cycle.
+pub const RESULT_WORDS: usize = 3;
+/// Tape size of the check in cells.
+pub const TAPE_SIZE_CELLS: u32 = 128;
+
+// Result kinds, the first result word of a machine.
+pub const RESULT_STEP_LIMIT: u32 = 0;
+pub const RESULT_HALT: u32 = 1;
+pub const RESULT_CYCLE: u32 = 2;
+pub const RESULT_TAPE_BOUND: u32 = 3;
+
+/// Backend running the bounded cycler check on a batch of machines, see [module documentation](self).
+pub trait CyclerOffload {
+    /// Name of the backend, e.g. for the log.
+    fn name(&self) -> String;
+
+    /// Checks all machines with the step limit.
+    /// # Returns
+    /// The verdict for each machine in the order of the machines, or an error if the backend failed.
+    fn check_batch(
+        &mut self,
+        machines: &[MachineId],
+        step_limit: StepBig,
+    ) -> Result<Vec<MachineStatus>, String>;
+}
+
+/// Reference backend, which runs the core on the host.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CyclerOffloadCpu;
+
+impl CyclerOffload for CyclerOffloadCpu {
+    fn name(&self) -> String {
+        "CPU".to_string()
+    }
+
+    fn check_batch(
+        &mut self,
+        machines: &[MachineId],
+        step_limit: StepBig,
+    ) -> Result<Vec<MachineStatus>, String> {
+        Ok(machines
+            .iter()
+            .map(|m| {
+                let transitions = m.machine().transitions_all().map(|t| t.transition);
+                status_from_core_run(run_u128(&transitions, step_limit), step_limit)
+            })
+            .collect())
+    }
+}
+
+/// Verdict of a run of the core.
+pub fn status_from_core_run(result: CoreRunResult, step_limit: StepBig) -> MachineStatus {
+    match result {
+        CoreRunResult::Halt(steps) => MachineStatus::DecidedHalt(steps),
+        CoreRunResult::Cycle(steps, period) => {
+            MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(steps, period))
+        }
+        CoreRunResult::TapeBound(steps) => {
+            MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, steps, TAPE_SIZE_CELLS)
+        }
+        CoreRunResult::StepLimit => {
+            MachineStatus::Undecided(UndecidedReason::StepLimit, step_limit, TAPE_SIZE_CELLS)
+        }
+    }
+}
+
+/// Verdict of the [RESULT_WORDS] result words of a machine written by an accelerator.
+pub fn status_from_result(words: &[u32], step_limit: StepBig) -> Result<MachineStatus, String> {
+    let result = match words {
+        [RESULT_HALT, steps, _] => CoreRunResult::Halt(*steps),
+        [RESULT_CYCLE, steps, period] => CoreRunResult::Cycle(*steps, *period),
+        [RESULT_TAPE_BOUND, steps, _] => CoreRunResult::TapeBound(*steps),
+        [RESULT_STEP_LIMIT, _, _] => CoreRunResult::StepLimit,
+        _ => return Err(format!("Invalid offload result: {words:?}")),
+    };
+
+    Ok(status_from_core_run(result, step_limit))
+}
+
+/// Transition tables of the machines as u32 words, machine i uses the words i * [TABLE_STRIDE].. indexed by
+/// the array id like [run_u128]. The unused words are 0 (halt).
+pub fn pack_transition_tables(machines: &[MachineId]) -> Vec<u32> {
+    let mut tables = vec![0; machines.len() * TABLE_STRIDE];
+    for (table, machine) in tables.chunks_exact_mut(TABLE_STRIDE).zip(machines) {
+        for (word, t) in table.iter_mut().zip(machine.machine().transitions_all()) {
+            *word = t.transition as u32;
+        }
+    }
+
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::{
+            enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            DataProvider,
+        },
+        decider::{decider_cycler::DeciderCycler, Decider},
+        machine_binary::NotableMachineBinary,
+    };
+
+    #[test]
+    fn offload_cpu_matches_cycler() {
+        let config = Config::builder(3).machine_limit(0).build();
+        let mut data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let mut machines = data_provider.machine_batch_next().unwrap().machines;
+        machines.push(MachineId::new_no_id(NotableMachineBinary::BB4Max.machine()));
+        let statuses = CyclerOffloadCpu.check_batch(&machines, 1000).unwrap();
+        assert_eq!(statuses.len(), machines.len());
+
+        let mut decider = DeciderCycler::new(&config);
+        let mut num_cycler = 0;
+        for (machine, status) in machines.iter().zip(statuses) {
+            let status_cycler = decider.decide_machine(machine);
+            match status {
+                MachineStatus::DecidedHalt(steps) => {
+                    assert_eq!(status_cycler.steps(), Some(steps), "{machine}")
+                }
+                MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(_, _)) => {
+                    num_cycler += 1;
+                    assert!(
+                        matches!(
+                            status_cycler,
+                            MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(_, _))
+                        ),
+                        "{machine}: {status_cycler:?}"
+                    );
+                }
+                _ => {}
+            }
+        }
+        assert!(num_cycler > 0);
+
+        // the result words of the accelerators give the same verdicts
+        let tables = pack_transition_tables(&machines[machines.len() - 1..]);
+        assert_eq!(tables.len(), TABLE_STRIDE);
+        assert_eq!(
+            status_from_result(&[RESULT_HALT, 107, 0], 1000),
+            Ok(MachineStatus::DecidedHalt(107))
+        );
+        assert_eq!(
+            status_from_result(&[RESULT_CYCLE, 20, 4], 1000),
+            Ok(MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(20, 4)))
+        );
+        assert!(status_from_result(&[9, 0, 0], 1000).is_err());
+    }
+}
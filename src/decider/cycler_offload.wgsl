@@ -0,0 +1,90 @@
+// Bounded cycler check of cycler_offload, one machine per invocation.
+// Same loop as run_u128 of sim_core.rs: the u128 tape is a vec4<u32> (x lowest word), the head is bit 63
+// (bit 31 of y) and cycles are found by comparing with a saved configuration (Brent).
+
+struct Params {
+    num_machines: u32,
+    step_limit: u32,
+    table_stride: u32,
+    _pad: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> tables: array<u32>;
+@group(0) @binding(2) var<storage, read_write> results: array<u32>;
+
+// bit layout of a transition, see sim_core.rs
+const FILTER_SYMBOL: u32 = 0x01u;
+const FILTER_STATE: u32 = 0x1eu;
+const FILTER_DIR: u32 = 0xc0u;
+const TO_RIGHT: u32 = 0xc0u;
+const ARRAY_ID_START: u32 = 2u;
+const HEAD_BIT: u32 = 0x80000000u;
+
+// result kinds, see cycler_offload.rs
+const RESULT_STEP_LIMIT: u32 = 0u;
+const RESULT_HALT: u32 = 1u;
+const RESULT_CYCLE: u32 = 2u;
+const RESULT_TAPE_BOUND: u32 = 3u;
+const RESULT_WORDS: u32 = 3u;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let machine = id.x;
+    if (machine >= params.num_machines) {
+        return;
+    }
+    let table = machine * params.table_stride;
+    var tape = vec4<u32>(0u);
+    var array_id = ARRAY_ID_START;
+    var saved_tape = tape;
+    var saved_id = array_id;
+    var power = 1u;
+    var lambda = 0u;
+    var kind = RESULT_STEP_LIMIT;
+    var steps = params.step_limit;
+    var period = 0u;
+    for (var step_no = 1u; step_no <= params.step_limit; step_no++) {
+        let tr = tables[table + array_id];
+        if ((tr & FILTER_STATE) == 0u) {
+            kind = RESULT_HALT;
+            steps = step_no;
+            break;
+        }
+        tape.y = (tape.y & ~HEAD_BIT) | ((tr & FILTER_SYMBOL) << 31u);
+        if ((tr & FILTER_DIR) == TO_RIGHT) {
+            // head moves right, tape moves left
+            if ((tape.w & HEAD_BIT) != 0u) {
+                kind = RESULT_TAPE_BOUND;
+                steps = step_no - 1u;
+                break;
+            }
+            tape = (tape << vec4<u32>(1u)) | (vec4<u32>(0u, tape.x, tape.y, tape.z) >> vec4<u32>(31u));
+        } else {
+            if ((tape.x & 1u) != 0u) {
+                kind = RESULT_TAPE_BOUND;
+                steps = step_no - 1u;
+                break;
+            }
+            tape = (tape >> vec4<u32>(1u)) | (vec4<u32>(tape.y, tape.z, tape.w, 0u) << vec4<u32>(31u));
+        }
+        array_id = (tr & FILTER_STATE) + (tape.y >> 31u);
+        if (array_id == saved_id && all(tape == saved_tape)) {
+            kind = RESULT_CYCLE;
+            steps = step_no;
+            period = lambda + 1u;
+            break;
+        }
+        lambda += 1u;
+        if (lambda == power) {
+            saved_tape = tape;
+            saved_id = array_id;
+            power *= 2u;
+            lambda = 0u;
+        }
+    }
+    let result = machine * RESULT_WORDS;
+    results[result] = kind;
+    results[result + 1u] = steps;
+    results[result + 2u] = period;
+}
@@ -0,0 +1,235 @@
+//! GPU backend of the [cycler offload](crate::decider::cycler_offload) with wgpu (feature 'gpu'). \
+//! The loop of [run_u128](crate::sim_core::run_u128) is the compute shader cycler_offload.wgsl, one invocation
+//! per machine. WGSL has no 64-bit integers, so the u128 tape is a vec4<u32>. The machines are uploaded as
+//! packed transition tables, each dispatch runs up to [MACHINES_PER_DISPATCH_MAX] machines. \
+//! wgpu selects Vulkan, Metal, DX12 or OpenGL, the environment variable WGPU_BACKEND overrides this.
+//! Without any adapter [CyclerOffloadGpu::try_new] returns an error, so the caller can use
+//! [CyclerOffloadCpu](crate::decider::cycler_offload::CyclerOffloadCpu) instead.
+//!
+//! Each invocation runs until its machine is decided or the step limit is reached, so the invocations of
+//! a workgroup wait for the slowest machine. The step limit should therefore be small, e.g. the step limit
+//! of the cycler, and the undecided machines be passed on to the decider chain.
+
+use crate::{
+    config::StepBig,
+    decider::cycler_offload::{
+        pack_transition_tables, status_from_result, CyclerOffload, RESULT_WORDS, TABLE_STRIDE,
+    },
+    machine_binary::MachineId,
+    status::MachineStatus,
+};
+
+/// Invocations per workgroup, must match @workgroup_size of the shader.
+const WORKGROUP_SIZE: usize = 64;
+/// Machines per dispatch, limited by the default maximum storage buffer size (128 MiB) of the tables.
+pub const MACHINES_PER_DISPATCH_MAX: usize = 1 << 20;
+const WORD_BYTES: u64 = 4;
+
+/// GPU backend, see [module documentation](self).
+#[derive(Debug)]
+pub struct CyclerOffloadGpu {
+    adapter_name: String,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+impl CyclerOffloadGpu {
+    /// Requests an adapter and device and compiles the shader.
+    pub fn try_new() -> Result<Self, String> {
+        let instance =
+            wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle_from_env());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .map_err(|e| format!("No GPU adapter: {e}"))?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("cycler offload"),
+            ..Default::default()
+        }))
+        .map_err(|e| format!("No GPU device: {e}"))?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("cycler offload"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("cycler_offload.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("cycler offload"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        Ok(Self {
+            adapter_name: adapter.get_info().name,
+            device,
+            queue,
+            pipeline,
+        })
+    }
+
+    /// Runs one dispatch and returns the result words.
+    fn check_dispatch(
+        &self,
+        machines: &[MachineId],
+        step_limit: StepBig,
+    ) -> Result<Vec<u32>, String> {
+        let params = [machines.len() as u32, step_limit, TABLE_STRIDE as u32, 0];
+        let tables = pack_transition_tables(machines);
+        let results_size = (machines.len() * RESULT_WORDS) as u64 * WORD_BYTES;
+        let params_buffer = self.buffer_init(
+            "params",
+            &params,
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        );
+        let tables_buffer = self.buffer_init(
+            "tables",
+            &tables,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        );
+        let results_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("results"),
+            size: results_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let read_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("read"),
+            size: results_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("cycler offload"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: tables_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: results_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(machines.len().div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&results_buffer, 0, &read_buffer, 0, results_size);
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        read_buffer.map_async(wgpu::MapMode::Read, .., move |r| {
+            let _ = sender.send(r);
+        });
+        self.device
+            .poll(wgpu::PollType::wait_indefinitely())
+            .map_err(|e| format!("GPU poll failed: {e}"))?;
+        receiver
+            .recv()
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("GPU buffer map failed: {e}"))?;
+        let words = read_buffer
+            .get_mapped_range(..)
+            .chunks_exact(WORD_BYTES as usize)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        read_buffer.unmap();
+
+        Ok(words)
+    }
+
+    fn buffer_init(&self, label: &str, words: &[u32], usage: wgpu::BufferUsages) -> wgpu::Buffer {
+        let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: bytes.len() as u64,
+            usage,
+            mapped_at_creation: false,
+        });
+        self.queue.write_buffer(&buffer, 0, &bytes);
+
+        buffer
+    }
+}
+
+impl CyclerOffload for CyclerOffloadGpu {
+    fn name(&self) -> String {
+        format!("GPU ({})", self.adapter_name)
+    }
+
+    fn check_batch(
+        &mut self,
+        machines: &[MachineId],
+        step_limit: StepBig,
+    ) -> Result<Vec<MachineStatus>, String> {
+        let mut statuses = Vec::with_capacity(machines.len());
+        for chunk in machines.chunks(MACHINES_PER_DISPATCH_MAX) {
+            let words = self.check_dispatch(chunk, step_limit)?;
+            for result in words.chunks_exact(RESULT_WORDS) {
+                statuses.push(status_from_result(result, step_limit)?);
+            }
+        }
+
+        Ok(statuses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::{
+            enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            DataProvider,
+        },
+        decider::cycler_offload::{status_from_core_run, CyclerOffloadCpu},
+        machine_binary::NotableMachineBinary,
+        sim_core::run_u128,
+    };
+
+    /// Checks the shader against the reference loop of the core. Skipped if no GPU adapter is available.
+    #[test]
+    fn offload_gpu_matches_run_u128() {
+        let mut gpu = match CyclerOffloadGpu::try_new() {
+            Ok(gpu) => gpu,
+            Err(e) => {
+                println!("Test skipped: {e}");
+                return;
+            }
+        };
+        let config = Config::builder(4).machine_limit(0).build();
+        let mut data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let mut machines = data_provider.machine_batch_next().unwrap().machines;
+        machines.push(MachineId::new_no_id(NotableMachineBinary::BB4Max.machine()));
+        for step_limit in [10, 1000] {
+            let statuses_gpu = gpu.check_batch(&machines, step_limit).unwrap();
+            assert_eq!(statuses_gpu.len(), machines.len());
+            for (machine, status_gpu) in machines.iter().zip(statuses_gpu.iter()) {
+                let transitions = machine.machine().transitions_all().map(|t| t.transition);
+                let status_core =
+                    status_from_core_run(run_u128(&transitions, step_limit), step_limit);
+                assert_eq!(*status_gpu, status_core, "{}: {machine}", gpu.name());
+            }
+            let statuses_cpu = CyclerOffloadCpu.check_batch(&machines, step_limit).unwrap();
+            assert_eq!(statuses_gpu, statuses_cpu, "{}", gpu.name());
+        }
+        assert!(gpu.check_batch(&[], 10).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! Backward reasoning decider, which proves that no halting configuration can be reached. \
+//! How it works: \
+//! The search starts at each halt transition with a partial configuration: the state of the halt transition
+//! and the head reading its symbol, all other cells are unknown. A predecessor is a transition into this state
+//! which writes a symbol matching the (known or unknown) cell it moved away from. The search goes backwards
+//! depth first. If no branch has a predecessor within [Config::step_limit_decider_backward_reasoning] steps,
+//! the halt transition cannot be reached from any configuration, so also not from the start. \
+//! The search gives up (undecided) when a partial configuration could be the start configuration
+//! (state A and all known cells 0), as then the machine may really halt, or when the depth or node limit is reached.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     decider::{decider_backward_reasoning::DeciderBackwardReasoning, Decider},
+//!     machine_binary::MachineId,
+//!     status::{MachineStatus, NonHaltReason},
+//! };
+//!
+//! let config = Config::new_default(4);
+//! let machine = MachineId::try_from("1RB---_1LB1RC_0LD0RC_0RB0RA").unwrap();
+//! let status = DeciderBackwardReasoning::decide_single_machine(&machine, &config);
+//! assert_eq!(status, MachineStatus::DecidedNonHalt(NonHaltReason::BackwardUnreachable(1)));
+//! ```
+//!
+//! [Config::step_limit_decider_backward_reasoning]: crate::config::Config::step_limit_decider_backward_reasoning
+
+use crate::{
+    config::{Config, StepBig, StepSmall},
+    decider::{
+        self,
+        decider_result::{BatchData, ResultUnitEndReason},
+        Decider, DECIDER_BACKWARD_REASONING_ID,
+    },
+    machine_binary::{MachineId, State, Symbol},
+    status::{MachineStatus, NonHaltReason, UndecidedReason},
+};
+
+/// Maximum number of partial configurations searched per machine.
+const NODES_MAX: usize = 10_000;
+const CELL_UNKNOWN: u8 = 2;
+
+/// Transition leading into a state, read backwards.
+#[derive(Debug, Clone, Copy)]
+struct Predecessor {
+    /// State and symbol read before the transition.
+    state: u8,
+    read: u8,
+    /// Symbol written and direction of the transition.
+    write: u8,
+    is_dir_right: bool,
+}
+
+/// Result of the search from one partial configuration.
+enum Search {
+    /// No branch reaches further back than this depth.
+    Unreachable(StepSmall),
+    /// Start configuration possible or a limit reached.
+    Undecided(UndecidedReason),
+}
+
+#[derive(Debug)]
+pub struct DeciderBackwardReasoning {
+    depth_limit: StepBig,
+    /// Cells of the partial configuration (0, 1 or unknown), the halt cell is in the middle.
+    tape: Vec<u8>,
+    /// Number of known cells with a 1, the configuration can only be the start if this is 0.
+    num_ones: usize,
+    num_nodes: usize,
+    /// Predecessors for each state (index state - 1).
+    predecessors: Vec<Vec<Predecessor>>,
+}
+
+impl DeciderBackwardReasoning {
+    pub fn new(config: &Config) -> Self {
+        let depth_limit = config.step_limit_decider_backward_reasoning() as StepBig;
+        Self {
+            depth_limit,
+            tape: vec![CELL_UNKNOWN; Self::tape_len(depth_limit)],
+            num_ones: 0,
+            num_nodes: 0,
+            predecessors: Vec::new(),
+        }
+    }
+
+    fn tape_len(depth_limit: StepBig) -> usize {
+        2 * depth_limit as usize + 3
+    }
+
+    /// Collects the predecessors of each state and returns the halt transitions (state, read symbol).
+    fn init_machine(&mut self, machine: &MachineId) -> Vec<(u8, u8)> {
+        let n_states = machine.n_states();
+        if self.tape.len() < Self::tape_len(self.depth_limit) {
+            self.tape = vec![CELL_UNKNOWN; Self::tape_len(self.depth_limit)];
+        }
+        self.predecessors.iter_mut().for_each(|p| p.clear());
+        self.predecessors.resize(n_states, Vec::new());
+        let mut halts = Vec::new();
+        for state in 1..=n_states {
+            for read in 0..2 {
+                let tr = machine
+                    .machine()
+                    .transition(State::new(state), Symbol::new(read));
+                if tr.is_halt() {
+                    halts.push((state as u8, read as u8));
+                } else {
+                    self.predecessors[tr.state() as usize - 1].push(Predecessor {
+                        state: state as u8,
+                        read: read as u8,
+                        write: tr.is_symbol_one() as u8,
+                        is_dir_right: tr.is_dir_right(),
+                    });
+                }
+            }
+        }
+        halts
+    }
+
+    fn set_cell(&mut self, pos: usize, symbol: u8) {
+        if self.tape[pos] == 1 {
+            self.num_ones -= 1;
+        }
+        if symbol == 1 {
+            self.num_ones += 1;
+        }
+        self.tape[pos] = symbol;
+    }
+
+    /// Searches the predecessors of the partial configuration in state with the head at pos.
+    fn search(&mut self, state: u8, pos: usize, depth: StepSmall) -> Search {
+        if state == 1 && self.num_ones == 0 {
+            return Search::Undecided(UndecidedReason::DeciderNoResult);
+        }
+        if depth >= self.depth_limit {
+            return Search::Undecided(UndecidedReason::StepLimit);
+        }
+        self.num_nodes += 1;
+        if self.num_nodes > NODES_MAX {
+            return Search::Undecided(UndecidedReason::StepLimit);
+        }
+        let mut depth_max = depth;
+        for i in 0..self.predecessors[state as usize - 1].len() {
+            let p = self.predecessors[state as usize - 1][i];
+            // the head came from the other side of the direction
+            let pos_before = if p.is_dir_right { pos - 1 } else { pos + 1 };
+            let cell = self.tape[pos_before];
+            if cell != CELL_UNKNOWN && cell != p.write {
+                continue;
+            }
+            self.set_cell(pos_before, p.read);
+            let r = self.search(p.state, pos_before, depth + 1);
+            self.set_cell(pos_before, cell);
+            match r {
+                Search::Unreachable(d) => depth_max = depth_max.max(d),
+                undecided => return undecided,
+            }
+        }
+        Search::Unreachable(depth_max)
+    }
+}
+
+impl Decider for DeciderBackwardReasoning {
+    fn decider_id() -> &'static decider::DeciderId {
+        &DECIDER_BACKWARD_REASONING_ID
+    }
+
+    fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
+        let halts = self.init_machine(machine);
+        self.num_nodes = 0;
+        let pos = self.tape.len() / 2;
+        let mut depth_max = 0;
+        for (state, read) in halts {
+            self.set_cell(pos, read);
+            let r = self.search(state, pos, 0);
+            self.set_cell(pos, CELL_UNKNOWN);
+            match r {
+                Search::Unreachable(d) => depth_max = depth_max.max(d),
+                Search::Undecided(reason) => {
+                    return MachineStatus::Undecided(reason, depth_max, 0);
+                }
+            }
+        }
+
+        MachineStatus::DecidedNonHalt(NonHaltReason::BackwardUnreachable(depth_max))
+    }
+
+    fn decide_single_machine(machine: &MachineId, config: &Config) -> MachineStatus {
+        let mut d = Self::new(config);
+        d.decide_machine(machine)
+    }
+
+    fn step_limit(&self) -> StepBig {
+        self.depth_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.depth_limit = step_limit;
+    }
+
+    fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
+        let decider = Self::new(batch_data.config);
+        decider::decider_generic_run_batch(decider, batch_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decider::decider_cycler::DeciderCycler;
+
+    fn decide(tm: &str) -> MachineStatus {
+        let machine = MachineId::try_from(tm).unwrap();
+        let config = Config::new_default(machine.n_states());
+        DeciderBackwardReasoning::decide_single_machine(&machine, &config)
+    }
+
+    #[test]
+    fn decider_backward_reasoning_unreachable() {
+        // A1 halts, but A is only entered from D moving right, and D only from C0 moving left after
+        // writing 0 on that cell, so A always reads 0 there.
+        // The cycler and the bouncer cannot decide this machine.
+        let tm = "1RB---_1LB1RC_0LD0RC_0RB0RA";
+        let machine = MachineId::try_from(tm).unwrap();
+        let status = DeciderCycler::decide_single_machine(&machine, &Config::new_default(4));
+        assert!(
+            matches!(status, MachineStatus::Undecided(_, _, _)),
+            "{status}"
+        );
+        assert_eq!(
+            decide(tm),
+            MachineStatus::DecidedNonHalt(NonHaltReason::BackwardUnreachable(1))
+        );
+    }
+
+    #[test]
+    fn decider_backward_reasoning_halting_undecided() {
+        // BB4 Max halts, so the search must reach a possible start configuration
+        assert!(matches!(
+            decide("1RB1LB_1LA0LC_1RZ1LD_1RD0RA"),
+            MachineStatus::Undecided(_, _, _)
+        ));
+    }
+}
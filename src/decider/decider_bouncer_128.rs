@@ -4,9 +4,14 @@
 //! follow a rhythm, see examples below. \
 //! It runs on the 128 bit tape, which limits the expansion to the sides, but is highly effective
 //! and eliminates >90% of the machines the cycler does not catch.
+//! The rhythm is a strong indication, but no proof. The [DeciderBouncerProof] proves bouncers by induction
+//! and emits a certificate.
 //!
 //! # Config
-//! step_limit_decider_bouncer: Undecided if number of steps are reached.
+//! step_limit_decider_bouncer: Undecided if number of steps are reached. The undecided reason tells why no
+//! bouncer was found: [NoSinusRhythmIdentified](UndecidedReason::NoSinusRhythmIdentified) if the tape was not
+//! swept often enough to compare the sweeps, otherwise
+//! [BouncerPatternTooComplex](UndecidedReason::BouncerPatternTooComplex).
 //! This can be set high as mostly the bouncer is identified quickly or the tape borders are reached,
 //! e.g. 20_000. Since the cycler takes care of most machines, the amount of checked machines is fairly low.
 //!
@@ -27,7 +32,8 @@
 //! Running BB5 complete, with first cycler and first bouncer only takes about 50 Minutes
 //! for 16,679,880,978,201 machines with a pre-decider reducing this to 59,649,822,720
 //! which need to be evaluated.
-//! 7,827,594 machines are left undecided.
+//! 7,827,594 machines are left undecided. These can be exported and run through the next stages only, see
+//! [resume_undecided](crate::resume_undecided).
 //!
 //! # Examples
 //!
@@ -116,24 +122,29 @@
 //! (use the test is_bouncer_bb5_example3 to create the HTML file): \
 //! For right of head is 0, step 1, 6 (7), 20 (21), 50 (51), 112 (113) skipped in brackets, \
 //! for left of head is 2 (3, 4), 10 (11, 12, 15, 16), 26 (27, 28, 38, 39, 42, 43)
+//!
+//! [DeciderBouncerProof]: crate::decider::decider_bouncer_proof::DeciderBouncerProof
 
 use std::fmt::Display;
 
 use crate::{
-    config::Config,
+    config::{Config, StepBig},
     decider::{
         self,
         decider_data_128::DeciderData128,
+        decider_hint::DeciderHint,
         decider_result::{BatchData, ResultUnitEndReason},
-        Decider,
+        scratch_buffer, Decider,
     },
     machine_binary::MachineId,
-    status::{MachineStatus, NonHaltReason},
+    status::{MachineStatus, NonHaltReason, UndecidedReason},
     tape::{tape_utils::U64Ext, Tape},
 };
 
 /// Initial capacity for step recorder. Not so relevant.
 const MAX_INIT_CAPACITY: usize = 10_000;
+/// Number of sweeps required to compare the growth of the sweeps.
+const NUM_SWEEPS_COMPARED: usize = 8;
 
 // TODO Use long tape, or tape_shifted left & right bound could be introduced.
 // TODO 1RB---_1LC0RB_0LC1RB runs full 200000 steps. Can this be limited by a different rule?
@@ -143,6 +154,9 @@ pub struct DeciderBouncer128 {
     /// Store all steps to do comparisons (test if a cycle is repeating)
     /// All even indices are lower bits, all odd upper bits
     steps: Vec<StepBouncer>,
+    /// The tape records are compared from this step on, taken from the [DeciderHint] of the previous decider. \
+    /// The steps before the first sweep are the warm-up of the machine, which cannot be a bouncer yet.
+    compare_start_step: StepBig,
     // / Stores the step ids (2 = 3rd step) for each field in the transition table. \
     // / (basically e.g. all steps for e.g. field 'B0' steps: 1 if A0 points to B, as step 1 then has state B and head symbol 0.)
     // TODO performance: extra differentiation for 0/1 at head position? The idea is, that the field cannot be identical if head read is different
@@ -158,7 +172,8 @@ impl DeciderBouncer128 {
         let cap = (config.step_limit_decider_bouncer() as usize).min(MAX_INIT_CAPACITY);
         let mut decider = Self {
             data: DeciderData128::new(config),
-            steps: Vec::with_capacity(cap),
+            steps: scratch_buffer::take_vec(cap),
+            compare_start_step: 0,
 
             #[cfg(all(feature = "decider_timer_info", not(debug_assertions)))]
             start_time: std::time::Instant::now(),
@@ -183,14 +198,13 @@ impl DeciderBouncer128 {
         // initialize decider
         self.clear();
 
-        self.data.transition_table = *machine.machine();
         let mut last_left_empty_step_no = 0;
         let mut last_right_empty_step_no = 0;
         let mut is_bouncing_right = false;
 
         // loop over transitions to write tape
         loop {
-            if self.data.next_transition() {
+            if self.data.next_transition(machine.machine()) {
                 // is done
                 break;
             }
@@ -214,6 +228,9 @@ impl DeciderBouncer128 {
                     tape_after: self.data.tape.right_64_bit(),
                 };
                 self.steps.push(step);
+                if self.data.step_no < self.compare_start_step {
+                    continue;
+                }
                 #[cfg(all(debug_assertions, feature = "bb_debug"))]
                 {
                     let text = format!("  Step {}: tape LEFT empty: comparing", self.data.step_no);
@@ -222,7 +239,7 @@ impl DeciderBouncer128 {
                     self.data.write_html_p(&text);
                 }
                 // compare and check if same expanding bits for three consecutive steps
-                if self.steps.len() > 7 {
+                if self.steps.len() >= NUM_SWEEPS_COMPARED {
                     let i = self.steps.len() - 1;
                     let changed = [
                         Changed::new(self.steps[i - 4].tape_after, self.steps[i - 6].tape_after),
@@ -284,6 +301,9 @@ impl DeciderBouncer128 {
                     tape_after: self.data.tape.left_64_bit(),
                 };
                 self.steps.push(step);
+                if self.data.step_no < self.compare_start_step {
+                    continue;
+                }
                 #[cfg(all(debug_assertions, feature = "bb_debug"))]
                 {
                     let text = format!("  Step {}: tape RIGHT empty: comparing", self.data.step_no);
@@ -292,7 +312,7 @@ impl DeciderBouncer128 {
                     self.data.write_html_p(&text);
                 }
                 // compare and check if same expanding bits for both sides
-                if is_bouncing_right && self.steps.len() > 7 {
+                if is_bouncing_right && self.steps.len() >= NUM_SWEEPS_COMPARED {
                     let i = self.steps.len() - 1;
                     let changed = [
                         Changed::new(self.steps[i - 4].tape_after, self.steps[i - 6].tape_after),
@@ -350,6 +370,21 @@ impl DeciderBouncer128 {
             }
         }
 
+        // report why the step limit was reached without bouncer
+        if let MachineStatus::Undecided(UndecidedReason::StepLimit, steps, tape_size) =
+            self.data.status
+        {
+            let num_sweeps = self.steps.len() as u32;
+            let reason = if self.steps.len() < NUM_SWEEPS_COMPARED {
+                UndecidedReason::NoSinusRhythmIdentified {
+                    sinus_steps: num_sweeps,
+                }
+            } else {
+                UndecidedReason::BouncerPatternTooComplex { num_sweeps }
+            };
+            self.data.status = MachineStatus::Undecided(reason, steps, tape_size);
+        }
+
         #[cfg(feature = "enable_html_reports")]
         self.data.write_html_file_end();
 
@@ -357,6 +392,13 @@ impl DeciderBouncer128 {
     }
 }
 
+/// Returns the buffer to the arena of the worker thread for the decider of the next batch.
+impl Drop for DeciderBouncer128 {
+    fn drop(&mut self) {
+        scratch_buffer::return_vec(std::mem::take(&mut self.steps));
+    }
+}
+
 impl Decider for DeciderBouncer128 {
     fn decider_id() -> &'static decider::DeciderId {
         // &DECIDER_BOUNCER_ID
@@ -406,6 +448,24 @@ impl Decider for DeciderBouncer128 {
         d.decide_machine(machine)
     }
 
+    fn step_limit(&self) -> StepBig {
+        self.data.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.data.step_limit = step_limit;
+    }
+
+    #[cfg(feature = "enable_html_reports")]
+    fn html_write_failures(&self) -> Option<&crate::html::HtmlWriteFailures> {
+        self.data.html_writer.as_ref().map(|w| w.write_failures())
+    }
+
+    /// Skips the warm-up of the machine up to the first sweep seen by the previous decider.
+    fn set_hint(&mut self, hint: Option<&DeciderHint>) {
+        self.compare_start_step = hint.map_or(0, |h| h.sweep_start_step);
+    }
+
     fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
         let decider = Self::new(batch_data.config);
         decider::decider_generic_run_batch(decider, batch_data)
@@ -878,7 +938,11 @@ mod tests {
         let check_result = DeciderBouncer128::decide_single_machine(&machine, &config);
         assert_eq!(
             check_result,
-            MachineStatus::Undecided(UndecidedReason::StepLimit, 2000, 59)
+            MachineStatus::Undecided(
+                UndecidedReason::BouncerPatternTooComplex { num_sweeps: 29 },
+                2000,
+                59
+            )
         );
     }
 
@@ -918,7 +982,9 @@ mod tests {
             .step_limit_decider_bouncer(2000)
             .build();
         let check_result = DeciderBouncer128::decide_single_machine(&machine, &config);
-        if let MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, _, _) = check_result {
+        if let MachineStatus::Undecided(UndecidedReason::TapeLimitRightBoundReached, _, _) =
+            check_result
+        {
         } else {
             panic!("{check_result}");
         }
@@ -935,12 +1001,41 @@ mod tests {
             .write_html_file(true)
             .build();
         let check_result = DeciderBouncer128::decide_single_machine(&machine, &config);
-        if let MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, _, _) = check_result {
+        if let MachineStatus::Undecided(UndecidedReason::TapeLimitRightBoundReached, _, _) =
+            check_result
+        {
         } else {
             panic!("{check_result}");
         }
     }
 
+    #[test]
+    fn tape_bound_reached_in_head_direction() {
+        // the head only moves right or only moves left and leaves the 128-bit tape on that side
+        let machine = MachineId::try_from("1RB---_1RA---").unwrap();
+        let config = Config::new_default(machine.n_states());
+        let check_result = DeciderBouncer128::decide_single_machine(&machine, &config);
+        assert!(
+            matches!(
+                check_result,
+                MachineStatus::Undecided(UndecidedReason::TapeLimitRightBoundReached, _, 128)
+            ),
+            "{check_result}"
+        );
+        assert!(check_result.to_string().contains("Right tape bound"));
+
+        let machine = MachineId::try_from("1LB---_1LA---").unwrap();
+        let check_result = DeciderBouncer128::decide_single_machine(&machine, &config);
+        assert!(
+            matches!(
+                check_result,
+                MachineStatus::Undecided(UndecidedReason::TapeLimitLeftBoundReached, _, 128)
+            ),
+            "{check_result}"
+        );
+        assert!(check_result.to_string().contains("Left tape bound"));
+    }
+
     #[test]
     fn is_not_bouncer_bb3_max_651320() {
         // BB3 Max
@@ -0,0 +1,1137 @@
+//! Formal bouncer prover, which proves non-halting of bouncers and emits a certificate of the proof. \
+//! A bouncer sweeps back and forth over the tape and extends the same words on every sweep. Whenever the head
+//! reaches a new cell (record), the tape is written as a formula tape, e.g. `0^inf 1 (10)^(n+2) C>0 0^inf`, where
+//! the words in brackets (repeaters) are repeated n + c times and `C>0` is the head in state C reading 0.
+//! 1. Repeating words: Three records of the same state and side with equally growing tapes are aligned. The words
+//!    inserted from one record to the next are the repeaters. The formula F(n) is the middle record for n = 0.
+//! 2. Induction step: F(n) is simulated symbolically for any n. A repeater is passed at once with a shift rule
+//!    `C> w -> w' C>`: if the machine enters w in state C and leaves it on the other side in state C, it does so for
+//!    any number of copies. Otherwise one copy is taken from the repeater, which requires c >= 1.
+//!    If the symbolic run reaches F(n+1), then F(n) leads to F(n+1) for all n and the machine never halts.
+//! 3. The steps of the induction step are linear in n, so F(n) is reached after a quadratic polynomial of steps,
+//!    see [BouncerCertificate::steps_at].
+//!
+//! The formula tapes are compared in a normal form: copies next to a repeater are absorbed and the repeater is
+//! rotated as far left as possible. Each rewrite holds for all n. \
+//! The [BouncerCertificate] is the proof as text, which can be read with [BouncerCertificate::from_text] and
+//! checked with [BouncerCertificate::verify] independent of the run. Unlike the [DeciderBouncer128], which
+//! identifies bouncers by the rhythm of the tape, this is a proof. It is not part of the standard chain.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     decider::{decider_bouncer_proof::DeciderBouncerProof, Decider},
+//!     machine_binary::MachineId,
+//! };
+//!
+//! let config = Config::new_default(4);
+//! let machine = MachineId::try_from("1RB0LB_1LA0LC_---1RD_0RA0RA").unwrap();
+//! let mut decider = DeciderBouncerProof::new(&config);
+//! let status = decider.decide_machine(&machine);
+//! println!("{}", decider.certificate().unwrap());
+//! ```
+//! prints
+//! ```text
+//! bouncer proof certificate
+//! machine 1RB0LB_1LA0LC_---1RD_0RA0RA
+//! start_step 13
+//! formula 0^inf (1010)^(n+1) 1 B>0 0^inf
+//! induction_steps 12*n+24
+//! rule 1010 <B -> <B 0101 4 steps
+//! rule A> 0101 -> 1010 A> 8 steps
+//! # formula(n) is reached at step 13 + 24*n + 12*n*(n-1)/2
+//! ```
+//! Some bouncers need the cell behind the head to pass a word, e.g. `rule 1 <D 1 -> <D 1 1 3 steps`, the
+//! context (here 1) is carried along. \
+//! BB4: Of the 71,864 machines left undecided by the cycler (limit 1,000), 63,436 are proven.
+//!
+//! [DeciderBouncer128]: crate::decider::decider_bouncer_128::DeciderBouncer128
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+};
+
+use crate::{
+    certificate::Certificate,
+    config::{Config, StepBig, StepSmall},
+    decider::{
+        self,
+        decider_result::{BatchData, ResultUnitEndReason},
+        Decider, DECIDER_BOUNCER_PROOF_ID,
+    },
+    format::standard_tm,
+    machine_binary::{MachineBinary, MachineId, State, Symbol},
+    status::{MachineStatus, NonHaltReason, UndecidedReason},
+};
+
+/// Maximum number of repeaters of a formula tape.
+const REPEATERS_MAX: usize = 3;
+/// Maximum growth of the tape from one record to the next.
+const INSERT_LEN_MAX: usize = 16;
+/// Records kept per state and side, the records to align are up to 3 apart.
+const RECORDS_KEPT: usize = 7;
+/// Maximum number of visited cells, beyond the machine is left undecided.
+const TAPE_CELLS_MAX: usize = 2_000;
+/// Cells added when the concrete tape grows.
+const TAPE_GROW: usize = 64;
+/// Maximum number of symbolic steps of the induction step.
+const SYMBOLIC_STEPS_MAX: usize = 10_000;
+/// Maximum number of steps to pass the word of a shift rule.
+const RULE_STEPS_MAX: u64 = 10_000;
+/// Maximum length of the context carried along by a shift rule.
+const CONTEXT_LEN_MAX: usize = 2;
+const CERTIFICATE_HEADER: &str = "bouncer proof certificate";
+const BLANK_TAPE: &str = "0^inf";
+
+/// Shift rules by (state, direction right, word, context).
+type RuleCache = HashMap<(u8, bool, Vec<u8>, Vec<u8>), Option<ShiftRule>>;
+/// Inserted words (position, word).
+type Insertions = [(usize, Vec<u8>)];
+
+#[derive(Debug)]
+pub struct DeciderBouncerProof {
+    step_limit: StepBig,
+    rule_cache: RuleCache,
+    certificate: Option<BouncerCertificate>,
+}
+
+impl DeciderBouncerProof {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            step_limit: config.step_limit_decider_bouncer_proof() as StepBig,
+            rule_cache: HashMap::new(),
+            certificate: None,
+        }
+    }
+
+    /// Certificate of the last machine, if it was proven.
+    pub fn certificate(&self) -> Option<&BouncerCertificate> {
+        self.certificate.as_ref()
+    }
+
+    /// Tries to prove the bouncer from the records (newest last) of one state and side.
+    fn prove(
+        &mut self,
+        machine: &MachineId,
+        records: &VecDeque<Record>,
+        state: u8,
+        is_right: bool,
+    ) -> Option<BouncerCertificate> {
+        let last = records.len() - 1;
+        for k in 1..=last / 2 {
+            let (a, b, c) = (&records[last - 2 * k], &records[last - k], &records[last]);
+            let d = b.cells.len() as isize - a.cells.len() as isize;
+            // the tape must grow equally and the sweeps must get longer
+            if d <= 0
+                || d as usize > INSERT_LEN_MAX
+                || c.cells.len() as isize - b.cells.len() as isize != d
+                || c.step - b.step <= b.step - a.step
+            {
+                continue;
+            }
+            let Some(insertions) = find_insertions(&a.cells, &b.cells, &c.cells) else {
+                continue;
+            };
+            // F(0) is record b
+            let mut items = Vec::new();
+            let mut pos = 0;
+            for (p, word) in insertions {
+                items.extend(a.cells[pos..p].iter().map(|&s| FormulaItem::Symbol(s)));
+                items.push(FormulaItem::Repeat(word, 1));
+                pos = p;
+            }
+            items.extend(a.cells[pos..].iter().map(|&s| FormulaItem::Symbol(s)));
+            let (left, right) = if is_right {
+                (items, Vec::new())
+            } else {
+                (Vec::new(), items)
+            };
+            let formula = FormulaTape {
+                left,
+                state,
+                head: 0,
+                right,
+            }
+            .normalized();
+            let Some(induction) =
+                prove_induction(machine.machine(), &formula, &mut self.rule_cache)
+            else {
+                continue;
+            };
+            // F(1) is record c
+            if induction.steps_const != c.step - b.step {
+                continue;
+            }
+            return Some(BouncerCertificate {
+                machine: *machine,
+                start_step: b.step,
+                formula,
+                steps_n: induction.steps_n,
+                steps_const: induction.steps_const,
+                rules: induction.rules,
+            });
+        }
+        None
+    }
+}
+
+impl Decider for DeciderBouncerProof {
+    fn decider_id() -> &'static decider::DeciderId {
+        &DECIDER_BOUNCER_PROOF_ID
+    }
+
+    fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
+        self.certificate = None;
+        self.rule_cache.clear();
+        let m = machine.machine();
+        let mut records: Vec<VecDeque<Record>> = vec![VecDeque::new(); (m.n_states() + 1) * 2];
+        let mut tape = Tape::new();
+        let mut state = 1;
+        let mut step = 0;
+        while step < self.step_limit as u64 {
+            let tr = m.transition(
+                State::new(state as usize),
+                Symbol::new(tape.read() as usize),
+            );
+            step += 1;
+            if tr.is_halt() {
+                return MachineStatus::DecidedHalt(step as StepBig);
+            }
+            state = tr.state() as u8;
+            let is_right = tr.is_dir_right();
+            if !tape.write_move(tr.is_symbol_one() as u8, is_right) {
+                continue;
+            }
+            if tape.width() > TAPE_CELLS_MAX {
+                return MachineStatus::Undecided(
+                    UndecidedReason::TapeSizeLimit,
+                    step as StepBig,
+                    tape.width() as u32,
+                );
+            }
+            let list = &mut records[state as usize * 2 + is_right as usize];
+            list.push_back(Record {
+                step,
+                cells: tape.cells_behind(is_right).to_vec(),
+            });
+            if list.len() > RECORDS_KEPT {
+                list.pop_front();
+            }
+            let list = &records[state as usize * 2 + is_right as usize];
+            if list.len() < 3 {
+                continue;
+            }
+            if let Some(certificate) = self.prove(machine, list, state, is_right) {
+                let num_repeaters = certificate.formula.num_repeaters() as u8;
+                self.certificate = Some(certificate);
+                return MachineStatus::DecidedNonHalt(NonHaltReason::BouncerProof(
+                    step as StepSmall,
+                    num_repeaters,
+                ));
+            }
+        }
+
+        MachineStatus::Undecided(
+            UndecidedReason::StepLimit,
+            self.step_limit,
+            tape.width() as u32,
+        )
+    }
+
+    fn decide_single_machine(machine: &MachineId, config: &Config) -> MachineStatus {
+        let mut d = Self::new(config);
+        d.decide_machine(machine)
+    }
+
+    fn step_limit(&self) -> StepBig {
+        self.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.step_limit = step_limit;
+    }
+
+    fn proof_certificate(&self) -> Option<Certificate> {
+        self.certificate.clone().map(Certificate::Bouncer)
+    }
+
+    fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
+        let decider = Self::new(batch_data.config);
+        decider::decider_generic_run_batch(decider, batch_data)
+    }
+}
+
+/// Proof of a bouncer as text, see [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BouncerCertificate {
+    pub machine: MachineId,
+    /// Step at which the machine reaches the formula tape with n = 0.
+    pub start_step: u64,
+    pub formula: FormulaTape,
+    /// The induction step F(n) -> F(n+1) takes steps_n * n + steps_const steps.
+    pub steps_n: u64,
+    pub steps_const: u64,
+    /// Shift rules used in the induction step.
+    pub rules: Vec<ShiftRule>,
+}
+
+impl BouncerCertificate {
+    /// Step at which the machine reaches the formula tape F(n).
+    pub fn steps_at(&self, n: u64) -> u64 {
+        self.start_step + self.steps_const * n + self.steps_n * n * n.saturating_sub(1) / 2
+    }
+
+    /// Reads the certificate text, lines starting with '#' are comments.
+    pub fn from_text(text: &str) -> Result<Self, &'static str> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        if lines.next() != Some(CERTIFICATE_HEADER) {
+            return Err("Bouncer certificate: header missing");
+        }
+        let mut machine = None;
+        let mut start_step = None;
+        let mut formula = None;
+        let mut steps = None;
+        let mut rules = Vec::new();
+        for line in lines {
+            let (key, value) = line
+                .split_once(' ')
+                .ok_or("Bouncer certificate: invalid line")?;
+            match key {
+                "machine" => machine = Some(MachineId::try_from(value)?),
+                "start_step" => {
+                    start_step = Some(
+                        value
+                            .parse()
+                            .map_err(|_| "Bouncer certificate: invalid start step")?,
+                    )
+                }
+                "formula" => formula = Some(FormulaTape::parse(value)?),
+                "induction_steps" => {
+                    let (n, c) = value
+                        .split_once("*n+")
+                        .ok_or("Bouncer certificate: invalid induction steps")?;
+                    steps = n.parse().ok().zip(c.parse().ok());
+                }
+                "rule" => rules.push(ShiftRule::parse(value)?),
+                _ => return Err("Bouncer certificate: unknown line"),
+            }
+        }
+        let (steps_n, steps_const) = steps.ok_or("Bouncer certificate: induction steps missing")?;
+        Ok(Self {
+            machine: machine.ok_or("Bouncer certificate: machine missing")?,
+            start_step: start_step.ok_or("Bouncer certificate: start step missing")?,
+            formula: formula.ok_or("Bouncer certificate: formula missing")?,
+            steps_n,
+            steps_const,
+            rules,
+        })
+    }
+
+    /// Checks the proof: The machine reaches the formula tape for n = 0 at the start step and the
+    /// induction step with its steps and shift rules holds.
+    pub fn verify(&self) -> Result<(), &'static str> {
+        let m = self.machine.machine();
+        let mut tape = Tape::new();
+        let mut state = 1;
+        for _ in 0..self.start_step {
+            let tr = m.transition(
+                State::new(state as usize),
+                Symbol::new(tape.read() as usize),
+            );
+            if tr.is_halt() {
+                return Err("Bouncer certificate: the machine halts before the start step");
+            }
+            state = tr.state() as u8;
+            tape.write_move(tr.is_symbol_one() as u8, tr.is_dir_right());
+        }
+        let left = cells(&self.formula.left, 0);
+        let right = cells(&self.formula.right, 0);
+        if state != self.formula.state
+            || tape.read() != self.formula.head
+            || trim_start_zeros(tape.cells_behind(true)) != trim_start_zeros(&left)
+            || trim_end_zeros(tape.cells_behind(false)) != trim_end_zeros(&right)
+        {
+            return Err("Bouncer certificate: the start step does not match the formula");
+        }
+
+        let induction = prove_induction(m, &self.formula, &mut HashMap::new())
+            .ok_or("Bouncer certificate: the induction step fails")?;
+        if induction.steps_n != self.steps_n
+            || induction.steps_const != self.steps_const
+            || induction.rules != self.rules
+        {
+            return Err("Bouncer certificate: the induction step differs");
+        }
+        Ok(())
+    }
+}
+
+impl Display for BouncerCertificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{CERTIFICATE_HEADER}")?;
+        writeln!(f, "machine {}", self.machine.to_standard_tm_text_format())?;
+        writeln!(f, "start_step {}", self.start_step)?;
+        writeln!(f, "formula {}", self.formula)?;
+        writeln!(f, "induction_steps {}*n+{}", self.steps_n, self.steps_const)?;
+        for rule in self.rules.iter() {
+            writeln!(f, "rule {rule}")?;
+        }
+        writeln!(
+            f,
+            "# formula(n) is reached at step {} + {}*n + {}*n*(n-1)/2",
+            self.start_step, self.steps_const, self.steps_n
+        )
+    }
+}
+
+/// Part of a formula tape.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FormulaItem {
+    Symbol(u8),
+    /// Word repeated n + c times (word, c).
+    Repeat(Vec<u8>, u32),
+}
+
+/// Tape with repeated words, the blank tape is on both sides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormulaTape {
+    /// Items left of the head, from left to right.
+    pub left: Vec<FormulaItem>,
+    pub state: u8,
+    /// Symbol under the head.
+    pub head: u8,
+    /// Items right of the head, from left to right.
+    pub right: Vec<FormulaItem>,
+}
+
+impl FormulaTape {
+    pub fn num_repeaters(&self) -> usize {
+        self.left
+            .iter()
+            .chain(self.right.iter())
+            .filter(|item| matches!(item, FormulaItem::Repeat(_, _)))
+            .count()
+    }
+
+    /// The formula tape for n + 1.
+    fn next(&self) -> Self {
+        let inc = |items: &[FormulaItem]| {
+            items
+                .iter()
+                .map(|item| match item {
+                    FormulaItem::Repeat(word, c) => FormulaItem::Repeat(word.clone(), c + 1),
+                    item => item.clone(),
+                })
+                .collect()
+        };
+        Self {
+            left: inc(&self.left),
+            right: inc(&self.right),
+            ..*self
+        }
+    }
+
+    fn normalized(&self) -> Self {
+        Self {
+            left: normalized(&self.left, true),
+            right: normalized(&self.right, false),
+            ..*self
+        }
+    }
+
+    /// Reads the formula tape as written by Display, e.g. '0^inf 1 (10)^(n+2) C>0 0^inf'.
+    pub fn parse(text: &str) -> Result<Self, &'static str> {
+        let invalid = "Formula tape: invalid";
+        let tokens: Vec<&str> = text.split_whitespace().collect();
+        if tokens.len() < 3 || tokens[0] != BLANK_TAPE || tokens[tokens.len() - 1] != BLANK_TAPE {
+            return Err("Formula tape: blank tape on both sides missing");
+        }
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        let mut head = None;
+        for token in &tokens[1..tokens.len() - 1] {
+            let items = if head.is_some() {
+                &mut right
+            } else {
+                &mut left
+            };
+            if let Some((state, symbol)) = token.split_once('>') {
+                let state = standard_tm::state_from_char(*state.as_bytes().first().ok_or(invalid)?)
+                    .filter(|&s| s > 0 && state.len() == 1)
+                    .ok_or(invalid)?;
+                let symbol = parse_word(symbol).filter(|w| w.len() == 1).ok_or(invalid)?;
+                if head.is_some() {
+                    return Err(invalid);
+                }
+                head = Some((state as u8, symbol[0]));
+            } else if let Some(repeat) = token.strip_prefix('(') {
+                let (word, exponent) = repeat.split_once(")^").ok_or(invalid)?;
+                let c = match exponent {
+                    "n" => 0,
+                    _ => exponent
+                        .strip_prefix("(n+")
+                        .and_then(|c| c.strip_suffix(')'))
+                        .and_then(|c| c.parse().ok())
+                        .ok_or(invalid)?,
+                };
+                items.push(FormulaItem::Repeat(parse_word(word).ok_or(invalid)?, c));
+            } else {
+                let word = parse_word(token).ok_or(invalid)?;
+                items.extend(word.into_iter().map(FormulaItem::Symbol));
+            }
+        }
+        let (state, head) = head.ok_or("Formula tape: head missing")?;
+        Ok(Self {
+            left,
+            state,
+            head,
+            right,
+        })
+    }
+}
+
+impl Display for FormulaTape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut tokens = vec![BLANK_TAPE.to_string()];
+        tokens.extend(item_tokens(&self.left));
+        tokens.push(format!(
+            "{}>{}",
+            standard_tm::state_to_char(self.state as usize),
+            self.head
+        ));
+        tokens.extend(item_tokens(&self.right));
+        tokens.push(BLANK_TAPE.to_string());
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+/// Shift rule 'u C> w -> w' u C>' (direction right) or 'w <C u -> <C u w'' (direction left): The machine enters
+/// the word in the state and leaves it on the other side in the same state after the steps. The context u next to
+/// the head is carried along, it is empty for most rules.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ShiftRule {
+    pub state: u8,
+    pub is_dir_right: bool,
+    pub word: Vec<u8>,
+    pub context: Vec<u8>,
+    pub word_new: Vec<u8>,
+    pub steps: u64,
+}
+
+impl ShiftRule {
+    /// Simulates the machine entering the word with the context behind the head.
+    /// None if it does not leave the word on the other side in the state with the context in front of the word.
+    fn simulate(
+        machine: &MachineBinary,
+        state: u8,
+        is_dir_right: bool,
+        word: &[u8],
+        context: &[u8],
+    ) -> Option<Self> {
+        let mut cells = if is_dir_right {
+            [context, word].concat()
+        } else {
+            [word, context].concat()
+        };
+        let len = cells.len() as isize;
+        let mut pos = if is_dir_right {
+            context.len() as isize
+        } else {
+            word.len() as isize - 1
+        };
+        let mut s = state;
+        let mut steps = 0;
+        loop {
+            let tr = machine.transition(
+                State::new(s as usize),
+                Symbol::new(cells[pos as usize] as usize),
+            );
+            if tr.is_halt() || steps == RULE_STEPS_MAX {
+                return None;
+            }
+            steps += 1;
+            cells[pos as usize] = tr.is_symbol_one() as u8;
+            s = tr.state() as u8;
+            pos += if tr.is_dir_right() { 1 } else { -1 };
+            if pos < 0 || pos >= len {
+                break;
+            }
+        }
+        let (word_new, context_new) = if is_dir_right {
+            cells.split_at(word.len())
+        } else {
+            let (context_new, word_new) = cells.split_at(context.len());
+            (word_new, context_new)
+        };
+        if (pos >= len) != is_dir_right || s != state || context_new != context {
+            return None;
+        }
+        Some(Self {
+            state,
+            is_dir_right,
+            word: word.to_vec(),
+            context: context.to_vec(),
+            word_new: word_new.to_vec(),
+            steps,
+        })
+    }
+
+    /// Reads the rule as written by Display.
+    fn parse(text: &str) -> Result<Self, &'static str> {
+        let invalid = "Bouncer certificate: invalid rule";
+        let (lhs, rhs) = text.split_once(" -> ").ok_or(invalid)?;
+        let lhs: Vec<&str> = lhs.split_whitespace().collect();
+        let mut rhs: Vec<&str> = rhs.split_whitespace().collect();
+        if rhs.pop() != Some("steps") {
+            return Err(invalid);
+        }
+        let steps = rhs.pop().and_then(|t| t.parse().ok()).ok_or(invalid)?;
+        let is_dir_right = lhs.iter().any(|t| t.ends_with('>'));
+        let (head, context, word, word_new) = match (is_dir_right, lhs.as_slice(), rhs.as_slice()) {
+            (true, [head, word], [word_new, head_new])
+            | (false, [word, head], [head_new, word_new])
+                if head == head_new =>
+            {
+                (*head, "", *word, *word_new)
+            }
+            (true, [context, head, word], [word_new, context_new, head_new])
+            | (false, [word, head, context], [head_new, context_new, word_new])
+                if head == head_new && context == context_new =>
+            {
+                (*head, *context, *word, *word_new)
+            }
+            _ => return Err(invalid),
+        };
+        let state = match is_dir_right {
+            true => head.strip_suffix('>'),
+            false => head.strip_prefix('<'),
+        }
+        .ok_or(invalid)?;
+        let state = standard_tm::state_from_char(*state.as_bytes().first().ok_or(invalid)?)
+            .filter(|&s| s > 0 && state.len() == 1)
+            .ok_or(invalid)? as u8;
+        let context = if context.is_empty() {
+            Vec::new()
+        } else {
+            parse_word(context).ok_or(invalid)?
+        };
+        Ok(Self {
+            state,
+            is_dir_right,
+            word: parse_word(word).ok_or(invalid)?,
+            context,
+            word_new: parse_word(word_new).ok_or(invalid)?,
+            steps,
+        })
+    }
+}
+
+impl Display for ShiftRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = standard_tm::state_to_char(self.state as usize);
+        let word = word_to_string(&self.word);
+        let word_new = word_to_string(&self.word_new);
+        // the context with its separating space
+        let context = match self.context.is_empty() {
+            true => String::new(),
+            false => format!("{} ", word_to_string(&self.context)),
+        };
+        if self.is_dir_right {
+            write!(
+                f,
+                "{context}{state}> {word} -> {word_new} {context}{state}> {} steps",
+                self.steps
+            )
+        } else {
+            write!(
+                f,
+                "{word} <{state} {context}-> <{state} {context}{word_new} {} steps",
+                self.steps
+            )
+        }
+    }
+}
+
+/// Steps and rules of the proven induction step.
+struct Induction {
+    steps_n: u64,
+    steps_const: u64,
+    rules: Vec<ShiftRule>,
+}
+
+/// Symbolic run of a formula tape, which holds for every n.
+struct SymbolicRun<'a> {
+    machine: &'a MachineBinary,
+    /// Items left of the head, the last is next to the head.
+    left: Vec<FormulaItem>,
+    /// Items right of the head, the last is next to the head.
+    right: Vec<FormulaItem>,
+    state: u8,
+    head: u8,
+    steps_n: u64,
+    steps_const: u64,
+    rules: Vec<ShiftRule>,
+}
+
+impl SymbolicRun<'_> {
+    fn formula(&self) -> FormulaTape {
+        FormulaTape {
+            left: self.left.clone(),
+            state: self.state,
+            head: self.head,
+            right: self.right.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// Executes one step and passes repeaters with shift rules. False if the step cannot be proven for all n.
+    fn step(&mut self, rule_cache: &mut RuleCache) -> bool {
+        let tr = self.machine.transition(
+            State::new(self.state as usize),
+            Symbol::new(self.head as usize),
+        );
+        if tr.is_halt() {
+            return false;
+        }
+        self.steps_const += 1;
+        self.state = tr.state() as u8;
+        let is_right = tr.is_dir_right();
+        let (behind, ahead) = if is_right {
+            (&mut self.left, &mut self.right)
+        } else {
+            (&mut self.right, &mut self.left)
+        };
+        behind.push(FormulaItem::Symbol(tr.is_symbol_one() as u8));
+        loop {
+            let (word, c) = match ahead.pop() {
+                None => {
+                    self.head = 0;
+                    return true;
+                }
+                Some(FormulaItem::Symbol(symbol)) => {
+                    self.head = symbol;
+                    return true;
+                }
+                Some(FormulaItem::Repeat(word, c)) => (word, c),
+            };
+            // symbols behind the head, nearest first, blank beyond the tape
+            let mut behind_symbols = Vec::with_capacity(CONTEXT_LEN_MAX);
+            for item in behind.iter().rev().map(Some).chain(std::iter::repeat(None)) {
+                match item {
+                    _ if behind_symbols.len() == CONTEXT_LEN_MAX => break,
+                    Some(FormulaItem::Symbol(symbol)) => behind_symbols.push(*symbol),
+                    Some(FormulaItem::Repeat(_, _)) => break,
+                    None => behind_symbols.push(0),
+                }
+            }
+            let state = self.state;
+            let machine = self.machine;
+            let mut rule = |word: &[u8]| {
+                (0..=behind_symbols.len()).find_map(|n| {
+                    let mut context = behind_symbols[..n].to_vec();
+                    if is_right {
+                        context.reverse();
+                    }
+                    rule_cache
+                        .entry((state, is_right, word.to_vec(), context.clone()))
+                        .or_insert_with(|| {
+                            ShiftRule::simulate(machine, state, is_right, word, &context)
+                        })
+                        .clone()
+                })
+            };
+            if let Some(r) = rule(&word) {
+                // the context stays next to the head
+                for _ in 0..r.context.len() {
+                    behind.pop();
+                }
+                behind.push(FormulaItem::Repeat(r.word_new.clone(), c));
+                if is_right {
+                    behind.extend(r.context.iter().map(|&s| FormulaItem::Symbol(s)));
+                } else {
+                    behind.extend(r.context.iter().rev().map(|&s| FormulaItem::Symbol(s)));
+                }
+                self.steps_n += r.steps;
+                self.steps_const += r.steps * c as u64;
+                if !self.rules.contains(&r) {
+                    self.rules.push(r);
+                }
+                continue;
+            }
+            if c == 0 {
+                return false;
+            }
+            // Take one copy, split so the rest of the repeater can be passed: w^m = u (vu)^(m-1) v
+            // with u the part next to the head. Without such a split the whole copy is taken.
+            let len = word.len();
+            let rotated = |r: usize| {
+                if is_right {
+                    [&word[r..], &word[..r]].concat()
+                } else {
+                    [&word[len - r..], &word[..len - r]].concat()
+                }
+            };
+            let r = (1..len)
+                .find(|&r| rule(&rotated(r)).is_some())
+                .unwrap_or(len);
+            let (near, far) = if is_right {
+                (&word[..r], &word[r..])
+            } else {
+                (&word[len - r..], &word[..len - r])
+            };
+            let symbols = |part: &[u8]| -> Vec<FormulaItem> {
+                part.iter().map(|&s| FormulaItem::Symbol(s)).collect()
+            };
+            // the stack ahead has the item next to the head last
+            if is_right {
+                ahead.extend(symbols(far).into_iter().rev());
+                ahead.push(FormulaItem::Repeat(rotated(r), c - 1));
+                ahead.extend(symbols(near).into_iter().rev());
+            } else {
+                ahead.extend(symbols(far));
+                ahead.push(FormulaItem::Repeat(rotated(r), c - 1));
+                ahead.extend(symbols(near));
+            }
+        }
+    }
+}
+
+/// Simulates the formula tape symbolically until it reaches the formula tape for n + 1.
+fn prove_induction(
+    machine: &MachineBinary,
+    formula: &FormulaTape,
+    rule_cache: &mut RuleCache,
+) -> Option<Induction> {
+    let target = formula.next().normalized();
+    let mut run = SymbolicRun {
+        machine,
+        left: formula.left.clone(),
+        right: formula.right.iter().rev().cloned().collect(),
+        state: formula.state,
+        head: formula.head,
+        steps_n: 0,
+        steps_const: 0,
+        rules: Vec::new(),
+    };
+    for _ in 0..SYMBOLIC_STEPS_MAX {
+        if !run.step(rule_cache) {
+            return None;
+        }
+        if run.state == target.state
+            && run.head == target.head
+            && run.formula().normalized() == target
+        {
+            return Some(Induction {
+                steps_n: run.steps_n,
+                steps_const: run.steps_const,
+                rules: run.rules,
+            });
+        }
+    }
+    None
+}
+
+/// Normal form of the items (left to right): No blank symbols or blank repeaters at the far end, repeaters
+/// rotated as far left as possible and copies right of a repeater absorbed.
+fn normalized(items: &[FormulaItem], is_far_end_left: bool) -> Vec<FormulaItem> {
+    let is_blank = |item: &FormulaItem| match item {
+        FormulaItem::Symbol(s) => *s == 0,
+        FormulaItem::Repeat(word, _) => word.iter().all(|&s| s == 0),
+    };
+    let mut v = items.to_vec();
+    loop {
+        if is_far_end_left {
+            let n = v.iter().take_while(|item| is_blank(item)).count();
+            v.drain(..n);
+        } else {
+            let n = v.iter().rev().take_while(|item| is_blank(item)).count();
+            v.truncate(v.len() - n);
+        }
+        let mut is_changed = false;
+        for i in 0..v.len() {
+            let FormulaItem::Repeat(word, c) = &v[i] else {
+                continue;
+            };
+            let k = word.len();
+            // x w^m = (x w')^m x, if x is the last symbol of w
+            if i > 0 && v[i - 1] == FormulaItem::Symbol(word[k - 1]) {
+                let rotated = [&word[k - 1..], &word[..k - 1]].concat();
+                v[i] = FormulaItem::Repeat(rotated, *c);
+                v[i - 1..=i].rotate_left(1);
+                is_changed = true;
+                break;
+            }
+            // w^m w = w^(m+1)
+            if v.len() >= i + 1 + k
+                && word
+                    .iter()
+                    .zip(&v[i + 1..i + 1 + k])
+                    .all(|(&s, item)| *item == FormulaItem::Symbol(s))
+            {
+                v[i] = FormulaItem::Repeat(word.clone(), c + 1);
+                v.drain(i + 1..i + 1 + k);
+                is_changed = true;
+                break;
+            }
+        }
+        if !is_changed {
+            return v;
+        }
+    }
+}
+
+/// Finds the words inserted into a to get b, as (position in a, word). The insertion points are as far right
+/// as possible. Inserting the words twice into a must give c.
+fn find_insertions(a: &[u8], b: &[u8], c: &[u8]) -> Option<Vec<(usize, Vec<u8>)>> {
+    let mut found = Vec::new();
+    let is_valid = |found: &Insertions| with_insertions(a, found, 2) == c;
+    if search_insertions(a, b, 0, &mut found, &is_valid) {
+        Some(found)
+    } else {
+        None
+    }
+}
+
+fn search_insertions(
+    a: &[u8],
+    b: &[u8],
+    pos: usize,
+    found: &mut Vec<(usize, Vec<u8>)>,
+    is_valid: &dyn Fn(&Insertions) -> bool,
+) -> bool {
+    let p = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    if p == a.len() && p == b.len() {
+        return !found.is_empty() && is_valid(found);
+    }
+    // no two insertions at the same point
+    if found.len() == REPEATERS_MAX || b.len() <= a.len() || (p == 0 && !found.is_empty()) {
+        return false;
+    }
+    for m in 1..=b.len() - a.len() {
+        found.push((pos + p, b[p..p + m].to_vec()));
+        if search_insertions(&a[p..], &b[p + m..], pos + p, found, is_valid) {
+            return true;
+        }
+        found.pop();
+    }
+    false
+}
+
+fn with_insertions(a: &[u8], insertions: &Insertions, copies: usize) -> Vec<u8> {
+    let mut cells = Vec::new();
+    let mut pos = 0;
+    for (p, word) in insertions {
+        cells.extend_from_slice(&a[pos..*p]);
+        for _ in 0..copies {
+            cells.extend_from_slice(word);
+        }
+        pos = *p;
+    }
+    cells.extend_from_slice(&a[pos..]);
+    cells
+}
+
+/// Cells of the items for n.
+fn cells(items: &[FormulaItem], n: u32) -> Vec<u8> {
+    let mut cells = Vec::new();
+    for item in items {
+        match item {
+            FormulaItem::Symbol(s) => cells.push(*s),
+            FormulaItem::Repeat(word, c) => {
+                for _ in 0..n + c {
+                    cells.extend_from_slice(word);
+                }
+            }
+        }
+    }
+    cells
+}
+
+/// Items as text, consecutive symbols are one word.
+fn item_tokens(items: &[FormulaItem]) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = Vec::new();
+    for item in items {
+        match item {
+            FormulaItem::Symbol(s) => word.push(*s),
+            FormulaItem::Repeat(w, c) => {
+                if !word.is_empty() {
+                    tokens.push(word_to_string(&word));
+                    word.clear();
+                }
+                if *c == 0 {
+                    tokens.push(format!("({})^n", word_to_string(w)));
+                } else {
+                    tokens.push(format!("({})^(n+{c})", word_to_string(w)));
+                }
+            }
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word_to_string(&word));
+    }
+    tokens
+}
+
+fn word_to_string(word: &[u8]) -> String {
+    word.iter().map(|s| (b'0' + s) as char).collect()
+}
+
+fn parse_word(text: &str) -> Option<Vec<u8>> {
+    if text.is_empty() {
+        return None;
+    }
+    text.bytes()
+        .map(|b| match b {
+            b'0' | b'1' => Some(b - b'0'),
+            _ => None,
+        })
+        .collect()
+}
+
+fn trim_start_zeros(cells: &[u8]) -> &[u8] {
+    let n = cells.iter().take_while(|&&s| s == 0).count();
+    &cells[n..]
+}
+
+fn trim_end_zeros(cells: &[u8]) -> &[u8] {
+    let n = cells.iter().rev().take_while(|&&s| s == 0).count();
+    &cells[..cells.len() - n]
+}
+
+/// Tape of a record, the cells behind the head. The head is on a new, blank cell.
+#[derive(Debug, Clone)]
+struct Record {
+    step: u64,
+    cells: Vec<u8>,
+}
+
+/// Concrete tape, which grows on both sides.
+struct Tape {
+    cells: Vec<u8>,
+    pos: usize,
+    /// Visited cells min..=max.
+    min: usize,
+    max: usize,
+}
+
+impl Tape {
+    fn new() -> Self {
+        Self {
+            cells: vec![0; TAPE_GROW],
+            pos: TAPE_GROW / 2,
+            min: TAPE_GROW / 2,
+            max: TAPE_GROW / 2,
+        }
+    }
+
+    fn read(&self) -> u8 {
+        self.cells[self.pos]
+    }
+
+    /// Writes the symbol and moves the head. Returns true if the head is on a new cell (record).
+    fn write_move(&mut self, symbol: u8, is_dir_right: bool) -> bool {
+        self.cells[self.pos] = symbol;
+        if is_dir_right {
+            self.pos += 1;
+            if self.pos == self.cells.len() {
+                self.cells.resize(self.cells.len() + TAPE_GROW, 0);
+            }
+            if self.pos > self.max {
+                self.max = self.pos;
+                return true;
+            }
+        } else {
+            if self.pos == 0 {
+                self.cells.splice(0..0, [0; TAPE_GROW]);
+                self.pos += TAPE_GROW;
+                self.min += TAPE_GROW;
+                self.max += TAPE_GROW;
+            }
+            self.pos -= 1;
+            if self.pos < self.min {
+                self.min = self.pos;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn width(&self) -> usize {
+        self.max - self.min + 1
+    }
+
+    /// Visited cells left of the head if the head moved right, otherwise right of the head.
+    fn cells_behind(&self, is_dir_right: bool) -> &[u8] {
+        if is_dir_right {
+            &self.cells[self.min..self.pos]
+        } else {
+            &self.cells[self.pos + 1..=self.max]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_binary::NotableMachineBinary;
+
+    #[test]
+    fn decider_bouncer_proof_certificate() {
+        // simple bouncer, context rule, two repeaters
+        for tm in [
+            "1RB0LB_1LA0LC_---1RD_0RA0RA",
+            "1RB1LC_0RC---_1LD1RC_0RC0RA",
+            "1RB1RA_1LC---_1RD1LC_0RA0RA",
+        ] {
+            let machine = MachineId::try_from(tm).unwrap();
+            let mut decider = DeciderBouncerProof::new(&Config::new_default(machine.n_states()));
+            let status = decider.decide_machine(&machine);
+            assert!(
+                matches!(
+                    status,
+                    MachineStatus::DecidedNonHalt(NonHaltReason::BouncerProof(_, _))
+                ),
+                "{tm}: {status}"
+            );
+            let certificate = decider.certificate().unwrap();
+            assert_eq!(certificate.verify(), Ok(()));
+            let read = BouncerCertificate::from_text(&certificate.to_string()).unwrap();
+            assert_eq!(&read, certificate);
+
+            // a changed formula is rejected
+            let mut wrong = read.clone();
+            wrong.formula.head = 1 - wrong.formula.head;
+            assert!(wrong.verify().is_err());
+        }
+        let certificate = BouncerCertificate::from_text(
+            "bouncer proof certificate\nmachine 1RB0LB_1LA0LC_---1RD_0RA0RA\nstart_step 13\n\
+            formula 0^inf (1010)^(n+1) 1 B>0 0^inf\ninduction_steps 12*n+24\n\
+            rule 1010 <B -> <B 0101 4 steps\nrule A> 0101 -> 1010 A> 8 steps\n",
+        )
+        .unwrap();
+        assert_eq!(certificate.verify(), Ok(()));
+        assert_eq!(certificate.steps_at(2), 13 + 24 * 2 + 12);
+    }
+
+    #[test]
+    fn decider_bouncer_proof_halting_undecided() {
+        let machine = NotableMachineBinary::BB4Max.machine_id();
+        let mut decider = DeciderBouncerProof::new(&Config::new_default(4));
+        assert_eq!(
+            decider.decide_machine(&machine),
+            MachineStatus::DecidedHalt(107)
+        );
+        assert!(decider.certificate().is_none());
+
+        // the tape grows like a bouncer, but the sweeps double in length (counter)
+        let machine = MachineId::try_from("0RB---_1LC1RB_0LD0LC_0RA0RA").unwrap();
+        assert!(matches!(
+            decider.decide_machine(&machine),
+            MachineStatus::Undecided(_, _, _)
+        ));
+    }
+}
@@ -0,0 +1,789 @@
+//! Diagnostic comparison of the step counts of halting machines. \
+//! Each decider runs on all machines of the data provider (not as chain), so a machine can be decided
+//! as halting by several deciders. The step counts of these machines must match, a mismatch
+//! indicates a bug, e.g. in the acceleration of a macro decider. This is the cheapest possible check
+//! of accelerated deciders, as the halt steps are calculated anyway.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+//!     decider::{decider_compare::compare_halt_steps, DeciderStandard},
+//! };
+//!
+//! let config = Config::builder(3).machine_limit(0).build();
+//! let deciders = [
+//!     DeciderStandard::Cycler.decider_config(&config),
+//!     DeciderStandard::Hold.decider_config(&config),
+//! ];
+//! let data_provider = EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+//! let comparison = compare_halt_steps(&deciders, data_provider).unwrap();
+//! assert!(!comparison.has_mismatches(), "{comparison}");
+//! ```
+//!
+//! With [Config::compare_halt_steps] set, [run_decider_chain_gen](crate::decider::decider_engine::run_decider_chain_gen)
+//! runs this comparison after the run and adds the mismatches as warnings to the result.
+//!
+//! [compare_decider_coverage] evaluates a new decider: It runs a baseline chain and the candidate decider
+//! on the same machines and lists the machines which only the candidate decides and vice versa.
+//!
+//! [cross_check_verdicts] runs two independent deciders, e.g. the cycler and the hold decider, on the same
+//! machines and reports each machine with contradicting verdicts: halt vs non-halt or different halt steps.
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+//!     decider::{decider_compare::cross_check_verdicts, DeciderStandard},
+//! };
+//!
+//! let config = Config::builder(3).machine_limit(0).build();
+//! let cycler = DeciderStandard::Cycler.decider_config(&config);
+//! let hold = DeciderStandard::Hold.decider_config(&config);
+//! let data_provider = EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+//! let check = cross_check_verdicts(&cycler, &hold, data_provider).unwrap();
+//! assert!(!check.has_mismatches(), "{check}");
+//! ```
+
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use num_format::ToFormattedString;
+
+use crate::{
+    config::{user_locale, Config, StepBig},
+    data_provider::DataProvider,
+    decider::{
+        decider_engine::decide_machines_statuses,
+        decider_result::{BatchData, DeciderResultStats, EndReason, WarningKind},
+        pre_decider::PreDeciderRun,
+        DeciderConfig,
+    },
+    machine_binary::MachineId,
+    status::MachineStatus,
+};
+
+/// Number of histogram buckets, bucket k holds the step counts 2^k..2^(k+1)-1 (bucket 0 also 0).
+const NUM_BUCKETS: usize = StepBig::BITS as usize;
+
+/// Halt step counts of one decider.
+#[derive(Debug, Clone)]
+pub struct DeciderHaltSteps {
+    pub decider_name: &'static str,
+    /// Number of machines decided as halting.
+    pub num_halt: u64,
+    /// Number of halting machines per step count bucket, see [HaltStepsComparison::bucket].
+    pub histogram: [u64; NUM_BUCKETS],
+}
+
+/// A machine which was decided as halting by several deciders with different step counts.
+#[derive(Debug, Clone)]
+pub struct HaltStepsMismatch {
+    pub machine: MachineId,
+    /// Decider name and halt steps of each decider which decided the machine as halting.
+    pub steps: Vec<(&'static str, StepBig)>,
+}
+
+impl Display for HaltStepsMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|(name, steps)| format!("{name}: {}", steps.to_formatted_string(&locale)))
+            .collect();
+        write!(
+            f,
+            "Machine No. {}: {}, {}",
+            self.machine.id().to_formatted_string(&locale),
+            self.machine.to_standard_tm_text_format(),
+            steps.join(", ")
+        )
+    }
+}
+
+/// Result of [compare_halt_steps].
+#[derive(Debug, Default, Clone)]
+pub struct HaltStepsComparison {
+    pub num_machines: u64,
+    /// Halt step counts per decider in order of the decider configs.
+    pub deciders: Vec<DeciderHaltSteps>,
+    /// Number of machines decided as halting by at least two deciders.
+    pub num_compared: u64,
+    /// Machines with different step counts, in order of the data provider.
+    pub mismatches: Vec<HaltStepsMismatch>,
+}
+
+impl HaltStepsComparison {
+    /// Histogram bucket of the step count.
+    pub fn bucket(steps: StepBig) -> usize {
+        steps.max(1).ilog2() as usize
+    }
+
+    pub fn has_mismatches(&self) -> bool {
+        !self.mismatches.is_empty()
+    }
+}
+
+impl Display for HaltStepsComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        writeln!(
+            f,
+            "Halt steps comparison: {} machines, halting in two or more deciders: {}, mismatches: {}",
+            self.num_machines.to_formatted_string(&locale),
+            self.num_compared.to_formatted_string(&locale),
+            self.mismatches.len()
+        )?;
+
+        // histogram with one column per decider, only buckets with machines
+        write!(f, "   {:<25}", "Steps")?;
+        for d in self.deciders.iter() {
+            write!(f, " | {:>12}", d.decider_name)?;
+        }
+        writeln!(f)?;
+        for bucket in 0..NUM_BUCKETS {
+            if self.deciders.iter().all(|d| d.histogram[bucket] == 0) {
+                continue;
+            }
+            let first: u64 = if bucket == 0 { 0 } else { 1 << bucket };
+            let last: u64 = (1 << (bucket + 1)) - 1;
+            let range = format!(
+                "{} - {}",
+                first.to_formatted_string(&locale),
+                last.to_formatted_string(&locale)
+            );
+            write!(f, "   {range:<25}")?;
+            for d in self.deciders.iter() {
+                write!(
+                    f,
+                    " | {:>12}",
+                    d.histogram[bucket].to_formatted_string(&locale)
+                )?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.mismatches.is_empty() {
+            writeln!(f, "Halt steps mismatches:")?;
+            for m in self.mismatches.iter() {
+                writeln!(f, "   {m}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs each decider on all machines of the data provider and compares the step counts of the machines
+/// which are decided as halting by more than one decider. \
+/// The pre-decider is not run, so the step counts are those of the deciders. The deciders need to return
+/// the decided machines in order of the batch, which all deciders of this crate do.
+pub fn compare_halt_steps(
+    decider_configs: &[DeciderConfig],
+    mut data_provider: impl DataProvider,
+) -> Result<HaltStepsComparison, EndReason> {
+    assert!(!decider_configs.is_empty(), "No decider given");
+    // record all decided machines of a batch
+    let batch_size = data_provider.batch_size().max(1);
+    let configs: Vec<Config> = decider_configs
+        .iter()
+        .map(|dc| {
+            Config::builder_from_config(dc.config())
+                .limit_machines_decided(batch_size)
+                .build()
+        })
+        .collect();
+
+    let mut comparison = HaltStepsComparison {
+        deciders: decider_configs
+            .iter()
+            .map(|dc| DeciderHaltSteps {
+                decider_name: dc.decider_id().name,
+                num_halt: 0,
+                histogram: [0; NUM_BUCKETS],
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let num_batches = data_provider.num_batches();
+    loop {
+        let data = data_provider
+            .machine_batch_next()
+            .map_err(|e| EndReason::Error(0, e.to_string()))?;
+        comparison.num_machines += data.machines.len() as u64;
+        if !data.machines.is_empty() {
+            // halt steps of each decider per machine of the batch
+            let mut halt_steps: Vec<Vec<(&'static str, StepBig)>> =
+                vec![Vec::new(); data.machines.len()];
+            for (i, (dc, config)) in decider_configs.iter().zip(configs.iter()).enumerate() {
+                let mut batch_data = BatchData {
+                    machines: &data.machines,
+                    result_decided: DeciderResultStats::new(config),
+                    machines_decided: Default::default(),
+                    machines_undecided: Default::default(),
+                    batch_no: data.batch_no,
+                    num_batches,
+                    decider_id: dc.decider_id(),
+                    config,
+                    run_predecider: PreDeciderRun::DoNotRun,
+                    step_budget: 0,
+                    steps_used: Vec::new(),
+                    hints: Vec::new(),
+                };
+                dc.f_decider()(&mut batch_data)?;
+                let decider_name = dc.decider_id().name;
+                // the decided machines are in order of the batch
+                let mut pos = 0;
+                for (machine, status) in batch_data
+                    .machines_decided
+                    .machines
+                    .iter()
+                    .zip(batch_data.machines_decided.states.iter())
+                {
+                    while data.machines[pos] != *machine {
+                        pos += 1;
+                    }
+                    let Some(steps) = status_halt_steps(status) else {
+                        continue;
+                    };
+                    let d = &mut comparison.deciders[i];
+                    d.num_halt += 1;
+                    d.histogram[HaltStepsComparison::bucket(steps)] += 1;
+                    halt_steps[pos].push((decider_name, steps));
+                }
+            }
+            for (machine, steps) in data.machines.iter().zip(halt_steps) {
+                if steps.len() < 2 {
+                    continue;
+                }
+                comparison.num_compared += 1;
+                if steps.iter().any(|(_, s)| *s != steps[0].1) {
+                    comparison.mismatches.push(HaltStepsMismatch {
+                        machine: *machine,
+                        steps,
+                    });
+                }
+            }
+        }
+        match data.end_reason {
+            EndReason::IsLastBatch | EndReason::NoMoreData => break,
+            EndReason::None => {}
+            end_reason => return Err(end_reason),
+        }
+    }
+
+    Ok(comparison)
+}
+
+/// Runs [compare_halt_steps] after a run with [Config::compare_halt_steps] set and adds each mismatch
+/// as warning to the result of the run.
+pub(crate) fn compare_halt_steps_or_warn(
+    decider_configs: &[DeciderConfig],
+    data_provider: impl DataProvider,
+    result: &mut DeciderResultStats,
+) {
+    match compare_halt_steps(decider_configs, data_provider) {
+        Ok(comparison) => {
+            for m in comparison.mismatches.iter() {
+                result.add_warning(WarningKind::HaltStepsMismatch, || m.to_string());
+            }
+        }
+        Err(e) => result.add_warning(WarningKind::HaltStepsMismatch, || {
+            format!("Halt steps comparison failed: {e}")
+        }),
+    }
+}
+
+/// A machine decided by only one side of [compare_decider_coverage].
+#[derive(Debug, Clone)]
+pub struct CoverageDifference {
+    pub machine: MachineId,
+    /// Final status of the baseline chain.
+    pub status_baseline: MachineStatus,
+    pub status_candidate: MachineStatus,
+}
+
+/// Result of [compare_decider_coverage].
+#[derive(Debug, Default, Clone)]
+pub struct DeciderCoverageComparison {
+    pub num_machines: u64,
+    pub baseline_names: Vec<&'static str>,
+    pub candidate_name: &'static str,
+    pub num_decided_baseline: u64,
+    pub num_decided_candidate: u64,
+    /// Run time of the baseline chain, summed over all batches.
+    pub duration_baseline: Duration,
+    pub duration_candidate: Duration,
+    /// Machines decided by the candidate but not by the baseline chain, in order of the data provider.
+    pub only_candidate: Vec<CoverageDifference>,
+    /// Machines decided by the baseline chain but not by the candidate, in order of the data provider.
+    pub only_baseline: Vec<CoverageDifference>,
+}
+
+impl Display for DeciderCoverageComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        writeln!(
+            f,
+            "Decider coverage: {} machines, baseline {} vs candidate {}",
+            self.num_machines.to_formatted_string(&locale),
+            self.baseline_names.join(" -> "),
+            self.candidate_name
+        )?;
+        writeln!(
+            f,
+            "   Baseline:  {:>12} decided in {:?}",
+            self.num_decided_baseline.to_formatted_string(&locale),
+            self.duration_baseline
+        )?;
+        writeln!(
+            f,
+            "   Candidate: {:>12} decided in {:?}",
+            self.num_decided_candidate.to_formatted_string(&locale),
+            self.duration_candidate
+        )?;
+        for (title, differences) in [
+            ("Decided only by the candidate", &self.only_candidate),
+            ("Decided only by the baseline", &self.only_baseline),
+        ] {
+            writeln!(f, "{title}: {}", differences.len())?;
+            for d in differences.iter() {
+                writeln!(
+                    f,
+                    "   Machine No. {}: {}, baseline: {}, candidate: {}",
+                    d.machine.id().to_formatted_string(&locale),
+                    d.machine.to_standard_tm_text_format(),
+                    d.status_baseline,
+                    d.status_candidate
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs the baseline chain and the candidate decider on all machines of the data provider and returns
+/// the machines which only one of them decides, with the run time of both. \
+/// The baseline runs as chain, each decider on the machines left undecided by the previous ones. The
+/// pre-decider is not run, see [decide_machines_statuses].
+pub fn compare_decider_coverage(
+    baseline: &[DeciderConfig],
+    candidate: &DeciderConfig,
+    mut data_provider: impl DataProvider,
+) -> Result<DeciderCoverageComparison, EndReason> {
+    assert!(!baseline.is_empty(), "No baseline decider given");
+    let mut comparison = DeciderCoverageComparison {
+        baseline_names: baseline.iter().map(|dc| dc.decider_id().name).collect(),
+        candidate_name: candidate.decider_id().name,
+        ..Default::default()
+    };
+    loop {
+        let data = data_provider
+            .machine_batch_next()
+            .map_err(|e| EndReason::Error(0, e.to_string()))?;
+        comparison.num_machines += data.machines.len() as u64;
+        if !data.machines.is_empty() {
+            let start = Instant::now();
+            let statuses_baseline = decide_machines_statuses(baseline, &data.machines)?;
+            comparison.duration_baseline += start.elapsed();
+            let start = Instant::now();
+            let statuses_candidate =
+                decide_machines_statuses(std::slice::from_ref(candidate), &data.machines)?;
+            comparison.duration_candidate += start.elapsed();
+
+            for ((machine, status_baseline), status_candidate) in data
+                .machines
+                .iter()
+                .zip(statuses_baseline)
+                .zip(statuses_candidate)
+            {
+                let difference = CoverageDifference {
+                    machine: *machine,
+                    status_baseline,
+                    status_candidate,
+                };
+                match (is_decided(&status_baseline), is_decided(&status_candidate)) {
+                    (true, true) => {
+                        comparison.num_decided_baseline += 1;
+                        comparison.num_decided_candidate += 1;
+                    }
+                    (true, false) => {
+                        comparison.num_decided_baseline += 1;
+                        comparison.only_baseline.push(difference);
+                    }
+                    (false, true) => {
+                        comparison.num_decided_candidate += 1;
+                        comparison.only_candidate.push(difference);
+                    }
+                    (false, false) => {}
+                }
+            }
+        }
+        match data.end_reason {
+            EndReason::IsLastBatch | EndReason::NoMoreData => break,
+            EndReason::None => {}
+            end_reason => return Err(end_reason),
+        }
+    }
+
+    Ok(comparison)
+}
+
+/// Kind of contradiction found by [cross_check_verdicts].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerdictMismatchKind {
+    /// One decider decided halt, the other non-halt.
+    HaltNonHalt,
+    /// Both deciders decided halt, but after different steps.
+    HaltSteps,
+}
+
+/// A machine with contradicting verdicts of the two deciders of [cross_check_verdicts].
+#[derive(Debug, Clone)]
+pub struct VerdictMismatch {
+    pub machine: MachineId,
+    pub kind: VerdictMismatchKind,
+    pub status_a: MachineStatus,
+    pub status_b: MachineStatus,
+}
+
+/// Result of [cross_check_verdicts].
+#[derive(Debug, Default, Clone)]
+pub struct VerdictCrossCheck {
+    pub num_machines: u64,
+    pub decider_name_a: &'static str,
+    pub decider_name_b: &'static str,
+    /// Number of machines decided by both deciders, only these can contradict.
+    pub num_compared: u64,
+    /// Machines with contradicting verdicts, in order of the data provider.
+    pub mismatches: Vec<VerdictMismatch>,
+}
+
+impl VerdictCrossCheck {
+    pub fn has_mismatches(&self) -> bool {
+        !self.mismatches.is_empty()
+    }
+}
+
+impl Display for VerdictCrossCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        writeln!(
+            f,
+            "Verdict cross-check {} vs {}: {} machines, decided by both: {}, mismatches: {}",
+            self.decider_name_a,
+            self.decider_name_b,
+            self.num_machines.to_formatted_string(&locale),
+            self.num_compared.to_formatted_string(&locale),
+            self.mismatches.len()
+        )?;
+        for m in self.mismatches.iter() {
+            writeln!(
+                f,
+                "   Machine No. {}: {}, {:?}, {}: {}, {}: {}",
+                m.machine.id().to_formatted_string(&locale),
+                m.machine.to_standard_tm_text_format(),
+                m.kind,
+                self.decider_name_a,
+                m.status_a,
+                self.decider_name_b,
+                m.status_b
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs two independent deciders on all machines of the data provider and returns the machines for which
+/// the verdicts contradict each other: one decider decided halt and the other non-halt, or both decided halt
+/// with different step counts. A machine left undecided by one decider is no contradiction. \
+/// The pre-decider is not run, see [decide_machines_statuses].
+pub fn cross_check_verdicts(
+    decider_a: &DeciderConfig,
+    decider_b: &DeciderConfig,
+    mut data_provider: impl DataProvider,
+) -> Result<VerdictCrossCheck, EndReason> {
+    let mut check = VerdictCrossCheck {
+        decider_name_a: decider_a.decider_id().name,
+        decider_name_b: decider_b.decider_id().name,
+        ..Default::default()
+    };
+    loop {
+        let data = data_provider
+            .machine_batch_next()
+            .map_err(|e| EndReason::Error(0, e.to_string()))?;
+        check.num_machines += data.machines.len() as u64;
+        if !data.machines.is_empty() {
+            let statuses_a =
+                decide_machines_statuses(std::slice::from_ref(decider_a), &data.machines)?;
+            let statuses_b =
+                decide_machines_statuses(std::slice::from_ref(decider_b), &data.machines)?;
+            for ((machine, status_a), status_b) in
+                data.machines.iter().zip(statuses_a).zip(statuses_b)
+            {
+                if !is_decided(&status_a) || !is_decided(&status_b) {
+                    continue;
+                }
+                check.num_compared += 1;
+                let kind = match (status_halt_steps(&status_a), status_halt_steps(&status_b)) {
+                    (Some(steps_a), Some(steps_b)) if steps_a != steps_b => {
+                        VerdictMismatchKind::HaltSteps
+                    }
+                    (Some(_), None) | (None, Some(_))
+                        if is_non_halt(&status_a) || is_non_halt(&status_b) =>
+                    {
+                        VerdictMismatchKind::HaltNonHalt
+                    }
+                    _ => continue,
+                };
+                check.mismatches.push(VerdictMismatch {
+                    machine: *machine,
+                    kind,
+                    status_a,
+                    status_b,
+                });
+            }
+        }
+        match data.end_reason {
+            EndReason::IsLastBatch | EndReason::NoMoreData => break,
+            EndReason::None => {}
+            end_reason => return Err(end_reason),
+        }
+    }
+
+    Ok(check)
+}
+
+/// Steps of a halting machine, None if the status is not halt.
+fn status_halt_steps(status: &MachineStatus) -> Option<StepBig> {
+    match status {
+        MachineStatus::DecidedHalt(steps)
+        | MachineStatus::DecidedHaltField(steps, _)
+        | MachineStatus::DecidedHaltDetail(steps, _, _) => Some(*steps),
+        _ => None,
+    }
+}
+
+fn is_non_halt(status: &MachineStatus) -> bool {
+    matches!(status, MachineStatus::DecidedNonHalt(_))
+}
+
+fn is_decided(status: &MachineStatus) -> bool {
+    !matches!(
+        status,
+        MachineStatus::NoDecision | MachineStatus::Undecided(_, _, _)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::CoreUsage,
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::run_decider_chain_gen, decider_halt_long::DeciderHaltLong, Decider,
+            DeciderId, DeciderStandard, DECIDER_HALT_ID,
+        },
+        status::NonHaltReason,
+    };
+
+    static DECIDER_HALT_PLUS_ONE_ID: DeciderId = DeciderId {
+        id: 90,
+        name: "Halt + 1",
+        sub_dir: "halt_plus_one",
+    };
+
+    static DECIDER_ALL_NON_HALT_ID: DeciderId = DeciderId {
+        id: 91,
+        name: "All Non-Halt",
+        sub_dir: "all_non_halt",
+    };
+
+    /// Halt decider which counts one step too many for machines halting after 10 or more steps.
+    fn decider_halt_plus_one(batch_data: &mut BatchData) -> Result<(), EndReason> {
+        let config = batch_data.config;
+        for machine in batch_data.machines.iter() {
+            let status = match DeciderHaltLong::decide_single_machine(machine, config) {
+                MachineStatus::DecidedHalt(steps) | MachineStatus::DecidedHaltField(steps, _)
+                    if steps >= 10 =>
+                {
+                    MachineStatus::DecidedHalt(steps + 1)
+                }
+                status => status,
+            };
+            if !matches!(status, MachineStatus::Undecided(_, _, _)) {
+                batch_data.machines_decided.machines.push(*machine);
+                batch_data.machines_decided.states.push(status);
+            }
+        }
+        Ok(())
+    }
+
+    /// Decider which wrongly decides all machines as non-halting cycler.
+    fn decider_all_non_halt(batch_data: &mut BatchData) -> Result<(), EndReason> {
+        for machine in batch_data.machines.iter() {
+            batch_data.machines_decided.machines.push(*machine);
+            batch_data
+                .machines_decided
+                .states
+                .push(MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(1, 1)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn cross_check_verdicts_finds_contradictions() {
+        let config = Config::builder(3).machine_limit(0).build();
+        let new_provider =
+            || EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let cycler = DeciderStandard::Cycler.decider_config(&config);
+        let hold = DeciderStandard::Hold.decider_config(&config);
+        let check = cross_check_verdicts(&cycler, &hold, new_provider()).unwrap();
+        assert!(check.num_compared > 0);
+        assert!(!check.has_mismatches(), "{check}");
+
+        let plus_one =
+            DeciderConfig::new(&DECIDER_HALT_PLUS_ONE_ID, decider_halt_plus_one, &config);
+        let check = cross_check_verdicts(&hold, &plus_one, new_provider()).unwrap();
+        assert!(check.has_mismatches());
+        assert!(check
+            .mismatches
+            .iter()
+            .all(|m| m.kind == VerdictMismatchKind::HaltSteps));
+
+        let all_non_halt =
+            DeciderConfig::new(&DECIDER_ALL_NON_HALT_ID, decider_all_non_halt, &config);
+        let check = cross_check_verdicts(&all_non_halt, &hold, new_provider()).unwrap();
+        assert!(check.has_mismatches());
+        assert!(check
+            .mismatches
+            .iter()
+            .all(|m| m.kind == VerdictMismatchKind::HaltNonHalt
+                && status_halt_steps(&m.status_b).is_some()));
+        assert!(check.to_string().contains("HaltNonHalt"));
+    }
+
+    #[test]
+    fn compare_halt_steps_cycler_hold_match() {
+        let config = Config::builder(2).machine_limit(0).build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let data_provider = EnumeratorBinary::new(EnumeratorType::EnumeratorFullForward, &config);
+        let comparison = compare_halt_steps(&deciders, data_provider).unwrap();
+
+        assert_eq!(comparison.num_machines, 9u64.pow(4));
+        assert!(comparison.num_compared > 0);
+        assert!(!comparison.has_mismatches());
+        // BB2 max halts after 6 steps
+        for d in comparison.deciders.iter() {
+            assert!(d.histogram[HaltStepsComparison::bucket(6)] > 0);
+            assert!(d.histogram[3..].iter().all(|n| *n == 0));
+        }
+    }
+
+    #[test]
+    fn compare_halt_steps_reports_mismatch() {
+        let config = Config::builder(3).machine_limit(0).build();
+        let deciders = [
+            DeciderConfig::new(
+                &DECIDER_HALT_ID,
+                DeciderHaltLong::decider_run_batch,
+                &config,
+            ),
+            DeciderConfig::new(&DECIDER_HALT_PLUS_ONE_ID, decider_halt_plus_one, &config),
+        ];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let comparison = compare_halt_steps(&deciders, data_provider).unwrap();
+
+        assert!(comparison.has_mismatches());
+        for m in comparison.mismatches.iter() {
+            assert_eq!(m.steps.len(), 2);
+            assert_eq!(m.steps[0].1 + 1, m.steps[1].1);
+            assert!(m.steps[0].1 >= 10);
+        }
+        let s = comparison.to_string();
+        assert!(s.contains("Halt steps mismatches:"));
+        assert!(s.contains(
+            &comparison.mismatches[0]
+                .machine
+                .to_standard_tm_text_format()
+        ));
+    }
+
+    #[test]
+    fn compare_halt_steps_config_warns_in_run() {
+        let run = |compare: bool| {
+            let config = Config::builder(3)
+                .machine_limit(0)
+                .compare_halt_steps(compare)
+                .build();
+            let deciders = [
+                DeciderConfig::new(
+                    &DECIDER_HALT_ID,
+                    DeciderHaltLong::decider_run_batch,
+                    &config,
+                ),
+                DeciderConfig::new(&DECIDER_HALT_PLUS_ONE_ID, decider_halt_plus_one, &config),
+            ];
+            let comparison = compare_halt_steps(
+                &deciders,
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config),
+            )
+            .unwrap();
+            let result = run_decider_chain_gen(
+                &deciders,
+                EnumeratorType::EnumeratorReducedForward,
+                CoreUsage::SingleCore,
+            );
+            (comparison.mismatches.len() as u64, result)
+        };
+
+        let (num_mismatches, result) = run(true);
+        assert!(num_mismatches > 0);
+        assert_eq!(
+            result.warnings().count(WarningKind::HaltStepsMismatch),
+            num_mismatches
+        );
+        let (_, result) = run(false);
+        assert_eq!(result.warnings().count(WarningKind::HaltStepsMismatch), 0);
+    }
+
+    #[test]
+    fn compare_decider_coverage_hold_vs_cycler() {
+        let config = Config::builder(3).machine_limit(0).build();
+        let hold = DeciderStandard::Hold.decider_config(&config);
+        let cycler = DeciderStandard::Cycler.decider_config(&config);
+        let new_provider =
+            || EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+
+        // the cycler decides the cyclers on top of the halting machines
+        let coverage =
+            compare_decider_coverage(std::slice::from_ref(&hold), &cycler, new_provider()).unwrap();
+        assert!(coverage.num_machines > 0);
+        assert!(!coverage.only_candidate.is_empty());
+        assert!(coverage.only_candidate.iter().all(|d| matches!(
+            d.status_candidate,
+            MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(_, _))
+        )));
+        assert_eq!(
+            coverage.num_decided_candidate,
+            coverage.num_decided_baseline + coverage.only_candidate.len() as u64
+                - coverage.only_baseline.len() as u64
+        );
+
+        // roles swapped
+        let swapped =
+            compare_decider_coverage(std::slice::from_ref(&cycler), &hold, new_provider()).unwrap();
+        assert_eq!(swapped.only_baseline.len(), coverage.only_candidate.len());
+        assert_eq!(swapped.only_candidate.len(), coverage.only_baseline.len());
+        assert!(swapped.to_string().contains("Decided only by the baseline"));
+    }
+}
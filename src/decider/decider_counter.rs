@@ -0,0 +1,313 @@
+//! Counter decider, which proves non-halting of machines implementing a binary counter. \
+//! The machine is run as [MacroMachine] with block sizes 1 to [COUNTER_BLOCK_SIZE_MAX]. The counter is a
+//! word of digit blocks D0 and D1 between the blank tape and the head. The head faces the least significant
+//! digit in the carry state C, behind the head is a fixed tail (possibly empty) followed by the blank tape:
+//! `0^∞ {D0,D1}* <C tail 0^∞` (or mirrored). The counter is proven, if these block transitions hold:
+//! 1. Carry: C turns D1 into D0 and continues in C towards the most significant digit.
+//! 2. Set: C turns D0 into D1 and turns around in the return state R.
+//! 3. Extend: C turns a blank block into D1 and turns around in R.
+//! 4. Return: R passes D0 unchanged.
+//! 5. Tail: R runs through the tail and comes back to the counter in C, leaving the tail unchanged.
+//!
+//! Then each counter value leads to the next counter value without halting, so by induction over the
+//! counter values the machine never halts. All rules are exact simulations of finitely many steps. \
+//! Rule extend defines D1 and R for each carry state, rule carry then defines D0, so the rules carry, set,
+//! extend and return are checked once per block size with four block transitions per carry state and direction.
+//! A block size without any carry state is skipped. During the run only configurations in a carry state with
+//! these rules are checked for the digits and the tail. It is not part of the standard chain.
+//!
+//! Cost: about 10 µs per machine (release build, random BB4 machines, default step limit), most machines
+//! are rejected before the run as no block size has a carry state. The run itself costs up to
+//! [COUNTER_BLOCK_SIZE_MAX] times the step limit in machine steps, with macro steps passing whole runs.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     decider::{decider_counter::DeciderCounter, Decider},
+//!     machine_binary::MachineId,
+//!     status::{MachineStatus, NonHaltReason},
+//! };
+//!
+//! let config = Config::new_default(4);
+//! let machine = MachineId::try_from("1RB---_0LB1RC_1LB0RD_0RC0RA").unwrap();
+//! let status = DeciderCounter::decide_single_machine(&machine, &config);
+//! assert_eq!(status, MachineStatus::DecidedNonHalt(NonHaltReason::Counter(14, 2)));
+//! ```
+
+use std::collections::HashSet;
+
+use crate::{
+    config::{Config, StepBig, StepSmall},
+    decider::{
+        self,
+        decider_result::{BatchData, ResultUnitEndReason},
+        Decider, DECIDER_COUNTER_ID,
+    },
+    machine_binary::MachineId,
+    macro_machine::{Block, MacroMachine, MacroRunResult},
+    status::{MachineStatus, NonHaltReason, UndecidedReason},
+};
+
+/// Largest block size of the digits.
+pub const COUNTER_BLOCK_SIZE_MAX: usize = 6;
+/// Maximum number of runs of the tail.
+const TAIL_RUNS_MAX: usize = 8;
+/// Maximum number of macro steps to run through the tail.
+const TAIL_MACRO_STEPS_MAX: u64 = 200;
+
+type Runs = Vec<(Block, u64)>;
+
+/// Digit blocks of the counter and the return state R for a carry state.
+#[derive(Debug, Clone, Copy)]
+struct Digits {
+    d0: Block,
+    d1: Block,
+    ret: u8,
+}
+
+#[derive(Debug)]
+pub struct DeciderCounter {
+    step_limit: StepBig,
+}
+
+impl DeciderCounter {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            step_limit: config.step_limit_decider_counter() as StepBig,
+        }
+    }
+
+    /// Returns the [Digits] for each carry state (index state) and carry direction (index facing right), if the
+    /// rules carry, set, extend and return hold. Only configurations in these states can be counters.
+    fn digits_all(mm: &mut MacroMachine, n_states: usize) -> Vec<[Option<Digits>; 2]> {
+        (0..=n_states as u8)
+            .map(|carry| {
+                if carry == 0 {
+                    return [None; 2];
+                }
+                [false, true].map(|f| Self::digits(mm, carry, f))
+            })
+            .collect()
+    }
+
+    /// Returns the [Digits] if the rules carry, set, extend and return hold for the carry state.     /// The blank block defines D1 and the return state (rule extend), then D1 defines D0 (rule carry),
+    /// so only four block transitions are checked.
+    fn digits(mm: &mut MacroMachine, carry: u8, f: bool) -> Option<Digits> {
+        // extend
+        let (d1, ret, is_dir_right) = mm.block_transition(carry, !f, 0)?;
+        if is_dir_right == f {
+            return None;
+        }
+        // carry
+        let (d0, state, is_dir_right) = mm.block_transition(carry, !f, d1)?;
+        if state != carry || is_dir_right != f {
+            return None;
+        }
+        // set and return
+        (mm.block_transition(carry, !f, d0) == Some((d1, ret, !f))
+            && mm.block_transition(ret, f, d0) == Some((d0, ret, !f)))
+        .then_some(Digits { d0, d1, ret })
+    }
+
+    /// Returns true if the current configuration of the macro machine is a proven counter.
+    /// The rules are known per carry state, so the tape is only checked for the carry states with rules.
+    /// The tail is simulated on the second macro machine, failed tails (per carry state) are not simulated again.
+    fn is_counter(
+        mm: &MacroMachine,
+        mm_tail: &mut MacroMachine,
+        digits_all: &[[Option<Digits>; 2]],
+        tails_failed: &mut [HashSet<Runs>],
+    ) -> bool {
+        // f: the carry runs in this direction
+        let f = mm.is_facing_right();
+        let carry = mm.state();
+        let Some(Digits { d0, d1, ret }) = digits_all[carry as usize][f as usize] else {
+            return false;
+        };
+        let mut digits = mm
+            .runs(f)
+            .iter()
+            .skip_while(|(block, _)| *block == 0)
+            .peekable();
+        if digits.peek().is_none() || digits.any(|&(block, _)| block != d0 && block != d1) {
+            return false;
+        }
+
+        // rule tail
+        let tail = normalized(mm.runs(!f));
+        let tails_failed = &mut tails_failed[carry as usize];
+        if tail.len() > TAIL_RUNS_MAX || tails_failed.contains(&tail) {
+            return false;
+        }
+        let (left, right) = if f {
+            (tail.clone(), Vec::new())
+        } else {
+            (Vec::new(), tail.clone())
+        };
+        mm_tail.set_configuration(ret, !f, left, right);
+        while mm_tail.num_macro_steps() < TAIL_MACRO_STEPS_MAX {
+            if mm_tail.step() != MacroRunResult::Running {
+                break;
+            }
+            // back at the counter
+            if mm_tail.is_facing_right() == f && mm_tail.runs(f).is_empty() {
+                if mm_tail.state() == carry && normalized(mm_tail.runs(!f)) == tail {
+                    return true;
+                }
+                break;
+            }
+        }
+        tails_failed.insert(tail);
+        false
+    }
+}
+
+/// Runs without the blank blocks at the far end and with identical neighbors merged.
+fn normalized(runs: &[(Block, u64)]) -> Runs {
+    let mut result: Runs = Vec::with_capacity(runs.len());
+    for &(block, count) in runs.iter().skip_while(|(block, _)| *block == 0) {
+        match result.last_mut() {
+            Some((b, c)) if *b == block => *c += count,
+            _ => result.push((block, count)),
+        }
+    }
+    result
+}
+
+impl Decider for DeciderCounter {
+    fn decider_id() -> &'static decider::DeciderId {
+        &DECIDER_COUNTER_ID
+    }
+
+    fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
+        let step_limit = self.step_limit as u64;
+        let n_states = machine.n_states();
+        let mut has_digits = false;
+        for block_size in 1..=COUNTER_BLOCK_SIZE_MAX {
+            let mut mm = MacroMachine::new(machine.machine(), block_size);
+            let digits_all = Self::digits_all(&mut mm, n_states);
+            // the rules can never hold for this block size
+            if digits_all.iter().flatten().all(Option::is_none) {
+                continue;
+            }
+            has_digits = true;
+            // shares the calculated block transitions
+            let mut mm_tail = mm.clone();
+            let mut tails_failed = vec![HashSet::new(); n_states + 1];
+            while mm.steps() <= step_limit {
+                match mm.step() {
+                    MacroRunResult::Running => {
+                        if Self::is_counter(&mm, &mut mm_tail, &digits_all, &mut tails_failed) {
+                            return MachineStatus::DecidedNonHalt(NonHaltReason::Counter(
+                                mm.steps() as StepSmall,
+                                block_size as u8,
+                            ));
+                        }
+                    }
+                    // the same for all block sizes
+                    _ => {
+                        return MachineStatus::Undecided(
+                            UndecidedReason::DeciderNoResult,
+                            mm.steps() as StepBig,
+                            mm.tape_size_cells() as u32,
+                        )
+                    }
+                }
+            }
+        }
+
+        // no block size was run
+        if !has_digits {
+            return MachineStatus::Undecided(UndecidedReason::DeciderNoResult, 0, 0);
+        }
+        MachineStatus::Undecided(UndecidedReason::StepLimit, self.step_limit, 0)
+    }
+
+    fn decide_single_machine(machine: &MachineId, config: &Config) -> MachineStatus {
+        let mut d = Self::new(config);
+        d.decide_machine(machine)
+    }
+
+    fn step_limit(&self) -> StepBig {
+        self.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.step_limit = step_limit;
+    }
+
+    fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
+        let decider = Self::new(batch_data.config);
+        decider::decider_generic_run_batch(decider, batch_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_binary::NotableMachineBinary;
+
+    fn decide(tm: &str) -> MachineStatus {
+        let machine = MachineId::try_from(tm).unwrap();
+        let config = Config::new_default(machine.n_states());
+        DeciderCounter::decide_single_machine(&machine, &config)
+    }
+
+    #[test]
+    fn decider_counter_binary_counter() {
+        // undecided by the cycler, bouncer and halt decider
+        assert_eq!(
+            decide("1RB---_0LB1RC_1LB0RD_0RC0RA"),
+            MachineStatus::DecidedNonHalt(NonHaltReason::Counter(14, 2))
+        );
+    }
+
+    #[test]
+    fn decider_counter_halting_undecided() {
+        let machine = NotableMachineBinary::BB4Max.machine_id();
+        let status = DeciderCounter::decide_single_machine(&machine, &Config::new_default(4));
+        assert!(matches!(status, MachineStatus::Undecided(_, _, _)));
+    }
+
+    #[test]
+    fn decider_counter_batch_with_non_counters() {
+        use crate::decider::{
+            decider_result::DeciderResultStats, pre_decider::PreDeciderRun, DECIDER_COUNTER_ID,
+        };
+
+        let machines = [
+            // halts
+            NotableMachineBinary::BB4Max.machine_id(),
+            // counter
+            MachineId::try_from("1RB---_0LB1RC_1LB0RD_0RC0RA").unwrap(),
+            // no counter
+            MachineId::try_from("1RB---_1LB1RC_0LD0RC_0RB0RA").unwrap(),
+            MachineId::try_from("1RB---_1LC0RA_0LD0LB_1RA0RA").unwrap(),
+            MachineId::try_from("1RB1LC_0RD0RA_0LA---_1RC0RA").unwrap(),
+        ];
+        let config = Config::new_default(4);
+        let mut batch_data = BatchData {
+            machines: &machines,
+            result_decided: DeciderResultStats::new(&config),
+            machines_decided: Default::default(),
+            machines_undecided: Default::default(),
+            batch_no: 0,
+            num_batches: 1,
+            decider_id: &DECIDER_COUNTER_ID,
+            run_predecider: PreDeciderRun::DoNotRun,
+            config: &config,
+            step_budget: 0,
+            steps_used: Vec::new(),
+            hints: Vec::new(),
+        };
+        DeciderCounter::decider_run_batch(&mut batch_data).unwrap();
+        assert_eq!(batch_data.result_decided.num_non_halt(), 1);
+        assert_eq!(batch_data.result_decided.num_halt(), 0);
+        assert_eq!(batch_data.machines_undecided.machines.len(), 4);
+        assert!(batch_data
+            .machines_undecided
+            .states
+            .iter()
+            .all(|status| matches!(status, MachineStatus::Undecided(_, _, _))));
+    }
+}
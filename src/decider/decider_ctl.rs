@@ -0,0 +1,273 @@
+//! CTL decider (closed tape language), which proves non-halting with a regular set of configurations
+//! closed under the transitions. \
+//! Each half-tape is read from the blank end towards the head and described by an [Nfa], whose states are the
+//! last context_len cells read. A transition from context u on symbol x exists, if the context_len + 1 cells
+//! u x occur on some reachable tape. The invariant language is the set of configurations where both
+//! half-tapes are accepted and the local context (left context, state, head symbol, right context) is known. \
+//! Starting with the blank tape, each local context is advanced by one step of the machine. Moving the head
+//! adds the written cell to the automaton of the side it leaves and takes the next cell from all transitions
+//! of the automaton of the other side. This is repeated until nothing is added. If no halt transition is
+//! used in the closed set, the machine never halts. \
+//! This is the n-gram variant of the closed position set, which decides many machines with a complex but
+//! locally regular tape, which the cycler and bouncer cannot handle. Context lengths from 1 to
+//! [CONTEXT_LEN_MAX] are tried. It is not part of the standard chain, as a failing search is expensive.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     decider::{decider_ctl::DeciderCTL, Decider},
+//!     machine_binary::MachineId,
+//!     status::{MachineStatus, NonHaltReason},
+//! };
+//!
+//! let config = Config::new_default(4);
+//! let machine = MachineId::try_from("1RB1LC_0RD0RA_0LA---_1RC0RA").unwrap();
+//! let status = DeciderCTL::decide_single_machine(&machine, &config);
+//! assert_eq!(status, MachineStatus::DecidedNonHalt(NonHaltReason::ClosedTapeLanguage(3)));
+//! ```
+
+use std::collections::HashSet;
+
+use crate::{
+    automaton::Nfa,
+    certificate::{Certificate, CtlCertificate},
+    config::{Config, StepBig},
+    decider::{
+        self,
+        decider_result::{BatchData, ResultUnitEndReason},
+        Decider, DECIDER_CTL_ID,
+    },
+    machine_binary::{MachineId, State, Symbol},
+    status::{MachineStatus, NonHaltReason, UndecidedReason},
+};
+
+/// Largest context length tried, the automata have 2^context_len states.
+pub const CONTEXT_LEN_MAX: usize = 6;
+
+/// Transition of the machine, None is halt.
+type Transition = Option<(u8, bool, u8)>;
+
+/// Cells next to the head (bit 0 is the adjacent cell), state and symbol under the head.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocalContext {
+    pub left: u32,
+    pub state: u8,
+    pub symbol: u8,
+    pub right: u32,
+}
+
+/// Result of the search for one context length.
+enum Search {
+    /// Automata of the left and right side and the local contexts of the closed set.
+    Closed(Nfa, Nfa, Vec<LocalContext>),
+    HaltReached,
+    LimitReached,
+}
+
+#[derive(Debug)]
+pub struct DeciderCTL {
+    /// Maximum number of local contexts per context length.
+    step_limit: StepBig,
+    certificate: Option<CtlCertificate>,
+}
+
+impl DeciderCTL {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            step_limit: config.step_limit_decider_ctl() as StepBig,
+            certificate: None,
+        }
+    }
+
+    /// Searches the closed set of local contexts with the given context length.
+    fn search(&self, transitions: &[[Transition; 2]], context_len: usize) -> Search {
+        let mask = (1u32 << context_len) - 1;
+        // the automata of both sides, read from the blank end towards the head
+        let mut left = Nfa::new(1 << context_len);
+        let mut right = Nfa::new(1 << context_len);
+        for nfa in [&mut left, &mut right] {
+            // the blank end is context 0, all cells read form a valid half-tape
+            nfa.add_start(0);
+            (0..nfa.num_states()).for_each(|state| nfa.set_accepting(state, true));
+            nfa.add_transition(0, 0, 0);
+        }
+        let start = LocalContext {
+            left: 0,
+            state: 1,
+            symbol: 0,
+            right: 0,
+        };
+        let mut contexts = vec![start];
+        let mut known = HashSet::from([start]);
+
+        // repeat until a full pass adds no transition, as new transitions also apply to known contexts
+        let mut is_changed = true;
+        while is_changed {
+            is_changed = false;
+            let mut i = 0;
+            while i < contexts.len() {
+                let c = contexts[i];
+                i += 1;
+                let Some((write, is_dir_right, next_state)) =
+                    transitions[c.state as usize - 1][c.symbol as usize]
+                else {
+                    return Search::HaltReached;
+                };
+                // the head leaves side 'from' and moves into side 'to'
+                let (from, to, from_nfa, to_nfa) = if is_dir_right {
+                    (c.left, c.right, &mut left, &right)
+                } else {
+                    (c.right, c.left, &mut right, &left)
+                };
+                let from_next = ((from << 1) | write as u32) & mask;
+                is_changed |= from_nfa.add_transition(from as usize, write, from_next as usize);
+                // the cell under the head is the last cell read on the side 'to', the new context
+                // is any state with a transition on this cell to the old context
+                let symbol = (to & 1) as u8;
+                for y in 0..2 {
+                    let to_next = (to >> 1) | (y << (context_len - 1));
+                    if !to_nfa.has_transition(to_next as usize, symbol, to as usize) {
+                        continue;
+                    }
+                    let (left_next, right_next) = if is_dir_right {
+                        (from_next, to_next)
+                    } else {
+                        (to_next, from_next)
+                    };
+                    let next = LocalContext {
+                        left: left_next,
+                        state: next_state,
+                        symbol,
+                        right: right_next,
+                    };
+                    if known.insert(next) {
+                        is_changed = true;
+                        contexts.push(next);
+                        if contexts.len() as StepBig > self.step_limit {
+                            return Search::LimitReached;
+                        }
+                    }
+                }
+            }
+        }
+
+        Search::Closed(left, right, contexts)
+    }
+}
+
+impl Decider for DeciderCTL {
+    fn decider_id() -> &'static decider::DeciderId {
+        &DECIDER_CTL_ID
+    }
+
+    fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
+        let transitions: Vec<[Transition; 2]> = (1..=machine.n_states())
+            .map(|state| {
+                [0, 1].map(|read| {
+                    let tr = machine
+                        .machine()
+                        .transition(State::new(state), Symbol::new(read));
+                    (!tr.is_halt()).then(|| {
+                        (
+                            tr.is_symbol_one() as u8,
+                            tr.is_dir_right(),
+                            tr.state() as u8,
+                        )
+                    })
+                })
+            })
+            .collect();
+
+        self.certificate = None;
+        let mut reason = UndecidedReason::DeciderNoResult;
+        for context_len in 1..=CONTEXT_LEN_MAX {
+            match self.search(&transitions, context_len) {
+                Search::Closed(left, right, contexts) => {
+                    self.certificate = Some(CtlCertificate {
+                        machine: *machine,
+                        context_len,
+                        left,
+                        right,
+                        contexts,
+                    });
+                    return MachineStatus::DecidedNonHalt(NonHaltReason::ClosedTapeLanguage(
+                        context_len as u8,
+                    ));
+                }
+                Search::HaltReached => {}
+                Search::LimitReached => reason = UndecidedReason::StepLimit,
+            }
+        }
+
+        MachineStatus::Undecided(reason, 0, 0)
+    }
+
+    fn decide_single_machine(machine: &MachineId, config: &Config) -> MachineStatus {
+        let mut d = Self::new(config);
+        d.decide_machine(machine)
+    }
+
+    fn step_limit(&self) -> StepBig {
+        self.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.step_limit = step_limit;
+    }
+
+    fn proof_certificate(&self) -> Option<Certificate> {
+        self.certificate
+            .clone()
+            .map(Certificate::ClosedTapeLanguage)
+    }
+
+    fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
+        let decider = Self::new(batch_data.config);
+        decider::decider_generic_run_batch(decider, batch_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decider::{
+        decider_backward_reasoning::DeciderBackwardReasoning,
+        decider_bouncer_128::DeciderBouncer128, decider_cycler::DeciderCycler,
+    };
+
+    fn decide(tm: &str) -> MachineStatus {
+        let machine = MachineId::try_from(tm).unwrap();
+        let config = Config::new_default(machine.n_states());
+        DeciderCTL::decide_single_machine(&machine, &config)
+    }
+
+    #[test]
+    fn decider_ctl_undecided_of_other_deciders() {
+        let tm = "1RB1LC_0RD0RA_0LA---_1RC0RA";
+        let machine = MachineId::try_from(tm).unwrap();
+        let config = Config::new_default(4);
+        for status in [
+            DeciderCycler::decide_single_machine(&machine, &config),
+            DeciderBouncer128::decide_single_machine(&machine, &config),
+            DeciderBackwardReasoning::decide_single_machine(&machine, &config),
+        ] {
+            assert!(
+                !matches!(status, MachineStatus::DecidedNonHalt(_)),
+                "{status}"
+            );
+        }
+        assert_eq!(
+            decide(tm),
+            MachineStatus::DecidedNonHalt(NonHaltReason::ClosedTapeLanguage(3))
+        );
+    }
+
+    #[test]
+    fn decider_ctl_halting_undecided() {
+        // BB4 Max
+        assert!(matches!(
+            decide("1RB1LB_1LA0LC_1RZ1LD_1RD0RA"),
+            MachineStatus::Undecided(_, _, _)
+        ));
+    }
+}
@@ -31,6 +31,10 @@
 //! checked if each step is identical. \
 //! If this is the case then also the tape will be compared. It needs to match for the \
 //! relevant part, meaning all cells touched in this cycle will be compared.
+//! Once the tape is longer than 128 bit, each step also records a digest of the whole tape
+//! ([TapeDigest]), so a cycle is only accepted if the tape outside the 128-bit tape matches, too.
+//! A shifted cycle may leave a trail which changes the digest, then the relevant part of the tape is
+//! verified exactly by running the machine again.
 
 // TODO bug shift with machine 1RB1RD_1LC1RB_1RA0LB_0RA1LE_---0RB
 // TODO cycle validation with 3rd and 4th cycle
@@ -39,18 +43,22 @@
 // but seems to work on 1RB---_1LB1LC_0RD0RC_1LE1RE_1LA0LE (not shifted)
 
 use crate::{
+    certificate::{Certificate, CycleCertificate},
     config::{Config, StepBig, StepSmall, MAX_STATES},
     decider::{
         self,
         decider_data_long::DeciderDataLong,
+        decider_hint::DeciderHint,
         decider_result::{BatchData, ResultUnitEndReason},
+        scratch_buffer,
         step_record::StepRecordU128,
-        Decider, DECIDER_CYCLER_ID,
+        Decider, NeedsRetry, DECIDER_CYCLER_ID,
     },
-    machine_binary::MachineId,
+    machine_binary::{MachineBinary, MachineId},
+    machine_runner::MachineRunner,
     status::{MachineStatus, NonHaltReason, UndecidedReason},
     // step_record::StepRecordU128,
-    tape::tape_utils::{MIDDLE_BIT_U128, TAPE_SIZE_BIT_U128},
+    tape::tape_utils::{tape_digest_128_shifted, TapeDigest, MIDDLE_BIT_U128, TAPE_SIZE_BIT_U128},
 };
 
 #[cfg(debug_assertions)]
@@ -62,6 +70,9 @@ const DEBUG_MIN_DISTANCE: usize = 75;
 const MAX_INIT_CAPACITY: usize = 10_000;
 /// Reduces number of checks. This relies on a cycle which always has one tape side 0.
 const SEARCH_ONLY_0_SIDE_FROM: usize = 50;
+/// Head range up to which a machine at the step limit is run again with a higher step limit, if re-runs are
+/// enabled (see [Config::decider_retry_step_limit_max]). Half of the 128-bit tape, wider machines are expanding.
+const RETRY_HEAD_RANGE_MAX: i64 = 64;
 
 // TODO 1RB---_1LC0RB_0LC1RB runs full 200000 steps. Can this be limited by a different rule?
 // TODO Timer in runtime as feature to fine too long running machines
@@ -74,6 +85,10 @@ pub struct DeciderCycler {
     /// (basically e.g. all steps for e.g. field 'B0' steps: 1 if A0 points to B, as step 1 then has state B and head symbol 0.)
     // TODO performance: extra differentiation for 0/1 at head position? The idea is, that the field cannot be identical if head read is different
     maps_1d: [Vec<usize>; 2 * (MAX_STATES + 1)],
+    /// Digest of the whole tape, only maintained once the long tape is used.
+    digest: Option<TapeDigest>,
+    /// Cycle of the last machine, if it was decided as cycler.
+    certificate: Option<CycleCertificate>,
 
     #[cfg(all(feature = "decider_timer_info", not(debug_assertions)))]
     start_time: std::time::Instant,
@@ -86,8 +101,10 @@ impl DeciderCycler {
         let cap = (config.step_limit_decider_cycler() as usize).min(MAX_INIT_CAPACITY);
         let mut decider = Self {
             data: DeciderDataLong::new(config),
-            steps: Vec::with_capacity(cap),
-            maps_1d: core::array::from_fn(|_| Vec::with_capacity(cap / 4)),
+            steps: scratch_buffer::take_vec(cap),
+            maps_1d: core::array::from_fn(|_| scratch_buffer::take_vec(cap / 4)),
+            digest: None,
+            certificate: None,
 
             #[cfg(all(feature = "decider_timer_info", not(debug_assertions)))]
             start_time: std::time::Instant::now(),
@@ -108,6 +125,7 @@ impl DeciderCycler {
         for map in self.maps_1d.iter_mut() {
             map.clear();
         }
+        self.digest = None;
     }
 
     #[inline]
@@ -129,8 +147,11 @@ impl DeciderCycler {
             // maps: store step id leading to this
             self.maps_1d[self.data.tr_field].push(self.steps.len());
             let mut step = StepRecordU128::new(self.data.tr_field, 0, self.data.tape_shifted());
-            self.data.tr = machine.machine().transition(self.data.tr_field);
+            self.data.tr = machine.machine().transition_array_id(self.data.tr_field);
             step.direction = self.data.tr.direction();
+            if let Some(digest) = self.digest {
+                step.tape_digest = digest.value();
+            }
             self.steps.push(step);
 
             // check if done
@@ -165,6 +186,18 @@ impl DeciderCycler {
             if !self.data.update_tape_single_step() {
                 return self.data.status;
             };
+            match self.digest.as_mut() {
+                Some(digest) => {
+                    // the field id of the transition contains the symbol read
+                    digest.write(self.data.tr_field & 1, self.data.tr.symbol_usize());
+                    digest.move_head(self.data.tr.is_dir_right());
+                }
+                None => {
+                    if self.data.tape.is_tape_extended() {
+                        self.digest = Some(self.data.tape.tape_digest());
+                    }
+                }
+            }
 
             // get next transition
             read_symbol_next = self.data.get_current_symbol();
@@ -180,7 +213,10 @@ impl DeciderCycler {
                 tape_shifted as u64,
                 tr.state_to_char(),
                 read_symbol_next,
-                machine.transition(tr.state_x2() + read_symbol_next),
+                machine.transition(
+                    tr.next_state(),
+                    crate::machine_binary::Symbol::new(read_symbol_next)
+                ),
             );
 
             // check endless cycle for multiple steps
@@ -272,8 +308,9 @@ impl DeciderCycler {
                     // }
 
                     // check if full tape is identical (this is not necessary, only relevant bytes)
-                    // TODO requires comparison of long_tape
-                    if step_tape_before == self.data.tape_shifted() {
+                    if step_tape_before == self.data.tape_shifted()
+                        && self.is_tape_digest_equal(step_id)
+                    {
                         // Same, we found a cycle!
                         #[cfg(all(debug_assertions, feature = "debug_cycler"))]
                         println!("*** Found Cycle (tape identical)!");
@@ -324,6 +361,7 @@ impl DeciderCycler {
                             max_r = total_shift
                         };
                     }
+                    let (min_l_cycle, max_r_cycle) = (min_l, max_r);
                     // When shifted, eventually all bits on that side are used after x cycles, check all
                     #[allow(clippy::comparison_chain)]
                     if total_shift > 0 {
@@ -366,7 +404,7 @@ impl DeciderCycler {
                     #[cfg(all(debug_assertions, feature = "debug_cycler"))]
                     {
                         // for (i, step) in self.steps.iter().enumerate().skip(step_id) {
-                        //     let t = machine.transition(step.for_field_id);
+                        //     let t = machine.transition_array_id(step.for_field_id);
                         //     println!(
                         //         "   Step {i:3}: {} {}: {}",
                         //         step.field_id_to_string(),
@@ -395,7 +433,17 @@ impl DeciderCycler {
                     }
 
                     // check if full tape is identical (this is not necessary, only relevant bytes)
-                    if step_tape_before & mask == self.data.tape_shifted() & mask {
+                    // with the long tape in use this is confirmed on the whole tape
+                    if step_tape_before & mask == self.data.tape_shifted() & mask
+                        && self.is_cycle_confirmed(
+                            machine.machine(),
+                            step_id,
+                            distance,
+                            total_shift,
+                            min_l_cycle,
+                            max_r_cycle,
+                        )
+                    {
                         // Same, we found a cycle!
                         #[cfg(all(debug_assertions, feature = "debug_cycler"))]
                         println!("  *** Found Cycle with mask!");
@@ -437,6 +485,102 @@ impl DeciderCycler {
     }
 }
 
+impl DeciderCycler {
+    /// Returns true if the whole tape equals the tape before step step_id, compared by digest. \
+    /// As long as the long tape is not used, the 128-bit tape is the whole tape and this is always true,
+    /// so the 128-bit tapes need to be compared separately.
+    fn is_tape_digest_equal(&self, step_id: usize) -> bool {
+        let Some(digest) = self.digest else {
+            return true;
+        };
+        let step = &self.steps[step_id];
+        let digest_before = if step.tape_digest == 0 {
+            tape_digest_128_shifted(step.tape_before)
+        } else {
+            step.tape_digest
+        };
+
+        digest_before == digest.value()
+    }
+
+    /// Confirms a cycle whose cells in the 128-bit tape match the cells before step step_id. Once the long
+    /// tape is used, the 128-bit tape is not the whole tape and may not be clean, so the cycle is checked again:
+    /// - The whole tape is identical (same digest), so the configuration repeats. Without shift this is
+    ///   also required, as the cycle does not change the cells it does not touch.
+    /// - A shifted cycle may leave a trail, so the cells ahead of the cycle in shift direction must be 0,
+    ///   as they are not touched by the cycle but read at the same relative position one cycle later.
+    /// - Then the machine is run again to compare the relevant part of the tape exactly.
+    fn is_cycle_confirmed(
+        &self,
+        machine: &MachineBinary,
+        step_id: usize,
+        distance: usize,
+        total_shift: isize,
+        min_l: isize,
+        max_r: isize,
+    ) -> bool {
+        if self.digest.is_none() || self.is_tape_digest_equal(step_id) {
+            return true;
+        }
+        match total_shift {
+            0 => false,
+            1.. => {
+                self.data
+                    .tape
+                    .is_blank_beyond((max_r - total_shift) as i64, true)
+                    && Self::is_shifted_cycle_verified(
+                        machine,
+                        step_id,
+                        distance,
+                        total_shift,
+                        min_l,
+                    )
+            }
+            _ => {
+                self.data
+                    .tape
+                    .is_blank_beyond((min_l - total_shift) as i64, false)
+                    && Self::is_shifted_cycle_verified(
+                        machine,
+                        step_id,
+                        distance,
+                        total_shift,
+                        max_r,
+                    )
+            }
+        }
+    }
+
+    /// Exact check of a shifted cycle. Runs the machine again and compares the cells from the cycle bound opposite to
+    /// the shift (relative to the head) up to the end of the tape in shift direction, before step step_id
+    /// and distance steps later (now).
+    fn is_shifted_cycle_verified(
+        machine: &MachineBinary,
+        step_id: usize,
+        distance: usize,
+        total_shift: isize,
+        bound: isize,
+    ) -> bool {
+        let dir_right = total_shift > 0;
+        let mut runner = MachineRunner::new(machine);
+        runner.steps().take(step_id).for_each(drop);
+        let cells_before = runner.cells_from_head(bound as i64, dir_right);
+        runner.steps().take(distance).for_each(drop);
+
+        cells_before == runner.cells_from_head(bound as i64, dir_right)
+    }
+}
+
+/// Returns the buffers to the arena of the worker thread for the decider of the next batch.
+impl Drop for DeciderCycler {
+    fn drop(&mut self) {
+        scratch_buffer::return_vec(std::mem::take(&mut self.steps));
+        for map in self.maps_1d.iter_mut() {
+            scratch_buffer::return_vec(std::mem::take(map));
+        }
+    }
+}
+
 impl Decider for DeciderCycler {
     fn decider_id() -> &'static decider::DeciderId {
         &DECIDER_CYCLER_ID
@@ -452,6 +596,7 @@ impl Decider for DeciderCycler {
         }
 
         let status = self.decide_machine_main(machine);
+        self.certificate = CycleCertificate::from_status(machine, &status);
 
         #[cfg(all(feature = "decider_timer_info", not(debug_assertions)))]
         if std::time::Instant::elapsed(&self.start_time) > self.duration_max_info {
@@ -480,6 +625,60 @@ impl Decider for DeciderCycler {
         d.decide_machine(machine)
     }
 
+    fn step_limit(&self) -> StepBig {
+        self.data.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.data.step_limit = step_limit;
+    }
+
+    fn proof_certificate(&self) -> Option<Certificate> {
+        self.certificate.map(Certificate::Cycle)
+    }
+
+    #[cfg(feature = "enable_html_reports")]
+    fn html_write_failures(&self) -> Option<&crate::html::HtmlWriteFailures> {
+        self.data.html_writer.as_ref().map(|w| w.write_failures())
+    }
+
+    fn tape_memory_bytes(&self) -> Option<usize> {
+        Some(self.data.tape.tape_long_bytes())
+    }
+
+    fn tape_size_limit_u32_blocks(&self) -> u32 {
+        self.data.tape.tape_size_limit_u32_blocks()
+    }
+
+    fn set_tape_size_limit_u32_blocks(&mut self, tape_size_limit_u32_blocks: u32) {
+        self.data
+            .tape
+            .set_tape_size_limit_u32_blocks(tape_size_limit_u32_blocks);
+    }
+
+    /// The head bounds and sweeps of the recorded steps.
+    fn hint(&self) -> Option<DeciderHint> {
+        Some(DeciderHint::from_directions(
+            self.steps.iter().map(|step| step.direction),
+        ))
+    }
+
+    /// A machine which reached the step limit on a small part of the tape may be a cycler with a long cycle,
+    /// so it is run again with a quadrupled step limit.
+    fn needs_retry(&self) -> Option<NeedsRetry> {
+        if (self.steps.len() as StepBig) < self.data.step_limit {
+            return None;
+        }
+        let hint = self.hint()?;
+        if hint.head_pos_max - hint.head_pos_min >= RETRY_HEAD_RANGE_MAX {
+            return None;
+        }
+        Some(NeedsRetry {
+            suggested_step_limit: self.data.step_limit.saturating_mul(4),
+            suggested_tape_blocks: self.tape_size_limit_u32_blocks(),
+        })
+    }
+
     fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
         let decider = Self::new(batch_data.config);
         decider::decider_generic_run_batch(decider, batch_data)
@@ -509,6 +708,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decider_cycler_retry_with_larger_step_limit() {
+        use crate::{
+            data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            decider::{
+                decider_engine::batch_run_decider_chain_data_provider_single_thread,
+                DeciderStandard,
+            },
+        };
+
+        let run = |retry_step_limit_max, step_budget| {
+            let config = Config::builder(3)
+                .machine_limit(0)
+                .step_limit_decider_cycler(20)
+                .decider_retry_step_limit_max(retry_step_limit_max)
+                .step_budget_chain(step_budget)
+                .limit_machines_undecided(usize::MAX)
+                .build();
+            let deciders = [DeciderStandard::Cycler.decider_config(&config)];
+            let data_provider =
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+            batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider)
+        };
+        let result = run(0, 0);
+        let result_retry = run(400, 0);
+        assert_eq!(result.num_decider_retries(), 0);
+        assert!(result_retry.num_decider_retries() > 0);
+        assert!(result_retry.num_undecided() < result.num_undecided());
+
+        // the retries are charged to the step budget
+        let result_budget = run(400, 50);
+        assert!(result_budget.num_decider_retries() > 0);
+        assert!(result_budget.num_undecided() < result.num_undecided());
+        assert!(result_budget.num_undecided() > result_retry.num_undecided());
+        for machine in result_budget.machines_undecided().unwrap() {
+            assert!(machine.status().steps().unwrap() <= 50, "{machine}");
+        }
+    }
+
+    #[test]
+    fn decider_cycler_shifted_cycle_long_tape() {
+        // Erases the ones right of it in B1, which looks like a shifted cycle within the 128-bit tape.
+        // The long tape has more ones ahead, so it is not a cycle.
+        let machine = MachineId::try_from("1RB---_0LD0RB_0LB0LA_1LD1LA").unwrap();
+        let config = Config::builder(4).step_limit_decider_cycler(5_000).build();
+        let status = DeciderCycler::decide_single_machine(&machine, &config);
+        assert!(
+            !matches!(status, MachineStatus::DecidedNonHalt(_)),
+            "{status}"
+        );
+
+        // writes ones to the right in A0 and B0, a cycle of 2 steps shifting 2 cells
+        let machine = MachineBinary::try_from_standard_tm_text_format("1RB---_1RA---").unwrap();
+        assert!(DeciderCycler::is_shifted_cycle_verified(
+            &machine, 2, 2, 2, 0
+        ));
+        assert!(DeciderCycler::is_shifted_cycle_verified(
+            &machine, 100, 4, 4, 0
+        ));
+        // the trail on the left is not relevant, the cycle start is
+        assert!(!DeciderCycler::is_shifted_cycle_verified(
+            &machine, 2, 2, 2, -3
+        ));
+    }
+
     #[test]
     fn decider_cycler_is_cycle_bb4_1166084() {
         let tm = "1RB1LD_1RC---_1LC0RA_0RA0RA";
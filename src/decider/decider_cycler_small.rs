@@ -43,6 +43,7 @@ use crate::{
         self,
         decider_data_long::DeciderDataLong,
         decider_result::{BatchData, ResultUnitEndReason},
+        scratch_buffer,
         step_record::StepRecordU128,
         Decider, DECIDER_CYCLER_ID,
     },
@@ -81,8 +82,8 @@ impl DeciderCyclerSmall {
         let cap = (config.step_limit_decider_cycler() as usize).min(MAX_INIT_CAPACITY);
         let mut decider = Self {
             data: DeciderDataLong::new(config),
-            steps: Vec::with_capacity(cap),
-            maps_1d: core::array::from_fn(|_| Vec::with_capacity(cap / 4)),
+            steps: scratch_buffer::take_vec(cap),
+            maps_1d: core::array::from_fn(|_| scratch_buffer::take_vec(cap / 4)),
             #[cfg(feature = "enable_html_reports")]
             machine_id: None,
         };
@@ -129,7 +130,7 @@ impl DeciderCyclerSmall {
             // maps: store step id leading to this
             self.maps_1d[self.data.tr_field].push(self.steps.len());
             let mut step = StepRecordU128::new(self.data.tr_field, 0, self.data.tape_shifted());
-            self.data.tr = machine.transition(self.data.tr_field);
+            self.data.tr = machine.transition_array_id(self.data.tr_field);
             step.direction = self.data.tr.direction();
             self.steps.push(step);
 
@@ -169,7 +170,10 @@ impl DeciderCyclerSmall {
                 tape_shifted as u64,
                 tr.state_to_char(),
                 read_symbol_next,
-                machine.transition(tr.state_x2() + read_symbol_next),
+                machine.transition(
+                    tr.next_state(),
+                    crate::machine_binary::Symbol::new(read_symbol_next)
+                ),
             );
 
             // check endless cycle for multiple steps
@@ -338,7 +342,7 @@ impl DeciderCyclerSmall {
                     #[cfg(all(debug_assertions, feature = "debug_cycler"))]
                     {
                         // for (i, step) in self.steps.iter().enumerate().skip(step_id) {
-                        //     let t = machine.transition(step.for_field_id);
+                        //     let t = machine.transition_array_id(step.for_field_id);
                         //     println!(
                         //         "   Step {i:3}: {} {}: {}",
                         //         step.field_id_to_string(),
@@ -409,6 +413,16 @@ impl DeciderCyclerSmall {
     }
 }
 
+/// Returns the buffers to the arena of the worker thread for the decider of the next batch.
+impl Drop for DeciderCyclerSmall {
+    fn drop(&mut self) {
+        scratch_buffer::return_vec(std::mem::take(&mut self.steps));
+        for map in self.maps_1d.iter_mut() {
+            scratch_buffer::return_vec(std::mem::take(map));
+        }
+    }
+}
+
 impl Decider for DeciderCyclerSmall {
     fn decider_id() -> &'static decider::DeciderId {
         &DECIDER_CYCLER_ID
@@ -430,6 +444,33 @@ impl Decider for DeciderCyclerSmall {
         d.decide_machine(machine)
     }
 
+    fn step_limit(&self) -> StepBig {
+        self.data.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.data.step_limit = step_limit;
+    }
+
+    #[cfg(feature = "enable_html_reports")]
+    fn html_write_failures(&self) -> Option<&crate::html::HtmlWriteFailures> {
+        self.data.html_writer.as_ref().map(|w| w.write_failures())
+    }
+
+    fn tape_memory_bytes(&self) -> Option<usize> {
+        Some(self.data.tape.tape_long_bytes())
+    }
+
+    fn tape_size_limit_u32_blocks(&self) -> u32 {
+        self.data.tape.tape_size_limit_u32_blocks()
+    }
+
+    fn set_tape_size_limit_u32_blocks(&mut self, tape_size_limit_u32_blocks: u32) {
+        self.data
+            .tape
+            .set_tape_size_limit_u32_blocks(tape_size_limit_u32_blocks);
+    }
+
     fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
         let decider = Self::new(batch_data.config);
         decider::decider_generic_run_batch(decider, batch_data)
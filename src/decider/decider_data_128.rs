@@ -3,6 +3,7 @@ use std::fmt::Display;
 
 use crate::{
     config::{Config, StepBig},
+    decider::machine_time_limit::MachineTimeLimit,
     machine_binary::{MachineBinary, MachineId},
     status::{MachineStatus, UndecidedReason},
     tape::{
@@ -16,6 +17,15 @@ use crate::{
     transition_binary::{TransitionBinary, TRANSITION_0RA_BINARY_FIRST},
 };
 
+/// Result of one time slice, see [DeciderData128::poll].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollStatus {
+    /// The machine is not decided yet, call poll again.
+    Pending,
+    /// The machine has ended with this status.
+    Done(MachineStatus),
+}
+
 // TODO this could be merged as decider_data with traits
 /// This contains the functionality for a hold decider and can be used to create more elaborate deciders. \
 #[derive(Debug)]
@@ -32,10 +42,10 @@ pub struct DeciderData128 {
     /// be copied into the long tape when a bound is reached.
     pub tape: Tape128,
 
-    pub transition_table: MachineBinary,
-
     /// Maximum number of steps, after that Undecided will be returned.
     pub step_limit: StepBig,
+    /// Wall-clock time limit of the current machine.
+    pub time_limit: MachineTimeLimit,
     // /// Tape size limit in number of cells
     // tape_size_limit_u32_blocks: u32,
     /// Final status, only valid once machine has ended, but intended to be used internally.
@@ -52,12 +62,12 @@ impl DeciderData128 {
             tape: Tape128::new(config),
 
             step_no: 0,
-            transition_table: MachineBinary::default(),
             // Initialize transition with A0 as start
             tr: TRANSITION_0RA_BINARY_FIRST,
             tr_field: 2,
             status: MachineStatus::NoDecision,
             step_limit: config.step_limit_decider_halt(),
+            time_limit: MachineTimeLimit::new(config),
 
             #[cfg(feature = "enable_html_reports")]
             html_writer: if config.write_html_file() {
@@ -77,6 +87,41 @@ impl DeciderData128 {
         self.tr = TRANSITION_0RA_BINARY_FIRST;
         self.tr_field = 2;
         self.status = MachineStatus::NoDecision;
+        self.time_limit.start();
+    }
+
+    /// Resets the decider for a time-sliced run of a new machine with [Self::poll].
+    pub fn start_machine(&mut self) {
+        self.clear();
+    }
+
+    /// Runs the machine for about max_steps steps and returns. The same machine needs to be passed
+    /// on each call after [Self::start_machine]. \
+    /// This allows e.g. a UI thread to interleave rendering with long decisions without real threads.
+    /// The self-referencing speed-up may exceed max_steps within the last step. The status is kept,
+    /// so polling again after Done returns the same status.
+    pub fn poll(&mut self, machine: &MachineBinary, max_steps: StepBig) -> PollStatus {
+        if self.status != MachineStatus::NoDecision {
+            return PollStatus::Done(self.status);
+        }
+        let use_self_ref = cfg!(not(feature = "without_self_ref_acceleration"))
+            && machine.has_self_referencing_transition();
+        // at least one step to guarantee progress
+        let step_end = self.step_no.saturating_add(max_steps.max(1));
+        while self.step_no < step_end {
+            if self.next_transition(machine) {
+                return PollStatus::Done(self.status);
+            }
+            let tape_ok = if use_self_ref {
+                self.update_tape_self_ref_speed_up()
+            } else {
+                self.update_tape_single_step()
+            };
+            if !tape_ok {
+                return PollStatus::Done(self.status);
+            }
+        }
+        PollStatus::Pending
     }
 
     #[inline(always)]
@@ -85,15 +130,16 @@ impl DeciderData128 {
     }
 
     /// Sets the next transition and updates the step counter. It does not update the tape yet,
-    /// but in the case the execution ended because of halt or limit.
+    /// but in the case the execution ended because of halt or limit. \
+    /// The transition is read from the borrowed machine, so the table is not copied for each machine.
     /// # Returns
     /// true if execution ended (is_done)
     #[must_use]
     #[inline(always)]
-    pub fn next_transition(&mut self) -> bool {
+    pub fn next_transition(&mut self, machine: &MachineBinary) -> bool {
         self.step_no += 1;
         self.tr_field = self.tr.state_x2() + self.tape.get_current_symbol();
-        self.tr = self.transition_table.transition(self.tr_field);
+        self.tr = machine.transition_array_id(self.tr_field);
         // #[cfg(all(debug_assertions, feature = "bb_debug"))]
         // println!("{}", self.step_to_string());
         self.is_done()
@@ -116,7 +162,13 @@ impl DeciderData128 {
 
             return true;
         } else if self.step_no >= self.step_limit {
-            self.status = self.status_undecided_step_limit();
+            self.status = self.status_undecided_limit(UndecidedReason::StepLimit);
+            #[cfg(feature = "enable_html_reports")]
+            self.write_step_html();
+
+            return true;
+        } else if self.time_limit.is_exceeded(self.step_no) {
+            self.status = self.status_undecided_limit(UndecidedReason::TimeLimit);
             #[cfg(feature = "enable_html_reports")]
             self.write_step_html();
 
@@ -137,12 +189,8 @@ impl DeciderData128 {
         }
     }
 
-    fn status_undecided_step_limit(&self) -> MachineStatus {
-        MachineStatus::Undecided(
-            UndecidedReason::StepLimit,
-            self.step_no as StepBig,
-            self.tape.tape_size_cells(),
-        )
+    fn status_undecided_limit(&self, reason: UndecidedReason) -> MachineStatus {
+        MachineStatus::Undecided(reason, self.step_no as StepBig, self.tape.tape_size_cells())
     }
 
     /// Returns the status of the decider
@@ -196,14 +244,15 @@ impl DeciderData128 {
     /// Updates tape_shifted and tape_long.
     /// Also prints and writes step to html if feature is set.
     /// # Returns
-    /// False if the tape could not be expanded (tape_size_limit). Then self.status is set to that error.
+    /// False if a bound of the 128-bit tape was reached. Then self.status is set to the bound in the direction
+    /// of the head (TapeLimitLeftBoundReached or TapeLimitRightBoundReached).
     #[must_use]
     #[inline(always)]
     pub fn update_tape_single_step(&mut self) -> bool {
         let shift_ok = self.tape.update_tape_single_step(self.tr);
         if !shift_ok {
             self.status = MachineStatus::Undecided(
-                UndecidedReason::TapeSizeLimit,
+                UndecidedReason::tape_bound(self.tr.is_dir_right()),
                 self.step_no,
                 TAPE_SIZE_BIT_U128,
             );
@@ -223,7 +272,8 @@ impl DeciderData128 {
 
     /// Updates tape_shifted and tape_long.
     /// # Returns
-    /// False if the tape could not be expanded (tape_size_limit). Then self.status is set to that error.
+    /// False if a bound of the 128-bit tape was reached. Then self.status is set to the bound in the direction
+    /// of the head.
     // TODO some of this logic should be moved to TapeLong
     #[must_use]
     #[inline(always)]
@@ -234,7 +284,7 @@ impl DeciderData128 {
         // return value
         if jump == 0 {
             self.status = MachineStatus::Undecided(
-                UndecidedReason::TapeSizeLimit,
+                UndecidedReason::tape_bound(self.tr.is_dir_right()),
                 self.step_no,
                 self.tape.tape_size_cells(),
             );
@@ -263,10 +313,14 @@ impl DeciderData128 {
         machine: &MachineId,
     ) {
         if let Some(html_writer) = &mut self.html_writer {
-            html_writer
+            if html_writer
                 .create_html_file_start(decider_id, machine)
-                .expect("Html file could not be written");
-            self.write_html_p("Note: Here the full 128 Bit Tape is shown, there is no long tape.");
+                .is_ok()
+            {
+                self.write_html_p(
+                    "Note: Here the full 128 Bit Tape is shown, there is no long tape.",
+                );
+            }
         }
     }
 
@@ -12,6 +12,7 @@ use std::fmt::Display;
 use crate::machine_binary::MachineId;
 use crate::{
     config::{Config, StepBig},
+    decider::machine_time_limit::MachineTimeLimit,
     machine_binary::MachineBinary,
     status::{MachineStatus, UndecidedReason},
     tape::{tape_long_shifted::TapeLongShifted, Tape, TapeAcceleration},
@@ -35,10 +36,10 @@ pub struct DeciderDataLong {
 
     // machine id, just for debugging
     // machine_id: IdBig,
-    pub transition_table: MachineBinary,
-
     /// Maximum number of steps, after that Undecided will be returned.
     pub step_limit: StepBig,
+    /// Wall-clock time limit of the current machine.
+    pub time_limit: MachineTimeLimit,
     // /// Tape size limit in number of cells
     // tape_size_limit_u32_blocks: u32,
     /// Final status, only valid once machine has ended, but intended to be used internally.
@@ -55,12 +56,12 @@ impl DeciderDataLong {
             tape: TapeLongShifted::new(config),
 
             step_no: 0,
-            transition_table: MachineBinary::default(),
             // Initialize transition with A0 as start
             tr: TRANSITION_0RA_BINARY_FIRST,
             tr_field: 2,
             status: MachineStatus::NoDecision,
             step_limit: config.step_limit_decider_halt(),
+            time_limit: MachineTimeLimit::new(config),
 
             #[cfg(feature = "enable_html_reports")]
             html_writer: if config.write_html_file() {
@@ -80,6 +81,7 @@ impl DeciderDataLong {
         self.tr = TRANSITION_0RA_BINARY_FIRST;
         self.tr_field = 2;
         self.status = MachineStatus::NoDecision;
+        self.time_limit.start();
     }
 
     /// Reads the current symbol of the tape. Use with care, as this inspects data in the tape directly, which should generally be avoided.
@@ -89,15 +91,16 @@ impl DeciderDataLong {
     }
 
     /// Sets the next transition and updates the step counter. It does not update the tape yet,
-    /// but in the case the execution ended because of halt or limit.
+    /// but in the case the execution ended because of halt or limit. \
+    /// The transition is read from the borrowed machine, so the table is not copied for each machine.
     /// # Returns
     /// true if execution ended (is_done)
     #[must_use]
     #[inline(always)]
-    pub fn next_transition(&mut self) -> bool {
+    pub fn next_transition(&mut self, machine: &MachineBinary) -> bool {
         self.step_no += 1;
         self.tr_field = self.tr.state_x2() + self.tape.get_current_symbol();
-        self.tr = self.transition_table.transition(self.tr_field);
+        self.tr = machine.transition_array_id(self.tr_field);
 
         // print tape before change
         // #[cfg(all(debug_assertions, feature = "bb_debug"))]
@@ -122,7 +125,13 @@ impl DeciderDataLong {
 
             return true;
         } else if self.step_no >= self.step_limit {
-            self.status = self.status_undecided_step_limit();
+            self.status = self.status_undecided_limit(UndecidedReason::StepLimit);
+            #[cfg(feature = "enable_html_reports")]
+            self.write_step_html();
+
+            return true;
+        } else if self.time_limit.is_exceeded(self.step_no) {
+            self.status = self.status_undecided_limit(UndecidedReason::TimeLimit);
             #[cfg(feature = "enable_html_reports")]
             self.write_step_html();
 
@@ -143,12 +152,8 @@ impl DeciderDataLong {
         }
     }
 
-    fn status_undecided_step_limit(&self) -> MachineStatus {
-        MachineStatus::Undecided(
-            UndecidedReason::StepLimit,
-            self.step_no as StepBig,
-            self.tape.tape_size_cells(),
-        )
+    fn status_undecided_limit(&self, reason: UndecidedReason) -> MachineStatus {
+        MachineStatus::Undecided(reason, self.step_no as StepBig, self.tape.tape_size_cells())
     }
 
     /// Returns the status of the decider
@@ -261,12 +266,14 @@ impl DeciderDataLong {
     #[cfg(feature = "enable_html_reports")]
     pub fn write_html_file_start(&mut self, decider_id: &super::DeciderId, machine: &MachineId) {
         if let Some(html_writer) = &mut self.html_writer {
-            html_writer
+            if html_writer
                 .create_html_file_start(decider_id, machine)
-                .expect("Html file could not be written");
-            self.write_html_p(
-                "Note: Only the 128 Bit Tape is shown, the underlying long tape holds more data.",
-            );
+                .is_ok()
+            {
+                self.write_html_p(
+                    "Note: Only the 128 Bit Tape is shown, the underlying long tape holds more data.",
+                );
+            }
         }
     }
 
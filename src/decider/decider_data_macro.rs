@@ -2,6 +2,7 @@ use std::fmt::Display;
 
 use crate::{
     config::{Config, StepBig},
+    decider::machine_time_limit::MachineTimeLimit,
     machine_binary::MachineBinary,
     status::{MachineStatus, UndecidedReason},
     tape::{tape_macro::TapeCompact, tape_utils::TAPE_SIZE_BIT_U128, Tape},
@@ -25,10 +26,10 @@ pub struct DeciderDataMacro {
 
     // machine id, just for debugging
     // machine_id: IdBig,
-    pub transition_table: MachineBinary,
-
     /// Maximum number of steps, after that Undecided will be returned.
     pub step_limit: StepBig,
+    /// Wall-clock time limit of the current machine.
+    pub time_limit: MachineTimeLimit,
     // /// Tape size limit in number of cells
     // tape_size_limit_u32_blocks: u32,
     /// Final status, only valid once machine has ended, but intended to be used internally.
@@ -46,7 +47,6 @@ impl DeciderDataMacro {
             tape: TapeCompact::new(config),
 
             step_no: 0,
-            transition_table: MachineBinary::default(),
             // Initialize transition with A0 as start
             tr: TRANSITION_0RA_BINARY_FIRST,
             tr_field: 2,
@@ -55,6 +55,7 @@ impl DeciderDataMacro {
             // transition_table: TransitionTableSymbol2::default(),
             status: MachineStatus::NoDecision,
             step_limit: config.step_limit_decider_halt(),
+            time_limit: MachineTimeLimit::new(config),
 
             #[cfg(feature = "enable_html_reports")]
             html_writer: if config.write_html_file() {
@@ -74,6 +75,7 @@ impl DeciderDataMacro {
         self.tr = TRANSITION_0RA_BINARY_FIRST;
         self.tr_field = 2;
         self.status = MachineStatus::NoDecision;
+        self.time_limit.start();
         // self.html_writer.reset_write_html_line_count();
         // keep step_limit and other config data
     }
@@ -85,10 +87,10 @@ impl DeciderDataMacro {
 
     // Returns the next transition and updates the step counter, but does not update the tape yet
     #[inline(always)]
-    pub fn next_transition(&mut self) -> bool {
+    pub fn next_transition(&mut self, machine: &MachineBinary) -> bool {
         self.step_no += 1;
         self.tr_field = self.tr.state_x2() + self.tape.get_current_symbol();
-        self.tr = self.transition_table.transition(self.tr_field);
+        self.tr = machine.transition_array_id(self.tr_field);
         // #[cfg(all(debug_assertions, feature = "bb_debug"))]
         // println!("{}", self.step_to_string());
 
@@ -111,7 +113,12 @@ impl DeciderDataMacro {
             self.write_step_html();
             return true;
         } else if self.step_no >= self.step_limit {
-            self.status = self.status_undecided_step_limit();
+            self.status = self.status_undecided_limit(UndecidedReason::StepLimit);
+            #[cfg(feature = "enable_html_reports")]
+            self.write_step_html();
+            return true;
+        } else if self.time_limit.is_exceeded(self.step_no) {
+            self.status = self.status_undecided_limit(UndecidedReason::TimeLimit);
             #[cfg(feature = "enable_html_reports")]
             self.write_step_html();
             return true;
@@ -131,9 +138,9 @@ impl DeciderDataMacro {
         }
     }
 
-    fn status_undecided_step_limit(&self) -> MachineStatus {
+    fn status_undecided_limit(&self, reason: UndecidedReason) -> MachineStatus {
         MachineStatus::Undecided(
-            UndecidedReason::StepLimit,
+            reason,
             self.step_no as StepBig,
             self.tape.tape_size_cells() as u32,
         )
@@ -267,12 +274,14 @@ impl DeciderDataMacro {
         machine: &crate::machine_binary::MachineId,
     ) {
         if let Some(html_writer) = &mut self.html_writer {
-            html_writer
+            if html_writer
                 .create_html_file_start(decider_id, machine)
-                .expect("Html file could not be written");
-            self.write_html_p(
-                "Note: Only the 128 Bit Tape is shown, the underlying long tape holds more data.",
-            );
+                .is_ok()
+            {
+                self.write_html_p(
+                    "Note: Only the 128 Bit Tape is shown, the underlying long tape holds more data.",
+                );
+            }
         }
     }
 
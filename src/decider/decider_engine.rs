@@ -1,29 +1,52 @@
 //! This crates holds functions to control the decider runs.
 //! Mostly relevant are the functions [run_decider_gen] and [run_decider_chain_gen] to execute the
 //! generator with the different deciders and to run over the bb_challenge file [run_deciders_bb_challenge_file].
+//! [DeciderPipeline] builds a chain of standard deciders with individual step limits.
+//! [run_decider_stages] runs each decider over all machines left undecided by the previous one.
 //!
 
 use std::{
+    fmt::Display,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Mutex,
+    },
     thread,
     time::{Duration, Instant},
 };
 
+use num_format::ToFormattedString;
+
 use crate::{
-    config::CoreUsage,
+    checkpoint::{checkpoint_or_warn, Checkpointer},
+    config::{user_locale, Config, CoreUsage, StepBig},
+    dashboard::{Dashboard, DashboardSender},
     data_provider::{
         // bb_file_reader::BBFileDataProviderBuilder,
         enumerator_binary::{EnumeratorBinary, EnumeratorType},
         enumerator_tnf::EnumeratorTNF,
+        machine_spool::MachineSpool,
         DataProvider,
+        DataProviderBatch,
         DataProviderThreaded,
     },
     decider::{
-        decider_result::{BatchData, DeciderResultStats, DurationDataProvider, EndReason},
-        pre_decider::PreDeciderRun,
-        DeciderConfig, ThreadResultDataProvider, ThreadResultDecider,
+        decider_compare::compare_halt_steps_or_warn,
+        decider_result::{
+            BatchData, BatchResult, DeciderResultStats, DeciderStats, DurationDataProvider,
+            EndReason, PreDeciderCount, ResultUnitEndReason, WarningKind,
+        },
+        pre_decider::{PreDeciderRun, PreDeciderTimer},
+        DeciderConfig, DeciderStandard, ThreadResultDataProvider, ThreadResultDecider,
     },
-    reporter::Reporter,
-    utils::num_cpus_percentage,
+    event_log::{log_or_warn, EventLog, RunEvent},
+    machine_binary::{MachineFeatures, MachineId},
+    machine_info::UndecidedStage,
+    reporter::{format_duration_hhmmss_ms, Reporter},
+    stats_snapshot::{snapshot_or_warn, StatsSnapshot},
+    status::{MachineStatus, UndecidedReason},
+    verdict_log::{write_verdicts_or_warn, VerdictSinks},
 };
 
 /// General function to call a single decider. \
@@ -48,7 +71,7 @@ use crate::{
 /// let mut dc_cycler = DeciderStandard::Cycler.decider_config(&config_cycler);
 /// let result = bb_challenge::decider_engine::run_decider(
 ///     dc_cycler,
-///     CoreUsage::MultiCore,
+///     CoreUsage::MultiCore(0),
 ///     GeneratorStandard::GeneratorReduced,
 /// );
 /// println!("{}", result.to_string_with_duration());
@@ -69,38 +92,93 @@ pub fn run_decider_chain_gen(
     multi_core: CoreUsage,
 ) -> DeciderResultStats {
     let first_config = decider_config.first().expect("No decider given").config();
-    if enumerator_std == EnumeratorType::EnumeratorTNF {
-        let enumerator = EnumeratorTNF::new(first_config);
-        match multi_core {
-            CoreUsage::SingleCore => {
-                batch_run_decider_chain_data_provider_single_thread(decider_config, enumerator)
-            }
-            CoreUsage::SingleCoreEnumeratorMultiCoreDecider => {
-                batch_run_decider_chain_threaded_data_provider_single_thread(
-                    decider_config,
-                    enumerator,
-                )
-            }
-            CoreUsage::MultiCore => panic!("MultiCore not supported"),
+    let enumerator = match EnumeratorBinary::try_new(enumerator_std, first_config) {
+        Ok(enumerator) => enumerator,
+        Err(e) => {
+            let mut result = DeciderResultStats::new(first_config);
+            result.end_reason = EndReason::Error(first_config.start_from_id(), e);
+            return result;
         }
-    } else {
-        let enumerator = EnumeratorBinary::new(enumerator_std, first_config);
-        match multi_core {
+    };
+    let run = |decider_config: &[DeciderConfig]| match multi_core {
+        CoreUsage::SingleCore => {
+            batch_run_decider_chain_data_provider_single_thread(decider_config, enumerator)
+        }
+        CoreUsage::SingleCoreEnumeratorMultiCoreDecider => {
+            batch_run_decider_chain_threaded_data_provider_single_thread(decider_config, enumerator)
+        }
+        CoreUsage::MultiCore(_) | CoreUsage::Percent(_) => {
+            batch_run_decider_chain_threaded_data_provider_multi_thread(decider_config, enumerator)
+        }
+        CoreUsage::Pipelined => batch_run_decider_chain_pipelined(decider_config, enumerator),
+    };
+    let mut result = run_with_core_usage(decider_config, multi_core, run);
+    // diagnostic mode, the machines are enumerated again
+    if first_config.compare_halt_steps() && decider_config.len() > 1 {
+        compare_halt_steps_or_warn(
+            decider_config,
+            EnumeratorBinary::new(enumerator_std, first_config),
+            &mut result,
+        );
+    }
+
+    result
+}
+
+/// Runs the chain with the configs set to the core usage, so the threaded runs use its number of threads,
+/// see [CoreUsage::num_threads]. The configs are only copied if the number of threads differs.
+fn run_with_core_usage(
+    decider_configs: &[DeciderConfig],
+    core_usage: CoreUsage,
+    run: impl FnOnce(&[DeciderConfig]) -> DeciderResultStats,
+) -> DeciderResultStats {
+    let first_config = decider_configs.first().expect("No decider given").config();
+    if core_usage.num_threads(first_config) == first_config.num_threads_effective() {
+        return run(decider_configs);
+    }
+    let configs: Vec<Config> = decider_configs
+        .iter()
+        .map(|dc| {
+            Config::builder_from_config(dc.config())
+                .core_usage(core_usage)
+                .build()
+        })
+        .collect();
+    let decider_configs: Vec<DeciderConfig> = decider_configs
+        .iter()
+        .zip(configs.iter())
+        .map(|(dc, config)| dc.with_config(config))
+        .collect();
+
+    run(&decider_configs)
+}
+
+/// Same as [run_decider_chain_gen], but the machines are enumerated in Tree Normal Form by
+/// [EnumeratorTNF]. The tree is enumerated on one thread, MultiCore runs the deciders multi-threaded.
+pub fn run_decider_chain_tnf(
+    decider_config: &[DeciderConfig],
+    multi_core: CoreUsage,
+) -> DeciderResultStats {
+    let first_config = decider_config.first().expect("No decider given").config();
+    let enumerator = EnumeratorTNF::new(first_config);
+    run_with_core_usage(
+        decider_config,
+        multi_core,
+        |decider_config| match multi_core {
             CoreUsage::SingleCore => {
                 batch_run_decider_chain_data_provider_single_thread(decider_config, enumerator)
             }
-            CoreUsage::SingleCoreEnumeratorMultiCoreDecider => {
+            CoreUsage::SingleCoreEnumeratorMultiCoreDecider
+            | CoreUsage::MultiCore(_)
+            | CoreUsage::Percent(_) => {
                 batch_run_decider_chain_threaded_data_provider_single_thread(
                     decider_config,
                     enumerator,
                 )
             }
-            CoreUsage::MultiCore => batch_run_decider_chain_threaded_data_provider_multi_thread(
-                decider_config,
-                enumerator,
-            ), // _ => panic!("use 0: single, 1: multi with single generator, 2: multi"),
-        }
-    }
+            CoreUsage::Pipelined => batch_run_decider_chain_pipelined(decider_config, enumerator),
+        },
+    )
 }
 
 /// General function to call a decider chain.
@@ -114,14 +192,19 @@ pub fn run_decider_chain_data_provider_single(
             batch_run_decider_chain_data_provider_single_thread(decider_config, data_provider)
         }
         CoreUsage::SingleCoreEnumeratorMultiCoreDecider => {
-            batch_run_decider_chain_threaded_data_provider_single_thread(
-                decider_config,
-                data_provider,
-            )
+            run_with_core_usage(decider_config, multi_core, |decider_config| {
+                batch_run_decider_chain_threaded_data_provider_single_thread(
+                    decider_config,
+                    data_provider,
+                )
+            })
         }
-        CoreUsage::MultiCore => {
+        CoreUsage::MultiCore(_) | CoreUsage::Percent(_) => {
             panic!("MultiCore requires trait DataProviderThreaded and can't be used here.")
         }
+        CoreUsage::Pipelined => {
+            panic!("Pipelined requires a data provider with Send, use batch_run_decider_chain_pipelined.")
+        }
     }
 }
 
@@ -135,7 +218,18 @@ pub fn decide_batch_chain(
     // run_predecider: PreDeciderRun,
     decider_configs: &[DeciderConfig],
 ) -> DeciderResultStats {
+    decide_batch_chain_batch_result(batch_data, decider_configs).result_decided
+}
+
+/// Same as [decide_batch_chain], but additionally returns the machines which are still undecided
+/// after the last decider of the chain.
+pub fn decide_batch_chain_batch_result(
+    batch_data: BatchData,
+    decider_configs: &[DeciderConfig],
+) -> BatchResult {
     let start_decider = Instant::now();
+    // undecided machines of the previous decider, must outlive batch_data
+    let mut m_undecided;
     // interestingly this is required
     let mut batch_data = batch_data;
     let first_decider = decider_configs.first().expect("No decider!");
@@ -150,7 +244,14 @@ pub fn decide_batch_chain(
     // let mut undecided_available = true;
     let mut stop_run = false;
 
-    match first_decider.f_decider()(&mut batch_data) {
+    let start_first_decider = Instant::now();
+    let r = run_decider_isolated(first_decider, &mut batch_data);
+    result_batch.add_decider_stats(&decider_stats(
+        &batch_data,
+        first_decider.decider_id().name,
+        start_first_decider,
+    ));
+    match r {
         Ok(_) => {
             // Call user analyzer/worker so result can be dealt with individually (e.g. save), also in case of error.
             if let Some(fnr) = first_decider.fo_result_worker() {
@@ -163,13 +264,44 @@ pub fn decide_batch_chain(
             if !result_batch.add_result(&batch_data.result_decided) {
                 stop_run = true
             }
+            // stage which first returned the machine undecided, only required if undecided machines are recorded
+            let mut undecided_stages: Vec<UndecidedStage> =
+                if first_decider.config().limit_machines_undecided() > 0 {
+                    batch_data
+                        .machines_undecided
+                        .states
+                        .iter()
+                        .map(|status| UndecidedStage {
+                            stage_no: 0,
+                            decider_name: first_decider.decider_id().name,
+                            status: *status,
+                        })
+                        .collect()
+                } else {
+                    Vec::new()
+                };
 
-            let mut m_undecided;
             let batch_no = batch_data.batch_no;
+            let step_budget = batch_data.step_budget;
             let num_batches = batch_data.num_batches;
             // run other deciders
             for d in decider_configs.iter().skip(1) {
                 if !stop_run && !batch_data.machines_undecided.machines.is_empty() {
+                    // steps used so far are carried in the undecided status if a step budget is set
+                    let steps_used = if step_budget > 0 {
+                        batch_data
+                            .machines_undecided
+                            .states
+                            .iter()
+                            .map(|status| match status {
+                                MachineStatus::Undecided(_, steps, _) => *steps,
+                                _ => 0,
+                            })
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+                    let hints = std::mem::take(&mut batch_data.machines_undecided.hints);
                     m_undecided = batch_data.machines_undecided.machines;
                     // borrow checker requires new object instead of just updating ref to machines
                     batch_data = BatchData {
@@ -185,9 +317,19 @@ pub fn decide_batch_chain(
                         decider_id: d.decider_id(),
                         config: d.config(),
                         run_predecider: PreDeciderRun::DoNotRun,
+                        step_budget,
+                        steps_used,
+                        hints,
                     };
 
-                    match d.f_decider()(&mut batch_data) {
+                    let start_next_decider = Instant::now();
+                    let r = run_decider_isolated(d, &mut batch_data);
+                    result_batch.add_decider_stats(&decider_stats(
+                        &batch_data,
+                        d.decider_id().name,
+                        start_next_decider,
+                    ));
+                    match r {
                         Ok(()) => {
                             batch_data.result_decided.clear_total();
                             // call user analyzer/worker so result can be dealt with individually (e.g. save)
@@ -199,6 +341,13 @@ pub fn decide_batch_chain(
                                 }
                             }
                             result_batch.add_result(&batch_data.result_decided);
+                            if !undecided_stages.is_empty() {
+                                undecided_stages = undecided_stages_remaining(
+                                    batch_data.machines,
+                                    &undecided_stages,
+                                    &batch_data.machines_undecided.machines,
+                                );
+                            }
                         }
                         Err(e) => {
                             result_batch.end_reason = e;
@@ -208,9 +357,24 @@ pub fn decide_batch_chain(
                 }
             }
 
+            // the remaining undecided get the verdict of the last decider run
+            if first_decider.config().record_verdicts() {
+                for (m, status) in batch_data
+                    .machines_undecided
+                    .machines
+                    .iter()
+                    .zip(batch_data.machines_undecided.states.iter())
+                {
+                    result_batch.add_verdict(m, batch_data.decider_id.name, *status);
+                }
+            }
             // add remaining undecided to final result
             for (i, m) in batch_data.machines_undecided.machines.iter().enumerate() {
-                if !result_batch.add(m, &batch_data.machines_undecided.states[i]) {
+                if !result_batch.add_with_undecided_stage(
+                    m,
+                    &batch_data.machines_undecided.states[i],
+                    undecided_stages.get(i).copied(),
+                ) {
                     // println!("result decided/undecided full");
                     break;
                 }
@@ -223,8 +387,393 @@ pub fn decide_batch_chain(
         duration_decider: start_decider.elapsed(),
         ..Default::default()
     };
+    // background mode, the sleep is not counted as decider duration
+    if let Some(throttle) = first_decider.config().throttle() {
+        if let Err(e) = throttle.after_batch(result_batch.duration.duration_decider) {
+            result_batch.add_warning(WarningKind::Throttle, || {
+                format!("Thread priority could not be lowered: {e}")
+            });
+        }
+    }
+
+    BatchResult {
+        result_decided: result_batch,
+        machines_undecided: std::mem::take(&mut batch_data.machines_undecided),
+        batch_no: batch_data.batch_no,
+        num_batches: batch_data.num_batches,
+        decider_name: batch_data.decider_id.name.to_string(),
+    }
+}
+
+/// Keeps the stages of the machines which are still undecided after a decider.
+/// The undecided machines are in order of the machines of the decider.
+fn undecided_stages_remaining(
+    machines: &[MachineId],
+    stages: &[UndecidedStage],
+    machines_undecided: &[MachineId],
+) -> Vec<UndecidedStage> {
+    let mut stages_remaining = Vec::with_capacity(machines_undecided.len());
+    let mut k = 0;
+    for machine in machines_undecided.iter() {
+        while machines[k] != *machine {
+            k += 1;
+        }
+        stages_remaining.push(stages[k]);
+        k += 1;
+    }
+    stages_remaining
+}
+
+/// Runs the decider chain on the machines and returns the final status of each machine in the order
+/// of the machines. Each decider runs on the machines left undecided by the previous deciders, the pre-decider
+/// is not run. Used for analysis of small machine sets, where the status of each machine is of interest.
+pub fn decide_machines_statuses(
+    decider_configs: &[DeciderConfig],
+    machines: &[MachineId],
+) -> Result<Vec<MachineStatus>, EndReason> {
+    assert!(!decider_configs.is_empty(), "No decider given");
+    let mut statuses = vec![MachineStatus::NoDecision; machines.len()];
+    // positions of the machines still undecided
+    let mut undecided: Vec<usize> = (0..machines.len()).collect();
+    for (i, dc) in decider_configs.iter().enumerate() {
+        if undecided.is_empty() {
+            break;
+        }
+        // record all decided machines
+        let config = Config::builder_from_config(dc.config())
+            .limit_machines_decided(undecided.len())
+            .build();
+        let batch_machines: Vec<MachineId> = undecided.iter().map(|pos| machines[*pos]).collect();
+        let mut batch_data = BatchData {
+            machines: &batch_machines,
+            result_decided: DeciderResultStats::new(&config),
+            machines_decided: Default::default(),
+            machines_undecided: Default::default(),
+            batch_no: 0,
+            num_batches: 1,
+            decider_id: dc.decider_id(),
+            config: &config,
+            run_predecider: PreDeciderRun::DoNotRun,
+            step_budget: 0,
+            steps_used: Vec::new(),
+            hints: Vec::new(),
+        };
+        dc.f_decider()(&mut batch_data)?;
+
+        // the decided and undecided machines are in order of the batch
+        let mut k = 0;
+        for (machine, status) in batch_data
+            .machines_decided
+            .machines
+            .iter()
+            .zip(batch_data.machines_decided.states.iter())
+        {
+            while batch_machines[k] != *machine {
+                k += 1;
+            }
+            statuses[undecided[k]] = *status;
+            k += 1;
+        }
+        let mut k = 0;
+        let mut undecided_next = Vec::with_capacity(batch_data.machines_undecided.machines.len());
+        for (machine, status) in batch_data
+            .machines_undecided
+            .machines
+            .iter()
+            .zip(batch_data.machines_undecided.states.iter())
+        {
+            while batch_machines[k] != *machine {
+                k += 1;
+            }
+            if i + 1 == decider_configs.len() {
+                statuses[undecided[k]] = *status;
+            }
+            undecided_next.push(undecided[k]);
+            k += 1;
+        }
+        undecided = undecided_next;
+    }
+
+    Ok(statuses)
+}
+
+/// Runs the decider on the batch. If the decider panics, e.g. on a todo!() of a rarely used code path,
+/// the batch is run again machine by machine. The machines on which the decider panics again are listed
+/// in the result (see [DeciderResultStats::machines_errored]) and passed to the next decider as undecided
+/// with [UndecidedReason::DeciderPanicked]. \
+/// The panic messages are printed by the panic hook of the application, the messages of the single machines
+/// are also kept in the result.
+fn run_decider_isolated(dc: &DeciderConfig, batch_data: &mut BatchData) -> ResultUnitEndReason {
+    let steps_max = batch_data.result_decided.steps_max();
+    if let Ok(r) = panic::catch_unwind(AssertUnwindSafe(|| dc.f_decider()(batch_data))) {
+        return r;
+    }
+
+    // retry each machine on its own, the batch data may be partially filled
+    batch_data.result_decided =
+        DeciderResultStats::new_init_steps_max(batch_data.config, steps_max);
+    batch_data.machines_decided = Default::default();
+    batch_data.machines_undecided = Default::default();
+    let limit_decided = batch_data.config.limit_machines_decided();
+    for (i, machine) in batch_data.machines.iter().enumerate() {
+        let mut batch_single = BatchData {
+            machines: std::slice::from_ref(machine),
+            result_decided: DeciderResultStats::new_init_steps_max(
+                batch_data.config,
+                batch_data.result_decided.steps_max(),
+            ),
+            machines_decided: Default::default(),
+            machines_undecided: Default::default(),
+            batch_no: batch_data.batch_no,
+            num_batches: batch_data.num_batches,
+            decider_id: batch_data.decider_id,
+            config: batch_data.config,
+            run_predecider: batch_data.run_predecider,
+            step_budget: batch_data.step_budget,
+            steps_used: batch_data
+                .steps_used
+                .get(i)
+                .map(|s| vec![*s])
+                .unwrap_or_default(),
+            hints: batch_data
+                .hints
+                .get(i)
+                .map(|h| vec![*h])
+                .unwrap_or_default(),
+        };
+        match panic::catch_unwind(AssertUnwindSafe(|| dc.f_decider()(&mut batch_single))) {
+            Ok(r) => {
+                r?;
+                batch_data
+                    .result_decided
+                    .add_result(&batch_single.result_decided);
+                let decided = batch_single.machines_decided;
+                let num_free =
+                    limit_decided.saturating_sub(batch_data.machines_decided.machines.len());
+                for (m, status) in decided
+                    .machines
+                    .into_iter()
+                    .zip(decided.states)
+                    .take(num_free)
+                {
+                    batch_data.machines_decided.machines.push(m);
+                    batch_data.machines_decided.states.push(status);
+                }
+                let undecided = batch_single.machines_undecided;
+                batch_data
+                    .machines_undecided
+                    .machines
+                    .extend(undecided.machines);
+                batch_data
+                    .machines_undecided
+                    .states
+                    .extend(undecided.states);
+                batch_data.machines_undecided.hints.extend(undecided.hints);
+            }
+            Err(payload) => {
+                let message = if let Some(s) = payload.downcast_ref::<&str>() {
+                    s.to_string()
+                } else if let Some(s) = payload.downcast_ref::<String>() {
+                    s.clone()
+                } else {
+                    "unknown panic".to_string()
+                };
+                batch_data
+                    .result_decided
+                    .add_errored(machine, dc.decider_id().name, message);
+                let steps_used = batch_data.steps_used.get(i).copied().unwrap_or_default();
+                let undecided = &mut batch_data.machines_undecided;
+                undecided.machines.push(*machine);
+                undecided.states.push(MachineStatus::Undecided(
+                    UndecidedReason::DeciderPanicked,
+                    steps_used,
+                    0,
+                ));
+                if batch_data.config.decider_hints() {
+                    undecided
+                        .hints
+                        .push(batch_data.hints.get(i).copied().unwrap_or_default());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Throughput of the decider which just ran on batch_data. Machines eliminated by the pre-decider
+/// in the first decider are not counted, as the decider did not run them.
+fn decider_stats(
+    batch_data: &BatchData,
+    decider_name: &'static str,
+    start_decider: Instant,
+) -> DeciderStats {
+    let result = &batch_data.result_decided;
+    let num_eliminated = result.pre_decider_count().num_total()
+        + result
+            .n_states_sections()
+            .iter()
+            .map(|section| section.pre_decider_count().num_total())
+            .sum::<u64>();
+    let num_machines = batch_data.machines.len() as u64 - num_eliminated;
+    DeciderStats {
+        decider_name,
+        num_machines,
+        num_decided: num_machines - batch_data.machines_undecided.machines.len() as u64,
+        num_steps: batch_data.result_decided.num_steps_simulated(),
+        duration: start_decider.elapsed(),
+    }
+}
+
+/// Runs the deciders one after the other over all machines: The first decider runs on all machines of the
+/// data provider, each following decider on all machines left undecided by the previous decider. \
+/// The undecided machines of a stage are collected in a [MachineSpool], which writes them to a temporary file
+/// if there are more than spill_threshold machines, and are the data provider of the next stage. So a
+/// multi-stage run, e.g. cycler, then bouncer on what is left, then hold, is a single call. \
+/// The result is the same as of [batch_run_decider_chain_data_provider_single_thread], only the undecided
+/// machines are not recorded with the stage where they first were undecided.
+pub fn run_decider_stages(
+    decider_configs: &[DeciderConfig],
+    mut data_provider: impl DataProvider,
+    spill_threshold: usize,
+) -> DeciderResultStats {
+    let first_config = decider_configs.first().expect("No decider given").config();
+    let start = Instant::now();
+    let mut result_main = DeciderResultStats::new(first_config);
+    for dc in decider_configs.iter() {
+        result_main.enhance_machines_un_decided(dc.config());
+    }
+    let num_stages = decider_configs.len();
+    let new_spool =
+        |stage_no: usize| (stage_no + 1 < num_stages).then(|| MachineSpool::new(spill_threshold));
+    let mut verdict_sinks = VerdictSinks::start_run(decider_configs, &mut result_main);
+    let mut spool = new_spool(0);
+    let mut r = run_stage(
+        &decider_configs[0],
+        0,
+        &mut data_provider,
+        &mut result_main,
+        spool.as_mut(),
+        &mut verdict_sinks,
+    );
+    for (stage_no, dc) in decider_configs.iter().enumerate().skip(1) {
+        if r.is_err() {
+            break;
+        }
+        let mut data_provider_stage = match spool.take().unwrap().into_data_provider() {
+            Ok(dp) => dp,
+            Err(e) => {
+                r = Err(e.into());
+                break;
+            }
+        };
+        spool = new_spool(stage_no);
+        r = run_stage(
+            dc,
+            stage_no,
+            &mut data_provider_stage,
+            &mut result_main,
+            spool.as_mut(),
+            &mut verdict_sinks,
+        );
+    }
+    result_main.end_reason = match r {
+        Ok(()) => EndReason::AllMachinesChecked,
+        Err(e) => e,
+    };
+    result_main.duration = DurationDataProvider {
+        duration_total: start.elapsed(),
+        ..Default::default()
+    };
 
-    result_batch
+    result_main
+}
+
+/// Runs one stage of [run_decider_stages] over all batches of the data provider and adds the undecided
+/// machines to the spool of the next stage. Without spool this is the last stage and the undecided
+/// machines are added to the result. The verdicts of each batch are written into the verdict outputs.
+fn run_stage(
+    dc: &DeciderConfig,
+    stage_no: usize,
+    data_provider: &mut impl DataProvider,
+    result_main: &mut DeciderResultStats,
+    mut spool_next: Option<&mut MachineSpool>,
+    verdict_sinks: &mut VerdictSinks,
+) -> ResultUnitEndReason {
+    loop {
+        let data = data_provider
+            .machine_batch_next()
+            .map_err(|e| EndReason::Error(0, e.to_string()))?;
+        if let Some(pre) = data.pre_decider_count {
+            let result_n_states = result_main.n_states_section_mut(data.n_states);
+            result_n_states.add_pre_decider_count(&pre);
+            result_n_states.add_total(pre.num_total());
+        }
+        result_main.add_pre_decider_timer_data_provider(&data.pre_decider_timer);
+        if !data.machines.is_empty() {
+            let start_decider = Instant::now();
+            let mut batch_data = BatchData {
+                machines: &data.machines,
+                result_decided: DeciderResultStats::new_init_steps_max(
+                    dc.config(),
+                    result_main.steps_max(),
+                ),
+                machines_decided: Default::default(),
+                machines_undecided: Default::default(),
+                batch_no: data.batch_no,
+                num_batches: data_provider.num_batches(),
+                decider_id: dc.decider_id(),
+                config: dc.config(),
+                run_predecider: data_provider.requires_pre_decider_check(),
+                step_budget: 0,
+                steps_used: Vec::new(),
+                hints: Vec::new(),
+            };
+            run_decider_isolated(dc, &mut batch_data)?;
+            // the machines of later stages are already counted in the first stage
+            if stage_no > 0 {
+                batch_data.result_decided.clear_total();
+            }
+            if let Some(fnr) = dc.fo_result_worker() {
+                fnr(&mut batch_data)?;
+            }
+            let mut result_batch =
+                DeciderResultStats::new_init_steps_max(dc.config(), result_main.steps_max());
+            result_batch.add_result(&batch_data.result_decided);
+            result_batch.add_decider_stats(&decider_stats(
+                &batch_data,
+                dc.decider_id().name,
+                start_decider,
+            ));
+            let undecided = std::mem::take(&mut batch_data.machines_undecided);
+            match spool_next.as_deref_mut() {
+                Some(spool) => spool.push(&undecided.machines)?,
+                None => {
+                    if dc.config().record_verdicts() {
+                        for (m, status) in undecided.machines.iter().zip(undecided.states.iter()) {
+                            result_batch.add_verdict(m, dc.decider_id().name, *status);
+                        }
+                    }
+                    for (m, status) in undecided.machines.iter().zip(undecided.states.iter()) {
+                        if !result_batch.add_with_undecided_stage(m, status, None) {
+                            break;
+                        }
+                    }
+                }
+            }
+            let is_ok = result_main.add_result(&result_batch);
+            write_verdicts_or_warn(verdict_sinks, result_main);
+            if !is_ok {
+                return Err(result_main.end_reason.clone());
+            }
+        }
+        if matches!(
+            data.end_reason,
+            EndReason::IsLastBatch | EndReason::NoMoreData
+        ) {
+            return Ok(());
+        }
+    }
 }
 
 /// Runs the data provider and the deciders both on the main thread
@@ -237,7 +786,10 @@ pub fn batch_run_decider_chain_data_provider_single_thread(
     batch_run_decider_chain_data_provider_single_thread_reporting(
         decider_configs,
         data_provider,
-        Some(Reporter::new_default(total)),
+        Some(Reporter::from_config(
+            total,
+            decider_configs.first().expect("No decider given").config(),
+        )),
     )
 }
 
@@ -245,9 +797,79 @@ pub fn batch_run_decider_chain_data_provider_single_thread(
 /// using a custom reporter (or None to omit reporting).
 // TODO check end_result like in multi
 pub fn batch_run_decider_chain_data_provider_single_thread_reporting(
+    decider_configs: &[DeciderConfig],
+    data_provider: impl DataProvider,
+    reporter: Option<Reporter>,
+) -> DeciderResultStats {
+    batch_run_single_thread_router(decider_configs, data_provider, reporter, None)
+}
+
+/// Runs the decider chain like [batch_run_decider_chain_data_provider_single_thread], but the machines
+/// matching a route of the router run with the decider order of that route, see [DeciderRouter].
+pub fn batch_run_decider_chain_routed_data_provider_single_thread(
+    decider_configs: &[DeciderConfig],
+    router: &DeciderRouter,
+    data_provider: impl DataProvider,
+) -> DeciderResultStats {
+    batch_run_single_thread_router(decider_configs, data_provider, None, Some(router))
+}
+
+/// Sets the end reason of the result if one of the [StopConditions] of the config is met.
+/// Returns true if the run has to stop.
+pub(crate) fn check_stop_conditions(
+    config: &Config,
+    result: &mut DeciderResultStats,
+    start: Instant,
+) -> bool {
+    let conditions = config.stop_conditions();
+    if conditions.is_empty() || result.end_reason != EndReason::None {
+        return false;
+    }
+    match conditions.check(result, start.elapsed()) {
+        Some(end_reason) => {
+            result.end_reason = end_reason;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Sets the end reason of the result if [Config::check_invariants] is set and the counters of the result
+/// violate an invariant after the batch was added. Returns true if the run has to stop. \
+/// A result which already has an end reason is not checked, as it may be incomplete, e.g. when the
+/// record limit of the undecided machines was reached.
+pub(crate) fn check_invariants(
+    config: &Config,
+    result: &mut DeciderResultStats,
+    batch_no: usize,
+) -> bool {
+    if !config.check_invariants() || result.end_reason != EndReason::None {
+        return false;
+    }
+    match result.check_invariants() {
+        Ok(()) => false,
+        Err(message) => {
+            result.end_reason = EndReason::InvariantViolated(batch_no, message);
+            true
+        }
+    }
+}
+
+/// Logs the stage event when the data provider has delivered all batches.
+fn log_data_provider_finished(
+    event_log: &mut EventLog,
+    _result: &DeciderResultStats,
+) -> std::io::Result<()> {
+    event_log.log(&RunEvent::Stage {
+        name: "data_provider_finished".to_string(),
+    })
+}
+
+fn batch_run_single_thread_router(
     decider_configs: &[DeciderConfig],
     mut data_provider: impl DataProvider,
     mut reporter: Option<Reporter>,
+    router: Option<&DeciderRouter>,
 ) -> DeciderResultStats {
     let first_config = decider_configs.first().expect("No decider given").config();
 
@@ -259,6 +881,25 @@ pub fn batch_run_decider_chain_data_provider_single_thread_reporting(
     for dc in decider_configs.iter() {
         result_main.enhance_machines_un_decided(dc.config());
     }
+    let mut event_log = EventLog::start_run(
+        decider_configs,
+        data_provider.num_machines_to_process(),
+        1,
+        &mut result_main,
+    );
+    let mut stats_snapshot = StatsSnapshot::start_run(decider_configs);
+    let mut verdict_sinks = VerdictSinks::start_run(decider_configs, &mut result_main);
+    let mut checkpointer =
+        Checkpointer::start_run(decider_configs, &mut data_provider, &mut result_main);
+    let dashboard = Dashboard::start_run(
+        decider_configs,
+        data_provider.num_batches(),
+        data_provider.num_machines_to_process(),
+        1,
+    );
+    let dashboard_sender = dashboard
+        .as_ref()
+        .map_or_else(DashboardSender::default, Dashboard::sender);
     loop {
         // generate or get one batch of machines
         let start_gen = Instant::now();
@@ -266,9 +907,14 @@ pub fn batch_run_decider_chain_data_provider_single_thread_reporting(
         match r {
             Ok(data) => {
                 if let Some(pre) = data.pre_decider_count {
-                    result_main.add_pre_decider_count(&pre);
-                    result_main.add_total(pre.num_total());
+                    let result_n_states = result_main.n_states_section_mut(data.n_states);
+                    result_n_states.add_pre_decider_count(&pre);
+                    result_n_states.add_total(pre.num_total());
+                    if let Some(c) = checkpointer.as_mut() {
+                        c.add_pre_decider_count(data.batch_no, data.n_states, &pre);
+                    }
                 }
+                result_main.add_pre_decider_timer_data_provider(&data.pre_decider_timer);
                 duration_data_provider += start_gen.elapsed();
                 if !data.machines.is_empty() {
                     // TODO check on end_reason
@@ -276,6 +922,7 @@ pub fn batch_run_decider_chain_data_provider_single_thread_reporting(
 
                     // run deciders
                     let start_decider = Instant::now();
+                    dashboard_sender.batch_started(0, data.batch_no);
                     // run first decider which includes pre-decider elimination
                     // let mut undecided_available = true;
                     // let mut stop_run = false;
@@ -293,9 +940,37 @@ pub fn batch_run_decider_chain_data_provider_single_thread_reporting(
                         decider_id: decider_configs[0].decider_id(),
                         config: first_config,
                         run_predecider: data_provider.requires_pre_decider_check(),
+                        step_budget: first_config.step_budget_chain(),
+                        steps_used: Vec::new(),
+                        hints: Vec::new(),
                     };
-                    let dc_result = decide_batch_chain(batch_data, decider_configs);
+                    let dc_result = match router {
+                        Some(router) => {
+                            decide_batch_chain_routed(batch_data, decider_configs, router)
+                        }
+                        None => decide_batch_chain(batch_data, decider_configs),
+                    };
+                    dashboard_sender.batch_finished(
+                        0,
+                        data.batch_no,
+                        &dc_result,
+                        start_decider.elapsed(),
+                    );
                     result_main.add_result(&dc_result);
+                    if let Some(c) = checkpointer.as_mut() {
+                        c.add_result(data.batch_no, &dc_result);
+                    }
+                    log_or_warn(
+                        &mut event_log,
+                        &mut result_main,
+                        EventLog::log_result_progress,
+                    );
+                    snapshot_or_warn(
+                        &mut stats_snapshot,
+                        &mut result_main,
+                        StatsSnapshot::batch_finished,
+                    );
+                    write_verdicts_or_warn(&mut verdict_sinks, &mut result_main);
                     duration_decider += start_decider.elapsed();
                     match dc_result.end_reason {
                         EndReason::AllMachinesChecked => todo!(),
@@ -307,6 +982,10 @@ pub fn batch_run_decider_chain_data_provider_single_thread_reporting(
                         EndReason::RecordLimitDecidedReached(_) => break,
                         EndReason::RecordLimitUndecidedReached(_) => break,
                         EndReason::StopRequested(_, _) => break,
+                        EndReason::UndecidedLimitReached(_)
+                        | EndReason::HaltStepsReached(_)
+                        | EndReason::TimeLimitReached(_)
+                        | EndReason::InvariantViolated(_, _) => todo!(),
                         EndReason::None => {}
                     };
                     // let undecided_available = result.add_result(&br.result_decided);
@@ -316,32 +995,42 @@ pub fn batch_run_decider_chain_data_provider_single_thread_reporting(
                     //     break;
                     // }
                 }
+                checkpoint_or_warn(&mut checkpointer, &mut event_log, &mut result_main, |c| {
+                    c.batch_finished(data.batch_no)
+                });
+
+                // Output info on progress
+                if let Some(reporter) = reporter.as_mut() {
+                    reporter.batch_finished(data_provider.num_batches(), &result_main);
+                }
                 match data.end_reason {
                     EndReason::AllMachinesChecked => todo!(),
                     EndReason::Error(_, _) => todo!(),
                     EndReason::IsLastBatch => {
+                        log_or_warn(&mut event_log, &mut result_main, log_data_provider_finished);
                         result_main.end_reason = EndReason::AllMachinesChecked;
                         break;
                     }
                     EndReason::MachineLimitReached(_) => todo!(),
                     EndReason::NoBatchData => todo!(),
                     EndReason::NoMoreData => {
+                        log_or_warn(&mut event_log, &mut result_main, log_data_provider_finished);
                         result_main.end_reason = data.end_reason;
                         break;
                     }
                     EndReason::RecordLimitDecidedReached(_) => todo!(),
                     EndReason::RecordLimitUndecidedReached(_) => todo!(),
                     EndReason::StopRequested(_, _) => todo!(),
+                    EndReason::UndecidedLimitReached(_)
+                    | EndReason::HaltStepsReached(_)
+                    | EndReason::TimeLimitReached(_)
+                    | EndReason::InvariantViolated(_, _) => todo!(),
                     EndReason::None => {}
                 }
-
-                // Output info on progress
-                if let Some(reporter) = reporter.as_mut() {
-                    if reporter.is_due_progress() {
-                        let s =
-                            reporter.report_stats(result_main.num_processed_total(), &result_main);
-                        println!("{s}");
-                    }
+                if check_invariants(first_config, &mut result_main, data.batch_no)
+                    || check_stop_conditions(first_config, &mut result_main, start)
+                {
+                    break;
                 }
             }
             Err(_) => todo!(),
@@ -352,6 +1041,18 @@ pub fn batch_run_decider_chain_data_provider_single_thread_reporting(
         duration_decider,
         duration_total: start.elapsed(),
     };
+    checkpoint_or_warn(
+        &mut checkpointer,
+        &mut event_log,
+        &mut result_main,
+        Checkpointer::run_end,
+    );
+    log_or_warn(&mut event_log, &mut result_main, EventLog::log_run_end);
+    snapshot_or_warn(&mut stats_snapshot, &mut result_main, |s, r| s.write(r));
+    drop(dashboard_sender);
+    if let Some(dashboard) = dashboard {
+        dashboard.finish();
+    }
 
     // Add the name at the end or it will result in a little performance loss. Reason unknown.
     // TODO name
@@ -374,7 +1075,10 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread(
     batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
         decider_configs,
         data_provider,
-        Some(Reporter::new_default(total)),
+        Some(Reporter::from_config(
+            total,
+            decider_configs.first().expect("No decider given").config(),
+        )),
     )
 }
 
@@ -391,7 +1095,7 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
         .first()
         .expect("No decider given")
         .config_clone();
-    let max_threads = num_cpus_percentage(first_config.cpu_utilization_percent());
+    let max_threads = first_config.num_threads_effective();
     // if single thread run single
     if max_threads == 1 {
         return batch_run_decider_chain_data_provider_single_thread_reporting(
@@ -404,11 +1108,32 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
     for dc in decider_configs.iter() {
         result_main.enhance_machines_un_decided(dc.config());
     }
+    let mut event_log = EventLog::start_run(
+        decider_configs,
+        data_provider.num_machines_to_process(),
+        max_threads,
+        &mut result_main,
+    );
+    let mut stats_snapshot = StatsSnapshot::start_run(decider_configs);
+    let mut verdict_sinks = VerdictSinks::start_run(decider_configs, &mut result_main);
+    let mut checkpointer =
+        Checkpointer::start_run(decider_configs, &mut data_provider, &mut result_main);
     let mut duration_data_provider = Duration::default();
     let mut duration_decider = Duration::default();
+    let dashboard = Dashboard::start_run(
+        decider_configs,
+        data_provider.num_batches(),
+        data_provider.num_machines_to_process(),
+        max_threads,
+    );
+    let dashboard_sender = dashboard
+        .as_ref()
+        .map_or_else(DashboardSender::default, Dashboard::sender);
 
     // Make a Thread Scope so that references can be accessed
     thread::scope(|s| {
+        // numbers of the decider threads not running, so each running thread has its own number
+        let mut free_workers: Vec<usize> = (0..max_threads).rev().collect();
         // TODO some fine tuning. Now the decider uses all threads, which leads to more load than CPUs are available.
         // If we leave one open, then CPU is not used in case of quick data provider.
         let max_threads_decider = max_threads;
@@ -434,10 +1159,21 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
                         // TODO handle other end reasons
                         match batch.end_reason {
                             EndReason::Error(_, _) => todo!(),
-                            EndReason::IsLastBatch => is_gen_finished = true,
+                            EndReason::IsLastBatch => {
+                                is_gen_finished = true;
+                                log_or_warn(
+                                    &mut event_log,
+                                    &mut result_main,
+                                    log_data_provider_finished,
+                                );
+                            }
                             EndReason::MachineLimitReached(_) => todo!(),
                             EndReason::NoBatchData => todo!(),
                             EndReason::NoMoreData => todo!(),
+                            EndReason::UndecidedLimitReached(_)
+                            | EndReason::HaltStepsReached(_)
+                            | EndReason::TimeLimitReached(_)
+                            | EndReason::InvariantViolated(_, _) => todo!(),
                             EndReason::None => {}
                             EndReason::AllMachinesChecked => todo!(),
                             EndReason::StopRequested(_, _) => todo!(),
@@ -473,12 +1209,23 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
                 do_sleep = false;
                 num_threads_decider_running += 1;
                 let send_finished_thread_dec = send_finished_thread_decider.clone();
+                let worker = free_workers.pop().unwrap_or_default();
+                let dashboard_sender = dashboard_sender.clone();
                 // move result out of vector to move into thread
                 let gen_result = buffer_gen_result.remove(0);
                 if let Some(pre_decider_count) = gen_result.pre_decider_count.as_ref() {
-                    result_main.add_pre_decider_count(pre_decider_count);
-                    result_main.add_total(pre_decider_count.num_total());
+                    let result_n_states = result_main.n_states_section_mut(gen_result.n_states);
+                    result_n_states.add_pre_decider_count(pre_decider_count);
+                    result_n_states.add_total(pre_decider_count.num_total());
+                    if let Some(c) = checkpointer.as_mut() {
+                        c.add_pre_decider_count(
+                            gen_result.batch_no,
+                            gen_result.n_states,
+                            pre_decider_count,
+                        );
+                    }
                 }
+                result_main.add_pre_decider_timer_data_provider(&gen_result.pre_decider_timer);
 
                 let run_predecider = data_provider.requires_pre_decider_check();
                 let num_batches = data_provider.num_batches();
@@ -494,6 +1241,7 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
                 // );
                 s.spawn(move || {
                     let start = Instant::now();
+                    dashboard_sender.batch_started(worker, gen_result.batch_no);
                     // gen_result is moved and not used further
                     // let machines = gen_result.machines;
                     // create batch data for first decider
@@ -507,10 +1255,20 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
                         decider_id: decider_configs[0].decider_id(),
                         config: &config,
                         run_predecider,
+                        step_budget: config.step_budget_chain(),
+                        steps_used: Vec::new(),
+                        hints: Vec::new(),
                     };
                     let dr = decide_batch_chain(batch_data, decider_configs);
+                    dashboard_sender.batch_finished(
+                        worker,
+                        gen_result.batch_no,
+                        &dr,
+                        start.elapsed(),
+                    );
                     let decider_result = ThreadResultDecider {
                         batch_no: gen_result.batch_no,
+                        worker,
                         result: dr,
                         duration: start.elapsed(),
                     };
@@ -528,20 +1286,34 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
             // Check if deciders have finished
             while let Ok(thread_result_dec) = receive_finished_thread_decider.try_recv() {
                 result_main.add_result(&thread_result_dec.result);
+                checkpoint_or_warn(&mut checkpointer, &mut event_log, &mut result_main, |c| {
+                    c.add_result(thread_result_dec.batch_no, &thread_result_dec.result);
+                    c.batch_finished(thread_result_dec.batch_no)
+                });
+                check_invariants(*first_config, &mut result_main, thread_result_dec.batch_no);
+                log_or_warn(
+                    &mut event_log,
+                    &mut result_main,
+                    EventLog::log_result_progress,
+                );
+                snapshot_or_warn(
+                    &mut stats_snapshot,
+                    &mut result_main,
+                    StatsSnapshot::batch_finished,
+                );
+                write_verdicts_or_warn(&mut verdict_sinks, &mut result_main);
                 duration_decider += thread_result_dec.duration;
                 num_threads_decider_running -= 1;
+                free_workers.push(thread_result_dec.worker);
                 // println!(
                 //     "Decider batch {}/{} finished",
                 //     thread_result_dec.batch_no + 1,
                 //     data_provider.num_batches()
                 // );
-            }
 
-            // Output info on progress
-            if let Some(reporter) = reporter.as_mut() {
-                if reporter.is_due_progress() {
-                    let s = reporter.report_stats(result_main.num_processed_total(), &result_main);
-                    println!("{s}");
+                // Output info on progress
+                if let Some(reporter) = reporter.as_mut() {
+                    reporter.batch_finished(data_provider.num_batches(), &result_main);
                 }
             }
 
@@ -568,6 +1340,7 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
                 // }
                 break;
             }
+            check_stop_conditions(*first_config, &mut result_main, start);
             match result_main.end_reason {
                 EndReason::AllMachinesChecked => todo!(),
                 EndReason::Error(_, _) => todo!(),
@@ -578,6 +1351,10 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
                 EndReason::RecordLimitDecidedReached(_) => break,
                 EndReason::RecordLimitUndecidedReached(_) => break,
                 EndReason::NoBatchData => todo!(),
+                EndReason::UndecidedLimitReached(_)
+                | EndReason::HaltStepsReached(_)
+                | EndReason::TimeLimitReached(_)
+                | EndReason::InvariantViolated(_, _) => break,
                 EndReason::None => {}
             }
 
@@ -592,6 +1369,18 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
         duration_decider,
         duration_total: start.elapsed(),
     };
+    checkpoint_or_warn(
+        &mut checkpointer,
+        &mut event_log,
+        &mut result_main,
+        Checkpointer::run_end,
+    );
+    log_or_warn(&mut event_log, &mut result_main, EventLog::log_run_end);
+    snapshot_or_warn(&mut stats_snapshot, &mut result_main, |s, r| s.write(r));
+    drop(dashboard_sender);
+    if let Some(dashboard) = dashboard {
+        dashboard.finish();
+    }
 
     for d in decider_configs {
         result_main.add_name(&format!(
@@ -604,6 +1393,265 @@ pub fn batch_run_decider_chain_threaded_data_provider_single_thread_reporting(
     result_main
 }
 
+/// Result of a decider thread of the pipelined run, the pre-decider count and timer of the batch are added in the main thread.
+struct PipelineResultDecider {
+    n_states: usize,
+    pre_decider_count: Option<PreDeciderCount>,
+    pre_decider_timer: PreDeciderTimer,
+    thread_result: ThreadResultDecider,
+}
+
+/// Runs the data provider in its own thread and the deciders in the remaining threads,
+/// connected by a bounded queue, using the standard reporter. See [batch_run_decider_chain_pipelined_reporting].
+pub fn batch_run_decider_chain_pipelined(
+    decider_configs: &[DeciderConfig],
+    data_provider: impl DataProvider + Send,
+) -> DeciderResultStats {
+    let total = data_provider.num_machines_to_process();
+    batch_run_decider_chain_pipelined_reporting(
+        decider_configs,
+        data_provider,
+        Some(Reporter::from_config(
+            total,
+            decider_configs.first().expect("No decider given").config(),
+        )),
+    )
+}
+
+/// Runs the data provider in its own thread, which passes each batch through a bounded queue
+/// (see [Config::pipeline_queue_depth]) to the decider threads. \
+/// The enumeration of the next batches overlaps with deciding and the data provider blocks when the queue is full,
+/// so at most queue depth + number of decider threads batches are held in memory, even with huge batch sizes.
+/// Uses a custom reporter (or None to omit reporting).
+pub fn batch_run_decider_chain_pipelined_reporting(
+    decider_configs: &[DeciderConfig],
+    mut data_provider: impl DataProvider + Send,
+    mut reporter: Option<Reporter>,
+) -> DeciderResultStats {
+    let start = Instant::now();
+    let first_config = decider_configs
+        .first()
+        .expect("No decider given")
+        .config_clone();
+    let max_threads = first_config.num_threads_effective();
+    // one thread is used by the data provider, but the pipeline always has a decider thread
+    let num_threads_decider = (max_threads - 1).max(1);
+    let mut result_main = DeciderResultStats::new(*first_config);
+    for dc in decider_configs.iter() {
+        result_main.enhance_machines_un_decided(dc.config());
+    }
+    let mut event_log = EventLog::start_run(
+        decider_configs,
+        data_provider.num_machines_to_process(),
+        num_threads_decider + 1,
+        &mut result_main,
+    );
+    let mut stats_snapshot = StatsSnapshot::start_run(decider_configs);
+    let mut verdict_sinks = VerdictSinks::start_run(decider_configs, &mut result_main);
+    let mut checkpointer =
+        Checkpointer::start_run(decider_configs, &mut data_provider, &mut result_main);
+    let run_predecider = data_provider.requires_pre_decider_check();
+    let num_batches = data_provider.num_batches();
+    let mut duration_decider = Duration::default();
+    let stop_requested = AtomicBool::new(false);
+    // max steps of the machines decided so far, so the deciders record less machines
+    let steps_max = AtomicU32::new(result_main.steps_max());
+
+    let dashboard = Dashboard::start_run(
+        decider_configs,
+        num_batches,
+        data_provider.num_machines_to_process(),
+        num_threads_decider,
+    );
+    let dashboard_sender = dashboard
+        .as_ref()
+        .map_or_else(DashboardSender::default, Dashboard::sender);
+
+    let (send_batch, receive_batch) =
+        std::sync::mpsc::sync_channel::<DataProviderBatch>(first_config.pipeline_queue_depth());
+    // the decider threads take turns in receiving the next batch
+    let receive_batch = Mutex::new(receive_batch);
+
+    // Make a Thread Scope so that references can be accessed
+    let (duration_data_provider, end_reason_data_provider) = thread::scope(|s| {
+        let (send_finished_thread_decider, receive_finished_thread_decider) =
+            std::sync::mpsc::channel::<PipelineResultDecider>();
+
+        let stop_requested_gen = &stop_requested;
+        let data_provider = &mut data_provider;
+        // send_batch is moved, so the deciders see the end of the queue when the data provider has ended
+        let data_provider_thread = s.spawn(move || {
+            let mut duration = Duration::default();
+            loop {
+                if stop_requested_gen.load(Ordering::Relaxed) {
+                    return (duration, EndReason::None);
+                }
+                let start = Instant::now();
+                let batch = match data_provider.machine_batch_next() {
+                    Ok(batch) => batch,
+                    Err(e) => return (duration, EndReason::Error(0, e.to_string())),
+                };
+                duration += start.elapsed();
+                let end_reason = batch.end_reason.clone();
+                // blocks while the queue is full, fails only if all deciders have ended
+                if send_batch.send(batch).is_err() || end_reason != EndReason::None {
+                    return (duration, end_reason);
+                }
+            }
+        });
+
+        for worker in 0..num_threads_decider {
+            let send_finished_thread_dec = send_finished_thread_decider.clone();
+            let dashboard_sender = dashboard_sender.clone();
+            let receive_batch = &receive_batch;
+            let stop_requested = &stop_requested;
+            let steps_max = &steps_max;
+            let config = *first_config;
+            s.spawn(move || loop {
+                // the lock is released before deciding
+                let batch = receive_batch.lock().expect("lock").recv();
+                // the data provider has ended and the queue is empty
+                let Ok(batch) = batch else {
+                    return;
+                };
+                // drain the queue, so a blocked data provider can see the stop
+                if stop_requested.load(Ordering::Relaxed) {
+                    continue;
+                }
+                let start = Instant::now();
+                dashboard_sender.batch_started(worker, batch.batch_no);
+                let batch_data = BatchData {
+                    machines: &batch.machines,
+                    result_decided: DeciderResultStats::new_init_steps_max(
+                        config,
+                        steps_max.load(Ordering::Relaxed),
+                    ),
+                    machines_decided: Default::default(),
+                    machines_undecided: Default::default(),
+                    batch_no: batch.batch_no,
+                    num_batches,
+                    decider_id: decider_configs[0].decider_id(),
+                    config,
+                    run_predecider,
+                    step_budget: config.step_budget_chain(),
+                    steps_used: Vec::new(),
+                    hints: Vec::new(),
+                };
+                let result = decide_batch_chain(batch_data, decider_configs);
+                dashboard_sender.batch_finished(worker, batch.batch_no, &result, start.elapsed());
+                let decider_result = PipelineResultDecider {
+                    n_states: batch.n_states,
+                    pre_decider_count: batch.pre_decider_count,
+                    pre_decider_timer: batch.pre_decider_timer,
+                    thread_result: ThreadResultDecider {
+                        batch_no: batch.batch_no,
+                        worker,
+                        result,
+                        duration: start.elapsed(),
+                    },
+                };
+                // unwrap error can occur if stop is requested while other threads are still running
+                send_finished_thread_dec
+                    .send(decider_result)
+                    .unwrap_or_default();
+            });
+        }
+        // only the decider threads hold senders now, so the loop ends when all deciders have ended
+        drop(send_finished_thread_decider);
+
+        for decider_result in receive_finished_thread_decider.iter() {
+            if let Some(pre_decider_count) = decider_result.pre_decider_count.as_ref() {
+                let result_n_states = result_main.n_states_section_mut(decider_result.n_states);
+                result_n_states.add_pre_decider_count(pre_decider_count);
+                result_n_states.add_total(pre_decider_count.num_total());
+                if let Some(c) = checkpointer.as_mut() {
+                    c.add_pre_decider_count(
+                        decider_result.thread_result.batch_no,
+                        decider_result.n_states,
+                        pre_decider_count,
+                    );
+                }
+            }
+            result_main.add_pre_decider_timer_data_provider(&decider_result.pre_decider_timer);
+            let thread_result_dec = decider_result.thread_result;
+            result_main.add_result(&thread_result_dec.result);
+            checkpoint_or_warn(&mut checkpointer, &mut event_log, &mut result_main, |c| {
+                c.add_result(thread_result_dec.batch_no, &thread_result_dec.result);
+                c.batch_finished(thread_result_dec.batch_no)
+            });
+            check_invariants(*first_config, &mut result_main, thread_result_dec.batch_no);
+            steps_max.store(result_main.steps_max(), Ordering::Relaxed);
+            log_or_warn(
+                &mut event_log,
+                &mut result_main,
+                EventLog::log_result_progress,
+            );
+            snapshot_or_warn(
+                &mut stats_snapshot,
+                &mut result_main,
+                StatsSnapshot::batch_finished,
+            );
+            write_verdicts_or_warn(&mut verdict_sinks, &mut result_main);
+            duration_decider += thread_result_dec.duration;
+
+            // Output info on progress
+            if let Some(reporter) = reporter.as_mut() {
+                reporter.batch_finished(num_batches, &result_main);
+            }
+
+            check_stop_conditions(*first_config, &mut result_main, start);
+            match result_main.end_reason {
+                EndReason::StopRequested(_, _)
+                | EndReason::RecordLimitDecidedReached(_)
+                | EndReason::RecordLimitUndecidedReached(_)
+                | EndReason::UndecidedLimitReached(_)
+                | EndReason::HaltStepsReached(_)
+                | EndReason::TimeLimitReached(_)
+                | EndReason::InvariantViolated(_, _) => {
+                    stop_requested.store(true, Ordering::Relaxed)
+                }
+                _ => {}
+            }
+        }
+
+        data_provider_thread.join().expect("data provider thread")
+    });
+
+    if result_main.end_reason == EndReason::None {
+        result_main.end_reason = match end_reason_data_provider {
+            EndReason::IsLastBatch => EndReason::AllMachinesChecked,
+            end_reason => end_reason,
+        };
+    }
+    result_main.duration = DurationDataProvider {
+        duration_data_provider,
+        duration_decider,
+        duration_total: start.elapsed(),
+    };
+    checkpoint_or_warn(
+        &mut checkpointer,
+        &mut event_log,
+        &mut result_main,
+        Checkpointer::run_end,
+    );
+    log_or_warn(&mut event_log, &mut result_main, EventLog::log_run_end);
+    snapshot_or_warn(&mut stats_snapshot, &mut result_main, |s, r| s.write(r));
+    drop(dashboard_sender);
+    if let Some(dashboard) = dashboard {
+        dashboard.finish();
+    }
+
+    for d in decider_configs {
+        result_main.add_name(&format!(
+            "BB{} pipelined: {}",
+            first_config.n_states(),
+            d.decider_id().name
+        ));
+    }
+
+    result_main
+}
+
 /// Runs the data provider and the deciders in separate threads (both can have multiple threads)
 /// using the standard reporter.
 pub fn batch_run_decider_chain_threaded_data_provider_multi_thread(
@@ -614,7 +1662,10 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread(
     batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
         decider_configs,
         data_provider,
-        Some(Reporter::new_default(total)),
+        Some(Reporter::from_config(
+            total,
+            decider_configs.first().expect("No decider given").config(),
+        )),
     )
 }
 
@@ -635,7 +1686,7 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread(
 // TODO thread recycling.
 pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
     decider_configs: &[DeciderConfig],
-    data_provider: impl DataProviderThreaded + std::marker::Send,
+    mut data_provider: impl DataProviderThreaded + std::marker::Send,
     mut reporter: Option<Reporter>,
 ) -> DeciderResultStats {
     let start = Instant::now();
@@ -643,7 +1694,7 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
         .first()
         .expect("No decider given")
         .config_clone();
-    let max_threads = num_cpus_percentage(first_config.cpu_utilization_percent());
+    let max_threads = first_config.num_threads_effective();
     // if single thread run single
     if max_threads == 1 {
         return batch_run_decider_chain_data_provider_single_thread_reporting(
@@ -657,13 +1708,35 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
     for dc in decider_configs.iter().skip(1) {
         result_main.enhance_machines_un_decided(dc.config());
     }
+    let mut event_log = EventLog::start_run(
+        decider_configs,
+        data_provider.num_machines_to_process(),
+        max_threads,
+        &mut result_main,
+    );
+    let mut stats_snapshot = StatsSnapshot::start_run(decider_configs);
+    let mut verdict_sinks = VerdictSinks::start_run(decider_configs, &mut result_main);
+    let mut checkpointer =
+        Checkpointer::start_run(decider_configs, &mut data_provider, &mut result_main);
     let mut duration_data_provider = Duration::default();
     let mut duration_decider = Duration::default();
+    let dashboard = Dashboard::start_run(
+        decider_configs,
+        data_provider.num_batches(),
+        data_provider.num_machines_to_process(),
+        max_threads,
+    );
+    let dashboard_sender = dashboard
+        .as_ref()
+        .map_or_else(DashboardSender::default, Dashboard::sender);
 
     // Make a Thread Scope so that references can be accessed
     thread::scope(|s| {
+        // numbers of the decider threads not running, so each running thread has its own number
+        let mut free_workers: Vec<usize> = (0..max_threads).rev().collect();
         let mut max_threads_gen = (max_threads / 2 + 1).max(1);
-        let mut batch_no = 0;
+        // a resumed run starts with the next batch of the checkpoint
+        let mut batch_no = checkpointer.as_ref().map_or(0, Checkpointer::next_batch_no);
         let (send_finished_thread_data_provider, receive_finished_thread_data_provider) =
             std::sync::mpsc::channel();
         let (send_finished_thread_decider, receive_finished_thread_decider) =
@@ -710,6 +1783,7 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                         batch_no,
                         machines: data.machines,
                         pre_decider_count: data.pre_decider_count,
+                        pre_decider_timer: data.pre_decider_timer,
                         duration: start.elapsed(),
                     });
                     // TODO sending result doubles the time as the data is copied within memory, or not? Should be moved.
@@ -724,6 +1798,7 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                     // turn off data_provider threads, they are not needed any more
                     max_threads_gen = 0;
                     is_gen_finished = true;
+                    log_or_warn(&mut event_log, &mut result_main, log_data_provider_finished);
                 }
             }
 
@@ -785,12 +1860,22 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                 do_sleep = false;
                 num_threads_decider_running += 1;
                 let send_finished_thread_dec = send_finished_thread_decider.clone();
+                let worker = free_workers.pop().unwrap_or_default();
+                let dashboard_sender = dashboard_sender.clone();
                 // move result out of vector to move into thread
                 let gen_result = buffer_gen_result.remove(0);
                 if let Some(pre_decider_count) = gen_result.pre_decider_count.as_ref() {
                     result_main.add_pre_decider_count(pre_decider_count);
                     result_main.add_total(pre_decider_count.num_total());
+                    if let Some(c) = checkpointer.as_mut() {
+                        c.add_pre_decider_count(
+                            gen_result.batch_no,
+                            first_config.n_states(),
+                            pre_decider_count,
+                        );
+                    }
                 }
+                result_main.add_pre_decider_timer_data_provider(&gen_result.pre_decider_timer);
                 let config = *first_config;
                 let run_predecider = data_provider.requires_pre_decider_check();
                 let num_batches = data_provider.num_batches();
@@ -805,6 +1890,7 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                 // );
                 s.spawn(move || {
                     let start = Instant::now();
+                    dashboard_sender.batch_started(worker, gen_result.batch_no);
                     // gen_result is moved and not used further
                     // let machines = gen_result.machines;
                     // create batch data for first decider
@@ -818,6 +1904,9 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                         decider_id: decider_configs[0].decider_id(),
                         config: &config,
                         run_predecider,
+                        step_budget: config.step_budget_chain(),
+                        steps_used: Vec::new(),
+                        hints: Vec::new(),
                     };
                     // println!(
                     //     "Decider batch {}/{} send b {}",
@@ -826,8 +1915,15 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                     //     batch_data.machines.len(),
                     // );
                     let dr = decide_batch_chain(batch_data, decider_configs);
+                    dashboard_sender.batch_finished(
+                        worker,
+                        gen_result.batch_no,
+                        &dr,
+                        start.elapsed(),
+                    );
                     let decider_result = ThreadResultDecider {
                         batch_no: gen_result.batch_no,
+                        worker,
                         result: dr,
                         duration: start.elapsed(),
                     };
@@ -848,16 +1944,29 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                 //     thread_result_dec.result.num_processed_total()
                 // );
                 result_main.add_result(&thread_result_dec.result);
+                checkpoint_or_warn(&mut checkpointer, &mut event_log, &mut result_main, |c| {
+                    c.add_result(thread_result_dec.batch_no, &thread_result_dec.result);
+                    c.batch_finished(thread_result_dec.batch_no)
+                });
+                check_invariants(*first_config, &mut result_main, thread_result_dec.batch_no);
+                log_or_warn(
+                    &mut event_log,
+                    &mut result_main,
+                    EventLog::log_result_progress,
+                );
+                snapshot_or_warn(
+                    &mut stats_snapshot,
+                    &mut result_main,
+                    StatsSnapshot::batch_finished,
+                );
+                write_verdicts_or_warn(&mut verdict_sinks, &mut result_main);
                 duration_decider += thread_result_dec.duration;
                 num_threads_decider_running -= 1;
+                free_workers.push(thread_result_dec.worker);
 
                 // Output info on progress
                 if let Some(reporter) = reporter.as_mut() {
-                    if reporter.is_due_progress() {
-                        let s =
-                            reporter.report_stats(result_main.num_processed_total(), &result_main);
-                        println!("{s}");
-                    }
+                    reporter.batch_finished(data_provider.num_batches(), &result_main);
                 }
             }
 
@@ -875,6 +1984,7 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                 result_main.end_reason = EndReason::AllMachinesChecked;
                 break;
             }
+            check_stop_conditions(*first_config, &mut result_main, start);
             match result_main.end_reason {
                 EndReason::AllMachinesChecked => todo!(),
                 EndReason::Error(_, _) => todo!(),
@@ -885,6 +1995,10 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
                 EndReason::RecordLimitDecidedReached(_) => break,
                 EndReason::RecordLimitUndecidedReached(_) => break,
                 EndReason::NoBatchData => todo!(),
+                EndReason::UndecidedLimitReached(_)
+                | EndReason::HaltStepsReached(_)
+                | EndReason::TimeLimitReached(_)
+                | EndReason::InvariantViolated(_, _) => break,
                 EndReason::None => {}
             }
 
@@ -899,6 +2013,18 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
         duration_decider,
         duration_total: start.elapsed(),
     };
+    checkpoint_or_warn(
+        &mut checkpointer,
+        &mut event_log,
+        &mut result_main,
+        Checkpointer::run_end,
+    );
+    log_or_warn(&mut event_log, &mut result_main, EventLog::log_run_end);
+    snapshot_or_warn(&mut stats_snapshot, &mut result_main, |s, r| s.write(r));
+    drop(dashboard_sender);
+    if let Some(dashboard) = dashboard {
+        dashboard.finish();
+    }
     result_main.set_name(format!(
         "BB{}: '{}' threaded",
         first_config.n_states(),
@@ -907,3 +2033,979 @@ pub fn batch_run_decider_chain_threaded_data_provider_multi_thread_reporting(
 
     result_main
 }
+
+/// Rule of a [DeciderRouter]: machines with matching features run the deciders in the given order.
+#[derive(Debug, Clone)]
+pub struct DeciderRoute {
+    pub name: &'static str,
+    pub matches: fn(&MachineFeatures) -> bool,
+    /// Indices into the decider chain, e.g. \[1, 0\] runs the second decider first.
+    /// Deciders which are not listed are not run for these machines.
+    pub decider_order: Vec<usize>,
+}
+
+/// Routes machines to different first deciders based on their static features
+/// (see [MachineFeatures]), e.g. machines with many self-referencing transitions to the bouncer first. \
+/// The first matching route is used, machines without matching route run the chain in its order.
+/// Use [routing_ab_test] to measure if the routing is faster.
+/// # Example
+/// ```
+/// use bb_challenge::{
+///     config::Config,
+///     data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+///     decider::{
+///         decider_engine::{
+///             batch_run_decider_chain_routed_data_provider_single_thread, DeciderRoute, DeciderRouter,
+///         },
+///         DeciderStandard,
+///     },
+/// };
+///
+/// let config = Config::builder(3).machine_limit(0).build();
+/// let deciders = [
+///     DeciderStandard::Cycler.decider_config(&config),
+///     DeciderStandard::Bouncer128.decider_config(&config),
+/// ];
+/// let router = DeciderRouter::default().with_route(DeciderRoute {
+///     name: "self-ref",
+///     matches: |f| f.num_self_ref >= 2,
+///     decider_order: vec![1, 0],
+/// });
+/// let enumerator = EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+/// let result = batch_run_decider_chain_routed_data_provider_single_thread(&deciders, &router, enumerator);
+/// println!("{result}");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeciderRouter {
+    routes: Vec<DeciderRoute>,
+}
+
+impl DeciderRouter {
+    pub fn with_route(mut self, route: DeciderRoute) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    pub fn routes(&self) -> &[DeciderRoute] {
+        &self.routes
+    }
+
+    /// Returns the index of the first matching route, None for the default order.
+    pub fn route_no(&self, features: &MachineFeatures) -> Option<usize> {
+        self.routes.iter().position(|r| (r.matches)(features))
+    }
+}
+
+/// Same as [decide_batch_chain], but the machines are grouped by the routes of the router and each group
+/// runs the deciders in the order of its route. The result merges all groups.
+pub fn decide_batch_chain_routed(
+    batch_data: BatchData,
+    decider_configs: &[DeciderConfig],
+    router: &DeciderRouter,
+) -> DeciderResultStats {
+    // group 0 is the default order, group n is route n - 1
+    let mut groups: Vec<Vec<MachineId>> = vec![Vec::new(); router.routes.len() + 1];
+    for machine in batch_data.machines.iter() {
+        let group = match router.route_no(&machine.machine().features()) {
+            Some(route_no) => route_no + 1,
+            None => 0,
+        };
+        groups[group].push(*machine);
+    }
+
+    let mut result = DeciderResultStats::new_init_steps_max(
+        batch_data.config,
+        batch_data.result_decided.steps_max(),
+    );
+    for dc in decider_configs.iter() {
+        result.enhance_machines_un_decided(dc.config());
+    }
+    for (group, machines) in groups.iter().enumerate() {
+        if machines.is_empty() {
+            continue;
+        }
+        let chain: Vec<DeciderConfig> = if group == 0 {
+            decider_configs.to_vec()
+        } else {
+            router.routes[group - 1]
+                .decider_order
+                .iter()
+                .map(|i| decider_configs[*i].clone())
+                .collect()
+        };
+        let group_data = BatchData {
+            machines,
+            result_decided: DeciderResultStats::new_init_steps_max(
+                chain[0].config(),
+                result.steps_max(),
+            ),
+            machines_decided: Default::default(),
+            machines_undecided: Default::default(),
+            batch_no: batch_data.batch_no,
+            num_batches: batch_data.num_batches,
+            decider_id: chain[0].decider_id(),
+            config: chain[0].config(),
+            run_predecider: batch_data.run_predecider,
+            step_budget: batch_data.step_budget,
+            steps_used: Vec::new(),
+            hints: Vec::new(),
+        };
+        result.add_result(&decide_batch_chain(group_data, &chain));
+    }
+
+    result
+}
+
+/// Result of [routing_ab_test].
+#[derive(Debug)]
+pub struct RoutingAbResult {
+    /// Result with the chain in its order.
+    pub result_default: DeciderResultStats,
+    pub result_routed: DeciderResultStats,
+}
+
+impl RoutingAbResult {
+    /// Sum of the decider durations of the result, the data provider is not included.
+    pub fn duration_deciders(result: &DeciderResultStats) -> Duration {
+        result.decider_stats().iter().map(|s| s.duration).sum()
+    }
+
+    /// Decider duration of the routed run relative to the default run, below 1.0 if the routing is faster.
+    pub fn duration_ratio(&self) -> f64 {
+        let default = Self::duration_deciders(&self.result_default).as_secs_f64();
+        if default == 0.0 {
+            1.0
+        } else {
+            Self::duration_deciders(&self.result_routed).as_secs_f64() / default
+        }
+    }
+}
+
+impl Display for RoutingAbResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, result) in [
+            ("Default", &self.result_default),
+            ("Routed", &self.result_routed),
+        ] {
+            writeln!(
+                f,
+                "{name:<8} deciders {}, halt {}, non-halt {}, undecided {}",
+                format_duration_hhmmss_ms(Self::duration_deciders(result), true),
+                result.num_halt(),
+                result.num_non_halt(),
+                result.num_undecided()
+            )?;
+        }
+        writeln!(f, "Routed / Default: {:.3}", self.duration_ratio())
+    }
+}
+
+/// A/B measurement of a router: runs the chain on all machines of the data provider once in its order
+/// and once routed, both single threaded.
+pub fn routing_ab_test<T: DataProviderThreaded>(
+    decider_configs: &[DeciderConfig],
+    router: &DeciderRouter,
+    data_provider: T,
+) -> RoutingAbResult {
+    let data_provider_routed = data_provider.new_from_data_provider();
+    RoutingAbResult {
+        result_default: batch_run_decider_chain_data_provider_single_thread(
+            decider_configs,
+            data_provider,
+        ),
+        result_routed: batch_run_decider_chain_routed_data_provider_single_thread(
+            decider_configs,
+            router,
+            data_provider_routed,
+        ),
+    }
+}
+
+/// Decider chain where each stage is a standard decider with its own step limit, e.g.
+/// Cycler(1_500) -> Bouncer(20_000) -> Cycler(110_000) -> Hold(50_000_000). \
+/// Each stage gets a copy of the base config with its step limit set, see [DeciderStandard::config_with_step_limit].
+/// The machines left undecided by a stage are forwarded to the next stage.
+/// # Example
+/// ```
+/// use bb_challenge::{
+///     config::{Config, CoreUsage},
+///     data_provider::enumerator_binary::EnumeratorType,
+///     decider::{decider_engine::DeciderPipeline, DeciderStandard},
+/// };
+///
+/// let config = Config::builder(3).machine_limit(0).build();
+/// let pipeline = DeciderPipeline::new(&config)
+///     .with_stage(DeciderStandard::Cycler, 100)
+///     .with_stage(DeciderStandard::Bouncer128, 1_000)
+///     .with_stage(DeciderStandard::Cycler, 1_000);
+/// let result = pipeline.run(EnumeratorType::EnumeratorReducedForward, CoreUsage::SingleCore);
+/// println!("{result}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DeciderPipeline {
+    config: Config,
+    stages: Vec<(DeciderStandard, StepBig)>,
+    /// Config of each stage, in order of the stages.
+    stage_configs: Vec<Config>,
+}
+
+impl DeciderPipeline {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            stages: Vec::new(),
+            stage_configs: Vec::new(),
+        }
+    }
+
+    /// Pipeline of the standard decider chain, see [DeciderStandard::standard_decider_for_config], with
+    /// the step limits of the config.
+    pub fn new_standard(config: &Config) -> Self {
+        let (config_1, config_2) = DeciderConfig::standard_config(config);
+        let stages = [
+            (DeciderStandard::Cycler, &config_1),
+            (DeciderStandard::Bouncer128, &config_1),
+            (DeciderStandard::Cycler, &config_2),
+            (DeciderStandard::BackwardReasoning, &config_1),
+            (DeciderStandard::TranslatedCycler, &config_1),
+            (DeciderStandard::Hold, &config_1),
+            (DeciderStandard::RepWL, &config_1),
+        ];
+        stages
+            .into_iter()
+            .fold(Self::new(config), |pipeline, (decider, c)| {
+                pipeline.with_stage(decider, decider.step_limit(c))
+            })
+    }
+
+    /// Adds a stage at the end of the pipeline.
+    pub fn with_stage(mut self, decider: DeciderStandard, step_limit: StepBig) -> Self {
+        self.stage_configs
+            .push(decider.config_with_step_limit(&self.config, step_limit));
+        self.stages.push((decider, step_limit));
+        self
+    }
+
+    pub fn stages(&self) -> &[(DeciderStandard, StepBig)] {
+        &self.stages
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The decider chain of the stages, e.g. to call one of the batch_run functions directly.
+    pub fn decider_configs(&self) -> Vec<DeciderConfig<'_>> {
+        self.stages
+            .iter()
+            .zip(self.stage_configs.iter())
+            .map(|((decider, _), config)| decider.decider_config(config))
+            .collect()
+    }
+
+    /// Runs the pipeline on the enumerator, see [run_decider_chain_gen].
+    pub fn run(
+        &self,
+        enumerator_type: EnumeratorType,
+        multi_core: CoreUsage,
+    ) -> DeciderResultStats {
+        run_decider_chain_gen(&self.decider_configs(), enumerator_type, multi_core)
+    }
+
+    /// Runs each stage over all machines left undecided by the previous stage, see [run_decider_stages].
+    pub fn run_stages(
+        &self,
+        data_provider: impl DataProvider,
+        spill_threshold: usize,
+    ) -> DeciderResultStats {
+        run_decider_stages(&self.decider_configs(), data_provider, spill_threshold)
+    }
+
+    /// Runs the stages on a single machine until one decides it.
+    /// # Returns
+    /// The deciding stage and its status, or None and the status of the last stage if the machine is undecided.
+    pub fn decide_single_machine(
+        &self,
+        machine: &MachineId,
+    ) -> (Option<DeciderStandard>, MachineStatus) {
+        let mut status = MachineStatus::NoDecision;
+        for ((decider, _), config) in self.stages.iter().zip(self.stage_configs.iter()) {
+            status = decider.decide_single_machine(machine, config);
+            if !matches!(
+                status,
+                MachineStatus::Undecided(_, _, _) | MachineStatus::NoDecision
+            ) {
+                return (Some(*decider), status);
+            }
+        }
+        (None, status)
+    }
+
+    /// Runs the pipeline on the data provider, see [run_decider_chain_data_provider_single].
+    pub fn run_data_provider(
+        &self,
+        data_provider: impl DataProvider,
+        multi_core: CoreUsage,
+    ) -> DeciderResultStats {
+        run_decider_chain_data_provider_single(&self.decider_configs(), data_provider, multi_core)
+    }
+}
+
+/// Displays the stages, e.g. 'Decider Cycler (1,500) -> Decider Bouncer (20,000)'.
+impl Display for DeciderPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        let stages: Vec<String> = self
+            .stages
+            .iter()
+            .map(|(decider, step_limit)| {
+                format!(
+                    "{} ({})",
+                    decider.decider_id().name,
+                    step_limit.to_formatted_string(&locale)
+                )
+            })
+            .collect();
+        write!(f, "{}", stages.join(" -> "))
+    }
+}
+
+/// Estimated machines and single thread duration of one decider of the chain, see [RunEstimate].
+#[derive(Debug, Clone)]
+pub struct StageEstimate {
+    pub decider_name: &'static str,
+    /// Machines passed to the decider.
+    pub num_machines: u64,
+    pub num_decided: u64,
+    pub duration: Duration,
+}
+
+/// Result of [estimate]: the sampled values extrapolated to the whole run.
+#[derive(Debug, Clone, Default)]
+pub struct RunEstimate {
+    pub num_batches: usize,
+    pub num_batches_sampled: usize,
+    pub num_machines_to_process: u64,
+    /// Machines eliminated by the pre-decider, in the data provider and in the first decider.
+    pub num_eliminated: u64,
+    /// One stage per decider in order of the chain.
+    pub stages: Vec<StageEstimate>,
+    /// Machines which are still undecided after the last decider.
+    pub num_undecided: u64,
+    /// Single thread duration of the data provider.
+    pub duration_data_provider: Duration,
+    /// Threads used for the wall time, see Config::num_threads_effective.
+    pub num_threads: usize,
+}
+
+impl RunEstimate {
+    /// Share of the machines eliminated by the pre-decider.
+    pub fn rate_eliminated(&self) -> f64 {
+        if self.num_machines_to_process == 0 {
+            0.0
+        } else {
+            self.num_eliminated as f64 / self.num_machines_to_process as f64
+        }
+    }
+
+    /// Duration of data provider and all deciders on a single thread.
+    pub fn duration_single_thread(&self) -> Duration {
+        self.duration_data_provider + self.stages.iter().map(|s| s.duration).sum::<Duration>()
+    }
+
+    /// Expected wall time with num_threads threads for the deciders, the data provider runs in parallel.
+    pub fn duration_wall(&self) -> Duration {
+        let duration_deciders: Duration = self.stages.iter().map(|s| s.duration).sum();
+        (duration_deciders / self.num_threads.max(1) as u32).max(self.duration_data_provider)
+    }
+}
+
+impl Display for RunEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        writeln!(
+            f,
+            "Run estimate from {} of {} batches:",
+            self.num_batches_sampled, self.num_batches
+        )?;
+        writeln!(
+            f,
+            "  Machines:         {:>20}",
+            self.num_machines_to_process.to_formatted_string(&locale)
+        )?;
+        writeln!(
+            f,
+            "  Pre-Decider:      {:>20} ({:.1}%)",
+            self.num_eliminated.to_formatted_string(&locale),
+            self.rate_eliminated() * 100.0
+        )?;
+        writeln!(
+            f,
+            "  Data provider:    {:>20} {}",
+            "",
+            format_duration_hhmmss_ms(self.duration_data_provider, false)
+        )?;
+        for s in self.stages.iter() {
+            writeln!(
+                f,
+                "  {:<16}  {:>20} -> decided {}, {}",
+                s.decider_name,
+                s.num_machines.to_formatted_string(&locale),
+                s.num_decided.to_formatted_string(&locale),
+                format_duration_hhmmss_ms(s.duration, false)
+            )?;
+        }
+        writeln!(
+            f,
+            "  Undecided:        {:>20}",
+            self.num_undecided.to_formatted_string(&locale)
+        )?;
+        writeln!(
+            f,
+            "  Duration: single thread {}, {} threads {}",
+            format_duration_hhmmss_ms(self.duration_single_thread(), false),
+            self.num_threads,
+            format_duration_hhmmss_ms(self.duration_wall(), false)
+        )
+    }
+}
+
+/// Dry run which estimates the size and duration of a run before starting it, e.g. a multi-day run. \
+/// Runs the decider chain on num_sample_batches batches spread evenly over the data provider and
+/// extrapolates the pre-decider elimination, the machines and the duration of each decider and the
+/// undecided machines to all machines of the data provider. The estimate is only as good as the sample,
+/// e.g. the duration of the halt decider depends on a few long running machines.
+pub fn estimate(
+    decider_configs: &[DeciderConfig],
+    mut data_provider: impl DataProviderThreaded,
+    num_sample_batches: usize,
+) -> RunEstimate {
+    let first_config = decider_configs.first().expect("No decider given").config();
+    let num_batches = data_provider.num_batches();
+    let num_sample_batches = num_sample_batches.clamp(1, num_batches.max(1));
+    let mut result = DeciderResultStats::new(first_config);
+    let mut num_processed = 0;
+    let mut num_eliminated_data_provider = 0;
+    let mut duration_data_provider = Duration::default();
+    for i in 0..num_sample_batches {
+        let batch_no = i * num_batches / num_sample_batches;
+        let start_gen = Instant::now();
+        let data = data_provider.batch_no(batch_no);
+        duration_data_provider += start_gen.elapsed();
+        if let Some(pre) = data.pre_decider_count {
+            num_eliminated_data_provider += pre.num_total();
+            num_processed += pre.num_total();
+        }
+        num_processed += data.machines.len() as u64;
+        if data.machines.is_empty() {
+            continue;
+        }
+        let batch_data = BatchData {
+            machines: &data.machines,
+            result_decided: DeciderResultStats::new_init_steps_max(
+                first_config,
+                result.steps_max(),
+            ),
+            machines_decided: Default::default(),
+            machines_undecided: Default::default(),
+            batch_no: data.batch_no,
+            num_batches,
+            decider_id: decider_configs[0].decider_id(),
+            config: first_config,
+            run_predecider: data_provider.requires_pre_decider_check(),
+            step_budget: first_config.step_budget_chain(),
+            steps_used: Vec::new(),
+            hints: Vec::new(),
+        };
+        result.add_result(&decide_batch_chain(batch_data, decider_configs));
+    }
+
+    let num_machines_to_process = data_provider.num_machines_to_process();
+    let factor = if num_processed == 0 {
+        0.0
+    } else {
+        num_machines_to_process as f64 / num_processed as f64
+    };
+    let scale = |n: u64| (n as f64 * factor).round() as u64;
+    let scale_duration = |d: Duration| d.mul_f64(factor);
+
+    RunEstimate {
+        num_batches,
+        num_batches_sampled: num_sample_batches,
+        num_machines_to_process,
+        num_eliminated: scale(
+            num_eliminated_data_provider + result.pre_decider_count().num_total(),
+        ),
+        stages: result
+            .decider_stats()
+            .iter()
+            .map(|s| StageEstimate {
+                decider_name: s.decider_name,
+                num_machines: scale(s.num_machines),
+                num_decided: scale(s.num_decided),
+                duration: scale_duration(s.duration),
+            })
+            .collect(),
+        num_undecided: scale(result.num_undecided()),
+        duration_data_provider: scale_duration(duration_data_provider),
+        num_threads: first_config.num_threads_effective(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::machine_spool::SPILL_THRESHOLD_DEFAULT,
+        decider::{
+            decider_halt_long::DeciderHaltLong,
+            pre_decider::{run_pre_decider_simple, run_pre_decider_strict},
+            Decider, DeciderStandard, DECIDER_HALT_ID,
+        },
+        machine_binary::MachineBinary,
+        reporter::{BatchProgress, ProgressReporter},
+        stop_conditions::StopConditions,
+    };
+
+    /// Halt decider which panics on all machines halting after 5 or more steps, which are not eliminated by
+    /// the pre-decider.
+    fn decider_halt_panic(batch_data: &mut BatchData) -> ResultUnitEndReason {
+        for machine in batch_data.machines.iter() {
+            let status = match batch_data.run_predecider {
+                PreDeciderRun::DoNotRun => MachineStatus::NoDecision,
+                PreDeciderRun::RunNormalForward => run_pre_decider_simple(machine.machine()),
+                PreDeciderRun::RunStartBRightOnly => run_pre_decider_strict(machine.machine()),
+            };
+            if status != MachineStatus::NoDecision {
+                continue;
+            }
+            if let MachineStatus::DecidedHalt(steps) | MachineStatus::DecidedHaltField(steps, _) =
+                DeciderHaltLong::decide_single_machine(machine, batch_data.config)
+            {
+                if steps >= 5 {
+                    panic!("not implemented: halts after {steps} steps");
+                }
+            }
+        }
+        DeciderHaltLong::decider_run_batch(batch_data)
+    }
+
+    #[test]
+    fn decider_panic_forwards_machines() {
+        let config = Config::builder(2).machine_limit(0).build();
+        let enumerator = || EnumeratorBinary::new(EnumeratorType::EnumeratorFullForward, &config);
+        let result_ok = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &[
+                DeciderConfig::new(
+                    &DECIDER_HALT_ID,
+                    DeciderHaltLong::decider_run_batch,
+                    &config,
+                ),
+                DeciderStandard::Cycler.decider_config(&config),
+            ],
+            enumerator(),
+            None,
+        );
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &[
+                DeciderConfig::new(&DECIDER_HALT_ID, decider_halt_panic, &config),
+                DeciderStandard::Cycler.decider_config(&config),
+            ],
+            enumerator(),
+            None,
+        );
+
+        assert_eq!(result.end_reason, EndReason::AllMachinesChecked);
+        assert_eq!(result.num_processed_total(), 9u64.pow(4));
+        let errored = result.machines_errored();
+        assert!(!errored.is_empty());
+        assert!(errored[0].message.contains("not implemented"));
+        assert_eq!(errored[0].decider_name, DECIDER_HALT_ID.name);
+        // only the machines halting after 5 or more steps panic, the cycler decides them instead
+        for e in errored.iter() {
+            let status = DeciderHaltLong::decide_single_machine(&e.machine, &config);
+            assert!(status.steps().unwrap() >= 5, "{}", e.machine);
+        }
+        assert_eq!(result.num_halt(), result_ok.num_halt());
+        assert_eq!(result.num_non_halt(), result_ok.num_non_halt());
+        assert_eq!(result.num_undecided(), result_ok.num_undecided());
+        assert_eq!(result.steps_max(), result_ok.steps_max());
+        assert!(result
+            .to_string()
+            .contains("Machines errored (decider panicked)"));
+
+        // the last decider of the chain passes the machines on as undecided
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &[DeciderConfig::new(
+                &DECIDER_HALT_ID,
+                decider_halt_panic,
+                &config,
+            )],
+            enumerator(),
+            None,
+        );
+        assert_eq!(
+            result.undecided_count().num_decider_panicked,
+            result.machines_errored().len() as u64
+        );
+        assert_eq!(result.num_processed_total(), 9u64.pow(4));
+    }
+
+    /// Keeps all reported progress for the test.
+    struct ProgressReporterRecord(std::sync::Arc<std::sync::Mutex<Vec<BatchProgress>>>);
+
+    impl ProgressReporter for ProgressReporterRecord {
+        fn report_batch(&mut self, progress: &BatchProgress) {
+            self.0.lock().unwrap().push(*progress);
+        }
+    }
+
+    #[test]
+    fn decider_progress_reported_per_batch() {
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .batch_size(1_000)
+            .build();
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let total = data_provider.num_machines_to_process();
+        let batches_total = data_provider.num_batches();
+        let record = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let reporter = Reporter::builder(total)
+            .progress_reporter(Box::new(ProgressReporterRecord(record.clone())))
+            .build();
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &[
+                DeciderStandard::Cycler.decider_config(&config),
+                DeciderStandard::Bouncer128.decider_config(&config),
+            ],
+            data_provider,
+            Some(reporter),
+        );
+
+        let record = record.lock().unwrap();
+        assert!(record.len() > 1);
+        assert_eq!(record.len(), batches_total);
+        for (i, progress) in record.iter().enumerate() {
+            assert_eq!(progress.batches_done, i + 1);
+            assert_eq!(progress.batches_total, batches_total);
+            assert_eq!(progress.machines_total, total);
+        }
+        let last = record.last().unwrap();
+        assert_eq!(last.machines_processed, result.num_processed_total());
+        assert!(last.eta.unwrap_or_default().is_zero());
+        assert!(last
+            .to_string()
+            .starts_with(&format!("Batch {batches_total} / {batches_total}:")));
+    }
+
+    #[test]
+    fn decider_pipeline_stage_step_limits() {
+        let config = Config::builder(3).machine_limit(0).build();
+        let pipeline = DeciderPipeline::new(&config)
+            .with_stage(DeciderStandard::Cycler, 20)
+            .with_stage(DeciderStandard::Bouncer128, 1_000)
+            .with_stage(DeciderStandard::Cycler, 500)
+            .with_stage(DeciderStandard::Hold, 1_000);
+        let decider_configs = pipeline.decider_configs();
+        assert_eq!(decider_configs[0].config().step_limit_decider_cycler(), 20);
+        assert_eq!(decider_configs[2].config().step_limit_decider_cycler(), 500);
+        assert_eq!(decider_configs[3].config().step_limit_decider_halt(), 1_000);
+        assert_eq!(
+            pipeline.to_string(),
+            format!(
+                "{} (20) -> {} (1{}000) -> {} (500) -> {} (1{}000)",
+                DeciderStandard::Cycler.decider_id().name,
+                DeciderStandard::Bouncer128.decider_id().name,
+                user_locale().separator(),
+                DeciderStandard::Cycler.decider_id().name,
+                DeciderStandard::Hold.decider_id().name,
+                user_locale().separator(),
+            )
+        );
+
+        let enumerator = EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = pipeline.run_data_provider(enumerator, CoreUsage::SingleCore);
+        assert_eq!(result.steps_max(), 21);
+        // the second cycler stage decides machines the first one left undecided
+        let enumerator = EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result_short = DeciderPipeline::new(&config)
+            .with_stage(DeciderStandard::Cycler, 20)
+            .with_stage(DeciderStandard::Bouncer128, 1_000)
+            .with_stage(DeciderStandard::Hold, 1_000)
+            .run_data_provider(enumerator, CoreUsage::SingleCore);
+        assert!(result.num_non_halt() > result_short.num_non_halt());
+        assert!(result.num_undecided() < result_short.num_undecided());
+    }
+
+    #[test]
+    fn decider_stages_equal_chain() {
+        let config = Config::builder(3).machine_limit(0).build();
+        let enumerator =
+            || EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let pipeline = DeciderPipeline::new(&config)
+            .with_stage(DeciderStandard::Cycler, 20)
+            .with_stage(DeciderStandard::Bouncer128, 1_000)
+            .with_stage(DeciderStandard::Hold, 1_000);
+        let result_chain = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &pipeline.decider_configs(),
+            enumerator(),
+            None,
+        );
+        // in memory and spilled to a file after the first machine
+        for spill_threshold in [SPILL_THRESHOLD_DEFAULT, 0] {
+            let result = pipeline.run_stages(enumerator(), spill_threshold);
+            assert_eq!(result.end_reason, EndReason::AllMachinesChecked);
+            assert_eq!(
+                result.num_processed_total(),
+                result_chain.num_processed_total()
+            );
+            assert_eq!(result.num_halt(), result_chain.num_halt());
+            assert_eq!(result.num_non_halt(), result_chain.num_non_halt());
+            assert_eq!(result.num_undecided(), result_chain.num_undecided());
+            assert_eq!(result.steps_max(), 21);
+        }
+    }
+
+    #[test]
+    fn pre_decider_duration_reported_separately() {
+        // full enumerator: the pre-decider runs before the first decider
+        let config = Config::builder(2).machine_limit(0).build();
+        let enumerator = EnumeratorBinary::new(EnumeratorType::EnumeratorFullForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &[DeciderStandard::Cycler.decider_config(&config)],
+            enumerator,
+            None,
+        );
+        assert_eq!(result.pre_decider_timer_decider().num_checks(), 9u64.pow(4));
+        assert_eq!(result.pre_decider_timer_data_provider().num_checks(), 0);
+        // sampled, so the duration is only an estimate
+        assert!(result.pre_decider_timer_decider().duration() > Duration::ZERO);
+
+        // reduced enumerator: the pre-decider runs in the enumerator
+        let config = Config::builder(3).machine_limit(0).build();
+        let enumerator = EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &[DeciderStandard::Cycler.decider_config(&config)],
+            enumerator,
+            None,
+        );
+        let timer = result.pre_decider_timer_data_provider();
+        assert_eq!(result.pre_decider_timer_decider().num_checks(), 0);
+        assert!(timer.num_checks() > 0 && timer.duration() > Duration::ZERO);
+        assert!(result.to_string_with_duration().contains("pre-decider"));
+    }
+
+    #[test]
+    fn stop_conditions_end_run_early() {
+        let builder = || {
+            Config::builder(3)
+                .machine_limit(0)
+                .enumerator_full_batch_size_request(1_000)
+                .step_limit_decider_cycler(10)
+        };
+        let run = |config: &Config| {
+            batch_run_decider_chain_data_provider_single_thread_reporting(
+                &[DeciderStandard::Cycler.decider_config(config)],
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, config),
+                None,
+            )
+        };
+        let result_all = run(&builder().build());
+        assert_eq!(result_all.end_reason, EndReason::AllMachinesChecked);
+
+        let config = builder()
+            .stop_conditions(StopConditions {
+                num_undecided: 1,
+                ..Default::default()
+            })
+            .build();
+        let result = run(&config);
+        assert!(
+            matches!(result.end_reason, EndReason::UndecidedLimitReached(n) if n >= 1),
+            "{}",
+            result.end_reason
+        );
+        assert!(result.num_processed_total() < result_all.num_processed_total());
+
+        let config = builder()
+            .stop_conditions(StopConditions {
+                halt_steps: 10,
+                ..Default::default()
+            })
+            .build();
+        let result = run(&config);
+        assert!(
+            matches!(result.end_reason, EndReason::HaltStepsReached(steps) if steps >= 10),
+            "{}",
+            result.end_reason
+        );
+    }
+
+    #[test]
+    fn pipelined_run_matches_single_thread() {
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .enumerator_full_batch_size_request(10_000)
+            .pipeline_queue_depth(1)
+            .build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let enumerator =
+            || EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        assert!(enumerator().num_batches() > 1);
+        let single = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &deciders,
+            enumerator(),
+            None,
+        );
+        let pipelined = batch_run_decider_chain_pipelined_reporting(&deciders, enumerator(), None);
+
+        assert_eq!(pipelined.end_reason, EndReason::AllMachinesChecked);
+        assert_eq!(
+            pipelined.num_processed_total(),
+            single.num_processed_total()
+        );
+        assert_eq!(pipelined.num_halt(), single.num_halt());
+        assert_eq!(pipelined.num_non_halt(), single.num_non_halt());
+        assert_eq!(pipelined.num_undecided(), single.num_undecided());
+        assert_eq!(pipelined.steps_max(), single.steps_max());
+        assert_eq!(
+            pipelined.pre_decider_count().num_total(),
+            single.pre_decider_count().num_total()
+        );
+    }
+
+    #[test]
+    fn estimate_matches_run_with_all_batches() {
+        let config = Config::builder(3).machine_limit(0).build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let enumerator =
+            || EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let num_batches = enumerator().num_batches();
+        let result = batch_run_decider_chain_data_provider_single_thread(&deciders, enumerator());
+
+        // sampling all batches is exact
+        let run_estimate = estimate(&deciders, enumerator(), num_batches);
+        assert_eq!(run_estimate.num_batches_sampled, num_batches);
+        assert_eq!(
+            run_estimate.num_machines_to_process,
+            result.num_processed_total()
+        );
+        assert_eq!(
+            run_estimate.num_eliminated,
+            result.pre_decider_count().num_total()
+        );
+        assert_eq!(run_estimate.num_undecided, result.num_undecided());
+        assert_eq!(run_estimate.stages.len(), 2);
+        for (stage, stats) in run_estimate.stages.iter().zip(result.decider_stats()) {
+            assert_eq!(stage.num_machines, stats.num_machines);
+            assert_eq!(stage.num_decided, stats.num_decided);
+        }
+
+        // a sample is extrapolated to all machines
+        let run_estimate = estimate(&deciders, enumerator(), 2);
+        assert_eq!(run_estimate.num_batches_sampled, 2);
+        assert!(run_estimate.stages[0].num_machines <= run_estimate.num_machines_to_process);
+        assert!(run_estimate.to_string().contains("Run estimate from"));
+    }
+
+    #[test]
+    fn routed_chain_decides_like_default_order() {
+        let mut machine = MachineBinary::try_from_standard_tm_text_format("1RB1LB_1LA1RZ").unwrap();
+        let features = MachineFeatures {
+            num_left: 2,
+            num_self_ref: 0,
+            halt_array_id: 5,
+        };
+        assert_eq!(machine.features(), features);
+        assert_eq!(machine.features_store_result(), features);
+        assert_eq!(machine.features(), features);
+        let machine = MachineBinary::try_from_standard_tm_text_format("1RB1RA_1LA---").unwrap();
+        assert_eq!(machine.features().num_self_ref, 1);
+
+        let config = Config::builder(3).machine_limit(0).build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let router = DeciderRouter::default().with_route(DeciderRoute {
+            name: "self-ref",
+            matches: |f| f.num_self_ref > 0,
+            decider_order: vec![1, 0],
+        });
+        let ab = routing_ab_test(
+            &deciders,
+            &router,
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config),
+        );
+
+        let (a, b) = (&ab.result_default, &ab.result_routed);
+        assert_eq!(b.end_reason, EndReason::AllMachinesChecked);
+        assert_eq!(a.num_processed_total(), b.num_processed_total());
+        assert_eq!(a.num_halt(), b.num_halt());
+        assert_eq!(a.num_non_halt(), b.num_non_halt());
+        assert_eq!(a.steps_max(), b.steps_max());
+        // the halt decider ran on more machines when it comes first for some of them
+        let num_machines_hold = |r: &DeciderResultStats| r.decider_stats()[1].num_machines;
+        assert!(num_machines_hold(b) > num_machines_hold(a));
+        assert!(ab.to_string().contains("Routed / Default"));
+    }
+
+    #[test]
+    fn check_invariants_every_batch() {
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .check_invariants(true)
+            .build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Bouncer128.decider_config(&config),
+        ];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result =
+            batch_run_decider_chain_threaded_data_provider_multi_thread(&deciders, data_provider);
+        assert_eq!(result.end_reason, EndReason::AllMachinesChecked);
+        assert_eq!(result.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn undecided_machines_keep_first_stage() {
+        let config = Config::builder(4)
+            .machine_limit(0)
+            .step_limit_decider_cycler(100)
+            .limit_machines_undecided(100_000)
+            .build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Bouncer128.decider_config(&config),
+        ];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider);
+        let groups = result.machines_undecided_by_stage().unwrap();
+        assert_eq!(
+            groups.iter().map(|g| g.len() as u64).sum::<u64>(),
+            result.num_undecided()
+        );
+        for group in groups.iter() {
+            let stage = group[0].undecided_stage().unwrap();
+            assert_eq!(stage.stage_no, 0);
+            assert_eq!(stage.decider_name, deciders[0].decider_id().name);
+            // the status of the machine is the status of the bouncer
+            assert_ne!(group[0].status(), stage.status);
+        }
+        // the cycler stops at the step limit for most machines
+        assert!(groups
+            .iter()
+            .any(|g| g[0].undecided_stage().unwrap().reason()
+                == crate::status::UndecidedReason::StepLimit));
+    }
+}
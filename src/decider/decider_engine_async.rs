@@ -0,0 +1,261 @@
+//! Async front-end for the decider engine, enabled with feature 'async'. \
+//! [run_async] runs the data provider and the decider chain on a blocking tokio worker and
+//! returns an [AsyncRun], which provides a Stream of the [BatchResult] of each batch and an
+//! awaitable final [RunReport]. This allows web services embedding this crate to use the
+//! engine without manual thread and channel handling.
+
+use std::time::{Duration, Instant};
+
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::{
+    config::Config,
+    data_provider::DataProvider,
+    decider::{
+        decider_engine::{check_stop_conditions, decide_batch_chain_batch_result},
+        decider_result::{
+            BatchData, BatchResult, DeciderResultStats, DurationDataProvider, EndReason,
+        },
+        decider_result_worker::FnResultWorker,
+        DeciderCaller, DeciderConfig,
+    },
+};
+
+/// Number of batch results which can be buffered before the worker waits for the stream to be read.
+const BATCH_RESULT_CHANNEL_CAPACITY: usize = 64;
+
+/// One decider of the chain for [run_async]. In contrast to [DeciderConfig] it owns its [Config],
+/// so it can be moved to the worker thread.
+#[derive(Debug, Clone)]
+pub struct AsyncDecider {
+    decider_caller: DeciderCaller<'static>,
+    fo_result_worker: Option<FnResultWorker>,
+    config: Config,
+}
+
+impl AsyncDecider {
+    pub fn new(decider_caller: DeciderCaller<'static>, config: Config) -> Self {
+        Self {
+            decider_caller,
+            fo_result_worker: None,
+            config,
+        }
+    }
+
+    pub fn new_with_worker(
+        decider_caller: DeciderCaller<'static>,
+        f_result_worker: FnResultWorker,
+        config: Config,
+    ) -> Self {
+        Self {
+            decider_caller,
+            fo_result_worker: Some(f_result_worker),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn decider_config(&self) -> DeciderConfig<'_> {
+        let mut dc = DeciderConfig::new_caller(&self.decider_caller, &self.config);
+        dc.fo_result_worker = self.fo_result_worker;
+        dc
+    }
+}
+
+/// Final result of an async run.
+#[derive(Debug)]
+pub struct RunReport {
+    /// Totals of all batches, the end_reason tells why the run ended.
+    pub result: DeciderResultStats,
+    /// Number of batches which have run through the decider chain.
+    pub num_batches_processed: usize,
+}
+
+/// Handle of a run started with [run_async].
+#[derive(Debug)]
+pub struct AsyncRun {
+    batch_results: ReceiverStream<BatchResult>,
+    worker: JoinHandle<RunReport>,
+}
+
+impl AsyncRun {
+    /// Stream of the results of each batch in the order they are decided. It ends when the run has ended.
+    pub fn batch_results(&mut self) -> &mut ReceiverStream<BatchResult> {
+        &mut self.batch_results
+    }
+
+    /// Waits for the end of the run and returns the final report. \
+    /// Batch results not yet read from the stream are dropped, the run itself continues until the end.
+    pub async fn report(self) -> RunReport {
+        // dropping the receiver lets the worker continue without waiting for the stream
+        drop(self.batch_results);
+        match self.worker.await {
+            Ok(report) => report,
+            Err(e) => {
+                let mut result = DeciderResultStats::default();
+                result.end_reason = EndReason::Error(0, format!("Async worker failed: {e}"));
+                RunReport {
+                    result,
+                    num_batches_processed: 0,
+                }
+            }
+        }
+    }
+}
+
+/// Runs the decider chain over all machines of the data provider on a blocking tokio worker. \
+/// The data provider and the deciders run on the same worker thread, one batch after the other.
+/// # Panics
+/// If called outside of a tokio runtime or if no decider is given.
+pub fn run_async(
+    deciders: Vec<AsyncDecider>,
+    data_provider: impl DataProvider + Send + 'static,
+) -> AsyncRun {
+    assert!(!deciders.is_empty(), "No decider given");
+    let (sender, receiver) = mpsc::channel(BATCH_RESULT_CHANNEL_CAPACITY);
+    let worker = tokio::task::spawn_blocking(move || {
+        run_deciders_blocking(&deciders, data_provider, sender)
+    });
+
+    AsyncRun {
+        batch_results: ReceiverStream::new(receiver),
+        worker,
+    }
+}
+
+fn run_deciders_blocking(
+    deciders: &[AsyncDecider],
+    mut data_provider: impl DataProvider,
+    sender: mpsc::Sender<BatchResult>,
+) -> RunReport {
+    let decider_configs: Vec<DeciderConfig> = deciders.iter().map(|d| d.decider_config()).collect();
+    let first_config = deciders[0].config();
+
+    let start = Instant::now();
+    let mut duration_data_provider = Duration::default();
+    let mut duration_decider = Duration::default();
+    let mut num_batches_processed = 0;
+    let mut result_main = DeciderResultStats::new(first_config);
+    for dc in decider_configs.iter() {
+        result_main.enhance_machines_un_decided(dc.config());
+    }
+
+    loop {
+        let start_gen = Instant::now();
+        let data = match data_provider.machine_batch_next() {
+            Ok(data) => data,
+            Err(e) => {
+                result_main.end_reason = EndReason::Error(0, e.to_string());
+                break;
+            }
+        };
+        duration_data_provider += start_gen.elapsed();
+        if let Some(pre) = data.pre_decider_count {
+            let result_n_states = result_main.n_states_section_mut(data.n_states);
+            result_n_states.add_pre_decider_count(&pre);
+            result_n_states.add_total(pre.num_total());
+        }
+        result_main.add_pre_decider_timer_data_provider(&data.pre_decider_timer);
+
+        if !data.machines.is_empty() {
+            let start_decider = Instant::now();
+            let batch_data = BatchData {
+                machines: &data.machines,
+                result_decided: DeciderResultStats::new_init_steps_max(
+                    first_config,
+                    result_main.steps_max(),
+                ),
+                machines_decided: Default::default(),
+                machines_undecided: Default::default(),
+                batch_no: data.batch_no,
+                num_batches: data_provider.num_batches(),
+                decider_id: decider_configs[0].decider_id(),
+                config: first_config,
+                run_predecider: data_provider.requires_pre_decider_check(),
+                step_budget: first_config.step_budget_chain(),
+                steps_used: Vec::new(),
+                hints: Vec::new(),
+            };
+            let batch_result = decide_batch_chain_batch_result(batch_data, &decider_configs);
+            duration_decider += start_decider.elapsed();
+            num_batches_processed += 1;
+            result_main.add_result(&batch_result.result_decided);
+            let end_reason = batch_result.result_decided.end_reason.clone();
+            // A closed stream is not an error, the report is still collected.
+            let _ = sender.blocking_send(batch_result);
+            match end_reason {
+                EndReason::None => {}
+                EndReason::Error(_, _)
+                | EndReason::RecordLimitDecidedReached(_)
+                | EndReason::RecordLimitUndecidedReached(_)
+                | EndReason::StopRequested(_, _) => {
+                    result_main.end_reason = end_reason;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        match data.end_reason {
+            EndReason::None => {}
+            EndReason::IsLastBatch => {
+                result_main.end_reason = EndReason::AllMachinesChecked;
+                break;
+            }
+            end_reason => {
+                result_main.end_reason = end_reason;
+                break;
+            }
+        }
+        if check_stop_conditions(first_config, &mut result_main, start) {
+            break;
+        }
+    }
+
+    result_main.duration = DurationDataProvider {
+        duration_data_provider,
+        duration_decider,
+        duration_total: start.elapsed(),
+    };
+
+    RunReport {
+        result: result_main,
+        num_batches_processed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_stream::StreamExt;
+
+    use super::*;
+    use crate::{
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::DeciderStandard,
+    };
+
+    #[tokio::test]
+    async fn run_async_bb3_streams_batches() {
+        let config = Config::builder(3).build();
+        let deciders = vec![
+            AsyncDecider::new(DeciderStandard::Cycler.decider_caller(), config.clone()),
+            AsyncDecider::new(DeciderStandard::Hold.decider_caller(), config.clone()),
+        ];
+        let enumerator = EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let mut run = run_async(deciders, enumerator);
+
+        let mut num_batch_results = 0;
+        while let Some(batch_result) = run.batch_results().next().await {
+            assert!(batch_result.batch_no < batch_result.num_batches);
+            num_batch_results += 1;
+        }
+        let report = run.report().await;
+        assert_eq!(report.num_batches_processed, num_batch_results);
+        assert_eq!(report.result.end_reason, EndReason::AllMachinesChecked);
+        assert_eq!(report.result.steps_max(), 21);
+    }
+}
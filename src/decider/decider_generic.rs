@@ -0,0 +1,191 @@
+//! Hold and cycler decider for machines with more than two symbols ([MachineGeneric]), e.g. BB(2,3), BB(2,4)
+//! or BB(3,3). \
+//! The standard deciders work on [MachineId](crate::machine_binary::MachineId) with the symbols 0 and 1 packed
+//! into bits, so they cannot run these machines. This decider simulates the machine on a [TapeSymbol] and
+//! decides in one run:
+//! - Hold: The machine reaches a halt transition within the step limit.
+//! - Cycler: The configuration (state, head position and tape) repeats. The configuration is stored at the steps
+//!   1, 2, 4, 8 etc. and compared to the configuration after each step (Brent's cycle detection), so a cycler
+//!   is found at the latest when twice the steps until the end of its first cycle are run.
+//!
+//! The step limit is [Config::step_limit_decider_cycler], the tape size limit [Config::tape_size_limit_cells]. \
+//! The machines can be enumerated with [EnumeratorGeneric](crate::data_provider::enumerator_generic::EnumeratorGeneric).
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config, decider::decider_generic::DeciderGeneric, machine_generic::MachineGeneric,
+//!     status::MachineStatus,
+//! };
+//! // BB(2,3) champion
+//! let machine = MachineGeneric::try_from_standard_tm_text_format("1RB2LB1RZ_2LA2RB1LB").unwrap();
+//! let status = DeciderGeneric::decide_single_machine(&machine, &Config::new_default(2));
+//! assert_eq!(status, MachineStatus::DecidedHalt(38));
+//! ```
+
+use std::fmt::Display;
+
+use crate::{
+    config::{Config, StepBig, StepSmall},
+    machine_generic::{MachineGeneric, StateType, SymbolType, A},
+    status::{MachineStatus, NonHaltReason, UndecidedReason},
+    tape::tape_symbol::TapeSymbol,
+};
+
+/// Configuration stored for the cycle check.
+#[derive(Debug, Default)]
+struct Snapshot {
+    step_no: StepBig,
+    state: StateType,
+    head_pos: i64,
+    /// Position of the first used cell, see [TapeSymbol::used_cells].
+    used_start: i64,
+    used_cells: Vec<SymbolType>,
+}
+
+#[derive(Debug)]
+pub struct DeciderGeneric {
+    tape: TapeSymbol,
+    step_limit: StepBig,
+    snapshot: Snapshot,
+}
+
+impl DeciderGeneric {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            tape: TapeSymbol::new(config),
+            step_limit: config.step_limit_decider_cycler() as StepBig,
+            snapshot: Snapshot::default(),
+        }
+    }
+
+    pub fn decide_single_machine(machine: &MachineGeneric, config: &Config) -> MachineStatus {
+        let mut d = Self::new(config);
+        d.decide_machine(machine)
+    }
+
+    /// Decides all machines, returning the status of each machine in the order of the machines.
+    pub fn decide_machines(machines: &[MachineGeneric], config: &Config) -> Vec<MachineStatus> {
+        let mut d = Self::new(config);
+        machines.iter().map(|m| d.decide_machine(m)).collect()
+    }
+
+    pub fn decide_machine(&mut self, machine: &MachineGeneric) -> MachineStatus {
+        self.tape.clear();
+        let mut state = A;
+        self.save_snapshot(0, state);
+        for step_no in 1..=self.step_limit {
+            let tr = machine.transition_for_state_symbol(state, self.tape.get_current_symbol());
+            if tr.is_halt() {
+                self.tape.write_last_symbol(tr);
+                return MachineStatus::DecidedHalt(step_no);
+            }
+            if !self.tape.update_tape_single_step(tr) {
+                return MachineStatus::Undecided(
+                    UndecidedReason::TapeSizeLimit,
+                    step_no,
+                    self.tape.tape_size_cells(),
+                );
+            }
+            state = tr.state_next;
+
+            if self.is_snapshot_config(state) {
+                return MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(
+                    step_no as StepSmall,
+                    (step_no - self.snapshot.step_no) as StepSmall,
+                ));
+            }
+            if step_no.is_power_of_two() {
+                self.save_snapshot(step_no, state);
+            }
+        }
+
+        MachineStatus::Undecided(
+            UndecidedReason::StepLimit,
+            self.step_limit,
+            self.tape.tape_size_cells(),
+        )
+    }
+
+    /// Returns true if the configuration is identical to the stored one. The tape is only compared if
+    /// the state and head position match.
+    fn is_snapshot_config(&self, state: StateType) -> bool {
+        if state != self.snapshot.state || self.tape.head_pos() != self.snapshot.head_pos {
+            return false;
+        }
+        let (used_start, used_cells) = self.tape.used_cells();
+        used_start == self.snapshot.used_start && used_cells == self.snapshot.used_cells
+    }
+
+    fn save_snapshot(&mut self, step_no: StepBig, state: StateType) {
+        let (used_start, used_cells) = self.tape.used_cells();
+        self.snapshot.step_no = step_no;
+        self.snapshot.state = state;
+        self.snapshot.head_pos = self.tape.head_pos();
+        self.snapshot.used_start = used_start;
+        self.snapshot.used_cells.clear();
+        self.snapshot.used_cells.extend_from_slice(used_cells);
+    }
+
+    pub fn step_limit(&self) -> StepBig {
+        self.step_limit
+    }
+
+    pub fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.step_limit = step_limit;
+    }
+
+    /// Tape of the last decided machine, e.g. to count the symbols written by a halting machine.
+    pub fn tape(&self) -> &TapeSymbol {
+        &self.tape
+    }
+}
+
+impl Display for DeciderGeneric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Decider Generic (hold and cycler), step limit {}",
+            self.step_limit
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_provider::enumerator_generic::EnumeratorGeneric;
+
+    #[test]
+    fn decider_generic_bb2_bb2x3_max() {
+        // (states, symbols, max steps)
+        for (n_states, n_symbols, steps_max) in [(2, 2, 6), (2, 3, 38)] {
+            let config = Config::builder(n_states)
+                .step_limit_decider_cycler(500)
+                .build();
+            let mut decider = DeciderGeneric::new(&config);
+            let (mut num_halt, mut num_cycler, mut num_undecided) = (0, 0, 0);
+            let mut steps = 0;
+            for machine in EnumeratorGeneric::new(n_states, n_symbols) {
+                match decider.decide_machine(&machine) {
+                    MachineStatus::DecidedHalt(s) => {
+                        num_halt += 1;
+                        steps = steps.max(s);
+                    }
+                    MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(_, _)) => num_cycler += 1,
+                    _ => num_undecided += 1,
+                }
+            }
+            assert_eq!(steps, steps_max);
+            assert!(num_halt > 0 && num_cycler > 0 && num_undecided > 0);
+        }
+
+        // cycle of 4 steps from step 6 on, found when step 12 repeats the configuration stored at step 8
+        let machine =
+            MachineGeneric::try_from_standard_tm_text_format("1RB1RA2LB_2LA1LA---").unwrap();
+        let status = DeciderGeneric::decide_single_machine(&machine, &Config::new_default(2));
+        assert_eq!(
+            status,
+            MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(12, 4))
+        );
+    }
+}
@@ -111,8 +111,11 @@
 // Step  4272 D0 1LA: 00000000000000000000000000000000_000000000000000000000000_00000000*01111100_100100100100100100100100_10010010010010010010010010010010
 use std::fmt::Display;
 
-use crate::machine_binary::MachineId;
-use crate::{config::Config, status::MachineStatus};
+use crate::machine_binary::{MachineBinary, MachineId};
+use crate::{
+    config::{Config, StepBig},
+    status::MachineStatus,
+};
 use crate::{
     decider::{
         self,
@@ -121,6 +124,7 @@ use crate::{
         Decider, DECIDER_HALT_ID,
     },
     machine_binary::NotableMachineBinary,
+    tape::tape_long_shifted::TapeLongShifted,
 };
 
 /// This decider runs on a 128-Bit number and moves data out to a long tape (Vec). \
@@ -153,10 +157,18 @@ impl DeciderHaltLong {
         }
     }
 
-    fn decide_machine_with_self_referencing_transition(&mut self) -> MachineStatus {
+    /// Returns the tape of the last decided machine, e.g. to output the final tape of a halting machine.
+    pub fn tape(&self) -> &TapeLongShifted {
+        &self.data.tape
+    }
+
+    fn decide_machine_with_self_referencing_transition(
+        &mut self,
+        machine: &MachineBinary,
+    ) -> MachineStatus {
         // loop over transitions to write tape
         loop {
-            if self.data.next_transition() {
+            if self.data.next_transition(machine) {
                 // is done
                 return self.data.status;
             }
@@ -170,10 +182,13 @@ impl DeciderHaltLong {
     /// Returns the [MachineStatus:DecidedHalt] with steps if steps were found within limits of tape and max steps. \
     /// This version has a long tape, so it is not restricted to the 128 bit range.
     /// This is not using the self reference speed-up and should only be used if those would mess up the tests.
-    fn decide_machine_without_self_referencing_transitions(&mut self) -> MachineStatus {
+    fn decide_machine_without_self_referencing_transitions(
+        &mut self,
+        machine: &MachineBinary,
+    ) -> MachineStatus {
         // loop over transitions to write tape
         loop {
-            if self.data.next_transition() {
+            if self.data.next_transition(machine) {
                 // is done
                 return self.data.status;
             }
@@ -192,24 +207,20 @@ impl Decider for DeciderHaltLong {
 
     fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
         self.data.clear();
-        self.data.transition_table = *machine.machine();
 
         #[cfg(feature = "enable_html_reports")]
         self.data
             .write_html_file_start(Self::decider_id(), &machine);
 
         #[cfg(feature = "without_self_ref_acceleration")]
-        let result_status = self.decide_machine_without_self_referencing_transitions();
+        let result_status =
+            self.decide_machine_without_self_referencing_transitions(machine.machine());
 
         #[cfg(not(feature = "without_self_ref_acceleration"))]
-        let result_status = if self
-            .data
-            .transition_table
-            .has_self_referencing_transition_store_result()
-        {
-            self.decide_machine_with_self_referencing_transition()
+        let result_status = if machine.machine().has_self_referencing_transition() {
+            self.decide_machine_with_self_referencing_transition(machine.machine())
         } else {
-            self.decide_machine_without_self_referencing_transitions()
+            self.decide_machine_without_self_referencing_transitions(machine.machine())
         };
 
         #[cfg(feature = "enable_html_reports")]
@@ -223,6 +234,33 @@ impl Decider for DeciderHaltLong {
         d.decide_machine(machine)
     }
 
+    fn step_limit(&self) -> StepBig {
+        self.data.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.data.step_limit = step_limit;
+    }
+
+    #[cfg(feature = "enable_html_reports")]
+    fn html_write_failures(&self) -> Option<&crate::html::HtmlWriteFailures> {
+        self.data.html_writer.as_ref().map(|w| w.write_failures())
+    }
+
+    fn tape_memory_bytes(&self) -> Option<usize> {
+        Some(self.data.tape.tape_long_bytes())
+    }
+
+    fn tape_size_limit_u32_blocks(&self) -> u32 {
+        self.data.tape.tape_size_limit_u32_blocks()
+    }
+
+    fn set_tape_size_limit_u32_blocks(&mut self, tape_size_limit_u32_blocks: u32) {
+        self.data
+            .tape
+            .set_tape_size_limit_u32_blocks(tape_size_limit_u32_blocks);
+    }
+
     fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
         let decider = Self::new(batch_data.config);
         decider::decider_generic_run_batch(decider, batch_data)
@@ -289,6 +327,204 @@ mod tests {
         assert_eq!(full, MachineStatus::DecidedHaltDetail(107, 128, 12));
     }
 
+    #[test]
+    fn decider_halt_long_html_write_failure_does_not_stop_decider() {
+        use crate::output_layout::OutputLayout;
+
+        // a file as root directory cannot hold the html directories
+        let root = std::env::temp_dir().join("bb_challenge_html_write_failure_test");
+        std::fs::write(&root, "x").unwrap();
+        let config = Config::builder(4)
+            .write_html_file(true)
+            .output_layout(OutputLayout::new(&root))
+            .build();
+
+        let machine = NotableMachineBinary::BB4Max.machine_id();
+        let status_expected =
+            DeciderHaltLong::decide_single_machine(&machine, &Config::new_default(4));
+        let mut decider = DeciderHaltLong::new(&config);
+        assert_eq!(decider.decide_machine(&machine), status_expected);
+        assert_eq!(decider.decide_machine(&machine), status_expected);
+        let write_failures = decider.html_write_failures().unwrap();
+        assert_eq!(write_failures.num_machines, 2);
+        assert!(write_failures.first_error.is_some());
+        std::fs::remove_file(&root).unwrap();
+    }
+
+    #[test]
+    fn decider_halt_long_step_budget_shared_in_chain() {
+        use crate::{
+            decider::{
+                decider_engine::decide_batch_chain, decider_result::DeciderResultStats,
+                pre_decider::PreDeciderRun, DeciderStandard,
+            },
+            status::UndecidedReason,
+        };
+
+        // BB4 Max halts after 107 steps, which exceeds the budget of 100 steps shared by both deciders
+        let machines = [NotableMachineBinary::BB4Max.machine_id()];
+        let config = Config::builder(4)
+            .step_limit_decider_cycler(60)
+            .step_limit_decider_halt(1_000)
+            .step_budget_chain(100)
+            .limit_machines_undecided(10)
+            .build();
+        let decider_configs = vec![
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let batch_data = BatchData {
+            machines: &machines,
+            result_decided: DeciderResultStats::new(&config),
+            machines_decided: Default::default(),
+            machines_undecided: Default::default(),
+            batch_no: 0,
+            num_batches: 1,
+            decider_id: decider_configs[0].decider_id(),
+            run_predecider: PreDeciderRun::DoNotRun,
+            config: &config,
+            step_budget: config.step_budget_chain(),
+            steps_used: Vec::new(),
+            hints: Vec::new(),
+        };
+        let result = decide_batch_chain(batch_data, &decider_configs);
+        assert_eq!(result.num_undecided(), 1);
+        assert_eq!(result.num_step_budget_exhausted(), 1);
+        let undecided = result.machines_undecided().unwrap();
+        assert!(matches!(
+            undecided[0].status(),
+            MachineStatus::Undecided(UndecidedReason::StepLimit, 100, _)
+        ));
+    }
+
+    #[test]
+    fn decider_halt_long_tape_limit_escalation() {
+        use crate::{
+            decider::{
+                decider_result::{DeciderResultStats, WarningKind},
+                pre_decider::PreDeciderRun,
+                DECIDER_HALT_ID,
+            },
+            status::UndecidedReason,
+        };
+
+        // A0 1RA runs right forever, so the tape limit is always reached
+        let machines = [MachineId::try_from("1RA0LB_1LA---").unwrap()];
+        let config = Config::builder(2)
+            .step_limit_decider_halt(100_000)
+            .tape_size_limit_cells(3_200)
+            .tape_size_limit_escalation_cells(12_800)
+            .build();
+        let mut batch_data = BatchData {
+            machines: &machines,
+            result_decided: DeciderResultStats::new(&config),
+            machines_decided: Default::default(),
+            machines_undecided: Default::default(),
+            batch_no: 0,
+            num_batches: 1,
+            decider_id: &DECIDER_HALT_ID,
+            run_predecider: PreDeciderRun::DoNotRun,
+            config: &config,
+            step_budget: 0,
+            steps_used: Vec::new(),
+            hints: Vec::new(),
+        };
+        DeciderHaltLong::decider_run_batch(&mut batch_data).unwrap();
+        // 100 -> 200 -> 400 blocks
+        assert_eq!(batch_data.result_decided.num_tape_limit_escalations(), 2);
+        let status = batch_data.machines_undecided.states[0];
+        match status {
+            MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, _, tape_size) => {
+                assert!(tape_size > 6_400)
+            }
+            _ => panic!("Unexpected status {status}"),
+        }
+        // the cap is reached, which is recorded as warning instead of being printed
+        let warnings = batch_data.result_decided.warnings();
+        assert_eq!(warnings.count(WarningKind::TapeLimitEscalationCap), 1);
+        assert_eq!(
+            warnings.iter().next().unwrap().first_message,
+            "1RA0LB_1LA---: tape size limit of 400 u32 blocks reached"
+        );
+        assert!(batch_data
+            .result_decided
+            .to_string()
+            .contains("Warning TapeLimitEscalationCap: 1 times"));
+
+        // the escalation runs are charged to the step budget
+        let mut batch_data = BatchData {
+            result_decided: DeciderResultStats::new(&config),
+            machines_decided: Default::default(),
+            machines_undecided: Default::default(),
+            step_budget: 5_000,
+            ..batch_data
+        };
+        DeciderHaltLong::decider_run_batch(&mut batch_data).unwrap();
+        assert_eq!(batch_data.result_decided.num_tape_limit_escalations(), 1);
+        assert_eq!(batch_data.result_decided.num_step_budget_exhausted(), 1);
+        assert!(matches!(
+            batch_data.machines_undecided.states[0],
+            MachineStatus::Undecided(UndecidedReason::StepLimit, 5_000, _)
+        ));
+    }
+
+    #[test]
+    fn decider_halt_long_tape_memory_budget() {
+        use crate::decider::{
+            decider_result::DeciderResultStats, pre_decider::PreDeciderRun, DECIDER_HALT_ID,
+        };
+
+        // A0 1RA runs right forever, so the tape limit is always reached
+        let machines = [MachineId::try_from("1RA0LB_1LA---").unwrap()];
+        // the first escalation to 200 blocks needs 800 bytes, the second to 400 blocks 1600 bytes
+        let config = Config::builder(2)
+            .step_limit_decider_halt(100_000)
+            .tape_size_limit_cells(3_200)
+            .tape_size_limit_escalation_cells(12_800)
+            .tape_memory_budget_bytes(1_000)
+            .build();
+        let run = |config: &Config| {
+            let mut batch_data = BatchData {
+                machines: &machines,
+                result_decided: DeciderResultStats::new(config),
+                machines_decided: Default::default(),
+                machines_undecided: Default::default(),
+                batch_no: 0,
+                num_batches: 1,
+                decider_id: &DECIDER_HALT_ID,
+                run_predecider: PreDeciderRun::DoNotRun,
+                config,
+                step_budget: 0,
+                steps_used: Vec::new(),
+                hints: Vec::new(),
+            };
+            DeciderHaltLong::decider_run_batch(&mut batch_data).unwrap();
+            batch_data.result_decided
+        };
+        let result = run(&config);
+        assert_eq!(result.num_tape_limit_escalations(), 1);
+        assert_eq!(result.num_tape_memory_budget_skips(), 1);
+        assert!(result.to_string().contains("Tape Budget used"));
+        assert_eq!(config.tape_memory_budget().reserved_bytes(), 0);
+
+        // another worker holds a part of the budget, which is shared by the clones of the config
+        let config_worker = config.clone();
+        assert!(config_worker.tape_memory_budget().try_reserve(500));
+        let result = run(&config);
+        assert_eq!(result.num_tape_limit_escalations(), 0);
+        assert_eq!(result.num_tape_memory_budget_skips(), 1);
+        config_worker.tape_memory_budget().release(500);
+        assert_eq!(config.tape_memory_budget().reserved_bytes(), 0);
+
+        // unlimited
+        let config_unlimited = Config::builder_from_config(&config)
+            .tape_memory_budget_bytes(0)
+            .build();
+        let result = run(&config_unlimited);
+        assert_eq!(result.num_tape_limit_escalations(), 2);
+        assert_eq!(result.num_tape_memory_budget_skips(), 0);
+    }
+
     #[test]
     /// This test runs 50 mio steps, so turn off default = ["bb_debug"].
     fn decider_halt_long_applies_bb5_max() {
@@ -1,32 +1,96 @@
-//! This decider just runs all steps until either a halt or limit is encountered. \
-//! This is just a test for a macro long tape and later speed up. \
-//! Currently this does not work correctly.
+//! This decider runs the machine until either a halt or limit is encountered. \
+//! It uses the block simulation of the [MacroMachine], which passes whole runs of identical blocks in one
+//! macro step, so long sweeps of bouncers and counters are accelerated. The block size is taken from
+//! [Config::macro_block_size] or detected for each machine. \
+//! If an html trace is written, the machine is run step by step on the compact tape instead, as the trace
+//! shows each step.
+//!
+//! [Config::macro_block_size]: crate::config::Config::macro_block_size
 
 use std::fmt::Display;
 
 use crate::{
-    config::Config,
+    config::{Config, StepBig},
     decider::{
         self,
         decider_data_macro::DeciderDataMacro,
         decider_result::{BatchData, ResultUnitEndReason},
         Decider, DECIDER_HALT_MACRO_ID,
     },
-    machine_binary::{MachineId, NotableMachineBinary},
-    status::MachineStatus,
+    machine_binary::{MachineBinary, MachineId, NotableMachineBinary},
+    macro_machine::{MacroMachine, MacroRunResult},
+    status::{MachineStatus, UndecidedReason},
 };
 
 pub struct DeciderHaltMacro {
     data: DeciderDataMacro,
+    /// Block size of the macro machine, 0: detected for each machine.
+    block_size: usize,
+    /// Number of runs on the tape of the last macro machine.
+    num_macro_runs: usize,
 }
 
 impl DeciderHaltMacro {
     pub fn new(config: &Config) -> Self {
         Self {
             data: DeciderDataMacro::new(config),
+            block_size: config.macro_block_size(),
+            num_macro_runs: 0,
         }
     }
 
+    /// Returns the MachineStatus:Hold with steps if steps were found within limits of tape and max steps. \
+    /// Runs the macro machine, see [MacroMachine].
+    fn decide_machine_macro(&mut self, machine: &MachineBinary) -> MachineStatus {
+        let block_size = if self.block_size == 0 {
+            MacroMachine::detect_block_size(machine)
+        } else {
+            self.block_size
+        };
+        let mut mm = MacroMachine::new(machine, block_size);
+        let step_limit = self.data.step_limit as u64;
+        let tape_size_limit = self.data.tape.tape_size_limit_cells() as u64;
+        let status = loop {
+            match mm.step() {
+                MacroRunResult::Running => {
+                    if mm.steps() > step_limit {
+                        break MachineStatus::Undecided(
+                            UndecidedReason::StepLimit,
+                            step_limit as StepBig,
+                            mm.tape_size_cells() as u32,
+                        );
+                    }
+                    if mm.tape_size_cells() > tape_size_limit {
+                        break MachineStatus::Undecided(
+                            UndecidedReason::TapeSizeLimit,
+                            mm.steps() as StepBig,
+                            mm.tape_size_cells() as u32,
+                        );
+                    }
+                }
+                MacroRunResult::Halt(steps) if steps <= step_limit => {
+                    break MachineStatus::DecidedHalt(steps as StepBig)
+                }
+                MacroRunResult::Halt(_) | MacroRunResult::StepLimit => {
+                    break MachineStatus::Undecided(
+                        UndecidedReason::StepLimit,
+                        step_limit as StepBig,
+                        mm.tape_size_cells() as u32,
+                    )
+                }
+                MacroRunResult::NonHalt => {
+                    break MachineStatus::Undecided(
+                        UndecidedReason::DeciderNoResult,
+                        mm.steps() as StepBig,
+                        mm.tape_size_cells() as u32,
+                    )
+                }
+            }
+        };
+        self.num_macro_runs = mm.num_runs();
+        status
+    }
+
     //     fn decide_machine_with_self_referencing_transition(&mut self) -> MachineStatus {
     //         // loop over transitions to write tape
     //         loop {
@@ -46,10 +110,13 @@ impl DeciderHaltMacro {
     /// Returns the MachineStatus:Hold with steps if steps were found within limits of tape and max steps. \
     /// This version has a long tape, so it is not restricted to the 128 bit range.
     /// This is not using the self reference speed-up and should only be used if those would mess up the tests.
-    fn decide_machine_without_self_referencing_transitions(&mut self) -> MachineStatus {
+    fn decide_machine_without_self_referencing_transitions(
+        &mut self,
+        machine: &MachineBinary,
+    ) -> MachineStatus {
         // loop over transitions to write tape
         loop {
-            if self.data.next_transition() {
+            if self.data.next_transition(machine) {
                 // is done
                 return self.data.status;
             }
@@ -68,14 +135,22 @@ impl Decider for DeciderHaltMacro {
 
     // TODO counter: longest loop
     fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
+        #[cfg(feature = "enable_html_reports")]
+        let is_html = self.data.html_writer.is_some();
+        #[cfg(not(feature = "enable_html_reports"))]
+        let is_html = false;
+        if !is_html {
+            return self.decide_machine_macro(machine.machine());
+        }
+
         self.data.clear();
-        self.data.transition_table = *machine.machine();
 
         #[cfg(feature = "enable_html_reports")]
         self.data.write_html_file_start(Self::decider_id(), machine);
 
         #[cfg(feature = "without_self_ref_acceleration")]
-        let result_status = self.decide_machine_without_self_referencing_transitions();
+        let result_status =
+            self.decide_machine_without_self_referencing_transitions(machine.machine());
 
         #[cfg(not(feature = "without_self_ref_acceleration"))]
         // let result_status = if self
@@ -86,7 +161,8 @@ impl Decider for DeciderHaltMacro {
         // TODO self-ref code
         //     self.decide_machine_with_self_referencing_transition()
         // } else {
-        let result_status = self.decide_machine_without_self_referencing_transitions();
+        let result_status =
+            self.decide_machine_without_self_referencing_transitions(machine.machine());
         // };
 
         #[cfg(feature = "enable_html_reports")]
@@ -100,6 +176,36 @@ impl Decider for DeciderHaltMacro {
         d.decide_machine(machine)
     }
 
+    fn step_limit(&self) -> StepBig {
+        self.data.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.data.step_limit = step_limit;
+    }
+
+    #[cfg(feature = "enable_html_reports")]
+    fn html_write_failures(&self) -> Option<&crate::html::HtmlWriteFailures> {
+        self.data.html_writer.as_ref().map(|w| w.write_failures())
+    }
+
+    fn tape_memory_bytes(&self) -> Option<usize> {
+        Some(
+            self.data.tape.patterns_bytes()
+                + self.num_macro_runs * std::mem::size_of::<(u16, u64)>(),
+        )
+    }
+
+    fn tape_size_limit_u32_blocks(&self) -> u32 {
+        self.data.tape.tape_size_limit_cells() / 32
+    }
+
+    fn set_tape_size_limit_u32_blocks(&mut self, tape_size_limit_u32_blocks: u32) {
+        self.data
+            .tape
+            .set_tape_size_limit_cells(tape_size_limit_u32_blocks * 32);
+    }
+
     fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
         let decider = Self::new(batch_data.config);
         decider::decider_generic_run_batch(decider, batch_data)
@@ -185,6 +291,13 @@ mod tests {
         let check_result = DeciderHaltMacro::decide_single_machine(&machine, &config);
         // println!("{}", check_result);
         assert_eq!(check_result, MachineStatus::DecidedHalt(47_176_870));
+
+        // macro machine
+        let config = Config::builder(5)
+            .step_limit_decider_halt(50_000_000)
+            .build();
+        let check_result = DeciderHaltMacro::decide_single_machine(&machine, &config);
+        assert_eq!(check_result, MachineStatus::DecidedHalt(47_176_870));
         // assert_eq!(
         //     check_result,
         //     MachineStatus::Undecided(crate::status::UndecidedReason::TapeSizeLimit, 1337, 23)
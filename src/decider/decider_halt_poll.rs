@@ -0,0 +1,129 @@
+//! Time-sliced halt decider, which runs a machine in slices of steps on the loop of [DeciderData128]. \
+//! This allows e.g. a UI thread (GUI, WASM) to interleave rendering with long decisions without real threads.
+//! The verdict is the same as of the [halt decider](crate::decider::decider_halt_long::DeciderHaltLong)
+//! within the 128-bit tape.
+
+#[cfg(feature = "enable_html_reports")]
+use crate::decider::{decider_halt_long::DeciderHaltLong, Decider};
+use crate::{
+    config::{Config, StepBig},
+    decider::decider_data_128::{DeciderData128, PollStatus},
+    machine_binary::{MachineBinary, MachineId},
+    status::MachineStatus,
+};
+
+#[derive(Debug)]
+pub struct DeciderHaltPoll {
+    data: DeciderData128,
+    /// Machine of the time-sliced run, see [Self::start_machine].
+    machine: MachineBinary,
+}
+
+impl DeciderHaltPoll {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            data: DeciderData128::new(config),
+            machine: MachineBinary::default(),
+        }
+    }
+
+    /// Sets the machine for a time-sliced run, see [Self::poll].
+    pub fn start_machine(&mut self, machine: &MachineId) {
+        self.data.start_machine();
+        self.machine = *machine.machine();
+
+        #[cfg(feature = "enable_html_reports")]
+        self.data
+            .write_html_file_start(DeciderHaltLong::decider_id(), machine);
+    }
+
+    /// Runs the machine set with [Self::start_machine] for about max_steps steps. \
+    /// This is the time-sliced version of decide_machine, which returns the same status once Done.
+    pub fn poll(&mut self, max_steps: StepBig) -> PollStatus {
+        #[cfg(feature = "enable_html_reports")]
+        let was_running = self.data.status == MachineStatus::NoDecision;
+        let poll_status = self.data.poll(&self.machine, max_steps);
+
+        #[cfg(feature = "enable_html_reports")]
+        if was_running && poll_status != PollStatus::Pending {
+            self.data.write_html_file_end();
+        }
+
+        poll_status
+    }
+
+    /// Runs the machine in slices of max_steps until it is decided or a limit is reached.
+    pub fn decide_machine_polled(
+        &mut self,
+        machine: &MachineId,
+        max_steps: StepBig,
+    ) -> MachineStatus {
+        self.start_machine(machine);
+        loop {
+            if let PollStatus::Done(status) = self.poll(max_steps) {
+                return status;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decider::{decider_halt_long::DeciderHaltLong, Decider},
+        machine_binary::NotableMachineBinary,
+    };
+
+    #[test]
+    fn decider_halt_poll_equals_decider_halt() {
+        let config = Config::new_default(4);
+        for notable in [NotableMachineBinary::BB3Max, NotableMachineBinary::BB4Max] {
+            let machine = notable.machine_id();
+            let expected = DeciderHaltLong::decide_single_machine(&machine, &config);
+
+            let mut decider = DeciderHaltPoll::new(&config);
+            decider.start_machine(&machine);
+            let mut num_polls = 0;
+            let status = loop {
+                num_polls += 1;
+                if let PollStatus::Done(status) = decider.poll(10) {
+                    break status;
+                }
+            };
+            assert!(num_polls > 1);
+            assert_eq!(status, expected, "{machine}");
+            assert_eq!(decider.poll(10), PollStatus::Done(status));
+
+            // restart with the same decider
+            assert_eq!(decider.decide_machine_polled(&machine, 1000), status);
+        }
+    }
+
+    #[test]
+    fn decider_halt_poll_single_steps_equal_long_run() {
+        let config_4 = Config::new_default(4);
+        let config_5 = Config::new_default(5);
+        let config_5_step_limit = Config::builder(5).step_limit_decider_halt(50).build();
+        for (notable, config) in [
+            (NotableMachineBinary::BB4Max, &config_4),
+            // the self-referencing speed-up jumps over several steps within one poll
+            (NotableMachineBinary::BB5Max, &config_5_step_limit),
+            // ends at the bound of the 128-bit tape
+            (NotableMachineBinary::BB5Max, &config_5),
+        ] {
+            let machine = notable.machine_id();
+            let mut decider = DeciderHaltPoll::new(config);
+            let status_long = decider.decide_machine_polled(&machine, StepBig::MAX);
+            assert_ne!(status_long, MachineStatus::NoDecision);
+            assert_eq!(
+                decider.decide_machine_polled(&machine, 1),
+                status_long,
+                "{machine}"
+            );
+        }
+        assert!(NotableMachineBinary::BB5Max
+            .machine()
+            .has_self_referencing_transition());
+    }
+}
@@ -0,0 +1,149 @@
+//! Hints which a decider passes along with an undecided machine to the next decider of the chain, so the
+//! next decider does not need to rediscover the structure of the machine. \
+//! The cycler records the whole run, so it knows the head bounds and when the machine starts to sweep
+//! the tape from one end to the other. The bouncer uses this to skip its warm-up phase: The tape records
+//! before the first sweep are only the irregular start of the machine and are not compared. \
+//! Hints are only passed if [Config::decider_hints](crate::config::Config::decider_hints) is set.
+//! They are a performance aid, a decider must not rely on them for its decision.
+
+use crate::{config::StepBig, transition_binary::DirectionType};
+
+/// Structure of the run of an undecided machine, see [module documentation](self).
+/// The default (all 0) is a hint without information.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DeciderHint {
+    /// Steps the decider simulated.
+    pub steps: StepBig,
+    /// Leftmost head position relative to the start cell.
+    pub head_pos_min: i64,
+    /// Rightmost head position relative to the start cell.
+    pub head_pos_max: i64,
+    /// Step in which the head first turned from a new bound on one side to a new bound on the other side,
+    /// 0 if this did not happen.
+    pub sweep_start_step: StepBig,
+    /// Steps of the last full sweep (left and right bound) as candidate period, 0 if not known.
+    pub sweep_period: StepBig,
+}
+
+impl DeciderHint {
+    /// Creates the hint from the directions of the steps of a run (-1 left, 1 right).
+    pub fn from_directions(directions: impl Iterator<Item = DirectionType>) -> Self {
+        let mut hint = Self::default();
+        let mut head_pos = 0;
+        // side of the last new bound: -1 left, 1 right
+        let mut bound_side = 0;
+        // steps of the last three turns, the oldest first
+        let mut turns = [0; 3];
+        let mut num_turns = 0;
+        for (i, direction) in directions.enumerate() {
+            head_pos += direction as i64;
+            let side = if head_pos > hint.head_pos_max {
+                hint.head_pos_max = head_pos;
+                1
+            } else if head_pos < hint.head_pos_min {
+                hint.head_pos_min = head_pos;
+                -1
+            } else {
+                0
+            };
+            if side != 0 {
+                if bound_side == -side {
+                    let step_no = i as StepBig + 1;
+                    if num_turns == 0 {
+                        hint.sweep_start_step = step_no;
+                    }
+                    turns.rotate_left(1);
+                    turns[2] = step_no;
+                    num_turns += 1;
+                }
+                bound_side = side;
+            }
+            hint.steps = i as StepBig + 1;
+        }
+        if num_turns >= 3 {
+            hint.sweep_period = turns[2] - turns[0];
+        }
+
+        hint
+    }
+
+    /// True if the hint holds no information.
+    pub fn is_empty(&self) -> bool {
+        self.steps == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_bouncer_128::DeciderBouncer128, decider_cycler::DeciderCycler,
+            decider_engine::batch_run_decider_chain_data_provider_single_thread, Decider,
+            DeciderStandard,
+        },
+        machine_binary::MachineId,
+        status::{MachineStatus, NonHaltReason},
+    };
+
+    #[test]
+    fn decider_hint_sweeps() {
+        // R, L L, R R R, L L L L, R R R R
+        let directions = [1, -1, -1, 1, 1, 1, -1, -1, -1, -1, 1, 1, 1, 1];
+        let hint = DeciderHint::from_directions(directions.into_iter());
+        assert_eq!(hint.steps, 14);
+        assert_eq!(hint.head_pos_min, -2);
+        assert_eq!(hint.head_pos_max, 2);
+        // first new left bound after the right bound
+        assert_eq!(hint.sweep_start_step, 3);
+        // turns in steps 3, 6 and 10, the last steps do not reach a new bound
+        assert_eq!(hint.sweep_period, 7);
+        assert!(DeciderHint::from_directions([].into_iter()).is_empty());
+    }
+
+    #[test]
+    fn decider_hint_cycler_to_bouncer() {
+        let machine = MachineId::try_from("1RB0LB_1LA0LC_---1RD_0RA0RA").unwrap();
+        let config = Config::builder(4).step_limit_decider_cycler(100).build();
+        let mut cycler = DeciderCycler::new(&config);
+        assert!(matches!(
+            cycler.decide_machine(&machine),
+            MachineStatus::Undecided(_, _, _)
+        ));
+        let hint = cycler.hint().unwrap();
+        assert_eq!(hint.steps, 100);
+        assert!(hint.head_pos_min < 0 && hint.head_pos_max > 0);
+        assert!(hint.sweep_start_step > 0 && hint.sweep_period > 0);
+
+        let mut bouncer = DeciderBouncer128::new(&config);
+        bouncer.set_hint(Some(&hint));
+        assert!(matches!(
+            bouncer.decide_machine(&machine),
+            MachineStatus::DecidedNonHalt(NonHaltReason::Bouncer(_))
+        ));
+    }
+
+    #[test]
+    fn decider_hints_chain_same_result() {
+        let run = |decider_hints: bool| {
+            let config = Config::builder(3)
+                .machine_limit(0)
+                .step_limit_decider_cycler(50)
+                .decider_hints(decider_hints)
+                .build();
+            let deciders = [
+                DeciderStandard::Cycler.decider_config(&config),
+                DeciderStandard::Bouncer128.decider_config(&config),
+            ];
+            let data_provider =
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+            batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider)
+        };
+        let result = run(false);
+        let result_hints = run(true);
+        assert_eq!(result_hints.num_undecided(), result.num_undecided());
+        assert_eq!(result_hints.num_non_halt(), result.num_non_halt());
+    }
+}
@@ -0,0 +1,150 @@
+//! Reference simulator without any acceleration, used as ground truth for the step counts and the halting
+//! behavior of the optimized deciders, e.g. the 128-bit and long tape of [DeciderHaltLong]. \
+//! Each step is executed one by one on a [VecDeque] tape with one byte per cell, see [MachineRunner].
+//! This is deliberately simple and slow, so it is only used in tests and to cross-check verdicts, see
+//! [cross_check_verdicts].
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     decider::{decider_hold_naive::DeciderHoldNaive, Decider},
+//!     machine_binary::NotableMachineBinary,
+//!     status::MachineStatus,
+//! };
+//! let machine = NotableMachineBinary::BB4Max.machine_id();
+//! let status = DeciderHoldNaive::decide_single_machine(&machine, &Config::new_default(4));
+//! assert_eq!(status, MachineStatus::DecidedHalt(107));
+//! ```
+//!
+//! [DeciderHaltLong]: crate::decider::decider_halt_long::DeciderHaltLong
+//! [cross_check_verdicts]: crate::decider::decider_compare::cross_check_verdicts
+//! [VecDeque]: std::collections::VecDeque
+
+use crate::{
+    config::{Config, StepBig},
+    decider::{
+        self,
+        decider_result::{BatchData, ResultUnitEndReason},
+        Decider, DECIDER_HOLD_NAIVE_ID,
+    },
+    machine_binary::MachineId,
+    machine_runner::MachineRunner,
+    status::{MachineStatus, UndecidedReason},
+};
+
+/// Naive halt decider, see [module documentation](self). It uses the step limit of the halt decider
+/// ([Config::step_limit_decider_halt]) and the tape size limit ([Config::tape_size_limit_cells]).
+#[derive(Debug, Clone)]
+pub struct DeciderHoldNaive {
+    step_limit: StepBig,
+    tape_size_limit_cells: usize,
+}
+
+impl DeciderHoldNaive {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            step_limit: config.step_limit_decider_halt(),
+            tape_size_limit_cells: config.tape_size_limit_cells() as usize,
+        }
+    }
+}
+
+impl Decider for DeciderHoldNaive {
+    fn decider_id() -> &'static decider::DeciderId {
+        &DECIDER_HOLD_NAIVE_ID
+    }
+
+    fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
+        let mut runner = MachineRunner::new(machine.machine());
+        while let Some(step) = runner.step() {
+            if step.is_halt() {
+                return MachineStatus::DecidedHalt(runner.num_steps() as StepBig);
+            }
+            let reason = if runner.num_steps() >= self.step_limit as u64 {
+                UndecidedReason::StepLimit
+            } else if runner.tape_len() > self.tape_size_limit_cells {
+                UndecidedReason::TapeSizeLimit
+            } else {
+                continue;
+            };
+            return MachineStatus::Undecided(
+                reason,
+                runner.num_steps() as StepBig,
+                runner.tape_len() as u32,
+            );
+        }
+        unreachable!("The runner only ends after the halt step.")
+    }
+
+    fn decide_single_machine(machine: &MachineId, config: &Config) -> MachineStatus {
+        Self::new(config).decide_machine(machine)
+    }
+
+    fn step_limit(&self) -> StepBig {
+        self.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.step_limit = step_limit;
+    }
+
+    fn tape_size_limit_u32_blocks(&self) -> u32 {
+        (self.tape_size_limit_cells / 32) as u32
+    }
+
+    fn set_tape_size_limit_u32_blocks(&mut self, tape_size_limit_u32_blocks: u32) {
+        self.tape_size_limit_cells = tape_size_limit_u32_blocks as usize * 32;
+    }
+
+    fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
+        let decider = Self::new(batch_data.config);
+        decider::decider_generic_run_batch(decider, batch_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{decider_compare::cross_check_verdicts, DeciderStandard},
+        machine_binary::NotableMachineBinary,
+    };
+
+    #[test]
+    fn decider_hold_naive_limits() {
+        let config = Config::builder(5).step_limit_decider_halt(1_000).build();
+        let machine = NotableMachineBinary::BB5Max.machine_id();
+        assert_eq!(
+            DeciderHoldNaive::decide_single_machine(&machine, &config),
+            MachineStatus::Undecided(UndecidedReason::StepLimit, 1_000, 59)
+        );
+
+        // A0 1RA runs right forever
+        let machine = MachineId::try_from("1RA0LB_1LA---").unwrap();
+        let config = Config::builder(2)
+            .step_limit_decider_halt(100_000)
+            .tape_size_limit_cells(3_200)
+            .build();
+        assert_eq!(
+            DeciderHoldNaive::decide_single_machine(&machine, &config),
+            MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, 3_200, 3_201)
+        );
+    }
+
+    /// The halt steps of the accelerated deciders must match the naive simulation.
+    #[test]
+    fn decider_hold_naive_matches_hold_and_cycler_bb3() {
+        let config = Config::builder(3).machine_limit(0).build();
+        let naive = DeciderStandard::HoldNaive.decider_config(&config);
+        for decider in [DeciderStandard::Hold, DeciderStandard::Cycler] {
+            let data_provider =
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+            let check =
+                cross_check_verdicts(&naive, &decider.decider_config(&config), data_provider)
+                    .unwrap();
+            assert!(check.num_compared > 0);
+            assert!(!check.has_mismatches(), "{check}");
+        }
+    }
+}
@@ -0,0 +1,489 @@
+//! RepWL decider (repeated word list), which proves non-halting with a closed set of abstract configurations. \
+//! The tape is cut into blocks of block_size cells and each tape side is stored as list of (word, count),
+//! where equal neighbor blocks are merged. Counts of rep_threshold or more are abstracted to 'at least rep_threshold',
+//! so the tapes are regular expressions like `0110 (10)^2 (11)^{3+}`. The machine is run block by block as macro
+//! machine. Taking a block from an abstracted count splits the configuration into two: the count is now exactly
+//! rep_threshold - 1 or still at least rep_threshold. \
+//! All configurations reachable from the start are searched. If this set is closed (no new configuration)
+//! and no configuration halts, the machine never halts. If a halting configuration is reached, the machine
+//! may halt (or the abstraction is too coarse) and the next parameters are tried. \
+//! This decides bouncers with a repeating pattern as well as many machines with a more complex structure of
+//! the tape, which the bouncer and cycler cannot handle. As the search is exhaustive, it should run
+//! late in the chain on the remaining undecided machines. \
+//! The closed set is the certificate, see [RepWLCertificate::verify].
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     decider::{decider_rep_wl::DeciderRepWL, Decider},
+//!     machine_binary::MachineId,
+//!     status::{MachineStatus, NonHaltReason},
+//! };
+//!
+//! let config = Config::new_default(5);
+//! let machine = MachineId::try_from("1RB0LB_1LA0LC_---1RD_0RA0RA").unwrap();
+//! let status = DeciderRepWL::decide_single_machine(&machine, &config);
+//! assert!(matches!(status, MachineStatus::DecidedNonHalt(NonHaltReason::RepWL(_, _))));
+//! ```
+
+use std::collections::HashSet;
+
+use crate::{
+    config::{Config, StepBig},
+    decider::{
+        self,
+        decider_result::{BatchData, ResultUnitEndReason},
+        Decider, DECIDER_REP_WL_ID,
+    },
+    machine_binary::{MachineBinary, MachineId, State, Symbol},
+    status::{MachineStatus, NonHaltReason, UndecidedReason},
+};
+
+/// Largest block size tried, the block simulation table grows with 2^block_size.
+const BLOCK_SIZE_MAX: usize = 6;
+/// Repeat thresholds tried for each block size.
+const REP_THRESHOLDS: [u16; 2] = [2, 3];
+/// Maximum number of words per tape side, a longer list is not regular enough for this decider.
+const WORD_LIST_LEN_MAX: usize = 32;
+
+/// Word of the word list, the block content (bit i: cell i from the left) and its repeat count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RepWord {
+    pub word: u16,
+    pub count: u16,
+    /// Count is 'at least count' (count is the repeat threshold).
+    pub is_at_least: bool,
+}
+
+/// Abstract configuration of the macro machine. The head is between the two tape sides and faces the
+/// side from which the next block is taken. The last entry of a side is next to the head, an empty side is all 0.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RepConfig {
+    /// State A = 1.
+    pub state: u8,
+    pub is_facing_right: bool,
+    pub left: Vec<RepWord>,
+    pub right: Vec<RepWord>,
+}
+
+impl RepConfig {
+    fn start() -> Self {
+        Self {
+            state: 1,
+            is_facing_right: true,
+            left: Vec::new(),
+            right: Vec::new(),
+        }
+    }
+}
+
+/// Result of running the machine inside a single block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockResult {
+    /// Head left the block with the state and the new block content.
+    Exit {
+        state: u8,
+        word: u16,
+        is_exit_right: bool,
+    },
+    Halt,
+    /// Machine never leaves the block.
+    Loop,
+}
+
+/// Result of the closure search.
+enum ClosureResult {
+    Closed(Vec<RepConfig>),
+    Halt,
+    ConfigLimit,
+    WordListLimit,
+}
+
+/// Closed set of abstract configurations which proves that the machine never halts.
+#[derive(Debug, Clone)]
+pub struct RepWLCertificate {
+    pub block_size: usize,
+    pub rep_threshold: u16,
+    pub configs: Vec<RepConfig>,
+}
+
+impl RepWLCertificate {
+    /// Verifies the certificate independent of the search: the start configuration is contained,
+    /// no configuration halts and all successors of each configuration are contained.
+    pub fn verify(&self, machine: &MachineBinary) -> bool {
+        if self.block_size == 0 || self.block_size > BLOCK_SIZE_MAX || self.rep_threshold < 2 {
+            return false;
+        }
+        let set: HashSet<&RepConfig> = self.configs.iter().collect();
+        if !set.contains(&RepConfig::start()) {
+            return false;
+        }
+        let mut macro_machine = MacroMachine::new(machine, self.block_size, self.rep_threshold);
+        let mut successors = Vec::new();
+        for config in self.configs.iter() {
+            successors.clear();
+            if !macro_machine.successors(config, &mut successors) {
+                return false;
+            }
+            if successors.iter().any(|s| !set.contains(s)) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Block macro machine of a machine for one parameter set.
+struct MacroMachine<'a> {
+    machine: &'a MachineBinary,
+    block_size: usize,
+    rep_threshold: u16,
+    /// Cache of the block results, index (state * 2^block_size + word) * 2 + entered from right.
+    block_results: Vec<Option<BlockResult>>,
+}
+
+impl<'a> MacroMachine<'a> {
+    fn new(machine: &'a MachineBinary, block_size: usize, rep_threshold: u16) -> Self {
+        let n_states = machine.n_states();
+        Self {
+            machine,
+            block_size,
+            rep_threshold,
+            block_results: vec![None; (n_states + 1) * (1 << block_size) * 2],
+        }
+    }
+
+    fn block_result(&mut self, state: u8, word: u16, is_entered_right: bool) -> BlockResult {
+        let id =
+            ((state as usize) << self.block_size | word as usize) * 2 + is_entered_right as usize;
+        if let Some(r) = self.block_results[id] {
+            return r;
+        }
+        let r = self.run_block(state, word, is_entered_right);
+        self.block_results[id] = Some(r);
+        r
+    }
+
+    /// Runs the machine inside the block until the head leaves it.
+    fn run_block(&self, state: u8, word: u16, is_entered_right: bool) -> BlockResult {
+        let mut state = state as usize;
+        let mut word = word;
+        let mut pos = if is_entered_right {
+            self.block_size as isize - 1
+        } else {
+            0
+        };
+        // more steps than distinct block configurations is a loop
+        let step_limit = self.machine.n_states() * self.block_size * (1 << self.block_size);
+        for _ in 0..=step_limit {
+            let symbol = (word >> pos) & 1;
+            let tr = self
+                .machine
+                .transition(State::new(state), Symbol::new(symbol as usize));
+            if tr.is_halt() {
+                return BlockResult::Halt;
+            }
+            if tr.is_symbol_one() {
+                word |= 1 << pos;
+            } else {
+                word &= !(1 << pos);
+            }
+            state = tr.state() as usize;
+            if tr.is_dir_right() {
+                pos += 1;
+                if pos == self.block_size as isize {
+                    return BlockResult::Exit {
+                        state: state as u8,
+                        word,
+                        is_exit_right: true,
+                    };
+                }
+            } else {
+                pos -= 1;
+                if pos < 0 {
+                    return BlockResult::Exit {
+                        state: state as u8,
+                        word,
+                        is_exit_right: false,
+                    };
+                }
+            }
+        }
+        BlockResult::Loop
+    }
+
+    /// Adds all successors of the configuration. Returns false if a successor halts.
+    fn successors(&mut self, config: &RepConfig, successors: &mut Vec<RepConfig>) -> bool {
+        let side = if config.is_facing_right {
+            &config.right
+        } else {
+            &config.left
+        };
+        for (word, side_rest) in self.pop(side) {
+            match self.block_result(config.state, word, !config.is_facing_right) {
+                BlockResult::Exit {
+                    state,
+                    word,
+                    is_exit_right,
+                } => {
+                    let (mut left, mut right) = if config.is_facing_right {
+                        (config.left.clone(), side_rest)
+                    } else {
+                        (side_rest, config.right.clone())
+                    };
+                    // the block is now behind the head
+                    if is_exit_right {
+                        self.push(&mut left, word);
+                    } else {
+                        self.push(&mut right, word);
+                    }
+                    successors.push(RepConfig {
+                        state,
+                        is_facing_right: is_exit_right,
+                        left,
+                        right,
+                    });
+                }
+                BlockResult::Halt => return false,
+                // never leaves the block, this branch does not halt
+                BlockResult::Loop => {}
+            }
+        }
+        true
+    }
+
+    /// Takes the block next to the head. An abstracted count splits into exactly threshold - 1 and still at least threshold.
+    fn pop(&self, side: &[RepWord]) -> Vec<(u16, Vec<RepWord>)> {
+        let Some(top) = side.last() else {
+            return vec![(0, Vec::new())];
+        };
+        let mut rest = side.to_vec();
+        if top.is_at_least {
+            let mut rest_exact = rest.clone();
+            let last = rest_exact.last_mut().unwrap();
+            last.count = self.rep_threshold - 1;
+            last.is_at_least = false;
+            return vec![(top.word, rest_exact), (top.word, rest)];
+        }
+        if top.count == 1 {
+            rest.pop();
+        } else {
+            rest.last_mut().unwrap().count -= 1;
+        }
+        vec![(top.word, rest)]
+    }
+
+    /// Puts the block next to the head, merging it with an equal word. 0 blocks on an empty side are dropped.
+    fn push(&self, side: &mut Vec<RepWord>, word: u16) {
+        match side.last_mut() {
+            None if word == 0 => {}
+            Some(top) if top.word == word => {
+                if !top.is_at_least {
+                    top.count += 1;
+                    if top.count >= self.rep_threshold {
+                        top.count = self.rep_threshold;
+                        top.is_at_least = true;
+                    }
+                }
+            }
+            _ => side.push(RepWord {
+                word,
+                count: 1,
+                is_at_least: false,
+            }),
+        }
+    }
+
+    /// Searches all configurations reachable from the start.
+    fn closure(&mut self, config_limit: usize) -> ClosureResult {
+        let start = RepConfig::start();
+        let mut visited: HashSet<RepConfig> = HashSet::new();
+        visited.insert(start.clone());
+        let mut queue = vec![start];
+        let mut successors = Vec::new();
+        while let Some(config) = queue.pop() {
+            successors.clear();
+            if !self.successors(&config, &mut successors) {
+                return ClosureResult::Halt;
+            }
+            for s in successors.drain(..) {
+                if s.left.len() > WORD_LIST_LEN_MAX || s.right.len() > WORD_LIST_LEN_MAX {
+                    return ClosureResult::WordListLimit;
+                }
+                if !visited.contains(&s) {
+                    if visited.len() >= config_limit {
+                        return ClosureResult::ConfigLimit;
+                    }
+                    visited.insert(s.clone());
+                    queue.push(s);
+                }
+            }
+        }
+        ClosureResult::Closed(visited.into_iter().collect())
+    }
+}
+
+#[derive(Debug)]
+pub struct DeciderRepWL {
+    /// Maximum number of abstract configurations per parameter set.
+    step_limit: StepBig,
+}
+
+impl DeciderRepWL {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            step_limit: config.step_limit_decider_rep_wl() as StepBig,
+        }
+    }
+
+    /// Searches the parameters (block size, repeat threshold) for a closed configuration set and returns it
+    /// as certificate, or the reason why no certificate was found (last parameter set).
+    pub fn certificate(
+        &self,
+        machine: &MachineBinary,
+    ) -> Result<RepWLCertificate, UndecidedReason> {
+        let mut reason = UndecidedReason::DeciderNoResult;
+        for block_size in 1..=BLOCK_SIZE_MAX {
+            for rep_threshold in REP_THRESHOLDS {
+                let mut macro_machine = MacroMachine::new(machine, block_size, rep_threshold);
+                match macro_machine.closure(self.step_limit as usize) {
+                    ClosureResult::Closed(configs) => {
+                        return Ok(RepWLCertificate {
+                            block_size,
+                            rep_threshold,
+                            configs,
+                        })
+                    }
+                    ClosureResult::Halt => reason = UndecidedReason::DeciderNoResult,
+                    ClosureResult::ConfigLimit => reason = UndecidedReason::StepLimit,
+                    ClosureResult::WordListLimit => reason = UndecidedReason::TapeSizeLimit,
+                }
+            }
+        }
+        Err(reason)
+    }
+}
+
+impl Decider for DeciderRepWL {
+    fn decider_id() -> &'static decider::DeciderId {
+        &DECIDER_REP_WL_ID
+    }
+
+    fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
+        match self.certificate(machine.machine()) {
+            Ok(c) => MachineStatus::DecidedNonHalt(NonHaltReason::RepWL(
+                c.block_size as u8,
+                c.rep_threshold as u8,
+            )),
+            Err(reason) => MachineStatus::Undecided(reason, self.step_limit, 0),
+        }
+    }
+
+    fn decide_single_machine(machine: &MachineId, config: &Config) -> MachineStatus {
+        let mut d = Self::new(config);
+        d.decide_machine(machine)
+    }
+
+    fn step_limit(&self) -> StepBig {
+        self.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.step_limit = step_limit;
+    }
+
+    fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
+        let decider = Self::new(batch_data.config);
+        decider::decider_generic_run_batch(decider, batch_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decider::{decider_bouncer_128::DeciderBouncer128, decider_cycler::DeciderCycler};
+
+    fn decide(tm: &str) -> MachineStatus {
+        let machine = MachineId::try_from(tm).unwrap();
+        let config = Config::new_default(machine.n_states());
+        DeciderRepWL::decide_single_machine(&machine, &config)
+    }
+
+    #[test]
+    fn decider_rep_wl_non_halt() {
+        for tm in [
+            "1RB0LB_1LA0LC_---1RD_0RA0RA",
+            // cycler
+            "1RB1LD_1RC---_1LC0RA_0RA0RA",
+        ] {
+            let status = decide(tm);
+            assert!(
+                matches!(
+                    status,
+                    MachineStatus::DecidedNonHalt(NonHaltReason::RepWL(_, _))
+                ),
+                "{tm}: {status}"
+            );
+        }
+    }
+
+    #[test]
+    fn decider_rep_wl_decides_undecided_of_cycler_bouncer() {
+        for tm in ["1RB1RA_1LC---_1RD1LC_0RA0RA", "1RB1LC_0RC---_1LD1RC_0RC0RA"] {
+            let machine = MachineId::try_from(tm).unwrap();
+            let config = Config::new_default(4);
+            let status = DeciderCycler::decide_single_machine(&machine, &config);
+            assert!(
+                matches!(status, MachineStatus::Undecided(_, _, _)),
+                "{tm}: {status}"
+            );
+            let status = DeciderBouncer128::decide_single_machine(&machine, &config);
+            assert!(
+                matches!(status, MachineStatus::Undecided(_, _, _)),
+                "{tm}: {status}"
+            );
+            let status = decide(tm);
+            assert!(
+                matches!(
+                    status,
+                    MachineStatus::DecidedNonHalt(NonHaltReason::RepWL(_, _))
+                ),
+                "{tm}: {status}"
+            );
+        }
+    }
+
+    #[test]
+    fn decider_rep_wl_binary_counter_undecided() {
+        // counters need an unbounded word list
+        let status = decide("1RB---_1LC0RD_0LC0LE_0RB0RA_0RA0RA");
+        assert_eq!(
+            status,
+            MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, 10_000, 0)
+        );
+    }
+
+    #[test]
+    fn decider_rep_wl_halting_undecided() {
+        let status = decide("1RB1LB_1LA0LC_1RZ1LD_1RD0RA");
+        assert!(
+            matches!(status, MachineStatus::Undecided(_, _, _)),
+            "{status}"
+        );
+    }
+
+    #[test]
+    fn decider_rep_wl_certificate_verify() {
+        let machine =
+            MachineBinary::try_from_standard_tm_text_format("1RB0LB_1LA0LC_---1RD_0RA0RA").unwrap();
+        let config = Config::new_default(4);
+        let mut certificate = DeciderRepWL::new(&config).certificate(&machine).unwrap();
+        assert!(certificate.verify(&machine));
+        // the certificate does not hold for another machine (BB4 Max) and not if incomplete
+        let bb4 =
+            MachineBinary::try_from_standard_tm_text_format("1RB1LB_1LA0LC_1RZ1LD_1RD0RA").unwrap();
+        assert!(!certificate.verify(&bb4));
+        certificate.configs.retain(|c| c.left.is_empty());
+        assert!(!certificate.verify(&machine));
+    }
+}
@@ -1,29 +1,94 @@
 use num_format::{Buffer, ToFormattedString};
+use serde::{Deserialize, Serialize};
 use std::{fmt::Display, time::Duration};
 
 use crate::{
+    checkpoint::CheckpointCounters,
     config::{user_locale, Config, IdNormalized, StepBig, StepSmall},
     data_provider::enumerator::num_turing_machine_permutations,
-    decider::{pre_decider::PreDeciderRun, DeciderId},
+    decider::{
+        decider_hint::DeciderHint,
+        pre_decider::{PreDeciderRun, PreDeciderTimer},
+        DeciderId, DeciderName,
+    },
+    html::HtmlWriteFailures,
     machine_binary::{MachineBinary, MachineId},
-    machine_info::MachineInfo,
+    machine_info::{MachineInfo, MachineInfoList, UndecidedStage},
     reporter::format_duration_hhmmss_ms,
-    status::{MachineStatus, NonHaltReason, PreDeciderReason},
+    status::{MachineStatus, NonHaltReason, PreDeciderReason, UndecidedReason},
+    verdict_log::Verdict,
 };
 
 const NUM_LONG_LEN: usize = 18;
 const NUM_SHORT_LEN: usize = 14;
 const LEVEL_1_CHAR: char = '\u{2022}';
-const NUM_MAX_MACHINES_TO_DISPLAY_IN_RESULT: usize = 10;
-const NUM_UNDECIDED_MACHINES_TO_DISPLAY_IN_RESULT: usize = 10;
+/// Default for [ResultDisplay::limit_machines_max_steps].
+pub const NUM_MAX_MACHINES_TO_DISPLAY_IN_RESULT: usize = 10;
+/// Default for [ResultDisplay::limit_machines_undecided].
+pub const NUM_UNDECIDED_MACHINES_TO_DISPLAY_IN_RESULT: usize = 10;
 
 pub type ResultDeciderStats = std::result::Result<DeciderResultStats, String>;
 pub type ResultUnitEndReason = Result<(), EndReason>;
 
 // TODO result print undecided
 
+/// Level of detail when displaying [DeciderResultStats].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResultVerbosity {
+    /// Only the totals, no breakdown and no machines. Keeps automated logs short.
+    Summary,
+    /// Totals with breakdown and the first machines up to the display limits.
+    #[default]
+    Normal,
+    /// Everything including all recorded machines, the display limits are ignored.
+    Full,
+}
+
+/// Controls the Display of [DeciderResultStats], set in [Config].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResultDisplay {
+    pub verbosity: ResultVerbosity,
+    /// Number of machines with max steps which are displayed.
+    pub limit_machines_max_steps: usize,
+    /// Number of undecided machines which are displayed.
+    pub limit_machines_undecided: usize,
+}
+
+impl ResultDisplay {
+    /// Number of machines with max steps to display depending on verbosity.
+    pub fn num_machines_max_steps(&self) -> usize {
+        match self.verbosity {
+            ResultVerbosity::Summary => 0,
+            ResultVerbosity::Normal => self.limit_machines_max_steps,
+            ResultVerbosity::Full => usize::MAX,
+        }
+    }
+
+    /// Number of undecided machines to display depending on verbosity.
+    pub fn num_machines_undecided(&self) -> usize {
+        match self.verbosity {
+            ResultVerbosity::Summary => 0,
+            ResultVerbosity::Normal => self.limit_machines_undecided,
+            ResultVerbosity::Full => usize::MAX,
+        }
+    }
+}
+
+impl Default for ResultDisplay {
+    fn default() -> Self {
+        Self {
+            verbosity: ResultVerbosity::default(),
+            limit_machines_max_steps: NUM_MAX_MACHINES_TO_DISPLAY_IN_RESULT,
+            limit_machines_undecided: NUM_UNDECIDED_MACHINES_TO_DISPLAY_IN_RESULT,
+        }
+    }
+}
+
 #[non_exhaustive]
 #[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 // TODO allow error?
 pub enum EndReason {
     /// Final end reason of the decider(s).
@@ -44,6 +109,14 @@ pub enum EndReason {
     RecordLimitDecidedReached(usize),
     /// When the maximum number of recorded undecided machines is reached. For analyzing undecided.
     RecordLimitUndecidedReached(usize),
+    /// Stop condition: This many undecided machines have been found, see [StopConditions](crate::stop_conditions::StopConditions).
+    UndecidedLimitReached(u64),
+    /// Stop condition: A machine halting after this many steps has been found.
+    HaltStepsReached(StepBig),
+    /// Stop condition: The run took longer than this duration.
+    TimeLimitReached(Duration),
+    /// Batch no, violated invariant of the counters, see [Config::check_invariants].
+    InvariantViolated(usize, String),
     /// Default state indicating no action has been taken yet.
     #[default]
     None,
@@ -95,6 +168,26 @@ impl Display for EndReason {
                     "Limit ({limit}) for recording undecided machines reached"
                 )
             }
+            EndReason::UndecidedLimitReached(num_undecided) => {
+                write!(
+                    f,
+                    "Stop condition: {num_undecided} undecided machines found"
+                )
+            }
+            EndReason::InvariantViolated(batch_no, message) => {
+                write!(f, "Invariant violated after batch {batch_no}: {message}")
+            }
+            EndReason::HaltStepsReached(steps) => {
+                write!(
+                    f,
+                    "Stop condition: Machine halting after {steps} steps found"
+                )
+            }
+            EndReason::TimeLimitReached(duration) => write!(
+                f,
+                "Stop condition: Time limit of {} reached",
+                format_duration_hhmmss_ms(*duration, false)
+            ),
             EndReason::None => write!(f, "No end reason"),
         }
         // write(f, "{s}")
@@ -106,6 +199,7 @@ impl Display for EndReason {
 /// This is always returned. end_reason should give error information if any.
 // TODO list of deciders with id, name, config, runtime, evaluated and decided (= and undecided)
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeciderResultStats {
     /// Number of machines which have been tested by the deciders (not pre-deciders) or have been eliminated during
     /// enumeration. This needs to be the num_turing_machines if not limited.
@@ -115,14 +209,26 @@ pub struct DeciderResultStats {
     /// Tested machines which come to a halt.
     /// This does not include machines which have not been tested, e.g. because they cannot produce the maximal steps.
     num_halt: u64,
+    /// Tested machines which do not halt, the breakdown by reason is non_halt_count.
+    num_non_halt: u64,
     /// Eliminated machines which cannot reach the maximum steps (may or may not halt).
     num_not_max: u64,
     /// Tested machines which did not come to a result.
     num_undecided: u64,
     /// Breakdown of eliminated machines
     pre_decider_count: PreDeciderCount,
+    /// Machines eliminated by the pre-decider of the first decider. They are counted as evaluated and in
+    /// pre_decider_count, all other eliminated machines are not evaluated.
+    num_eliminated_evaluated: u64,
+    /// Pre-decider checks in the data provider, their duration is part of [DurationDataProvider::duration_data_provider].
+    /// Like the decider stats, this is only kept in the main result, not in the n_states sections.
+    pre_decider_timer_data_provider: PreDeciderTimer,
+    /// Pre-decider checks before the first decider, their duration is part of [DurationDataProvider::duration_decider].
+    pre_decider_timer_decider: PreDeciderTimer,
     /// Breakdown of non-halting machines
     non_halt_count: NonHaltCount,
+    /// Breakdown of undecided machines
+    undecided_count: UndecidedCount,
 
     /// Number of states used for the Turing machines.
     n_states: usize,
@@ -131,9 +237,40 @@ pub struct DeciderResultStats {
     num_not_max_too_many_halt_transitions: u64,
     /// Eliminated machines which cannot reach the maximum steps because not all states were used.
     num_not_max_not_all_states_used: u64,
+    /// Undecided machines which were stopped because the shared step budget of the decider chain was used up.
+    num_step_budget_exhausted: u64,
+    /// Number of retries with a doubled tape size limit, see [Config::tape_size_limit_escalation_cells].
+    num_tape_limit_escalations: u64,
+    /// Number of re-runs with a larger tape size limit skipped because the tape memory budget was used up,
+    /// see [Config::tape_memory_budget_bytes].
+    num_tape_memory_budget_skips: u64,
+    /// Number of re-runs with larger limits requested by the deciders, see [Config::decider_retry_step_limit_max].
+    num_decider_retries: u64,
+    /// Html files which could not be written, the deciders continued without html output.
+    html_write_failures: HtmlWriteFailures,
+    /// Non-fatal anomalies of the run, which are summarized in the result instead of being printed.
+    warnings: Warnings,
+    /// Steps run by the deciders, as far as the machine status contains them.
+    num_steps_simulated: u64,
+    /// Throughput of each decider of the chain, in order of the chain.
+    decider_stats: Vec<DeciderStats>,
+    /// Largest tape memory and distribution of the tape sizes of the decided machines.
+    tape_memory: TapeMemoryStats,
+    /// Machines on which a decider panicked. They are passed to the next decider as undecided with
+    /// [UndecidedReason::DeciderPanicked].
+    machines_errored: Vec<MachineErrored>,
+    /// Verdicts of the machines reaching a decider if [Config::record_verdicts] is set. They are collected
+    /// per batch and taken by the engine for the [VerdictLog](crate::verdict_log::VerdictLog) and the SQLite store.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    verdicts: Vec<Verdict>,
+    /// Results of machines with another number of states than n_states, ordered by n_states. \
+    /// Only used if the data provider returns machines of different sizes, e.g. DataProviderComposite.
+    n_states_sections: Vec<DeciderResultStats>,
 
     // steps
     steps_max: StepMaxResult,
+    /// Halting machines with at least [Config::high_steps_threshold] steps.
+    high_steps: HighStepsMachines,
     // pub steps_max: StepTypeBig,
     // pub num_machines_for_steps_max: u16,
     // machine_max_steps: Option<MachineInfo>,
@@ -153,10 +290,13 @@ pub struct DeciderResultStats {
     // pub name: String,
     /// Optional name of the tests or any other info.
     names: Vec<String>,
+    /// Verbosity and number of machines shown in Display.
+    display: ResultDisplay,
 
     // Additional statistics, possibly make this a struct in an Option to turn on at runtime
     // TODO HashMaps for larger
     #[cfg(feature = "counter_stats")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub counter_stats: CounterStats,
 }
 
@@ -175,7 +315,8 @@ impl DeciderResultStats {
         let limit_machines_decided = config.limit_machines_decided();
         DeciderResultStats {
             n_states: config.n_states(),
-            steps_max: StepMaxResult::new(init_steps_max),
+            steps_max: StepMaxResult::new(init_steps_max).with_counts_only(config.counts_only()),
+            high_steps: HighStepsMachines::from_config(config),
             limit_machines_decided,
             machines_decided: if limit_machines_decided > 0 {
                 Some(Vec::new())
@@ -183,6 +324,7 @@ impl DeciderResultStats {
                 None
             },
             limit_machines_undecided: config.limit_machines_undecided(),
+            display: config.result_display(),
             ..Default::default()
         }
     }
@@ -198,6 +340,15 @@ impl DeciderResultStats {
         if self.limit_machines_undecided < config.limit_machines_undecided() {
             self.limit_machines_undecided = config.limit_machines_undecided();
         }
+        if !self.high_steps.is_active() && config.high_steps_threshold() > 0 {
+            self.high_steps = HighStepsMachines::from_config(config);
+        }
+        // the configs of a chain are usually built from the same config, add its warnings only once
+        if self.warnings.count(WarningKind::Config) == 0 {
+            for warning in config.warnings() {
+                self.warnings.add(WarningKind::Config, || warning.clone());
+            }
+        }
     }
 
     // /// Set steps_max a bit higher to avoid saving a lot of machines with low steps
@@ -213,6 +364,15 @@ impl DeciderResultStats {
         self.limit_machines_undecided
     }
 
+    pub fn display(&self) -> ResultDisplay {
+        self.display
+    }
+
+    /// Changes verbosity and display limits, e.g. to print a short summary of a result.
+    pub fn set_display(&mut self, display: ResultDisplay) {
+        self.display = display;
+    }
+
     // pub fn set_limit_machines_undecided(&mut self, limit: usize) {
     //     self.limit_machines_undecided = limit;
     //     if limit == 0 {
@@ -226,6 +386,20 @@ impl DeciderResultStats {
     /// which allows the caller to stop further processing. \
     /// In this case the end_reason is set also.  
     pub fn add(&mut self, machine: &MachineId, status: &MachineStatus) -> bool {
+        self.add_with_undecided_stage(machine, status, None)
+    }
+
+    /// Same as [DeciderResultStats::add], a recorded undecided machine additionally keeps the stage of
+    /// the decider chain which first returned it undecided.
+    pub fn add_with_undecided_stage(
+        &mut self,
+        machine: &MachineId,
+        status: &MachineStatus,
+        undecided_stage: Option<UndecidedStage>,
+    ) -> bool {
+        if machine.n_states() != self.n_states && self.n_states != 0 && machine.n_states() != 0 {
+            return self.add_n_states_section(machine, status, undecided_stage);
+        }
         // self.num_checked_total += 1;
         let mut is_decided = true;
         self.num_evaluated += 1;
@@ -233,6 +407,7 @@ impl DeciderResultStats {
             MachineStatus::DecidedHalt(steps) | MachineStatus::DecidedHaltField(steps, _) => {
                 self.num_halt += 1;
                 self.steps_max.add_steps(*steps, machine, status);
+                self.high_steps.add_steps(*steps, machine, status);
 
                 #[cfg(feature = "counter_stats")]
                 {
@@ -252,28 +427,39 @@ impl DeciderResultStats {
                     // }
                 }
             }
-            MachineStatus::EliminatedPreDecider(reason) => match reason {
-                PreDeciderReason::None => panic!("None must not happen."),
-                PreDeciderReason::NotAllStatesUsed => {
-                    self.pre_decider_count.num_not_all_states_used += 1
-                }
-                PreDeciderReason::NotExactlyOneHaltCondition => {
-                    self.pre_decider_count.num_not_exactly_one_halt_condition += 1
-                }
-                PreDeciderReason::OnlyOneDirection => {
-                    self.pre_decider_count.num_only_one_direction += 1
-                }
-                PreDeciderReason::SimpleStartCycle => {
-                    self.pre_decider_count.num_simple_start_cycle += 1
-                }
-                PreDeciderReason::StartRecursive => self.pre_decider_count.num_start_recursive += 1,
-                PreDeciderReason::NotStartStateBRight => {
-                    self.pre_decider_count.num_not_start_state_b_right += 1
-                }
-                PreDeciderReason::WritesOnlyZero => {
-                    self.pre_decider_count.num_writes_only_zero += 1
+            MachineStatus::EliminatedPreDecider(reason) => {
+                self.num_eliminated_evaluated += 1;
+                match reason {
+                    PreDeciderReason::None => panic!("None must not happen."),
+                    PreDeciderReason::NotAllStatesUsed => {
+                        self.pre_decider_count.num_not_all_states_used += 1
+                    }
+                    PreDeciderReason::NotExactlyOneHaltCondition => {
+                        self.pre_decider_count.num_not_exactly_one_halt_condition += 1
+                    }
+                    PreDeciderReason::OnlyOneDirection => {
+                        self.pre_decider_count.num_only_one_direction += 1
+                    }
+                    PreDeciderReason::SimpleStartCycle => {
+                        self.pre_decider_count.num_simple_start_cycle += 1
+                    }
+                    PreDeciderReason::StartRecursive => {
+                        self.pre_decider_count.num_start_recursive += 1
+                    }
+                    PreDeciderReason::NotStartStateBRight => {
+                        self.pre_decider_count.num_not_start_state_b_right += 1
+                    }
+                    PreDeciderReason::WritesOnlyZero => {
+                        self.pre_decider_count.num_writes_only_zero += 1
+                    }
+                    PreDeciderReason::LookaheadHalt => {
+                        self.pre_decider_count.num_lookahead_halt += 1
+                    }
+                    PreDeciderReason::LookaheadCycle => {
+                        self.pre_decider_count.num_lookahead_cycle += 1
+                    }
                 }
-            },
+            }
             MachineStatus::DecidedHaltDetail(_, _, _) => todo!(),
             // MachineStatus::DecidedHaltsOld(steps, _) => {
             //     self.num_halt += 1;
@@ -288,19 +474,21 @@ impl DeciderResultStats {
             //     self.add_steps(*steps, machine, status);
             // }
             MachineStatus::DecidedNonHalt(non_halt_reason) => {
+                self.num_non_halt += 1;
                 self.non_halt_count.add_non_halt_reason(non_halt_reason);
                 #[cfg(feature = "counter_stats")]
                 self.counter_stats.add_non_halt_cycle(non_halt_reason);
             }
-            MachineStatus::Undecided(_, _, _) => {
+            MachineStatus::Undecided(reason, _, _) => {
                 is_decided = false;
                 if self.limit_machines_undecided > 0 {
                     if self.num_undecided < self.limit_machines_undecided as u64 {
+                        let m_info = MachineInfo::from_machine_id(machine, status)
+                            .with_undecided_stage(undecided_stage);
                         if let Some(machines) = self.machines_undecided.as_mut() {
-                            machines.push(MachineInfo::from_machine_id(machine, status));
+                            machines.push(m_info);
                         } else {
-                            self.machines_undecided =
-                                Some(vec![MachineInfo::from_machine_id(machine, status)]);
+                            self.machines_undecided = Some(vec![m_info]);
                         }
                     } else {
                         self.end_reason =
@@ -309,6 +497,7 @@ impl DeciderResultStats {
                     }
                 }
                 self.num_undecided += 1;
+                self.undecided_count.add_undecided_reason(reason);
             }
             MachineStatus::DecidedNotMaxTooManyHaltTransitions => {
                 self.num_not_max_too_many_halt_transitions += 1;
@@ -335,6 +524,11 @@ impl DeciderResultStats {
         true
     }
 
+    /// Names of the tests or other info, see [Self::add_name].
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
     pub fn set_name(&mut self, name: String) {
         self.add_name(&name);
     }
@@ -351,23 +545,121 @@ impl DeciderResultStats {
         }
     }
 
-    /// Add another result to this result. \
+    /// Returns the result for machines with n_states, which is self for the n_states of this result or 0.
+    /// The section is created if it does not exist yet.
+    pub fn n_states_section_mut(&mut self, n_states: usize) -> &mut DeciderResultStats {
+        if n_states == self.n_states || n_states == 0 || self.n_states == 0 {
+            return self;
+        }
+        let pos = match self
+            .n_states_sections
+            .binary_search_by_key(&n_states, |section| section.n_states)
+        {
+            Ok(pos) => pos,
+            Err(pos) => {
+                let section = DeciderResultStats {
+                    n_states,
+                    steps_max: StepMaxResult::new(if n_states == 1 { 0 } else { 2 })
+                        .with_counts_only(self.steps_max.counts_only),
+                    high_steps: self.high_steps.new_same_limits(),
+                    limit_machines_decided: self.limit_machines_decided,
+                    machines_decided: self.machines_decided.as_ref().map(|_| Vec::new()),
+                    limit_machines_undecided: self.limit_machines_undecided,
+                    display: self.display,
+                    ..Default::default()
+                };
+                self.n_states_sections.insert(pos, section);
+                pos
+            }
+        };
+        &mut self.n_states_sections[pos]
+    }
+
+    /// Results of machines with another number of states than [Self::n_states], ordered by n_states.
+    /// All counters of this result only refer to machines with n_states.
+    pub fn n_states_sections(&self) -> &[DeciderResultStats] {
+        &self.n_states_sections
+    }
+
+    fn add_n_states_section(
+        &mut self,
+        machine: &MachineId,
+        status: &MachineStatus,
+        undecided_stage: Option<UndecidedStage>,
+    ) -> bool {
+        let section = self.n_states_section_mut(machine.n_states());
+        if section.add_with_undecided_stage(machine, status, undecided_stage) {
+            return true;
+        }
+        let end_reason = section.end_reason.clone();
+        self.end_reason = end_reason;
+        false
+    }
+
+    /// Add another result to this result. Results of other n_states are added to their section. \
     /// Returns false if <limit_machines_(un)decided> (Un)decided Machines have been stored
     /// which allows the caller to stop further processing.  
     pub fn add_result(&mut self, result: &DeciderResultStats) -> bool {
+        for stats in result.decider_stats.iter() {
+            self.add_decider_stats(stats);
+        }
+        self.verdicts.extend_from_slice(&result.verdicts);
+        self.pre_decider_timer_data_provider
+            .add_self(&result.pre_decider_timer_data_provider);
+        self.pre_decider_timer_decider
+            .add_self(&result.pre_decider_timer_decider);
+        let mut is_ok = self.add_result_to_section(result);
+        for section in result.n_states_sections.iter() {
+            is_ok &= self.add_result_to_section(section);
+        }
+        is_ok
+    }
+
+    fn add_result_to_section(&mut self, result: &DeciderResultStats) -> bool {
+        let section = self.n_states_section_mut(result.n_states);
+        if section.add_result_n_states(result) {
+            return true;
+        }
+        let end_reason = section.end_reason.clone();
+        if matches!(
+            self.end_reason,
+            EndReason::None | EndReason::AllMachinesChecked
+        ) {
+            self.end_reason = end_reason;
+        }
+        false
+    }
+
+    /// Adds the result of the same n_states, without its sections.
+    fn add_result_n_states(&mut self, result: &DeciderResultStats) -> bool {
         self.num_processed_total += result.num_processed_total;
         self.num_evaluated += result.num_evaluated;
         self.num_halt += result.num_halt;
+        self.num_non_halt += result.num_non_halt;
         self.num_not_max += result.num_not_max;
 
         self.steps_max.add_self(&result.steps_max);
+        self.high_steps.add_self(&result.high_steps);
 
         self.pre_decider_count.add_self(&result.pre_decider_count);
+        self.num_eliminated_evaluated += result.num_eliminated_evaluated;
         // self.pre_decider_count.num_checked = self.pre_decider_count.total() + self.num_evaluated;
         self.non_halt_count.add_self(&result.non_halt_count);
+        self.undecided_count.add_self(&result.undecided_count);
 
         self.num_not_max_not_all_states_used += result.num_not_max_not_all_states_used;
         self.num_not_max_too_many_halt_transitions += result.num_not_max_too_many_halt_transitions;
+        self.num_step_budget_exhausted += result.num_step_budget_exhausted;
+        self.num_tape_limit_escalations += result.num_tape_limit_escalations;
+        self.num_tape_memory_budget_skips += result.num_tape_memory_budget_skips;
+        self.num_decider_retries += result.num_decider_retries;
+        self.html_write_failures
+            .add_self(&result.html_write_failures);
+        self.warnings.add_self(&result.warnings);
+        self.num_steps_simulated += result.num_steps_simulated;
+        self.tape_memory.add_self(&result.tape_memory);
+        self.machines_errored
+            .extend_from_slice(&result.machines_errored);
 
         let mut is_ok = true;
 
@@ -458,13 +750,337 @@ impl DeciderResultStats {
         self.pre_decider_count.add_self(count);
     }
 
+    /// Adds the duration of the pre-decider checks which ran in the data provider.
+    pub fn add_pre_decider_timer_data_provider(&mut self, timer: &PreDeciderTimer) {
+        self.pre_decider_timer_data_provider.add_self(timer);
+    }
+
+    /// Adds the duration of the pre-decider checks which ran before the first decider.
+    pub fn add_pre_decider_timer_decider(&mut self, timer: &PreDeciderTimer) {
+        self.pre_decider_timer_decider.add_self(timer);
+    }
+
+    /// Counts a machine which was stopped because the shared step budget was used up.
+    pub fn add_step_budget_exhausted(&mut self) {
+        self.num_step_budget_exhausted += 1;
+    }
+
+    /// Counts a retry of a machine with a doubled tape size limit.
+    pub fn add_tape_limit_escalation(&mut self) {
+        self.num_tape_limit_escalations += 1;
+    }
+
+    /// Counts a re-run with a larger tape size limit which was skipped because of the tape memory budget.
+    pub fn add_tape_memory_budget_skip(&mut self) {
+        self.num_tape_memory_budget_skips += 1;
+    }
+
+    /// Counts a re-run of a machine with the larger limits requested by the decider.
+    pub fn add_decider_retry(&mut self) {
+        self.num_decider_retries += 1;
+    }
+
+    pub fn add_html_write_failures(&mut self, write_failures: &HtmlWriteFailures) {
+        self.html_write_failures.add_self(write_failures);
+    }
+
+    pub fn add_steps_simulated(&mut self, num_steps: u64) {
+        self.num_steps_simulated += num_steps;
+    }
+
+    /// Records the final status of a machine for the verdict outputs, see [Config::record_verdicts].
+    pub fn add_verdict(
+        &mut self,
+        machine: &MachineId,
+        decider_name: DeciderName,
+        status: MachineStatus,
+    ) {
+        self.verdicts.push(Verdict {
+            machine: *machine,
+            decider_name,
+            status,
+        });
+    }
+
+    /// Takes the recorded verdicts, so they are written only once.
+    pub fn take_verdicts(&mut self) -> Vec<Verdict> {
+        std::mem::take(&mut self.verdicts)
+    }
+
+    /// Adds the throughput of a decider run to the stats of the decider with the same name.
+    pub fn add_decider_stats(&mut self, stats: &DeciderStats) {
+        match self
+            .decider_stats
+            .iter_mut()
+            .find(|s| s.decider_name == stats.decider_name)
+        {
+            Some(s) => s.add_self(stats),
+            None => self.decider_stats.push(stats.clone()),
+        }
+    }
+
+    /// Records the tape memory used by the machine in the section of its n_states.
+    pub fn add_tape_memory(&mut self, machine: &MachineId, bytes: usize, is_decided: bool) {
+        self.n_states_section_mut(machine.n_states())
+            .tape_memory
+            .add(machine, bytes, is_decided);
+    }
+
+    /// Records a machine on which the decider panicked. The machine counts as processed, the caller passes it
+    /// to the next decider as undecided, so it is evaluated there.
+    pub fn add_errored(
+        &mut self,
+        machine: &MachineId,
+        decider_name: &'static str,
+        message: String,
+    ) {
+        let section = self.n_states_section_mut(machine.n_states());
+        section.num_processed_total += 1;
+        section.machines_errored.push(MachineErrored {
+            machine: *machine,
+            decider_name,
+            message,
+        });
+    }
+
+    /// Records a non-fatal anomaly. The message is only created for the first warning of this kind.
+    pub fn add_warning(&mut self, kind: WarningKind, message: impl FnOnce() -> String) {
+        self.warnings.add(kind, message);
+    }
+
     pub fn add_total(&mut self, value: u64) {
         self.num_processed_total += value;
     }
 
+    /// Counters of the result stored in a [Checkpoint](crate::checkpoint::Checkpoint), without the n_states sections.
+    pub fn checkpoint_counters(&self) -> CheckpointCounters {
+        CheckpointCounters {
+            num_processed_total: self.num_processed_total,
+            num_evaluated: self.num_evaluated,
+            num_halt: self.num_halt,
+            num_not_max: self.num_not_max,
+            num_undecided: self.num_undecided,
+            num_not_max_too_many_halt_transitions: self.num_not_max_too_many_halt_transitions,
+            num_not_max_not_all_states_used: self.num_not_max_not_all_states_used,
+            num_step_budget_exhausted: self.num_step_budget_exhausted,
+            num_steps_simulated: self.num_steps_simulated,
+            steps_max: self.steps_max.steps_max,
+            num_machines_steps_max: self.steps_max.num_machines_steps_max,
+            pre_decider_count: self.pre_decider_count,
+            num_eliminated_evaluated: Some(self.num_eliminated_evaluated),
+            non_halt_count: self.non_halt_count.clone(),
+            undecided_count: self.undecided_count,
+        }
+    }
+
+    /// Result restored from the counters and machines of a [Checkpoint](crate::checkpoint::Checkpoint).
+    pub fn from_checkpoint(
+        config: &Config,
+        counters: &CheckpointCounters,
+        machines_max_steps: Vec<MachineInfo>,
+        machines_undecided: Vec<MachineInfo>,
+    ) -> Self {
+        let mut result = Self::new(config);
+        result.num_processed_total = counters.num_processed_total;
+        result.num_evaluated = counters.num_evaluated;
+        result.num_halt = counters.num_halt;
+        result.num_not_max = counters.num_not_max;
+        result.num_undecided = counters.num_undecided;
+        result.num_not_max_too_many_halt_transitions =
+            counters.num_not_max_too_many_halt_transitions;
+        result.num_not_max_not_all_states_used = counters.num_not_max_not_all_states_used;
+        result.num_step_budget_exhausted = counters.num_step_budget_exhausted;
+        result.num_steps_simulated = counters.num_steps_simulated;
+        result.steps_max.steps_max = counters.steps_max;
+        result.steps_max.num_machines_steps_max = counters.num_machines_steps_max;
+        if !machines_max_steps.is_empty() {
+            result.steps_max.machines_max_steps = Some(machines_max_steps);
+        }
+        result.pre_decider_count = counters.pre_decider_count;
+        result.num_non_halt = counters.non_halt_count.num_non_halt_total();
+        result.non_halt_count = counters.non_halt_count.clone();
+        result.undecided_count = counters.undecided_count;
+        result.num_eliminated_evaluated = counters.num_eliminated_evaluated.unwrap_or_else(|| {
+            counters
+                .num_evaluated
+                .saturating_sub(result.num_categories())
+        });
+        if !machines_undecided.is_empty() {
+            result.machines_undecided = Some(machines_undecided);
+        }
+        result
+    }
+
+    /// Merges the result of a run on machines left undecided by this result, e.g. a new decider chain on the
+    /// exported undecided machines, see [resume_undecided](crate::resume_undecided). The machines are removed
+    /// from the undecided counts and the recorded undecided machines, then the resumed result is added. \
+    /// If not all undecided machines were recorded, the undecided reasons of the unrecorded machines are unknown
+    /// and are removed from the largest reason count. \
+    /// Returns an error if the machines cannot be undecided machines of this result.
+    pub fn merge_resumed_undecided(
+        &mut self,
+        resumed: &DeciderResultStats,
+        machines: &[MachineId],
+    ) -> Result<(), String> {
+        let num_machines = machines.len() as u64;
+        if !self.n_states_sections.is_empty()
+            || !resumed.n_states_sections.is_empty()
+            || resumed.n_states != self.n_states
+        {
+            return Err("Only results of one n_states can be merged".to_string());
+        }
+        if resumed.num_processed_total != num_machines {
+            return Err(format!(
+                "Resumed result has {} machines, expected {num_machines}",
+                resumed.num_processed_total
+            ));
+        }
+        if num_machines > self.num_undecided {
+            return Err(format!(
+                "{num_machines} machines to merge, but only {} undecided",
+                self.num_undecided
+            ));
+        }
+
+        // remove the machines from the recorded undecided machines with their reason
+        let mut num_removed = 0;
+        if let Some(recorded) = self.machines_undecided.as_mut() {
+            let resumed_machines: std::collections::HashSet<String> = machines
+                .iter()
+                .map(|m| m.to_standard_tm_text_format())
+                .collect();
+            let (removed, kept): (Vec<MachineInfo>, Vec<MachineInfo>) = recorded
+                .iter()
+                .partition(|m| resumed_machines.contains(&m.to_standard_tm_text_format()));
+            num_removed = removed.len() as u64;
+            if recorded.len() as u64 == self.num_undecided && num_removed != num_machines {
+                return Err(format!(
+                    "{} machines are not undecided in this result",
+                    num_machines - num_removed
+                ));
+            }
+            for m_info in removed.iter() {
+                match m_info.status() {
+                    MachineStatus::Undecided(reason, _, _) => {
+                        self.undecided_count.remove_undecided_reason(&reason)
+                    }
+                    _ => self.undecided_count.remove_largest(),
+                }
+            }
+            *recorded = kept;
+        }
+        for _ in num_removed..num_machines {
+            self.undecided_count.remove_largest();
+        }
+        self.num_undecided -= num_machines;
+        self.num_processed_total -= num_machines;
+        self.num_evaluated = self.num_evaluated.saturating_sub(resumed.num_evaluated);
+        self.add_result(resumed);
+
+        Ok(())
+    }
+
+    /// Adds the machines to the total of the section of their n_states.
+    pub fn add_total_machines(&mut self, machines: &[MachineId]) {
+        let n_states = self.n_states;
+        if machines.iter().all(|m| m.n_states() == n_states) {
+            self.add_total(machines.len() as u64);
+        } else {
+            for m in machines.iter() {
+                self.n_states_section_mut(m.n_states()).num_processed_total += 1;
+            }
+        }
+    }
+
+    /// Checks the invariants of the counters, also of the n_states sections, see [Config::check_invariants].
+    /// Returns the violated invariant with the counter values.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.check_invariants_n_states()?;
+        for section in self.n_states_sections.iter() {
+            section.check_invariants_n_states()?;
+        }
+        Ok(())
+    }
+
+    fn check_invariants_n_states(&self) -> Result<(), String> {
+        let n = self.n_states;
+        // per-reason sums
+        if self.undecided_count.num_total() != self.num_undecided {
+            return Err(format!(
+                "BB{n}: undecided {} != sum of undecided reasons {}",
+                self.num_undecided,
+                self.undecided_count.num_total()
+            ));
+        }
+        if self.non_halt_count.num_non_halt_total() != self.num_non_halt {
+            return Err(format!(
+                "BB{n}: non-halt {} != sum of non-halt reasons {}",
+                self.num_non_halt,
+                self.non_halt_count.num_non_halt_total()
+            ));
+        }
+        // the halts have no reasons, but each machine with max steps or high steps is a halt
+        if self.steps_max.num_machines_steps_max as u64 > self.num_halt
+            || self.high_steps.num_machines() > self.num_halt
+        {
+            return Err(format!(
+                "BB{n}: halt {} < machines with max steps {} or high steps {}",
+                self.num_halt,
+                self.steps_max.num_machines_steps_max,
+                self.high_steps.num_machines()
+            ));
+        }
+        // Each evaluated machine is in exactly one category, except the machines eliminated by the
+        // pre-decider of the first decider.
+        let num_categories = self.num_categories();
+        let num_eliminated = self.pre_decider_count.num_total();
+        if num_categories + self.num_eliminated_evaluated != self.num_evaluated {
+            return Err(format!(
+                "BB{n}: evaluated {} != halt {} + non-halt {} + undecided {} + not max {} \
+                 + eliminated in decider {}",
+                self.num_evaluated,
+                self.num_halt,
+                self.num_non_halt,
+                self.num_undecided,
+                self.num_not_max
+                    + self.num_not_max_too_many_halt_transitions
+                    + self.num_not_max_not_all_states_used,
+                self.num_eliminated_evaluated,
+            ));
+        }
+        if self.num_eliminated_evaluated > num_eliminated {
+            return Err(format!(
+                "BB{n}: eliminated in decider {} > sum of pre-decider reasons {num_eliminated}",
+                self.num_eliminated_evaluated,
+            ));
+        }
+        // machines on which a decider panicked are passed on, so they are also in a category
+        if num_categories + num_eliminated != self.num_processed_total {
+            return Err(format!(
+                "BB{n}: processed {} != decided and undecided {num_categories} + eliminated {num_eliminated}",
+                self.num_processed_total,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Machines in one of the categories halt, non-halt, undecided or not max.
+    fn num_categories(&self) -> u64 {
+        self.num_halt
+            + self.num_non_halt
+            + self.num_undecided
+            + self.num_not_max
+            + self.num_not_max_too_many_halt_transitions
+            + self.num_not_max_not_all_states_used
+    }
+
     /// Clears the total which is required if multiple deciders run as this would result in a double count.
     pub fn clear_total(&mut self) {
         self.num_processed_total = 0;
+        for section in self.n_states_sections.iter_mut() {
+            section.clear_total();
+        }
     }
 
     /// Returns the first machine with max steps.
@@ -472,6 +1088,10 @@ impl DeciderResultStats {
         &self.non_halt_count
     }
 
+    pub fn undecided_count(&self) -> &UndecidedCount {
+        &self.undecided_count
+    }
+
     pub fn machine_max_steps(&self) -> Option<MachineInfo> {
         self.steps_max.machine_max_steps()
     }
@@ -482,10 +1102,15 @@ impl DeciderResultStats {
     }
 
     /// Returns all recorded machines with max steps, sorted by id.
-    pub fn machines_max_steps_sorted(&self) -> Option<Vec<MachineInfo>> {
+    pub fn machines_max_steps_sorted(&self) -> Option<MachineInfoList> {
         self.steps_max.machines_max_steps_sorted()
     }
 
+    /// Halting machines with at least [Config::high_steps_threshold] steps.
+    pub fn high_steps_machines(&self) -> &HighStepsMachines {
+        &self.high_steps
+    }
+
     // pub fn machines_max_steps_to_string(&self, max_machines: usize) -> String {
     //     if let Some(machines) = &self.machines_max_steps {
     //         let last = machines.len().min(max_machines);
@@ -513,30 +1138,25 @@ impl DeciderResultStats {
         self.machines_decided.as_ref()
     }
 
-    /// Returns all recorded machines with max steps, sorted by id.
-    pub fn machines_decided_sorted(&self) -> Option<Vec<MachineInfo>> {
-        if let Some(machines) = self.machines_decided.as_ref() {
-            let mut v = machines.to_vec();
-            v.sort();
-            Some(v)
-        } else {
-            None
-        }
+    /// Returns all recorded decided machines, sorted by id.
+    pub fn machines_decided_sorted(&self) -> Option<MachineInfoList> {
+        self.machines_decided.as_deref().map(sorted_by_id)
     }
 
     pub fn machines_undecided(&self) -> Option<&Vec<MachineInfo>> {
         self.machines_undecided.as_ref()
     }
 
-    /// Returns all recorded machines with max steps, sorted by id.
-    pub fn machines_undecided_sorted(&self) -> Option<Vec<MachineInfo>> {
-        if let Some(machines) = self.machines_undecided.as_ref() {
-            let mut v = machines.to_vec();
-            v.sort();
-            Some(v)
-        } else {
-            None
-        }
+    /// Returns all recorded undecided machines, sorted by id.
+    pub fn machines_undecided_sorted(&self) -> Option<MachineInfoList> {
+        self.machines_undecided.as_deref().map(sorted_by_id)
+    }
+
+    /// Returns all recorded undecided machines sorted by id and grouped by the stage of the decider chain
+    /// which first returned them undecided, see [MachineInfoList::group_by_undecided_stage].
+    pub fn machines_undecided_by_stage(&self) -> Option<Vec<MachineInfoList>> {
+        self.machines_undecided_sorted()
+            .map(MachineInfoList::group_by_undecided_stage)
     }
 
     // TODO move undecided in own struct and replace this with Display. Merge from result Display.
@@ -547,13 +1167,17 @@ impl DeciderResultStats {
             for m in machines.iter().take(last) {
                 s.push_str(
                     format!(
-                        "Undecided M No. {:5} {}, Steps: {}\n",
+                        "Undecided M No. {:5} {}, Steps: {}",
                         m.id(),
                         m.to_standard_tm_text_format(),
                         m.steps()
                     )
                     .as_str(),
                 );
+                if let Some(stage) = m.undecided_stage() {
+                    s.push_str(&format!(", first undecided in {stage}"));
+                }
+                s.push('\n');
             }
             s
         } else if self.num_undecided == 0 {
@@ -570,7 +1194,17 @@ impl DeciderResultStats {
         self.n_states
     }
 
+    /// Number of processed machines, including the machines of the n_states sections.
     pub fn num_processed_total(&self) -> u64 {
+        self.num_processed_total_n_states()
+            + self
+                .n_states_sections
+                .iter()
+                .map(|section| section.num_processed_total_n_states())
+                .sum::<u64>()
+    }
+
+    fn num_processed_total_n_states(&self) -> u64 {
         if self.num_processed_total != 0 {
             self.num_processed_total
         } else {
@@ -580,7 +1214,7 @@ impl DeciderResultStats {
     }
 
     pub fn num_non_halt(&self) -> u64 {
-        self.non_halt_count.num_non_halt_total()
+        self.num_non_halt
     }
 
     pub fn num_evaluated(&self) -> u64 {
@@ -599,6 +1233,47 @@ impl DeciderResultStats {
         self.num_undecided
     }
 
+    pub fn num_step_budget_exhausted(&self) -> u64 {
+        self.num_step_budget_exhausted
+    }
+
+    pub fn num_tape_limit_escalations(&self) -> u64 {
+        self.num_tape_limit_escalations
+    }
+
+    pub fn num_tape_memory_budget_skips(&self) -> u64 {
+        self.num_tape_memory_budget_skips
+    }
+
+    pub fn num_decider_retries(&self) -> u64 {
+        self.num_decider_retries
+    }
+
+    pub fn html_write_failures(&self) -> &HtmlWriteFailures {
+        &self.html_write_failures
+    }
+
+    pub fn warnings(&self) -> &Warnings {
+        &self.warnings
+    }
+
+    pub fn num_steps_simulated(&self) -> u64 {
+        self.num_steps_simulated
+    }
+
+    pub fn decider_stats(&self) -> &[DeciderStats] {
+        &self.decider_stats
+    }
+
+    pub fn tape_memory(&self) -> &TapeMemoryStats {
+        &self.tape_memory
+    }
+
+    /// Machines on which a decider panicked, see [DeciderResultStats::add_errored].
+    pub fn machines_errored(&self) -> &[MachineErrored] {
+        &self.machines_errored
+    }
+
     pub fn num_undecided_free(&self) -> usize {
         if self.limit_machines_undecided == 0
             || self.num_undecided >= self.limit_machines_undecided as u64
@@ -621,6 +1296,23 @@ impl DeciderResultStats {
         self.pre_decider_count
     }
 
+    /// Pre-decider checks which ran in the data provider, e.g. in the EnumeratorReduced.
+    pub fn pre_decider_timer_data_provider(&self) -> PreDeciderTimer {
+        self.pre_decider_timer_data_provider
+    }
+
+    /// Pre-decider checks which ran before the first decider, e.g. for the EnumeratorFull.
+    pub fn pre_decider_timer_decider(&self) -> PreDeciderTimer {
+        self.pre_decider_timer_decider
+    }
+
+    /// All pre-decider checks, whether they ran in the data provider or before the first decider.
+    pub fn pre_decider_timer(&self) -> PreDeciderTimer {
+        let mut timer = self.pre_decider_timer_data_provider;
+        timer.add_self(&self.pre_decider_timer_decider);
+        timer
+    }
+
     pub fn steps_max(&self) -> StepBig {
         self.steps_max.steps_max()
     }
@@ -635,15 +1327,29 @@ impl DeciderResultStats {
             names = "\n".to_string() + self.names.join(", ").as_str();
             String::new()
         };
+        // the pre-decider checks are part of the data provider or decider duration
+        let pre_decider = self.pre_decider_timer();
+        let duration_enumeration = self
+            .duration
+            .duration_data_provider
+            .saturating_sub(self.pre_decider_timer_data_provider.duration());
+        let duration_decider = self
+            .duration
+            .duration_decider
+            .saturating_sub(self.pre_decider_timer_decider.duration());
+        let locale = user_locale();
         format!(
-            "{}{names}\n{name} time elapsed for {} machines:\n Get machines {:?}, decider {}, total time {}.",
+            "{}{names}\n{name} time elapsed for {} machines:\n Get machines {}, pre-decider {} ({} checks, {}/s), decider {}, total time {}.",
             self,
-            self.num_evaluated.to_formatted_string(&user_locale()),
+            self.num_evaluated.to_formatted_string(&locale),
             // duration_as_ms_rounded(self.duration.duration_data_provider),
             // duration_as_ms_rounded(self.duration.duration_decider),
             // duration_as_ms_rounded(self.duration.duration_total),
-            format_duration_hhmmss_ms(self.duration.duration_data_provider, true),
-            format_duration_hhmmss_ms(self.duration.duration_decider, true),
+            format_duration_hhmmss_ms(duration_enumeration, true),
+            format_duration_hhmmss_ms(pre_decider.duration(), true),
+            pre_decider.num_checks().to_formatted_string(&locale),
+            (pre_decider.checks_per_sec() as u64).to_formatted_string(&locale),
+            format_duration_hhmmss_ms(duration_decider, true),
             format_duration_hhmmss_ms(self.duration.duration_total, true),
         )
     }
@@ -662,7 +1368,7 @@ impl Display for DeciderResultStats {
         writeln!(f, "Result BB{}: {}", self.n_states, self.end_reason)?;
         buf.write_formatted(&self.num_total_turing_machines(), &locale);
         s.push_str(format!("Turing machines:    {:>NUM_LONG_LEN$}\n", buf.as_str()).as_str());
-        if self.num_processed_total() != self.num_evaluated {
+        if self.num_processed_total_n_states() != self.num_evaluated {
             buf.write_formatted(&self.num_processed_total, &locale);
             s.push_str(format!("Total processed:    {:>NUM_LONG_LEN$}\n", buf.as_str()).as_str());
         }
@@ -676,6 +1382,29 @@ impl Display for DeciderResultStats {
             )
             .as_str(),
         );
+        if self.num_undecided > 0 && self.display.verbosity != ResultVerbosity::Summary {
+            s.push_str(format!("{}", self.undecided_count).as_str());
+        }
+        if self.num_step_budget_exhausted > 0 && self.display.verbosity != ResultVerbosity::Summary
+        {
+            buf.write_formatted(&self.num_step_budget_exhausted, &locale);
+            s.push_str(format!("    Step Budget used: {:>NUM_LONG_LEN$}\n", buf.as_str()).as_str());
+        }
+        if self.num_tape_limit_escalations > 0 && self.display.verbosity != ResultVerbosity::Summary
+        {
+            buf.write_formatted(&self.num_tape_limit_escalations, &locale);
+            s.push_str(format!("    Tape Escalations: {:>NUM_LONG_LEN$}\n", buf.as_str()).as_str());
+        }
+        if self.num_tape_memory_budget_skips > 0
+            && self.display.verbosity != ResultVerbosity::Summary
+        {
+            buf.write_formatted(&self.num_tape_memory_budget_skips, &locale);
+            s.push_str(format!("    Tape Budget used: {:>NUM_LONG_LEN$}\n", buf.as_str()).as_str());
+        }
+        if self.num_decider_retries > 0 && self.display.verbosity != ResultVerbosity::Summary {
+            buf.write_formatted(&self.num_decider_retries, &locale);
+            s.push_str(format!("    Decider Retries:  {:>NUM_LONG_LEN$}\n", buf.as_str()).as_str());
+        }
         buf.write_formatted(&self.num_halt, &locale);
         s.push_str(
             format!(
@@ -690,41 +1419,392 @@ impl Display for DeciderResultStats {
         // s.push_str(format!("  Not All States used:{:>13}\n", buf.as_str()).as_str());
         // buf.write_formatted(&self.num_non_halt, &locale);
         // s.push_str(format!("  Decided Non-Halt:  {:>NUM_LEN$}\n", buf.as_str()).as_str());
-        s.push_str(format!("{}", self.non_halt_count).as_str());
-        s.push_str(format!("{}", self.pre_decider_count).as_str());
-        s.push_str(format!("{}", self.steps_max).as_str());
+        if self.display.verbosity == ResultVerbosity::Summary {
+            buf.write_formatted(&self.non_halt_count.num_non_halt_total(), &locale);
+            s.push_str(
+                format!(
+                    "  {LEVEL_1_CHAR} Decided Non-Halt:  {:>NUM_LONG_LEN$}\n",
+                    buf.as_str()
+                )
+                .as_str(),
+            );
+            buf.write_formatted(&self.pre_decider_count.num_total(), &locale);
+            s.push_str(
+                format!(
+                    "  {LEVEL_1_CHAR} Eliminated Pre-Decider: {:>NUM_LONG_LEN$}\n",
+                    buf.as_str()
+                )
+                .as_str(),
+            );
+        } else {
+            s.push_str(format!("{}", self.non_halt_count).as_str());
+            s.push_str(format!("{}", self.pre_decider_count).as_str());
+        }
+        self.steps_max
+            .write_machines(&mut s, self.display.num_machines_max_steps())?;
+        if self.high_steps.is_active() {
+            self.high_steps
+                .write_machines(&mut s, self.display.num_machines_max_steps())?;
+        }
         write!(f, "{s}")?;
 
-        if let Some(machines) = self.machines_undecided.as_ref() {
-            writeln!(
-                f,
-                "  Undecided:             (Number of machines: {})",
-                self.num_undecided,
-            )?;
-            // print first undecided machines
-            // get first machines, but need to sort first for batches may come in other order
-            let mut v = machines.to_vec();
-            v.sort();
-            // format right aligned
-            let len = v.last().unwrap().id().to_formatted_string(&locale).len();
-            for m in v.iter().take(NUM_UNDECIDED_MACHINES_TO_DISPLAY_IN_RESULT) {
-                writeln!(
-                    f,
-                    "   Machine No. {:>len$}: {}, {}",
-                    m.id().to_formatted_string(&locale),
-                    m.to_standard_tm_text_format(),
-                    m.status()
-                )?;
+        let num_machines_undecided = self.display.num_machines_undecided();
+        if let Some(machines) = self
+            .machines_undecided
+            .as_ref()
+            .filter(|_| num_machines_undecided > 0)
+        {
+            writeln!(
+                f,
+                "  Undecided:             (Number of machines: {})",
+                self.num_undecided,
+            )?;
+            // print first undecided machines
+            // get first machines, but need to sort first for batches may come in other order
+            let mut list = sorted_by_id(machines);
+            list.truncate(num_machines_undecided);
+            list.write_machines(f, true)?;
+        };
+
+        #[cfg(feature = "counter_stats")]
+        if self.display.verbosity != ResultVerbosity::Summary {
+            write!(f, "{}", self.counter_stats)?;
+        }
+
+        if !self.decider_stats.is_empty() && self.display.verbosity != ResultVerbosity::Summary {
+            writeln!(
+                f,
+                "{:<20} {:>NUM_SHORT_LEN$} {:>NUM_SHORT_LEN$} {:>12} {:>NUM_SHORT_LEN$} {:>NUM_SHORT_LEN$} {:>NUM_LONG_LEN$}",
+                "Decider", "Machines", "Decided", "Time", "Machines/s", "Decided/s", "Steps/s"
+            )?;
+            for s in self.decider_stats.iter() {
+                writeln!(f, "{s}")?;
+            }
+        }
+        if !self.tape_memory.is_empty() && self.display.verbosity != ResultVerbosity::Summary {
+            write!(f, "{}", self.tape_memory)?;
+        }
+        if !self.html_write_failures.is_empty() {
+            writeln!(f, "Html write failures: {}", self.html_write_failures)?;
+        }
+        if !self.warnings.is_empty() {
+            write!(f, "{}", self.warnings)?;
+        }
+        if !self.machines_errored.is_empty() {
+            writeln!(
+                f,
+                "Machines errored (decider panicked): {}",
+                self.machines_errored.len()
+            )?;
+            // usually few machines, all are listed
+            if self.display.verbosity != ResultVerbosity::Summary {
+                for m in self.machines_errored.iter() {
+                    writeln!(f, "   {m}")?;
+                }
+            }
+        }
+
+        for section in self.n_states_sections.iter() {
+            writeln!(f)?;
+            write!(f, "{section}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Throughput of a decider of the chain, to compare the cost per decision of the deciders, e.g. to choose
+/// between raising the cycler limits and adding another decider. \
+/// In a threaded run the duration is the sum of all threads, so the rates are per thread.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeciderStats {
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::utils::deserialize_static_str")
+    )]
+    pub decider_name: DeciderName,
+    /// Machines passed to the decider, which are the undecided machines of the previous decider.
+    pub num_machines: u64,
+    pub num_decided: u64,
+    /// Steps run by the decider, as far as the machine status contains them.
+    pub num_steps: u64,
+    pub duration: Duration,
+}
+
+impl DeciderStats {
+    pub fn add_self(&mut self, other: &DeciderStats) {
+        self.num_machines += other.num_machines;
+        self.num_decided += other.num_decided;
+        self.num_steps += other.num_steps;
+        self.duration += other.duration;
+    }
+
+    fn per_sec(&self, value: u64) -> f64 {
+        let secs = self.duration.as_secs_f64();
+        if secs == 0.0 {
+            0.0
+        } else {
+            value as f64 / secs
+        }
+    }
+
+    pub fn machines_per_sec(&self) -> f64 {
+        self.per_sec(self.num_machines)
+    }
+
+    pub fn decided_per_sec(&self) -> f64 {
+        self.per_sec(self.num_decided)
+    }
+
+    pub fn steps_per_sec(&self) -> f64 {
+        self.per_sec(self.num_steps)
+    }
+}
+
+impl Display for DeciderStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        write!(
+            f,
+            "{:<20} {:>NUM_SHORT_LEN$} {:>NUM_SHORT_LEN$} {:>12} {:>NUM_SHORT_LEN$} {:>NUM_SHORT_LEN$} {:>NUM_LONG_LEN$}",
+            self.decider_name,
+            self.num_machines.to_formatted_string(&locale),
+            self.num_decided.to_formatted_string(&locale),
+            format_duration_hhmmss_ms(self.duration, true),
+            (self.machines_per_sec() as u64).to_formatted_string(&locale),
+            (self.decided_per_sec() as u64).to_formatted_string(&locale),
+            (self.steps_per_sec() as u64).to_formatted_string(&locale),
+        )
+    }
+}
+
+/// High-water mark of the tape memory of a run, the machine which caused it and the distribution of the
+/// final tape sizes of the decided machines. Used to size the memory budget and the tape size limits. \
+/// Only deciders with a growing tape report their memory, see [Decider::tape_memory_bytes](crate::decider::Decider::tape_memory_bytes).
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TapeMemoryStats {
+    /// Largest tape in bytes of all machines, decided or not.
+    pub max_bytes: usize,
+    /// First machine which required max_bytes.
+    pub max_bytes_machine: Option<MachineId>,
+    /// Number of decided machines per final tape size, bucket k holds the sizes 2^k..2^(k+1)-1 bytes.
+    pub histogram_decided: Vec<u64>,
+}
+
+impl TapeMemoryStats {
+    /// Histogram bucket of the tape size in bytes.
+    pub fn bucket(bytes: usize) -> usize {
+        bytes.max(1).ilog2() as usize
+    }
+
+    pub fn add(&mut self, machine: &MachineId, bytes: usize, is_decided: bool) {
+        if bytes > self.max_bytes {
+            self.max_bytes = bytes;
+            self.max_bytes_machine = Some(*machine);
+        }
+        if is_decided {
+            let bucket = Self::bucket(bytes);
+            if self.histogram_decided.len() <= bucket {
+                self.histogram_decided.resize(bucket + 1, 0);
+            }
+            self.histogram_decided[bucket] += 1;
+        }
+    }
+
+    pub fn add_self(&mut self, other: &TapeMemoryStats) {
+        if other.max_bytes > self.max_bytes {
+            self.max_bytes = other.max_bytes;
+            self.max_bytes_machine = other.max_bytes_machine;
+        }
+        if self.histogram_decided.len() < other.histogram_decided.len() {
+            self.histogram_decided
+                .resize(other.histogram_decided.len(), 0);
+        }
+        for (n, other_n) in self
+            .histogram_decided
+            .iter_mut()
+            .zip(other.histogram_decided.iter())
+        {
+            *n += other_n;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_bytes == 0
+    }
+}
+
+impl Display for TapeMemoryStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        write!(
+            f,
+            "Tape memory max: {} bytes",
+            self.max_bytes.to_formatted_string(&locale)
+        )?;
+        if let Some(machine) = self.max_bytes_machine.as_ref() {
+            if let Some(id) = machine.id_as_option() {
+                write!(f, ", machine No. {}", id.to_formatted_string(&locale))?;
+            }
+            write!(f, " {}", machine.to_standard_tm_text_format())?;
+        }
+        writeln!(f)?;
+        for (bucket, n) in self.histogram_decided.iter().enumerate() {
+            if *n == 0 {
+                continue;
+            }
+            let first: usize = if bucket == 0 { 0 } else { 1 << bucket };
+            let last: usize = (1 << (bucket + 1)) - 1;
+            let range = format!(
+                "{} - {} bytes:",
+                first.to_formatted_string(&locale),
+                last.to_formatted_string(&locale)
+            );
+            writeln!(
+                f,
+                "  Decided {range:<25} {:>NUM_LONG_LEN$}",
+                n.to_formatted_string(&locale)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A machine on which a decider panicked, e.g. on a todo!() of a rarely used code path.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MachineErrored {
+    pub machine: MachineId,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::utils::deserialize_static_str")
+    )]
+    pub decider_name: DeciderName,
+    /// Panic message.
+    pub message: String,
+}
+
+impl Display for MachineErrored {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(id) = self.machine.id_as_option() {
+            write!(f, "No. {} ", id.to_formatted_string(&user_locale()))?;
+        }
+        write!(
+            f,
+            "{} {}: {}",
+            self.machine.to_standard_tm_text_format(),
+            self.decider_name,
+            self.message
+        )
+    }
+}
+
+/// Kind of a non-fatal anomaly recorded in [Warnings].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WarningKind {
+    /// An option of the config could not be applied, e.g. html output without the feature 'enable_html_reports'.
+    Config,
+    /// The machine is still undecided because of the tape size after the tape size limit escalation
+    /// reached its cap, see [Config::tape_size_limit_escalation_cells].
+    TapeLimitEscalationCap,
+    /// The event log could not be written, see [EventLog](crate::event_log::EventLog).
+    EventLog,
+    /// The stats snapshot could not be written, see [StatsSnapshot](crate::stats_snapshot::StatsSnapshot).
+    StatsSnapshot,
+    /// The verdict log could not be written, see [VerdictLog](crate::verdict_log::VerdictLog).
+    VerdictLog,
+    /// The SQLite result store could not be written (feature 'sqlite'), see
+    /// [results_store_sqlite](crate::results_store_sqlite).
+    ResultsSqlite,
+    /// The checkpoint could not be written, see [Checkpoint](crate::checkpoint::Checkpoint).
+    Checkpoint,
+    /// The priority of a worker thread could not be lowered, see [Throttle](crate::throttle::Throttle).
+    Throttle,
+    /// Two deciders of the chain decided a machine as halting with different steps, see
+    /// [Config::compare_halt_steps].
+    HaltStepsMismatch,
+}
+
+/// A non-fatal anomaly with the number of occurrences and the message of the first occurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub count: u64,
+    /// First message of this kind, e.g. '1RB1LB_1LA---: tape size limit of 256 u32 blocks reached'.
+    pub first_message: String,
+}
+
+/// Collects the non-fatal anomalies of a run instead of printing them, so the library can run quietly.
+/// Each kind is kept once with its count and first message. The warnings are merged with the results
+/// of the batches and summarized at the end of the result.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Warnings {
+    warnings: Vec<Warning>,
+}
+
+impl Warnings {
+    /// Counts a warning of this kind. The message is only created for the first warning of this kind.
+    pub fn add(&mut self, kind: WarningKind, message: impl FnOnce() -> String) {
+        match self.warnings.iter_mut().find(|w| w.kind == kind) {
+            Some(w) => w.count += 1,
+            None => self.warnings.push(Warning {
+                kind,
+                count: 1,
+                first_message: message(),
+            }),
+        }
+    }
+
+    pub fn add_self(&mut self, other: &Warnings) {
+        for warning in other.warnings.iter() {
+            match self.warnings.iter_mut().find(|w| w.kind == warning.kind) {
+                Some(w) => w.count += warning.count,
+                None => self.warnings.push(warning.clone()),
             }
-        };
+        }
+    }
 
-        #[cfg(feature = "counter_stats")]
-        write!(f, "{}", self.counter_stats)?;
+    /// Number of warnings of this kind.
+    pub fn count(&self, kind: WarningKind) -> u64 {
+        self.warnings
+            .iter()
+            .find(|w| w.kind == kind)
+            .map_or(0, |w| w.count)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Warning> {
+        self.warnings.iter()
+    }
+}
 
+impl Display for Warnings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for w in self.warnings.iter() {
+            writeln!(
+                f,
+                "Warning {:?}: {} times, first: {}",
+                w.kind, w.count, w.first_message
+            )?;
+        }
         Ok(())
     }
 }
 
+fn sorted_by_id(machines: &[MachineInfo]) -> MachineInfoList {
+    let mut list = MachineInfoList::from(machines);
+    list.sort_by_id();
+    list
+}
+
 pub struct ResultBatchInfo {
     pub n_states: usize,
     pub steps_min: StepBig,
@@ -732,18 +1812,25 @@ pub struct ResultBatchInfo {
     pub limit_machines_undecided: usize,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct NonHaltCount {
     pub num_expanding_cycler: u64,
     pub num_expanding_bouncer: u64,
     pub num_only_one_direction: u64,
-    /// Eliminated machines which cannot halt because they have no halt conditions.
+    /// Machines which cannot halt because they have no halt transition, only counted if the
+    /// pre-decider does not run, e.g. for machines from a file.
     pub num_no_halt_transition: u64,
     /// Eliminated machines which cannot reach the maximum steps because they have two or more halt conditions.
     pub num_simple_start_cycle: u64,
     pub num_start_recursive: u64,
     pub num_writes_only_zeros: u64,
     pub num_cycle: u64,
+    pub num_rep_wl: u64,
+    pub num_translated_cycler: u64,
+    pub num_backward_unreachable: u64,
+    pub num_ctl: u64,
+    pub num_counter: u64,
+    pub num_bouncer_proof: u64,
     pub longest_cycle: StepSmall,
     pub detect_cycle_step_max: StepSmall,
 }
@@ -756,8 +1843,7 @@ impl NonHaltCount {
             NonHaltReason::ExpandingCycler => todo!(),
             // NonHaltReason::OnlyOneDirection => self.num_only_one_direction += 1,
             NonHaltReason::OnlyOneDirection => todo!(),
-            // NonHaltReason::NoHaltTransition => self.num_no_halt_transition += 1,
-            NonHaltReason::NoHaltTransition => todo!(),
+            NonHaltReason::NoHaltTransition => self.num_no_halt_transition += 1,
             // NonHaltReason::SimpleStartCycle => self.num_simple_start_cycle += 1,
             NonHaltReason::SimpleStartCycle => todo!(),
             // NonHaltReason::StartRecursive => self.num_start_recursive += 1,
@@ -767,6 +1853,12 @@ impl NonHaltCount {
             NonHaltReason::ExpandingBouncer(_) => self.num_expanding_bouncer += 1,
             // TODO steps? differentiate to expanding bouncer
             NonHaltReason::Bouncer(_) => self.num_expanding_bouncer += 1,
+            NonHaltReason::RepWL(_, _) => self.num_rep_wl += 1,
+            NonHaltReason::TranslatedCycler(_, _, _) => self.num_translated_cycler += 1,
+            NonHaltReason::BackwardUnreachable(_) => self.num_backward_unreachable += 1,
+            NonHaltReason::ClosedTapeLanguage(_) => self.num_ctl += 1,
+            NonHaltReason::Counter(_, _) => self.num_counter += 1,
+            NonHaltReason::BouncerProof(_, _) => self.num_bouncer_proof += 1,
             NonHaltReason::Cycler(steps, cycle_size) => {
                 self.num_cycle += 1;
                 if *cycle_size > self.longest_cycle {
@@ -796,6 +1888,12 @@ impl NonHaltCount {
         self.num_start_recursive += other.num_start_recursive;
         self.num_writes_only_zeros += other.num_writes_only_zeros;
         self.num_cycle += other.num_cycle;
+        self.num_rep_wl += other.num_rep_wl;
+        self.num_translated_cycler += other.num_translated_cycler;
+        self.num_backward_unreachable += other.num_backward_unreachable;
+        self.num_ctl += other.num_ctl;
+        self.num_counter += other.num_counter;
+        self.num_bouncer_proof += other.num_bouncer_proof;
         self.longest_cycle = other.longest_cycle.max(self.longest_cycle);
         self.detect_cycle_step_max = other.detect_cycle_step_max.max(self.detect_cycle_step_max);
     }
@@ -809,6 +1907,12 @@ impl NonHaltCount {
             + self.num_start_recursive
             + self.num_writes_only_zeros
             + self.num_cycle
+            + self.num_rep_wl
+            + self.num_translated_cycler
+            + self.num_backward_unreachable
+            + self.num_ctl
+            + self.num_counter
+            + self.num_bouncer_proof
     }
 }
 
@@ -823,8 +1927,14 @@ impl Display for NonHaltCount {
             "  {LEVEL_1_CHAR} Decided Non-Halt:  {:>NUM_LONG_LEN$}",
             buf.as_str()
         )?;
-        // buf.write_formatted(&self.num_no_halt_transition, &locale);
-        // s.push_str(format!("   No Halt Transition: {:>NUM_SHORT_LEN$}\n", buf.as_str()).as_str());
+        if self.num_no_halt_transition > 0 {
+            buf.write_formatted(&self.num_no_halt_transition, &locale);
+            writeln!(
+                f,
+                "     No Halt Transition:    {:>NUM_SHORT_LEN$}",
+                buf.as_str()
+            )?;
+        }
         // // s.push_str(
         // //     format!(
         // //         "   2+ Halt Transitions:{:10}\n",
@@ -858,6 +1968,54 @@ impl Display for NonHaltCount {
             "     Cycle:                 {:>NUM_SHORT_LEN$}",
             buf.as_str()
         )?;
+        if self.num_translated_cycler > 0 {
+            buf.write_formatted(&self.num_translated_cycler, &locale);
+            writeln!(
+                f,
+                "     Translated Cycle:      {:>NUM_SHORT_LEN$}",
+                buf.as_str()
+            )?;
+        }
+        if self.num_backward_unreachable > 0 {
+            buf.write_formatted(&self.num_backward_unreachable, &locale);
+            writeln!(
+                f,
+                "     Backward Unreachable:  {:>NUM_SHORT_LEN$}",
+                buf.as_str()
+            )?;
+        }
+        if self.num_ctl > 0 {
+            buf.write_formatted(&self.num_ctl, &locale);
+            writeln!(
+                f,
+                "     Closed Tape Language:  {:>NUM_SHORT_LEN$}",
+                buf.as_str()
+            )?;
+        }
+        if self.num_counter > 0 {
+            buf.write_formatted(&self.num_counter, &locale);
+            writeln!(
+                f,
+                "     Counter:               {:>NUM_SHORT_LEN$}",
+                buf.as_str()
+            )?;
+        }
+        if self.num_bouncer_proof > 0 {
+            buf.write_formatted(&self.num_bouncer_proof, &locale);
+            writeln!(
+                f,
+                "     Bouncer proof:         {:>NUM_SHORT_LEN$}",
+                buf.as_str()
+            )?;
+        }
+        if self.num_rep_wl > 0 {
+            buf.write_formatted(&self.num_rep_wl, &locale);
+            writeln!(
+                f,
+                "     RepWL:                 {:>NUM_SHORT_LEN$}",
+                buf.as_str()
+            )?;
+        }
         writeln!(
             f,
             "     - Longest Cycle:       {:>NUM_SHORT_LEN$}",
@@ -871,7 +2029,138 @@ impl Display for NonHaltCount {
     }
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+/// Number of undecided machines per [UndecidedReason]. The bound reasons count the side of the tape
+/// which was reached, see [UndecidedReason::TapeLimitLeftBoundReached].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndecidedCount {
+    pub num_step_limit: u64,
+    pub num_tape_left_bound: u64,
+    pub num_tape_right_bound: u64,
+    pub num_tape_size_limit: u64,
+    #[serde(default)]
+    pub num_time_limit: u64,
+    /// Deciders which gave up without a more specific reason, e.g. the closed tape language search.
+    #[serde(default)]
+    pub num_decider_no_result: u64,
+    #[serde(default)]
+    pub num_no_sinus_rhythm: u64,
+    #[serde(default)]
+    pub num_bouncer_pattern_too_complex: u64,
+    #[serde(default)]
+    pub num_decider_panicked: u64,
+    /// All other reasons.
+    pub num_other: u64,
+}
+
+impl UndecidedCount {
+    pub fn add_undecided_reason(&mut self, reason: &UndecidedReason) {
+        match reason {
+            UndecidedReason::StepLimit => self.num_step_limit += 1,
+            UndecidedReason::TapeLimitLeftBoundReached => self.num_tape_left_bound += 1,
+            UndecidedReason::TapeLimitRightBoundReached => self.num_tape_right_bound += 1,
+            UndecidedReason::TapeSizeLimit => self.num_tape_size_limit += 1,
+            UndecidedReason::TimeLimit => self.num_time_limit += 1,
+            UndecidedReason::DeciderNoResult => self.num_decider_no_result += 1,
+            UndecidedReason::NoSinusRhythmIdentified { .. } => self.num_no_sinus_rhythm += 1,
+            UndecidedReason::BouncerPatternTooComplex { .. } => {
+                self.num_bouncer_pattern_too_complex += 1
+            }
+            UndecidedReason::DeciderPanicked => self.num_decider_panicked += 1,
+            UndecidedReason::Undefined => self.num_other += 1,
+        }
+    }
+
+    /// Removes a machine counted by [Self::add_undecided_reason].
+    pub fn remove_undecided_reason(&mut self, reason: &UndecidedReason) {
+        let count = match reason {
+            UndecidedReason::StepLimit => &mut self.num_step_limit,
+            UndecidedReason::TapeLimitLeftBoundReached => &mut self.num_tape_left_bound,
+            UndecidedReason::TapeLimitRightBoundReached => &mut self.num_tape_right_bound,
+            UndecidedReason::TapeSizeLimit => &mut self.num_tape_size_limit,
+            UndecidedReason::TimeLimit => &mut self.num_time_limit,
+            UndecidedReason::DeciderNoResult => &mut self.num_decider_no_result,
+            UndecidedReason::NoSinusRhythmIdentified { .. } => &mut self.num_no_sinus_rhythm,
+            UndecidedReason::BouncerPatternTooComplex { .. } => {
+                &mut self.num_bouncer_pattern_too_complex
+            }
+            UndecidedReason::DeciderPanicked => &mut self.num_decider_panicked,
+            UndecidedReason::Undefined => &mut self.num_other,
+        };
+        *count = count.saturating_sub(1);
+    }
+
+    /// Removes a machine with unknown reason from the largest count.
+    pub fn remove_largest(&mut self) {
+        let count = [
+            &mut self.num_step_limit,
+            &mut self.num_tape_left_bound,
+            &mut self.num_tape_right_bound,
+            &mut self.num_tape_size_limit,
+            &mut self.num_time_limit,
+            &mut self.num_decider_no_result,
+            &mut self.num_no_sinus_rhythm,
+            &mut self.num_bouncer_pattern_too_complex,
+            &mut self.num_decider_panicked,
+            &mut self.num_other,
+        ]
+        .into_iter()
+        .max_by_key(|c| **c)
+        .unwrap();
+        *count = count.saturating_sub(1);
+    }
+
+    pub fn add_self(&mut self, other: &Self) {
+        self.num_step_limit += other.num_step_limit;
+        self.num_tape_left_bound += other.num_tape_left_bound;
+        self.num_tape_right_bound += other.num_tape_right_bound;
+        self.num_tape_size_limit += other.num_tape_size_limit;
+        self.num_time_limit += other.num_time_limit;
+        self.num_decider_no_result += other.num_decider_no_result;
+        self.num_no_sinus_rhythm += other.num_no_sinus_rhythm;
+        self.num_bouncer_pattern_too_complex += other.num_bouncer_pattern_too_complex;
+        self.num_decider_panicked += other.num_decider_panicked;
+        self.num_other += other.num_other;
+    }
+
+    pub fn num_total(&self) -> u64 {
+        self.num_step_limit
+            + self.num_tape_left_bound
+            + self.num_tape_right_bound
+            + self.num_tape_size_limit
+            + self.num_time_limit
+            + self.num_decider_no_result
+            + self.num_no_sinus_rhythm
+            + self.num_bouncer_pattern_too_complex
+            + self.num_decider_panicked
+            + self.num_other
+    }
+}
+
+impl Display for UndecidedCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        let mut buf = Buffer::default();
+        let counts = [
+            ("Step Limit:", self.num_step_limit),
+            ("Tape Left Bound:", self.num_tape_left_bound),
+            ("Tape Right Bound:", self.num_tape_right_bound),
+            ("Tape Size Limit:", self.num_tape_size_limit),
+            ("Time Limit:", self.num_time_limit),
+            ("Decider No Result:", self.num_decider_no_result),
+            ("No Sinus Rhythm:", self.num_no_sinus_rhythm),
+            ("Bouncer Too Complex:", self.num_bouncer_pattern_too_complex),
+            ("Decider Panicked:", self.num_decider_panicked),
+            ("Other:", self.num_other),
+        ];
+        for (name, count) in counts.iter().filter(|(_, count)| *count > 0) {
+            buf.write_formatted(count, &locale);
+            writeln!(f, "    - {name:<24}{:>NUM_SHORT_LEN$}", buf.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PreDeciderCount {
     // reference for percent calculation, halts the total number checked (not only pre-decider)
     pub num_checked_total_for_display: u64,
@@ -883,6 +2172,9 @@ pub struct PreDeciderCount {
     pub num_simple_start_cycle: u64,
     pub num_start_recursive: u64,
     pub num_writes_only_zero: u64,
+    /// Halts within the lookahead steps of the enumerator. These are not counted as halt.
+    pub num_lookahead_halt: u64,
+    pub num_lookahead_cycle: u64,
     // TODO num_halt or DeciderStats
 }
 
@@ -896,6 +2188,8 @@ impl PreDeciderCount {
         self.num_start_recursive += other.num_start_recursive;
         self.num_not_start_state_b_right += other.num_not_start_state_b_right;
         self.num_writes_only_zero += other.num_writes_only_zero;
+        self.num_lookahead_halt += other.num_lookahead_halt;
+        self.num_lookahead_cycle += other.num_lookahead_cycle;
     }
 
     pub fn num_total(&self) -> u64 {
@@ -907,6 +2201,8 @@ impl PreDeciderCount {
             + self.num_not_start_state_b_right
             + self.num_start_recursive
             + self.num_writes_only_zero
+            + self.num_lookahead_halt
+            + self.num_lookahead_cycle
     }
 }
 
@@ -1011,6 +2307,24 @@ impl Display for PreDeciderCount {
                     .as_str(),
                 );
             }
+            if self.num_lookahead_halt + self.num_lookahead_cycle != 0 {
+                buf.write_formatted(&self.num_lookahead_halt, &locale);
+                s.push_str(
+                    format!(
+                        "    - Lookahead Halt:              {:>NUM_SHORT_LEN$}\n",
+                        buf.as_str()
+                    )
+                    .as_str(),
+                );
+                buf.write_formatted(&self.num_lookahead_cycle, &locale);
+                s.push_str(
+                    format!(
+                        "    - Lookahead Cycle:             {:>NUM_SHORT_LEN$}\n",
+                        buf.as_str()
+                    )
+                    .as_str(),
+                );
+            }
         } else {
             s.push('\n');
         }
@@ -1020,6 +2334,7 @@ impl Display for PreDeciderCount {
 
 /// Duration of the enumerator/data provider tasks.
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DurationDataProvider {
     pub duration_data_provider: Duration,
     /// Duration of the decider tasks.
@@ -1029,11 +2344,14 @@ pub struct DurationDataProvider {
 }
 
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StepMaxResult {
     steps_max: StepBig,
     // steps_min: StepTypeBig,
     num_machines_steps_max: usize,
     machines_max_steps: Option<Vec<MachineInfo>>,
+    /// Only steps_max and num_machines_steps_max are kept, see [Config::counts_only].
+    counts_only: bool,
 }
 
 impl StepMaxResult {
@@ -1045,10 +2363,19 @@ impl StepMaxResult {
         }
     }
 
+    /// Records no machines, only the max steps and the number of machines with max steps.
+    pub fn with_counts_only(mut self, counts_only: bool) -> Self {
+        self.counts_only = counts_only;
+        self
+    }
+
     pub fn add_self(&mut self, other: &Self) {
         if other.steps_max >= self.steps_max {
             if other.steps_max == self.steps_max {
                 self.num_machines_steps_max += other.num_machines_steps_max;
+                if self.counts_only {
+                    return;
+                }
                 if let Some(machines) = other.machines_max_steps.as_ref() {
                     if self.machines_max_steps.is_none() {
                         self.machines_max_steps = Some(machines.clone());
@@ -1060,7 +2387,9 @@ impl StepMaxResult {
                 // new max
                 self.steps_max = other.steps_max;
                 self.num_machines_steps_max = other.num_machines_steps_max;
-                self.machines_max_steps = other.machines_max_steps.clone();
+                if !self.counts_only {
+                    self.machines_max_steps = other.machines_max_steps.clone();
+                }
             }
         }
     }
@@ -1068,6 +2397,14 @@ impl StepMaxResult {
     fn add_steps(&mut self, steps: StepBig, machine: &MachineId, status: &MachineStatus) {
         // Check biggerThan to avoid two ifs on every check as it occurs rarely
         if steps >= self.steps_max {
+            if self.counts_only {
+                if steps > self.steps_max {
+                    self.steps_max = steps;
+                    self.num_machines_steps_max = 0;
+                }
+                self.num_machines_steps_max += 1;
+                return;
+            }
             if steps == self.steps_max {
                 // store additional max step machine
                 if self.machines_max_steps.is_none() {
@@ -1124,14 +2461,8 @@ impl StepMaxResult {
     }
 
     /// Returns all recorded machines with max steps, sorted by id.
-    pub fn machines_max_steps_sorted(&self) -> Option<Vec<MachineInfo>> {
-        if let Some(machines) = self.machines_max_steps.as_ref() {
-            let mut v = machines.to_vec();
-            v.sort();
-            Some(v)
-        } else {
-            None
-        }
+    pub fn machines_max_steps_sorted(&self) -> Option<MachineInfoList> {
+        self.machines_max_steps.as_deref().map(sorted_by_id)
     }
 
     pub fn machines_max_steps_to_string(&self, return_max_machines: usize) -> String {
@@ -1184,8 +2515,13 @@ impl StepMaxResult {
     }
 }
 
-impl Display for StepMaxResult {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl StepMaxResult {
+    /// Writes max steps and the first max_machines machines with these steps.
+    pub fn write_machines(
+        &self,
+        f: &mut impl std::fmt::Write,
+        max_machines: usize,
+    ) -> std::fmt::Result {
         let locale = user_locale();
         writeln!(
             f,
@@ -1194,6 +2530,9 @@ impl Display for StepMaxResult {
             self.num_machines_steps_max,
         )?;
         // print first max step machines
+        if max_machines == 0 {
+            return Ok(());
+        }
         if self.num_machines_steps_max == 1 {
             if let Some(m) = self.machine_max_steps() {
                 writeln!(
@@ -1205,27 +2544,155 @@ impl Display for StepMaxResult {
             };
             return Ok(());
         }
-        if let Some(machines) = self.machines_max_steps_sorted() {
-            // format right aligned
-            let len = machines
-                .last()
-                .unwrap()
-                .id()
-                .to_formatted_string(&locale)
-                .len();
-            for m in machines.iter().take(NUM_MAX_MACHINES_TO_DISPLAY_IN_RESULT) {
-                writeln!(
-                    f,
-                    "   Machine No. {:>len$}: {}",
-                    m.id().to_formatted_string(&locale),
-                    m.to_standard_tm_text_format()
-                )?;
-            }
+        if let Some(mut machines) = self.machines_max_steps_sorted() {
+            machines.truncate(max_machines);
+            machines.write_machines(f, false)?;
         }
         Ok(())
     }
 }
 
+impl Display for StepMaxResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_machines(f, NUM_MAX_MACHINES_TO_DISPLAY_IN_RESULT)
+    }
+}
+
+/// Halting machines with at least threshold steps, see [Config::high_steps_threshold]. \
+/// The store is bounded by the limit, if full the machine with the lowest steps is dropped,
+/// so always the machines with the highest steps are kept. Dropped machines are counted as overflow.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HighStepsMachines {
+    /// 0 if disabled.
+    threshold: StepBig,
+    limit: usize,
+    machines: Vec<MachineInfo>,
+    /// All machines with high steps, including the overflow.
+    num_machines: u64,
+}
+
+impl HighStepsMachines {
+    pub fn new(threshold: StepBig, limit: usize) -> Self {
+        Self {
+            threshold,
+            limit,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.high_steps_threshold(),
+            config.limit_machines_high_steps(),
+        )
+    }
+
+    /// Empty store with the same threshold and limit.
+    pub fn new_same_limits(&self) -> Self {
+        Self::new(self.threshold, self.limit)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.threshold > 0
+    }
+
+    pub fn threshold(&self) -> StepBig {
+        self.threshold
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Number of machines with high steps, including the overflow.
+    pub fn num_machines(&self) -> u64 {
+        self.num_machines
+    }
+
+    /// Number of machines with high steps which were not stored because the limit was reached.
+    pub fn num_overflow(&self) -> u64 {
+        self.num_machines - self.machines.len() as u64
+    }
+
+    /// Recorded machines in the order found, see [Self::machines_sorted].
+    pub fn machines(&self) -> &[MachineInfo] {
+        &self.machines
+    }
+
+    /// Recorded machines sorted by steps descending, and by id for the same steps.
+    pub fn machines_sorted(&self) -> MachineInfoList {
+        let mut list = sorted_by_id(&self.machines);
+        list.sort_by_steps();
+        list
+    }
+
+    fn add_steps(&mut self, steps: StepBig, machine: &MachineId, status: &MachineStatus) {
+        if !self.is_active() || steps < self.threshold {
+            return;
+        }
+        self.add_machine_info(MachineInfo::from_machine_id(machine, status));
+    }
+
+    fn add_machine_info(&mut self, machine_info: MachineInfo) {
+        self.num_machines += 1;
+        if self.machines.len() < self.limit {
+            self.machines.push(machine_info);
+            return;
+        }
+        // replace the machine with the lowest steps, this is rare as the threshold is usually high
+        let steps = machine_info.status().steps().unwrap_or_default();
+        if let Some(lowest) = self
+            .machines
+            .iter_mut()
+            .min_by_key(|m| m.status().steps().unwrap_or_default())
+            .filter(|m| m.status().steps().unwrap_or_default() < steps)
+        {
+            *lowest = machine_info;
+        }
+    }
+
+    pub fn add_self(&mut self, other: &Self) {
+        if !self.is_active() {
+            return;
+        }
+        let num_machines = self.num_machines + other.num_machines;
+        for mi in other.machines.iter() {
+            if mi.status().steps().unwrap_or_default() >= self.threshold {
+                self.add_machine_info(*mi);
+            }
+        }
+        // the overflow of other is not contained in its machines
+        self.num_machines = num_machines;
+    }
+
+    /// Writes the number of machines with high steps and the first max_machines machines with the highest steps.
+    pub fn write_machines(
+        &self,
+        f: &mut impl std::fmt::Write,
+        max_machines: usize,
+    ) -> std::fmt::Result {
+        let locale = user_locale();
+        write!(
+            f,
+            "  High Steps:     {:>10} (Number of machines with steps >= {}",
+            self.num_machines.to_formatted_string(&locale),
+            self.threshold.to_formatted_string(&locale),
+        )?;
+        if self.num_overflow() > 0 {
+            write!(
+                f,
+                ", {} not recorded",
+                self.num_overflow().to_formatted_string(&locale)
+            )?;
+        }
+        writeln!(f, ")")?;
+        let mut machines = self.machines_sorted();
+        machines.truncate(max_machines);
+        machines.write_machines(f, true)
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct MachinesStates {
     /// All undecided machines of one batch run. \
@@ -1233,6 +2700,9 @@ pub struct MachinesStates {
     pub machines: Vec<MachineId>,
     /// The detailed MachineStatus which halts the UndecidedReason. State corresponds with the machine with the same index.
     pub states: Vec<MachineStatus>,
+    /// Hints of the deciders for the next decider, corresponding with the machine with the same index. \
+    /// Empty if [Config::decider_hints] is not set.
+    pub hints: Vec<DeciderHint>,
 }
 
 impl MachinesStates {
@@ -1240,6 +2710,7 @@ impl MachinesStates {
         Self {
             machines: Vec::with_capacity(capacity),
             states: Vec::with_capacity(capacity),
+            hints: Vec::new(),
         }
     }
 
@@ -1265,6 +2736,7 @@ impl MachinesStates {
 
 /// Result of a batch run with results for all machines in the batch.
 /// All undecided Turing machines are recorded in detail.
+#[derive(Debug)]
 pub struct BatchResult {
     pub result_decided: DeciderResultStats,
     pub machines_undecided: MachinesStates,
@@ -1288,6 +2760,14 @@ pub struct BatchData<'a> {
     pub decider_id: &'a DeciderId,
     pub run_predecider: PreDeciderRun,
     pub config: &'a Config,
+    /// Shared step budget per machine over the decider chain, 0 if not used. See [Config::step_budget_chain].
+    pub step_budget: StepBig,
+    /// Steps already used by the previous deciders of the chain for each machine in `machines`. \
+    /// Empty for the first decider or if no step budget is used.
+    pub steps_used: Vec<StepBig>,
+    /// Hints of the previous deciders of the chain for each machine in `machines`. \
+    /// Empty for the first decider or if [Config::decider_hints] is not set.
+    pub hints: Vec<DeciderHint>,
 }
 
 /// Result of a batch run with results for all machines in the batch.
@@ -1436,3 +2916,326 @@ fn fmt_array(arr: &[StepBig]) -> String {
     ));
     v.join("\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_binary::NotableMachineBinary;
+
+    #[test]
+    fn result_display_summary_has_no_breakdown() {
+        let config = Config::builder(4)
+            .result_verbosity(ResultVerbosity::Summary)
+            .build();
+        let result = DeciderResultStats::new(&config);
+        let s = result.to_string();
+        assert!(s.contains("Decided Non-Halt"));
+        assert!(!s.contains("Expanding Bouncer"));
+
+        let mut result = result;
+        result.set_display(ResultDisplay::default());
+        assert!(result.to_string().contains("Expanding Bouncer"));
+    }
+
+    #[test]
+    fn result_check_invariants() {
+        let config = Config::new_default(4);
+        let mut result = DeciderResultStats::new(&config);
+        assert_eq!(result.check_invariants(), Ok(()));
+        // an undecided machine without reason and not processed
+        result.num_undecided = 1;
+        assert!(result
+            .check_invariants()
+            .unwrap_err()
+            .contains("undecided reasons"));
+    }
+
+    /// Result with a machine in each category and eliminated machines in and before the decider.
+    fn result_all_categories(config: &Config) -> DeciderResultStats {
+        let mut result = DeciderResultStats::new(config);
+        let machine = NotableMachineBinary::BB3Max.machine_id();
+        result.add(&machine, &MachineStatus::DecidedHalt(21));
+        result.add(
+            &machine,
+            &MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(10, 2)),
+        );
+        result.add(
+            &machine,
+            &MachineStatus::Undecided(UndecidedReason::StepLimit, 100, 10),
+        );
+        result.add(
+            &machine,
+            &MachineStatus::EliminatedPreDecider(PreDeciderReason::NotAllStatesUsed),
+        );
+        result.add_pre_decider_count(&PreDeciderCount {
+            num_not_exactly_one_halt_condition: 2,
+            ..Default::default()
+        });
+        result.add_total(6);
+        result
+    }
+
+    #[test]
+    fn result_check_invariants_each_counter() {
+        use crate::decider::decider_engine::check_invariants;
+
+        let config = Config::builder(3).check_invariants(true).build();
+        let mut result = result_all_categories(&config);
+        assert!(!check_invariants(&config, &mut result, 7));
+        assert_eq!(result.end_reason, EndReason::None);
+
+        let corruptions: [fn(&mut DeciderResultStats); 12] = [
+            |r| r.num_processed_total += 1,
+            |r| r.num_evaluated += 1,
+            |r| r.num_halt += 1,
+            |r| r.num_non_halt += 1,
+            |r| r.num_undecided += 1,
+            |r| r.num_not_max += 1,
+            |r| r.num_eliminated_evaluated += 1,
+            |r| r.non_halt_count.num_cycle += 1,
+            |r| r.undecided_count.num_step_limit += 1,
+            |r| r.pre_decider_count.num_not_all_states_used += 1,
+            |r| r.steps_max.num_machines_steps_max += 1,
+            |r| r.num_halt -= 1,
+        ];
+        for (i, corrupt) in corruptions.iter().enumerate() {
+            let mut result = result_all_categories(&config);
+            corrupt(&mut result);
+            assert!(check_invariants(&config, &mut result, 7), "corruption {i}");
+            assert!(
+                matches!(result.end_reason, EndReason::InvariantViolated(7, _)),
+                "corruption {i}: {:?}",
+                result.end_reason
+            );
+        }
+    }
+
+    #[test]
+    fn result_decider_stats_per_decider() {
+        use crate::{
+            data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            decider::{
+                decider_engine::batch_run_decider_chain_data_provider_single_thread,
+                DeciderStandard,
+            },
+        };
+
+        let config = Config::builder(3).machine_limit(0).build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider);
+
+        let stats = result.decider_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].decider_name, deciders[0].decider_id().name);
+        assert_eq!(stats[0].num_machines, result.num_evaluated());
+        // the Hold decider gets the machines left undecided by the Cycler
+        assert_eq!(
+            stats[1].num_machines,
+            stats[0].num_machines - stats[0].num_decided
+        );
+        assert_eq!(
+            stats[0].num_decided + stats[1].num_decided + result.num_undecided(),
+            result.num_evaluated()
+        );
+        assert!(stats.iter().all(|s| s.num_steps > 0));
+        assert!(result.to_string().contains("Machines/s"));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn result_serde_round_trip() {
+        use crate::{
+            data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            decider::{
+                decider_engine::batch_run_decider_chain_data_provider_single_thread,
+                DeciderStandard,
+            },
+        };
+
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .limit_machines_undecided(5)
+            .build();
+        let deciders = [DeciderStandard::Cycler.decider_config(&config)];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider);
+
+        let json = serde_json::to_string(&result).unwrap();
+        let back: DeciderResultStats = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.num_undecided(), result.num_undecided());
+        assert_eq!(back.decider_stats(), result.decider_stats());
+        assert_eq!(back.to_string(), result.to_string());
+    }
+
+    #[test]
+    fn result_high_steps_machines_bounded() {
+        use crate::{
+            data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            decider::{
+                decider_engine::batch_run_decider_chain_data_provider_single_thread,
+                DeciderStandard,
+            },
+        };
+
+        let run = |limit: usize| {
+            let config = Config::builder(3)
+                .machine_limit(0)
+                .enumerator_reduced_batch_size_request(10_000)
+                .high_steps_threshold(15)
+                .limit_machines_high_steps(limit)
+                .build();
+            let deciders = [
+                DeciderStandard::Cycler.decider_config(&config),
+                DeciderStandard::Hold.decider_config(&config),
+            ];
+            let data_provider =
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+            batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider)
+        };
+        let result_all = run(10_000);
+        let all = result_all.high_steps_machines();
+        assert!(all.num_machines() > 2);
+        assert_eq!(all.num_overflow(), 0);
+        assert!(all
+            .machines()
+            .iter()
+            .all(|m| m.status().steps() >= Some(15)));
+        assert_eq!(
+            all.machines_sorted().machines()[0].status().steps(),
+            Some(21)
+        );
+
+        let result = run(2);
+        let high_steps = result.high_steps_machines();
+        assert_eq!(high_steps.num_machines(), all.num_machines());
+        assert_eq!(high_steps.num_overflow(), all.num_machines() - 2);
+        // the machines with the highest steps are kept
+        let steps: Vec<_> = high_steps
+            .machines_sorted()
+            .machines()
+            .iter()
+            .map(|m| m.status().steps())
+            .collect();
+        let steps_all: Vec<_> = all.machines_sorted().machines()[..2]
+            .iter()
+            .map(|m| m.status().steps())
+            .collect();
+        assert_eq!(steps, steps_all);
+        assert!(result.to_string().contains("not recorded"));
+    }
+
+    #[test]
+    fn result_counts_only_records_no_machines() {
+        use crate::{
+            data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            decider::{
+                decider_engine::batch_run_decider_chain_data_provider_single_thread,
+                DeciderStandard,
+            },
+        };
+
+        let run = |counts_only: bool| {
+            let config = Config::builder(3)
+                .machine_limit(0)
+                .enumerator_reduced_batch_size_request(10_000)
+                .limit_machines_undecided(100_000)
+                .high_steps_threshold(15)
+                .counts_only(counts_only)
+                .build();
+            let deciders = [
+                DeciderStandard::Cycler.decider_config(&config),
+                DeciderStandard::Hold.decider_config(&config),
+            ];
+            let data_provider =
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+            batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider)
+        };
+        let result_full = run(false);
+        let result = run(true);
+        assert!(result_full.machines_max_steps().is_some());
+        assert!(result_full.machines_undecided().is_some());
+        assert!(result.machines_max_steps().is_none());
+        assert!(result.machines_undecided().is_none());
+        assert_eq!(result.high_steps_machines().num_machines(), 0);
+        // the counters are identical
+        assert_eq!(result.steps_max(), 21);
+        assert_eq!(result.steps_max(), result_full.steps_max());
+        assert_eq!(result.num_undecided(), result_full.num_undecided());
+        assert_eq!(result.num_halt(), result_full.num_halt());
+        let num_max = result_full.machines_max_steps().unwrap().len();
+        assert!(result
+            .to_string()
+            .contains(&format!("(Number of machines: {num_max})")));
+    }
+
+    #[test]
+    fn result_tape_memory_high_water() {
+        use crate::{
+            config::TAPE_SIZE_INIT_CELL_BLOCKS,
+            data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            decider::{
+                decider_engine::batch_run_decider_chain_data_provider_single_thread,
+                DeciderStandard,
+            },
+        };
+
+        let config = Config::builder(3).machine_limit(0).build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider);
+
+        let tape_memory = result.tape_memory();
+        // BB3 machines do not grow the tape beyond its initial size
+        assert_eq!(tape_memory.max_bytes, TAPE_SIZE_INIT_CELL_BLOCKS * 4);
+        assert!(tape_memory.max_bytes_machine.is_some());
+        let num_decided: u64 = result.decider_stats().iter().map(|s| s.num_decided).sum();
+        assert_eq!(
+            tape_memory.histogram_decided.iter().sum::<u64>(),
+            num_decided
+        );
+        assert_eq!(
+            tape_memory.histogram_decided[TapeMemoryStats::bucket(tape_memory.max_bytes)],
+            num_decided
+        );
+        assert!(result.to_string().contains("Tape memory max: 256 bytes"));
+    }
+
+    #[test]
+    fn result_undecided_count_per_reason() {
+        let config = Config::builder(2).machine_limit(0).build();
+        let machine = MachineId::try_from("1RB1LB_1LA1RZ").unwrap();
+        let mut result = DeciderResultStats::new(&config);
+        let mut other = DeciderResultStats::new(&config);
+        for reason in [
+            UndecidedReason::TapeLimitLeftBoundReached,
+            UndecidedReason::TapeLimitRightBoundReached,
+            UndecidedReason::TapeLimitRightBoundReached,
+            UndecidedReason::StepLimit,
+        ] {
+            result.add(&machine, &MachineStatus::Undecided(reason, 100, 128));
+        }
+        other.add(
+            &machine,
+            &MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, 100, 3200),
+        );
+        result.add_result(&other);
+
+        let count = result.undecided_count();
+        assert_eq!(count.num_tape_left_bound, 1);
+        assert_eq!(count.num_tape_right_bound, 2);
+        assert_eq!(count.num_step_limit, 1);
+        assert_eq!(count.num_tape_size_limit, 1);
+        assert_eq!(count.num_total(), result.num_undecided());
+        assert!(result.to_string().contains("Tape Right Bound:"));
+    }
+}
@@ -4,12 +4,12 @@
 
 use std::fs::File;
 use std::io::Write;
-
-use chrono::{DateTime, Local, Utc};
+use std::path::Path;
 
 use crate::{
-    config::{Config, PATH_DATA},
+    config::Config,
     decider::decider_result::{BatchData, BatchResult, EndReason},
+    output_layout::create_dir,
     status::{MachineStatus, UndecidedReason},
 };
 
@@ -17,28 +17,30 @@ pub type FnResultWorker = fn(&mut BatchData) -> ResultWorker;
 pub type ResultWorker = std::result::Result<(), EndReason>;
 pub type ResultString = std::result::Result<(), String>;
 
+/// Appends the undecided machines to one text file per reason in the undecided directory of the
+/// [OutputLayout](crate::output_layout::OutputLayout::undecided_path), by default [PATH_DATA](crate::config::PATH_DATA).
 pub fn save_machines_undecided(batch_result: &BatchResult, config: &Config) -> ResultWorker {
     let machine_infos = batch_result.machines_undecided.to_machine_info();
 
-    let time_string = if config.use_local_time() {
-        let datetime_local: DateTime<Local> = config.creation_time().into();
-        datetime_local.format("%Y%m%d_%H%M%S").to_string()
-    } else {
-        let datetime_utc: DateTime<Utc> = config.creation_time().into();
-        datetime_utc.format("%Y%m%d_%H%M%S").to_string()
-    };
+    let time_string = config.creation_time_to_string();
 
     // thread::spawn(move || {
-    let path = PATH_DATA;
+    let path = config.output_layout().undecided_path();
+    if let Err(e) = create_dir(&path) {
+        return Err(EndReason::Error(
+            0,
+            format!("{e}: {}", path.to_string_lossy()),
+        ));
+    }
     let file_name =
         time_string.to_owned() + "_undecided_step_limit " + &batch_result.decider_name + ".txt";
-    let mut file_step_limit = open_file_for_append(path, &file_name)?;
+    let mut file_step_limit = open_file_for_append(&path, &file_name)?;
     let file_name =
         time_string.to_owned() + "_undecided_tape_bound " + &batch_result.decider_name + ".txt";
-    let mut file_tape_bound = open_file_for_append(path, &file_name)?;
+    let mut file_tape_bound = open_file_for_append(&path, &file_name)?;
     let file_name =
         time_string.to_owned() + "_undecided_other " + &batch_result.decider_name + ".txt";
-    // let mut file_other = open_file_for_append(path, &file_name)?;
+    // let mut file_other = open_file_for_append(&path, &file_name)?;
     let mut file_other = None;
 
     // save machines
@@ -49,15 +51,17 @@ pub fn save_machines_undecided(batch_result: &BatchResult, config: &Config) -> R
                 | UndecidedReason::TapeLimitRightBoundReached => {
                     writeln!(file_tape_bound, "{}: {}", batch_result.decider_name, mi)?
                 }
-                UndecidedReason::NoSinusRhythmIdentified => todo!(),
-                UndecidedReason::StepLimit => {
+                // the bouncer reports why the step limit was reached
+                UndecidedReason::StepLimit
+                | UndecidedReason::NoSinusRhythmIdentified { .. }
+                | UndecidedReason::BouncerPatternTooComplex { .. } => {
                     writeln!(file_step_limit, "{}: {}", batch_result.decider_name, mi)?
                 }
                 UndecidedReason::TapeSizeLimit => todo!(),
                 UndecidedReason::Undefined => todo!(),
                 _ => {
                     if file_other.is_none() {
-                        file_other = Some(open_file_for_append(path, &file_name)?);
+                        file_other = Some(open_file_for_append(&path, &file_name)?);
                     }
                     writeln!(
                         file_other.as_ref().unwrap(),
@@ -75,9 +79,9 @@ pub fn save_machines_undecided(batch_result: &BatchResult, config: &Config) -> R
     Ok(())
 }
 
-fn open_file_for_append(path: &str, file_name: &str) -> Result<File, EndReason> {
+fn open_file_for_append(path: &Path, file_name: &str) -> Result<File, EndReason> {
     // open file for append
-    let file_path = path.to_owned() + file_name;
+    let file_path = path.join(file_name);
     let r = std::fs::OpenOptions::new()
         .append(true)
         .create(true)
@@ -87,7 +91,7 @@ fn open_file_for_append(path: &str, file_name: &str) -> Result<File, EndReason>
         Ok(file) => Ok(file),
         Err(e) => Err(EndReason::Error(
             0,
-            e.to_string() + ":" + file_path.as_str(),
+            e.to_string() + ":" + &file_path.to_string_lossy(),
         )),
     }
 }
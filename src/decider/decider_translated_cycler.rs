@@ -0,0 +1,327 @@
+//! Translated cycler decider, which detects machines repeating the same tape pattern while drifting to one side. \
+//! The [DeciderCycler] only finds cycles where the tape returns to the identical content, so these machines
+//! (many of the BB5 holdouts) remain undecided there.
+//! How it works: \
+//! Each time the head reaches a new rightmost (or leftmost) cell, a record with step, state, head position
+//! and a snapshot of the tape behind the head is stored. For each record the farthest cell behind the head
+//! visited since then is tracked. \
+//! Two records R1 and R2 of the same side and state prove non-halting if the tape from the head back to the
+//! farthest cell visited between R1 and R2 is identical at both records. The cells in front of the head are
+//! blank at both records, so the machine repeats the run from R1 to R2 shifted by the distance of the
+//! two head positions, forever.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     config::Config,
+//!     decider::{decider_translated_cycler::DeciderTranslatedCycler, Decider},
+//!     machine_binary::MachineId,
+//!     status::{MachineStatus, NonHaltReason},
+//! };
+//!
+//! let config = Config::new_default(5);
+//! let machine = MachineId::try_from("1RB---_1LC0RB_1RD0LB_1RA0RE_0LD1RB").unwrap();
+//! let status = DeciderTranslatedCycler::decide_single_machine(&machine, &config);
+//! assert!(matches!(status, MachineStatus::DecidedNonHalt(NonHaltReason::TranslatedCycler(_, _, _))));
+//! ```
+//!
+//! [DeciderCycler]: crate::decider::decider_cycler::DeciderCycler
+
+use crate::{
+    certificate::{Certificate, CycleCertificate},
+    config::{Config, StepBig, StepSmall},
+    decider::{
+        self,
+        decider_result::{BatchData, ResultUnitEndReason},
+        scratch_buffer, Decider, DECIDER_TRANSLATED_CYCLER_ID,
+    },
+    machine_binary::{MachineId, State, Symbol},
+    status::{MachineStatus, NonHaltReason, UndecidedReason},
+};
+
+/// Maximum number of cells of the tape snapshot behind the head. A record can only prove a cycle
+/// if the machine did not run further back than this between the two records.
+const TAPE_BEHIND_CELLS_MAX: usize = 512;
+
+/// Head position, state and tape at the moment the head reached a new cell at one end of the tape.
+#[derive(Debug)]
+struct Record {
+    step: StepSmall,
+    state: u8,
+    head: usize,
+    /// Farthest position behind the head (smallest for the right end, largest for the left end)
+    /// visited since this record.
+    farthest_since: usize,
+    /// Tape from the head backwards, cell 0 is the head position.
+    tape_behind: Vec<u8>,
+    /// The snapshot reaches the end of the visited tape, all cells further behind are blank.
+    is_complete: bool,
+}
+
+impl Record {
+    /// Cell i behind the head, None if the snapshot is too short.
+    fn cell_behind(&self, i: usize) -> Option<u8> {
+        match self.tape_behind.get(i) {
+            Some(cell) => Some(*cell),
+            None if self.is_complete => Some(0),
+            None => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DeciderTranslatedCycler {
+    step_limit: StepBig,
+    /// Cells of the tape, the head starts in the middle so it cannot leave the tape within the step limit.
+    tape: Vec<u8>,
+    /// Smallest and largest visited tape position.
+    visited_min: usize,
+    visited_max: usize,
+    records_right: Vec<Record>,
+    records_left: Vec<Record>,
+    /// Cycle of the last machine, if it was decided as translated cycler.
+    certificate: Option<CycleCertificate>,
+}
+
+impl DeciderTranslatedCycler {
+    pub fn new(config: &Config) -> Self {
+        let step_limit = config.step_limit_decider_translated_cycler() as StepBig;
+        Self {
+            step_limit,
+            tape: Self::take_tape(step_limit),
+            visited_min: 0,
+            visited_max: 0,
+            records_right: scratch_buffer::take_vec(0),
+            records_left: scratch_buffer::take_vec(0),
+            certificate: None,
+        }
+    }
+
+    fn tape_len(step_limit: StepBig) -> usize {
+        2 * step_limit as usize + 3
+    }
+
+    /// Blank tape for the step limit, the buffer is taken from the arena of the worker thread.
+    fn take_tape(step_limit: StepBig) -> Vec<u8> {
+        let mut tape = scratch_buffer::take_vec(Self::tape_len(step_limit));
+        tape.resize(Self::tape_len(step_limit), 0);
+        tape
+    }
+
+    /// Clears the tape and records of the previous machine and returns the start position of the head.
+    fn reset(&mut self) -> usize {
+        if self.tape.len() < Self::tape_len(self.step_limit) {
+            scratch_buffer::return_vec(std::mem::take(&mut self.tape));
+            self.tape = Self::take_tape(self.step_limit);
+        } else if self.visited_max >= self.visited_min {
+            self.tape[self.visited_min..=self.visited_max].fill(0);
+        }
+        self.records_right.clear();
+        self.records_left.clear();
+        self.certificate = None;
+        let head = self.tape.len() / 2;
+        self.visited_min = head;
+        self.visited_max = head;
+        head
+    }
+
+    /// Stores a record for a new rightmost (is_right) or leftmost cell. Returns (period, shift) if the tape behind the
+    /// head matches an earlier record of the same state.
+    fn add_record(
+        &mut self,
+        step: StepSmall,
+        state: u8,
+        head: usize,
+        is_right: bool,
+    ) -> Option<(StepSmall, i32)> {
+        let records = if is_right {
+            &self.records_right
+        } else {
+            &self.records_left
+        };
+        let visited_len = if is_right {
+            head - self.visited_min + 1
+        } else {
+            self.visited_max - head + 1
+        };
+        let cell_behind = |i: usize| {
+            if is_right {
+                self.tape[head - i]
+            } else {
+                self.tape[head + i]
+            }
+        };
+        for r in records.iter().rev().filter(|r| r.state == state) {
+            // cells the machine read between the two records
+            let len = r.head.abs_diff(r.farthest_since) + 1;
+            if len > visited_len {
+                continue;
+            }
+            if (0..len).all(|i| r.cell_behind(i) == Some(cell_behind(i))) {
+                let shift = if is_right {
+                    (head - r.head) as i32
+                } else {
+                    -((r.head - head) as i32)
+                };
+                return Some((step - r.step, shift));
+            }
+        }
+
+        let snapshot_len = visited_len.min(TAPE_BEHIND_CELLS_MAX);
+        let record = Record {
+            step,
+            state,
+            head,
+            farthest_since: head,
+            tape_behind: (0..snapshot_len).map(cell_behind).collect(),
+            is_complete: snapshot_len == visited_len,
+        };
+        if is_right {
+            self.records_right.push(record);
+        } else {
+            self.records_left.push(record);
+        }
+        None
+    }
+
+    /// Updates the farthest cell behind the head for all records. The later the record, the smaller
+    /// the range since then, so only the last records need to be updated.
+    fn update_farthest(&mut self, head: usize) {
+        for r in self.records_right.iter_mut().rev() {
+            if r.farthest_since <= head {
+                break;
+            }
+            r.farthest_since = head;
+        }
+        for r in self.records_left.iter_mut().rev() {
+            if r.farthest_since >= head {
+                break;
+            }
+            r.farthest_since = head;
+        }
+    }
+}
+
+/// Returns the buffers to the arena of the worker thread for the decider of the next batch.
+impl Drop for DeciderTranslatedCycler {
+    fn drop(&mut self) {
+        scratch_buffer::return_vec(std::mem::take(&mut self.tape));
+        scratch_buffer::return_vec(std::mem::take(&mut self.records_right));
+        scratch_buffer::return_vec(std::mem::take(&mut self.records_left));
+    }
+}
+
+impl Decider for DeciderTranslatedCycler {
+    fn decider_id() -> &'static decider::DeciderId {
+        &DECIDER_TRANSLATED_CYCLER_ID
+    }
+
+    fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
+        let mut head = self.reset();
+        let mut state = 1;
+        let mut step: StepSmall = 0;
+        while step < self.step_limit {
+            let tr = machine
+                .machine()
+                .transition(State::new(state), Symbol::new(self.tape[head] as usize));
+            step += 1;
+            if tr.is_halt() {
+                return MachineStatus::DecidedHalt(step);
+            }
+            self.tape[head] = tr.is_symbol_one() as u8;
+            state = tr.state() as usize;
+            if tr.is_dir_right() {
+                head += 1;
+            } else {
+                head -= 1;
+            }
+            self.update_farthest(head);
+            let is_new_right = head > self.visited_max;
+            let is_new_left = head < self.visited_min;
+            if is_new_right {
+                self.visited_max = head;
+            } else if is_new_left {
+                self.visited_min = head;
+            }
+            if is_new_right || is_new_left {
+                if let Some((period, shift)) =
+                    self.add_record(step, state as u8, head, is_new_right)
+                {
+                    let status = MachineStatus::DecidedNonHalt(NonHaltReason::TranslatedCycler(
+                        step, period, shift,
+                    ));
+                    self.certificate = CycleCertificate::from_status(machine, &status);
+                    return status;
+                }
+            }
+        }
+
+        MachineStatus::Undecided(
+            UndecidedReason::StepLimit,
+            step,
+            (self.visited_max - self.visited_min + 1) as u32,
+        )
+    }
+
+    fn decide_single_machine(machine: &MachineId, config: &Config) -> MachineStatus {
+        let mut d = Self::new(config);
+        d.decide_machine(machine)
+    }
+
+    fn step_limit(&self) -> StepBig {
+        self.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.step_limit = step_limit;
+    }
+
+    fn proof_certificate(&self) -> Option<Certificate> {
+        self.certificate.map(Certificate::Cycle)
+    }
+
+    fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
+        let decider = Self::new(batch_data.config);
+        decider::decider_generic_run_batch(decider, batch_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decider::decider_cycler::DeciderCycler;
+
+    fn decide(tm: &str) -> MachineStatus {
+        let machine = MachineId::try_from(tm).unwrap();
+        let config = Config::new_default(machine.n_states());
+        DeciderTranslatedCycler::decide_single_machine(&machine, &config)
+    }
+
+    #[test]
+    fn decider_translated_cycler_undecided_of_cycler() {
+        let tm = "1RB---_1LC0RB_1RD0LB_1RA0RE_0LD1RB";
+        let machine = MachineId::try_from(tm).unwrap();
+        let status = DeciderCycler::decide_single_machine(&machine, &Config::new_default(5));
+        assert!(
+            matches!(status, MachineStatus::Undecided(_, _, _)),
+            "{status}"
+        );
+        assert_eq!(
+            decide(tm),
+            MachineStatus::DecidedNonHalt(NonHaltReason::TranslatedCycler(2158, 522, 16))
+        );
+    }
+
+    #[test]
+    fn decider_translated_cycler_left_and_halt() {
+        // runs left writing 1, 0 alternately
+        assert_eq!(
+            decide("1LB---_0LA1RA"),
+            MachineStatus::DecidedNonHalt(NonHaltReason::TranslatedCycler(3, 2, -2))
+        );
+        // BB4 Max
+        assert_eq!(
+            decide("1RB1LB_1LA0LC_1RZ1LD_1RD0RA"),
+            MachineStatus::DecidedHalt(107)
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Wall-clock time limit per machine, see [Config::time_limit_machine_ms]. \
+//! The step limits bound the work of a decider only if each step is cheap. A pathological machine with
+//! expensive steps (e.g. a growing long tape) can stall a thread for a long time, the time limit ends it with
+//! [UndecidedReason::TimeLimit](crate::status::UndecidedReason::TimeLimit) instead. \
+//! The clock is only read every [TIME_LIMIT_CHECK_STEPS] steps, so the check costs one comparison per step.
+//! Without time limit the comparison never triggers.
+//!
+//! [Config::time_limit_machine_ms]: crate::config::Config::time_limit_machine_ms
+
+use std::time::{Duration, Instant};
+
+use crate::config::{Config, StepBig};
+
+/// Steps between two reads of the clock.
+pub const TIME_LIMIT_CHECK_STEPS: StepBig = 1 << 16;
+
+/// Time limit of the machine currently decided, see [module documentation](self).
+#[derive(Debug, Clone, Copy)]
+pub struct MachineTimeLimit {
+    limit: Option<Duration>,
+    start: Instant,
+    /// Step at which the clock is read next, StepBig::MAX without time limit.
+    next_check_step: StepBig,
+}
+
+impl MachineTimeLimit {
+    pub fn new(config: &Config) -> Self {
+        let mut time_limit = Self {
+            limit: config.time_limit_machine(),
+            start: Instant::now(),
+            next_check_step: StepBig::MAX,
+        };
+        time_limit.start();
+        time_limit
+    }
+
+    /// Starts the clock for the next machine.
+    #[inline]
+    pub fn start(&mut self) {
+        if self.limit.is_some() {
+            self.start = Instant::now();
+            self.next_check_step = TIME_LIMIT_CHECK_STEPS;
+        }
+    }
+
+    /// Returns true if the time limit is exceeded at this step. The clock is only read every
+    /// [TIME_LIMIT_CHECK_STEPS] steps.
+    #[inline(always)]
+    pub fn is_exceeded(&mut self, step_no: StepBig) -> bool {
+        if step_no < self.next_check_step {
+            return false;
+        }
+        self.next_check_step = step_no.saturating_add(TIME_LIMIT_CHECK_STEPS);
+        self.limit
+            .is_some_and(|limit| self.start.elapsed() >= limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decider::{decider_halt_long::DeciderHaltLong, Decider},
+        machine_binary::MachineId,
+        status::{MachineStatus, UndecidedReason},
+    };
+
+    #[test]
+    fn time_limit_ends_endless_machine() {
+        // runs endlessly on two cells
+        let machine = MachineId::try_from("1RB1RB_0LA---").unwrap();
+        let config = Config::builder(2)
+            .step_limit_decider_halt(StepBig::MAX)
+            .time_limit_machine_ms(1)
+            .build();
+        let mut decider = DeciderHaltLong::new(&config);
+        let status = decider.decide_machine(&machine);
+        let MachineStatus::Undecided(UndecidedReason::TimeLimit, steps, _) = status else {
+            panic!("{status:?}");
+        };
+        assert!((TIME_LIMIT_CHECK_STEPS..StepBig::MAX).contains(&steps));
+
+        // the limit is started again for each machine
+        let status_2 = decider.decide_machine(&machine);
+        assert!(matches!(
+            status_2,
+            MachineStatus::Undecided(UndecidedReason::TimeLimit, _, _)
+        ));
+
+        let mut time_limit = MachineTimeLimit::new(&Config::new_default(2));
+        assert!(!time_limit.is_exceeded(StepBig::MAX - 1));
+    }
+}
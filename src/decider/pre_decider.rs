@@ -3,17 +3,102 @@
 //! and rules out >90% of the machines. \
 //! This is implemented in an even more efficient way in EnumeratorReduced (which should be used always).
 //! EnumeratorFull generates all machines and then can be filtered by this pre-decider first. Just call
-//! run_pre_decider(&machine) for this.
+//! run_pre_decider(&machine) for this. \
+//! [analyze] returns all applicable reasons for a single machine, e.g. for external tools. \
+//! [run_lookahead] is a small bounded simulator, which the EnumeratorReduced runs inline to eliminate
+//! machines halting or cycling within a few steps. \
+//! [PreDeciderTimer] measures the duration of the checks, so the report can show it separately from the
+//! enumeration and the deciders.
+
+use std::time::{Duration, Instant};
 
 use crate::{
     config::{StepBig, MAX_STATES},
-    machine_binary::MachineBinary,
-    status::{MachineStatus, PreDeciderReason},
+    machine_binary::{MachineBinary, State, Symbol},
+    status::{MachineStatus, NonHaltReason, PreDeciderReason},
     transition_binary::{TransitionBinary, TransitionType, STATE_HALT_BINARY, TRANSITIONS_FOR_A0},
 };
 
+/// Max steps of [run_lookahead], the tape is a single u64 with the head starting in the middle.
+pub const LOOKAHEAD_STEPS_MAX: usize = 31;
+
+/// Result of the bounded simulation [run_lookahead].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookaheadResult {
+    /// Halts with this many steps (including the halt transition).
+    Halt(StepBig),
+    /// A configuration (state, tape and head position) repeats, so the machine never halts.
+    Cycle,
+    /// No decision within the steps.
+    Undecided,
+}
+
+/// Only every n-th check is timed by [PreDeciderTimer], as reading the clock takes about as long as a check.
+pub const PRE_DECIDER_TIMER_SAMPLE_INTERVAL: u64 = 64;
+
+/// Duration and number of the pre-decider checks. To keep the overhead low, only every
+/// [PRE_DECIDER_TIMER_SAMPLE_INTERVAL]-th check is timed and the duration of all checks is extrapolated. \
+/// In a threaded run the duration is the sum of all threads.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PreDeciderTimer {
+    num_checks: u64,
+    num_sampled: u64,
+    duration_sampled: Duration,
+}
+
+impl PreDeciderTimer {
+    /// Runs the check, it is timed if it is a sample.
+    #[inline(always)]
+    pub fn measure<T>(&mut self, check: impl FnOnce() -> T) -> T {
+        let is_sample = self
+            .num_checks
+            .is_multiple_of(PRE_DECIDER_TIMER_SAMPLE_INTERVAL);
+        self.num_checks += 1;
+        if is_sample {
+            let start = Instant::now();
+            let result = check();
+            self.duration_sampled += start.elapsed();
+            self.num_sampled += 1;
+            result
+        } else {
+            check()
+        }
+    }
+
+    pub fn add_self(&mut self, other: &PreDeciderTimer) {
+        self.num_checks += other.num_checks;
+        self.num_sampled += other.num_sampled;
+        self.duration_sampled += other.duration_sampled;
+    }
+
+    /// Number of checked machines.
+    pub fn num_checks(&self) -> u64 {
+        self.num_checks
+    }
+
+    /// Estimated duration of all checks.
+    pub fn duration(&self) -> Duration {
+        if self.num_sampled == 0 {
+            return Duration::ZERO;
+        }
+        self.duration_sampled
+            .mul_f64(self.num_checks as f64 / self.num_sampled as f64)
+    }
+
+    pub fn checks_per_sec(&self) -> f64 {
+        let secs = self.duration().as_secs_f64();
+        if secs == 0.0 {
+            0.0
+        } else {
+            self.num_checks as f64 / secs
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PreDeciderRun {
+    /// Only [run_pre_decider_halt_transitions] runs, e.g. for machines supplied by a file.
     DoNotRun,
     RunNormalForward,
     RunStartBRightOnly,
@@ -126,6 +211,105 @@ pub fn run_pre_decider_simple(machine: &MachineBinary) -> MachineStatus {
     MachineStatus::NoDecision
 }
 
+/// Check for machines which skip the pre-decider, e.g. supplied by a file, where the transition table
+/// may have no or several halt transitions. \
+/// Without halt transition the machine never halts, which is returned as
+/// [NonHaltReason::NoHaltTransition]. Machines with several halt transitions are left to the deciders,
+/// which stop on whichever halt transition is reached first.
+/// Returns MachineStatus::NoDecision if the machine has a halt transition.
+#[inline(always)]
+pub fn run_pre_decider_halt_transitions(machine: &MachineBinary) -> MachineStatus {
+    if check_no_halt_transition(machine.transitions_used_eval()) {
+        return MachineStatus::DecidedNonHalt(NonHaltReason::NoHaltTransition);
+    }
+
+    MachineStatus::NoDecision
+}
+
+/// Runs all pre-decider checks on the transition table and returns every applicable reason, not only the
+/// first one like [run_pre_decider_strict]. An empty list means no check applies. \
+/// A machine with halt in A0 returns no reason, as it is decided as halt after 1 step.
+/// # Soundness
+/// - StartRecursive, SimpleStartCycle: The machine never halts.
+/// - NotExactlyOneHaltCondition: Without halt transition the machine never halts. With more than one it may halt, but
+///   cannot be a max machine.
+/// - OnlyOneDirection, WritesOnlyZero: The machine reads only 0, so it halts within n_states steps or never.
+/// - NotAllStatesUsed: Not all transitions are reachable, so the machine cannot be a max machine.
+/// - NotStartStateBRight: An equivalent machine with A0 0RB or 1RB exists (state renaming and mirroring).
+pub fn analyze(machine: &MachineBinary) -> Vec<PreDeciderReason> {
+    let mut reasons = Vec::new();
+    let t_start = machine.transition_start();
+    if t_start.is_halt() {
+        return reasons;
+    }
+
+    let is_recursive = check_start_transition_is_recursive(machine);
+    if is_recursive {
+        reasons.push(PreDeciderReason::StartRecursive);
+    }
+    if t_start != TRANSITIONS_FOR_A0[0] && t_start != TRANSITIONS_FOR_A0[1] {
+        reasons.push(PreDeciderReason::NotStartStateBRight);
+    }
+
+    let n_states = machine.n_states();
+    let tr_used = machine.transitions_used(n_states);
+    if count_halt_transitions(tr_used) != 1 {
+        reasons.push(PreDeciderReason::NotExactlyOneHaltCondition);
+    }
+    if check_only_one_direction(tr_used) {
+        reasons.push(PreDeciderReason::OnlyOneDirection);
+    }
+    if check_simple_start_cycle(machine) {
+        reasons.push(PreDeciderReason::SimpleStartCycle);
+    }
+    if check_only_zero_writes(tr_used) {
+        reasons.push(PreDeciderReason::WritesOnlyZero);
+    }
+    // requires A0 to lead to another state
+    if !is_recursive && check_not_all_states_used(machine, n_states) {
+        reasons.push(PreDeciderReason::NotAllStatesUsed);
+    }
+
+    reasons
+}
+
+/// Simulates the machine for at most `steps` steps (capped at [LOOKAHEAD_STEPS_MAX]) and classifies
+/// trivial halts and cycles. The configurations are compared after each step, so a cycle is only found
+/// if it does not move the tape (unlike the Cycler, which also finds translated cycles). \
+/// This needs no allocation and is cheap enough to run for each machine in the enumerator.
+pub fn run_lookahead(machine: &MachineBinary, steps: usize) -> LookaheadResult {
+    let steps = steps.min(LOOKAHEAD_STEPS_MAX);
+    let mut tape = 0u64;
+    // head in the middle, it cannot leave the u64 within LOOKAHEAD_STEPS_MAX steps
+    let mut pos = 32u32;
+    let mut state = State::A;
+    let mut configurations = [(State::HALT, 0u64, 0u32); LOOKAHEAD_STEPS_MAX + 1];
+    configurations[0] = (state, tape, pos);
+    for step in 1..=steps {
+        let tr = machine.transition(state, Symbol::new(((tape >> pos) & 1) as usize));
+        if tr.is_halt() {
+            return LookaheadResult::Halt(step as StepBig);
+        }
+        if tr.is_symbol_one() {
+            tape |= 1 << pos;
+        } else {
+            tape &= !(1 << pos);
+        }
+        if tr.is_dir_right() {
+            pos += 1;
+        } else {
+            pos -= 1;
+        }
+        state = tr.next_state();
+        let configuration = (state, tape, pos);
+        if configurations[..step].contains(&configuration) {
+            return LookaheadResult::Cycle;
+        }
+        configurations[step] = configuration;
+    }
+    LookaheadResult::Undecided
+}
+
 // All checks return true if the check condition is met, in other words an error is returned.
 
 /// Checks if the first transition A0 changes the state. If not, it will
@@ -219,13 +403,15 @@ pub fn count_halt_transitions(tr_used: &[TransitionBinary]) -> usize {
 pub fn check_simple_start_cycle(table: &MachineBinary) -> bool {
     let t_start = table.transition_start();
     let start_state = t_start.state_x2();
-    let tr_2nd = table.transition(start_state);
+    let tr_2nd = table.transition(t_start.next_state(), Symbol::ZERO);
     // 2nd needs to point back to A0 (0 is always the case)
     if tr_2nd.has_next_state_a() {
         if t_start.is_symbol_one() && tr_2nd.direction() == t_start.direction() {
             // case 3 and 4, also to left: true, else case 7, 8
             return true;
-        } else if tr_2nd.direction() == t_start.direction() || tr_2nd.is_symbol_zero() {
+        } else if t_start.is_symbol_zero()
+            && (tr_2nd.direction() == t_start.direction() || tr_2nd.is_symbol_zero())
+        {
             // case 1, 2, 5: true, 6: false
             return true;
         }
@@ -257,17 +443,33 @@ pub fn check_not_all_states_used(table: &MachineBinary, n_states: usize) -> bool
     let mut state_stack = [0; 10];
     let mut state_stack_size = 0;
     // follow state from A0 and look where it is going
-    let second_state_next_symbol_0 = table.transition(a0_state_next * 2).state() as usize;
+    let t_second = table.transition(State::new(a0_state_next), Symbol::ZERO);
+    let second_state_next_symbol_0 = t_second.state() as usize;
     if second_state_next_symbol_0 == STATE_HALT_BINARY as usize {
         return true;
     }
+    // The third step reads the symbol written by A0 if the head moved back, otherwise 0.
+    let t_start = table.transition_start();
+    let symbol_third = if t_second.direction() != t_start.direction() {
+        t_start.symbol_usize()
+    } else {
+        0
+    };
+    if table
+        .transition(
+            State::new(second_state_next_symbol_0),
+            Symbol::new(symbol_third),
+        )
+        .is_halt()
+    {
+        return true;
+    }
     // in this example mark C0 as used, but it is possible C is never visited again
     // example goes back to A, but from now on it is unclear if symbol on tape is 0 or 1
     // TODO (unless both have been writing 0)
-    let s0 = table.transition(second_state_next_symbol_0 * 2).state() as usize;
-    if s0 == STATE_HALT_BINARY as usize {
-        return true;
-    }
+    let s0 = table
+        .transition(State::new(second_state_next_symbol_0), Symbol::ZERO)
+        .state() as usize;
     // mark both fields as used
     states_used[second_state_next_symbol_0] = (true, true);
     state_fields_used += if second_state_next_symbol_0 == a0_state_next {
@@ -276,12 +478,15 @@ pub fn check_not_all_states_used(table: &MachineBinary, n_states: usize) -> bool
         2
     };
 
-    state_stack[state_stack_size] = s0;
-    state_stack_size += 1;
-    let s1 = table.transition(second_state_next_symbol_0 * 2 + 1).state() as usize;
+    if s0 != STATE_HALT_BINARY as usize {
+        state_stack[state_stack_size] = s0;
+        state_stack_size += 1;
+    }
+    let s1 = table
+        .transition(State::new(second_state_next_symbol_0), Symbol::ONE)
+        .state() as usize;
     if s0 != s1 && s1 != STATE_HALT_BINARY as usize {
-        state_stack[state_stack_size] =
-            table.transition(second_state_next_symbol_0 * 2 + 1).state() as usize;
+        state_stack[state_stack_size] = s1;
         state_stack_size += 1;
     }
     // now follow until all states have been evaluated
@@ -289,7 +494,7 @@ pub fn check_not_all_states_used(table: &MachineBinary, n_states: usize) -> bool
         let state = state_stack[state_stack_size - 1];
         state_stack_size -= 1;
         if !states_used[state].0 {
-            let s = table.transition(state * 2).state() as usize;
+            let s = table.transition(State::new(state), Symbol::ZERO).state() as usize;
             if s != STATE_HALT_BINARY as usize && s != state {
                 state_stack[state_stack_size] = s;
                 state_stack_size += 1;
@@ -298,7 +503,7 @@ pub fn check_not_all_states_used(table: &MachineBinary, n_states: usize) -> bool
             state_fields_used += 1;
         }
         if !states_used[state].1 {
-            let s = table.transition(state * 2 + 1).state() as usize;
+            let s = table.transition(State::new(state), Symbol::ONE).state() as usize;
             if s != STATE_HALT_BINARY as usize && s != state {
                 state_stack[state_stack_size] = s;
                 state_stack_size += 1;
@@ -510,10 +715,41 @@ pub fn moves_only_right_status(tr_used: &[TransitionBinary]) -> MachineStatus {
 #[cfg(test)]
 mod tests {
 
-    use crate::machine_binary::{MachineBinary, NotableMachineBinary};
+    use crate::{
+        config::Config,
+        data_provider::{
+            enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            DataProvider,
+        },
+        decider::{
+            decider_engine::decide_machines_statuses, decider_result::EndReason, DeciderStandard,
+        },
+        machine_binary::{MachineBinary, MachineId, NotableMachineBinary},
+    };
 
     use super::*;
 
+    #[test]
+    fn pre_decider_timer_extrapolates_samples() {
+        let machine = MachineBinary::try_from_standard_tm_text_format("1RB1LB_1LA1RZ").unwrap();
+        let mut timer = PreDeciderTimer::default();
+        for _ in 0..PRE_DECIDER_TIMER_SAMPLE_INTERVAL * 2 + 1 {
+            assert_eq!(
+                timer.measure(|| run_pre_decider_simple(&machine)),
+                MachineStatus::NoDecision
+            );
+        }
+        // first, 65th and 129th check are timed
+        assert_eq!((timer.num_checks(), timer.num_sampled), (129, 3));
+        assert_eq!(timer.duration(), timer.duration_sampled.mul_f64(43.0));
+
+        let mut sum = timer;
+        sum.add_self(&timer);
+        assert_eq!(sum.num_checks(), 258);
+        assert_eq!(sum.duration(), sum.duration_sampled.mul_f64(43.0));
+        assert_eq!(PreDeciderTimer::default().duration(), Duration::ZERO);
+    }
+
     #[test]
     fn check_pre_decider_states_can_be_switched() {
         // BB4 Max Steps:             107 (Number of machines: 2)
@@ -791,6 +1027,45 @@ mod tests {
         assert_eq!(check_result, true);
     }
 
+    #[test]
+    fn pre_decider_halt_transitions_for_all_deciders() {
+        let config = Config::new_default(4);
+        let no_halt = MachineBinary::try_from_standard_tm_text_format("1RB1LB_1LA1RA").unwrap();
+        let two_halts = MachineBinary::try_from_standard_tm_text_format("1RB---_1LA---").unwrap();
+        assert_eq!(
+            run_pre_decider_halt_transitions(&no_halt),
+            MachineStatus::DecidedNonHalt(NonHaltReason::NoHaltTransition)
+        );
+        assert_eq!(
+            run_pre_decider_halt_transitions(&two_halts),
+            MachineStatus::NoDecision
+        );
+
+        let machines = [
+            MachineId::new_no_id(no_halt),
+            MachineId::new_no_id(two_halts),
+        ];
+        for decider in DeciderStandard::ALL {
+            let statuses =
+                decide_machines_statuses(&[decider.decider_config(&config)], &machines).unwrap();
+            assert_eq!(
+                statuses[0],
+                MachineStatus::DecidedNonHalt(NonHaltReason::NoHaltTransition)
+            );
+            assert!(
+                !matches!(statuses[1], MachineStatus::DecidedNonHalt(_)),
+                "{}",
+                decider.decider_id().name
+            );
+        }
+        let statuses = decide_machines_statuses(
+            &[DeciderStandard::Hold.decider_config(&config)],
+            &machines[1..],
+        )
+        .unwrap();
+        assert_eq!(statuses[0].steps(), Some(3));
+    }
+
     #[test]
     fn check_pre_decider_simple_start_cycle() {
         // check does not apply
@@ -819,6 +1094,129 @@ mod tests {
         assert_eq!(check_result, true);
     }
 
+    /// Property test: Every reason returned by analyze must hold when the machine is simulated.
+    /// All BB2 machines and every 37th BB3 machine are checked. A machine which did not halt within
+    /// the step limit is regarded as non-halting, as BB3 halts after 21 steps at most.
+    #[test]
+    fn analyze_reasons_are_sound() {
+        for (n_states, step) in [(2, 1), (3, 37)] {
+            let config = Config::builder(n_states).machine_limit(0).build();
+            let mut enumerator =
+                EnumeratorBinary::new(EnumeratorType::EnumeratorFullForward, &config);
+            let mut num_checked = 0;
+            loop {
+                let data = enumerator.machine_batch_next().unwrap();
+                for m in data.machines.iter().step_by(step) {
+                    assert_analyze_sound(m.machine());
+                    num_checked += 1;
+                }
+                if data.end_reason == EndReason::IsLastBatch {
+                    break;
+                }
+            }
+            assert!(num_checked > 6_000);
+        }
+    }
+
+    #[test]
+    fn lookahead_halt_and_cycle() {
+        let machine = NotableMachineBinary::BB3Max.machine();
+        assert_eq!(run_lookahead(&machine, 20), LookaheadResult::Undecided);
+        assert_eq!(run_lookahead(&machine, 21), LookaheadResult::Halt(21));
+        // A0 halts in step 1
+        let machine = MachineBinary::try_from("---1RB_1LA1RA").unwrap();
+        assert_eq!(run_lookahead(&machine, 20), LookaheadResult::Halt(1));
+        // A0 and B0 move back and forth on an empty tape
+        let machine = MachineBinary::try_from("0RB---_0LA1RA").unwrap();
+        assert_eq!(run_lookahead(&machine, 20), LookaheadResult::Cycle);
+        // translated cycle, found by the Cycler only
+        let machine = MachineBinary::try_from("1RB---_1RA1RA").unwrap();
+        assert_eq!(run_lookahead(&machine, 20), LookaheadResult::Undecided);
+    }
+
+    #[test]
+    fn analyze_returns_all_reasons() {
+        // starts recursive, only right, writes only zero and has no halt transition
+        let machine = MachineBinary::try_from("0RA1RB_0RA1RB").unwrap();
+        let reasons = analyze(&machine);
+        for r in [
+            PreDeciderReason::StartRecursive,
+            PreDeciderReason::NotStartStateBRight,
+            PreDeciderReason::NotExactlyOneHaltCondition,
+            PreDeciderReason::OnlyOneDirection,
+            PreDeciderReason::WritesOnlyZero,
+        ] {
+            assert!(reasons.contains(&r), "{r:?} missing in {reasons:?}");
+        }
+
+        let machine = NotableMachineBinary::BB5Max.machine();
+        assert!(analyze(&machine).is_empty());
+        let machine = MachineBinary::try_from("---1RB_1LA1RA").unwrap();
+        assert!(analyze(&machine).is_empty());
+    }
+
+    fn assert_analyze_sound(machine: &MachineBinary) {
+        const STEP_LIMIT: usize = 200;
+        let reasons = analyze(machine);
+        // the first reason of the strict pre-decider is always included
+        match run_pre_decider_strict(machine) {
+            MachineStatus::EliminatedPreDecider(r) => assert!(reasons.contains(&r)),
+            _ => assert!(reasons.is_empty()),
+        }
+        if reasons.is_empty() {
+            return;
+        }
+
+        let n_states = machine.n_states();
+        let (steps_halt, fields_visited) = simulate(machine, STEP_LIMIT);
+        let text = machine.to_standard_tm_text_format();
+        for r in reasons {
+            let sound = match r {
+                PreDeciderReason::StartRecursive | PreDeciderReason::SimpleStartCycle => {
+                    steps_halt.is_none()
+                }
+                PreDeciderReason::NotExactlyOneHaltCondition => {
+                    count_halt_transitions(machine.transitions_used(n_states)) > 1
+                        || steps_halt.is_none()
+                }
+                PreDeciderReason::OnlyOneDirection | PreDeciderReason::WritesOnlyZero => {
+                    steps_halt.is_none_or(|steps| steps <= n_states)
+                }
+                PreDeciderReason::NotAllStatesUsed => fields_visited < n_states * 2,
+                PreDeciderReason::NotStartStateBRight | PreDeciderReason::None => true,
+                // not returned by analyze
+                PreDeciderReason::LookaheadHalt | PreDeciderReason::LookaheadCycle => false,
+            };
+            assert!(sound, "{r:?} is wrong for {text}");
+        }
+    }
+
+    /// Simple simulation returning the halt step (if halted within the step limit)
+    /// and the number of distinct transitions used.
+    fn simulate(machine: &MachineBinary, step_limit: usize) -> (Option<usize>, usize) {
+        let mut tape = vec![0usize; step_limit * 2 + 1];
+        let mut pos = step_limit;
+        let mut state = 1;
+        let mut used = [false; (MAX_STATES + 1) * 2];
+        for step in 1..=step_limit {
+            let field = state * 2 + tape[pos];
+            used[field] = true;
+            let tr = machine.transition_array_id(field);
+            if tr.is_halt() {
+                return (Some(step), used.iter().filter(|u| **u).count());
+            }
+            tape[pos] = tr.is_symbol_one() as usize;
+            if tr.is_dir_right() {
+                pos += 1;
+            } else {
+                pos -= 1;
+            }
+            state = tr.state() as usize;
+        }
+
+        (None, used.iter().filter(|u| **u).count())
+    }
+
     #[test]
     fn check_pre_decider_no_decision() {
         // check does not apply
@@ -0,0 +1,91 @@
+//! Reusable scratch buffers of the deciders per worker thread. \
+//! The deciders are created for each batch and record the steps of a machine in Vecs sized to the step limit.
+//! Within a batch these are only cleared for the next machine, but each new decider allocated them again.
+//! The deciders now take their buffers from the arena of the thread with [take_vec] and return them on drop
+//! with [return_vec], so in a batch run the buffers are allocated once per thread and decider type. \
+//! The buffers are returned empty, but keep their capacity. At most [NUM_BUFFERS_PER_TYPE_MAX] buffers of the
+//! same type are kept, so a decider with many buffers (e.g. the maps of the cycler) can run next to another one.
+//! # Example
+//! ```
+//! use bb_challenge::decider::{scratch_buffer, step_record::StepRecordU128};
+//!
+//! let steps: Vec<StepRecordU128> = scratch_buffer::take_vec(1_000);
+//! // ... decide machines, clear steps for each machine
+//! scratch_buffer::return_vec(steps);
+//! ```
+
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+};
+
+use hashbrown::HashMap;
+
+/// Number of buffers kept per element type and thread.
+pub const NUM_BUFFERS_PER_TYPE_MAX: usize = 32;
+
+thread_local! {
+    /// Returned buffers of this thread by element type, each entry is a `Vec<T>`.
+    static ARENA: RefCell<HashMap<TypeId, Vec<Box<dyn Any>>>> = RefCell::new(HashMap::new());
+}
+
+/// Takes an empty buffer with at least this capacity from the arena of this thread,
+/// or allocates a new one if none was returned.
+pub fn take_vec<T: 'static>(capacity: usize) -> Vec<T> {
+    let buffer = ARENA.with_borrow_mut(|arena| {
+        arena
+            .get_mut(&TypeId::of::<Vec<T>>())
+            .and_then(|buffers| buffers.pop())
+    });
+    match buffer.and_then(|b| b.downcast::<Vec<T>>().ok()) {
+        Some(mut buffer) => {
+            buffer.reserve(capacity);
+            *buffer
+        }
+        None => Vec::with_capacity(capacity),
+    }
+}
+
+/// Returns the buffer to the arena of this thread, the elements are dropped.
+pub fn return_vec<T: 'static>(mut buffer: Vec<T>) {
+    if buffer.capacity() == 0 {
+        return;
+    }
+    buffer.clear();
+    // try_with: the arena may already be destroyed when a decider is dropped at thread exit
+    let _ = ARENA.try_with(|arena| {
+        let mut arena = arena.borrow_mut();
+        let buffers = arena.entry(TypeId::of::<Vec<T>>()).or_default();
+        if buffers.len() < NUM_BUFFERS_PER_TYPE_MAX {
+            buffers.push(Box::new(buffer));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_is_reused() {
+        let mut buffer: Vec<u64> = take_vec(100);
+        buffer.extend([1, 2, 3]);
+        let ptr = buffer.as_ptr();
+        return_vec(buffer);
+
+        let buffer: Vec<u64> = take_vec(10);
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 100);
+        assert_eq!(buffer.as_ptr(), ptr);
+        // other element type
+        let other: Vec<u32> = take_vec(10);
+        assert_ne!(other.as_ptr() as usize, ptr as usize);
+
+        // the arena keeps a limited number of buffers
+        for _ in 0..NUM_BUFFERS_PER_TYPE_MAX + 5 {
+            return_vec::<u16>(Vec::with_capacity(1));
+        }
+        let num_buffers = ARENA.with_borrow(|arena| arena[&TypeId::of::<Vec<u16>>()].len());
+        assert_eq!(num_buffers, NUM_BUFFERS_PER_TYPE_MAX);
+    }
+}
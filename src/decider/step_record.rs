@@ -10,6 +10,10 @@ pub struct StepRecordU128 {
     pub direction: DirectionType,
     /// tape before the current transition was executed
     pub tape_before: u128,
+    /// Digest of the whole tape before the current transition was executed, see
+    /// [TapeDigest](crate::tape::tape_utils::TapeDigest). 0 as long as the long tape is not used, then tape_before
+    /// is the whole tape.
+    pub tape_digest: u128,
     #[cfg(all(debug_assertions, feature = "debug_cycler"))]
     #[allow(dead_code)]
     text: [char; 3],
@@ -22,6 +26,7 @@ impl StepRecordU128 {
             for_field_id,
             direction,
             tape_before,
+            tape_digest: 0,
             #[cfg(all(debug_assertions, feature = "debug_cycler"))]
             text: Self::to_chars(for_field_id, direction),
         }
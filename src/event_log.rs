@@ -0,0 +1,396 @@
+//! Append-only event log of a run, one JSON object per line (JSONL). \
+//! The engine writes timestamped events: start and end of the run, each new max steps machine,
+//! each full percent of progress and stage transitions like the end of the enumeration. Other parts
+//! (e.g. checkpoints) can log their own events with [EventLog::log]. Each line is flushed immediately,
+//! so the log survives an aborted run and allows to reconstruct the timeline of a run for reports. \
+//! The log is written if [Config::write_event_log] is set, the file is [OutputLayout::event_log_file_path].
+//! # Example
+//! ```text
+//! {"time":"2025-06-01T12:00:00.000+02:00","elapsed_ms":0,"event":"run_start","n_states":4,"num_machines":6975757441,"transition_order":"direction-major","num_threads":1,"deciders":["Decider Cycler","Decider Halt"]}
+//! {"time":"2025-06-01T12:00:00.113+02:00","elapsed_ms":113,"event":"new_max_steps","steps":107,"machine":"1RB1LB_1LA0LC_---1LD_1RD0RA"}
+//! {"time":"2025-06-01T12:00:00.250+02:00","elapsed_ms":250,"event":"progress","percent":1,"processed":69757575}
+//! ```
+//!
+//! [OutputLayout::event_log_file_path]: crate::output_layout::OutputLayout::event_log_file_path
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use chrono::Local;
+
+use crate::{
+    config::{Config, StepBig},
+    decider::{
+        decider_result::{DeciderResultStats, WarningKind},
+        DeciderConfig,
+    },
+    output_layout::create_dir,
+    transition_binary::TransitionOrder,
+};
+
+/// Event of a run, see [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunEvent {
+    RunStart {
+        n_states: usize,
+        num_machines: u64,
+        /// Order of the transitions in the enumeration, which defines the machine ids.
+        transition_order: TransitionOrder,
+        /// Effective number of threads of the run, see [Config::num_threads_effective].
+        num_threads: usize,
+        deciders: Vec<String>,
+    },
+    /// A halting machine with more steps than all machines before was found.
+    NewMaxSteps {
+        steps: StepBig,
+        machine: String,
+    },
+    /// Another full percent of the machines was processed.
+    Progress {
+        percent: u32,
+        processed: u64,
+    },
+    /// The run reached a new stage, e.g. 'enumeration_finished'.
+    Stage {
+        name: String,
+    },
+    CheckpointWritten {
+        path: String,
+    },
+    RunEnd {
+        processed: u64,
+        num_halt: u64,
+        num_non_halt: u64,
+        num_undecided: u64,
+        steps_max: StepBig,
+    },
+}
+
+impl RunEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RunEvent::RunStart { .. } => "run_start",
+            RunEvent::NewMaxSteps { .. } => "new_max_steps",
+            RunEvent::Progress { .. } => "progress",
+            RunEvent::Stage { .. } => "stage",
+            RunEvent::CheckpointWritten { .. } => "checkpoint_written",
+            RunEvent::RunEnd { .. } => "run_end",
+        }
+    }
+
+    /// Fields of the event as JSON members, without braces.
+    fn json_fields(&self) -> String {
+        match self {
+            RunEvent::RunStart {
+                n_states,
+                num_machines,
+                transition_order,
+                num_threads,
+                deciders,
+            } => {
+                let deciders: Vec<String> = deciders.iter().map(|d| json_string(d)).collect();
+                format!(
+                    "\"n_states\":{n_states},\"num_machines\":{num_machines},\
+                    \"transition_order\":{},\"num_threads\":{num_threads},\"deciders\":[{}]",
+                    json_string(transition_order.name()),
+                    deciders.join(",")
+                )
+            }
+            RunEvent::NewMaxSteps { steps, machine } => {
+                format!("\"steps\":{steps},\"machine\":{}", json_string(machine))
+            }
+            RunEvent::Progress { percent, processed } => {
+                format!("\"percent\":{percent},\"processed\":{processed}")
+            }
+            RunEvent::Stage { name } => format!("\"name\":{}", json_string(name)),
+            RunEvent::CheckpointWritten { path } => format!("\"path\":{}", json_string(path)),
+            RunEvent::RunEnd {
+                processed,
+                num_halt,
+                num_non_halt,
+                num_undecided,
+                steps_max,
+            } => format!(
+                "\"processed\":{processed},\"num_halt\":{num_halt},\"num_non_halt\":{num_non_halt},\
+                \"num_undecided\":{num_undecided},\"steps_max\":{steps_max}"
+            ),
+        }
+    }
+}
+
+/// Returns the text as JSON string including the quotes.
+pub(crate) fn json_string(text: &str) -> String {
+    let mut s = String::with_capacity(text.len() + 2);
+    s.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            c if (c as u32) < 0x20 => s.push_str(&format!("\\u{:04x}", c as u32)),
+            c => s.push(c),
+        }
+    }
+    s.push('"');
+    s
+}
+
+/// Event log file of a run, see [module documentation](self).
+#[derive(Debug)]
+pub struct EventLog {
+    file_path: PathBuf,
+    writer: BufWriter<File>,
+    start: Instant,
+    /// Machines to process for the progress, 0 if unknown.
+    total: u64,
+    percent_logged: u32,
+    steps_max_logged: StepBig,
+}
+
+impl EventLog {
+    /// Opens the log for appending, the directory is created if required.
+    pub fn create(file_path: impl Into<PathBuf>, total: u64) -> io::Result<Self> {
+        let file_path = file_path.into();
+        if let Some(dir) = file_path.parent() {
+            create_dir(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)?;
+        Ok(Self {
+            file_path,
+            writer: BufWriter::new(file),
+            start: Instant::now(),
+            total,
+            percent_logged: 0,
+            steps_max_logged: 0,
+        })
+    }
+
+    /// Opens the log of the output layout if [Config::write_event_log] is set and logs the run start. \
+    /// Returns None if the log is not configured. If it cannot be written, a warning is added to the result.
+    pub(crate) fn start_run(
+        decider_configs: &[DeciderConfig],
+        total: u64,
+        num_threads: usize,
+        result: &mut DeciderResultStats,
+    ) -> Option<Self> {
+        let config: &Config = decider_configs.first()?.config();
+        if !config.write_event_log() {
+            return None;
+        }
+        let file_path = config.output_layout().event_log_file_path(config);
+        let event = RunEvent::RunStart {
+            n_states: config.n_states(),
+            num_machines: total,
+            transition_order: config.transition_order(),
+            num_threads,
+            deciders: decider_configs
+                .iter()
+                .map(|dc| dc.decider_id().name.to_string())
+                .collect(),
+        };
+        match Self::create(&file_path, total).and_then(|mut log| log.log(&event).map(|_| log)) {
+            Ok(log) => Some(log),
+            Err(e) => {
+                result.add_warning(WarningKind::EventLog, || {
+                    format!("{}: {e}", file_path.display())
+                });
+                None
+            }
+        }
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Appends the event with the current time and flushes the file.
+    pub fn log(&mut self, event: &RunEvent) -> io::Result<()> {
+        writeln!(
+            self.writer,
+            "{{\"time\":\"{}\",\"elapsed_ms\":{},\"event\":\"{}\",{}}}",
+            Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+            self.start.elapsed().as_millis(),
+            event.name(),
+            event.json_fields()
+        )?;
+        self.writer.flush()
+    }
+
+    /// Logs a new max steps machine and the progress of the result since the last call.
+    pub fn log_result_progress(&mut self, result: &DeciderResultStats) -> io::Result<()> {
+        if result.steps_max() > self.steps_max_logged {
+            self.steps_max_logged = result.steps_max();
+            let machine = result
+                .machine_max_steps()
+                .map(|m| m.to_standard_tm_text_format())
+                .unwrap_or_default();
+            self.log(&RunEvent::NewMaxSteps {
+                steps: self.steps_max_logged,
+                machine,
+            })?;
+        }
+        if self.total > 0 {
+            let processed = result.num_processed_total();
+            let percent = (processed as u128 * 100 / self.total as u128).min(100) as u32;
+            if percent > self.percent_logged {
+                self.percent_logged = percent;
+                self.log(&RunEvent::Progress { percent, processed })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Logs the end of the run with the totals of the result.
+    pub fn log_run_end(&mut self, result: &DeciderResultStats) -> io::Result<()> {
+        self.log_result_progress(result)?;
+        self.log(&RunEvent::RunEnd {
+            processed: result.num_processed_total(),
+            num_halt: result.num_halt(),
+            num_non_halt: result.num_non_halt(),
+            num_undecided: result.num_undecided(),
+            steps_max: result.steps_max(),
+        })
+    }
+}
+
+/// Runs the event log function and adds a warning to the result if the log cannot be written.
+/// Does nothing if there is no log.
+pub(crate) fn log_or_warn(
+    event_log: &mut Option<EventLog>,
+    result: &mut DeciderResultStats,
+    f: impl FnOnce(&mut EventLog, &DeciderResultStats) -> io::Result<()>,
+) {
+    if let Some(log) = event_log.as_mut() {
+        if let Err(e) = f(log, result) {
+            let file_path = log.file_path().to_path_buf();
+            result.add_warning(WarningKind::EventLog, || {
+                format!("{}: {e}", file_path.display())
+            });
+        }
+    }
+}
+
+/// Line of the event log with the fields required for a timeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventLogLine {
+    pub time: String,
+    pub elapsed_ms: u64,
+    pub event: String,
+    /// The full JSON line.
+    pub line: String,
+}
+
+/// Reads the event log, e.g. to create a timeline of the run. Lines which are not events are skipped.
+pub fn read_event_log(file_path: impl AsRef<Path>) -> io::Result<Vec<EventLogLine>> {
+    let reader = BufReader::new(File::open(file_path)?);
+    let mut lines = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let (Some(time), Some(elapsed_ms), Some(event)) = (
+            json_field(&line, "time"),
+            json_field(&line, "elapsed_ms"),
+            json_field(&line, "event"),
+        ) else {
+            continue;
+        };
+        let Ok(elapsed_ms) = elapsed_ms.parse() else {
+            continue;
+        };
+        lines.push(EventLogLine {
+            time: time.to_string(),
+            elapsed_ms,
+            event: event.to_string(),
+            line,
+        });
+    }
+    Ok(lines)
+}
+
+/// Returns the value of a top level field written by [EventLog::log] (string values without quotes).
+pub(crate) fn json_field<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let key = format!("\"{name}\":");
+    let start = line.find(&key)? + key.len();
+    let rest = &line[start..];
+    if let Some(rest) = rest.strip_prefix('"') {
+        rest.find('"').map(|end| &rest[..end])
+    } else {
+        rest.find([',', '}']).map(|end| &rest[..end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::batch_run_decider_chain_data_provider_single_thread_reporting,
+            DeciderStandard,
+        },
+        output_layout::{OutputLayout, EVENT_LOG_FILE_NAME},
+    };
+
+    #[test]
+    fn event_log_json_string_escaped() {
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        let line = "{\"time\":\"t\",\"elapsed_ms\":12,\"event\":\"stage\",\"name\":\"x\"}";
+        assert_eq!(json_field(line, "elapsed_ms"), Some("12"));
+        assert_eq!(json_field(line, "name"), Some("x"));
+    }
+
+    #[test]
+    fn event_log_written_by_engine() {
+        let dir = std::env::temp_dir().join("bb_challenge_event_log_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .output_layout(OutputLayout::new(&dir))
+            .write_event_log(true)
+            .build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &deciders,
+            data_provider,
+            None,
+        );
+
+        let lines = read_event_log(dir.join(EVENT_LOG_FILE_NAME)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(result.warnings().count(WarningKind::EventLog), 0);
+        assert_eq!(lines.first().unwrap().event, "run_start");
+        assert_eq!(
+            json_field(&lines[0].line, "transition_order"),
+            Some("direction-major")
+        );
+        assert_eq!(json_field(&lines[0].line, "num_threads"), Some("1"));
+        assert!(lines[0]
+            .line
+            .contains("\"deciders\":[\"Decider Cycler\",\"Decider Halt\"]"));
+        assert_eq!(lines.last().unwrap().event, "run_end");
+        assert!(lines.iter().any(|l| l.event == "stage"));
+        // BB3 max with 21 steps is the last max steps event
+        let max_steps: Vec<_> = lines
+            .iter()
+            .filter(|l| l.event == "new_max_steps")
+            .collect();
+        assert!(max_steps.last().unwrap().line.contains("\"steps\":21"));
+        assert!(lines
+            .iter()
+            .any(|l| l.event == "progress" && l.line.contains("\"percent\":100")));
+        assert!(lines.windows(2).all(|w| w[0].elapsed_ms <= w[1].elapsed_ms));
+    }
+}
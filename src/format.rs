@@ -0,0 +1,3 @@
+//! Text formats of machines.
+
+pub mod standard_tm;
@@ -0,0 +1,169 @@
+//! Rules of the Standard TM Text Format, <https://www.sligocki.com/2022/10/09/standard-tm-format.html>. \
+//! All machine types ([MachineBinary], [MachineGeneric], [TransitionBinary] etc.) read and write the format
+//! with these constants, so the formatters cannot drift apart.
+//! - The states are written in order A, B, C, ..., separated by [STATE_SEPARATOR].
+//! - Each state has one transition per symbol, in symbol order (symbol 0 first).
+//! - A transition has [TRANSITION_LEN] characters: symbol to write, direction ('L' or 'R'), next state.
+//! - The states are the letters 'A' to 'Y' (state 1 to 25). There are no letters beyond 'Y', as 'Z' is the
+//!   halt state. The generic machines of this crate are limited to 10 states anyway.
+//! - An undefined transition is written as [TRANSITION_UNDEFINED], this is also the halt transition of
+//!   bbchallenge. A defined halt transition like '1RZ' is kept as written.
+//!
+//! The parsers also accept the digits '1' to '9' for states and '0' for halt, which are used in seed files.
+//! # Example
+//! ```
+//! # use bb_challenge::format::standard_tm;
+//! assert_eq!(standard_tm::dimensions("1RB1LB_1LA1RZ"), Ok((2, 2)));
+//! assert_eq!(standard_tm::state_to_char(2), 'B');
+//! ```
+//!
+//! [MachineBinary]: crate::machine_binary::MachineBinary
+//! [MachineGeneric]: crate::machine_generic::MachineGeneric
+//! [TransitionBinary]: crate::transition_binary::TransitionBinary
+
+/// Separator between the transitions of two states.
+pub const STATE_SEPARATOR: &str = "_";
+/// Number of characters of a transition.
+pub const TRANSITION_LEN: usize = 3;
+/// Undefined transition, which halts the machine.
+pub const TRANSITION_UNDEFINED: &str = "---";
+pub const DIR_LEFT_CHAR: char = 'L';
+pub const DIR_RIGHT_CHAR: char = 'R';
+pub const STATE_HALT_CHAR: char = 'Z';
+/// State 1.
+pub const STATE_FIRST_CHAR: char = 'A';
+/// Highest state letter, state 25.
+pub const STATE_LAST_CHAR: char = 'Y';
+
+/// Returns the letter of the state (1 = A), 0 is the halt state 'Z'.
+/// # Panics
+/// If the state has no letter (above 25).
+pub fn state_to_char(state: usize) -> char {
+    if state == 0 {
+        return STATE_HALT_CHAR;
+    }
+    let c = (STATE_FIRST_CHAR as u8 + state as u8 - 1) as char;
+    assert!(
+        c <= STATE_LAST_CHAR,
+        "State {state} has no letter in the standard TM text format."
+    );
+    c
+}
+
+/// Returns the state of the letter (A = 1), 0 for the halt state 'Z'. None if it is no state letter.
+pub fn state_from_char(c: u8) -> Option<usize> {
+    match c as char {
+        STATE_HALT_CHAR => Some(0),
+        STATE_FIRST_CHAR..=STATE_LAST_CHAR => Some((c - STATE_FIRST_CHAR as u8) as usize + 1),
+        _ => None,
+    }
+}
+
+pub fn dir_to_char(is_dir_right: bool) -> char {
+    if is_dir_right {
+        DIR_RIGHT_CHAR
+    } else {
+        DIR_LEFT_CHAR
+    }
+}
+
+/// Joins the transitions of the states, e.g. ["1RB1LB", "1LA1RZ"] to '1RB1LB_1LA1RZ'.
+pub fn join_states<S: AsRef<str>>(states: &[S]) -> String {
+    states
+        .iter()
+        .map(|s| s.as_ref())
+        .collect::<Vec<_>>()
+        .join(STATE_SEPARATOR)
+}
+
+/// Checks the text strictly against the format (letters only) and returns (n_states, n_symbols).
+/// # Errors
+/// Describes the first rule which is violated.
+pub fn dimensions(text: &str) -> Result<(usize, usize), &'static str> {
+    let states: Vec<&str> = text.split(STATE_SEPARATOR).collect();
+    let len_line = states[0].len();
+    if len_line == 0 || !len_line.is_multiple_of(TRANSITION_LEN) {
+        return Err("Each transition needs three characters.");
+    }
+    let n_states = states.len();
+    let n_symbols = len_line / TRANSITION_LEN;
+    if n_symbols > 10 {
+        return Err("Symbols are single digits, at most 10 symbols.");
+    }
+    for state in states.iter() {
+        if state.len() != len_line {
+            return Err("The length of the separated transition lines is not identical.");
+        }
+        for tr in state.as_bytes().chunks(TRANSITION_LEN) {
+            if tr == TRANSITION_UNDEFINED.as_bytes() {
+                continue;
+            }
+            let symbol = (tr[0] as char).to_digit(10);
+            if symbol.is_none_or(|s| s as usize >= n_symbols) {
+                return Err("The symbol to write is not a symbol of the machine.");
+            }
+            if tr[1] as char != DIR_LEFT_CHAR && tr[1] as char != DIR_RIGHT_CHAR {
+                return Err("The direction must be 'L' or 'R'.");
+            }
+            if state_from_char(tr[2]).is_none_or(|s| s > n_states) {
+                return Err("The next state is not a state of the machine.");
+            }
+        }
+    }
+
+    Ok((n_states, n_symbols))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{machine_binary::MachineBinary, machine_generic::MachineGeneric};
+
+    /// Machines from bbchallenge and the literature in the standard TM text format.
+    const CORPUS_STANDARD_TM: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test_corpus/standard_tm_format.txt"
+    );
+
+    #[test]
+    fn standard_tm_corpus_round_trip() {
+        let corpus = std::fs::read_to_string(CORPUS_STANDARD_TM).unwrap();
+        let mut num_machines = 0;
+        for line in corpus.lines() {
+            let text = line.split('#').next().unwrap().trim();
+            if text.is_empty() {
+                continue;
+            }
+            num_machines += 1;
+            let (n_states, n_symbols) = dimensions(text).unwrap();
+            let generic = MachineGeneric::try_from_standard_tm_text_format(text).unwrap();
+            assert_eq!(generic.to_standard_tm_text_format(), text);
+            if n_symbols == 2 && n_states <= crate::config::MAX_STATES {
+                let binary = MachineBinary::try_from_standard_tm_text_format(text).unwrap();
+                assert_eq!(binary.to_standard_tm_text_format(), text);
+                assert_eq!(binary.n_states(), n_states);
+            }
+        }
+        assert!(num_machines >= 10);
+    }
+
+    #[test]
+    fn standard_tm_rules() {
+        assert_eq!(state_to_char(0), 'Z');
+        assert_eq!(state_to_char(25), 'Y');
+        assert_eq!(state_from_char(b'Y'), Some(25));
+        assert_eq!(state_from_char(b'Z'), Some(0));
+        assert_eq!(state_from_char(b'-'), None);
+        assert_eq!(join_states(&["1RB1LB", "1LA1RZ"]), "1RB1LB_1LA1RZ");
+        assert_eq!(dimensions("1RB2LB1RZ_2LA2RB1LB"), Ok((2, 3)));
+        for invalid in [
+            "1RB1LB_1LA1R",
+            "1RB1LC_1LA1RZ",
+            "1XB1LB_1LA1RZ",
+            "2RB1LB_1LA1RZ",
+            "",
+        ] {
+            assert!(dimensions(invalid).is_err(), "{invalid}");
+        }
+    }
+}
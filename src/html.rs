@@ -14,6 +14,13 @@
 //! Instead use config.write_html_line_limit which counts the actually written steps. For BB5_MAX only 91,021 of 47,176,870 = 0.02 %
 //! are written, which makes it possible to write the full output to an only 19,1 MB large html file.
 //!
+//! The head cell is colored by the state the machine is in after the step, a legend below the transition table
+//! shows the colors. At the end of the file a step-density minimap is written: Each bar covers the same number
+//! of written lines (1/MINIMAP_NUM_BARS of the line limit), its height shows the steps executed in this part.
+//! High bars are parts where the self-ref speed-up jumped over many steps. Clicking a bar jumps to the part.
+//! Below the legend the space-time diagram of the first SPACE_TIME_NUM_STEPS steps gives an overview of the
+//! behavior, see [SpaceTimeDiagram].
+//!
 //! # How this is used
 //! - Create a new HtmlWriter.
 //! - create_html_file_start is used open the file and write the header. Also creates the directory and css files if they do not exist.
@@ -26,24 +33,33 @@
 //! This config is not in the toml file as it would cause to much performance delay.
 //!
 //! ## config.toml
-//! - path: html_out_path (subdirectories will be created automatically depending on decider)
+//! - path: html_out_path (subdirectories will be created automatically depending on decider). \
+//!   This is the default root of the [OutputLayout](crate::output_layout::OutputLayout), which can be set in the Config.
 //! - html_tape_shifts: If true, then the head is always in the middle and the tape shifts. Else head moves.
 //!
 //! ## Program Config
 //! Set Config.write_html_file(true) to enable html output for that decider.
+//!
+//! # Errors
+//! File system errors (e.g. missing permissions, disk full) do not stop the decider. The html output of the
+//! affected machine stops, the failure is counted in [HtmlWriteFailures] and shown in the final result.
 
 use std::{
+    fmt::Display,
     fs::File,
     io::{self, BufWriter, Write},
-    path::{Path, MAIN_SEPARATOR_STR},
+    path::{Path, PathBuf},
     time::Instant,
 };
 
 use crate::{
-    config::{self, Config, StepBig},
+    config::{self, Config, StepBig, MAX_STATES},
     decider::{decider_halt_long::DeciderHaltLong, Decider},
+    format::standard_tm,
     machine_binary::{MachineBinary, MachineId},
     machine_info::MachineInfo,
+    output_layout::decider_dir_name,
+    space_time::SpaceTimeDiagram,
     status::MachineStatus,
     tape::tape_utils::TapeLongPositions,
     transition_binary::TransitionBinary,
@@ -80,6 +96,24 @@ td {
     padding: 6px;
     margin-left: 10px;
 }";
+/// Background color of the head cell for states A to E. Transparent, so it works in light and dark mode.
+const CSS_STATE_COLORS: [&str; MAX_STATES] = [
+    "rgba(255, 99, 71, 0.5)",
+    "rgba(60, 179, 113, 0.5)",
+    "rgba(30, 144, 255, 0.5)",
+    "rgba(255, 165, 0, 0.5)",
+    "rgba(186, 85, 211, 0.5)",
+];
+const CLASS_STATE_PREFIX: &str = "state_";
+const CLASS_MINIMAP: &str = "minimap";
+const ID_MINIMAP: &str = "minimap";
+/// Number of bars in the step-density minimap, this also limits the number of anchors in the file.
+const MINIMAP_NUM_BARS: u32 = 100;
+const MINIMAP_BAR_HEIGHT_MAX: u64 = 60;
+/// Steps (rows) of the space-time diagram below the legend.
+const SPACE_TIME_NUM_STEPS: u64 = 200;
+/// Pixels per cell of the space-time diagram.
+const SPACE_TIME_SCALE: usize = 3;
 const CSS_TABLE_DARK: &str = "table,
 th,
 td {
@@ -89,6 +123,47 @@ td {
     margin-left: 10px;
 }";
 
+/// Html files which could not be written. The decider keeps running, only the html output
+/// of the affected machines is incomplete.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HtmlWriteFailures {
+    /// Number of machines with a failed html output.
+    pub num_machines: u64,
+    /// First error including the machine, e.g. '1RB1LB_1LA---: Permission denied (os error 13)'.
+    pub first_error: Option<String>,
+}
+
+impl HtmlWriteFailures {
+    pub fn add(&mut self, machine_text: &str, error: &io::Error) {
+        self.num_machines += 1;
+        if self.first_error.is_none() {
+            self.first_error = Some(format!("{machine_text}: {error}"));
+        }
+    }
+
+    pub fn add_self(&mut self, other: &HtmlWriteFailures) {
+        self.num_machines += other.num_machines;
+        if self.first_error.is_none() {
+            self.first_error.clone_from(&other.first_error);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_machines == 0
+    }
+}
+
+impl Display for HtmlWriteFailures {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} machines", self.num_machines)?;
+        if let Some(e) = &self.first_error {
+            write!(f, ", first error: {e}")?;
+        }
+        Ok(())
+    }
+}
+
 // All calls do nothing, if write_html_file() is off.
 #[derive(Debug, Default)]
 pub struct HtmlWriter {
@@ -101,14 +176,21 @@ pub struct HtmlWriter {
     write_html_tape_shifted_64_bit: bool,
 
     n_states: usize,
-    /// Main path without sub directory
-    html_out_path: String,
+    /// Main path without sub directory, see [OutputLayout](crate::output_layout::OutputLayout).
+    html_out_path: PathBuf,
     // / Sub-dir. This is mandatory, the option is only to check if it is set.
     // sub_dir: Option<String>,
     /// full path, set_sub_dir to set this path, mandatory.
-    path: Option<String>,
+    path: Option<PathBuf>,
     file_name: Option<String>,
     buf_writer: Option<BufWriter<File>>,
+    /// Machine of the current file in standard TM text format, used for the error message.
+    machine_text: String,
+    write_failures: HtmlWriteFailures,
+    /// Number of written lines covered by one bar of the minimap.
+    minimap_lines_per_bar: u32,
+    /// First step no of each minimap bar.
+    minimap_step_starts: Vec<StepBig>,
 }
 
 impl HtmlWriter {
@@ -122,10 +204,17 @@ impl HtmlWriter {
                 write_html_tape_shifted_64_bit: config.write_html_tape_shifted_64_bit(),
 
                 n_states: config.n_states(),
-                html_out_path: config.config_toml().html_out_path().to_string(),
+                html_out_path: config.output_layout().run_path(config),
                 path: None,
                 file_name: None,
                 buf_writer: None,
+                machine_text: String::new(),
+                write_failures: HtmlWriteFailures::default(),
+                minimap_lines_per_bar: config
+                    .write_html_line_limit()
+                    .div_ceil(MINIMAP_NUM_BARS)
+                    .max(1),
+                minimap_step_starts: Vec::new(),
             }
         } else {
             Self::default()
@@ -133,17 +222,14 @@ impl HtmlWriter {
     }
 
     /// Sets the sub directory. This is mandatory.
-    /// # Panics
-    /// If the sub directory could not be created.
-    fn init_sub_dir(&mut self, sub_dir: &str) {
-        let path = format!(
-            "{}{MAIN_SEPARATOR_STR}{sub_dir}_bb{}",
-            self.html_out_path, self.n_states
-        );
-        let msg = format!("CSS files could not be created in {path}.");
-        create_css(&path).expect(&msg);
+    fn init_sub_dir(&mut self, sub_dir: &str) -> io::Result<()> {
+        let path = self
+            .html_out_path
+            .join(decider_dir_name(sub_dir, self.n_states));
+        create_css(&path)?;
 
         self.path = Some(path);
+        Ok(())
     }
 
     pub fn file_name(&self) -> Option<&String> {
@@ -165,7 +251,7 @@ impl HtmlWriter {
     //     self.write_html_file && self.path.is_some()
     // }
 
-    pub fn path(&self) -> Option<&String> {
+    pub fn path(&self) -> Option<&PathBuf> {
         self.path.as_ref()
     }
 
@@ -174,6 +260,17 @@ impl HtmlWriter {
         self.write_html_line_count = 0;
     }
 
+    /// All html write failures of this writer so far.
+    pub fn write_failures(&self) -> &HtmlWriteFailures {
+        &self.write_failures
+    }
+
+    /// Counts the failure for the current machine and stops the html output for it.
+    fn add_write_failure(&mut self, error: &io::Error) {
+        self.write_failures.add(&self.machine_text, error);
+        self.buf_writer = None;
+    }
+
     /// Writes to html header and the start of the body to the file. \
     /// Sets path and file_name in self.
     /// # Arguments
@@ -181,6 +278,9 @@ impl HtmlWriter {
     /// - machine to write, uses tm_standard_name for file name
     ///
     /// Example: ("data", "hold", m) creates /data/hold_1RB0RC_1RA0RA_0RB1RC.html
+    /// # Errors
+    /// The error is also counted in the [HtmlWriteFailures] and the html output for this machine is skipped,
+    /// so the caller can ignore it and the decider continues without html output.
     pub fn create_html_file_start(
         &mut self,
         decider_id: &DeciderId,
@@ -189,7 +289,23 @@ impl HtmlWriter {
         if !self.write_html_file {
             return Ok(());
         }
-        self.init_sub_dir(decider_id.sub_dir);
+        // a file left open by a previous failure must not receive the steps of this machine
+        self.buf_writer = None;
+        self.file_name = None;
+        self.machine_text = machine.to_standard_tm_text_format();
+        let r = self.create_html_file_start_machine(decider_id, machine);
+        if let Err(e) = &r {
+            self.add_write_failure(e);
+        }
+        r
+    }
+
+    fn create_html_file_start_machine(
+        &mut self,
+        decider_id: &DeciderId,
+        machine: &MachineId,
+    ) -> io::Result<()> {
+        self.init_sub_dir(decider_id.sub_dir)?;
         match &self.path {
             Some(path) => {
                 if !std::fs::exists(path)? {
@@ -199,7 +315,7 @@ impl HtmlWriter {
                     + "_"
                     + machine.file_name().as_str()
                     + ".html";
-                let p = Path::new(&path).join(&file_name);
+                let p = path.join(&file_name);
                 let mut file = File::create(&p)?;
                 write_html_header(&mut file, &machine.to_standard_tm_text_format())?;
                 writeln!(file, "<body>")?;
@@ -219,6 +335,16 @@ impl HtmlWriter {
                 )?;
                 // Machine transitions as table
                 writeln!(file, "{}", machine.machine().to_table_html_string(true))?;
+                writeln!(file, "{}", state_legend_html(machine.n_states()))?;
+                writeln!(
+                    file,
+                    "{}",
+                    space_time_diagram_html(machine.machine(), SPACE_TIME_NUM_STEPS)
+                )?;
+                writeln!(
+                    file,
+                    "<p><a href=\"#{ID_MINIMAP}\">Step density minimap</a> (end of file)</p>"
+                )?;
 
                 // write self-referencing
                 if machine.machine().has_self_referencing_transition() {
@@ -241,6 +367,7 @@ impl HtmlWriter {
                 self.buf_writer = Some(BufWriter::new(file));
                 self.file_name = Some(file_name);
                 self.write_html_line_count = 0;
+                self.minimap_step_starts.clear();
 
                 Ok(())
             }
@@ -251,14 +378,18 @@ impl HtmlWriter {
         }
     }
 
+    /// Renames the current file depending on the status, a failure is counted in the [HtmlWriteFailures].
     pub fn rename_file_to_status_self(&mut self, status: &MachineStatus) {
-        rename_file_to_status(
-            self.path.as_ref().unwrap(),
-            self.file_name.as_ref().unwrap(),
-            status,
-        );
+        let r = match (&self.path, &self.file_name) {
+            (Some(path), Some(file_name)) => rename_file_to_status(path, file_name, status),
+            _ => Ok(()),
+        };
+        if let Err(e) = r {
+            self.add_write_failure(&e);
+        }
     }
 
+    /// Writes the final lines and closes the file. Does nothing if the file could not be created.
     pub fn write_html_file_end(&mut self, step_no: StepBig, status: &MachineStatus) {
         // no if let as borrow checker would complain
         if self.buf_writer.is_some() {
@@ -297,8 +428,19 @@ impl HtmlWriter {
             // }
             let text = format!("{}", status);
             self.write_html_p(&text);
-            if let Some(buf_writer) = self.buf_writer.as_mut() {
-                crate::html::write_file_end(buf_writer).expect("Html file could not be written")
+            if !self.minimap_step_starts.is_empty() {
+                let minimap = self.minimap_html(step_no);
+                self.write_html_p(&minimap);
+            }
+            // the file is closed here, so it can be renamed
+            if let Some(mut buf_writer) = self.buf_writer.take() {
+                if let Err(e) = write_file_end(&mut buf_writer) {
+                    self.add_write_failure(&e);
+                    return;
+                }
+            } else {
+                // writing failed
+                return;
             }
 
             // dbg!(
@@ -312,21 +454,61 @@ impl HtmlWriter {
 
     pub fn write_html_p(&mut self, text: &str) {
         if let Some(buf_writer) = self.buf_writer.as_mut() {
-            write_html_p(buf_writer, text);
+            if let Err(e) = write_html_p(buf_writer, text) {
+                self.add_write_failure(&e);
+            }
         }
     }
 
-    /// Write a single step to the html file.
-    /// # Panics
-    /// If file cannot be written. Unlikely as the file is already open for write. \
-    /// If it panics, then init_sub_dir() was not called.
+    /// Write a single step to the html file. Does nothing if the file could not be created.
     pub fn write_step_html(&mut self, step_data: &StepHtml) {
         if self.is_write_html_in_limit(step_data.step_no) {
-            step_data.write_step_html(self.buf_writer.as_mut().unwrap());
+            if let Some(buf_writer) = self.buf_writer.as_mut() {
+                let mut r = Ok(());
+                if self
+                    .write_html_line_count
+                    .is_multiple_of(self.minimap_lines_per_bar)
+                {
+                    // anchor for the minimap
+                    r = write_html(
+                        buf_writer,
+                        &format!("<a id=\"m{}\"></a>", self.minimap_step_starts.len()),
+                    );
+                    self.minimap_step_starts.push(step_data.step_no);
+                }
+                if let Err(e) = r.and_then(|_| step_data.write_step_html(buf_writer)) {
+                    self.add_write_failure(&e);
+                    return;
+                }
+            }
             self.write_html_line_count += 1;
         }
     }
 
+    /// Creates the step-density minimap. The bar height is relative to the bar with the most steps.
+    fn minimap_html(&self, step_no: StepBig) -> String {
+        let mut steps: Vec<(StepBig, u64)> = Vec::with_capacity(self.minimap_step_starts.len());
+        for (i, start) in self.minimap_step_starts.iter().enumerate() {
+            let end = match self.minimap_step_starts.get(i + 1) {
+                Some(next) => *next,
+                None => step_no + 1,
+            };
+            steps.push((*start, end.saturating_sub(*start) as u64));
+        }
+        let max_steps = steps.iter().map(|(_, n)| *n).max().unwrap_or(1).max(1);
+        let mut bars = String::new();
+        for (i, (start, n)) in steps.iter().enumerate() {
+            let height = 2 + n * MINIMAP_BAR_HEIGHT_MAX / max_steps;
+            bars.push_str(&format!(
+                "<a href=\"#m{i}\" title=\"Step {start}: {n} steps\"><span style=\"height: {height}px\"></span></a>"
+            ));
+        }
+        format!(
+            "<span id=\"{ID_MINIMAP}\">Step density minimap ({} lines per bar):</span><br><span class=\"{CLASS_MINIMAP}\">{bars}</span>",
+            self.minimap_lines_per_bar
+        )
+    }
+
     pub fn write_html_tape_shifted_64_bit(&self) -> bool {
         self.write_html_tape_shifted_64_bit
     }
@@ -342,9 +524,9 @@ pub fn blanks(num_blanks: usize) -> String {
 }
 
 /// Creates the css files if they do not exist.
-pub fn create_css(path: &str) -> io::Result<()> {
+pub fn create_css(path: impl AsRef<Path>) -> io::Result<()> {
     // Define file names
-    let css_path = Path::new(path).join(CSS_FOLDER);
+    let css_path = path.as_ref().join(CSS_FOLDER);
     if !css_path.exists() {
         std::fs::create_dir_all(&css_path)?;
     }
@@ -366,6 +548,47 @@ pub fn create_css(path: &str) -> io::Result<()> {
     Ok(())
 }
 
+/// State char for the index, 0 = A.
+fn state_char(state_index: usize) -> char {
+    standard_tm::state_to_char(state_index + 1)
+}
+
+/// Html of a head cell, colored by the state of the transition (the state after the step).
+pub fn head_cell_html(cell: &str, tr: &TransitionBinary) -> String {
+    let state = tr.state() as usize;
+    if state == 0 || state > MAX_STATES {
+        return format!("<span class=\"{CLASS_HEAD_POSITION}\">{cell}</span>");
+    }
+    format!(
+        "<span class=\"{CLASS_HEAD_POSITION} {CLASS_STATE_PREFIX}{}\">{cell}</span>",
+        state_char(state - 1).to_ascii_lowercase()
+    )
+}
+
+/// Legend of the head cell colors.
+pub fn state_legend_html(n_states: usize) -> String {
+    let mut s = String::from("<p>Head color by state:");
+    for i in 0..n_states.min(MAX_STATES) {
+        let c = state_char(i);
+        s.push_str(&format!(
+            " <span class=\"{CLASS_STATE_PREFIX}{}\">&nbsp;{c}&nbsp;</span>",
+            c.to_ascii_lowercase()
+        ));
+    }
+    s.push_str("</p>");
+    s
+}
+
+/// Space-time diagram of the first steps as inline SVG, see [SpaceTimeDiagram].
+pub fn space_time_diagram_html(machine: &MachineBinary, num_steps: u64) -> String {
+    let diagram = SpaceTimeDiagram::new(machine, 0, num_steps);
+    format!(
+        "<p>Space-time diagram of the first {} steps:<br>\n{}</p>",
+        diagram.num_rows(),
+        diagram.to_svg(SPACE_TIME_SCALE)
+    )
+}
+
 /// Formats an Integer right aligned
 pub fn format_right_aligned_int_html(number: usize, size: usize) -> String {
     let s = format!("{number:>size$}");
@@ -375,67 +598,63 @@ pub fn format_right_aligned_int_html(number: usize, size: usize) -> String {
 /// Creates the folder path of the html file and the css files in the folder if not already existing.
 /// # Returns
 /// - the path for the html files, like '/result/<sub_path>_bb5', e.g. '/result/cycler_bb5' \
-/// - None if write_html_file in [Config] is set to false.
-/// # Panics
-/// If the path could not be created.
+///   The root is taken from the [OutputLayout](crate::output_layout::OutputLayout) of the config. \
+/// - None if the path could not be created.
 pub fn get_html_path(sub_path: &str, config: &Config) -> Option<String> {
     // if config.write_html_file() {
-    let path = format!(
-        "{}{MAIN_SEPARATOR_STR}{sub_path}_bb{}",
-        config.config_toml().html_out_path(),
-        config.n_states()
-    );
-    let msg = format!("CSS files could not be created in {path}.");
-    create_css(&path).expect(&msg);
-    Some(path)
+    let path = config.output_layout().decider_path(sub_path, config);
+    create_css(&path).ok()?;
+    Some(path.to_string_lossy().to_string())
     // } else {
     //     None
     // }
 }
 
 /// Rename file depending on status, Decided or Undecided will be added to the file name.
-/// #Panics
-/// If file could not be renamed.
-pub fn rename_file_to_status(file_path: &str, file_name: &str, machine_status: &MachineStatus) {
-    let old_path = format!("{}{}{}", file_path, MAIN_SEPARATOR_STR, file_name);
+/// # Errors
+/// If the file does not exist or could not be renamed.
+pub fn rename_file_to_status(
+    file_path: &Path,
+    file_name: &str,
+    machine_status: &MachineStatus,
+) -> io::Result<()> {
+    let old_path = file_path.join(file_name);
     let new_path = match machine_status {
         MachineStatus::NoDecision => todo!(),
         MachineStatus::EliminatedPreDecider(_) => todo!(),
         MachineStatus::Undecided(_, _, _) => {
             // rename file
             let f_name_new = "undecided_".to_string() + file_name;
-            Some(format!("{}{}{}", file_path, MAIN_SEPARATOR_STR, f_name_new))
+            Some(file_path.join(f_name_new))
         }
         MachineStatus::DecidedHalt(steps) => {
             // rename file
             let f_name_new = format!("decided_halt_{steps}_{}", file_name);
-            Some(format!("{}{}{}", file_path, MAIN_SEPARATOR_STR, f_name_new))
+            Some(file_path.join(f_name_new))
         }
         MachineStatus::DecidedNonHalt(_) => {
             // rename file
             let f_name_new = format!("decided_non_halt_{}", file_name);
-            Some(format!("{}{}{}", file_path, MAIN_SEPARATOR_STR, f_name_new))
+            Some(file_path.join(f_name_new))
         }
         _ => {
             // rename file
             // dbg!(machine_status);
             let f_name_new = "decided_".to_string() + file_name;
-            Some(format!("{}{}{}", file_path, MAIN_SEPARATOR_STR, f_name_new))
+            Some(file_path.join(f_name_new))
         }
     };
     if let Some(new_path) = new_path {
-        let r = std::fs::exists(&old_path);
-        match r {
-            Ok(exists) => {
-                if !exists {
-                    panic!("File {old_path} not found!");
-                }
-            }
-            Err(e) => panic!("File Error: {e}"),
+        if !std::fs::exists(&old_path)? {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("File {} not found!", old_path.display()),
+            ));
         }
-        std::fs::rename(&old_path, &new_path)
-            .unwrap_or_else(|_| panic!("Could not rename file: {old_path}"));
+        std::fs::rename(&old_path, &new_path)?;
     }
+
+    Ok(())
 }
 
 /// Writes the \<head\> section of the file.
@@ -466,6 +685,21 @@ pub fn write_html_header(file: &mut File, title: &str) -> io::Result<()> {
         file,
         "        body {{ font-family: {BODY_FONT_FAMILY}; font-size: larger;}}"
     )?;
+    for (i, color) in CSS_STATE_COLORS.iter().enumerate() {
+        writeln!(
+            file,
+            "        .{CLASS_STATE_PREFIX}{} {{ background-color: {color}; }}",
+            state_char(i).to_ascii_lowercase()
+        )?;
+    }
+    writeln!(
+        file,
+        "        .{CLASS_MINIMAP} {{ display: flex; align-items: flex-end; gap: 1px; }}"
+    )?;
+    writeln!(
+        file,
+        "        .{CLASS_MINIMAP} span {{ display: block; width: 6px; background-color: gray; }}"
+    )?;
     writeln!(file, "    </style>")?;
     writeln!(file, "</head>")?;
     Ok(())
@@ -520,8 +754,7 @@ fn write_dark_css_content(file: &mut File) -> io::Result<()> {
 pub fn write_file_end(buf_writer: &mut BufWriter<File>) -> io::Result<()> {
     writeln!(buf_writer, "</body>")?;
     writeln!(buf_writer, "</html>")?;
-    buf_writer.flush().expect("Could not flush");
-    Ok(())
+    buf_writer.flush()
 }
 
 // #[deprecated]
@@ -546,21 +779,19 @@ pub fn write_file_end(buf_writer: &mut BufWriter<File>) -> io::Result<()> {
 // }
 
 /// Writes a text into an open Html file.
-/// # Panics
-/// Panics on file write error. At this point it is unlikely to occur.
-pub fn write_html(buf_writer: &mut BufWriter<File>, text: &str) {
-    writeln!(buf_writer, "{text}",).expect("Html write error");
+pub fn write_html(buf_writer: &mut BufWriter<File>, text: &str) -> io::Result<()> {
+    writeln!(buf_writer, "{text}",)?;
     #[cfg(feature = "bb_debug")]
-    buf_writer.flush().expect("Could not flush");
+    buf_writer.flush()?;
+    Ok(())
 }
 
 /// Writes a paragraphed text into an open Html file.
-/// # Panics
-/// Panics on file write error. At this point it is unlikely to occur.
-pub fn write_html_p(buf_writer: &mut BufWriter<File>, text: &str) {
-    writeln!(buf_writer, "<p>{text}</p>",).expect("Html write error");
+pub fn write_html_p(buf_writer: &mut BufWriter<File>, text: &str) -> io::Result<()> {
+    writeln!(buf_writer, "<p>{text}</p>",)?;
     #[cfg(feature = "bb_debug")]
-    buf_writer.flush().expect("Could not flush");
+    buf_writer.flush()?;
+    Ok(())
 }
 
 /// Writes a batch of machines to html
@@ -633,11 +864,9 @@ pub struct StepHtml {
 }
 
 impl StepHtml {
-    /// Write a single step to the html file. \
-    /// # Panics
-    /// If file cannot be written. Unlikely as the file is already open for write.
-    pub fn write_step_html(&self, buf_writer: &mut BufWriter<File>) {
-        write_html(buf_writer, &self.step_to_html_fmt());
+    /// Write a single step to the html file.
+    pub fn write_step_html(&self, buf_writer: &mut BufWriter<File>) -> io::Result<()> {
+        write_html(buf_writer, &self.step_to_html_fmt())
     }
 
     /// Formats the line
@@ -679,3 +908,38 @@ impl StepHtml {
         s.replace(" ", "&nbsp;")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{machine_binary::NotableMachineBinary, output_layout::OutputLayout};
+
+    #[test]
+    fn html_head_state_colors_and_minimap() {
+        let root = std::env::temp_dir().join("bb_challenge_html_minimap_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let config = Config::builder(4)
+            .write_html_file(true)
+            .write_html_line_limit(1000)
+            .output_layout(OutputLayout::new(&root))
+            .build();
+        let machine = NotableMachineBinary::BB4Max.machine_id();
+        DeciderHaltLong::decide_single_machine(&machine, &config);
+
+        let path = config.output_layout().decider_path("halt", &config);
+        let file = std::fs::read_dir(&path)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .find(|e| e.path().extension().is_some_and(|x| x == "html"))
+            .unwrap();
+        let html = std::fs::read_to_string(file.path()).unwrap();
+        assert!(html.contains(&state_legend_html(4)));
+        assert!(html.contains("Space-time diagram of the first 107 steps"));
+        assert!(html.contains(&format!("{CLASS_HEAD_POSITION} {CLASS_STATE_PREFIX}b")));
+        assert!(html.contains(&format!("id=\"{ID_MINIMAP}\"")));
+        // 107 steps with 10 lines per bar
+        assert!(html.contains("href=\"#m10\""));
+        assert!(!html.contains("href=\"#m11\""));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,28 +1,54 @@
+// std::simd for the batch stepper, nightly only
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 // // pub mod decider_engine;
 // // pub mod decider_expanding_loop;
 // // pub mod decider_u128;
 // // pub mod decider_u128_long;
 // // pub mod sub_decider;
 // // pub mod sub_decider_loop_v4;
+pub mod analysis;
 pub mod arg_handler;
+pub mod automaton;
+pub mod certificate;
+pub mod certificate_export;
+pub mod checkpoint;
 pub mod config;
+pub mod config_file;
+pub mod dashboard;
 pub mod data_provider;
 pub mod decider;
+pub mod event_log;
+pub mod format;
 // pub mod error;
 // pub mod examples;
 pub mod html;
 // pub mod machine;
 pub mod machine_binary;
 pub mod machine_info;
+pub mod machine_runner;
+pub mod macro_machine;
+pub mod output_layout;
 // pub mod pre_decider;
 pub mod reporter;
+pub mod results_store;
+#[cfg(feature = "sqlite")]
+pub mod results_store_sqlite;
+pub mod resume_undecided;
+pub mod sim_core;
+pub mod space_time;
+pub mod static_site;
+pub mod stats_snapshot;
 // pub mod single_thread_worker;
 pub mod status;
+pub mod stop_conditions;
 // pub mod step_record;
 pub mod machine_generic;
 // pub mod machine_id;
 pub mod tape;
+pub mod test_generator;
+pub mod throttle;
 pub mod toml;
 pub mod transition_binary;
 pub mod unused;
 pub mod utils;
+pub mod verdict_log;
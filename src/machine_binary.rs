@@ -4,6 +4,8 @@
 //! Transition[0] contains additional information, always the number of states used, see [MachineBinary],
 //! therefore use either new or make sure the number of states are set.
 //!
+//! Use [MachineBinary::transition] with the typed [State] and [Symbol] to read a transition, this avoids
+//! the manual array id calculation. \
 //! Use TryFrom to create a machine from Standard TM Text Format. \
 //! A normalized ID can be calculated by calling calc_normalized_id, see [calc_normalized_id].
 
@@ -13,9 +15,13 @@ use num_format::ToFormattedString;
 
 use crate::{
     config::{IdNormalized, MAX_STATES, NUM_FIELDS},
+    format::standard_tm,
     machine_generic::{MachineGeneric, NotableMachine, StateType, SymbolType},
     machine_info::MachineInfo,
-    transition_binary::{TransitionBinary, TransitionType, TRANSITION_BINARY_UNUSED},
+    transition_binary::{
+        TransitionBinary, TransitionError, TransitionOrder, TransitionType,
+        TRANSITION_BINARY_UNUSED,
+    },
 };
 // use crate::{
 //     data_provider::enumerator::create_all_transition_permutations,
@@ -31,11 +37,78 @@ use crate::{
 pub type TransitionTableBinaryArray1D = [TransitionBinary; NUM_FIELDS];
 pub const TRANSITION_TABLE_BINARY_DEFAULT: TransitionTableBinaryArray1D =
     [TRANSITION_BINARY_UNUSED; NUM_FIELDS];
+/// Size of one machine record in the bbchallenge seed database, see [MachineBinary::from_bb_challenge_record].
+pub const BB_CHALLENGE_RECORD_SIZE: usize = 30;
+/// Number of states of a machine in the bbchallenge seed database.
+const BB_CHALLENGE_RECORD_N_STATES: usize = 5;
 const FILTER_TABLE_N_STATES: TransitionType = 0b0000_1111;
 const FILTER_TABLE_SELF_REF: TransitionType = 0b1100_0000;
 const SELF_REF_NOT_CHECKED: TransitionType = 0b0000_0000;
 const SELF_REF_SET_TRUE: TransitionType = 0b1000_0000;
 const SELF_REF_SET_FALSE: TransitionType = 0b0100_0000;
+// MachineFeatures stored in the unused transition[1]: halt array id bits 0-3, left bits 4-7,
+// self-ref bits 8-11, bit 12 set when stored.
+const FEATURES_SET: TransitionType = 0b1_0000_0000_0000;
+const FILTER_FEATURE: TransitionType = 0b1111;
+
+/// Cheap static features of a machine, which are calculated once by the enumerator and stored in the machine. \
+/// They allow to route a machine to a suitable first decider,
+/// see [DeciderRouter](crate::decider::decider_engine::DeciderRouter).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MachineFeatures {
+    /// Number of transitions moving left.
+    pub num_left: u8,
+    /// Number of self-referencing transitions, e.g. D1 1LD.
+    pub num_self_ref: u8,
+    /// Array id of the first halt (or undefined) transition, 0 if the machine has none.
+    pub halt_array_id: u8,
+}
+
+/// State of a binary machine, A = 1, B = 2 etc. 0 is the halt state 'Z', which has no transitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct State(u8);
+
+impl State {
+    pub const HALT: State = State(0);
+    pub const A: State = State(1);
+
+    /// State from its number, A = 1.
+    pub fn new(state: usize) -> Self {
+        debug_assert!(state <= MAX_STATES, "State {state} exceeds {MAX_STATES}.");
+        Self(state as u8)
+    }
+
+    pub fn value(&self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn is_halt(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the state as char (A,B,C,... or Z for halt).
+    pub fn to_char(&self) -> char {
+        standard_tm::state_to_char(self.0 as usize)
+    }
+}
+
+/// Symbol read from or written to the tape, 0 or 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u8);
+
+impl Symbol {
+    pub const ZERO: Symbol = Symbol(0);
+    pub const ONE: Symbol = Symbol(1);
+
+    pub fn new(symbol: usize) -> Self {
+        debug_assert!(symbol <= 1, "Symbol {symbol} is not binary.");
+        Self(symbol as u8)
+    }
+
+    pub fn value(&self) -> usize {
+        self.0 as usize
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct MachineBinary {
@@ -86,7 +159,7 @@ impl MachineBinary {
         for t in transitions_as_str {
             v.push(format!("{}{}", t.0, t.1));
         }
-        let s = v.join("_");
+        let s = standard_tm::join_states(&v);
         let tg = MachineGeneric::try_from_standard_tm_text_format(&s).expect("Wrong format");
         Self::try_from(tg).unwrap()
     }
@@ -119,11 +192,57 @@ impl MachineBinary {
         let mut transition_texts = Vec::new();
         // let n_states = self.n_states();
         for (i, transition) in self.transitions_used_eval().iter().enumerate().step_by(2) {
-            let s = format!("{transition}{}", self.transition(i + 3));
+            let s = format!("{transition}{}", self.transition_array_id(i + 3));
             transition_texts.push(s);
         }
 
-        transition_texts.join("_")
+        standard_tm::join_states(&transition_texts)
+    }
+
+    /// Creates the machine from a 30-byte record of the bbchallenge seed database. \
+    /// Each of the 10 transitions A0 A1 B0 ... E1 is stored as 3 bytes: symbol (0/1), direction (R=0, L=1)
+    /// and next state (A=1, halt=0). An undefined transition is stored as 0 0 0. \
+    /// <https://bbchallenge.org/method#format> \
+    /// The machine always has 5 states, as in the seed database.
+    pub fn from_bb_challenge_record(
+        record: &[u8; BB_CHALLENGE_RECORD_SIZE],
+    ) -> Result<Self, TransitionError> {
+        let mut transitions = TRANSITION_TABLE_BINARY_DEFAULT;
+        for (i, tr_bytes) in record.chunks_exact(3).enumerate() {
+            transitions[i + 2] = TransitionBinary::try_new(tr_bytes.try_into().unwrap())?;
+        }
+
+        Ok(Self::new_with_n_states(
+            transitions,
+            BB_CHALLENGE_RECORD_N_STATES,
+        ))
+    }
+
+    /// Returns the machine as 30-byte record of the bbchallenge seed database,
+    /// see [MachineBinary::from_bb_challenge_record]. \
+    /// Machines with less than 5 states are filled with undefined transitions.
+    /// Note that a halt transition writing 0 and moving right (0RZ) is stored as undefined (---).
+    /// # Panics
+    /// Panics if the machine has more than 5 states.
+    pub fn to_bb_challenge_record(&self) -> [u8; BB_CHALLENGE_RECORD_SIZE] {
+        let n_states = self.n_states();
+        assert!(
+            n_states <= BB_CHALLENGE_RECORD_N_STATES,
+            "bbchallenge record requires max {BB_CHALLENGE_RECORD_N_STATES} states, machine has {n_states}."
+        );
+        let mut record = [0; BB_CHALLENGE_RECORD_SIZE];
+        for (tr, tr_bytes) in self.transitions[2..(n_states + 1) * 2]
+            .iter()
+            .zip(record.chunks_exact_mut(3))
+        {
+            if !tr.is_undefined() {
+                tr_bytes[0] = tr.symbol() as u8;
+                tr_bytes[1] = tr.is_dir_left() as u8;
+                tr_bytes[2] = tr.state() as u8;
+            }
+        }
+
+        record
     }
 
     /// Returns the transition table as formatted table (for print output).
@@ -144,7 +263,7 @@ impl MachineBinary {
             .enumerate()
             .take(states)
         {
-            s.push((i as u8 + b'A') as char);
+            s.push(standard_tm::state_to_char(i + 1));
             s.push(' ');
             // transition 0
             s.push_str(&t.to_string());
@@ -184,7 +303,7 @@ impl MachineBinary {
         {
             s.push_str("  <tr>\n");
             s.push_str("    <td>");
-            s.push((i as u8 + b'A') as char);
+            s.push(standard_tm::state_to_char(i + 1));
             s.push_str("</td>\n");
             // transition 0
             s.push_str("    <td>");
@@ -253,8 +372,24 @@ impl MachineBinary {
         max_state / 2
     }
 
-    /// Returns the transition for the array id, which is state * 2 + symbol. A0 = 2.
-    pub fn transition(&self, array_id: usize) -> TransitionBinary {
+    /// Returns the transition for the state and the read symbol.
+    /// # Panics
+    /// In debug mode if the state is halt or not used in this machine.
+    #[inline]
+    pub fn transition(&self, state: State, read: Symbol) -> TransitionBinary {
+        debug_assert!(
+            !state.is_halt() && (self.n_states() == 0 || state.value() <= self.n_states()),
+            "State {} has no transition in a machine with {} states.",
+            state.to_char(),
+            self.n_states()
+        );
+        self.transitions[state.value() * 2 + read.value()]
+    }
+
+    /// Returns the transition for the array id, which is state * 2 + symbol. A0 = 2. \
+    /// Prefer [MachineBinary::transition], this is for loops over all fields and the deciders which keep the array id.
+    #[inline]
+    pub fn transition_array_id(&self, array_id: usize) -> TransitionBinary {
         self.transitions[array_id]
     }
 
@@ -297,8 +432,15 @@ impl MachineBinary {
     // Rotating backward all machines can be skipped if the first entry is --- or once the last entry is reached.
     // Need to think about it.
     pub fn normalized_id_calc(&self) -> IdNormalized {
+        self.normalized_id_calc_ordered(TransitionOrder::default())
+    }
+
+    /// Calculates the id as [Self::normalized_id_calc] for an enumeration with the given transition order,
+    /// e.g. to compare the ids with other published enumerations.
+    pub fn normalized_id_calc_ordered(&self, order: TransitionOrder) -> IdNormalized {
         let n_states = self.n_states();
-        let tr_permutations = TransitionBinary::create_all_transition_permutations(n_states);
+        let tr_permutations =
+            TransitionBinary::create_all_transition_permutations_ordered(n_states, order);
         #[cfg(not(feature = "normalized_id_reversed"))]
         return Self::calc_normalized_id_forward(&self, &tr_permutations);
         #[cfg(feature = "normalized_id_reversed")]
@@ -448,9 +590,51 @@ impl MachineBinary {
         self.transitions[0].transition &= !FILTER_TABLE_SELF_REF;
     }
 
+    /// Returns the static features, which are calculated if not stored in the machine.
+    pub fn features(&self) -> MachineFeatures {
+        let f = self.transitions[1].transition;
+        if f & FEATURES_SET == 0 {
+            return self.features_calc();
+        }
+        MachineFeatures {
+            halt_array_id: (f & FILTER_FEATURE) as u8,
+            num_left: ((f >> 4) & FILTER_FEATURE) as u8,
+            num_self_ref: ((f >> 8) & FILTER_FEATURE) as u8,
+        }
+    }
+
+    /// Returns the static features and stores them in the machine (in the unused transition\[1\]).
+    pub fn features_store_result(&mut self) -> MachineFeatures {
+        let features = self.features();
+        self.transitions[1].transition = FEATURES_SET
+            | features.halt_array_id as TransitionType
+            | (features.num_left as TransitionType) << 4
+            | (features.num_self_ref as TransitionType) << 8;
+        features
+    }
+
+    fn features_calc(&self) -> MachineFeatures {
+        let mut features = MachineFeatures::default();
+        for (id, t) in self.transitions_used_eval().iter().enumerate() {
+            if t.is_halt() {
+                if features.halt_array_id == 0 {
+                    features.halt_array_id = (id + 2) as u8;
+                }
+                continue;
+            }
+            if t.is_dir_left() {
+                features.num_left += 1;
+            }
+            if t.self_ref_array_id() == id + 2 {
+                features.num_self_ref += 1;
+            }
+        }
+        features
+    }
+
     // Returns the machine table field name from the transition array id in an 1D-array, e.g. 2 -> A0.
     pub fn array_id_to_field_name(arr_id: usize) -> String {
-        let state = ((arr_id / 2) as u8 + b'A' - 1) as char;
+        let state = standard_tm::state_to_char(arr_id / 2);
         let symbol = ((arr_id & 1) as u8 + b'0') as char;
         format!("{state}{symbol}")
     }
@@ -538,9 +722,26 @@ impl Display for MachineBinary {
     }
 }
 
+/// The machine is serialized in the Standard TM Text Format, e.g. "1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA".
+#[cfg(feature = "serde")]
+impl serde::Serialize for MachineBinary {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_standard_tm_text_format())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MachineBinary {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tm = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        Self::try_from_standard_tm_text_format(&tm).map_err(serde::de::Error::custom)
+    }
+}
+
 /// This struct is used in DataProvider to allow an index id. \
 /// To keep the size small, instead of Option<id> the u64::MAX is used to indicate not used.
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MachineId {
     id: u64,
     machine: MachineBinary,
@@ -774,3 +975,66 @@ impl NotableMachineBinary {
         MachineId::new_no_id(m)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transition_by_state_and_symbol() {
+        let machine = NotableMachineBinary::BB3Max.machine();
+        // 1RB---_1LB0RC_1LC1LA
+        assert_eq!(
+            machine.transition(State::A, Symbol::ZERO),
+            machine.transition_start()
+        );
+        assert!(machine.transition(State::A, Symbol::ONE).is_halt());
+        let c1 = machine.transition(State::new(3), Symbol::ONE);
+        assert_eq!(c1, machine.transition_array_id(7));
+        assert_eq!(c1.next_state(), State::A);
+        assert_eq!(State::new(3).to_char(), 'C');
+    }
+
+    #[test]
+    fn bb_challenge_record_round_trip() {
+        // record bytes as in the seed database
+        let tm = "1RB1LC_1RC1RB_1RD0LE_1LA1LD_---0LA";
+        let record: [u8; BB_CHALLENGE_RECORD_SIZE] = [
+            1, 0, 2, 1, 1, 3, 1, 0, 3, 1, 0, 2, 1, 0, 4, 0, 1, 5, 1, 1, 1, 1, 1, 4, 0, 0, 0, 0, 1,
+            1,
+        ];
+        let machine = MachineBinary::from_bb_challenge_record(&record).unwrap();
+        assert_eq!(machine.to_standard_tm_text_format(), tm);
+        assert_eq!(machine.n_states(), 5);
+        assert_eq!(machine.to_bb_challenge_record(), record);
+        let machine = MachineBinary::try_from_standard_tm_text_format(tm).unwrap();
+        assert_eq!(machine.to_bb_challenge_record(), record);
+
+        // smaller machines are filled with undefined transitions, halt is kept
+        let machine =
+            MachineBinary::try_from_standard_tm_text_format("1RB1RZ_1LB0RC_1LC1LA").unwrap();
+        let record = machine.to_bb_challenge_record();
+        assert_eq!(record[3..6], [1, 0, 0]);
+        assert!(record[18..].iter().all(|b| *b == 0));
+        let m_back = MachineBinary::from_bb_challenge_record(&record).unwrap();
+        assert_eq!(
+            m_back.to_standard_tm_text_format(),
+            "1RB1RZ_1LB0RC_1LC1LA_------_------"
+        );
+
+        let mut record = [0; BB_CHALLENGE_RECORD_SIZE];
+        record[1] = 7;
+        assert_eq!(
+            MachineBinary::from_bb_challenge_record(&record),
+            Err(TransitionError::InvalidDirection(7))
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn transition_of_halt_state_panics() {
+        let machine = NotableMachineBinary::BB3Max.machine();
+        machine.transition(State::HALT, Symbol::ZERO);
+    }
+}
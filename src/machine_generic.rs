@@ -1,14 +1,20 @@
 //! This is a Turing Machine. It is called Generic as it is working with different numbers of symbols and states. \
 //! The limit is set to 10 symbols and 10 states, which should be sufficient for now. \
-//! Currently this only serves as intermediate format to read machine data and convert it then to MachineBinary,
-//! as the Busy Beaver Challenge only works with the symbols 0 and 1, which can be handled more efficiently.
+//! It serves as intermediate format to read machine data and convert it then to MachineBinary,
+//! as the Busy Beaver Challenge only works with the symbols 0 and 1, which can be handled more efficiently. \
+//! Machines with more than two symbols, e.g. BB(2,3) or BB(3,3), are run directly in this format by
+//! [DeciderGeneric](crate::decider::decider_generic::DeciderGeneric) and enumerated by
+//! [EnumeratorGeneric](crate::data_provider::enumerator_generic::EnumeratorGeneric).
 // TODO convert to use with Enums, the machine is not good to use anyhow
 // TODO Efficient Transition for BB(8,8) or BB(4/10), see what is reasonable. (symbols, states). bb_challenge deals with (6,2), (2,6) and (4,3)
 // largest according to wiki pages (https://wiki.bbchallenge.org/wiki/Champions).
 
 use std::fmt::Display;
 
-use crate::config::{MAX_STATES_GENERIC, MAX_SYMBOLS_GENERIC};
+use crate::{
+    config::{MAX_STATES_GENERIC, MAX_SYMBOLS_GENERIC},
+    format::standard_tm::{self, STATE_SEPARATOR, TRANSITION_UNDEFINED},
+};
 
 pub type TransitionTableGenericArray =
     [[TransitionGeneric; MAX_SYMBOLS_GENERIC]; MAX_STATES_GENERIC + 1];
@@ -77,17 +83,40 @@ pub struct MachineGeneric {
 }
 
 impl MachineGeneric {
+    /// Creates a machine with n_states states and n_symbols symbols, where all transitions are undefined ('---').
+    pub fn new_undefined(n_states: usize, n_symbols: usize) -> Self {
+        assert!(
+            (1..=MAX_STATES_GENERIC).contains(&n_states)
+                && (2..=MAX_SYMBOLS_GENERIC).contains(&n_symbols),
+            "A machine can have up to {MAX_STATES_GENERIC} states and 2 to {MAX_SYMBOLS_GENERIC} symbols."
+        );
+        let mut transitions = TRANSITION_TABLE_GENERIC_DEFAULT;
+        for line in transitions.iter_mut().skip(1).take(n_states) {
+            for transition in line.iter_mut().take(n_symbols) {
+                *transition = TRANSITION_HALT;
+            }
+        }
+
+        Self {
+            id: None,
+            transitions,
+        }
+    }
+
     /// Creates the transition table from the Standard TM Text Format \
     /// <https://www.sligocki.com/2022/10/09/standard-tm-format.html>
     pub fn try_from_standard_tm_text_format(transitions_text: &str) -> Result<Self, &'static str> {
         let mut transitions = TRANSITION_TABLE_GENERIC_DEFAULT;
-        let transition_tuples: Vec<&str> = transitions_text.split('_').collect();
+        let transition_tuples: Vec<&str> = transitions_text.split(STATE_SEPARATOR).collect();
         if transition_tuples.len() > MAX_STATES_GENERIC {
             // println!("{:?}", transition_tuples);
             // println!("{}", transition_tuples.len());
             return Err("The number of table states exceeds the states set in MAX_STATES_GENERIC!");
         }
         let len_line = transition_tuples.first().unwrap().len();
+        if len_line == 0 || !len_line.is_multiple_of(3) {
+            return Err("Expected a format like '1RB1LC_1RC1RB_1RD0LE_1LA1LD_1RZ0LA'. Each transition needs three characters.");
+        }
         if len_line / 3 > MAX_SYMBOLS_GENERIC {
             return Err(
                 "The number of table symbols exceeds the symbols set in MAX_SYMBOLS_GENERIC!",
@@ -140,7 +169,7 @@ impl MachineGeneric {
             transition_texts.push(s);
         }
 
-        transition_texts.join("_")
+        standard_tm::join_states(&transition_texts)
     }
 
     // Returns the transition for state (numeric A=1, B=2 etc.) and read symbol.
@@ -152,6 +181,28 @@ impl MachineGeneric {
         self.transitions[state as usize][symbol as usize]
     }
 
+    /// Sets the transition for state (numeric A=1, B=2 etc.) and read symbol.
+    pub fn set_transition(
+        &mut self,
+        state: StateType,
+        symbol: SymbolType,
+        transition: TransitionGeneric,
+    ) {
+        self.transitions[state as usize][symbol as usize] = transition;
+    }
+
+    /// Returns the number of halt transitions in the used part of the table.
+    pub fn count_halt_transitions(&self) -> usize {
+        let dim = self.dimensions();
+        self.transitions
+            .iter()
+            .skip(1)
+            .take(dim.n_states)
+            .flat_map(|line| line.iter().take(dim.n_symbols))
+            .filter(|t| t.is_halt())
+            .count()
+    }
+
     /// Returns the number of (states, symbols) used. Symbol is the highest used symbol, e.g. 1 for machines writing only 0 and 1. \
     /// As this is evaluating the dimensions in a loop, this is comparatively slow and should not be used in extensive loops.
     pub fn dimensions(&self) -> MachineDimensions {
@@ -217,7 +268,7 @@ impl Display for MachineGeneric {
             .take(dim.n_states)
         {
             // status as letter
-            s.push(((state_no - 1) as u8 + b'A') as char);
+            s.push(standard_tm::state_to_char(state_no));
             // transitions
             for transition in transition_line.iter().take(dim.n_symbols) {
                 s.push(' ');
@@ -276,9 +327,8 @@ impl TransitionGeneric {
         let state_next = match transition[2] {
             1..9 => transition[2],
             b'1'..b'9' => transition[2] - b'0',
-            b'A'..b'Y' => transition[2] - b'A' + 1,
-            // b'-' | b'Z' => 0,
-            _ => STATE_HALT_GENERIC,
+            // 'Z', '-' and unknown chars are halt
+            c => standard_tm::state_from_char(c).map_or(STATE_HALT_GENERIC, |s| s as StateType),
         };
         assert!(state_next <= MAX_STATES_GENERIC as u8);
 
@@ -289,6 +339,31 @@ impl TransitionGeneric {
         }
     }
 
+    /// New transition from its fields, e.g. 2RB is (2, [DIR_RIGHT], [B]).
+    pub const fn new_fields(
+        symbol_write: SymbolType,
+        direction: MoveType,
+        state_next: StateType,
+    ) -> Self {
+        Self {
+            symbol_write,
+            direction,
+            state_next,
+        }
+    }
+
+    /// Returns true if the machine halts on this transition ('---' or e.g. '1RZ').
+    #[inline]
+    pub fn is_halt(&self) -> bool {
+        self.state_next == STATE_HALT_GENERIC
+    }
+
+    /// Returns true if the transition writes a symbol, which a halt transition like '---' does not.
+    #[inline]
+    pub fn has_symbol_write(&self) -> bool {
+        self.symbol_write < SYMBOL_UNUSED
+    }
+
     pub fn is_unused(&self) -> bool {
         self.symbol_write == SYMBOL_UNUSED
     }
@@ -319,22 +394,18 @@ impl Display for TransitionGeneric {
         if self.is_unused()
             || (self.state_next == STATE_HALT_GENERIC && self.direction == DIR_UNDEFINED)
         {
-            return write!(f, "---");
+            return write!(f, "{TRANSITION_UNDEFINED}");
         }
         let write_symbol = match self.symbol_write {
             0..=9 => (self.symbol_write as u8 + b'0') as char,
             _ => '-',
         };
         let move_next = match self.direction {
-            -1 => 'L',
-            1 => 'R',
+            -1 => standard_tm::DIR_LEFT_CHAR,
+            1 => standard_tm::DIR_RIGHT_CHAR,
             _ => '-',
         };
-        let next_state = if self.state_next == 0 {
-            'Z'
-        } else {
-            (self.state_next + 64) as char
-        };
+        let next_state = standard_tm::state_to_char(self.state_next as usize);
         write!(f, "{write_symbol}{move_next}{next_state}")
     }
 }
@@ -4,18 +4,68 @@ use num_format::ToFormattedString;
 
 use crate::{
     config::{user_locale, StepBig},
+    decider::DeciderName,
     machine_binary::{MachineBinary, MachineId},
-    status::MachineStatus,
+    space_time::SpaceTimeDiagram,
+    status::{MachineStatus, UndecidedReason},
 };
 
 /// Machine with its status and an optional id for result and display.
 /// This is designed to be immutable and only created from another machine.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MachineInfo {
     /// Outside id, e.g. file machine id. Normalized Id is always calculated, since it is only used for display purposes.
     id: Option<u64>,
     machine: MachineBinary,
     status: MachineStatus,
+    /// Stage of the decider chain which first returned the machine undecided, only set for recorded
+    /// undecided machines.
+    undecided_stage: Option<UndecidedStage>,
+}
+
+/// The stage of the decider chain which first returned a machine undecided, with the undecided status
+/// of this stage, e.g. the step limit of the cycler. \
+/// The status of the [MachineInfo] is the status of the last decider, so follow-up runs can select e.g.
+/// the machines which the first decider left undecided due to its step limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UndecidedStage {
+    /// Position of the decider in the chain, starting with 0.
+    pub stage_no: usize,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::utils::deserialize_static_str")
+    )]
+    pub decider_name: DeciderName,
+    pub status: MachineStatus,
+}
+
+impl UndecidedStage {
+    /// Undecided reason of the stage, Undefined if the status is not undecided.
+    pub fn reason(&self) -> UndecidedReason {
+        match self.status {
+            MachineStatus::Undecided(reason, _, _) => reason,
+            _ => UndecidedReason::Undefined,
+        }
+    }
+
+    /// Key to sort and group by stage and reason.
+    fn sort_key(stage: Option<&Self>) -> (usize, usize) {
+        stage.map_or((usize::MAX, 0), |s| {
+            (s.stage_no, s.status.as_code() as usize)
+        })
+    }
+}
+
+impl Display for UndecidedStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stage {} {}: {}",
+            self.stage_no, self.decider_name, self.status
+        )
+    }
 }
 
 impl MachineInfo {
@@ -24,6 +74,7 @@ impl MachineInfo {
             id: None,
             machine,
             status,
+            undecided_stage: None,
         }
     }
 
@@ -32,6 +83,7 @@ impl MachineInfo {
             id: machine.id_as_option(),
             machine: *machine.machine(),
             status,
+            undecided_stage: None,
         }
     }
 
@@ -40,6 +92,7 @@ impl MachineInfo {
             id: None,
             machine: machine,
             status: status,
+            undecided_stage: None,
         }
     }
 
@@ -48,9 +101,16 @@ impl MachineInfo {
             id: machine.id_as_option(),
             machine: *machine.machine(),
             status: *status,
+            undecided_stage: None,
         }
     }
 
+    /// Sets the stage of the decider chain which first returned the machine undecided.
+    pub fn with_undecided_stage(mut self, undecided_stage: Option<UndecidedStage>) -> Self {
+        self.undecided_stage = undecided_stage;
+        self
+    }
+
     pub fn has_id(&self) -> bool {
         self.id.is_some()
     }
@@ -112,9 +172,19 @@ impl MachineInfo {
         self.status
     }
 
+    /// Stage of the decider chain which first returned the machine undecided, see [UndecidedStage].
+    pub fn undecided_stage(&self) -> Option<&UndecidedStage> {
+        self.undecided_stage.as_ref()
+    }
+
     pub fn to_standard_tm_text_format(&self) -> String {
         self.machine.to_standard_tm_text_format()
     }
+
+    /// Space-time diagram of the step range, e.g. to write it as PNG or SVG.
+    pub fn space_time_diagram(&self, first_step: u64, num_steps: u64) -> SpaceTimeDiagram {
+        SpaceTimeDiagram::new(&self.machine, first_step, num_steps)
+    }
 }
 
 // impl From<&MachineCompactDeprecated> for MachineInfo {
@@ -146,6 +216,7 @@ impl From<&MachineBinary> for MachineInfo {
             id: None,
             machine: *machine,
             status: MachineStatus::NoDecision,
+            undecided_stage: None,
         }
     }
 }
@@ -173,7 +244,7 @@ impl Ord for MachineInfo {
 impl Display for MachineInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let locale = &user_locale();
-        let s = match self.id {
+        let mut s = match self.id {
             Some(id) => {
                 format!(
                     "Machine {:>12}, {}: {}",
@@ -191,6 +262,9 @@ impl Display for MachineInfo {
                 )
             }
         };
+        if let Some(stage) = self.undecided_stage {
+            s.push_str(&format!(", first undecided in {stage}"));
+        }
         // match self.status {
         //     MachineStatus::Undecided(steps, tape_len) => {
         //         s.push_str(format!(
@@ -207,3 +281,284 @@ impl Display for MachineInfo {
         write!(f, "{s}")
     }
 }
+
+/// List of machines with their status, e.g. the recorded undecided machines of a result. \
+/// The sorting is stable, so machines with the same key keep their order. The machines removed
+/// by [MachineInfoList::truncate] are counted and shown in Display.
+#[derive(Debug, Default, Clone)]
+pub struct MachineInfoList {
+    machines: Vec<MachineInfo>,
+    /// Number of machines removed by truncate.
+    num_truncated: usize,
+}
+
+impl MachineInfoList {
+    pub fn new(machines: Vec<MachineInfo>) -> Self {
+        Self {
+            machines,
+            num_truncated: 0,
+        }
+    }
+
+    /// Sorts by the given id or the normalized id if no id is given.
+    pub fn sort_by_id(&mut self) {
+        self.machines.sort_by_cached_key(|m| m.id());
+    }
+
+    /// Sorts by steps descending, machines without steps in the status are last.
+    /// Machines with the same steps keep their order, so sort by id first to get them sorted by id.
+    pub fn sort_by_steps(&mut self) {
+        self.machines
+            .sort_by_key(|m| std::cmp::Reverse(m.status().steps().unwrap_or_default()));
+    }
+
+    /// Sorts by the stage which first returned the machine undecided and by its undecided reason there,
+    /// see [MachineInfo::undecided_stage]. Machines without stage are last.
+    /// Machines of the same stage and reason keep their order, so sort by id first to get them sorted by id.
+    pub fn sort_by_undecided_stage(&mut self) {
+        self.machines
+            .sort_by_key(|m| UndecidedStage::sort_key(m.undecided_stage()));
+    }
+
+    /// Splits the machines into lists with the same first undecided stage and reason, in the order of
+    /// [MachineInfoList::sort_by_undecided_stage]. Each list holds at least one machine.
+    pub fn group_by_undecided_stage(mut self) -> Vec<MachineInfoList> {
+        self.sort_by_undecided_stage();
+        self.machines
+            .chunk_by(|a, b| {
+                UndecidedStage::sort_key(a.undecided_stage())
+                    == UndecidedStage::sort_key(b.undecided_stage())
+            })
+            .map(MachineInfoList::from)
+            .collect()
+    }
+
+    /// Writes the machines grouped by their first undecided stage, each group with a heading line, see
+    /// [MachineInfoList::group_by_undecided_stage].
+    pub fn write_machines_by_undecided_stage(
+        &self,
+        f: &mut impl std::fmt::Write,
+    ) -> std::fmt::Result {
+        let locale = user_locale();
+        for group in self.clone().group_by_undecided_stage() {
+            match group[0].undecided_stage() {
+                Some(stage) => writeln!(
+                    f,
+                    "Stage {} {}, {:?}: {} machines",
+                    stage.stage_no,
+                    stage.decider_name,
+                    stage.reason(),
+                    group.len().to_formatted_string(&locale)
+                )?,
+                None => writeln!(
+                    f,
+                    "Without stage: {} machines",
+                    group.len().to_formatted_string(&locale)
+                )?,
+            }
+            group.write_machines(f, true)?;
+        }
+        Ok(())
+    }
+
+    /// Sorts by id and removes machines with the same id, e.g. after merging the results of several runs.
+    /// # Returns
+    /// Number of removed machines.
+    pub fn dedup(&mut self) -> usize {
+        let len = self.machines.len();
+        self.sort_by_id();
+        self.machines.dedup_by_key(|m| m.id());
+        len - self.machines.len()
+    }
+
+    /// Keeps the first len machines and counts the removed machines.
+    /// # Returns
+    /// Number of removed machines.
+    pub fn truncate(&mut self, len: usize) -> usize {
+        let num_removed = self.machines.len().saturating_sub(len);
+        self.machines.truncate(len);
+        self.num_truncated += num_removed;
+        num_removed
+    }
+
+    /// Number of machines removed by [MachineInfoList::truncate].
+    pub fn num_truncated(&self) -> usize {
+        self.num_truncated
+    }
+
+    pub fn machines(&self) -> &[MachineInfo] {
+        &self.machines
+    }
+
+    pub fn into_vec(self) -> Vec<MachineInfo> {
+        self.machines
+    }
+
+    /// Writes one line per machine with right aligned machine numbers, optionally with the status.
+    pub fn write_machines(
+        &self,
+        f: &mut impl std::fmt::Write,
+        with_status: bool,
+    ) -> std::fmt::Result {
+        let locale = user_locale();
+        let ids: Vec<String> = self
+            .machines
+            .iter()
+            .map(|m| m.id().to_formatted_string(&locale))
+            .collect();
+        let len = ids.iter().map(|id| id.len()).max().unwrap_or_default();
+        for (m, id) in self.machines.iter().zip(ids.iter()) {
+            if with_status {
+                write!(
+                    f,
+                    "   Machine No. {id:>len$}: {}, {}",
+                    m.to_standard_tm_text_format(),
+                    m.status()
+                )?;
+                match m.undecided_stage() {
+                    Some(stage) => writeln!(f, ", first undecided in {stage}")?,
+                    None => writeln!(f)?,
+                }
+            } else {
+                writeln!(
+                    f,
+                    "   Machine No. {id:>len$}: {}",
+                    m.to_standard_tm_text_format()
+                )?;
+            }
+        }
+        if self.num_truncated > 0 {
+            writeln!(
+                f,
+                "   ... and {} more machines",
+                self.num_truncated.to_formatted_string(&locale)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::ops::Deref for MachineInfoList {
+    type Target = [MachineInfo];
+
+    fn deref(&self) -> &Self::Target {
+        &self.machines
+    }
+}
+
+impl From<Vec<MachineInfo>> for MachineInfoList {
+    fn from(machines: Vec<MachineInfo>) -> Self {
+        Self::new(machines)
+    }
+}
+
+impl From<&[MachineInfo]> for MachineInfoList {
+    fn from(machines: &[MachineInfo]) -> Self {
+        Self::new(machines.to_vec())
+    }
+}
+
+impl Display for MachineInfoList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_machines(f, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{machine_binary::NotableMachineBinary, status::UndecidedReason};
+
+    fn machine_info(id: u64, status: MachineStatus) -> MachineInfo {
+        let machine = NotableMachineBinary::BB3Max.machine_id();
+        MachineInfo::new_m_id(MachineId::new(id, *machine.machine()), status)
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn machine_info_serde_round_trip() {
+        let status = MachineStatus::Undecided(UndecidedReason::StepLimit, 100, 10);
+        let m_info = machine_info(7, status).with_undecided_stage(Some(UndecidedStage {
+            stage_no: 1,
+            decider_name: "Cycler",
+            status,
+        }));
+        let json = serde_json::to_string(&m_info).unwrap();
+        assert!(json.contains(r#""machine":"1RB---_1LB0RC_1LC1LA""#));
+        let back: MachineInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.id(), 7);
+        assert_eq!(back.machine(), m_info.machine());
+        assert_eq!(back.status(), status);
+        assert_eq!(back.undecided_stage(), m_info.undecided_stage());
+
+        let r = serde_json::from_str::<MachineBinary>(r#""no machine""#);
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn machine_info_list_sort_dedup_truncate() {
+        let undecided = MachineStatus::Undecided(UndecidedReason::StepLimit, 100, 10);
+        let mut list = MachineInfoList::new(vec![
+            machine_info(30, MachineStatus::DecidedHalt(5)),
+            machine_info(10, MachineStatus::DecidedHalt(21)),
+            machine_info(20, undecided),
+            machine_info(10, MachineStatus::DecidedHalt(21)),
+            machine_info(40, MachineStatus::DecidedHaltField(5, 3)),
+        ]);
+
+        list.sort_by_steps();
+        let ids: Vec<u64> = list.iter().map(|m| m.id()).collect();
+        // stable: 10 before 10, 30 before 40
+        assert_eq!(ids, [20, 10, 10, 30, 40]);
+
+        assert_eq!(list.dedup(), 1);
+        let ids: Vec<u64> = list.iter().map(|m| m.id()).collect();
+        assert_eq!(ids, [10, 20, 30, 40]);
+
+        assert_eq!(list.truncate(2), 2);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.num_truncated(), 2);
+        let s = list.to_string();
+        let text = NotableMachineBinary::BB3Max
+            .machine_id()
+            .to_standard_tm_text_format();
+        assert!(s.contains(&format!(
+            "Machine No. 10: {text}, Decided: Halts after 21 steps"
+        )));
+        assert!(s.ends_with("... and 2 more machines\n"));
+    }
+
+    #[test]
+    fn machine_info_list_group_by_undecided_stage() {
+        let stage = |stage_no, reason| {
+            Some(UndecidedStage {
+                stage_no,
+                decider_name: "Cycler",
+                status: MachineStatus::Undecided(reason, 100, 10),
+            })
+        };
+        let undecided = MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, 500, 10);
+        let list = MachineInfoList::new(vec![
+            machine_info(1, undecided),
+            machine_info(2, undecided).with_undecided_stage(stage(0, UndecidedReason::StepLimit)),
+            machine_info(3, undecided).with_undecided_stage(stage(1, UndecidedReason::StepLimit)),
+            machine_info(4, undecided)
+                .with_undecided_stage(stage(0, UndecidedReason::TapeSizeLimit)),
+            machine_info(5, undecided).with_undecided_stage(stage(0, UndecidedReason::StepLimit)),
+        ]);
+        let groups: Vec<Vec<u64>> = list
+            .clone()
+            .group_by_undecided_stage()
+            .iter()
+            .map(|g| g.iter().map(|m| m.id()).collect())
+            .collect();
+        assert_eq!(groups, [vec![2, 5], vec![4], vec![3], vec![1]]);
+
+        let mut s = String::new();
+        list.write_machines_by_undecided_stage(&mut s).unwrap();
+        assert!(s.starts_with("Stage 0 Cycler, StepLimit: 2 machines\n"));
+        assert!(s.contains("Stage 0 Cycler, TapeSizeLimit: 1 machines\n"));
+        assert!(s.contains(", first undecided in stage 1 Cycler: "));
+        assert!(s.contains("Without stage: 1 machines\n"));
+    }
+}
@@ -0,0 +1,240 @@
+//! Simple step by step simulation of a machine as an [Iterator] of [Step]s, e.g. for analysis code like
+//! `.take_while` or `.filter` over the execution, without the callback and trace machinery of the deciders. \
+//! Speed is not relevant here, so the tape is one byte per cell and grows on both sides.
+//! # Example
+//! ```
+//! use bb_challenge::{machine_binary::NotableMachineBinary, machine_runner::MachineRunner};
+//! let machine = NotableMachineBinary::BB3Max.machine();
+//! let mut runner = MachineRunner::new(&machine);
+//! // steps in state A, the last step is the halt step
+//! let num_state_a = runner.steps().filter(|step| step.state == 1).count();
+//! assert!(num_state_a > 0 && runner.is_halted());
+//! assert_eq!(runner.num_steps(), 21);
+//! ```
+
+use std::collections::VecDeque;
+
+use crate::{
+    machine_binary::{MachineBinary, State, Symbol},
+    transition_binary::TransitionBinary,
+};
+
+/// Single step of the [MachineRunner].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    /// State before the step (A = 1).
+    pub state: usize,
+    /// Symbol read by the head.
+    pub read: usize,
+    pub transition: TransitionBinary,
+    /// Movement of the head: -1 left, 1 right, 0 for the halt step.
+    pub head_delta: i8,
+}
+
+impl Step {
+    pub fn is_halt(&self) -> bool {
+        self.transition.is_halt()
+    }
+
+    /// Symbol written, the halt step does not write.
+    pub fn symbol_written(&self) -> usize {
+        if self.is_halt() {
+            self.read
+        } else {
+            self.transition.symbol_usize()
+        }
+    }
+}
+
+/// Simulator on a growing tape, see [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct MachineRunner<'a> {
+    machine: &'a MachineBinary,
+    tape: VecDeque<u8>,
+    /// Index of the head in the tape.
+    head: usize,
+    /// Position of the head relative to the start cell.
+    head_pos: i64,
+    state: usize,
+    num_steps: u64,
+    is_halted: bool,
+}
+
+impl<'a> MachineRunner<'a> {
+    pub fn new(machine: &'a MachineBinary) -> Self {
+        Self {
+            machine,
+            tape: VecDeque::from([0]),
+            head: 0,
+            head_pos: 0,
+            state: 1,
+            num_steps: 0,
+            is_halted: false,
+        }
+    }
+
+    /// Current state (A = 1).
+    pub fn state(&self) -> usize {
+        self.state
+    }
+
+    /// Position of the head relative to the start cell.
+    pub fn head_pos(&self) -> i64 {
+        self.head_pos
+    }
+
+    /// Steps executed so far, including the halt step.
+    pub fn num_steps(&self) -> u64 {
+        self.num_steps
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.is_halted
+    }
+
+    /// Number of cells of the tape, which are all cells visited by the head.
+    pub fn tape_len(&self) -> usize {
+        self.tape.len()
+    }
+
+    /// Number of ones on the tape.
+    pub fn count_ones(&self) -> usize {
+        self.tape.iter().filter(|&&s| s == 1).count()
+    }
+
+    /// Symbol of the cell at the position relative to the start cell, 0 for cells not visited yet.
+    pub fn cell(&self, pos: i64) -> u8 {
+        let i = self.head as i64 + pos - self.head_pos;
+        if i < 0 || i >= self.tape.len() as i64 {
+            0
+        } else {
+            self.tape[i as usize]
+        }
+    }
+
+    /// Returns the cells from the head offset to the end of the tape in the direction, without the trailing
+    /// 0 cells, e.g. to compare the tape ahead of the head in two configurations.
+    pub fn cells_from_head(&self, offset: i64, dir_right: bool) -> Vec<u8> {
+        let start = self.head as i64 + offset;
+        let cell = |i: i64| {
+            if i < 0 || i >= self.tape.len() as i64 {
+                0
+            } else {
+                self.tape[i as usize]
+            }
+        };
+        let mut cells: Vec<u8> = if dir_right {
+            (start.min(self.tape.len() as i64)..self.tape.len() as i64)
+                .map(cell)
+                .collect()
+        } else {
+            (0.min(start)..=start).rev().map(cell).collect()
+        };
+        while cells.last() == Some(&0) {
+            cells.pop();
+        }
+
+        cells
+    }
+
+    /// Executes one step and returns it, None if the machine has halted. A halting step does not change the tape.
+    pub fn step(&mut self) -> Option<Step> {
+        if self.is_halted {
+            return None;
+        }
+        let read = self.tape[self.head] as usize;
+        let transition = self
+            .machine
+            .transition(State::new(self.state), Symbol::new(read));
+        let mut step = Step {
+            state: self.state,
+            read,
+            transition,
+            head_delta: 0,
+        };
+        self.num_steps += 1;
+        if transition.is_halt() {
+            self.is_halted = true;
+            return Some(step);
+        }
+        self.tape[self.head] = transition.symbol_usize() as u8;
+        if transition.is_dir_right() {
+            step.head_delta = 1;
+            self.head += 1;
+            if self.head == self.tape.len() {
+                self.tape.push_back(0);
+            }
+        } else {
+            step.head_delta = -1;
+            if self.head == 0 {
+                self.tape.push_front(0);
+            } else {
+                self.head -= 1;
+            }
+        }
+        self.head_pos += step.head_delta as i64;
+        self.state = transition.state() as usize;
+
+        Some(step)
+    }
+
+    /// Iterator over the steps, which ends after the halt step. It runs endlessly for non-halting machines,
+    /// so limit it e.g. with `.take(n)`.
+    pub fn steps(&mut self) -> Steps<'_, 'a> {
+        Steps { runner: self }
+    }
+}
+
+/// Iterator of [MachineRunner::steps].
+#[derive(Debug)]
+pub struct Steps<'r, 'a> {
+    runner: &'r mut MachineRunner<'a>,
+}
+
+impl Iterator for Steps<'_, '_> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        self.runner.step()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_binary::NotableMachineBinary;
+
+    #[test]
+    fn machine_runner_steps_bb4() {
+        let machine = NotableMachineBinary::BB4Max.machine();
+        let mut runner = MachineRunner::new(&machine);
+        let steps: Vec<Step> = runner.steps().collect();
+        assert_eq!(steps.len(), 107);
+        assert!(steps[106].is_halt());
+        assert_eq!(steps[106].head_delta, 0);
+        assert!(steps[..106].iter().all(|s| !s.is_halt()));
+        assert!(runner.is_halted());
+        assert_eq!(runner.step(), None);
+        // the halt step does not write
+        assert_eq!(runner.count_ones(), 12);
+        // the head position is the sum of the movements
+        let head_pos: i64 = steps.iter().map(|s| s.head_delta as i64).sum();
+        assert_eq!(runner.head_pos(), head_pos);
+    }
+
+    #[test]
+    fn machine_runner_steps_endless() {
+        // runs endlessly to the right
+        let machine = MachineBinary::try_from_standard_tm_text_format("1RB---_0RA---").unwrap();
+        let mut runner = MachineRunner::new(&machine);
+        let num_right = runner
+            .steps()
+            .take(100)
+            .take_while(|s| !s.is_halt())
+            .filter(|s| s.head_delta == 1)
+            .count();
+        assert_eq!(runner.num_steps(), 100);
+        assert_eq!(num_right, 100);
+        assert!(!runner.is_halted());
+    }
+}
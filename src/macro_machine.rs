@@ -0,0 +1,369 @@
+//! Block simulation of a machine: the k-cell macro machine. \
+//! The tape is divided into blocks of [MacroMachine::block_size] cells. The macro head stands between two blocks
+//! and faces one of them. A macro step simulates the machine inside the faced block until the head leaves the
+//! block to the left or right. The result (new block, state, direction, steps) depends only on the state,
+//! the side the head entered from and the block content, so it is calculated once and cached. \
+//! The tape is stored run-length encoded as (block, count) runs on both sides of the head. If the machine
+//! leaves a block on the opposite side in the same state, it does the same for all identical blocks of the run,
+//! so the whole run is passed in one macro step. This massively reduces the steps for bouncers and counters,
+//! which sweep over long runs of identical blocks. \
+//! The best block size differs per machine, [MacroMachine::detect_block_size] tries all sizes for some macro steps
+//! and takes the one with the most machine steps per macro step.
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     machine_binary::NotableMachineBinary,
+//!     macro_machine::{MacroMachine, MacroRunResult},
+//! };
+//!
+//! let machine = NotableMachineBinary::BB5Max.machine();
+//! let block_size = MacroMachine::detect_block_size(&machine);
+//! let mut mm = MacroMachine::new(&machine, block_size);
+//! assert_eq!(mm.run(50_000_000, 1_000_000), MacroRunResult::Halt(47_176_870));
+//! ```
+//! The [DeciderHaltMacro] uses this to check for halt, the [DeciderCounter] uses the block transitions
+//! to prove counters.
+//!
+//! [DeciderHaltMacro]: crate::decider::decider_halt_macro::DeciderHaltMacro
+//! [DeciderCounter]: crate::decider::decider_counter::DeciderCounter
+
+use crate::machine_binary::{MachineBinary, State, Symbol};
+
+/// Largest block size, the cache holds n_states * 2 * 2^block_size transitions.
+pub const BLOCK_SIZE_MAX: usize = 12;
+/// Macro steps run for each block size by [MacroMachine::detect_block_size].
+pub const DETECT_MACRO_STEPS: u64 = 500;
+
+/// Content of a block, bit 0 is the leftmost cell.
+pub type Block = u16;
+
+/// Result of simulating the machine inside one block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MacroTransition {
+    Move {
+        block: Block,
+        state: u8,
+        is_dir_right: bool,
+        steps: u64,
+    },
+    /// Halts after steps, including the halt step.
+    Halt(u64),
+    /// The machine never leaves the block.
+    Cycle,
+}
+
+/// Result of one macro step or a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroRunResult {
+    /// The machine has not ended yet (only for a single step).
+    Running,
+    /// The machine halts after this many steps (including the halt step).
+    Halt(u64),
+    /// The machine never halts, as it cycles inside one block or runs endlessly into the blank tape.
+    NonHalt,
+    /// The step limit or macro step limit was reached.
+    StepLimit,
+}
+
+/// k-cell macro machine, see [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct MacroMachine {
+    machine: MachineBinary,
+    block_size: usize,
+    /// Cached macro transitions, index ((state - 1) * 2 + entered from right) * 2^block_size + block.
+    cache: Vec<Option<MacroTransition>>,
+    state: u8,
+    is_facing_right: bool,
+    /// Runs (block, count) on both sides of the head, the last run is next to the head.
+    left: Vec<(Block, u64)>,
+    right: Vec<(Block, u64)>,
+    steps: u64,
+    num_macro_steps: u64,
+    /// Number of blocks on the tape, blank blocks are added when the head enters them.
+    num_blocks: u64,
+}
+
+impl MacroMachine {
+    /// Creates the macro machine in the start configuration. The block size must be 1..=[BLOCK_SIZE_MAX].
+    pub fn new(machine: &MachineBinary, block_size: usize) -> Self {
+        assert!(
+            (1..=BLOCK_SIZE_MAX).contains(&block_size),
+            "Block size {block_size} not in 1..={BLOCK_SIZE_MAX}."
+        );
+        Self {
+            machine: *machine,
+            block_size,
+            cache: vec![None; (machine.n_states() * 2) << block_size],
+            state: 1,
+            is_facing_right: true,
+            left: Vec::new(),
+            right: Vec::new(),
+            steps: 0,
+            num_macro_steps: 0,
+            num_blocks: 0,
+        }
+    }
+
+    /// Returns the block size with the most machine steps per macro step within [DETECT_MACRO_STEPS] macro steps.
+    /// If the machine ends within these steps, the smallest block size is returned.
+    pub fn detect_block_size(machine: &MachineBinary) -> usize {
+        let mut best = (1, 0);
+        for block_size in 1..=BLOCK_SIZE_MAX {
+            let mut mm = Self::new(machine, block_size);
+            if mm.run(u64::MAX, DETECT_MACRO_STEPS) != MacroRunResult::StepLimit {
+                return 1;
+            }
+            if mm.steps > best.1 {
+                best = (block_size, mm.steps);
+            }
+        }
+        best.0
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// The head faces the block on the right side, otherwise on the left side.
+    pub fn is_facing_right(&self) -> bool {
+        self.is_facing_right
+    }
+
+    /// Runs (block, count) on the right side of the head if right, otherwise left side.
+    /// The last run is next to the head, all blocks beyond the runs are blank.
+    pub fn runs(&self, is_right: bool) -> &[(Block, u64)] {
+        if is_right {
+            &self.right
+        } else {
+            &self.left
+        }
+    }
+
+    /// Sets the configuration, e.g. to simulate a part of a tape. The steps are reset, the transition cache is kept.
+    pub fn set_configuration(
+        &mut self,
+        state: u8,
+        is_facing_right: bool,
+        left: Vec<(Block, u64)>,
+        right: Vec<(Block, u64)>,
+    ) {
+        self.state = state;
+        self.is_facing_right = is_facing_right;
+        self.num_blocks = left.iter().chain(right.iter()).map(|(_, n)| n).sum();
+        self.left = left;
+        self.right = right;
+        self.steps = 0;
+        self.num_macro_steps = 0;
+    }
+
+    /// Returns (new block, new state, leaves to the right) if the machine enters the block in the state
+    /// from the given side. None if the machine halts or cycles inside the block.
+    pub fn block_transition(
+        &mut self,
+        state: u8,
+        is_from_right: bool,
+        block: Block,
+    ) -> Option<(Block, u8, bool)> {
+        match self.transition(state, is_from_right, block) {
+            MacroTransition::Move {
+                block,
+                state,
+                is_dir_right,
+                ..
+            } => Some((block, state, is_dir_right)),
+            MacroTransition::Halt(_) | MacroTransition::Cycle => None,
+        }
+    }
+
+    /// Machine steps executed so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn num_macro_steps(&self) -> u64 {
+        self.num_macro_steps
+    }
+
+    /// Number of tape cells visited so far (rounded up to whole blocks).
+    pub fn tape_size_cells(&self) -> u64 {
+        self.num_blocks * self.block_size as u64
+    }
+
+    /// Number of runs on the tape, which is the memory used.
+    pub fn num_runs(&self) -> usize {
+        self.left.len() + self.right.len()
+    }
+
+    /// Runs until the machine ends, more than step_limit steps are executed or the macro step limit is reached.
+    pub fn run(&mut self, step_limit: u64, macro_step_limit: u64) -> MacroRunResult {
+        while self.num_macro_steps < macro_step_limit {
+            match self.step() {
+                MacroRunResult::Running => {
+                    if self.steps > step_limit {
+                        return MacroRunResult::StepLimit;
+                    }
+                }
+                result => return result,
+            }
+        }
+        MacroRunResult::StepLimit
+    }
+
+    /// Executes one macro step.
+    pub fn step(&mut self) -> MacroRunResult {
+        self.num_macro_steps += 1;
+        let (block, count, is_on_tape) = match self.side(self.is_facing_right).last() {
+            Some(&(block, count)) => (block, count, true),
+            // blank tape
+            None => (0, 1, false),
+        };
+        match self.transition(self.state, !self.is_facing_right, block) {
+            MacroTransition::Halt(steps) => {
+                self.steps += steps;
+                MacroRunResult::Halt(self.steps)
+            }
+            MacroTransition::Cycle => MacroRunResult::NonHalt,
+            MacroTransition::Move {
+                block: block_new,
+                state,
+                is_dir_right,
+                steps,
+            } => {
+                let is_passing = is_dir_right == self.is_facing_right;
+                if !is_on_tape {
+                    if is_passing && state == self.state {
+                        // does the same with all following blank blocks
+                        return MacroRunResult::NonHalt;
+                    }
+                    self.num_blocks += 1;
+                }
+                // the whole run is passed if the state does not change
+                let n = if is_passing && state == self.state {
+                    count
+                } else {
+                    1
+                };
+                if is_on_tape {
+                    let side = self.side(self.is_facing_right);
+                    if n == count {
+                        side.pop();
+                    } else {
+                        side.last_mut().unwrap().1 -= n;
+                    }
+                }
+                self.steps = self.steps.saturating_add(steps.saturating_mul(n));
+                // passed blocks are behind the head, otherwise the block stays in front of the head
+                let side_new = self.side(!is_dir_right);
+                match side_new.last_mut() {
+                    Some((b, c)) if *b == block_new => *c += n,
+                    _ => side_new.push((block_new, n)),
+                }
+                self.state = state;
+                self.is_facing_right = is_dir_right;
+                MacroRunResult::Running
+            }
+        }
+    }
+
+    /// Runs on the right side of the head if right, otherwise left side.
+    fn side(&mut self, is_right: bool) -> &mut Vec<(Block, u64)> {
+        if is_right {
+            &mut self.right
+        } else {
+            &mut self.left
+        }
+    }
+
+    fn transition(&mut self, state: u8, is_from_right: bool, block: Block) -> MacroTransition {
+        let index = (((state as usize - 1) * 2 + is_from_right as usize) << self.block_size)
+            + block as usize;
+        if let Some(tr) = self.cache[index] {
+            return tr;
+        }
+        let tr = self.simulate_block(state, is_from_right, block);
+        self.cache[index] = Some(tr);
+        tr
+    }
+
+    /// Simulates the machine inside the block, cell 0 (bit 0) is the leftmost cell.
+    fn simulate_block(
+        &self,
+        mut state: u8,
+        is_from_right: bool,
+        mut block: Block,
+    ) -> MacroTransition {
+        let k = self.block_size as i32;
+        let mut pos = if is_from_right { k - 1 } else { 0 };
+        // more steps than configurations inside the block is a cycle
+        let steps_max = (self.machine.n_states() as u64 * k as u64) << k;
+        let mut steps = 0;
+        loop {
+            let read = (block >> pos) & 1;
+            let tr = self
+                .machine
+                .transition(State::new(state as usize), Symbol::new(read as usize));
+            steps += 1;
+            if tr.is_halt() {
+                return MacroTransition::Halt(steps);
+            }
+            block = (block & !(1 << pos)) | ((tr.is_symbol_one() as Block) << pos);
+            state = tr.state() as u8;
+            pos += if tr.is_dir_right() { 1 } else { -1 };
+            if pos < 0 || pos >= k {
+                return MacroTransition::Move {
+                    block,
+                    state,
+                    is_dir_right: pos >= k,
+                    steps,
+                };
+            }
+            if steps > steps_max {
+                return MacroTransition::Cycle;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_binary::{MachineId, NotableMachineBinary};
+
+    #[test]
+    fn macro_machine_halt_steps_all_block_sizes() {
+        for (machine, steps) in [
+            (NotableMachineBinary::BB3Max.machine(), 21),
+            (NotableMachineBinary::BB4Max.machine(), 107),
+        ] {
+            for block_size in 1..=BLOCK_SIZE_MAX {
+                let mut mm = MacroMachine::new(&machine, block_size);
+                assert_eq!(
+                    mm.run(1_000, 1_000),
+                    MacroRunResult::Halt(steps),
+                    "block size {block_size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn macro_machine_bb5_max_accelerated() {
+        let machine = NotableMachineBinary::BB5Max.machine();
+        let block_size = MacroMachine::detect_block_size(&machine);
+        let mut mm = MacroMachine::new(&machine, block_size);
+        assert_eq!(
+            mm.run(50_000_000, 10_000_000),
+            MacroRunResult::Halt(47_176_870)
+        );
+        assert!(mm.num_macro_steps() * 10 < mm.steps());
+
+        // runs endlessly to the right
+        let machine = MachineId::try_from("1RB---_0RA---").unwrap();
+        let mut mm = MacroMachine::new(machine.machine(), 2);
+        assert_eq!(mm.run(1_000, 1_000), MacroRunResult::NonHalt);
+    }
+}
@@ -0,0 +1,243 @@
+//! The output layout defines where files written by a decider run are placed.
+//!
+//! All output goes into a root directory, which is html_out_path of the config.toml if not set otherwise.
+//! Optionally each run creates its own timestamped sub directory, so the results of different runs do not mix.
+//! ```text
+//! <root>/[run_<timestamp>/]
+//!     events.jsonl          event log of the run
+//!     stats_snapshot.json   rolling counters of the run
+//!     verdicts.jsonl        verdict of each machine reaching a decider
+//! <root>/results.sqlite     machines, verdicts and metadata of all runs (feature sqlite)
+//!     <decider>_bb<n>/      html files of the decider, e.g. cycler_bb5
+//!     reports/              undecided machine lists, csv files
+//!     certificates/         proof certificates
+//!     checkpoints/          checkpoints to resume a run
+//!     site/                 static site of the run for publishing
+//! ./data/                   undecided machine lists of the result worker (see below)
+//! ```
+//! The undecided machine lists written by [save_machines_undecided](crate::decider::decider_result_worker::save_machines_undecided)
+//! stay in [PATH_DATA] independent of the root, as before the layout existed.
+//! Use [OutputLayout::with_undecided_dir] to place them elsewhere, e.g. in the reports directory.
+//! All paths are built with [PathBuf], so the separator is correct on all platforms.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+
+use crate::config::{Config, PATH_DATA};
+
+pub const SUB_DIR_REPORTS: &str = "reports";
+pub const SUB_DIR_CERTIFICATES: &str = "certificates";
+pub const SUB_DIR_CHECKPOINTS: &str = "checkpoints";
+pub const SUB_DIR_SITE: &str = "site";
+pub const EVENT_LOG_FILE_NAME: &str = "events.jsonl";
+pub const STATS_SNAPSHOT_FILE_NAME: &str = "stats_snapshot.json";
+pub const VERDICT_LOG_FILE_NAME: &str = "verdicts.jsonl";
+pub const RESULTS_SQLITE_FILE_NAME: &str = "results.sqlite";
+pub const CHECKPOINT_FILE_NAME: &str = "checkpoint.toml";
+
+/// Directory layout for all files written during a run. Stored in [Config].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OutputLayout {
+    /// Root directory. If None, html_out_path of the config.toml is used.
+    root: Option<PathBuf>,
+    /// Name of the run directory, e.g. 'run_20250101_120000'. If None, all files are written into root.
+    run_dir: Option<String>,
+    /// Directory of the undecided machine lists. If None, [PATH_DATA] is used.
+    undecided_dir: Option<PathBuf>,
+}
+
+impl OutputLayout {
+    /// New layout with the given root directory.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: Some(root.into()),
+            run_dir: None,
+            undecided_dir: None,
+        }
+    }
+
+    /// Places all output in a sub directory 'run_<local time>' of the root. \
+    /// The timestamp is taken once here, so all deciders using this layout share the directory.
+    pub fn with_run_dir(mut self) -> Self {
+        let time_string = Local::now().format("%Y%m%d_%H%M%S").to_string();
+        self.run_dir = Some(format!("run_{time_string}"));
+        self
+    }
+
+    /// Places all output in the given sub directory of the root.
+    pub fn with_run_dir_name(mut self, run_dir: &str) -> Self {
+        self.run_dir = Some(run_dir.to_string());
+        self
+    }
+
+    /// The root directory, html_out_path of the config.toml if not set.
+    pub fn root(&self, config: &Config) -> PathBuf {
+        match &self.root {
+            Some(root) => root.clone(),
+            None => PathBuf::from(config.config_toml().html_out_path()),
+        }
+    }
+
+    /// Writes the undecided machine lists into this directory instead of [PATH_DATA].
+    pub fn with_undecided_dir(mut self, undecided_dir: impl Into<PathBuf>) -> Self {
+        self.undecided_dir = Some(undecided_dir.into());
+        self
+    }
+
+    pub fn run_dir(&self) -> Option<&String> {
+        self.run_dir.as_ref()
+    }
+
+    /// Root directory including the run directory if set.
+    pub fn run_path(&self, config: &Config) -> PathBuf {
+        let root = self.root(config);
+        match &self.run_dir {
+            Some(run_dir) => root.join(run_dir),
+            None => root,
+        }
+    }
+
+    /// Directory for the html files of a decider, like '<run_path>/<sub_dir>_bb5', e.g. '../bb_result_html/cycler_bb5'.
+    pub fn decider_path(&self, sub_dir: &str, config: &Config) -> PathBuf {
+        self.run_path(config)
+            .join(decider_dir_name(sub_dir, config.n_states()))
+    }
+
+    /// Directory for reports like the undecided machine lists.
+    pub fn reports_path(&self, config: &Config) -> PathBuf {
+        self.run_path(config).join(SUB_DIR_REPORTS)
+    }
+
+    /// Directory for the undecided machine lists of the result worker, [PATH_DATA] if not set
+    /// with [Self::with_undecided_dir].
+    pub fn undecided_path(&self) -> PathBuf {
+        match &self.undecided_dir {
+            Some(undecided_dir) => undecided_dir.clone(),
+            None => PathBuf::from(PATH_DATA),
+        }
+    }
+
+    /// Directory for proof certificates.
+    pub fn certificates_path(&self, config: &Config) -> PathBuf {
+        self.run_path(config).join(SUB_DIR_CERTIFICATES)
+    }
+
+    /// Directory for checkpoints.
+    pub fn checkpoints_path(&self, config: &Config) -> PathBuf {
+        self.run_path(config).join(SUB_DIR_CHECKPOINTS)
+    }
+
+    /// Directory for the static site, see [StaticSite](crate::static_site::StaticSite).
+    pub fn site_path(&self, config: &Config) -> PathBuf {
+        self.run_path(config).join(SUB_DIR_SITE)
+    }
+
+    /// File of the event log, see [EventLog](crate::event_log::EventLog).
+    pub fn event_log_file_path(&self, config: &Config) -> PathBuf {
+        self.run_path(config).join(EVENT_LOG_FILE_NAME)
+    }
+
+    /// File of the rolling stats snapshot, see [StatsSnapshot](crate::stats_snapshot::StatsSnapshot).
+    pub fn stats_snapshot_file_path(&self, config: &Config) -> PathBuf {
+        self.run_path(config).join(STATS_SNAPSHOT_FILE_NAME)
+    }
+
+    /// File of the verdict log, see [VerdictLog](crate::verdict_log::VerdictLog).
+    pub fn verdict_log_file_path(&self, config: &Config) -> PathBuf {
+        self.run_path(config).join(VERDICT_LOG_FILE_NAME)
+    }
+
+    /// SQLite database in the root, which is shared by all runs, see
+    /// [results_store_sqlite](crate::results_store_sqlite).
+    pub fn results_sqlite_file_path(&self, config: &Config) -> PathBuf {
+        self.root(config).join(RESULTS_SQLITE_FILE_NAME)
+    }
+
+    /// File of the checkpoint in the checkpoints directory, see [Checkpoint](crate::checkpoint::Checkpoint).
+    pub fn checkpoint_file_path(&self, config: &Config) -> PathBuf {
+        self.checkpoints_path(config).join(CHECKPOINT_FILE_NAME)
+    }
+}
+
+/// Name of the html directory of a decider, e.g. 'cycler_bb5'.
+pub fn decider_dir_name(sub_dir: &str, n_states: usize) -> String {
+    format!("{sub_dir}_bb{n_states}")
+}
+
+/// Creates the directory including all parents if it does not exist.
+pub fn create_dir(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        std::fs::create_dir_all(path)?;
+    }
+    Ok(())
+}
+
+/// Returns a file path in dir which does not exist yet. If dir/file_name exists,
+/// a number is appended to the file stem, e.g. 'BB5 enumeration_1.csv'.
+pub fn unique_file_path(dir: &Path, file_name: &str) -> PathBuf {
+    let path = dir.join(file_name);
+    if !path.exists() {
+        return path;
+    }
+    let file = Path::new(file_name);
+    let stem = file
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = file
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    let mut n = 1;
+    loop {
+        let path = dir.join(format!("{stem}_{n}{extension}"));
+        if !path.exists() {
+            return path;
+        }
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_layout_paths() {
+        let config = Config::new_default(5);
+        let layout = OutputLayout::new("out").with_run_dir_name("run_1");
+        assert_eq!(
+            layout.decider_path("cycler", &config),
+            Path::new("out").join("run_1").join("cycler_bb5")
+        );
+        assert_eq!(
+            layout.reports_path(&config),
+            Path::new("out").join("run_1").join(SUB_DIR_REPORTS)
+        );
+        assert_eq!(layout.undecided_path(), PathBuf::from(PATH_DATA));
+        let layout = OutputLayout::new("out");
+        assert_eq!(layout.run_path(&config), PathBuf::from("out"));
+        let reports_path = layout.reports_path(&config);
+        let layout = layout.with_undecided_dir(reports_path);
+        assert_eq!(
+            layout.undecided_path(),
+            Path::new("out").join(SUB_DIR_REPORTS)
+        );
+    }
+
+    #[test]
+    fn output_layout_unique_file_path() {
+        let dir = std::env::temp_dir().join("bb_challenge_output_layout_test");
+        create_dir(&dir).unwrap();
+        let path = dir.join("test.txt");
+        std::fs::write(&path, "x").unwrap();
+        let _ = std::fs::remove_file(dir.join("test_1.txt"));
+        assert_eq!(unique_file_path(&dir, "test.txt"), dir.join("test_1.txt"));
+        assert_eq!(unique_file_path(&dir, "new.txt"), dir.join("new.txt"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
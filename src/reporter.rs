@@ -1,9 +1,12 @@
-use std::time::{Duration, Instant};
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
 
 use num_format::ToFormattedString;
 
 use crate::{
-    config::{self, IdNormalized},
+    config::{self, Config, IdNormalized},
     decider::decider_result::DeciderResultStats,
 };
 
@@ -17,6 +20,9 @@ pub struct Reporter<'a> {
     report_detail_after: Duration,
     report_progress: &'a (dyn ReportProgress + 'a),
     progress_info: ProgressInfo,
+    /// Receives the progress after each batch, see [Reporter::batch_finished].
+    progress_reporter: Box<dyn ProgressReporter>,
+    batches_done: usize,
 }
 
 // impl<R: ReportProgress> Reporter<R> {
@@ -29,7 +35,19 @@ impl<'a> Reporter<'a> {
             report_detail_after: Duration::new(30, 0),
             report_progress: &REPORT_PROGRESS_STANDARD,
             progress_info: ProgressInfo::new(total),
+            progress_reporter: Box::new(ProgressReporterStderr::default()),
+            batches_done: 0,
+        }
+    }
+
+    /// Standard reporter, which reports nothing if [Config::progress_quiet] is set or the dashboard
+    /// is shown, see [Dashboard](crate::dashboard::Dashboard).
+    pub fn from_config(total: IdNormalized, config: &Config) -> Self {
+        let mut builder = Self::builder(total);
+        if config.progress_quiet() || (cfg!(feature = "tui") && config.dashboard()) {
+            builder = builder.progress_reporter(Box::new(ProgressReporterQuiet));
         }
+        builder.build()
     }
 
     // TODO extent Builder with these fields
@@ -101,6 +119,36 @@ impl<'a> Reporter<'a> {
         s
     }
 
+    /// Called by the engine after the result of a batch was added. Passes the progress to the
+    /// [ProgressReporter] and the current result about every 30 seconds.
+    pub fn batch_finished(&mut self, batches_total: usize, result: &DeciderResultStats) {
+        self.batches_done += 1;
+        let processed = result.num_processed_total();
+        self.progress_info.add_progress(processed);
+        let elapsed = self.progress_info.start_time.elapsed();
+        // machines per second of the last minute, the average since the start for shorter runs
+        let machines_per_sec = self
+            .progress_info
+            .progress_average_per_sec(60)
+            .unwrap_or(processed as f64 / elapsed.as_secs_f64());
+        let remaining = self.progress_info.total.saturating_sub(processed);
+        let progress = BatchProgress {
+            batches_done: self.batches_done,
+            batches_total,
+            machines_processed: processed,
+            machines_total: self.progress_info.total,
+            machines_per_sec,
+            eta: (machines_per_sec > 0.0 && machines_per_sec.is_finite())
+                .then(|| Duration::from_secs_f64(remaining as f64 / machines_per_sec)),
+            elapsed,
+        };
+        self.progress_reporter.report_batch(&progress);
+        if self.is_due_detail() {
+            self.progress_reporter.report_detail(result);
+            self.reset_last_report_detail_time();
+        }
+    }
+
     /// After info was reported, the time needs to be reset for due calculation.
     pub fn reset_last_report_progress_time(&mut self) {
         self.last_progress_time = std::time::Instant::now()
@@ -170,6 +218,97 @@ impl<'a> Reporter<'a> {
 //     }
 // }
 
+/// Progress of a run after a batch, see [ProgressReporter].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchProgress {
+    pub batches_done: usize,
+    pub batches_total: usize,
+    pub machines_processed: IdNormalized,
+    pub machines_total: IdNormalized,
+    /// Machines per second of the last minute.
+    pub machines_per_sec: f64,
+    /// Estimated remaining time, None if nothing was processed yet.
+    pub eta: Option<Duration>,
+    pub elapsed: Duration,
+}
+
+impl Display for BatchProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = config::user_locale();
+        let percent = if self.machines_total == 0 {
+            0.0
+        } else {
+            self.machines_processed as f64 / self.machines_total as f64 * 100.0
+        };
+        write!(
+            f,
+            "Batch {} / {}: {} / {} machines ({percent:.1}%), {} machines/s, ETA {}, runtime {}",
+            self.batches_done,
+            self.batches_total,
+            self.machines_processed.to_formatted_string(&locale),
+            self.machines_total.to_formatted_string(&locale),
+            (self.machines_per_sec.round() as u64).to_formatted_string(&locale),
+            match self.eta {
+                Some(eta) => format_duration_hhmmss_ms(eta, false),
+                None => "-".to_string(),
+            },
+            format_duration_hhmmss_ms(self.elapsed, false)
+        )
+    }
+}
+
+/// Receives the progress of a run. The engine calls it after each batch, see [Reporter::batch_finished].
+pub trait ProgressReporter {
+    fn report_batch(&mut self, progress: &BatchProgress);
+
+    /// Called about every 30 seconds with the current result.
+    fn report_detail(&mut self, _result: &DeciderResultStats) {}
+}
+
+/// Writes the progress to stderr, at most once per interval (default 2 seconds), so it does not mix
+/// with the result on stdout.
+#[derive(Debug)]
+pub struct ProgressReporterStderr {
+    report_after: Duration,
+    last_report: Instant,
+}
+
+impl ProgressReporterStderr {
+    pub fn new(report_after: Duration) -> Self {
+        Self {
+            report_after,
+            last_report: Instant::now(),
+        }
+    }
+}
+
+impl Default for ProgressReporterStderr {
+    fn default() -> Self {
+        Self::new(Duration::new(2, 0))
+    }
+}
+
+impl ProgressReporter for ProgressReporterStderr {
+    fn report_batch(&mut self, progress: &BatchProgress) {
+        if self.last_report.elapsed() > self.report_after {
+            eprintln!("{progress}");
+            self.last_report = Instant::now();
+        }
+    }
+
+    fn report_detail(&mut self, result: &DeciderResultStats) {
+        eprintln!("\nCurrent result\n{result}");
+    }
+}
+
+/// Reports nothing, e.g. for scripts which only want the final result.
+#[derive(Debug, Default)]
+pub struct ProgressReporterQuiet;
+
+impl ProgressReporter for ProgressReporterQuiet {
+    fn report_batch(&mut self, _progress: &BatchProgress) {}
+}
+
 pub trait ReportProgress {
     fn report_progress(&self, processed: IdNormalized, progress_info: &ProgressInfo) -> String;
     fn report_progress_stats(
@@ -381,11 +520,21 @@ impl Default for ProgressInfo {
 
 pub struct ReporterBuilder {
     total: IdNormalized,
+    progress_reporter: Option<Box<dyn ProgressReporter>>,
 }
 
 impl ReporterBuilder {
     pub fn new(total: IdNormalized) -> Self {
-        Self { total }
+        Self {
+            total,
+            progress_reporter: None,
+        }
+    }
+
+    /// Receives the progress after each batch, default is [ProgressReporterStderr].
+    pub fn progress_reporter(mut self, progress_reporter: Box<dyn ProgressReporter>) -> Self {
+        self.progress_reporter = Some(progress_reporter);
+        self
     }
 
     pub fn build(self) -> Reporter<'static> {
@@ -396,6 +545,10 @@ impl ReporterBuilder {
             report_detail_after: Duration::new(30, 0),
             report_progress: &REPORT_PROGRESS_STANDARD,
             progress_info: ProgressInfo::new(self.total),
+            progress_reporter: self
+                .progress_reporter
+                .unwrap_or_else(|| Box::new(ProgressReporterStderr::default())),
+            batches_done: 0,
         }
     }
 }
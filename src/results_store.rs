@@ -0,0 +1,363 @@
+//! Persisted store of the best machines found in the runs, which gives a personal BB leaderboard. \
+//! After each run [ResultsStore::append_result] adds the halting machines with max steps of the result (and of its
+//! n_states sections) to a csv file in the root of the [OutputLayout](crate::output_layout::OutputLayout).
+//! [ResultsStore::bb_lower_bounds] reports per n_states the best known steps and sigma (ones on the final tape)
+//! with the machines which achieved them. Each of these machines is simulated again, so the report also
+//! shows if the stored steps could be verified.
+
+use std::{
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use num_format::ToFormattedString;
+
+use crate::{
+    config::{user_locale, Config, StepBig},
+    decider::{decider_halt_long::DeciderHaltLong, decider_result::DeciderResultStats, Decider},
+    machine_binary::{MachineBinary, MachineId},
+    output_layout::create_dir,
+    status::MachineStatus,
+};
+
+pub const RESULTS_STORE_FILE_NAME: &str = "results_store.csv";
+const CSV_HEADER: &str = "n_states,steps,machine,run";
+
+/// A halting machine with max steps of a run, one line of the store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreRecord {
+    pub n_states: usize,
+    pub steps: StepBig,
+    pub machine: MachineBinary,
+    /// Name of the run given in [ResultsStore::append_result].
+    pub run: String,
+}
+
+/// Result of the simulation of a stored machine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verification {
+    /// The machine halts after the stored steps.
+    Verified,
+    /// The machine halts, but after the given steps.
+    StepMismatch(StepBig),
+    /// The machine did not halt within the stored steps, e.g. because of the tape size limit.
+    NotHalting(MachineStatus),
+}
+
+/// A machine of the leaderboard with its verification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChampionMachine {
+    pub machine: MachineBinary,
+    /// Steps as stored.
+    pub steps: StepBig,
+    /// Ones on the final tape, 0 if the machine could not be verified.
+    pub sigma: u64,
+    pub verification: Verification,
+    /// Runs which found the machine.
+    pub runs: Vec<String>,
+}
+
+/// Best known steps and sigma for one n_states.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BBLowerBound {
+    pub n_states: usize,
+    pub steps_max: StepBig,
+    /// Machines which halt after steps_max steps.
+    pub machines_steps_max: Vec<ChampionMachine>,
+    pub sigma_max: u64,
+    /// Verified machines which leave sigma_max ones on the tape.
+    pub machines_sigma_max: Vec<ChampionMachine>,
+}
+
+impl Display for BBLowerBound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        writeln!(
+            f,
+            "BB{}: steps >= {}, sigma >= {}",
+            self.n_states,
+            self.steps_max.to_formatted_string(&locale),
+            self.sigma_max.to_formatted_string(&locale)
+        )?;
+        for (name, machines) in [
+            ("steps", &self.machines_steps_max),
+            ("sigma", &self.machines_sigma_max),
+        ] {
+            for m in machines.iter() {
+                writeln!(
+                    f,
+                    "   {name}: {}, steps {}, sigma {}, {:?}, runs: {}",
+                    m.machine.to_standard_tm_text_format(),
+                    m.steps.to_formatted_string(&locale),
+                    m.sigma,
+                    m.verification,
+                    m.runs.join(", ")
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResultsStore {
+    file_path: PathBuf,
+}
+
+impl ResultsStore {
+    pub fn new(file_path: impl Into<PathBuf>) -> Self {
+        Self {
+            file_path: file_path.into(),
+        }
+    }
+
+    /// Store in the root of the output layout of the config, which is shared by all runs.
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config
+                .output_layout()
+                .root(config)
+                .join(RESULTS_STORE_FILE_NAME),
+        )
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Appends the halting machines with max steps of the result and its n_states sections.
+    /// Only the recorded machines are stored, see Config::limit_machines_max_steps. \
+    /// Returns the number of stored machines.
+    pub fn append_result(&self, result: &DeciderResultStats, run: &str) -> io::Result<usize> {
+        let run = run.replace([',', '\n'], " ");
+        let mut lines = Vec::new();
+        for r in std::iter::once(result).chain(result.n_states_sections().iter()) {
+            if let Some(machines) = r.machines_max_steps() {
+                for mi in machines.iter() {
+                    if let MachineStatus::DecidedHalt(steps)
+                    | MachineStatus::DecidedHaltField(steps, _)
+                    | MachineStatus::DecidedHaltDetail(steps, _, _) = mi.status()
+                    {
+                        lines.push(format!(
+                            "{},{steps},{},{run}",
+                            mi.n_states(),
+                            mi.to_standard_tm_text_format()
+                        ));
+                    }
+                }
+            }
+        }
+        if lines.is_empty() {
+            return Ok(0);
+        }
+
+        if let Some(dir) = self.file_path.parent() {
+            create_dir(dir)?;
+        }
+        let is_new = !self.file_path.exists();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_path)?;
+        let mut writer = BufWriter::new(file);
+        if is_new {
+            writeln!(writer, "{CSV_HEADER}")?;
+        }
+        for line in lines.iter() {
+            writeln!(writer, "{line}")?;
+        }
+        writer.flush()?;
+
+        Ok(lines.len())
+    }
+
+    /// Reads all records of the store. A missing store has no records.
+    pub fn read_records(&self) -> io::Result<Vec<StoreRecord>> {
+        if !self.file_path.exists() {
+            return Ok(Vec::new());
+        }
+        let reader = BufReader::new(File::open(&self.file_path)?);
+        let mut records = Vec::new();
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line_no == 0 || line.is_empty() {
+                continue;
+            }
+            let invalid = |e: String| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}, line {}: {e}", self.file_path.display(), line_no + 1),
+                )
+            };
+            let parts: Vec<&str> = line.splitn(4, ',').collect();
+            if parts.len() != 4 {
+                return Err(invalid("expected 4 columns".to_string()));
+            }
+            let machine = MachineBinary::try_from_standard_tm_text_format(parts[2])
+                .map_err(|e| invalid(e.to_string()))?;
+            records.push(StoreRecord {
+                n_states: parts[0].parse().map_err(|e| invalid(format!("{e}")))?,
+                steps: parts[1].parse().map_err(|e| invalid(format!("{e}")))?,
+                machine,
+                run: parts[3].to_string(),
+            });
+        }
+
+        Ok(records)
+    }
+
+    /// Best known steps and sigma per n_states over all stored runs, ordered by n_states. \
+    /// The machines with max steps and all stored machines for sigma are simulated again,
+    /// a machine only counts for sigma if it is verified.
+    pub fn bb_lower_bounds(&self) -> io::Result<Vec<BBLowerBound>> {
+        let records = self.read_records()?;
+        let mut n_states_list: Vec<usize> = records.iter().map(|r| r.n_states).collect();
+        n_states_list.sort_unstable();
+        n_states_list.dedup();
+
+        let mut bounds = Vec::new();
+        for n_states in n_states_list {
+            // one entry per machine, with all runs which found it
+            let mut champions: Vec<ChampionMachine> = Vec::new();
+            for r in records.iter().filter(|r| r.n_states == n_states) {
+                match champions
+                    .iter_mut()
+                    .find(|c| c.machine == r.machine && c.steps == r.steps)
+                {
+                    Some(c) => {
+                        if !c.runs.contains(&r.run) {
+                            c.runs.push(r.run.clone());
+                        }
+                    }
+                    None => champions.push(ChampionMachine {
+                        machine: r.machine,
+                        steps: r.steps,
+                        sigma: 0,
+                        verification: Verification::Verified,
+                        runs: vec![r.run.clone()],
+                    }),
+                }
+            }
+            for c in champions.iter_mut() {
+                (c.verification, c.sigma) = verify_machine(&c.machine, c.steps);
+            }
+
+            let steps_max = champions.iter().map(|c| c.steps).max().unwrap_or_default();
+            let sigma_max = champions
+                .iter()
+                .filter(|c| c.verification == Verification::Verified)
+                .map(|c| c.sigma)
+                .max()
+                .unwrap_or_default();
+            bounds.push(BBLowerBound {
+                n_states,
+                steps_max,
+                machines_steps_max: champions
+                    .iter()
+                    .filter(|c| c.steps == steps_max)
+                    .cloned()
+                    .collect(),
+                sigma_max,
+                machines_sigma_max: champions
+                    .iter()
+                    .filter(|c| c.verification == Verification::Verified && c.sigma == sigma_max)
+                    .cloned()
+                    .collect(),
+            });
+        }
+
+        Ok(bounds)
+    }
+}
+
+/// Runs the machine with the halt decider and returns the verification and sigma.
+fn verify_machine(machine: &MachineBinary, steps: StepBig) -> (Verification, u64) {
+    let config = Config::builder(machine.n_states())
+        .step_limit_decider_halt(steps.saturating_add(1))
+        .build();
+    let mut decider = DeciderHaltLong::new(&config);
+    let status = decider.decide_machine(&MachineId::new_no_id(*machine));
+    match status {
+        MachineStatus::DecidedHalt(s)
+        | MachineStatus::DecidedHaltField(s, _)
+        | MachineStatus::DecidedHaltDetail(s, _, _) => {
+            let sigma = decider
+                .tape()
+                .tape_long_clean()
+                .iter()
+                .map(|b| b.count_ones() as u64)
+                .sum();
+            if s == steps {
+                (Verification::Verified, sigma)
+            } else {
+                (Verification::StepMismatch(s), sigma)
+            }
+        }
+        status => (Verification::NotHalting(status), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::batch_run_decider_chain_data_provider_single_thread, DeciderStandard,
+        },
+    };
+
+    #[test]
+    fn results_store_bb_lower_bounds() {
+        let dir = std::env::temp_dir().join("bb_challenge_results_store_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = ResultsStore::new(dir.join(RESULTS_STORE_FILE_NAME));
+        assert!(store.bb_lower_bounds().unwrap().is_empty());
+
+        for n_states in [2, 3] {
+            let config = Config::builder(n_states).machine_limit(0).build();
+            let deciders = [
+                DeciderStandard::Cycler.decider_config(&config),
+                DeciderStandard::Hold.decider_config(&config),
+            ];
+            let data_provider =
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+            let result =
+                batch_run_decider_chain_data_provider_single_thread(&deciders, data_provider);
+            assert!(store.append_result(&result, "run 1").unwrap() > 0);
+            if n_states == 3 {
+                store.append_result(&result, "run 2").unwrap();
+            }
+        }
+        // a wrong step count is reported, but does not count
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(store.file_path())
+            .unwrap();
+        writeln!(file, "2,7,1RB1LB_1LA1RZ,bad run").unwrap();
+        drop(file);
+
+        let bounds = store.bb_lower_bounds().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(bounds.len(), 2);
+        let bb2 = &bounds[0];
+        assert_eq!(bb2.n_states, 2);
+        assert_eq!(bb2.steps_max, 7);
+        assert_eq!(
+            bb2.machines_steps_max[0].verification,
+            Verification::StepMismatch(6)
+        );
+        assert_eq!(bb2.sigma_max, 4);
+        let bb3 = &bounds[1];
+        // only the machines with max steps are stored, their sigma is 5 (BB3 sigma is 6)
+        assert_eq!((bb3.steps_max, bb3.sigma_max), (21, 5));
+        assert!(bb3
+            .machines_steps_max
+            .iter()
+            .all(|m| m.verification == Verification::Verified && m.runs.len() == 2));
+        assert!(bb3.to_string().starts_with("BB3: steps >= 21, sigma >= 5"));
+    }
+}
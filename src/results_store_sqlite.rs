@@ -0,0 +1,301 @@
+//! Result store in a SQLite database (feature 'sqlite'), for long runs where the results are queried with SQL
+//! instead of reading flat files, e.g. all undecided machines with more than 1 million steps:
+//! ```sql
+//! SELECT machine, steps FROM machines WHERE verdict = 'undecided' AND steps > 1000000 ORDER BY steps DESC;
+//! ```
+//! Each run gets one row in the table 'runs' with its metadata and counters, which are updated after each batch.
+//! Each machine reaching a decider gets one row in the table 'machines' with the same values as the
+//! [VerdictLog](crate::verdict_log::VerdictLog). The machines have indices on the status and the steps. \
+//! The store is written if [Config::write_results_sqlite] is set, the database is shared by all runs and
+//! located at [OutputLayout::results_sqlite_file_path].
+//!
+//! [Config::write_results_sqlite]: crate::config::Config::write_results_sqlite
+//! [OutputLayout::results_sqlite_file_path]: crate::output_layout::OutputLayout::results_sqlite_file_path
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use rusqlite::{params, Connection};
+
+use crate::{
+    config::{Config, StepBig},
+    decider::{
+        decider_result::{DeciderResultStats, WarningKind},
+        DeciderConfig,
+    },
+    output_layout::create_dir,
+    verdict_log::Verdict,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started TEXT NOT NULL,
+    updated TEXT NOT NULL,
+    n_states INTEGER NOT NULL,
+    deciders TEXT NOT NULL,
+    run_dir TEXT,
+    processed INTEGER NOT NULL DEFAULT 0,
+    halt INTEGER NOT NULL DEFAULT 0,
+    non_halt INTEGER NOT NULL DEFAULT 0,
+    not_max INTEGER NOT NULL DEFAULT 0,
+    undecided INTEGER NOT NULL DEFAULT 0,
+    steps_max INTEGER NOT NULL DEFAULT 0,
+    end_reason TEXT
+);
+CREATE TABLE IF NOT EXISTS machines (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    machine_id INTEGER,
+    machine TEXT NOT NULL,
+    decider TEXT NOT NULL,
+    verdict TEXT NOT NULL,
+    status_code INTEGER NOT NULL,
+    steps INTEGER
+);
+CREATE INDEX IF NOT EXISTS machines_status ON machines(verdict, status_code);
+CREATE INDEX IF NOT EXISTS machines_steps ON machines(steps);
+";
+
+/// A machine of the store as returned by [ResultsStoreSqlite::machines].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredMachine {
+    pub run_id: i64,
+    /// Machine in the Standard TM text format.
+    pub machine: String,
+    pub decider: String,
+    pub verdict: String,
+    pub steps: Option<StepBig>,
+}
+
+/// SQLite database of one run, see [module documentation](self).
+#[derive(Debug)]
+pub struct ResultsStoreSqlite {
+    file_path: PathBuf,
+    conn: Connection,
+    run_id: i64,
+}
+
+impl ResultsStoreSqlite {
+    /// Opens the database, creates the tables if required and adds a new run with the deciders of the chain.
+    pub fn create_run(
+        file_path: impl Into<PathBuf>,
+        config: &Config,
+        deciders: &[&str],
+    ) -> io::Result<Self> {
+        let file_path = file_path.into();
+        if let Some(dir) = file_path.parent() {
+            create_dir(dir)?;
+        }
+        let conn = Connection::open(&file_path).map_err(io::Error::other)?;
+        conn.execute_batch(SCHEMA).map_err(io::Error::other)?;
+        let time = local_time();
+        conn.execute(
+            "INSERT INTO runs (started, updated, n_states, deciders, run_dir) VALUES (?1, ?1, ?2, ?3, ?4)",
+            params![
+                time,
+                config.n_states() as i64,
+                deciders.join(","),
+                config.output_layout().run_dir()
+            ],
+        )
+        .map_err(io::Error::other)?;
+        let run_id = conn.last_insert_rowid();
+
+        Ok(Self {
+            file_path,
+            conn,
+            run_id,
+        })
+    }
+
+    /// Opens the store of the output layout if [Config::write_results_sqlite] is set. \
+    /// Returns None if the store is not configured. If it cannot be opened, a warning is added to the result.
+    pub(crate) fn start_run(
+        decider_configs: &[DeciderConfig],
+        result: &mut DeciderResultStats,
+    ) -> Option<Self> {
+        let config: &Config = decider_configs.first()?.config();
+        if !config.write_results_sqlite() {
+            return None;
+        }
+        let file_path = config.output_layout().results_sqlite_file_path(config);
+        let deciders: Vec<&str> = decider_configs
+            .iter()
+            .map(|dc| dc.decider_id().name)
+            .collect();
+        match Self::create_run(&file_path, config, &deciders) {
+            Ok(store) => Some(store),
+            Err(e) => {
+                result.add_warning(WarningKind::ResultsSqlite, || {
+                    format!("{}: {e}", file_path.display())
+                });
+                None
+            }
+        }
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Id of the run in the table 'runs'.
+    pub fn run_id(&self) -> i64 {
+        self.run_id
+    }
+
+    /// The database connection, e.g. for own queries.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Inserts the verdicts in one transaction and updates the counters of the run with the result.
+    pub fn write_batch(
+        &mut self,
+        verdicts: &[Verdict],
+        result: &DeciderResultStats,
+    ) -> io::Result<()> {
+        let tx = self.conn.transaction().map_err(io::Error::other)?;
+        {
+            let mut insert = tx
+                .prepare_cached(
+                    "INSERT INTO machines (run_id, machine_id, machine, decider, verdict, status_code, steps)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )
+                .map_err(io::Error::other)?;
+            for verdict in verdicts.iter() {
+                insert
+                    .execute(params![
+                        self.run_id,
+                        verdict.machine.id_as_option().map(|id| id as i64),
+                        verdict.machine.to_standard_tm_text_format(),
+                        verdict.decider_name,
+                        verdict.name(),
+                        verdict.status.as_code(),
+                        verdict.status.steps().map(i64::from),
+                    ])
+                    .map_err(io::Error::other)?;
+            }
+            tx.execute(
+                "UPDATE runs SET updated = ?2, processed = ?3, halt = ?4, non_halt = ?5, not_max = ?6,
+                undecided = ?7, steps_max = ?8, end_reason = ?9 WHERE id = ?1",
+                params![
+                    self.run_id,
+                    local_time(),
+                    result.num_processed_total() as i64,
+                    result.num_halt() as i64,
+                    result.num_non_halt() as i64,
+                    result.num_not_max() as i64,
+                    result.num_undecided() as i64,
+                    result.steps_max() as i64,
+                    result.end_reason.to_string(),
+                ],
+            )
+            .map_err(io::Error::other)?;
+        }
+        tx.commit().map_err(io::Error::other)
+    }
+
+    /// Machines of all runs with the verdict ('halt', 'non_halt', 'not_max' or 'undecided') and at least
+    /// min_steps steps, ordered by the steps descending.
+    pub fn machines(&self, verdict: &str, min_steps: StepBig) -> io::Result<Vec<StoredMachine>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT run_id, machine, decider, verdict, steps FROM machines
+                WHERE verdict = ?1 AND steps >= ?2 ORDER BY steps DESC",
+            )
+            .map_err(io::Error::other)?;
+        let rows = stmt
+            .query_map(params![verdict, min_steps as i64], |row| {
+                Ok(StoredMachine {
+                    run_id: row.get(0)?,
+                    machine: row.get(1)?,
+                    decider: row.get(2)?,
+                    verdict: row.get(3)?,
+                    steps: row.get::<_, Option<i64>>(4)?.map(|s| s as StepBig),
+                })
+            })
+            .map_err(io::Error::other)?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(io::Error::other)
+    }
+}
+
+fn local_time() -> String {
+    Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::batch_run_decider_chain_data_provider_single_thread_reporting,
+            DeciderStandard,
+        },
+        output_layout::{OutputLayout, RESULTS_SQLITE_FILE_NAME},
+    };
+
+    #[test]
+    fn results_sqlite_written_by_engine() {
+        let dir = std::env::temp_dir().join("bb_challenge_results_sqlite_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .output_layout(OutputLayout::new(&dir))
+            .write_results_sqlite(true)
+            .build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &deciders,
+            data_provider,
+            None,
+        );
+        assert_eq!(result.warnings().count(WarningKind::ResultsSqlite), 0);
+
+        let conn = Connection::open(dir.join(RESULTS_SQLITE_FILE_NAME)).unwrap();
+        let (halt, undecided, steps_max): (i64, i64, i64) = conn
+            .query_row(
+                "SELECT halt, undecided, steps_max FROM runs WHERE id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(halt as u64, result.num_halt());
+        assert_eq!(undecided as u64, result.num_undecided());
+        assert_eq!(steps_max, 21);
+        let num_halt: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM machines WHERE verdict = 'halt'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(num_halt as u64, result.num_halt());
+
+        // second run in the same database
+        let mut store = ResultsStoreSqlite::create_run(
+            dir.join(RESULTS_SQLITE_FILE_NAME),
+            &config,
+            &["Decider Cycler"],
+        )
+        .unwrap();
+        assert_eq!(store.run_id(), 2);
+        store.write_batch(&[], &result).unwrap();
+        let halting = store.machines("halt", 21).unwrap();
+        drop(store);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(halting.len(), 1);
+        assert_eq!(halting[0].machine, "1RB---_1LB0RC_1LC1LA");
+        assert_eq!(halting[0].steps, Some(21));
+    }
+}
@@ -0,0 +1,196 @@
+//! Iterative work on the undecided machines of a previous run: the exported undecided machines are read
+//! again and only these machines run through a new decider chain. The new verdicts are merged into the
+//! result of the previous run, so the merged result looks like one run with both chains. \
+//! This is the workflow of the bouncer docs: run a fast chain on all machines, export the undecided machines,
+//! then try slower deciders or higher step limits on the remaining machines only.
+//!
+//! The undecided list can be read in these formats, see [UndecidedListFormat]:
+//! - Text: one machine per line with optional id, as written by the CLI subcommand 'export',
+//!   see [text_file](crate::data_provider::text_file).
+//! - JSON: objects with a 'machine' field, e.g. undecided.json of the [static site](crate::static_site) or
+//!   the [verdict log](crate::verdict_log), where only the undecided verdicts are read.
+//! - bbchallenge index: u32 big-endian ids of the bbchallenge seed file, see
+//!   [bb_undecided_index](crate::data_provider::bb_undecided_index).
+
+use std::{io, path::Path};
+
+use crate::{
+    config::{Config, CoreUsage},
+    data_provider::{
+        bb_file_reader::BBFileReader,
+        bb_undecided_index::read_undecided_index,
+        machine_spool::{MachineSpool, SPILL_THRESHOLD_DEFAULT},
+        text_file::read_text_file,
+    },
+    decider::{
+        decider_engine::run_decider_chain_data_provider_single, decider_result::DeciderResultStats,
+        DeciderConfig,
+    },
+    event_log::json_field,
+    machine_binary::{MachineBinary, MachineId},
+};
+
+/// File format of an undecided machine list, see [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndecidedListFormat {
+    Text,
+    Json,
+    BBChallengeIndex,
+}
+
+impl UndecidedListFormat {
+    /// Format by file extension: 'json' and 'jsonl' are JSON, 'txt' is text, all others the bbchallenge index.
+    pub fn from_file_path(file_path: impl AsRef<Path>) -> Self {
+        let extension = file_path
+            .as_ref()
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("json") | Some("jsonl") => UndecidedListFormat::Json,
+            Some("txt") => UndecidedListFormat::Text,
+            _ => UndecidedListFormat::BBChallengeIndex,
+        }
+    }
+}
+
+/// Reads the machines of the undecided list. The machines of the bbchallenge index are read from the seed file
+/// of the config.toml.
+pub fn read_undecided_list(
+    file_path: impl AsRef<Path>,
+    format: UndecidedListFormat,
+    config: &Config,
+) -> io::Result<Vec<MachineId>> {
+    let file_path = file_path.as_ref();
+    match format {
+        UndecidedListFormat::Text => Ok(read_text_file(file_path)?
+            .into_iter()
+            .map(|m| m.machine)
+            .collect()),
+        UndecidedListFormat::Json => json_machines(&std::fs::read_to_string(file_path)?),
+        UndecidedListFormat::BBChallengeIndex => {
+            let ids = read_undecided_index(&file_path.to_string_lossy())?;
+            BBFileReader::try_new_toml_path(config)?.read_machines(&ids)
+        }
+    }
+}
+
+/// Machines of the JSON objects with a 'machine' field. Objects with a 'verdict' other than 'undecided'
+/// are skipped, the id is taken from the 'id' field if it is a number.
+fn json_machines(text: &str) -> io::Result<Vec<MachineId>> {
+    let mut machines = Vec::new();
+    for object in text.split('{').skip(1) {
+        let object = object.split('}').next().unwrap_or_default();
+        let Some(tm) = json_field(object, "machine") else {
+            continue;
+        };
+        if json_field(object, "verdict").is_some_and(|v| v != "undecided") {
+            continue;
+        }
+        let machine = MachineBinary::try_from_standard_tm_text_format(tm)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{tm}: {e}")))?;
+        machines.push(
+            match json_field(object, "id").and_then(|id| id.trim().parse::<u64>().ok()) {
+                Some(id) => MachineId::new(id, machine),
+                None => MachineId::new_no_id(machine),
+            },
+        );
+    }
+
+    Ok(machines)
+}
+
+/// Runs the decider chain on the machines of an undecided list. The pre-deciders are not run.
+pub fn run_undecided_list(
+    decider_configs: &[DeciderConfig],
+    machines: &[MachineId],
+) -> Result<DeciderResultStats, String> {
+    if machines.is_empty() {
+        return Err("No undecided machines to resume".to_string());
+    }
+    let mut spool = MachineSpool::new(SPILL_THRESHOLD_DEFAULT);
+    spool.push(machines).map_err(|e| e.to_string())?;
+    let data_provider = spool.into_data_provider().map_err(|e| e.to_string())?;
+
+    Ok(run_decider_chain_data_provider_single(
+        decider_configs,
+        data_provider,
+        CoreUsage::SingleCoreEnumeratorMultiCoreDecider,
+    ))
+}
+
+/// Runs the decider chain on the machines, which were left undecided by the previous result, and merges the
+/// new verdicts into it, see [DeciderResultStats::merge_resumed_undecided]. \
+/// Returns the result of the chain on these machines only.
+pub fn resume_undecided(
+    decider_configs: &[DeciderConfig],
+    machines: &[MachineId],
+    previous: &mut DeciderResultStats,
+) -> Result<DeciderResultStats, String> {
+    let resumed = run_undecided_list(decider_configs, machines)?;
+    previous.merge_resumed_undecided(&resumed, machines)?;
+
+    Ok(resumed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::batch_run_decider_chain_data_provider_single_thread, DeciderStandard,
+        },
+    };
+
+    #[test]
+    fn resume_undecided_bb3_merges_like_full_chain() {
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .limit_machines_undecided(usize::MAX)
+            .build();
+        let hold = DeciderStandard::Hold.decider_config(&config);
+        let cycler = DeciderStandard::Cycler.decider_config(&config);
+        let run = |deciders: &[DeciderConfig]| {
+            let data_provider =
+                EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+            batch_run_decider_chain_data_provider_single_thread(deciders, data_provider)
+        };
+        // the halt decider leaves the non-halting machines undecided
+        let mut previous = run(std::slice::from_ref(&hold));
+        let full = run(&[hold, cycler.clone()]);
+
+        // export and read the undecided machines
+        let file_path = std::env::temp_dir().join("bb_challenge_resume_undecided_bb3.json");
+        let json: Vec<String> = previous
+            .machines_undecided()
+            .unwrap()
+            .iter()
+            .map(|m| format!("{{\"machine\":\"{}\"}}", m.to_standard_tm_text_format()))
+            .collect();
+        std::fs::write(&file_path, format!("[{}]", json.join(","))).unwrap();
+        let format = UndecidedListFormat::from_file_path(&file_path);
+        assert_eq!(format, UndecidedListFormat::Json);
+        let machines = read_undecided_list(&file_path, format, &config).unwrap();
+        std::fs::remove_file(&file_path).unwrap();
+        assert_eq!(machines.len() as u64, previous.num_undecided());
+
+        let resumed =
+            resume_undecided(std::slice::from_ref(&cycler), &machines, &mut previous).unwrap();
+        assert_eq!(resumed.num_processed_total(), machines.len() as u64);
+        assert_eq!(previous.num_processed_total(), full.num_processed_total());
+        assert_eq!(previous.num_halt(), full.num_halt());
+        assert_eq!(previous.num_non_halt(), full.num_non_halt());
+        assert_eq!(previous.num_undecided(), full.num_undecided());
+        assert_eq!(
+            previous.undecided_count().num_total(),
+            previous.num_undecided()
+        );
+        assert_eq!(
+            previous.machines_undecided().map_or(0, |m| m.len()) as u64,
+            previous.num_undecided()
+        );
+        // the machines are not undecided any more
+        assert!(previous.num_non_halt() > 0);
+        assert!(resume_undecided(&[cycler], &machines, &mut previous).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! No-std friendly core of the simulation: the transition bit layout, the step on the 128-bit tape and the
+//! configuration hash for cycle detection. \
+//! This module only uses `core` (no std, no allocation), so embedded or accelerator experiments, e.g. offloading
+//! the inner loop, can compile it. The host types share the definitions:
+//! [TransitionBinary](crate::transition_binary::TransitionBinary) the bit layout and
+//! [Tape128](crate::tape::tape_128::Tape128) the head access. The step loop [run_u128] is the reference of the
+//! [cycler offload](crate::decider::cycler_offload) and the [batch stepper](crate::decider::batch_stepper),
+//! the host deciders use their own loops. The tests check it against the halt decider.
+//! The clippy lints below keep the module free of std.
+//!
+//! The transition table is a slice of [TransitionType] indexed by the array id (state * 2 + symbol, A0 = 2),
+//! like the transitions of [MachineBinary](crate::machine_binary::MachineBinary).
+//! # Example
+//! ```
+//! use bb_challenge::{
+//!     machine_binary::NotableMachineBinary,
+//!     sim_core::{run_u128, CoreRunResult},
+//! };
+//!
+//! let machine = NotableMachineBinary::BB4Max.machine();
+//! let transitions = machine.transitions_all().map(|t| t.transition);
+//! assert_eq!(run_u128(&transitions, 1000), CoreRunResult::Halt(107));
+//! ```
+
+#![deny(
+    clippy::std_instead_of_core,
+    clippy::std_instead_of_alloc,
+    clippy::alloc_instead_of_core
+)]
+
+/// Number format to represent a transition (lower 8 bit used for state, symbol and direction).
+/// Can be any type other than i8/u8 (potential extra info per transition). i16 seems fastest.
+pub type TransitionType = i16;
+
+// Bit layout of a transition, see the module documentation of transition_binary.
+pub const FILTER_SYMBOL: TransitionType = 0b0000_0001;
+pub const FILTER_DIR: TransitionType = 0b1100_0000;
+pub const FILTER_STATE: TransitionType = 0b0001_1110;
+pub const FILTER_ARRAY_ID: TransitionType = 0b0001_1111;
+pub const SYMBOL_ZERO: TransitionType = 0b0000_0000;
+pub const SYMBOL_ONE: TransitionType = 0b0000_0001;
+pub const DIRECTION_UNDEFINED: TransitionType = 0b1000_0000;
+pub const TO_RIGHT: TransitionType = 0b1100_0000;
+pub const TO_LEFT: TransitionType = 0b0100_0000;
+pub const STATE_HALT_BINARY: TransitionType = 0;
+
+/// Head position (bit 63) in the 128-bit tape, the cells left of the head are the higher bits.
+pub const POS_HALF_U128: u128 = 1 << 63;
+const HIGHEST_BIT_U128: u128 = 1 << 127;
+
+/// Array id of the start field A0.
+pub const ARRAY_ID_START: usize = 2;
+
+/// Packs symbol (0 or 1), direction and next state (A = 1, 0 for halt) into a transition.
+pub const fn pack_transition(symbol: u8, is_dir_right: bool, state: u8) -> TransitionType {
+    let dir = if is_dir_right { TO_RIGHT } else { TO_LEFT };
+    (symbol as TransitionType & FILTER_SYMBOL) | ((state as TransitionType) << 1) | dir
+}
+
+/// Returns true for the halt transition and the undefined transition.
+#[inline(always)]
+pub const fn is_halt(transition: TransitionType) -> bool {
+    transition & FILTER_STATE == STATE_HALT_BINARY
+}
+
+/// Returns the array id of the next transition, which is the next state doubled plus the symbol read.
+#[inline(always)]
+pub const fn next_array_id(transition: TransitionType, symbol_read: usize) -> usize {
+    (transition & FILTER_STATE) as usize + symbol_read
+}
+
+/// Returns the symbol under the head (bit 63) of the u128 tape.
+#[inline(always)]
+pub const fn read_head(tape: u128) -> usize {
+    ((tape & POS_HALF_U128) != 0) as usize
+}
+
+/// Returns the u128 tape with the symbol under the head (bit 63) set to 1 or 0.
+#[inline(always)]
+pub const fn set_head_symbol(tape: u128, symbol_one: bool) -> u128 {
+    if symbol_one {
+        tape | POS_HALF_U128
+    } else {
+        tape & !POS_HALF_U128
+    }
+}
+
+/// Executes one (non-halting) transition on the u128 tape: writes the symbol and moves the head, which
+/// shifts the tape as the head stays at bit 63. \
+/// Returns None if a 1 would be shifted out of the tape, the tape is exact as long as only zeros are lost.
+#[inline(always)]
+pub const fn step_u128(tape: u128, transition: TransitionType) -> Option<u128> {
+    let tape = set_head_symbol(tape, transition & FILTER_SYMBOL != 0);
+    if transition & FILTER_DIR == TO_RIGHT {
+        // head moves right, tape moves left
+        if tape & HIGHEST_BIT_U128 != 0 {
+            return None;
+        }
+        Some(tape << 1)
+    } else {
+        if tape & 1 != 0 {
+            return None;
+        }
+        Some(tape >> 1)
+    }
+}
+
+/// Hash of a configuration (array id of the next transition and u128 tape) for cycle detection with a hash set. \
+/// Multiply-rotate hash with fixed constants, so the host and other targets calculate the same value.
+#[inline(always)]
+pub const fn config_hash_u128(array_id: usize, tape: u128) -> u64 {
+    const K: u64 = 0x517c_c1b7_2722_0a95;
+    let mut h = (array_id as u64).wrapping_mul(K);
+    h = (h.rotate_left(5) ^ tape as u64).wrapping_mul(K);
+    (h.rotate_left(5) ^ (tape >> 64) as u64).wrapping_mul(K)
+}
+
+/// Result of [run_u128].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreRunResult {
+    /// Machine halts after steps (the halt transition counts as step).
+    Halt(u32),
+    /// The configuration after steps was seen before, the machine runs forever (steps, period of the cycle).
+    Cycle(u32, u32),
+    /// A 1 would have been shifted out of the tape after steps.
+    TapeBound(u32),
+    StepLimit,
+}
+
+/// Runs the machine on the u128 tape for up to step_limit steps. \
+/// Cycles are detected by comparing the configuration (array id and tape) with a saved configuration,
+/// which is updated at powers of two (Brent), so no memory is needed. This finds cyclers, also those
+/// moving over an empty tape, but later than the cycler decider.
+pub fn run_u128(transitions: &[TransitionType], step_limit: u32) -> CoreRunResult {
+    let mut tape: u128 = 0;
+    let mut array_id = ARRAY_ID_START;
+    let mut saved = (array_id, tape);
+    let mut power: u32 = 1;
+    let mut lambda: u32 = 0;
+    for step_no in 1..=step_limit {
+        let transition = transitions[array_id];
+        if is_halt(transition) {
+            return CoreRunResult::Halt(step_no);
+        }
+        tape = match step_u128(tape, transition) {
+            Some(tape) => tape,
+            None => return CoreRunResult::TapeBound(step_no - 1),
+        };
+        array_id = next_array_id(transition, read_head(tape));
+        if (array_id, tape) == saved {
+            return CoreRunResult::Cycle(step_no, lambda + 1);
+        }
+        lambda += 1;
+        if lambda == power {
+            saved = (array_id, tape);
+            power *= 2;
+            lambda = 0;
+        }
+    }
+
+    CoreRunResult::StepLimit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::{
+            enumerator_binary::{EnumeratorBinary, EnumeratorType},
+            DataProvider,
+        },
+        decider::{decider_halt_long::DeciderHaltLong, decider_result::EndReason, Decider},
+        machine_binary::NotableMachineBinary,
+        status::MachineStatus,
+        transition_binary::TransitionBinary,
+    };
+
+    #[test]
+    fn core_transition_layout_matches_transition_binary() {
+        for tr in ["0LA", "1RB", "1LE", "0RC", "1RZ"] {
+            let t = TransitionBinary::try_from(tr).unwrap();
+            let b = tr.as_bytes();
+            let state = if b[2] == b'Z' { 0 } else { b[2] - b'A' + 1 };
+            assert_eq!(
+                pack_transition(b[0] - b'0', b[1] == b'R', state),
+                t.transition
+            );
+            assert_eq!(is_halt(t.transition), t.is_halt());
+            assert_eq!(next_array_id(t.transition, 1), t.state_x2() + 1);
+        }
+        assert_eq!(read_head(set_head_symbol(0, true)), 1);
+        assert_eq!(
+            step_u128(POS_HALF_U128, pack_transition(1, true, 1)),
+            Some(1 << 64)
+        );
+        assert_eq!(
+            step_u128(HIGHEST_BIT_U128, pack_transition(0, true, 1)),
+            None
+        );
+        assert_ne!(config_hash_u128(2, 1), config_hash_u128(3, 1));
+        assert_ne!(config_hash_u128(2, 1), config_hash_u128(2, 1 << 64));
+        assert_eq!(config_hash_u128(2, 5), config_hash_u128(2, 5));
+    }
+
+    #[test]
+    fn core_run_u128_matches_decider_halt() {
+        let bb4 = NotableMachineBinary::BB4Max.machine();
+        assert_eq!(
+            run_u128(&bb4.transitions_all().map(|t| t.transition), 1000),
+            CoreRunResult::Halt(107)
+        );
+
+        // all BB2 machines halt with the same steps as the halt decider
+        let config = Config::builder(2).machine_limit(0).build();
+        let mut data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorFullForward, &config);
+        let mut machines = Vec::new();
+        loop {
+            let data = data_provider.machine_batch_next().unwrap();
+            machines.extend_from_slice(&data.machines);
+            if data.end_reason != EndReason::None {
+                break;
+            }
+        }
+        assert_eq!(machines.len(), 9usize.pow(4));
+        let mut num_halt = 0;
+        for machine in machines.iter() {
+            let transitions = machine.machine().transitions_all().map(|t| t.transition);
+            let status = DeciderHaltLong::decide_single_machine(machine, &config);
+            match run_u128(&transitions, 100) {
+                CoreRunResult::Halt(steps) => {
+                    num_halt += 1;
+                    assert_eq!(status.steps(), Some(steps), "{machine}");
+                }
+                result => assert!(
+                    !matches!(
+                        status,
+                        MachineStatus::DecidedHalt(_) | MachineStatus::DecidedHaltField(_, _)
+                    ),
+                    "{machine}: {result:?}"
+                ),
+            }
+        }
+        assert!(num_halt > 0);
+    }
+}
@@ -0,0 +1,318 @@
+//! Space-time diagram of a machine as known from bbchallenge.org: one row per step (top to bottom) and
+//! one pixel per cell. Cells with 0 are black, cells with 1 are white and the head cell is colored by
+//! the state reading it, using the colors of the HTML legend. The columns cover the cells visited by the
+//! head within the step range. \
+//! The diagram can be written as SVG or PNG (uncompressed, so no image crate is required), see
+//! [SpaceTimeDiagram::write_file]. It is also embedded in the HTML trace and rendered by the CLI
+//! subcommand 'render'.
+//! # Example
+//! ```
+//! use bb_challenge::{machine_binary::NotableMachineBinary, space_time::SpaceTimeDiagram};
+//! let machine = NotableMachineBinary::BB4Max.machine();
+//! // BB4 Max halts after 107 steps, the last row is the halt step
+//! let diagram = SpaceTimeDiagram::new(&machine, 0, 1_000);
+//! assert_eq!(diagram.num_rows(), 107);
+//! assert!(diagram.to_svg(2).starts_with("<svg"));
+//! assert!(diagram.to_png().starts_with(b"\x89PNG"));
+//! ```
+
+use std::{fs, io, path::Path};
+
+use crate::{config::MAX_STATES, machine_binary::MachineBinary, machine_runner::MachineRunner};
+
+const COLOR_BLANK: [u8; 3] = [0, 0, 0];
+const COLOR_ONE: [u8; 3] = [255, 255, 255];
+/// Head color for states A to E, the colors of the state legend of the HTML trace.
+const COLOR_STATES: [[u8; 3]; MAX_STATES] = [
+    [255, 99, 71],
+    [60, 179, 113],
+    [30, 144, 255],
+    [255, 165, 0],
+    [186, 85, 211],
+];
+
+/// Rendered step range of a machine, see [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpaceTimeDiagram {
+    first_step: u64,
+    /// Position of the first column relative to the start cell.
+    pos_first: i64,
+    width: usize,
+    /// Cell symbols of each row.
+    rows: Vec<Vec<u8>>,
+    /// Column and state (A = 1) of the head in each row.
+    heads: Vec<(usize, usize)>,
+}
+
+impl SpaceTimeDiagram {
+    /// Renders the configurations before the steps first_step..first_step + num_steps. The diagram ends
+    /// early with the halt step.
+    pub fn new(machine: &MachineBinary, first_step: u64, num_steps: u64) -> Self {
+        let mut runner = MachineRunner::new(machine);
+        while runner.num_steps() < first_step && runner.step().is_some() {}
+        let num_steps = num_steps.max(1);
+
+        // first run to get the columns, then run again to collect the rows
+        let (mut pos_min, mut pos_max) = (runner.head_pos(), runner.head_pos());
+        Self::run_rows(runner.clone(), num_steps, |r| {
+            pos_min = pos_min.min(r.head_pos());
+            pos_max = pos_max.max(r.head_pos());
+        });
+        let mut diagram = Self {
+            first_step: runner.num_steps(),
+            pos_first: pos_min,
+            width: (pos_max - pos_min + 1) as usize,
+            rows: Vec::new(),
+            heads: Vec::new(),
+        };
+        Self::run_rows(runner, num_steps, |r| {
+            diagram
+                .rows
+                .push((pos_min..=pos_max).map(|pos| r.cell(pos)).collect());
+            diagram
+                .heads
+                .push(((r.head_pos() - pos_min) as usize, r.state()));
+        });
+
+        diagram
+    }
+
+    /// Calls row for each configuration until num_steps rows or the halt step.
+    fn run_rows(mut runner: MachineRunner, num_steps: u64, mut row: impl FnMut(&MachineRunner)) {
+        for _ in 0..num_steps {
+            row(&runner);
+            match runner.step() {
+                Some(step) if !step.is_halt() => {}
+                _ => break,
+            }
+        }
+    }
+
+    /// Step of the first row.
+    pub fn first_step(&self) -> u64 {
+        self.first_step
+    }
+
+    /// Position of the first column relative to the start cell, usually negative.
+    pub fn pos_first(&self) -> i64 {
+        self.pos_first
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Color (RGB) of the cell of the row.
+    pub fn pixel(&self, row: usize, col: usize) -> [u8; 3] {
+        let (head, state) = self.heads[row];
+        if col == head && (1..=MAX_STATES).contains(&state) {
+            COLOR_STATES[state - 1]
+        } else if self.rows[row][col] == 1 {
+            COLOR_ONE
+        } else {
+            COLOR_BLANK
+        }
+    }
+
+    /// SVG image, each cell is scale x scale pixels. Runs of equal cells in a row are one rectangle.
+    pub fn to_svg(&self, scale: usize) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" \
+            shape-rendering=\"crispEdges\">\n<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+            self.width * scale,
+            self.num_rows() * scale,
+            self.width,
+            self.num_rows(),
+            self.width,
+            self.num_rows(),
+            svg_color(COLOR_BLANK),
+        );
+        for row in 0..self.num_rows() {
+            let mut col = 0;
+            while col < self.width {
+                let color = self.pixel(row, col);
+                let mut end = col + 1;
+                while end < self.width && self.pixel(row, end) == color {
+                    end += 1;
+                }
+                if color != COLOR_BLANK {
+                    svg.push_str(&format!(
+                        "<rect x=\"{col}\" y=\"{row}\" width=\"{}\" height=\"1\" fill=\"{}\"/>\n",
+                        end - col,
+                        svg_color(color)
+                    ));
+                }
+                col = end;
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// PNG image (RGB, one pixel per cell) with uncompressed image data.
+    pub fn to_png(&self) -> Vec<u8> {
+        // each row starts with the filter type 0 (none)
+        let mut raw = Vec::with_capacity(self.num_rows() * (self.width * 3 + 1));
+        for row in 0..self.num_rows() {
+            raw.push(0);
+            for col in 0..self.width {
+                raw.extend_from_slice(&self.pixel(row, col));
+            }
+        }
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.num_rows() as u32).to_be_bytes());
+        // bit depth 8, color type RGB, compression, filter and interlace method 0
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut png, b"IHDR", &header);
+        png_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// Writes the diagram as SVG if the file extension is 'svg', otherwise as PNG.
+    pub fn write_file(&self, file_path: impl AsRef<Path>) -> io::Result<()> {
+        let file_path = file_path.as_ref();
+        if file_path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("svg"))
+        {
+            fs::write(file_path, self.to_svg(1))
+        } else {
+            fs::write(file_path, self.to_png())
+        }
+    }
+}
+
+fn svg_color(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+fn png_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Zlib stream with the data in uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const BLOCK_LEN_MAX: usize = u16::MAX as usize;
+    let mut z = Vec::with_capacity(data.len() + data.len() / BLOCK_LEN_MAX * 5 + 11);
+    z.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = data.chunks(BLOCK_LEN_MAX).peekable();
+    if blocks.peek().is_none() {
+        z.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+        z.push(is_final as u8);
+        z.extend_from_slice(&len.to_le_bytes());
+        z.extend_from_slice(&(!len).to_le_bytes());
+        z.extend_from_slice(block);
+    }
+    z.extend_from_slice(&adler32(data).to_be_bytes());
+    z
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_binary::NotableMachineBinary;
+
+    #[test]
+    fn space_time_diagram_bb3_max() {
+        let machine = NotableMachineBinary::BB3Max.machine();
+        let diagram = SpaceTimeDiagram::new(&machine, 0, 100);
+        // 21 steps including the halt step
+        assert_eq!(diagram.num_rows(), 21);
+        // first row: blank tape, head on the start cell in state A
+        let start_col = (-diagram.pos_first()) as usize;
+        assert_eq!(diagram.pixel(0, start_col), COLOR_STATES[0]);
+        assert!((0..diagram.width())
+            .filter(|&c| c != start_col)
+            .all(|c| diagram.pixel(0, c) == COLOR_BLANK));
+        // the last row has the ones of the final tape except the head cell
+        let mut runner = MachineRunner::new(&machine);
+        while runner.step().is_some() {}
+        let ones = (0..diagram.width())
+            .filter(|&c| diagram.rows[20][c] == 1)
+            .count();
+        assert_eq!(ones, runner.count_ones());
+
+        // step range
+        let part = SpaceTimeDiagram::new(&machine, 10, 5);
+        assert_eq!(part.first_step(), 10);
+        assert_eq!(part.num_rows(), 5);
+        let col = (part.pos_first() - diagram.pos_first()) as usize;
+        assert_eq!(
+            part.rows[0],
+            diagram.rows[10][col..col + part.width()].to_vec()
+        );
+    }
+
+    #[test]
+    fn space_time_diagram_png_svg() {
+        let machine = NotableMachineBinary::BB4Max.machine();
+        let diagram = SpaceTimeDiagram::new(&machine, 0, 1_000);
+        let png = diagram.to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(
+            u32::from_be_bytes(png[16..20].try_into().unwrap()),
+            diagram.width() as u32
+        );
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 107);
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+
+        let svg = diagram.to_svg(3);
+        assert!(svg.starts_with(&format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"321\"",
+            diagram.width() * 3
+        )));
+        // head in state A
+        assert!(svg.contains("fill=\"#ff6347\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+
+        let dir = std::env::temp_dir();
+        let file_path = dir.join("bb_challenge_space_time_bb4_max.png");
+        diagram.write_file(&file_path).unwrap();
+        assert_eq!(fs::read(&file_path).unwrap(), png);
+        fs::remove_file(&file_path).unwrap();
+    }
+}
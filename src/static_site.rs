@@ -0,0 +1,292 @@
+//! Static site of a run, which can be published e.g. on GitHub Pages, so others can browse the results
+//! without installing this tool. \
+//! [StaticSite::write] creates in the given directory
+//! - summary.json: counts, end reason and duration of the run,
+//! - champions.json: best known machines per n_states of the [ResultsStore], if given,
+//! - undecided.json: a sample of the recorded undecided machines, see Config::limit_machines_undecided,
+//! - index.html: the same data as tables, using the html header and css files of the html reports.
+//!
+//! The JSON files can be read by other tools, the index.html works without JavaScript.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::{
+    decider::decider_result::DeciderResultStats,
+    event_log::json_string,
+    html::{create_css, write_file_end, write_html_header},
+    machine_info::MachineInfo,
+    output_layout::create_dir,
+    results_store::{BBLowerBound, ResultsStore, Verification},
+};
+
+pub const SITE_SUMMARY_FILE_NAME: &str = "summary.json";
+pub const SITE_CHAMPIONS_FILE_NAME: &str = "champions.json";
+pub const SITE_UNDECIDED_FILE_NAME: &str = "undecided.json";
+pub const SITE_INDEX_FILE_NAME: &str = "index.html";
+/// Default number of undecided machines on the site.
+const UNDECIDED_SAMPLES_DEFAULT: usize = 100;
+
+/// Generator of the static site of a run, see [module documentation](self).
+#[derive(Debug)]
+pub struct StaticSite<'a> {
+    result: &'a DeciderResultStats,
+    title: String,
+    num_batches_processed: Option<usize>,
+    champions: Vec<BBLowerBound>,
+    num_undecided_samples: usize,
+}
+
+impl<'a> StaticSite<'a> {
+    pub fn new(result: &'a DeciderResultStats, title: &str) -> Self {
+        Self {
+            result,
+            title: title.to_string(),
+            num_batches_processed: None,
+            champions: Vec::new(),
+            num_undecided_samples: UNDECIDED_SAMPLES_DEFAULT,
+        }
+    }
+
+    /// Site of the final report of an async run.
+    #[cfg(feature = "async")]
+    pub fn from_run_report(
+        report: &'a crate::decider::decider_engine_async::RunReport,
+        title: &str,
+    ) -> Self {
+        let mut site = Self::new(&report.result, title);
+        site.num_batches_processed = Some(report.num_batches_processed);
+        site
+    }
+
+    /// Adds the best known machines of the store. The machines are simulated again, see [ResultsStore::bb_lower_bounds].
+    pub fn with_results_store(mut self, store: &ResultsStore) -> io::Result<Self> {
+        self.champions = store.bb_lower_bounds()?;
+        Ok(self)
+    }
+
+    /// Maximum number of undecided machines on the site.
+    pub fn with_undecided_samples(mut self, num_undecided_samples: usize) -> Self {
+        self.num_undecided_samples = num_undecided_samples;
+        self
+    }
+
+    fn undecided_samples(&self) -> &[MachineInfo] {
+        match self.result.machines_undecided() {
+            Some(machines) => &machines[..machines.len().min(self.num_undecided_samples)],
+            None => &[],
+        }
+    }
+
+    pub fn summary_json(&self) -> String {
+        let r = self.result;
+        let names: Vec<String> = r.names().iter().map(|n| json_string(n)).collect();
+        let machine_max_steps = match r.machine_max_steps() {
+            Some(m) => json_string(&m.to_standard_tm_text_format()),
+            None => "null".to_string(),
+        };
+        let num_batches = match self.num_batches_processed {
+            Some(n) => n.to_string(),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"title\":{},\"names\":[{}],\"n_states\":{},\"end_reason\":{},\"processed\":{},\
+            \"halt\":{},\"non_halt\":{},\"not_max\":{},\"undecided\":{},\"steps_max\":{},\
+            \"machine_max_steps\":{machine_max_steps},\"batches\":{num_batches},\"duration_ms\":{}}}",
+            json_string(&self.title),
+            names.join(","),
+            r.n_states(),
+            json_string(&r.end_reason.to_string()),
+            r.num_processed_total(),
+            r.num_halt(),
+            r.num_non_halt(),
+            r.num_not_max(),
+            r.num_undecided(),
+            r.steps_max(),
+            r.duration.duration_total.as_millis(),
+        )
+    }
+
+    pub fn champions_json(&self) -> String {
+        let bounds: Vec<String> = self
+            .champions
+            .iter()
+            .map(|b| {
+                let machines: Vec<String> = b
+                    .machines_steps_max
+                    .iter()
+                    .map(|m| {
+                        format!(
+                            "{{\"machine\":{},\"steps\":{},\"sigma\":{},\"verified\":{}}}",
+                            json_string(&m.machine.to_standard_tm_text_format()),
+                            m.steps,
+                            m.sigma,
+                            m.verification == Verification::Verified
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"n_states\":{},\"steps_max\":{},\"sigma_max\":{},\"machines\":[{}]}}",
+                    b.n_states,
+                    b.steps_max,
+                    b.sigma_max,
+                    machines.join(",")
+                )
+            })
+            .collect();
+        format!("[{}]", bounds.join(","))
+    }
+
+    pub fn undecided_json(&self) -> String {
+        let machines: Vec<String> = self
+            .undecided_samples()
+            .iter()
+            .map(|m| {
+                format!(
+                    "{{\"machine\":{},\"status\":{}}}",
+                    json_string(&m.to_standard_tm_text_format()),
+                    json_string(&m.status().to_string())
+                )
+            })
+            .collect();
+        format!("[{}]", machines.join(","))
+    }
+
+    /// Writes the JSON files, the index.html and the css files into dir, which is created if required.
+    pub fn write(&self, dir: &Path) -> io::Result<()> {
+        create_dir(dir)?;
+        for (file_name, json) in [
+            (SITE_SUMMARY_FILE_NAME, self.summary_json()),
+            (SITE_CHAMPIONS_FILE_NAME, self.champions_json()),
+            (SITE_UNDECIDED_FILE_NAME, self.undecided_json()),
+        ] {
+            std::fs::write(dir.join(file_name), json + "\n")?;
+        }
+        create_css(dir)?;
+        self.write_index_html(&dir.join(SITE_INDEX_FILE_NAME))
+    }
+
+    fn write_index_html(&self, file_path: &Path) -> io::Result<()> {
+        let r = self.result;
+        let title = html_escape(&self.title);
+        let mut file = File::create(file_path)?;
+        write_html_header(&mut file, &title)?;
+        let mut w = BufWriter::new(file);
+        writeln!(w, "<body>")?;
+        writeln!(w, "<h1>{title}</h1>")?;
+        writeln!(w, "<table>")?;
+        for (name, value) in [
+            ("States", r.n_states().to_string()),
+            ("End reason", html_escape(&r.end_reason.to_string())),
+            ("Processed", r.num_processed_total().to_string()),
+            ("Halt", r.num_halt().to_string()),
+            ("Non-halt", r.num_non_halt().to_string()),
+            ("Undecided", r.num_undecided().to_string()),
+            ("Max steps", r.steps_max().to_string()),
+        ] {
+            writeln!(w, "<tr><td>{name}</td><td>{value}</td></tr>")?;
+        }
+        writeln!(w, "</table>")?;
+
+        if !self.champions.is_empty() {
+            writeln!(w, "<h2>Champions</h2>")?;
+            writeln!(
+                w,
+                "<table>\n<tr><th>States</th><th>Machine</th><th>Steps</th><th>Sigma</th><th>Verified</th></tr>"
+            )?;
+            for b in self.champions.iter() {
+                for m in b.machines_steps_max.iter() {
+                    writeln!(
+                        w,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        b.n_states,
+                        m.machine.to_standard_tm_text_format(),
+                        m.steps,
+                        m.sigma,
+                        m.verification == Verification::Verified
+                    )?;
+                }
+            }
+            writeln!(w, "</table>")?;
+        }
+
+        let undecided = self.undecided_samples();
+        if !undecided.is_empty() {
+            writeln!(
+                w,
+                "<h2>Undecided machines ({} of {})</h2>",
+                undecided.len(),
+                r.num_undecided()
+            )?;
+            writeln!(w, "<table>\n<tr><th>Machine</th><th>Status</th></tr>")?;
+            for m in undecided.iter() {
+                writeln!(
+                    w,
+                    "<tr><td>{}</td><td>{}</td></tr>",
+                    m.to_standard_tm_text_format(),
+                    html_escape(&m.status().to_string())
+                )?;
+            }
+            writeln!(w, "</table>")?;
+        }
+        writeln!(
+            w,
+            "<p>Data: <a href=\"{SITE_SUMMARY_FILE_NAME}\">{SITE_SUMMARY_FILE_NAME}</a>, \
+            <a href=\"{SITE_CHAMPIONS_FILE_NAME}\">{SITE_CHAMPIONS_FILE_NAME}</a>, \
+            <a href=\"{SITE_UNDECIDED_FILE_NAME}\">{SITE_UNDECIDED_FILE_NAME}</a></p>"
+        )?;
+        write_file_end(&mut w)
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config,
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::batch_run_decider_chain_data_provider_single_thread_reporting,
+            DeciderStandard,
+        },
+    };
+
+    #[test]
+    fn static_site_written() {
+        let config = Config::builder(2)
+            .machine_limit(0)
+            .limit_machines_undecided(10)
+            .step_limit_decider_cycler(3)
+            .build();
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &[DeciderStandard::Cycler.decider_config(&config)],
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config),
+            None,
+        );
+        assert!(result.num_undecided() > 0);
+        let site = StaticSite::new(&result, "BB2 <test>").with_undecided_samples(2);
+        let summary = site.summary_json();
+        assert!(summary.starts_with("{\"title\":\"BB2 <test>\",\"names\":["));
+        assert!(summary.contains("\"n_states\":2,"));
+        assert!(summary.contains("\"batches\":null,"));
+        assert_eq!(site.champions_json(), "[]");
+        assert_eq!(site.undecided_json().matches("\"machine\":").count(), 2);
+
+        let dir = std::env::temp_dir().join("bb_challenge_static_site_test");
+        site.write(&dir).unwrap();
+        let index = std::fs::read_to_string(dir.join(SITE_INDEX_FILE_NAME)).unwrap();
+        assert!(index.contains("<h1>BB2 &lt;test&gt;</h1>"));
+        assert!(index.contains("Undecided machines (2 of "));
+        assert!(dir.join(SITE_UNDECIDED_FILE_NAME).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
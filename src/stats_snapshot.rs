@@ -0,0 +1,237 @@
+//! Rolling statistics snapshot of a run for crash resilience. \
+//! Every [Config::stats_snapshot_interval] batches the counters of the result (no machines) are written
+//! to a fixed file, see [OutputLayout::stats_snapshot_file_path]. The file is first written to a temporary
+//! file, synced to the disk and then renamed, so the snapshot is never half written. After an unexpected
+//! abort, e.g. a power loss, at most the statistics of the last interval are lost instead of the whole report. \
+//! The snapshot is small and written quickly, in contrast to a full checkpoint.
+//! # Example
+//! ```text
+//! {"time":"2025-06-01T12:00:00.000+02:00","elapsed_ms":4213,"n_states":4,"batches":100,"processed":65536000,
+//! "halt":1234,"non_halt":65530000,"not_max":3000,"undecided":1766,"steps_max":107,"end_reason":"None"}
+//! ```
+//!
+//! [Config::stats_snapshot_interval]: crate::config::Config::stats_snapshot_interval
+//! [OutputLayout::stats_snapshot_file_path]: crate::output_layout::OutputLayout::stats_snapshot_file_path
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use chrono::Local;
+
+use crate::{
+    config::{Config, StepBig},
+    decider::{
+        decider_result::{DeciderResultStats, WarningKind},
+        DeciderConfig,
+    },
+    event_log::{json_field, json_string},
+    output_layout::create_dir,
+};
+
+/// Counters of a result as stored in the snapshot file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StatsCounters {
+    pub n_states: usize,
+    pub num_batches: u64,
+    pub num_processed: u64,
+    pub num_halt: u64,
+    pub num_non_halt: u64,
+    pub num_not_max: u64,
+    pub num_undecided: u64,
+    pub steps_max: StepBig,
+    pub end_reason: String,
+}
+
+impl StatsCounters {
+    pub fn from_result(result: &DeciderResultStats, num_batches: u64) -> Self {
+        Self {
+            n_states: result.n_states(),
+            num_batches,
+            num_processed: result.num_processed_total(),
+            num_halt: result.num_halt(),
+            num_non_halt: result.num_non_halt(),
+            num_not_max: result.num_not_max(),
+            num_undecided: result.num_undecided(),
+            steps_max: result.steps_max(),
+            end_reason: result.end_reason.to_string(),
+        }
+    }
+
+    /// Counters as JSON members, without braces.
+    fn json_fields(&self) -> String {
+        format!(
+            "\"n_states\":{},\"batches\":{},\"processed\":{},\"halt\":{},\"non_halt\":{},\
+            \"not_max\":{},\"undecided\":{},\"steps_max\":{},\"end_reason\":{}",
+            self.n_states,
+            self.num_batches,
+            self.num_processed,
+            self.num_halt,
+            self.num_non_halt,
+            self.num_not_max,
+            self.num_undecided,
+            self.steps_max,
+            json_string(&self.end_reason)
+        )
+    }
+
+    /// Reads the counters of a snapshot file, e.g. after an aborted run.
+    pub fn read(file_path: impl AsRef<Path>) -> io::Result<Self> {
+        let line = fs::read_to_string(file_path)?;
+        let invalid = |name: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Stats snapshot: field '{name}' missing or invalid"),
+            )
+        };
+        let number = |name: &str| -> io::Result<u64> {
+            json_field(&line, name)
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| invalid(name))
+        };
+        Ok(Self {
+            n_states: number("n_states")? as usize,
+            num_batches: number("batches")?,
+            num_processed: number("processed")?,
+            num_halt: number("halt")?,
+            num_non_halt: number("non_halt")?,
+            num_not_max: number("not_max")?,
+            num_undecided: number("undecided")?,
+            steps_max: number("steps_max")? as StepBig,
+            end_reason: json_field(&line, "end_reason")
+                .ok_or_else(|| invalid("end_reason"))?
+                .to_string(),
+        })
+    }
+}
+
+/// Writer of the rolling snapshot, see [module documentation](self).
+#[derive(Debug)]
+pub struct StatsSnapshot {
+    file_path: PathBuf,
+    start: Instant,
+    /// Write the snapshot every this many batches.
+    interval: u64,
+    num_batches: u64,
+}
+
+impl StatsSnapshot {
+    pub fn new(file_path: impl Into<PathBuf>, interval: u64) -> Self {
+        Self {
+            file_path: file_path.into(),
+            start: Instant::now(),
+            interval: interval.max(1),
+            num_batches: 0,
+        }
+    }
+
+    /// Creates the snapshot writer of the output layout if [Config::stats_snapshot_interval] is set.
+    pub(crate) fn start_run(decider_configs: &[DeciderConfig]) -> Option<Self> {
+        let config: &Config = decider_configs.first()?.config();
+        if config.stats_snapshot_interval() == 0 {
+            return None;
+        }
+        Some(Self::new(
+            config.output_layout().stats_snapshot_file_path(config),
+            config.stats_snapshot_interval() as u64,
+        ))
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Counts a finished batch and writes the snapshot every interval batches.
+    pub fn batch_finished(&mut self, result: &DeciderResultStats) -> io::Result<()> {
+        self.num_batches += 1;
+        if self.num_batches.is_multiple_of(self.interval) {
+            self.write(result)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the snapshot atomically: temporary file, sync, rename.
+    pub fn write(&self, result: &DeciderResultStats) -> io::Result<()> {
+        if let Some(dir) = self.file_path.parent() {
+            create_dir(dir)?;
+        }
+        let counters = StatsCounters::from_result(result, self.num_batches);
+        let file_path_tmp = self.file_path.with_extension("json.tmp");
+        let mut file = File::create(&file_path_tmp)?;
+        writeln!(
+            file,
+            "{{\"time\":\"{}\",\"elapsed_ms\":{},{}}}",
+            Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+            self.start.elapsed().as_millis(),
+            counters.json_fields()
+        )?;
+        file.sync_all()?;
+        fs::rename(&file_path_tmp, &self.file_path)
+    }
+}
+
+/// Runs the snapshot function and adds a warning to the result if the snapshot cannot be written.
+/// Does nothing if there is no snapshot.
+pub(crate) fn snapshot_or_warn(
+    snapshot: &mut Option<StatsSnapshot>,
+    result: &mut DeciderResultStats,
+    f: impl FnOnce(&mut StatsSnapshot, &DeciderResultStats) -> io::Result<()>,
+) {
+    if let Some(s) = snapshot.as_mut() {
+        if let Err(e) = f(s, result) {
+            let file_path = s.file_path().to_path_buf();
+            result.add_warning(WarningKind::StatsSnapshot, || {
+                format!("{}: {e}", file_path.display())
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::batch_run_decider_chain_data_provider_single_thread_reporting,
+            DeciderStandard,
+        },
+        output_layout::{OutputLayout, STATS_SNAPSHOT_FILE_NAME},
+    };
+
+    #[test]
+    fn stats_snapshot_written_by_engine() {
+        let dir = std::env::temp_dir().join("bb_challenge_stats_snapshot_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .enumerator_reduced_batch_size_request(1_000)
+            .output_layout(OutputLayout::new(&dir))
+            .stats_snapshot_interval(2)
+            .build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &deciders,
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config),
+            None,
+        );
+
+        let file_path = dir.join(STATS_SNAPSHOT_FILE_NAME);
+        let counters = StatsCounters::read(&file_path).unwrap();
+        assert!(!file_path.with_extension("json.tmp").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(result.warnings().count(WarningKind::StatsSnapshot), 0);
+        // the final snapshot has the counters of the result
+        assert!(counters.num_batches > 2);
+        assert_eq!(
+            counters,
+            StatsCounters::from_result(&result, counters.num_batches)
+        );
+    }
+}
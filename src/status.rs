@@ -1,10 +1,128 @@
+//! Status of a machine after a decider run. \
+//! Each status kind (variant and reason) has a stable numeric [StatusCode], so other languages
+//! and stored results can interpret a status without parsing Rust enum names.
+//! The codes must never change, new variants get new codes.
+//!
+//! | Code    | Status                                                                |
+//! |---------|-----------------------------------------------------------------------|
+//! | 0       | NoDecision                                                            |
+//! | 100     | DecidedHalt                                                           |
+//! | 101     | DecidedHaltField                                                      |
+//! | 102     | DecidedHaltDetail                                                     |
+//! | 200     | DecidedNonHalt Cycler                                                 |
+//! | 201     | DecidedNonHalt Bouncer                                                |
+//! | 202     | DecidedNonHalt ExpandingCycler                                        |
+//! | 203     | DecidedNonHalt OnlyOneDirection                                       |
+//! | 204     | DecidedNonHalt NoHaltTransition                                       |
+//! | 205     | DecidedNonHalt SimpleStartCycle                                       |
+//! | 206     | DecidedNonHalt StartRecursive                                         |
+//! | 207     | DecidedNonHalt WritesOnlyZero                                         |
+//! | 208     | DecidedNonHalt RepWL                                                  |
+//! | 209     | DecidedNonHalt TranslatedCycler                                       |
+//! | 210-217 | DecidedNonHalt ExpandingBouncer, reason in order of [ExpandingBouncerReason] |
+//! | 220     | DecidedNonHalt BackwardUnreachable                                    |
+//! | 221     | DecidedNonHalt ClosedTapeLanguage                                     |
+//! | 222     | DecidedNonHalt Counter                                                |
+//! | 223     | DecidedNonHalt BouncerProof                                           |
+//! | 300     | DecidedNotMaxTooManyHaltTransitions                                   |
+//! | 301     | DecidedNotMaxNotAllStatesUsed                                         |
+//! | 400-409 | EliminatedPreDecider, reason in order of [PreDeciderReason]           |
+//! | 500-508 | Undecided, reason in order of [UndecidedReason]                       |
+//!
+//! The values of a status (e.g. steps or the diagnostics of an undecided reason) are not part of the code and are
+//! set to 0 by [MachineStatus::from_code].
+
 use std::fmt::Display;
 
 use num_format::ToFormattedString;
 
 use crate::config::{user_locale, StepBig, StepSmall};
 
+/// Stable numeric code of a status kind, see the table in the [module documentation](self).
+pub type StatusCode = u16;
+
+const CODE_NO_DECISION: StatusCode = 0;
+const CODE_DECIDED_HALT: StatusCode = 100;
+const CODE_DECIDED_HALT_FIELD: StatusCode = 101;
+const CODE_DECIDED_HALT_DETAIL: StatusCode = 102;
+const CODE_NON_HALT_CYCLER: StatusCode = 200;
+const CODE_NON_HALT_BOUNCER: StatusCode = 201;
+const CODE_NON_HALT_EXPANDING_CYCLER: StatusCode = 202;
+const CODE_NON_HALT_ONLY_ONE_DIRECTION: StatusCode = 203;
+const CODE_NON_HALT_NO_HALT_TRANSITION: StatusCode = 204;
+const CODE_NON_HALT_SIMPLE_START_CYCLE: StatusCode = 205;
+const CODE_NON_HALT_START_RECURSIVE: StatusCode = 206;
+const CODE_NON_HALT_WRITES_ONLY_ZERO: StatusCode = 207;
+const CODE_NON_HALT_REP_WL: StatusCode = 208;
+const CODE_NON_HALT_TRANSLATED_CYCLER: StatusCode = 209;
+const CODE_NON_HALT_EXPANDING_BOUNCER_START: StatusCode = 210;
+const CODE_NON_HALT_BACKWARD_UNREACHABLE: StatusCode = 220;
+const CODE_NON_HALT_CLOSED_TAPE_LANGUAGE: StatusCode = 221;
+const CODE_NON_HALT_COUNTER: StatusCode = 222;
+const CODE_NON_HALT_BOUNCER_PROOF: StatusCode = 223;
+const CODE_NOT_MAX_TOO_MANY_HALT_TRANSITIONS: StatusCode = 300;
+const CODE_NOT_MAX_NOT_ALL_STATES_USED: StatusCode = 301;
+const CODE_PRE_DECIDER_START: StatusCode = 400;
+const CODE_UNDECIDED_START: StatusCode = 500;
+
+/// Order defines the code, only append.
+const PRE_DECIDER_REASONS: [PreDeciderReason; 10] = [
+    PreDeciderReason::None,
+    PreDeciderReason::NotAllStatesUsed,
+    PreDeciderReason::NotExactlyOneHaltCondition,
+    PreDeciderReason::NotStartStateBRight,
+    PreDeciderReason::OnlyOneDirection,
+    PreDeciderReason::SimpleStartCycle,
+    PreDeciderReason::StartRecursive,
+    PreDeciderReason::WritesOnlyZero,
+    PreDeciderReason::LookaheadHalt,
+    PreDeciderReason::LookaheadCycle,
+];
+
+/// Order defines the code, only append.
+const UNDECIDED_REASONS: [UndecidedReason; 10] = [
+    UndecidedReason::DeciderNoResult,
+    UndecidedReason::TapeLimitLeftBoundReached,
+    UndecidedReason::TapeLimitRightBoundReached,
+    UndecidedReason::NoSinusRhythmIdentified { sinus_steps: 0 },
+    UndecidedReason::StepLimit,
+    UndecidedReason::TapeSizeLimit,
+    UndecidedReason::Undefined,
+    UndecidedReason::TimeLimit,
+    UndecidedReason::BouncerPatternTooComplex { num_sweeps: 0 },
+    UndecidedReason::DeciderPanicked,
+];
+
+/// Order defines the code, only append.
+const EXPANDING_BOUNCER_REASONS: [ExpandingBouncerReason; 8] = [
+    ExpandingBouncerReason::DeciderNoResult,
+    ExpandingBouncerReason::StepDeltaIdentical,
+    ExpandingBouncerReason::StepDelta2ndRepeating,
+    ExpandingBouncerReason::StepDelta2ndDoubles,
+    ExpandingBouncerReason::StepDelta2ndCompressedRepeating,
+    ExpandingBouncerReason::HeadMiddleExpanding,
+    ExpandingBouncerReason::TapeValueDeltaAlternating,
+    ExpandingBouncerReason::TapeValueDeltaIdentical,
+];
+
+/// Code of a reason: start code + position of its variant in the reasons list, the values of the variant
+/// are ignored.
+fn reason_code<T>(reasons: &[T], reason: &T, code_start: StatusCode) -> StatusCode {
+    let pos = reasons
+        .iter()
+        .position(|r| std::mem::discriminant(r) == std::mem::discriminant(reason))
+        .expect("reason missing in code list");
+    code_start + pos as StatusCode
+}
+
+/// Reason of a code, None if the code is not in the range of the reasons.
+fn reason_from_code<T: Copy>(reasons: &[T], code: StatusCode, code_start: StatusCode) -> Option<T> {
+    let pos = code.checked_sub(code_start)? as usize;
+    reasons.get(pos).copied()
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PreDeciderReason {
     /// No Reason to eliminate machine found.
     None,
@@ -15,11 +133,18 @@ pub enum PreDeciderReason {
     SimpleStartCycle,
     StartRecursive,
     WritesOnlyZero,
+    /// Halts within the lookahead steps of the enumerator, see [Config::enumerator_lookahead_steps].
+    ///
+    /// [Config::enumerator_lookahead_steps]: crate::config::Config::enumerator_lookahead_steps
+    LookaheadHalt,
+    /// Repeats a configuration within the lookahead steps of the enumerator, so it never halts.
+    LookaheadCycle,
 }
 
 /// Some defined reasons why the machine will never end.
 // TODO Display
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NonHaltReason {
     /// Cycler (steps run, number of steps in the cycle)
     Cycler(StepSmall, StepSmall),
@@ -36,20 +161,66 @@ pub enum NonHaltReason {
     /// e.g. BB3: 84080
     StartRecursive,
     WritesOnlyZero,
+    /// Closed set of repeated word list configurations (block size, repeat threshold)
+    RepWL(u8, u8),
+    /// Translated cycler (steps run, number of steps in the cycle, shift of the tape per cycle, negative to the left)
+    TranslatedCycler(StepSmall, StepSmall, i32),
+    /// No halt transition can be reached, proven by backward reasoning (deepest predecessor chain)
+    BackwardUnreachable(StepSmall),
+    /// Closed tape language of n-gram automata (context length)
+    ClosedTapeLanguage(u8),
+    /// Binary counter proven by induction over the counter values (steps run, block size)
+    Counter(StepSmall, u8),
+    /// Bouncer proven by a formula tape certificate (steps run, number of repeaters)
+    BouncerProof(StepSmall, u8),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UndecidedReason {
     DeciderNoResult,
+    /// The head moved left beyond the left end (bound) of a fixed size tape, e.g. the 128-bit tape.
+    /// The name refers to the bound of the tape, not to the side the tape content is shifted to.
     TapeLimitLeftBoundReached,
+    /// The head moved right beyond the right end (bound) of a fixed size tape.
     TapeLimitRightBoundReached,
-    NoSinusRhythmIdentified,
+    /// The bouncer reached its step limit before the tape was swept often enough to compare the sweeps.
+    NoSinusRhythmIdentified {
+        /// Number of sweeps (one half of the tape empty) recorded until the step limit.
+        sinus_steps: u32,
+    },
     StepLimit,
+    /// The tape could not grow any further (tape size limit of the long tape) or the relevant part of the tape
+    /// does not fit in the fixed size tape. Unlike the bound reasons this is not related to a direction.
     TapeSizeLimit,
     Undefined,
+    /// The machine exceeded the wall-clock time limit per machine, see
+    /// [Config::time_limit_machine_ms](crate::config::Config::time_limit_machine_ms).
+    TimeLimit,
+    /// The bouncer reached its step limit while sweeping, but the tape growth of the sweeps did not match a
+    /// bouncer pattern, e.g. the growth alternates with a longer period than compared.
+    BouncerPatternTooComplex {
+        /// Number of sweeps recorded until the step limit.
+        num_sweeps: u32,
+    },
+    /// A decider panicked on the machine. It is passed to the next decider, see
+    /// [DeciderResultStats::machines_errored](crate::decider::decider_result::DeciderResultStats::machines_errored).
+    DeciderPanicked,
+}
+
+impl UndecidedReason {
+    /// Returns the bound reason for the direction in which the head moved when the bound was reached.
+    pub fn tape_bound(is_dir_right: bool) -> Self {
+        if is_dir_right {
+            UndecidedReason::TapeLimitRightBoundReached
+        } else {
+            UndecidedReason::TapeLimitLeftBoundReached
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExpandingBouncerReason {
     DeciderNoResult,
     StepDeltaIdentical,
@@ -62,6 +233,7 @@ pub enum ExpandingBouncerReason {
 }
 
 #[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MachineStatus {
     #[default]
     NoDecision,
@@ -81,6 +253,115 @@ pub enum MachineStatus {
 }
 
 impl MachineStatus {
+    /// Stable numeric code of the status kind, see the table in the [module documentation](self).
+    pub fn as_code(&self) -> StatusCode {
+        match self {
+            MachineStatus::NoDecision => CODE_NO_DECISION,
+            MachineStatus::DecidedHalt(_) => CODE_DECIDED_HALT,
+            MachineStatus::DecidedHaltField(_, _) => CODE_DECIDED_HALT_FIELD,
+            MachineStatus::DecidedHaltDetail(_, _, _) => CODE_DECIDED_HALT_DETAIL,
+            MachineStatus::DecidedNonHalt(reason) => match reason {
+                NonHaltReason::Cycler(_, _) => CODE_NON_HALT_CYCLER,
+                NonHaltReason::Bouncer(_) => CODE_NON_HALT_BOUNCER,
+                NonHaltReason::ExpandingBouncer(r) => reason_code(
+                    &EXPANDING_BOUNCER_REASONS,
+                    r,
+                    CODE_NON_HALT_EXPANDING_BOUNCER_START,
+                ),
+                NonHaltReason::ExpandingCycler => CODE_NON_HALT_EXPANDING_CYCLER,
+                NonHaltReason::OnlyOneDirection => CODE_NON_HALT_ONLY_ONE_DIRECTION,
+                NonHaltReason::NoHaltTransition => CODE_NON_HALT_NO_HALT_TRANSITION,
+                NonHaltReason::SimpleStartCycle => CODE_NON_HALT_SIMPLE_START_CYCLE,
+                NonHaltReason::StartRecursive => CODE_NON_HALT_START_RECURSIVE,
+                NonHaltReason::WritesOnlyZero => CODE_NON_HALT_WRITES_ONLY_ZERO,
+                NonHaltReason::RepWL(_, _) => CODE_NON_HALT_REP_WL,
+                NonHaltReason::TranslatedCycler(_, _, _) => CODE_NON_HALT_TRANSLATED_CYCLER,
+                NonHaltReason::BackwardUnreachable(_) => CODE_NON_HALT_BACKWARD_UNREACHABLE,
+                NonHaltReason::ClosedTapeLanguage(_) => CODE_NON_HALT_CLOSED_TAPE_LANGUAGE,
+                NonHaltReason::Counter(_, _) => CODE_NON_HALT_COUNTER,
+                NonHaltReason::BouncerProof(_, _) => CODE_NON_HALT_BOUNCER_PROOF,
+            },
+            MachineStatus::DecidedNotMaxTooManyHaltTransitions => {
+                CODE_NOT_MAX_TOO_MANY_HALT_TRANSITIONS
+            }
+            MachineStatus::DecidedNotMaxNotAllStatesUsed => CODE_NOT_MAX_NOT_ALL_STATES_USED,
+            MachineStatus::EliminatedPreDecider(r) => {
+                reason_code(&PRE_DECIDER_REASONS, r, CODE_PRE_DECIDER_START)
+            }
+            MachineStatus::Undecided(r, _, _) => {
+                reason_code(&UNDECIDED_REASONS, r, CODE_UNDECIDED_START)
+            }
+        }
+    }
+
+    /// Status for a code with all values (e.g. steps) set to 0. None if the code is unknown.
+    pub fn from_code(code: StatusCode) -> Option<MachineStatus> {
+        let status = match code {
+            CODE_NO_DECISION => MachineStatus::NoDecision,
+            CODE_DECIDED_HALT => MachineStatus::DecidedHalt(0),
+            CODE_DECIDED_HALT_FIELD => MachineStatus::DecidedHaltField(0, 0),
+            CODE_DECIDED_HALT_DETAIL => MachineStatus::DecidedHaltDetail(0, 0, 0),
+            CODE_NON_HALT_CYCLER => MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(0, 0)),
+            CODE_NON_HALT_BOUNCER => MachineStatus::DecidedNonHalt(NonHaltReason::Bouncer(0)),
+            CODE_NON_HALT_EXPANDING_CYCLER => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::ExpandingCycler)
+            }
+            CODE_NON_HALT_ONLY_ONE_DIRECTION => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::OnlyOneDirection)
+            }
+            CODE_NON_HALT_NO_HALT_TRANSITION => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::NoHaltTransition)
+            }
+            CODE_NON_HALT_SIMPLE_START_CYCLE => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::SimpleStartCycle)
+            }
+            CODE_NON_HALT_START_RECURSIVE => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::StartRecursive)
+            }
+            CODE_NON_HALT_WRITES_ONLY_ZERO => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::WritesOnlyZero)
+            }
+            CODE_NON_HALT_REP_WL => MachineStatus::DecidedNonHalt(NonHaltReason::RepWL(0, 0)),
+            CODE_NON_HALT_TRANSLATED_CYCLER => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::TranslatedCycler(0, 0, 0))
+            }
+            CODE_NON_HALT_BACKWARD_UNREACHABLE => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::BackwardUnreachable(0))
+            }
+            CODE_NON_HALT_CLOSED_TAPE_LANGUAGE => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::ClosedTapeLanguage(0))
+            }
+            CODE_NON_HALT_COUNTER => MachineStatus::DecidedNonHalt(NonHaltReason::Counter(0, 0)),
+            CODE_NON_HALT_BOUNCER_PROOF => {
+                MachineStatus::DecidedNonHalt(NonHaltReason::BouncerProof(0, 0))
+            }
+            CODE_NOT_MAX_TOO_MANY_HALT_TRANSITIONS => {
+                MachineStatus::DecidedNotMaxTooManyHaltTransitions
+            }
+            CODE_NOT_MAX_NOT_ALL_STATES_USED => MachineStatus::DecidedNotMaxNotAllStatesUsed,
+            _ => {
+                if let Some(r) = reason_from_code(
+                    &EXPANDING_BOUNCER_REASONS,
+                    code,
+                    CODE_NON_HALT_EXPANDING_BOUNCER_START,
+                ) {
+                    MachineStatus::DecidedNonHalt(NonHaltReason::ExpandingBouncer(r))
+                } else if let Some(r) =
+                    reason_from_code(&PRE_DECIDER_REASONS, code, CODE_PRE_DECIDER_START)
+                {
+                    MachineStatus::EliminatedPreDecider(r)
+                } else if let Some(r) =
+                    reason_from_code(&UNDECIDED_REASONS, code, CODE_UNDECIDED_START)
+                {
+                    MachineStatus::Undecided(r, 0, 0)
+                } else {
+                    return None;
+                }
+            }
+        };
+        Some(status)
+    }
+
     pub fn is_bouncer(&self) -> bool {
         if let MachineStatus::DecidedNonHalt(NonHaltReason::Bouncer(_)) = self {
             true
@@ -89,13 +370,29 @@ impl MachineStatus {
         }
     }
 
-     pub fn is_cycler(&self) -> bool {
-        if let MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(_,_)) = self {
+    pub fn is_cycler(&self) -> bool {
+        if let MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(_, _)) = self {
             true
         } else {
             false
         }
     }
+
+    /// Returns the steps run by the decider, if the status contains them (halt, cycler, bouncer and undecided).
+    pub fn steps(&self) -> Option<StepBig> {
+        match self {
+            MachineStatus::DecidedHalt(steps)
+            | MachineStatus::DecidedHaltField(steps, _)
+            | MachineStatus::DecidedHaltDetail(steps, _, _)
+            | MachineStatus::Undecided(_, steps, _) => Some(*steps),
+            MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(steps, _))
+            | MachineStatus::DecidedNonHalt(NonHaltReason::TranslatedCycler(steps, _, _))
+            | MachineStatus::DecidedNonHalt(NonHaltReason::Bouncer(steps)) => {
+                Some(*steps as StepBig)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl Display for MachineStatus {
@@ -124,7 +421,9 @@ impl Display for MachineStatus {
             MachineStatus::DecidedNonHalt(non_halt_reason) => {
                 s.push_str(format!("Decided: Non-Halt for {non_halt_reason:?}").as_str())
             }
-            MachineStatus::DecidedNotMaxTooManyHaltTransitions => todo!(),
+            MachineStatus::DecidedNotMaxTooManyHaltTransitions => {
+                s.push_str("Decided: Not max as more than one halt transition exists.")
+            }
             MachineStatus::DecidedNotMaxNotAllStatesUsed => {
                 s.push_str("Decided: Not max as not all states are used.")
             }
@@ -139,10 +438,10 @@ impl Display for MachineStatus {
                 match reason {
                             UndecidedReason::DeciderNoResult => s.push_str("Undecided: No result"),
                             UndecidedReason::TapeLimitLeftBoundReached => s.push_str(
-                                format!("Undecided: Tape bound reached (right {tape_size_limit} steps) after {steps} steps").as_str(),
+                                format!("Undecided: Left tape bound reached (tape size {tape_size_limit}) after {steps} steps").as_str(),
                             ),
                             UndecidedReason::TapeLimitRightBoundReached => s.push_str(
-                                format!("Undecided: Tape bound reached (left {tape_size_limit} steps) after {steps} steps").as_str(),
+                                format!("Undecided: Right tape bound reached (tape size {tape_size_limit}) after {steps} steps").as_str(),
                             ),
                             UndecidedReason::StepLimit => s.push_str(
                                 format!(
@@ -161,11 +460,18 @@ impl Display for MachineStatus {
                                     s.push_str(&s_limit)
                             }
                             UndecidedReason::Undefined => todo!(),
-                            UndecidedReason::NoSinusRhythmIdentified => {
+                            UndecidedReason::TimeLimit => s.push_str(
+                                format!("Undecided: Time limit reached after {steps} steps").as_str(),
+                            ),
+                            UndecidedReason::NoSinusRhythmIdentified { sinus_steps } => {
                                 s.push_str(
-                                                    format!("Undecided: No sinus rhythm reached: left {steps} steps").as_str(),
+                                                    format!("Undecided: No sinus rhythm identified, {sinus_steps} sweeps in {steps} steps").as_str(),
                                                 )
                             },
+                            UndecidedReason::BouncerPatternTooComplex { num_sweeps } => s.push_str(
+                                format!("Undecided: Bouncer pattern too complex, {num_sweeps} sweeps in {steps} steps").as_str(),
+                            ),
+                            UndecidedReason::DeciderPanicked => s.push_str("Undecided: Decider panicked"),
                         }
                 // s.push_str(format!(
                 // "Safety stop reached, machine did not halt for {steps} steps or tape length limit {tape_len}").as_str());
@@ -176,3 +482,53 @@ impl Display for MachineStatus {
         write!(f, "{s}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_code_round_trip() {
+        let mut num_codes = 0;
+        for code in 0..1000 {
+            if let Some(status) = MachineStatus::from_code(code) {
+                assert_eq!(status.as_code(), code, "{status:?}");
+                num_codes += 1;
+            }
+        }
+        // 1 + 3 halt + 14 non-halt + 8 expanding bouncer + 2 not max + 10 pre-decider + 10 undecided
+        assert_eq!(num_codes, 48);
+    }
+
+    #[test]
+    fn status_code_is_stable() {
+        assert_eq!(MachineStatus::DecidedHalt(107).as_code(), 100);
+        assert_eq!(
+            MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(10, 2)).as_code(),
+            200
+        );
+        assert_eq!(
+            MachineStatus::Undecided(UndecidedReason::TapeSizeLimit, 1000, 128).as_code(),
+            505
+        );
+        assert_eq!(
+            MachineStatus::Undecided(UndecidedReason::TimeLimit, 1000, 128).as_code(),
+            507
+        );
+        // the diagnostics are not part of the code
+        assert_eq!(
+            MachineStatus::Undecided(
+                UndecidedReason::BouncerPatternTooComplex { num_sweeps: 40 },
+                1000,
+                128
+            )
+            .as_code(),
+            508
+        );
+        assert_eq!(
+            MachineStatus::EliminatedPreDecider(PreDeciderReason::WritesOnlyZero).as_code(),
+            407
+        );
+        assert_eq!(MachineStatus::from_code(399), None);
+    }
+}
@@ -0,0 +1,169 @@
+//! Stop conditions for bounded exploratory runs, in addition to [Config::machines_limit]. \
+//! If [Config::stop_conditions] is set, the run ends after the batch in which
+//! - at least [StopConditions::num_undecided] undecided machines have been found,
+//! - a machine halting after at least [StopConditions::halt_steps] steps has been found,
+//! - the run took longer than [StopConditions::duration] (wall-clock time).
+//!
+//! The result then has the end reason [EndReason::UndecidedLimitReached], [EndReason::HaltStepsReached] or
+//! [EndReason::TimeLimitReached]. As the conditions are checked after each batch, the run may exceed the limits
+//! by up to one batch (one batch per thread in a threaded run).
+//! # Example
+//! ```
+//! use std::time::Duration;
+//!
+//! use bb_challenge::{config::Config, stop_conditions::StopConditions};
+//!
+//! let config = Config::builder(5)
+//!     .stop_conditions(StopConditions {
+//!         num_undecided: 1_000,
+//!         duration: Some(Duration::from_secs(600)),
+//!         ..Default::default()
+//!     })
+//!     .build();
+//! ```
+//! From the command line the conditions can be given as `--stop-undecided 1000 --stop-halt-steps 10000 --stop-time 600`,
+//! see [StopConditions::try_from_args].
+//!
+//! [Config::machines_limit]: crate::config::Config::machines_limit
+//! [Config::stop_conditions]: crate::config::Config::stop_conditions
+
+use std::{fmt::Display, time::Duration};
+
+use num_format::ToFormattedString;
+
+use crate::{
+    config::{user_locale, StepBig},
+    decider::decider_result::{DeciderResultStats, EndReason},
+};
+
+/// Conditions which end a run early, see [module documentation](self).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StopConditions {
+    /// Stop when at least this many machines are undecided. 0: no limit.
+    pub num_undecided: u64,
+    /// Stop when a machine halting after at least this many steps is found. 0: no limit.
+    pub halt_steps: StepBig,
+    /// Stop after this wall-clock time. None: no limit.
+    pub duration: Option<Duration>,
+}
+
+impl StopConditions {
+    /// True if no condition is set.
+    pub fn is_empty(&self) -> bool {
+        self.num_undecided == 0 && self.halt_steps == 0 && self.duration.is_none()
+    }
+
+    /// Returns the end reason of the first condition which is met.
+    pub fn check(&self, result: &DeciderResultStats, elapsed: Duration) -> Option<EndReason> {
+        if self.num_undecided > 0 {
+            let num_undecided = result.num_undecided()
+                + result
+                    .n_states_sections()
+                    .iter()
+                    .map(|section| section.num_undecided())
+                    .sum::<u64>();
+            if num_undecided >= self.num_undecided {
+                return Some(EndReason::UndecidedLimitReached(num_undecided));
+            }
+        }
+        if self.halt_steps > 0 && result.steps_max() >= self.halt_steps {
+            return Some(EndReason::HaltStepsReached(result.steps_max()));
+        }
+        if let Some(duration) = self.duration {
+            if elapsed >= duration {
+                return Some(EndReason::TimeLimitReached(duration));
+            }
+        }
+        None
+    }
+
+    /// Reads the stop conditions from the command line arguments
+    /// `--stop-undecided <number>`, `--stop-halt-steps <steps>` and `--stop-time <seconds>`.
+    /// Other arguments are ignored.
+    pub fn try_from_args(args: &[String]) -> Result<Self, String> {
+        let mut conditions = Self::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let name = arg.as_str();
+            if !matches!(
+                name,
+                "--stop-undecided" | "--stop-halt-steps" | "--stop-time"
+            ) {
+                continue;
+            }
+            let Some(value) = iter.next() else {
+                return Err(format!("Missing value for {name}"));
+            };
+            let value = value
+                .replace('_', "")
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid value for {name}: {value}"))?;
+            match name {
+                "--stop-undecided" => conditions.num_undecided = value,
+                "--stop-halt-steps" => {
+                    conditions.halt_steps = StepBig::try_from(value)
+                        .map_err(|_| format!("Steps too large for {name}: {value}"))?
+                }
+                _ => conditions.duration = Some(Duration::from_secs(value)),
+            }
+        }
+        Ok(conditions)
+    }
+}
+
+impl Display for StopConditions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = user_locale();
+        let mut conditions = Vec::new();
+        if self.num_undecided > 0 {
+            conditions.push(format!(
+                "{} undecided",
+                self.num_undecided.to_formatted_string(&locale)
+            ));
+        }
+        if self.halt_steps > 0 {
+            conditions.push(format!(
+                "halt after {} steps",
+                self.halt_steps.to_formatted_string(&locale)
+            ));
+        }
+        if let Some(duration) = self.duration {
+            conditions.push(format!("{} s", duration.as_secs()));
+        }
+        write!(f, "{}", conditions.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_conditions_from_args() {
+        let args: Vec<String> = [
+            "path",
+            "--stop-undecided",
+            "1_000",
+            "--stop-time",
+            "60",
+            "--other",
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+        let conditions = StopConditions::try_from_args(&args).unwrap();
+        assert_eq!(
+            conditions,
+            StopConditions {
+                num_undecided: 1000,
+                halt_steps: 0,
+                duration: Some(Duration::from_secs(60)),
+            }
+        );
+        assert_eq!(conditions.to_string(), "1,000 undecided, 60 s");
+        assert!(StopConditions::try_from_args(&args[..2]).is_err());
+        assert!(StopConditions::try_from_args(&args[..1])
+            .unwrap()
+            .is_empty());
+    }
+}
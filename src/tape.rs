@@ -4,7 +4,10 @@ pub mod tape_128;
 // pub mod tape_long_fixed_apex;
 pub mod tape_long_shifted;
 pub mod tape_macro;
+pub mod tape_rle;
+pub mod tape_symbol;
 pub mod tape_utils;
+pub mod tape_vec;
 
 use crate::{
     config::{Config, StepBig},
@@ -9,8 +9,9 @@ use crate::{
     config::{Config, StepBig},
     tape::{
         tape_utils::{
-            TapeLongPositions, U128Ext, FILTER_HIGH_BITS_INCLUDING_HEAD_U128, FILTER_LOW_BITS_U128,
-            MIDDLE_BIT_U128, POS_HALF_U128, TAPE_SIZE_BIT_U128,
+            read_head, set_head_symbol, TapeLongPositions, U128Ext,
+            FILTER_HIGH_BITS_INCLUDING_HEAD_U128, FILTER_LOW_BITS_U128, MIDDLE_BIT_U128,
+            TAPE_SIZE_BIT_U128,
         },
         Tape, TapeAcceleration,
     },
@@ -83,7 +84,7 @@ impl Tape for Tape128 {
     #[inline(always)]
     fn get_current_symbol(&self) -> usize {
         // resolves to one if bit is set
-        ((self.tape_shifted & POS_HALF_U128) != 0) as usize
+        read_head(self.tape_shifted)
     }
 
     fn is_left_empty(&self) -> bool {
@@ -112,11 +113,7 @@ impl Tape for Tape128 {
     /// Update tape: write symbol at head position into cell
     #[inline(always)]
     fn set_current_symbol(&mut self, transition: TransitionBinary) {
-        if transition.is_symbol_one() {
-            self.tape_shifted |= POS_HALF_U128
-        } else {
-            self.tape_shifted &= !POS_HALF_U128
-        };
+        self.tape_shifted = set_head_symbol(self.tape_shifted, transition.is_symbol_one());
     }
 
     #[inline(always)]
@@ -92,10 +92,9 @@ use crate::{
     config::{Config, StepBig, MAX_TAPE_GROWTH_BLOCKS, TAPE_SIZE_INIT_CELL_BLOCKS},
     tape::{
         tape_utils::{
-            TapeLongPositions, U128Ext, CLEAR_HIGH127_96BITS_U128, CLEAR_HIGH95_64BITS_U128,
-            CLEAR_LOW31_00BITS_U128, CLEAR_LOW63_00BITS_U128, CLEAR_LOW63_32BITS_U128,
-            HIGH32_SWITCH_U128, LOW32_SWITCH_U128, MIDDLE_BIT_U128, POS_HALF_U128,
-            TAPE_SIZE_FOURTH_UPPER_128, TAPE_SIZE_HALF_128, TL_POS_START_128,
+            block32, read_head, replace_block32, set_head_symbol, spill_high32, spill_low32,
+            TapeDigest, TapeLongPositions, U128Ext, CLEAR_LOW63_00BITS_U128, HIGH32_SWITCH_U128,
+            LOW32_SWITCH_U128, MIDDLE_BIT_U128, TAPE_SIZE_HALF_128, TL_POS_START_128,
         },
         Tape, TapeAcceleration,
     },
@@ -176,9 +175,8 @@ impl TapeLongShifted {
             ts <<= shift;
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             println!("shifted mid:  {}", ts.to_binary_split_string());
-            ts &= CLEAR_HIGH127_96BITS_U128;
             // bits 127-96 are always stored
-            ts |= (self.tape_long[self.tl_pos] as u128) << 96;
+            ts = replace_block32(ts, 0, self.tape_long[self.tl_pos]);
             // bits 95-64 may not be stored
             // if self.tl_high_bound > self.tl_pos + 4 {
             //     //  unclear if this is necessary and/or correct
@@ -193,8 +191,7 @@ impl TapeLongShifted {
             //     // }
             // }
             if self.tl_high_bound > self.tl_pos + 3 {
-                ts &= CLEAR_LOW31_00BITS_U128;
-                ts |= self.tape_long[self.tl_pos + 3] as u128;
+                ts = replace_block32(ts, 3, self.tape_long[self.tl_pos + 3]);
             }
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             println!("shifted fil:  {}", ts.to_binary_split_string());
@@ -218,8 +215,7 @@ impl TapeLongShifted {
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             println!("shifted mid:  {}", ts.to_binary_split_string());
             // bits 31-0 are always stored
-            ts &= CLEAR_LOW31_00BITS_U128;
-            ts |= self.tape_long[self.tl_pos + 3] as u128;
+            ts = replace_block32(ts, 3, self.tape_long[self.tl_pos + 3]);
             // bits 63-32 may not be stored
             // if self.tl_low_bound + 2 < self.tl_pos {
             //     //  unclear if this is necessary and/or correct
@@ -232,8 +228,7 @@ impl TapeLongShifted {
             // }
             // load bits 127-96 if tape has wandered
             if self.tl_pos > self.tl_low_bound {
-                ts &= CLEAR_HIGH127_96BITS_U128;
-                ts |= (self.tape_long[self.tl_pos] as u128) << 96;
+                ts = replace_block32(ts, 0, self.tape_long[self.tl_pos]);
             }
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             println!("shifted fil:  {}", ts.to_binary_split_string());
@@ -252,10 +247,8 @@ impl TapeLongShifted {
             // Also position matches tape_long, just load both outer u32.
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             dbg!(self.tl_pos);
-            ts &= CLEAR_HIGH127_96BITS_U128;
-            ts |= (self.tape_long[self.tl_pos] as u128) << 96;
-            ts &= CLEAR_LOW31_00BITS_U128;
-            ts |= self.tape_long[self.tl_pos + 3] as u128;
+            ts = replace_block32(ts, 0, self.tape_long[self.tl_pos]);
+            ts = replace_block32(ts, 3, self.tape_long[self.tl_pos + 3]);
         }
         #[cfg(all(debug_assertions, feature = "debug_tape"))]
         println!("shifted end:  {}\n", ts.to_binary_split_string());
@@ -282,9 +275,8 @@ impl TapeLongShifted {
             ts <<= shift;
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             println!("shifted mid:  {}", ts.to_binary_split_string());
-            ts &= CLEAR_HIGH127_96BITS_U128;
             // bits 127-96 are always stored
-            ts |= (self.tape_long[self.tl_pos] as u128) << 96;
+            ts = replace_block32(ts, 0, self.tape_long[self.tl_pos]);
             // bits 95-64 may not be stored
             // if self.tl_high_bound > self.tl_pos + 4 {
             //     //  unclear if this is necessary and/or correct
@@ -299,8 +291,7 @@ impl TapeLongShifted {
             //     // }
             // }
             if self.tl_high_bound > self.tl_pos + 3 {
-                ts &= CLEAR_LOW31_00BITS_U128;
-                ts |= self.tape_long[self.tl_pos + 3] as u128;
+                ts = replace_block32(ts, 3, self.tape_long[self.tl_pos + 3]);
             }
             // println!("shifted fil:  {}", ts.to_binary_split_string());
         } else if self.pos_middle > MIDDLE_BIT_U128 {
@@ -312,8 +303,7 @@ impl TapeLongShifted {
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             println!("shifted mid:  {}", ts.to_binary_split_string());
             // bits 31-0 are always stored
-            ts &= CLEAR_LOW31_00BITS_U128;
-            ts |= self.tape_long[self.tl_pos + 3] as u128;
+            ts = replace_block32(ts, 3, self.tape_long[self.tl_pos + 3]);
             // bits 63-32 may not be stored
             // if self.tl_low_bound + 2 < self.tl_pos {
             //     //  unclear if this is necessary and/or correct
@@ -326,18 +316,16 @@ impl TapeLongShifted {
             // }
             // load bits 127-96 if tape has wandered
             if self.tl_pos > self.tl_low_bound {
-                ts &= CLEAR_HIGH127_96BITS_U128;
-                ts |= (self.tape_long[self.tl_pos] as u128) << 96;
+                ts = replace_block32(ts, 0, self.tape_long[self.tl_pos]);
             }
             // println!("shifted fil:  {}", ts.to_binary_split_string());
         } else {
             // In the middle, both middle u32 are clean, one of them just loaded.
             // Also position matches tape_long, just load both outer u32.
+            #[cfg(all(debug_assertions, feature = "debug_tape"))]
             dbg!(self.tl_pos);
-            ts &= CLEAR_HIGH127_96BITS_U128;
-            ts |= (self.tape_long[self.tl_pos] as u128) << 96;
-            ts &= CLEAR_LOW31_00BITS_U128;
-            ts |= self.tape_long[self.tl_pos + 3] as u128;
+            ts = replace_block32(ts, 0, self.tape_long[self.tl_pos]);
+            ts = replace_block32(ts, 3, self.tape_long[self.tl_pos + 3]);
         }
         #[cfg(all(debug_assertions, feature = "debug_tape"))]
         println!("shifted end:  {}\n", ts.to_binary_split_string());
@@ -454,7 +442,7 @@ impl TapeLongShifted {
             }
 
             // The shift is left, so tape_shifted wanders right -> store low 32 bits.
-            self.tape_long[self.tl_pos + 3] = self.tape_shifted as u32;
+            self.tape_long[self.tl_pos + 3] = spill_low32(self.tape_shifted);
 
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             println!(
@@ -470,8 +458,8 @@ impl TapeLongShifted {
             self.pos_middle = MIDDLE_BIT_U128;
 
             // load high bytes
-            self.tape_shifted = (self.tape_shifted & CLEAR_HIGH95_64BITS_U128)
-                | ((self.tape_long[self.tl_pos + 1] as u128) << TAPE_SIZE_HALF_128);
+            self.tape_shifted =
+                replace_block32(self.tape_shifted, 1, self.tape_long[self.tl_pos + 1]);
 
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             {
@@ -517,7 +505,7 @@ impl TapeLongShifted {
             //     );
             //     return false;
             // }
-            self.tape_long[self.tl_pos] = (self.tape_shifted >> TAPE_SIZE_FOURTH_UPPER_128) as u32;
+            self.tape_long[self.tl_pos] = spill_high32(self.tape_shifted);
 
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             println!(
@@ -542,8 +530,8 @@ impl TapeLongShifted {
             //     );
             //     // } else {
             // }
-            self.tape_shifted = (self.tape_shifted & CLEAR_LOW63_32BITS_U128)
-                | ((self.tape_long[self.tl_pos + 2] as u128) << 32);
+            self.tape_shifted =
+                replace_block32(self.tape_shifted, 2, self.tape_long[self.tl_pos + 2]);
 
             #[cfg(all(debug_assertions, feature = "debug_tape"))]
             {
@@ -567,9 +555,19 @@ impl TapeLongShifted {
         true
     }
 
-    //     pub fn tape_size_limit_u32_blocks(&self) -> u32 {
-    //         self.tape_size_limit_u32_blocks
-    //     }
+    /// Memory of the long tape in bytes, which is the tape size used by the current machine.
+    pub fn tape_long_bytes(&self) -> usize {
+        self.tape_long.len() * std::mem::size_of::<u32>()
+    }
+
+    pub fn tape_size_limit_u32_blocks(&self) -> u32 {
+        self.tape_size_limit_u32_blocks
+    }
+
+    /// Sets the tape size limit for the next machines, e.g. to retry a machine with a larger tape.
+    pub fn set_tape_size_limit_u32_blocks(&mut self, tape_size_limit_u32_blocks: u32) {
+        self.tape_size_limit_u32_blocks = tape_size_limit_u32_blocks;
+    }
 
     pub fn tl_high_bound(&self) -> usize {
         self.tl_high_bound
@@ -616,6 +614,65 @@ impl TapeLongShifted {
     //     println!("{}", self.long_tape_to_string());
     // }
 
+    /// Returns the used part of the tape (low bound to high bound) with the correct cell values,
+    /// meaning the current tape_shifted is merged into the long tape. Intended for output of the final tape.
+    pub fn tape_long_clean(&self) -> Vec<u32> {
+        let ts = self.get_clean_tape_shifted_for_tape_long();
+        let mut cell_blocks = self.tape_long[self.tl_low_bound..self.tl_pos].to_vec();
+        cell_blocks.push((ts >> 96) as u32);
+        cell_blocks.push((ts >> 64) as u32);
+        cell_blocks.push((ts >> 32) as u32);
+        cell_blocks.push(ts as u32);
+        if self.tl_high_bound > self.tl_pos + 3 {
+            cell_blocks.extend_from_slice(&self.tape_long[self.tl_pos + 4..self.tl_high_bound + 1]);
+        }
+
+        cell_blocks
+    }
+
+    /// Returns the digest of the whole tape relative to the head, see [TapeDigest]. It is computed from the
+    /// clean tape, so it should only be created once and then updated with each step.
+    pub fn tape_digest(&self) -> TapeDigest {
+        // tape_shifted is aligned to tl_pos in the clean tape, the head is on bit 63 before the shift
+        let head_cell = (self.tl_pos - self.tl_low_bound) * 32 + self.pos_middle as usize + 1;
+        TapeDigest::from_cell_blocks(&self.tape_long_clean(), head_cell)
+    }
+
+    /// Returns true if all cells beyond the cell at the head offset are 0, right of it if dir_right, else left of it.
+    /// The long tape is included, so this is exact also when the tape is extended.
+    pub fn is_blank_beyond(&self, offset: i64, dir_right: bool) -> bool {
+        let ts = self.get_clean_tape_shifted_for_tape_long();
+        // block layout as in tape_long_clean
+        let num_blocks_left = self.tl_pos - self.tl_low_bound;
+        let num_blocks = num_blocks_left + 4 + self.tl_high_bound.saturating_sub(self.tl_pos + 3);
+        let block = |i: usize| {
+            if i < num_blocks_left {
+                self.tape_long[self.tl_low_bound + i]
+            } else if i < num_blocks_left + 4 {
+                block32(ts, i - num_blocks_left)
+            } else {
+                self.tape_long[self.tl_pos + i - num_blocks_left]
+            }
+        };
+        let cell = (num_blocks_left * 32) as i64 + self.pos_middle as i64 + 1 + offset;
+        if dir_right {
+            let first = cell + 1;
+            if first >= (num_blocks * 32) as i64 {
+                return true;
+            }
+            let first = first.max(0) as usize;
+            block(first / 32) & (u32::MAX >> (first % 32)) == 0
+                && (first / 32 + 1..num_blocks).all(|i| block(i) == 0)
+        } else {
+            if cell <= 0 {
+                return true;
+            }
+            let end = (cell as usize).min(num_blocks * 32);
+            (0..end / 32).all(|i| block(i) == 0)
+                && (end.is_multiple_of(32) || block(end / 32) >> (32 - end % 32) == 0)
+        }
+    }
+
     pub fn long_tape_to_string(&self) -> String {
         let mut cell_blocks = Vec::new();
         for (i, cell_block) in self.tape_long[self.tl_low_bound..self.tl_pos]
@@ -694,7 +751,7 @@ impl Tape for TapeLongShifted {
     #[inline(always)]
     fn get_current_symbol(&self) -> usize {
         // resolves to one if bit is set
-        ((self.tape_shifted & POS_HALF_U128) != 0) as usize
+        read_head(self.tape_shifted)
     }
 
     fn is_left_empty(&self) -> bool {
@@ -721,11 +778,7 @@ impl Tape for TapeLongShifted {
     /// Update tape: write symbol at head position into cell
     #[inline(always)]
     fn set_current_symbol(&mut self, transition: TransitionBinary) {
-        if transition.is_symbol_one() {
-            self.tape_shifted |= POS_HALF_U128
-        } else {
-            self.tape_shifted &= !POS_HALF_U128
-        };
+        self.tape_shifted = set_head_symbol(self.tape_shifted, transition.is_symbol_one());
     }
 
     fn tape_long_positions(&self) -> Option<TapeLongPositions> {
@@ -982,3 +1035,219 @@ impl std::fmt::Display for TapeLongShifted {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TAPE_SIZE_INIT_CELLS;
+
+    /// Reference model of the tape: one u8 per cell, growing in both directions.
+    /// Slow but obviously correct, used to check the spill and load logic of [TapeLongShifted].
+    #[derive(Debug, Default)]
+    struct TapeReference {
+        cells: Vec<u8>,
+        head: usize,
+    }
+
+    impl TapeReference {
+        fn new() -> Self {
+            Self {
+                cells: vec![0],
+                head: 0,
+            }
+        }
+
+        fn read(&self) -> usize {
+            self.cells[self.head] as usize
+        }
+
+        fn step(&mut self, symbol: usize, dir_right: bool) {
+            self.cells[self.head] = symbol as u8;
+            if dir_right {
+                self.head += 1;
+                if self.head == self.cells.len() {
+                    self.cells.push(0);
+                }
+            } else if self.head == 0 {
+                self.cells.insert(0, 0);
+            } else {
+                self.head -= 1;
+            }
+        }
+
+        fn count_ones(&self) -> u32 {
+            self.cells.iter().map(|&c| c as u32).sum()
+        }
+
+        fn is_blank_beyond(&self, offset: isize, dir_right: bool) -> bool {
+            let pos = self.head as isize + offset;
+            self.cells.iter().enumerate().all(|(i, &c)| {
+                c == 0 || (dir_right && i as isize <= pos) || (!dir_right && i as isize >= pos)
+            })
+        }
+
+        /// Cell relative to the head, 0 outside the written area.
+        fn cell(&self, rel_pos: isize) -> u8 {
+            let pos = self.head as isize + rel_pos;
+            if pos < 0 {
+                return 0;
+            }
+            self.cells.get(pos as usize).copied().unwrap_or(0)
+        }
+    }
+
+    /// Small xorshift generator, so the fuzz test is reproducible without extra crates.
+    struct XorShift64(u64);
+
+    impl XorShift64 {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+    }
+
+    /// Transition in state A, so a transition writing the read symbol is self referencing.
+    fn transition(symbol: usize, dir_right: bool) -> TransitionBinary {
+        let symbol = if symbol == 1 { b'1' } else { b'0' };
+        let dir = if dir_right { b'R' } else { b'L' };
+        TransitionBinary::try_new([symbol, dir, b'A']).unwrap()
+    }
+
+    /// Compares the head symbol, the number of ones and the full clean tape bit for bit.
+    fn assert_tape_eq(tape: &TapeLongShifted, reference: &TapeReference, info: &str) {
+        assert_eq!(tape.get_current_symbol(), reference.read(), "{info}: head");
+        assert_eq!(tape.count_ones(), reference.count_ones(), "{info}: ones");
+        let clean = tape.tape_long_clean();
+        // tape_shifted is aligned to tl_pos in the clean tape, the head is on bit 63 before the shift
+        let head =
+            ((tape.tl_pos() - tape.tl_low_bound()) * 32) as isize + tape.pos_middle as isize + 1;
+        for (i, block) in clean.iter().enumerate() {
+            for bit in 0..32 {
+                let cell = ((block >> (31 - bit)) & 1) as u8;
+                let rel_pos = (i * 32 + bit) as isize - head;
+                assert_eq!(
+                    cell,
+                    reference.cell(rel_pos),
+                    "{info}: cell {rel_pos} relative to head\n{}",
+                    tape.long_tape_to_string()
+                );
+            }
+        }
+    }
+
+    /// Runs random transitions in segments of one direction, so the head wanders far enough
+    /// to spill, reload and grow the long tape on both ends.
+    fn fuzz_tape(seed: u64, num_segments: usize, use_self_ref: bool) {
+        let mut rng = XorShift64(seed);
+        let mut tape = TapeLongShifted::default();
+        let mut reference = TapeReference::new();
+        let mut digest = TapeDigest::default();
+        let mut step = 0;
+        for segment in 0..num_segments {
+            let dir_right = rng.below(2) == 1;
+            let len_max = if rng.below(8) == 0 { 1500 } else { 100 };
+            let len = 1 + rng.below(len_max);
+            // mostly ones or zeros in a segment, so self referencing runs occur
+            let p_one = rng.below(5);
+            for _ in 0..len {
+                let read = tape.get_current_symbol();
+                if use_self_ref && rng.below(3) == 0 {
+                    let tr = transition(read, dir_right);
+                    let jump = tape.update_tape_self_ref_speed_up(tr, tr.self_ref_array_id());
+                    assert!(jump > 0);
+                    for _ in 0..jump {
+                        assert_eq!(reference.read(), read, "seed {seed}, step {step}: jump");
+                        reference.step(read, dir_right);
+                        digest.move_head(dir_right);
+                    }
+                    step += jump;
+                } else {
+                    let symbol = (rng.below(4) < p_one) as usize;
+                    assert!(tape.update_tape_single_step(transition(symbol, dir_right)));
+                    reference.step(symbol, dir_right);
+                    digest.write(read, symbol);
+                    digest.move_head(dir_right);
+                    step += 1;
+                }
+                assert_eq!(
+                    tape.get_current_symbol(),
+                    reference.read(),
+                    "seed {seed}, step {step}: head"
+                );
+            }
+            if segment % 4 == 0 {
+                assert_tape_eq(&tape, &reference, &format!("seed {seed}, step {step}"));
+                assert_eq!(
+                    tape.tape_digest(),
+                    digest,
+                    "seed {seed}, step {step}: digest"
+                );
+                for offset in [-100, -64, -3, 0, 5, 63, 64, 100] {
+                    for dir_right in [false, true] {
+                        assert_eq!(
+                            tape.is_blank_beyond(offset as i64, dir_right),
+                            reference.is_blank_beyond(offset, dir_right),
+                            "seed {seed}, step {step}: blank beyond {offset} {dir_right}"
+                        );
+                    }
+                }
+            }
+        }
+        assert_tape_eq(&tape, &reference, &format!("seed {seed}, end"));
+    }
+
+    #[test]
+    fn tape_long_shifted_matches_reference_single_steps() {
+        for seed in 1..=8u64 {
+            fuzz_tape(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15), 300, false);
+        }
+    }
+
+    #[test]
+    fn tape_long_shifted_matches_reference_self_ref_jumps() {
+        for seed in 1..=8u64 {
+            fuzz_tape(seed.wrapping_mul(0x2545_F491_4F6C_DD1D), 300, true);
+        }
+    }
+
+    #[test]
+    fn tape_long_shifted_grows_at_start() {
+        // walk left beyond the initial tape, which moves all blocks to the right
+        let mut tape = TapeLongShifted::default();
+        let mut reference = TapeReference::new();
+        let len_start = tape.tape_long.len();
+        for i in 0..(TAPE_SIZE_INIT_CELLS * 2) {
+            let symbol = (i % 3 == 0) as usize;
+            assert!(tape.update_tape_single_step(transition(symbol, false)));
+            reference.step(symbol, false);
+        }
+        assert!(tape.tape_long.len() > len_start);
+        for i in 0..(TAPE_SIZE_INIT_CELLS * 3) {
+            let symbol = (i % 5 == 0) as usize;
+            assert!(tape.update_tape_single_step(transition(symbol, true)));
+            reference.step(symbol, true);
+        }
+        assert_tape_eq(&tape, &reference, "grow at start");
+    }
+
+    #[test]
+    fn tape_long_shifted_size_limit() {
+        let mut tape = TapeLongShifted::default();
+        let limit = TAPE_SIZE_INIT_CELL_BLOCKS + 8;
+        tape.set_tape_size_limit_u32_blocks(limit as u32);
+        let tr = transition(1, true);
+        let mut steps = 0;
+        while tape.update_tape_single_step(tr) {
+            steps += 1;
+            assert!(steps < limit * 32, "tape limit not reached");
+        }
+        // the limit counts the blocks from the low bound
+        assert!(tape.tape_long.len() - tape.tl_low_bound() <= limit);
+    }
+}
@@ -479,6 +479,20 @@ pub struct TapeCompact {
 }
 
 impl TapeCompact {
+    /// Memory of the patterns in bytes, which is the tape size used by the current machine.
+    pub fn patterns_bytes(&self) -> usize {
+        self.patterns.len() * std::mem::size_of::<Pattern>()
+    }
+
+    pub fn tape_size_limit_cells(&self) -> u32 {
+        self.tape_size_limit_cells
+    }
+
+    /// Sets the tape size limit for the next machines, e.g. to retry a machine with a larger tape.
+    pub fn set_tape_size_limit_cells(&mut self, tape_size_limit_cells: u32) {
+        self.tape_size_limit_cells = tape_size_limit_cells;
+    }
+
     /// merges all patterns and updates pattern_id
     fn check_merge(&mut self) {
         // merge consecutive identical patterns
@@ -0,0 +1,381 @@
+//! TapeRle is a run-length encoded tape, which stores the tape as blocks of (symbol, count). \
+//! The blocks left and right of the head are kept on two stacks with the block next to the head on top, so a
+//! single step only changes the top blocks. A self referencing transition (the symbol read is written and the
+//! state does not change) moves the head over the whole block in one call of
+//! [TapeAcceleration::update_tape_self_ref_speed_up], no matter how long the block is. \
+//! Bouncers and counters sweep over long blocks of identical symbols, so they can be simulated millions of steps
+//! with a few blocks only. The tape size is only limited by [Config::tape_size_limit_cells]. \
+//! A block of 0 which reaches the end of the visited tape is endless, a self referencing transition on it never
+//! ends and returns 0 like reaching the tape size limit.
+
+use std::fmt::Display;
+
+use crate::{
+    config::{Config, StepBig},
+    tape::{tape_utils::TapeLongPositions, Tape, TapeAcceleration},
+    transition_binary::TransitionBinary,
+};
+
+/// Block of identical cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RleBlock {
+    pub symbol: u8,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct TapeRle {
+    /// Blocks left of the head, the block next to the head is the last one.
+    left: Vec<RleBlock>,
+    /// Blocks right of the head, the block next to the head is the last one.
+    right: Vec<RleBlock>,
+    /// Symbol under the head.
+    head: u8,
+    /// Head position relative to the start cell, negative to the left.
+    head_pos: i64,
+    /// Lowest and highest head position visited.
+    pos_min: i64,
+    pos_max: i64,
+    tape_size_limit: u32,
+}
+
+impl TapeRle {
+    pub fn new_tape_size_limit(tape_size_limit_cells: u32) -> Self {
+        Self {
+            left: Vec::new(),
+            right: Vec::new(),
+            head: 0,
+            head_pos: 0,
+            pos_min: 0,
+            pos_max: 0,
+            tape_size_limit: tape_size_limit_cells,
+        }
+    }
+
+    #[inline]
+    pub fn head_pos(&self) -> i64 {
+        self.head_pos
+    }
+
+    /// Number of blocks stored, a measure how well the tape compresses.
+    pub fn num_blocks(&self) -> usize {
+        self.left.len() + self.right.len()
+    }
+
+    /// Returns the cell relative to the head, 0 outside the written tape.
+    pub fn cell(&self, offset: i64) -> u8 {
+        if offset == 0 {
+            return self.head;
+        }
+        let blocks = if offset > 0 { &self.right } else { &self.left };
+        let mut distance = offset.unsigned_abs();
+        for block in blocks.iter().rev() {
+            if distance <= block.count as u64 {
+                return block.symbol;
+            }
+            distance -= block.count as u64;
+        }
+
+        0
+    }
+
+    /// Adds cells next to the head, merged with the top block if the symbol is the same.
+    #[inline(always)]
+    fn push(blocks: &mut Vec<RleBlock>, symbol: u8, count: u32) {
+        match blocks.last_mut() {
+            Some(block) if block.symbol == symbol => block.count += count,
+            _ => blocks.push(RleBlock { symbol, count }),
+        }
+    }
+
+    /// Removes the cell next to the head and returns its symbol, 0 if the written tape ends.
+    #[inline(always)]
+    fn pop(blocks: &mut Vec<RleBlock>) -> u8 {
+        match blocks.last_mut() {
+            Some(block) => {
+                let symbol = block.symbol;
+                block.count -= 1;
+                if block.count == 0 {
+                    blocks.pop();
+                }
+                symbol
+            }
+            None => 0,
+        }
+    }
+
+    /// Moves the head by distance cells, updating the visited bounds.
+    /// # Returns
+    /// False if the tape size limit is reached.
+    #[inline(always)]
+    fn move_head_pos(&mut self, distance: i64) -> bool {
+        self.head_pos += distance;
+        if self.head_pos > self.pos_max {
+            self.pos_max = self.head_pos;
+        } else if self.head_pos < self.pos_min {
+            self.pos_min = self.head_pos;
+        } else {
+            return true;
+        }
+        self.pos_max - self.pos_min < self.tape_size_limit as i64
+    }
+
+    /// Bits of the cells in the blocks starting next to the head, the cell next to the head is returned in the
+    /// lowest bit.
+    fn blocks_64_bit(blocks: &[RleBlock]) -> u64 {
+        let mut bits = 0;
+        let mut shift = 0;
+        for block in blocks.iter().rev() {
+            if shift >= 64 {
+                break;
+            }
+            let n = block.count.min(64 - shift);
+            if block.symbol == 1 {
+                bits |= (u64::MAX >> (64 - n)) << shift;
+            }
+            shift += n;
+        }
+
+        bits
+    }
+}
+
+impl Tape for TapeRle {
+    fn new(config: &Config) -> Self {
+        Self::new_tape_size_limit(config.tape_size_limit_cells())
+    }
+
+    fn clear(&mut self) {
+        self.left.clear();
+        self.right.clear();
+        self.head = 0;
+        self.head_pos = 0;
+        self.pos_min = 0;
+        self.pos_max = 0;
+    }
+
+    fn count_ones(&self) -> u32 {
+        self.left
+            .iter()
+            .chain(self.right.iter())
+            .filter(|b| b.symbol == 1)
+            .map(|b| b.count)
+            .sum::<u32>()
+            + self.head as u32
+    }
+
+    #[inline(always)]
+    fn get_current_symbol(&self) -> usize {
+        self.head as usize
+    }
+
+    fn is_left_empty(&self) -> bool {
+        self.head == 0 && self.left.iter().all(|b| b.symbol == 0)
+    }
+
+    fn is_right_empty(&self) -> bool {
+        self.head == 0 && self.right.iter().all(|b| b.symbol == 0)
+    }
+
+    fn left_64_bit(&self) -> u64 {
+        Self::blocks_64_bit(&self.left)
+    }
+
+    fn right_64_bit(&self) -> u64 {
+        (self.head as u64) << 63 | Self::blocks_64_bit(&self.right).reverse_bits() >> 1
+    }
+
+    #[cfg(feature = "enable_html_reports")]
+    fn pos_middle_print(&self) -> i64 {
+        crate::tape::tape_utils::MIDDLE_BIT_U128 as i64 + self.head_pos
+    }
+
+    #[inline(always)]
+    fn set_current_symbol(&mut self, transition: TransitionBinary) {
+        self.head = transition.symbol_usize() as u8;
+    }
+
+    #[inline(always)]
+    fn write_last_symbol(&mut self, transition: TransitionBinary) {
+        if !transition.is_undefined() {
+            self.set_current_symbol(transition);
+        }
+    }
+
+    fn tape_long_positions(&self) -> Option<TapeLongPositions> {
+        None
+    }
+
+    #[cfg(feature = "enable_html_reports")]
+    fn tape_shifted_clean(&self) -> u128 {
+        (self.left_64_bit() as u128) << 64 | self.right_64_bit() as u128
+    }
+
+    /// Returns the number of cells visited by the head.
+    fn tape_size_cells(&self) -> u32 {
+        (self.pos_max - self.pos_min + 1) as u32
+    }
+
+    #[inline(always)]
+    fn update_tape_single_step(&mut self, transition: TransitionBinary) -> bool {
+        let symbol = transition.symbol_usize() as u8;
+        if transition.is_dir_right() {
+            Self::push(&mut self.left, symbol, 1);
+            self.head = Self::pop(&mut self.right);
+            self.move_head_pos(1)
+        } else {
+            Self::push(&mut self.right, symbol, 1);
+            self.head = Self::pop(&mut self.left);
+            self.move_head_pos(-1)
+        }
+    }
+}
+
+impl TapeAcceleration for TapeRle {
+    /// Moves the head over the head cell and the whole block of the same symbol next to it.
+    #[inline(always)]
+    fn update_tape_self_ref_speed_up(&mut self, tr: TransitionBinary, tr_field: usize) -> StepBig {
+        if tr.self_ref_array_id() != tr_field {
+            return self.update_tape_single_step(tr) as StepBig;
+        }
+        let symbol = self.head;
+        let (behind, ahead) = if tr.is_dir_right() {
+            (&mut self.left, &mut self.right)
+        } else {
+            (&mut self.right, &mut self.left)
+        };
+        let mut jump = 1;
+        if let Some(&block) = ahead.last() {
+            if block.symbol == symbol {
+                jump += block.count;
+                ahead.pop();
+            }
+        }
+        if ahead.is_empty() && symbol == 0 {
+            // endless block of 0
+            return 0;
+        }
+        Self::push(behind, symbol, jump);
+        self.head = Self::pop(ahead);
+        let distance = if tr.is_dir_right() {
+            jump as i64
+        } else {
+            -(jump as i64)
+        };
+        if !self.move_head_pos(distance) {
+            return 0;
+        }
+
+        jump as StepBig
+    }
+}
+
+/// Displays the blocks with the count as exponent and the head cell in brackets, e.g. '1^3 0^2 [1] 0^5'.
+impl Display for TapeRle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let block = |b: &RleBlock| format!("{}^{}", b.symbol, b.count);
+        let mut parts: Vec<String> = self.left.iter().map(block).collect();
+        parts.push(format!("[{}]", self.head));
+        parts.extend(self.right.iter().rev().map(block));
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tape::tape_vec::TapeVec;
+
+    /// Transition in state A, so a transition writing the read symbol is self referencing.
+    fn transition(symbol: usize, dir_right: bool) -> TransitionBinary {
+        let symbol = if symbol == 1 { b'1' } else { b'0' };
+        let dir = if dir_right { b'R' } else { b'L' };
+        TransitionBinary::try_new([symbol, dir, b'A']).unwrap()
+    }
+
+    #[test]
+    fn tape_rle_equals_tape_vec() {
+        let config = Config::new_default(4);
+        let mut tape_rle = TapeRle::new(&config);
+        let mut tape_vec: TapeVec = TapeVec::new(&config);
+        // xorshift, reproducible random walk
+        let mut rng = 0x9E3779B9_7F4A7C15u64;
+        let mut next = || {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            rng
+        };
+        for step in 0..50_000 {
+            let r = next();
+            // segments of one direction and mostly one symbol, so long blocks occur
+            let dir_right = r >> 20 & 7 < 4;
+            let read = tape_rle.get_current_symbol();
+            if r & 3 == 0 {
+                let tr = transition(read, dir_right);
+                let jump = tape_rle.update_tape_self_ref_speed_up(tr, tr.self_ref_array_id());
+                if jump == 0 {
+                    // endless 0 block
+                    assert_eq!(read, 0);
+                    assert!(if dir_right {
+                        tape_vec.is_right_empty()
+                    } else {
+                        tape_vec.is_left_empty()
+                    });
+                    continue;
+                }
+                for _ in 0..jump {
+                    assert_eq!(tape_vec.get_current_symbol(), read, "step {step}");
+                    assert!(tape_vec.update_tape_single_step(tr));
+                }
+                assert_ne!(tape_vec.get_current_symbol(), read, "step {step}");
+            } else {
+                let symbol = (r >> 8 & 7 < 5) as usize;
+                let tr = transition(symbol, dir_right);
+                assert!(tape_rle.update_tape_single_step(tr));
+                assert!(tape_vec.update_tape_single_step(tr));
+            }
+            assert_eq!(tape_rle.get_current_symbol(), tape_vec.get_current_symbol());
+            assert_eq!(tape_rle.head_pos(), tape_vec.head_pos(), "step {step}");
+            assert_eq!(
+                tape_rle.left_64_bit(),
+                tape_vec.left_64_bit(),
+                "step {step}"
+            );
+            assert_eq!(
+                tape_rle.right_64_bit(),
+                tape_vec.right_64_bit(),
+                "step {step}"
+            );
+            assert_eq!(tape_rle.count_ones(), tape_vec.count_ones(), "step {step}");
+            assert_eq!(tape_rle.tape_size_cells(), tape_vec.tape_size_cells());
+        }
+    }
+
+    #[test]
+    fn tape_rle_long_block_single_jump() {
+        let mut tape = TapeRle::new_tape_size_limit(2_000_000);
+        let write_1_right = transition(1, true);
+        for _ in 0..1_000_000 {
+            assert!(tape.update_tape_single_step(write_1_right));
+        }
+        assert_eq!(tape.num_blocks(), 1);
+        assert_eq!(tape.to_string(), "1^1000000 [0]");
+        // back over all ones in one call, the head stops on the 0 left of them
+        let read_1_left = transition(1, false);
+        let tr = transition(0, false);
+        assert!(tape.update_tape_single_step(tr));
+        assert_eq!(
+            tape.update_tape_self_ref_speed_up(read_1_left, read_1_left.self_ref_array_id()),
+            1_000_000
+        );
+        assert_eq!((tape.head_pos(), tape.get_current_symbol()), (-1, 0));
+        // endless 0 block to the left
+        let read_0_left = transition(0, false);
+        assert_eq!(
+            tape.update_tape_self_ref_speed_up(read_0_left, read_0_left.self_ref_array_id()),
+            0
+        );
+        assert_eq!(tape.count_ones(), 1_000_000);
+    }
+}
@@ -0,0 +1,198 @@
+//! Tape for machines with more than two symbols ([MachineGeneric](crate::machine_generic::MachineGeneric)). \
+//! The other tapes pack one cell into one bit and work with [TransitionBinary](crate::transition_binary::TransitionBinary),
+//! which only knows the symbols 0 and 1. This tape stores each cell as [SymbolType] and grows in both directions
+//! until the tape size limit is reached. \
+//! It does not implement the [Tape](crate::tape::Tape) trait, as that one is built on the binary transitions.
+
+use std::fmt::Display;
+
+use crate::{
+    config::Config,
+    machine_generic::{SymbolType, TransitionGeneric},
+};
+
+/// Cells added when the tape needs to grow, doubled with the tape size.
+const TAPE_SIZE_INIT: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct TapeSymbol {
+    /// Cells of the tape, growing in both directions.
+    cells: Vec<SymbolType>,
+    /// Index of the head in cells.
+    pos: usize,
+    /// Index of the start cell in cells, the head positions are returned relative to this cell.
+    pos_start: usize,
+    /// Lowest and highest index visited by the head.
+    pos_min: usize,
+    pos_max: usize,
+    tape_size_limit: usize,
+}
+
+impl TapeSymbol {
+    pub fn new(config: &Config) -> Self {
+        Self::new_tape_size_limit(config.tape_size_limit_cells())
+    }
+
+    pub fn new_tape_size_limit(tape_size_limit_cells: u32) -> Self {
+        let mut tape = Self {
+            cells: Vec::new(),
+            pos: 0,
+            pos_start: 0,
+            pos_min: 0,
+            pos_max: 0,
+            tape_size_limit: tape_size_limit_cells as usize,
+        };
+        tape.clear();
+
+        tape
+    }
+
+    /// Resets the tape for a new machine.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.cells.resize(TAPE_SIZE_INIT, 0);
+        self.pos = TAPE_SIZE_INIT / 2;
+        self.pos_start = self.pos;
+        self.pos_min = self.pos;
+        self.pos_max = self.pos;
+    }
+
+    /// Returns the symbol at the head.
+    #[inline]
+    pub fn get_current_symbol(&self) -> SymbolType {
+        self.cells[self.pos]
+    }
+
+    /// Returns the head position relative to the start cell, negative to the left.
+    #[inline]
+    pub fn head_pos(&self) -> i64 {
+        self.pos as i64 - self.pos_start as i64
+    }
+
+    /// Returns the number of cells visited by the head.
+    pub fn tape_size_cells(&self) -> u32 {
+        (self.pos_max - self.pos_min + 1) as u32
+    }
+
+    /// Returns the number of cells which are not 0.
+    pub fn count_non_zero(&self) -> u32 {
+        self.cells.iter().filter(|&&c| c != 0).count() as u32
+    }
+
+    /// Returns the cells from the first to the last cell which is not 0 and the position of the first one
+    /// relative to the start cell. Two configurations with the same head position have the same tape if these are equal.
+    pub fn used_cells(&self) -> (i64, &[SymbolType]) {
+        let Some(first) = self.cells.iter().position(|&c| c != 0) else {
+            return (0, &[]);
+        };
+        let last = self.cells.iter().rposition(|&c| c != 0).unwrap();
+
+        (
+            first as i64 - self.pos_start as i64,
+            &self.cells[first..=last],
+        )
+    }
+
+    /// Writes the symbol of the transition and moves the head according to the direction of the transition.
+    /// # Returns
+    /// False if the tape size limit is reached, the head is not moved then.
+    #[must_use]
+    pub fn update_tape_single_step(&mut self, transition: TransitionGeneric) -> bool {
+        self.cells[self.pos] = transition.symbol_write;
+        if transition.direction > 0 {
+            if self.pos == self.pos_max {
+                if self.tape_size_cells() as usize >= self.tape_size_limit {
+                    return false;
+                }
+                if self.pos + 1 == self.cells.len() {
+                    let len = self.cells.len();
+                    self.cells.resize(len * 2, 0);
+                }
+                self.pos_max += 1;
+            }
+            self.pos += 1;
+        } else {
+            if self.pos == self.pos_min {
+                if self.tape_size_cells() as usize >= self.tape_size_limit {
+                    return false;
+                }
+                if self.pos == 0 {
+                    self.grow_left();
+                }
+                self.pos_min -= 1;
+            }
+            self.pos -= 1;
+        }
+
+        true
+    }
+
+    /// Writes the symbol of the last (halt) transition without moving the head. '---' does not write.
+    pub fn write_last_symbol(&mut self, transition: TransitionGeneric) {
+        if transition.has_symbol_write() {
+            self.cells[self.pos] = transition.symbol_write;
+        }
+    }
+
+    /// Doubles the tape to the left, all indices shift by the added cells.
+    fn grow_left(&mut self) {
+        let add = self.cells.len();
+        self.cells.splice(0..0, std::iter::repeat_n(0, add));
+        self.pos += add;
+        self.pos_start += add;
+        self.pos_min += add;
+        self.pos_max += add;
+    }
+}
+
+/// Displays the visited cells with the head cell in brackets, e.g. '1 2 [0] 1'.
+impl Display for TapeSymbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells: Vec<String> = (self.pos_min..=self.pos_max)
+            .map(|i| {
+                if i == self.pos {
+                    format!("[{}]", self.cells[i])
+                } else {
+                    self.cells[i].to_string()
+                }
+            })
+            .collect();
+        write!(f, "{}", cells.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_generic::{TransitionGeneric, A, DIR_LEFT, DIR_RIGHT};
+
+    #[test]
+    fn tape_symbol_grows_and_limits() {
+        let mut tape = TapeSymbol::new_tape_size_limit(200);
+        let write_2_left = TransitionGeneric::new_fields(2, DIR_LEFT, A);
+        for _ in 0..150 {
+            assert!(tape.update_tape_single_step(write_2_left));
+        }
+        assert_eq!(tape.head_pos(), -150);
+        assert_eq!(tape.count_non_zero(), 150);
+        assert_eq!(tape.used_cells().0, -149);
+        assert_eq!(tape.used_cells().1.len(), 150);
+
+        let write_3_right = TransitionGeneric::new_fields(3, DIR_RIGHT, A);
+        assert!(tape.update_tape_single_step(write_3_right));
+        assert_eq!(tape.head_pos(), -149);
+        assert_eq!(tape.used_cells().1[0], 3);
+        for _ in 0..149 {
+            assert!(tape.update_tape_single_step(write_2_left));
+            assert!(tape.update_tape_single_step(write_3_right));
+            assert!(tape.update_tape_single_step(write_3_right));
+        }
+        // 151 cells visited, the right side grows up to the limit of 200 cells
+        while tape.tape_size_cells() < 200 {
+            assert!(tape.update_tape_single_step(write_3_right));
+        }
+        assert!(!tape.update_tape_single_step(write_3_right));
+        assert_eq!(tape.tape_size_cells(), 200);
+        assert!(tape.to_string().ends_with("3 [3]"));
+    }
+}
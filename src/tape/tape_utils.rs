@@ -11,7 +11,7 @@ pub const TAPE_SIZE_HALF_128: u32 = TAPE_SIZE_BIT_U128 / 2;
 pub const TAPE_SIZE_FOURTH_128: u32 = TAPE_SIZE_BIT_U128 / 4;
 pub const TAPE_SIZE_FOURTH_UPPER_128: u32 = TAPE_SIZE_BIT_U128 / 4 + TAPE_SIZE_HALF_128;
 pub const MIDDLE_BIT_U128: u32 = TAPE_SIZE_BIT_U128 / 2 - 1;
-pub const POS_HALF_U128: u128 = 1 << MIDDLE_BIT_U128;
+pub use crate::sim_core::POS_HALF_U128;
 pub const TL_POS_START_128: usize = TAPE_SIZE_INIT_CELLS / 32 / 2 - 2;
 // const LOW32_SWITCH_U128: usize = MIDDLE_BIT_U128 - TAPE_SIZE_FOURTH;
 pub const LOW32_SWITCH_U128: u32 = MIDDLE_BIT_U128 - TAPE_SIZE_FOURTH_128;
@@ -37,6 +37,209 @@ pub const FILTER_LOW_BITS_U64: u64 = 0x00000000_FFFFFFFF;
 pub const TAPE_DISPLAY_RANGE_128: std::ops::Range<usize> =
     TL_POS_START_128 - 1..TL_POS_START_128 + 5;
 
+// Bit manipulation of the shifted tapes. The u128 tape has the head at bit 63 ([POS_HALF_U128]),
+// the cells left of the head are the higher bits. Tape_long stores the u128 tape as four u32 blocks,
+// block 0 being bits 127-96 (leftmost) and block 3 bits 31-0 (rightmost).
+// These functions should be used instead of the raw masks, they compile to the same instructions.
+
+/// Head access of the u128 tape, shared with the no-std core.
+pub use crate::sim_core::{read_head, set_head_symbol};
+
+/// Returns the symbol under the head (bit 31) of the u64 tape.
+#[inline(always)]
+pub fn read_head_u64(tape: u64) -> usize {
+    ((tape & POS_HALF_U64) != 0) as usize
+}
+
+/// Returns the u64 tape with the symbol under the head (bit 31) set to 1 or 0.
+#[inline(always)]
+pub fn set_head_symbol_u64(tape: u64, symbol_one: bool) -> u64 {
+    if symbol_one {
+        tape | POS_HALF_U64
+    } else {
+        tape & !POS_HALF_U64
+    }
+}
+
+/// Returns the u32 block of the u128 tape in tape_long order, block 0 is bits 127-96.
+#[inline(always)]
+pub fn block32(tape: u128, block_no: usize) -> u32 {
+    debug_assert!(block_no < 4);
+    (tape >> (96 - 32 * block_no as u32)) as u32
+}
+
+/// Returns the u128 tape with the u32 block (tape_long order, block 0 is bits 127-96) replaced.
+#[inline(always)]
+pub fn replace_block32(tape: u128, block_no: usize, block: u32) -> u128 {
+    let clear = match block_no {
+        0 => CLEAR_HIGH127_96BITS_U128,
+        1 => CLEAR_HIGH95_64BITS_U128,
+        2 => CLEAR_LOW63_32BITS_U128,
+        3 => CLEAR_LOW31_00BITS_U128,
+        _ => panic!("block_no {block_no} out of range 0..4"),
+    };
+    (tape & clear) | ((block as u128) << (96 - 32 * block_no as u32))
+}
+
+/// Returns the high 32 bits (block 0), which would be lost on [shift_left_into].
+#[inline(always)]
+pub fn spill_high32(tape: u128) -> u32 {
+    (tape >> TAPE_SIZE_FOURTH_UPPER_128) as u32
+}
+
+/// Returns the low 32 bits (block 3), which would be lost on [shift_right_into].
+#[inline(always)]
+pub fn spill_low32(tape: u128) -> u32 {
+    tape as u32
+}
+
+/// Shifts the u128 tape 32 bits left (head moves 32 cells right) and fills the low 32 bits with the block,
+/// which is the next block right of the tape. Use [spill_high32] first to save the high 32 bits.
+#[inline(always)]
+pub fn shift_left_into(tape: u128, block: u32) -> u128 {
+    (tape << 32) | block as u128
+}
+
+/// Shifts the u128 tape 32 bits right (head moves 32 cells left) and fills the high 32 bits with the block,
+/// which is the next block left of the tape. Use [spill_low32] first to save the low 32 bits.
+#[inline(always)]
+pub fn shift_right_into(tape: u128, block: u32) -> u128 {
+    (tape >> 32) | ((block as u128) << TAPE_SIZE_FOURTH_UPPER_128)
+}
+
+// 128-bit digest of the whole tape relative to the head, used by the cycler to compare tapes which are longer
+// than the u128 tape. Each cell with 1 at position p relative to the head adds r^p to a sum modulo the
+// prime 2^61-1, which is done for two bases r, so the digest has two independent 61-bit halves. \
+// The digest does not change when the tape is shifted, e.g. when the long tape grows, and it can be updated
+// in each step with a write and a head move.
+
+/// Mersenne prime 2^61-1, modulus of both digest halves.
+const DIGEST_PRIME: u64 = (1 << 61) - 1;
+/// Bases of the two digest halves.
+const DIGEST_BASE: [u64; 2] = [0x0AB5_4A98_CEB1_F0AD, 0x1C3F_5E27_9D46_B1E3];
+/// Inverses of the bases, used when the head moves right.
+const DIGEST_BASE_INV: [u64; 2] = [
+    pow_mod(DIGEST_BASE[0], DIGEST_PRIME - 2),
+    pow_mod(DIGEST_BASE[1], DIGEST_PRIME - 2),
+];
+/// Bases to the power of 32 to skip an empty u32 block.
+const DIGEST_BASE_POW32: [u64; 2] = [pow_mod(DIGEST_BASE[0], 32), pow_mod(DIGEST_BASE[1], 32)];
+
+#[inline(always)]
+const fn mul_mod(a: u64, b: u64) -> u64 {
+    let p = a as u128 * b as u128;
+    let r = (p as u64 & DIGEST_PRIME) + (p >> 61) as u64;
+    if r >= DIGEST_PRIME {
+        r - DIGEST_PRIME
+    } else {
+        r
+    }
+}
+
+const fn pow_mod(base: u64, exp: u64) -> u64 {
+    let (mut result, mut base, mut exp) = (1, base, exp);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base);
+        }
+        base = mul_mod(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Digest of the whole tape relative to the head, which can be updated step by step. \
+/// Two tapes with the same digest are identical relative to the head, a collision is practically impossible.
+/// See [tape_digest_128].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TapeDigest {
+    sums: [u64; 2],
+}
+
+impl TapeDigest {
+    /// Creates the digest of the cell blocks (32 cells each, first cell in the highest bit) with the head
+    /// on cell head_cell, counted from the first cell of the first block.
+    pub fn from_cell_blocks(cell_blocks: &[u32], head_cell: usize) -> Self {
+        let mut power = [
+            pow_mod(DIGEST_BASE_INV[0], head_cell as u64),
+            pow_mod(DIGEST_BASE_INV[1], head_cell as u64),
+        ];
+        let mut digest = Self::default();
+        for &block in cell_blocks {
+            if block == 0 {
+                for (p, b) in power.iter_mut().zip(DIGEST_BASE_POW32) {
+                    *p = mul_mod(*p, b);
+                }
+                continue;
+            }
+            for bit in (0..32).rev() {
+                for i in 0..2 {
+                    if (block >> bit) & 1 == 1 {
+                        digest.sums[i] = (digest.sums[i] + power[i]) % DIGEST_PRIME;
+                    }
+                    power[i] = mul_mod(power[i], DIGEST_BASE[i]);
+                }
+            }
+        }
+
+        digest
+    }
+
+    /// Updates the digest for the symbol written under the head, read is the symbol before the write.
+    #[inline(always)]
+    pub fn write(&mut self, read: usize, written: usize) {
+        if read == written {
+            return;
+        }
+        for sum in self.sums.iter_mut() {
+            *sum = if written == 1 {
+                (*sum + 1) % DIGEST_PRIME
+            } else {
+                (*sum + DIGEST_PRIME - 1) % DIGEST_PRIME
+            };
+        }
+    }
+
+    /// Updates the digest for a head move, all cells move one position relative to the head.
+    #[inline(always)]
+    pub fn move_head(&mut self, dir_right: bool) {
+        let factors = if dir_right {
+            DIGEST_BASE_INV
+        } else {
+            DIGEST_BASE
+        };
+        for (sum, f) in self.sums.iter_mut().zip(factors) {
+            *sum = mul_mod(*sum, f);
+        }
+    }
+
+    /// Returns the digest with the two halves in the high and low 64 bits.
+    #[inline(always)]
+    pub fn value(&self) -> u128 {
+        ((self.sums[0] as u128) << 64) | self.sums[1] as u128
+    }
+}
+
+/// Returns the 128-bit digest of the tape given as cell blocks (32 cells each, first cell in the highest bit)
+/// with the head on cell head_cell, counted from the first cell of the first block. \
+/// Only the cells with 1 and their position relative to the head count, so the digest is the same for
+/// any alignment of the blocks and any number of empty cells on either side.
+pub fn tape_digest_128(cell_blocks: &[u32], head_cell: usize) -> u128 {
+    TapeDigest::from_cell_blocks(cell_blocks, head_cell).value()
+}
+
+/// Returns the 128-bit digest of the u128 tape (head at bit 63), which is the same as [tape_digest_128]
+/// of the long tape as long as the u128 tape contains all cells.
+pub fn tape_digest_128_shifted(tape: u128) -> u128 {
+    let blocks = [
+        block32(tape, 0),
+        block32(tape, 1),
+        block32(tape, 2),
+        block32(tape, 3),
+    ];
+    tape_digest_128(&blocks, TAPE_SIZE_HALF_128 as usize)
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub struct TapeLongPositions {
     /// tl_pos represents the start of the 128 tape in the long tape (covering four u32 cell blocks)
@@ -73,7 +276,7 @@ impl U64Ext for u64 {
             let n = format!("{:08b}", (*self >> 24) as u8);
             let t = format!(
                 "{}<span class=\"{}\">{}</span>{}",
-                &n[0..1],
+                html::head_cell_html(&n[0..1], tr),
                 html::CLASS_CHANGED_POSITION,
                 &n[1..2],
                 &n[2..8]
@@ -92,10 +295,12 @@ impl U64Ext for u64 {
                 html::CLASS_CHANGED_POSITION,
                 &n[7..8]
             );
+            let h = format!("{:08b}", (self >> 24) as u8);
             format!(
-                "{:024b}_{t}&larr;{:08b}_{:024b}",
+                "{:024b}_{t}&larr;{}{}_{:024b}",
                 self >> 40,
-                (self >> 24) as u8,
+                html::head_cell_html(&h[0..1], tr),
+                &h[1..8],
                 (*self as u32) & 0b0000_0000_1111_1111_1111_1111_1111_1111,
             )
         }
@@ -142,7 +347,7 @@ impl U128Ext for u128 {
             let n = format!("{:08b}", (*self >> 56) as u8);
             let t = format!(
                 "{}<span class=\"{}\">{}</span>{}",
-                &n[0..1],
+                html::head_cell_html(&n[0..1], tr),
                 html::CLASS_CHANGED_POSITION,
                 &n[1..2],
                 &n[2..8]
@@ -163,11 +368,13 @@ impl U128Ext for u128 {
                 html::CLASS_CHANGED_POSITION,
                 &n[7..8]
             );
+            let h = format!("{:08b}", (*self >> 56) as u8);
             format!(
-                "{:032b}_{:024b}_{t}&larr;{:08b}_{:024b}_{:032b}",
+                "{:032b}_{:024b}_{t}&larr;{}{}_{:024b}_{:032b}",
                 (*self >> 96) as u32,
                 (*self >> 72) & 0b0000_0000_1111_1111_1111_1111_1111_1111,
-                (*self >> 56) as u8,
+                html::head_cell_html(&h[0..1], tr),
+                &h[1..8],
                 ((*self >> 32) as u32) & 0b0000_0000_1111_1111_1111_1111_1111_1111,
                 *self as u32,
             )
@@ -177,6 +384,9 @@ impl U128Ext for u128 {
 
 pub trait VecU32Ext {
     fn to_hex_string_range(&self, range: Range<usize>) -> String;
+    /// Run length encoded cells of the tape blocks, leading and trailing zeros are omitted,
+    /// e.g. '1^3 0 1^2' for 1110110.
+    fn to_rle_string(&self) -> String;
 }
 
 impl VecU32Ext for Vec<u32> {
@@ -188,6 +398,40 @@ impl VecU32Ext for Vec<u32> {
 
         s.join(" ")
     }
+
+    fn to_rle_string(&self) -> String {
+        let mut runs: Vec<(u32, usize)> = Vec::new();
+        for cell_pack in self.iter() {
+            for bit in (0..32).rev() {
+                let symbol = (cell_pack >> bit) & 1;
+                match runs.last_mut() {
+                    Some((last_symbol, count)) if *last_symbol == symbol => *count += 1,
+                    // skip leading zeros
+                    None if symbol == 0 => {}
+                    _ => runs.push((symbol, 1)),
+                }
+            }
+        }
+        // remove trailing zeros
+        if let Some((0, _)) = runs.last() {
+            runs.pop();
+        }
+        if runs.is_empty() {
+            return "0".to_string();
+        }
+
+        let s: Vec<String> = runs
+            .iter()
+            .map(|(symbol, count)| {
+                if *count == 1 {
+                    format!("{symbol}")
+                } else {
+                    format!("{symbol}^{count}")
+                }
+            })
+            .collect();
+        s.join(" ")
+    }
 }
 
 pub trait VecU64Ext {
@@ -204,3 +448,75 @@ impl VecU64Ext for Vec<u64> {
         s.join(" ")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tape_bits_head_symbol() {
+        let tape = set_head_symbol(0, true);
+        assert_eq!(tape, 1 << 63);
+        assert_eq!(read_head(tape), 1);
+        assert_eq!(read_head(tape << 1), 0);
+        assert_eq!(set_head_symbol(u128::MAX, false), !(1 << 63));
+        assert_eq!(read_head(set_head_symbol(u128::MAX, false)), 0);
+
+        let tape = set_head_symbol_u64(0, true);
+        assert_eq!(tape, 1 << 31);
+        assert_eq!(read_head_u64(tape), 1);
+        assert_eq!(set_head_symbol_u64(u64::MAX, false), !(1 << 31));
+    }
+
+    #[test]
+    fn tape_bits_blocks_shift_spill() {
+        let tape: u128 = 0x11111111_22222222_33333333_44444444;
+        assert_eq!(block32(tape, 0), 0x11111111);
+        assert_eq!(block32(tape, 3), 0x44444444);
+        assert_eq!(spill_high32(tape), block32(tape, 0));
+        assert_eq!(spill_low32(tape), block32(tape, 3));
+        for block_no in 0..4 {
+            let t = replace_block32(tape, block_no, 0xAAAAAAAA);
+            for b in 0..4 {
+                let expected = if b == block_no {
+                    0xAAAAAAAA
+                } else {
+                    block32(tape, b)
+                };
+                assert_eq!(block32(t, b), expected);
+            }
+        }
+
+        let left = shift_left_into(tape, 0x55555555);
+        assert_eq!(left, 0x22222222_33333333_44444444_55555555);
+        let right = shift_right_into(tape, 0x55555555);
+        assert_eq!(right, 0x55555555_11111111_22222222_33333333);
+        // shifting back with the spilled block restores the tape
+        assert_eq!(shift_right_into(left, spill_high32(tape)), tape);
+        assert_eq!(shift_left_into(right, spill_low32(tape)), tape);
+    }
+
+    #[test]
+    fn tape_digest_shift_invariant_and_incremental() {
+        let tape: u128 = 0x00000000_000000F0_25000000_00000001;
+        let digest = tape_digest_128_shifted(tape);
+        // same cells with empty blocks around and the head moved accordingly
+        let blocks = [0, 0, 0x000000F0, 0x25000000, 0x00000001, 0];
+        assert_eq!(tape_digest_128(&blocks, 64 + 32), digest);
+        assert_ne!(tape_digest_128(&blocks, 64 + 31), digest);
+        assert_ne!(tape_digest_128_shifted(tape ^ 1), digest);
+        assert_eq!(tape_digest_128_shifted(0), 0);
+
+        // write 1 under the head and move right: cells shift one position left relative to the head
+        let mut incremental = TapeDigest::from_cell_blocks(&blocks, 64 + 32);
+        incremental.write(0, 1);
+        incremental.move_head(true);
+        assert_eq!(
+            incremental.value(),
+            tape_digest_128_shifted(set_head_symbol(tape, true) << 1)
+        );
+        incremental.move_head(false);
+        incremental.write(1, 0);
+        assert_eq!(incremental.value(), digest);
+    }
+}
@@ -0,0 +1,388 @@
+//! TapeVec is a simple tape which stores each cell as one symbol (u8 by default) in a [Vec], growing in both
+//! directions until the tape size limit is reached. \
+//! Unlike the packed 128-bit tapes it implements the [Tape] trait without any bit shifting, which makes it much
+//! easier to debug. As the cells are not restricted to 0 and 1, the same tape can also run machines with more than
+//! two symbols, see [TapeVec::update_tape_single_step_generic]. \
+//! [Tape::left_64_bit] and [Tape::right_64_bit] return a bit for each cell which is not 0, which is exact for
+//! binary machines.
+
+use std::fmt::{Debug, Display};
+
+use crate::{
+    config::Config,
+    machine_generic::{SymbolType, TransitionGeneric},
+    tape::{tape_utils::TapeLongPositions, Tape},
+    transition_binary::TransitionBinary,
+};
+
+/// Cells added when the tape needs to grow, doubled with the tape size.
+const TAPE_SIZE_INIT: usize = 64;
+
+/// Symbol stored in a cell of [TapeVec].
+pub trait CellSymbol: Copy + Default + Eq + Debug + Display {
+    fn from_usize(symbol: usize) -> Self;
+    fn to_usize(self) -> usize;
+}
+
+impl CellSymbol for u8 {
+    #[inline(always)]
+    fn from_usize(symbol: usize) -> Self {
+        symbol as u8
+    }
+
+    #[inline(always)]
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl CellSymbol for u16 {
+    #[inline(always)]
+    fn from_usize(symbol: usize) -> Self {
+        symbol as u16
+    }
+
+    #[inline(always)]
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TapeVec<Symbol: CellSymbol = u8> {
+    /// Cells of the tape, growing in both directions.
+    cells: Vec<Symbol>,
+    /// Index of the head in cells.
+    pos: usize,
+    /// Index of the start cell in cells, the head positions are returned relative to this cell.
+    pos_start: usize,
+    /// Lowest and highest index visited by the head.
+    pos_min: usize,
+    pos_max: usize,
+    tape_size_limit: usize,
+}
+
+impl<Symbol: CellSymbol> TapeVec<Symbol> {
+    pub fn new_tape_size_limit(tape_size_limit_cells: u32) -> Self {
+        let mut tape = Self {
+            cells: Vec::new(),
+            pos: 0,
+            pos_start: 0,
+            pos_min: 0,
+            pos_max: 0,
+            tape_size_limit: tape_size_limit_cells as usize,
+        };
+        tape.clear();
+
+        tape
+    }
+
+    /// Returns the cell relative to the head, 0 outside the tape.
+    #[inline]
+    pub fn cell(&self, offset: i64) -> Symbol {
+        let i = self.pos as i64 + offset;
+        if i < 0 {
+            return Symbol::default();
+        }
+        self.cells.get(i as usize).copied().unwrap_or_default()
+    }
+
+    /// Returns the head position relative to the start cell, negative to the left.
+    #[inline]
+    pub fn head_pos(&self) -> i64 {
+        self.pos as i64 - self.pos_start as i64
+    }
+
+    /// Returns the position of the first visited cell relative to the start cell.
+    pub fn visited_start(&self) -> i64 {
+        self.pos_min as i64 - self.pos_start as i64
+    }
+
+    /// Returns the cells visited by the head, from left to right.
+    pub fn visited_cells(&self) -> &[Symbol] {
+        &self.cells[self.pos_min..=self.pos_max]
+    }
+
+    /// Writes the symbol and moves the head one cell.
+    /// # Returns
+    /// False if the tape size limit is reached, the head is not moved then.
+    #[must_use]
+    pub fn update_tape_single_step_symbol(&mut self, symbol: Symbol, dir_right: bool) -> bool {
+        self.cells[self.pos] = symbol;
+        if dir_right {
+            if self.pos == self.pos_max {
+                if self.tape_size_cells() as usize >= self.tape_size_limit {
+                    return false;
+                }
+                if self.pos + 1 == self.cells.len() {
+                    let len = self.cells.len();
+                    self.cells.resize(len * 2, Symbol::default());
+                }
+                self.pos_max += 1;
+            }
+            self.pos += 1;
+        } else {
+            if self.pos == self.pos_min {
+                if self.tape_size_cells() as usize >= self.tape_size_limit {
+                    return false;
+                }
+                if self.pos == 0 {
+                    self.grow_left();
+                }
+                self.pos_min -= 1;
+            }
+            self.pos -= 1;
+        }
+
+        true
+    }
+
+    /// Same as [Tape::update_tape_single_step] for machines with more than two symbols.
+    #[must_use]
+    pub fn update_tape_single_step_generic(&mut self, transition: TransitionGeneric) -> bool {
+        self.update_tape_single_step_symbol(
+            Symbol::from_usize(transition.symbol_write as usize),
+            transition.direction > 0,
+        )
+    }
+
+    /// Same as [Tape::write_last_symbol] for machines with more than two symbols. '---' does not write.
+    pub fn write_last_symbol_generic(&mut self, transition: TransitionGeneric) {
+        if transition.has_symbol_write() {
+            self.cells[self.pos] = Symbol::from_usize(transition.symbol_write as usize);
+        }
+    }
+
+    /// Doubles the tape to the left, all indices shift by the added cells.
+    fn grow_left(&mut self) {
+        let add = self.cells.len();
+        self.cells
+            .splice(0..0, std::iter::repeat_n(Symbol::default(), add));
+        self.pos += add;
+        self.pos_start += add;
+        self.pos_min += add;
+        self.pos_max += add;
+    }
+
+    /// Bit for a cell which is not 0.
+    #[inline(always)]
+    fn cell_bit(&self, offset: i64) -> u64 {
+        (self.cell(offset) != Symbol::default()) as u64
+    }
+}
+
+impl TapeVec<SymbolType> {
+    /// Creates a tape with cells wide enough for all symbols of [MachineGeneric](crate::machine_generic::MachineGeneric).
+    pub fn new_generic(config: &Config) -> Self {
+        Self::new_tape_size_limit(config.tape_size_limit_cells())
+    }
+}
+
+impl<Symbol: CellSymbol> Tape for TapeVec<Symbol> {
+    fn new(config: &Config) -> Self {
+        Self::new_tape_size_limit(config.tape_size_limit_cells())
+    }
+
+    fn clear(&mut self) {
+        self.cells.clear();
+        self.cells.resize(TAPE_SIZE_INIT, Symbol::default());
+        self.pos = TAPE_SIZE_INIT / 2;
+        self.pos_start = self.pos;
+        self.pos_min = self.pos;
+        self.pos_max = self.pos;
+    }
+
+    /// Returns the number of cells which are not 0, which are the ones for binary machines.
+    fn count_ones(&self) -> u32 {
+        self.cells
+            .iter()
+            .filter(|&&c| c != Symbol::default())
+            .count() as u32
+    }
+
+    #[inline(always)]
+    fn get_current_symbol(&self) -> usize {
+        self.cells[self.pos].to_usize()
+    }
+
+    fn is_left_empty(&self) -> bool {
+        self.cells[..=self.pos]
+            .iter()
+            .all(|&c| c == Symbol::default())
+    }
+
+    fn is_right_empty(&self) -> bool {
+        self.cells[self.pos..]
+            .iter()
+            .all(|&c| c == Symbol::default())
+    }
+
+    /// The 64 cells left of the head, the cell next to the head is bit 0.
+    fn left_64_bit(&self) -> u64 {
+        (0..64).fold(0, |bits, i| bits | self.cell_bit(-1 - i) << i)
+    }
+
+    /// The head and the 63 cells right of it, the head is bit 63.
+    fn right_64_bit(&self) -> u64 {
+        (0..64).fold(0, |bits, i| bits | self.cell_bit(i) << (63 - i))
+    }
+
+    #[cfg(feature = "enable_html_reports")]
+    fn pos_middle_print(&self) -> i64 {
+        crate::tape::tape_utils::MIDDLE_BIT_U128 as i64 + self.head_pos()
+    }
+
+    #[inline(always)]
+    fn set_current_symbol(&mut self, transition: TransitionBinary) {
+        self.cells[self.pos] = Symbol::from_usize(transition.symbol_usize());
+    }
+
+    #[inline(always)]
+    fn write_last_symbol(&mut self, transition: TransitionBinary) {
+        if !transition.is_undefined() {
+            self.set_current_symbol(transition);
+        }
+    }
+
+    fn tape_long_positions(&self) -> Option<TapeLongPositions> {
+        None
+    }
+
+    #[cfg(feature = "enable_html_reports")]
+    fn tape_shifted_clean(&self) -> u128 {
+        (self.left_64_bit() as u128) << 64 | self.right_64_bit() as u128
+    }
+
+    /// Returns the number of cells visited by the head.
+    fn tape_size_cells(&self) -> u32 {
+        (self.pos_max - self.pos_min + 1) as u32
+    }
+
+    #[inline(always)]
+    fn update_tape_single_step(&mut self, transition: TransitionBinary) -> bool {
+        self.update_tape_single_step_symbol(
+            Symbol::from_usize(transition.symbol_usize()),
+            transition.is_dir_right(),
+        )
+    }
+}
+
+/// Displays the visited cells with the head cell in brackets, e.g. '1 2 [0] 1'.
+impl<Symbol: CellSymbol> Display for TapeVec<Symbol> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells: Vec<String> = (self.pos_min..=self.pos_max)
+            .map(|i| {
+                if i == self.pos {
+                    format!("[{}]", self.cells[i])
+                } else {
+                    self.cells[i].to_string()
+                }
+            })
+            .collect();
+        write!(f, "{}", cells.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decider::decider_generic::DeciderGeneric,
+        machine_generic::{MachineGeneric, A},
+        status::MachineStatus,
+        tape::{
+            tape_128::Tape128, tape_long_shifted::TapeLongShifted, tape_utils::tape_digest_128,
+        },
+    };
+
+    /// Digest of the visited cells packed into blocks of 32 cells, comparable to [TapeLongShifted::tape_digest].
+    fn tape_digest(tape: &TapeVec) -> u128 {
+        let blocks: Vec<u32> = tape
+            .visited_cells()
+            .chunks(32)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |block, (i, &c)| block | ((c != 0) as u32) << (31 - i))
+            })
+            .collect();
+        tape_digest_128(&blocks, (tape.head_pos() - tape.visited_start()) as usize)
+    }
+
+    #[test]
+    fn tape_vec_binary_equals_packed_tapes() {
+        let config = Config::new_default(4);
+        let mut tape_vec: TapeVec = TapeVec::new(&config);
+        let mut tape_long = TapeLongShifted::new(&config);
+        let mut tape_128 = Tape128::new(&config);
+        let mut is_tape_128_in_bounds = true;
+        // xorshift, reproducible random walk
+        let mut rng = 0x2545F491_4F6CDD1Du64;
+        for step in 0..20_000 {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            let symbol = if rng & 1 == 1 { b'1' } else { b'0' };
+            // drift to the right, so the tape grows far beyond 128 cells
+            let dir = if rng >> 8 & 7 < 3 { b'L' } else { b'R' };
+            let tr = TransitionBinary::try_new([symbol, dir, b'A']).unwrap();
+            assert!(tape_vec.update_tape_single_step(tr));
+            assert!(tape_long.update_tape_single_step(tr));
+            assert_eq!(
+                tape_vec.get_current_symbol(),
+                tape_long.get_current_symbol()
+            );
+            assert_eq!(tape_vec.count_ones(), tape_long.count_ones(), "step {step}");
+            if step % 100 == 0 {
+                assert_eq!(
+                    tape_digest(&tape_vec),
+                    tape_long.tape_digest().value(),
+                    "step {step}"
+                );
+            }
+            is_tape_128_in_bounds = is_tape_128_in_bounds && tape_128.update_tape_single_step(tr);
+            if is_tape_128_in_bounds {
+                assert_eq!(
+                    tape_vec.left_64_bit(),
+                    tape_128.left_64_bit(),
+                    "step {step}"
+                );
+                assert_eq!(
+                    tape_vec.right_64_bit(),
+                    tape_128.right_64_bit(),
+                    "step {step}"
+                );
+            }
+        }
+        assert!(tape_vec.tape_size_cells() > 1000);
+    }
+
+    #[test]
+    fn tape_vec_generic_bb2x3_champion() {
+        let machine =
+            MachineGeneric::try_from_standard_tm_text_format("1RB2LB1RZ_2LA2RB1LB").unwrap();
+        let config = Config::new_default(2);
+        let mut tape = TapeVec::new_generic(&config);
+        let mut state = A;
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            let tr =
+                machine.transition_for_state_symbol(state, tape.get_current_symbol() as SymbolType);
+            if tr.is_halt() {
+                tape.write_last_symbol_generic(tr);
+                break;
+            }
+            assert!(tape.update_tape_single_step_generic(tr));
+            state = tr.state_next;
+        }
+        assert_eq!(
+            DeciderGeneric::decide_single_machine(&machine, &config),
+            MachineStatus::DecidedHalt(steps)
+        );
+        // the champion leaves 9 non-zero symbols on the tape
+        assert_eq!(tape.count_ones(), 9);
+        assert!(tape.visited_cells().contains(&2));
+    }
+}
@@ -0,0 +1,173 @@
+//! Generator for decider unit tests, to grow the regression tests every time a new edge case is found. \
+//! [generate_test] decides the machine with a standard decider and returns a ready-to-paste `#[test]`
+//! asserting the machine and the resulting [MachineStatus]. [generate_test_from_html] does the same for an
+//! html trace of a machine, see [Config::write_html_file]. The decider and machine are read from the header of
+//! the trace and the machine is decided again, which must give the status at the end of the trace. \
+//! The test uses the default config, so it only reproduces traces created with the default step limits.
+//! # Example
+//! ```
+//! use bb_challenge::{decider::DeciderStandard, machine_binary::MachineId, test_generator::generate_test};
+//!
+//! let machine = MachineId::try_from("1RB0RB_1LA1RB").unwrap();
+//! println!("{}", generate_test(&DeciderStandard::Cycler, &machine));
+//! ```
+//! prints
+//! ```text
+//! #[test]
+//! fn cycler_bb2_1rb0rb_1la1rb() {
+//!     let machine = MachineId::try_from("1RB0RB_1LA1RB").unwrap();
+//!     let config = Config::new_default(2);
+//!     let status = DeciderCycler::decide_single_machine(&machine, &config);
+//!     assert_eq!(status, MachineStatus::DecidedNonHalt(NonHaltReason::Cycler(7, 3)));
+//! }
+//! ```
+//!
+//! [Config::write_html_file]: crate::config::Config::write_html_file
+
+use std::{io, path::Path};
+
+use crate::{
+    config::Config,
+    decider::DeciderStandard,
+    machine_binary::MachineId,
+    status::{MachineStatus, NonHaltReason},
+};
+
+/// Decides the machine with the default config and returns the test for this decision.
+pub fn generate_test(decider: &DeciderStandard, machine: &MachineId) -> String {
+    let config = Config::new_default(machine.n_states());
+    let status = decider.decide_single_machine(machine, &config);
+    test_code(decider, machine, &status)
+}
+
+/// Returns the test for the html trace of a machine, see [module documentation](self).
+/// # Errors
+/// If the file cannot be read, the header is not found or the machine is decided differently than in the trace.
+pub fn generate_test_from_html(file_path: impl AsRef<Path>) -> io::Result<String> {
+    let html = std::fs::read_to_string(file_path)?;
+    let invalid = |text: String| io::Error::new(io::ErrorKind::InvalidData, text);
+    // header e.g. '<h2>BB4 Decider Cycler Machine Id: 32538705 0RC1LC_---1RC_1LD1RB_1RA0RA</h2>'
+    let header = html
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("<h2>BB")?.strip_suffix("</h2>"))
+        .ok_or_else(|| invalid("Html trace: machine header not found".to_string()))?;
+    let (decider_name, machine_text) = header
+        .split_once(' ')
+        .and_then(|(_, rest)| rest.split_once(" Machine"))
+        .and_then(|(name, rest)| Some((name, rest.split_whitespace().last()?)))
+        .ok_or_else(|| invalid(format!("Html trace: invalid header '{header}'")))?;
+    let decider = DeciderStandard::from_decider_name(decider_name)
+        .ok_or_else(|| invalid(format!("Html trace: unknown decider '{decider_name}'")))?;
+    let machine = MachineId::try_from(machine_text).map_err(|e| invalid(e.to_string()))?;
+
+    let config = Config::new_default(machine.n_states());
+    let status = decider.decide_single_machine(&machine, &config);
+    if !html.contains(&format!("<p>{status}</p>")) {
+        return Err(invalid(format!(
+            "Html trace: {machine_text} is decided as '{status}' with the default config, which is not the status of the trace"
+        )));
+    }
+    Ok(test_code(&decider, &machine, &status))
+}
+
+/// Returns the test asserting the status of the machine.
+pub fn test_code(decider: &DeciderStandard, machine: &MachineId, status: &MachineStatus) -> String {
+    let machine_text = machine.to_standard_tm_text_format();
+    let test_name = format!(
+        "{}_bb{}_{}",
+        decider.decider_id().sub_dir,
+        machine.n_states(),
+        machine_text.replace('-', "z")
+    )
+    .to_lowercase();
+    format!(
+        "#[test]\n\
+        fn {test_name}() {{\n    \
+            let machine = MachineId::try_from(\"{machine_text}\").unwrap();\n    \
+            let config = Config::new_default({});\n    \
+            let status = {}::decide_single_machine(&machine, &config);\n    \
+            assert_eq!(status, {});\n\
+        }}\n",
+        machine.n_states(),
+        decider.type_name(),
+        status_code(status)
+    )
+}
+
+/// Returns the status as Rust code, e.g. 'MachineStatus::DecidedHalt(107)'.
+pub fn status_code(status: &MachineStatus) -> String {
+    match status {
+        MachineStatus::DecidedNonHalt(NonHaltReason::ExpandingBouncer(reason)) => format!(
+            "MachineStatus::DecidedNonHalt(NonHaltReason::ExpandingBouncer(ExpandingBouncerReason::{reason:?}))"
+        ),
+        MachineStatus::DecidedNonHalt(reason) => {
+            format!("MachineStatus::DecidedNonHalt(NonHaltReason::{reason:?})")
+        }
+        MachineStatus::EliminatedPreDecider(reason) => {
+            format!("MachineStatus::EliminatedPreDecider(PreDeciderReason::{reason:?})")
+        }
+        MachineStatus::Undecided(reason, steps, tape_size) => {
+            format!("MachineStatus::Undecided(UndecidedReason::{reason:?}, {steps}, {tape_size})")
+        }
+        // only numbers
+        _ => format!("MachineStatus::{status:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decider::decider_cycler::DeciderCycler, decider::Decider, status::UndecidedReason,
+    };
+
+    #[test]
+    fn generated_test_code() {
+        let machine = MachineId::try_from("1RB1LB_1LA0LC_1RZ1LD_1RD0RA").unwrap();
+        let code = generate_test(&DeciderStandard::Hold, &machine);
+        assert!(code.starts_with("#[test]\nfn halt_bb4_1rb1lb_1la0lc_1rz1ld_1rd0ra() {\n"));
+        assert!(code.contains(
+            "    let status = DeciderHaltLong::decide_single_machine(&machine, &config);\n"
+        ));
+        assert!(code.contains("    assert_eq!(status, MachineStatus::DecidedHaltField(107, 6));\n"));
+
+        assert_eq!(
+            status_code(&MachineStatus::Undecided(
+                UndecidedReason::StepLimit,
+                500,
+                12
+            )),
+            "MachineStatus::Undecided(UndecidedReason::StepLimit, 500, 12)"
+        );
+        // the assertion of the test is the status of the decider
+        let status = DeciderCycler::decide_single_machine(&machine, &Config::new_default(4));
+        assert!(
+            test_code(&DeciderStandard::Cycler, &machine, &status).contains(&status_code(&status))
+        );
+    }
+
+    #[test]
+    fn generated_test_from_html_trace() {
+        let dir = std::env::temp_dir().join("bb_challenge_test_generator_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file_path = dir.join("trace.html");
+        let status = MachineStatus::DecidedHaltField(107, 6);
+        std::fs::write(
+            &file_path,
+            format!(
+                "<body>\n  <h2>BB4 Decider Halt Machine 1RB1LB_1LA0LC_1RZ1LD_1RD0RA</h2>\n<p>{status}</p>\n</body>\n"
+            ),
+        )
+        .unwrap();
+        let code = generate_test_from_html(&file_path).unwrap();
+        std::fs::write(
+            &file_path,
+            "<body>\n  <h2>BB4 Decider Cycler Machine 1RB1LB_1LA0LC_1RZ1LD_1RD0RA</h2>\n",
+        )
+        .unwrap();
+        let e = generate_test_from_html(&file_path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(code.contains("MachineStatus::DecidedHaltField(107, 6)"));
+        assert!(e.is_err());
+    }
+}
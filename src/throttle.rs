@@ -0,0 +1,253 @@
+//! Throttled low-priority background mode, so long runs can share a workstation. \
+//! If [Config::throttle] is set, each worker thread
+//! - lowers its OS priority once (nice 19 on Linux, not supported on other systems, as setpriority changes the
+//!   whole process there),
+//! - sleeps after each batch, so the thread runs about [Throttle::cpu_percent] of the time,
+//! - skips the sleep while the rest of the system is idle, if [Throttle::full_speed_when_idle] is set.
+//!   The load of the other processes is read from /proc/stat and /proc/self/stat at most once per second,
+//!   so this is only available on Linux.
+//!
+//! This limits the CPU usage per thread, the number of threads is limited by [Config::cpu_utilization_percent]
+//! or [Config::num_threads].
+//! # Example
+//! ```
+//! use bb_challenge::{config::Config, throttle::Throttle};
+//!
+//! let config = Config::builder(5)
+//!     .cpu_utilization(50)
+//!     .throttle(Some(Throttle { cpu_percent: 30, ..Default::default() }))
+//!     .build();
+//! ```
+//!
+//! [Config::throttle]: crate::config::Config::throttle
+//! [Config::cpu_utilization_percent]: crate::config::Config::cpu_utilization_percent
+//! [Config::num_threads]: crate::config::Config::num_threads
+
+use std::{
+    cell::Cell,
+    io,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Lowest priority (highest nice value) on Linux.
+#[cfg(target_os = "linux")]
+const NICE_LOWEST_PRIORITY: libc::c_int = 19;
+/// Share of the total CPU time used by other processes below which the system counts as idle.
+const IDLE_OTHER_LOAD_SHARE: f64 = 0.1;
+/// Minimum time between two reads of the system load.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Settings of the background mode, see [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throttle {
+    /// Target CPU usage of each worker thread in percent (1-100). The thread sleeps after each batch
+    /// (100 - cpu_percent) / cpu_percent times the duration of the batch.
+    pub cpu_percent: u8,
+    /// Runs the worker threads with the lowest OS priority.
+    pub low_priority: bool,
+    /// No sleep while the other processes use less than 10% of the CPU time (Linux only).
+    pub full_speed_when_idle: bool,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self {
+            cpu_percent: 50,
+            low_priority: true,
+            full_speed_when_idle: true,
+        }
+    }
+}
+
+impl Throttle {
+    /// Sleep time after a batch which took duration_work, so the thread runs cpu_percent of the time.
+    pub fn sleep_duration(&self, duration_work: Duration) -> Duration {
+        let percent = self.cpu_percent.clamp(1, 100) as u32;
+        duration_work * (100 - percent) / percent
+    }
+
+    /// Called by the worker thread after each batch. Lowers the priority of the thread on the first call
+    /// and sleeps to reach the target CPU usage. \
+    /// Returns an error if the priority could not be changed, this is only returned once per thread.
+    pub fn after_batch(&self, duration_work: Duration) -> io::Result<()> {
+        let mut result = Ok(());
+        if self.low_priority && !PRIORITY_LOWERED.get() {
+            PRIORITY_LOWERED.set(true);
+            result = set_current_thread_low_priority();
+        }
+        if self.cpu_percent < 100 && !(self.full_speed_when_idle && is_system_idle()) {
+            std::thread::sleep(self.sleep_duration(duration_work));
+        }
+        result
+    }
+}
+
+thread_local! {
+    /// The priority is lowered only once per thread.
+    static PRIORITY_LOWERED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Sets the lowest OS priority for the current thread (on Linux the nice value is per thread).
+#[cfg(target_os = "linux")]
+pub fn set_current_thread_low_priority() -> io::Result<()> {
+    // SAFETY: setpriority has no memory safety requirements, who = 0 is the calling thread.
+    let r = unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, NICE_LOWEST_PRIORITY) };
+    if r == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Sets the lowest OS priority for the current thread, not supported on this system.
+/// On other unix systems setpriority would lower the whole process including the caller's threads.
+#[cfg(not(target_os = "linux"))]
+pub fn set_current_thread_low_priority() -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "low thread priority is only supported on Linux",
+    ))
+}
+
+/// CPU times (clock ticks) of the system and this process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CpuSample {
+    total: u64,
+    idle: u64,
+    own: u64,
+}
+
+impl CpuSample {
+    #[cfg(target_os = "linux")]
+    fn read() -> Option<Self> {
+        let stat = std::fs::read_to_string("/proc/stat").ok()?;
+        let self_stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+        let (total, idle) = parse_proc_stat(&stat)?;
+        Some(Self {
+            total,
+            idle,
+            own: parse_proc_self_stat(&self_stat)?,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn read() -> Option<Self> {
+        None
+    }
+
+    /// Share of the CPU time between the samples used by the other processes.
+    fn other_load_share(&self, later: &CpuSample) -> f64 {
+        let total = later.total.saturating_sub(self.total);
+        if total == 0 {
+            return 0.0;
+        }
+        let busy = total.saturating_sub(later.idle.saturating_sub(self.idle));
+        let other = busy.saturating_sub(later.own.saturating_sub(self.own));
+        other as f64 / total as f64
+    }
+}
+
+/// Last system load check, shared by all worker threads.
+struct IdleCheck {
+    time: Instant,
+    sample: CpuSample,
+    is_idle: bool,
+}
+
+static IDLE_CHECK: Mutex<Option<IdleCheck>> = Mutex::new(None);
+
+/// True if the other processes used less than 10% of the CPU time since the last check.
+/// Returns false if the load cannot be read.
+fn is_system_idle() -> bool {
+    let Ok(mut check) = IDLE_CHECK.lock() else {
+        return false;
+    };
+    match check.as_mut() {
+        Some(c) if c.time.elapsed() < IDLE_CHECK_INTERVAL => c.is_idle,
+        Some(c) => {
+            let Some(sample) = CpuSample::read() else {
+                return false;
+            };
+            c.is_idle = c.sample.other_load_share(&sample) < IDLE_OTHER_LOAD_SHARE;
+            c.sample = sample;
+            c.time = Instant::now();
+            c.is_idle
+        }
+        None => {
+            // first call, no load known yet
+            if let Some(sample) = CpuSample::read() {
+                *check = Some(IdleCheck {
+                    time: Instant::now(),
+                    sample,
+                    is_idle: false,
+                });
+            }
+            false
+        }
+    }
+}
+
+/// Returns total and idle (including iowait) ticks of the 'cpu' line of /proc/stat.
+fn parse_proc_stat(stat: &str) -> Option<(u64, u64)> {
+    let line = stat.lines().find(|l| l.starts_with("cpu "))?;
+    let values = line
+        .split_whitespace()
+        .skip(1)
+        .map(|v| v.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    // user nice system idle iowait irq softirq steal, guest is included in user
+    let total = values.iter().take(8).sum();
+    let idle = values.get(3)? + values.get(4).unwrap_or(&0);
+    Some((total, idle))
+}
+
+/// Returns the user and system ticks (fields 14 and 15) of /proc/self/stat.
+fn parse_proc_self_stat(stat: &str) -> Option<u64> {
+    // the process name in brackets may contain spaces, the fields are counted after it
+    let (_, fields) = stat.rsplit_once(')')?;
+    let mut fields = fields.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_sleep_and_load_share() {
+        let throttle = Throttle {
+            cpu_percent: 25,
+            ..Default::default()
+        };
+        assert_eq!(
+            throttle.sleep_duration(Duration::from_millis(100)),
+            Duration::from_millis(300)
+        );
+        let full = Throttle {
+            cpu_percent: 100,
+            ..Default::default()
+        };
+        assert_eq!(full.sleep_duration(Duration::from_secs(1)), Duration::ZERO);
+
+        let stat = "cpu  100 10 50 800 40 0 0 0 0 0\ncpu0 100 10 50 800 40 0 0 0 0 0\n";
+        assert_eq!(parse_proc_stat(stat), Some((1000, 840)));
+        let self_stat = "1234 (bb challenge) R 1 2 3 4 5 6 7 8 9 10 70 20 0 0 20 0";
+        assert_eq!(parse_proc_self_stat(self_stat), Some(90));
+
+        // 1000 ticks, 600 idle, 300 own: the other processes used 10%
+        let s1 = CpuSample {
+            total: 1000,
+            idle: 840,
+            own: 90,
+        };
+        let s2 = CpuSample {
+            total: 2000,
+            idle: 1440,
+            own: 390,
+        };
+        assert!((s1.other_load_share(&s2) - 0.1).abs() < 1e-9);
+    }
+}
@@ -16,6 +16,11 @@ pub struct ConfigToml {
     #[serde(default = "default_bb_challenge_file")]
     bb_challenge_filename_path: String,
 
+    /// file path and file name of the bb_challenge undecided index, a list of machine ids (u32 big-endian)
+    /// into the bb_challenge file, usually named "bb5_undecided_index"
+    #[serde(default = "default_bb_challenge_undecided_index")]
+    bb_challenge_undecided_index_path: String,
+
     /// file path and file name of bb_challenge file, usually named "all_5_states_undecided_machines_with_global_header"
     #[serde(default = "default_html_out_path")]
     html_out_path: String,
@@ -62,6 +67,10 @@ impl ConfigToml {
         &self.bb_challenge_filename_path
     }
 
+    pub fn bb_challenge_undecided_index_path(&self) -> &str {
+        &self.bb_challenge_undecided_index_path
+    }
+
     pub fn html_out_path(&self) -> &str {
         &self.html_out_path
     }
@@ -80,6 +89,7 @@ impl Default for ConfigToml {
         ConfigToml {
             id_calc_forward: default_id_calc_forward(),
             bb_challenge_filename_path: default_bb_challenge_file(),
+            bb_challenge_undecided_index_path: default_bb_challenge_undecided_index(),
             html_out_path: default_html_out_path(),
             html_tape_shifts: default_html_tape_shifts(),
             decider_timer_info_ms: default_decider_timer_info_ms(),
@@ -95,6 +105,10 @@ fn default_bb_challenge_file() -> String {
     "../res/all_5_states_undecided_machines_with_global_header".to_string()
 }
 
+fn default_bb_challenge_undecided_index() -> String {
+    "../res/bb5_undecided_index".to_string()
+}
+
 fn default_html_out_path() -> String {
     "../bb_result_html".to_string()
 }
@@ -36,12 +36,16 @@
 // TODO possibly move direction and symbol to bit 2, leaving only state in bit 1, reducing one filter operation for state.
 
 use crate::config::MAX_STATES;
-use crate::machine_binary::MachineBinary;
+use crate::format::standard_tm::{self, TRANSITION_UNDEFINED};
+use crate::machine_binary::{MachineBinary, State};
 use crate::machine_generic::TransitionGeneric;
+// The bit layout is shared with the no-std core.
+pub use crate::sim_core::{TransitionType, FILTER_STATE, STATE_HALT_BINARY};
+use crate::sim_core::{
+    DIRECTION_UNDEFINED, FILTER_ARRAY_ID, FILTER_DIR, FILTER_SYMBOL, SYMBOL_ONE, SYMBOL_ZERO,
+    TO_LEFT, TO_RIGHT,
+};
 
-/// Number format to represent a transition (lower 8 bit used for state, symbol and direction).
-/// Can be any type other than i8/u8 (potential extra info per transition). i16 seems fastest.
-pub type TransitionType = i16;
 /// Number format for direction which is either -1 or 1. Can be any iXX type, i16 seems fastest.
 pub type DirectionType = i16;
 pub const TRANSITION_BINARY_UNUSED: TransitionBinary = TransitionBinary {
@@ -93,10 +97,6 @@ pub const TRANSITION_0LB: TransitionBinary = TransitionBinary {
 };
 pub const TRANSITIONS_FOR_A0: [TransitionBinary; 2] = [TRANSITION_0RB, TRANSITION_1RB];
 
-const FILTER_SYMBOL: TransitionType = 0b0000_0001;
-const FILTER_DIR: TransitionType = 0b1100_0000;
-pub const FILTER_STATE: TransitionType = 0b0001_1110;
-const FILTER_ARRAY_ID: TransitionType = 0b0001_1111;
 pub const TR_BINARY_UNDEFINED: TransitionType = DIRECTION_UNDEFINED;
 pub const TR_BINARY_UNUSED: TransitionType = 0b0000_0000; // 0b1010_0001;
 pub const TR_BINARY_0LA: TransitionType = 0b0100_0010;
@@ -106,12 +106,6 @@ pub const TR_BINARY_1RA: TransitionType = 0b1100_0011;
 pub const TR_BINARY_0RB: TransitionType = 0b1100_0100;
 pub const TR_BINARY_1RB: TransitionType = 0b1100_0101;
 pub const TR_BINARY_1RZ_HALT: TransitionType = 0b1100_0001;
-const SYMBOL_ZERO: TransitionType = 0b0000_0000;
-const SYMBOL_ONE: TransitionType = 0b0000_0001;
-const DIRECTION_UNDEFINED: TransitionType = 0b1000_0000;
-const TO_RIGHT: TransitionType = 0b1100_0000;
-const TO_LEFT: TransitionType = 0b0100_0000;
-pub const STATE_HALT_BINARY: TransitionType = 0;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TransitionBinary {
@@ -191,16 +185,17 @@ impl TransitionBinary {
                 }
             }
             // Alphabetic states A-Y (up to MAX_STATES)
-            b'A'..=b'Y' => {
-                let num_state = state_char - b'A' + 1;
-                if num_state > MAX_STATES as u8 {
-                    return Err(TransitionError::StateOutOfRange(num_state));
+            // '-' is an error as it cannot be undefined if symbol is not undefined also.
+            // If symbol is defined, 0 or 'Z' are expected as halt char.
+            _ => {
+                let Some(num_state) = standard_tm::state_from_char(state_char) else {
+                    return Err(TransitionError::InvalidStateChar(state_char));
+                };
+                if num_state > MAX_STATES {
+                    return Err(TransitionError::StateOutOfRange(num_state as u8));
                 }
                 transition_bits |= (num_state as TransitionType) << 1;
             }
-            // '-' is an error as it cannot be undefined if symbol is not undefined also.
-            // If symbol is defined, 0 or 'Z' are expected as halt char.
-            _ => return Err(TransitionError::InvalidStateChar(state_char)),
         }
 
         // direction
@@ -269,6 +264,11 @@ impl TransitionBinary {
         (self.transition & FILTER_STATE) >> 1
     }
 
+    /// Next state as [State], e.g. for [MachineBinary::transition].
+    pub fn next_state(&self) -> State {
+        State::new(((self.transition & FILTER_STATE) >> 1) as usize)
+    }
+
     /// returns the state doubled as usize for array access
     pub fn state_x2(&self) -> usize {
         (self.transition & FILTER_STATE) as usize
@@ -276,11 +276,7 @@ impl TransitionBinary {
 
     /// Returns the state as char (A,B,C,...)
     pub fn state_to_char(&self) -> char {
-        if self.transition & FILTER_STATE == 0 {
-            'Z'
-        } else {
-            (((self.transition & FILTER_STATE) >> 1) as u8 + b'A' - 1) as char
-        }
+        standard_tm::state_to_char(((self.transition & FILTER_STATE) >> 1) as usize)
     }
 
     /// returns only 0 or 1, not undefined
@@ -348,26 +344,48 @@ impl TransitionBinary {
     /// The number can be calculated by (4 * n_states + 1), e.g. 21 for BB5. \
     /// Keep this order as 0RB is expected to be on pos 3.
     pub fn create_all_transition_permutations(n_states: usize) -> Vec<TransitionBinary> {
-        let mut transitions = Vec::new();
-        let mut tr: [u8; 3];
-
-        // all to right
-        for i in 1..=n_states {
-            // tr as symbol, direction, next state
-            tr = [0, 0, i as u8];
-            transitions.push(TransitionBinary::try_new(tr).unwrap());
-            // write symbol
-            tr[0] = 1;
-            transitions.push(TransitionBinary::try_new(tr).unwrap());
-        }
-        // all to left
-        for i in 1..=n_states {
-            // tr as symbol, direction, next state
-            tr = [0, 1, i as u8];
-            transitions.push(TransitionBinary::try_new(tr).unwrap());
-            // write symbol
-            tr[0] = 1;
-            transitions.push(TransitionBinary::try_new(tr).unwrap());
+        Self::create_all_transition_permutations_ordered(n_states, TransitionOrder::DirectionMajor)
+    }
+
+    /// This creates all transition permutations for one field in the given order, see [TransitionOrder]. \
+    /// Halt is always the last transition.
+    pub fn create_all_transition_permutations_ordered(
+        n_states: usize,
+        order: TransitionOrder,
+    ) -> Vec<TransitionBinary> {
+        let mut transitions = Vec::with_capacity(4 * n_states + 1);
+        // tr as symbol, direction (0 right, 1 left), next state
+        let mut push = |symbol: u8, direction: u8, state: usize| {
+            transitions.push(TransitionBinary::try_new([symbol, direction, state as u8]).unwrap());
+        };
+        match order {
+            TransitionOrder::DirectionMajor => {
+                for direction in 0..2 {
+                    for state in 1..=n_states {
+                        for symbol in 0..2 {
+                            push(symbol, direction, state);
+                        }
+                    }
+                }
+            }
+            TransitionOrder::SymbolMajor => {
+                for symbol in 0..2 {
+                    for direction in 0..2 {
+                        for state in 1..=n_states {
+                            push(symbol, direction, state);
+                        }
+                    }
+                }
+            }
+            TransitionOrder::StateMajor => {
+                for state in 1..=n_states {
+                    for direction in 0..2 {
+                        for symbol in 0..2 {
+                            push(symbol, direction, state);
+                        }
+                    }
+                }
+            }
         }
         // halt as last transition
         transitions.push(TRANSITION_BINARY_UNDEFINED);
@@ -376,6 +394,49 @@ impl TransitionBinary {
     }
 }
 
+/// Order of the transitions of one field in the enumeration, which defines the machine ids. \
+/// The named part changes slowest, the remaining parts keep the default order direction, state, symbol.
+/// Halt (---) is always last. For BB2:
+/// - DirectionMajor: 0RA, 1RA, 0RB, 1RB, 0LA, 1LA, 0LB, 1LB, --- (default)
+/// - SymbolMajor: 0RA, 0RB, 0LA, 0LB, 1RA, 1RB, 1LA, 1LB, ---
+/// - StateMajor: 0RA, 1RA, 0LA, 1LA, 0RB, 1RB, 0LB, 1LB, ---
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransitionOrder {
+    #[default]
+    DirectionMajor,
+    SymbolMajor,
+    StateMajor,
+}
+
+impl TransitionOrder {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransitionOrder::DirectionMajor => "direction-major",
+            TransitionOrder::SymbolMajor => "symbol-major",
+            TransitionOrder::StateMajor => "state-major",
+        }
+    }
+}
+
+impl std::fmt::Display for TransitionOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl TryFrom<&str> for TransitionOrder {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "direction-major" => Ok(TransitionOrder::DirectionMajor),
+            "symbol-major" => Ok(TransitionOrder::SymbolMajor),
+            "state-major" => Ok(TransitionOrder::StateMajor),
+            _ => Err(format!("Unknown transition order: {value}")),
+        }
+    }
+}
+
 impl Default for TransitionBinary {
     fn default() -> Self {
         TRANSITION_BINARY_UNUSED
@@ -448,7 +509,7 @@ impl From<&TransitionGeneric> for TransitionBinary {
 impl std::fmt::Display for TransitionBinary {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.transition {
-            TR_BINARY_UNDEFINED => write!(f, "---"),
+            TR_BINARY_UNDEFINED => write!(f, "{TRANSITION_UNDEFINED}"),
             TR_BINARY_UNUSED => write!(f, "   "),
             _ => {
                 let write_symbol = match self.transition & FILTER_SYMBOL {
@@ -457,9 +518,9 @@ impl std::fmt::Display for TransitionBinary {
                     _ => '-',
                 };
                 let direction = match self.transition & FILTER_DIR {
-                    TO_LEFT => 'L',
-                    TO_RIGHT => 'R',
-                    _ => return write!(f, "---"),
+                    TO_LEFT => standard_tm::DIR_LEFT_CHAR,
+                    TO_RIGHT => standard_tm::DIR_RIGHT_CHAR,
+                    _ => return write!(f, "{TRANSITION_UNDEFINED}"),
                 };
                 let next_state = self.state_to_char();
                 write!(f, "{write_symbol}{direction}{next_state}")
@@ -530,4 +591,23 @@ mod tests {
         let tm_out = t.to_standard_tm_text_format();
         assert_eq!(tm_in, tm_out);
     }
+
+    #[test]
+    fn transition_orders_same_transitions() {
+        let default = TransitionBinary::create_all_transition_permutations(3);
+        assert_eq!(default[2], TRANSITION_0RB);
+        for order in [TransitionOrder::SymbolMajor, TransitionOrder::StateMajor] {
+            let ordered = TransitionBinary::create_all_transition_permutations_ordered(3, order);
+            assert_eq!(ordered.len(), default.len());
+            assert!(ordered.iter().all(|tr| default.contains(tr)));
+            assert_ne!(ordered, default);
+            assert_eq!(*ordered.last().unwrap(), TRANSITION_BINARY_UNDEFINED);
+            assert_eq!(TransitionOrder::try_from(order.name()), Ok(order));
+        }
+        let symbol_major = TransitionBinary::create_all_transition_permutations_ordered(
+            2,
+            TransitionOrder::SymbolMajor,
+        );
+        assert_eq!(symbol_major[1], TRANSITION_0RB);
+    }
 }
@@ -3,14 +3,14 @@
 use std::fmt::Display;
 
 use crate::{
-    config::Config,
+    config::{Config, StepBig},
     decider::{
         self,
         decider_data_128::DeciderData128,
         decider_result::{BatchData, ResultUnitEndReason},
         Decider, DeciderId,
     },
-    machine_binary::{MachineId, NotableMachineBinary},
+    machine_binary::{MachineBinary, MachineId, NotableMachineBinary},
     status::MachineStatus,
 };
 
@@ -25,11 +25,14 @@ impl DeciderHalt128 {
         }
     }
 
-    fn decide_machine_with_self_referencing_transition(&mut self) -> MachineStatus {
+    fn decide_machine_with_self_referencing_transition(
+        &mut self,
+        machine: &MachineBinary,
+    ) -> MachineStatus {
         // loop over transitions to write tape
         loop {
             // check if done
-            if self.data.next_transition() {
+            if self.data.next_transition(machine) {
                 return self.data.status;
             }
 
@@ -42,11 +45,14 @@ impl DeciderHalt128 {
     /// Returns the MachineStatus:Hold with steps if steps were found within limits of tape and max steps. \
     /// This version has a long tape, so it is not restricted to the 128 bit range.
     /// This is not using the self reference speed-up and should only be used if those would mess up the tests.
-    fn decide_machine_without_self_referencing_transitions(&mut self) -> MachineStatus {
+    fn decide_machine_without_self_referencing_transitions(
+        &mut self,
+        machine: &MachineBinary,
+    ) -> MachineStatus {
         // loop over transitions to write tape
         loop {
             // check if done
-            if self.data.next_transition() {
+            if self.data.next_transition(machine) {
                 return self.data.status;
             }
 
@@ -71,23 +77,19 @@ impl Decider for DeciderHalt128 {
     fn decide_machine(&mut self, machine: &MachineId) -> MachineStatus {
         self.data.clear();
         // self.machine_id = machine.id();
-        self.data.transition_table = *machine.machine();
 
         #[cfg(feature = "enable_html_reports")]
         self.data.write_html_file_start(Self::decider_id(), machine);
 
         #[cfg(feature = "without_self_ref_acceleration")]
-        let result_status = self.decide_machine_without_self_referencing_transitions();
+        let result_status =
+            self.decide_machine_without_self_referencing_transitions(machine.machine());
 
         #[cfg(not(feature = "without_self_ref_acceleration"))]
-        let result_status = if self
-            .data
-            .transition_table
-            .has_self_referencing_transition_store_result()
-        {
-            self.decide_machine_with_self_referencing_transition()
+        let result_status = if machine.machine().has_self_referencing_transition() {
+            self.decide_machine_with_self_referencing_transition(machine.machine())
         } else {
-            self.decide_machine_without_self_referencing_transitions()
+            self.decide_machine_without_self_referencing_transitions(machine.machine())
         };
 
         #[cfg(feature = "enable_html_reports")]
@@ -101,6 +103,14 @@ impl Decider for DeciderHalt128 {
         d.decide_machine(machine)
     }
 
+    fn step_limit(&self) -> StepBig {
+        self.data.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.data.step_limit = step_limit;
+    }
+
     fn decider_run_batch(batch_data: &mut BatchData) -> ResultUnitEndReason {
         let decider = Self::new(batch_data.config);
         decider::decider_generic_run_batch(decider, batch_data)
@@ -114,7 +114,7 @@ impl DeciderHalt64 {
         self.num_steps += 1;
         let curr_read_symbol = self.get_current_symbol();
         let arr_id = self.tr.state_x2() + curr_read_symbol;
-        self.tr = self.transition_table.transition(arr_id);
+        self.tr = self.transition_table.transition_array_id(arr_id);
         #[cfg(all(debug_assertions, feature = "bb_debug"))]
         println!("{}", self.step_to_string());
 
@@ -136,7 +136,7 @@ impl DeciderHalt64 {
             self.high_bound += 1;
             if self.high_bound == TAPE_SIZE_BIT_U64 {
                 self.status = MachineStatus::Undecided(
-                    UndecidedReason::TapeLimitLeftBoundReached,
+                    UndecidedReason::TapeLimitRightBoundReached,
                     self.num_steps as StepBig,
                     32,
                 );
@@ -156,7 +156,7 @@ impl DeciderHalt64 {
         } else {
             if self.low_bound == 0 {
                 self.status = MachineStatus::Undecided(
-                    UndecidedReason::TapeLimitRightBoundReached,
+                    UndecidedReason::TapeLimitLeftBoundReached,
                     self.num_steps as StepBig,
                     32,
                 );
@@ -189,7 +189,7 @@ impl DeciderHalt64 {
         self.num_steps += 1;
         let curr_read_symbol = self.get_current_symbol();
         let arr_id = self.tr.state_x2() + curr_read_symbol;
-        self.tr = self.transition_table.transition(arr_id);
+        self.tr = self.transition_table.transition_array_id(arr_id);
         #[cfg(all(debug_assertions, feature = "bb_debug"))]
         println!("{}", self.step_to_string());
 
@@ -239,7 +239,7 @@ impl DeciderHalt64 {
 
             if self.high_bound == TAPE_SIZE_BIT_U64 {
                 self.status = MachineStatus::Undecided(
-                    UndecidedReason::TapeLimitLeftBoundReached,
+                    UndecidedReason::TapeLimitRightBoundReached,
                     self.num_steps as StepBig,
                     32,
                 );
@@ -256,7 +256,7 @@ impl DeciderHalt64 {
 
             if self.low_bound == 0 {
                 self.status = MachineStatus::Undecided(
-                    UndecidedReason::TapeLimitRightBoundReached,
+                    UndecidedReason::TapeLimitLeftBoundReached,
                     self.num_steps as StepBig,
                     32,
                 );
@@ -389,6 +389,14 @@ impl Decider for DeciderHalt64 {
         d.decide_machine(machine)
     }
 
+    fn step_limit(&self) -> StepBig {
+        self.step_limit
+    }
+
+    fn set_step_limit(&mut self, step_limit: StepBig) {
+        self.step_limit = step_limit;
+    }
+
     #[allow(unused_variables)]
     fn decider_run_batch(
         batch_data: &mut crate::decider::decider_result::BatchData,
@@ -429,7 +437,7 @@ mod tests {
         // println!("{}", check_result);
         assert_eq!(
             check_result,
-            MachineStatus::Undecided(UndecidedReason::TapeLimitRightBoundReached, 301, 32)
+            MachineStatus::Undecided(UndecidedReason::TapeLimitLeftBoundReached, 301, 32)
         );
     }
 }
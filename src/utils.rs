@@ -24,3 +24,28 @@ pub fn num_cpus_percentage(percent: usize) -> usize {
 pub fn file_exists(file_path: &str) -> bool {
     std::path::Path::new(file_path).exists()
 }
+
+/// Deserializes a &'static str, e.g. a decider name. \
+/// Each distinct string is leaked once and reused, so loading many results does not grow the memory.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_static_str<'de, D>(deserializer: D) -> Result<&'static str, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+    use std::sync::{Mutex, OnceLock};
+
+    static INTERNED: OnceLock<Mutex<hashbrown::HashSet<&'static str>>> = OnceLock::new();
+    let s = String::deserialize(deserializer)?;
+    let mut interned = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(s) = interned.get(s.as_str()) {
+        return Ok(s);
+    }
+    let s: &'static str = Box::leak(s.into_boxed_str());
+    interned.insert(s);
+
+    Ok(s)
+}
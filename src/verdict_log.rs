@@ -0,0 +1,246 @@
+//! Append-only verdict log of a run, one JSON object per line (JSONL). \
+//! Each machine which reaches a decider (is not eliminated by the pre-deciders) gets one record with the
+//! decider which decided it, or the last decider of the chain if it stays undecided. The records are
+//! collected in the batch result and written and flushed after each batch, so external tools can consume
+//! the full results of a run instead of the summaries only. \
+//! The log is written if [Config::write_verdict_log] is set, the file is [OutputLayout::verdict_log_file_path].
+//! # Example
+//! ```text
+//! {"id":2211,"machine":"1RB1LB_1LA0LC_---1LD_1RD0RA","decider":"Decider Halt","verdict":"halt","code":100,"steps":107}
+//! {"id":2212,"machine":"1RB1LB_1LA0LC_0RA1LD_1RD0RA","decider":"Decider Cycler","verdict":"non_halt","code":200,"steps":52}
+//! {"id":2213,"machine":"1RB1LB_1LA0LC_1LA1LD_1RD0RA","decider":"Decider Halt","verdict":"undecided","code":500,"steps":5000}
+//! ```
+//! The code is the stable [StatusCode](crate::status::StatusCode) of the status. The id is null for machines
+//! without id (e.g. from the enumerator), steps is null if the status has no steps.
+//!
+//! [Config::write_verdict_log]: crate::config::Config::write_verdict_log
+//! [OutputLayout::verdict_log_file_path]: crate::output_layout::OutputLayout::verdict_log_file_path
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::Config,
+    decider::{
+        decider_result::{DeciderResultStats, WarningKind},
+        DeciderConfig, DeciderName,
+    },
+    event_log::json_string,
+    machine_binary::MachineId,
+    output_layout::create_dir,
+    status::MachineStatus,
+};
+
+/// Final status of a machine and the decider which returned it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Verdict {
+    pub machine: MachineId,
+    pub decider_name: DeciderName,
+    pub status: MachineStatus,
+}
+
+impl Verdict {
+    /// Short name of the verdict: 'halt', 'non_halt', 'not_max' or 'undecided'.
+    pub fn name(&self) -> &'static str {
+        match self.status {
+            MachineStatus::DecidedHalt(_)
+            | MachineStatus::DecidedHaltField(_, _)
+            | MachineStatus::DecidedHaltDetail(_, _, _) => "halt",
+            MachineStatus::DecidedNonHalt(_) => "non_halt",
+            MachineStatus::DecidedNotMaxTooManyHaltTransitions
+            | MachineStatus::DecidedNotMaxNotAllStatesUsed
+            | MachineStatus::EliminatedPreDecider(_) => "not_max",
+            MachineStatus::Undecided(_, _, _) | MachineStatus::NoDecision => "undecided",
+        }
+    }
+
+    /// The verdict as one JSON line without line break, see [module documentation](self).
+    pub fn json_line(&self) -> String {
+        let json_number = |n: Option<u64>| match n {
+            Some(n) => n.to_string(),
+            None => "null".to_string(),
+        };
+        format!(
+            "{{\"id\":{},\"machine\":{},\"decider\":{},\"verdict\":\"{}\",\"code\":{},\"steps\":{}}}",
+            json_number(self.machine.id_as_option()),
+            json_string(&self.machine.to_standard_tm_text_format()),
+            json_string(self.decider_name),
+            self.name(),
+            self.status.as_code(),
+            json_number(self.status.steps().map(u64::from)),
+        )
+    }
+}
+
+/// Verdict log file of a run, see [module documentation](self).
+#[derive(Debug)]
+pub struct VerdictLog {
+    file_path: PathBuf,
+    writer: BufWriter<File>,
+    num_written: u64,
+}
+
+impl VerdictLog {
+    /// Opens the log for appending, the directory is created if required.
+    pub fn create(file_path: impl Into<PathBuf>) -> io::Result<Self> {
+        let file_path = file_path.into();
+        if let Some(dir) = file_path.parent() {
+            create_dir(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)?;
+        Ok(Self {
+            file_path,
+            writer: BufWriter::new(file),
+            num_written: 0,
+        })
+    }
+
+    /// Opens the log of the output layout if [Config::write_verdict_log] is set. \
+    /// Returns None if the log is not configured. If it cannot be opened, a warning is added to the result.
+    pub(crate) fn start_run(
+        decider_configs: &[DeciderConfig],
+        result: &mut DeciderResultStats,
+    ) -> Option<Self> {
+        let config: &Config = decider_configs.first()?.config();
+        if !config.write_verdict_log() {
+            return None;
+        }
+        let file_path = config.output_layout().verdict_log_file_path(config);
+        match Self::create(&file_path) {
+            Ok(log) => Some(log),
+            Err(e) => {
+                result.add_warning(WarningKind::VerdictLog, || {
+                    format!("{}: {e}", file_path.display())
+                });
+                None
+            }
+        }
+    }
+
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    /// Number of verdicts written by this log.
+    pub fn num_written(&self) -> u64 {
+        self.num_written
+    }
+
+    /// Appends the verdicts and flushes the file.
+    pub fn write_verdicts(&mut self, verdicts: &[Verdict]) -> io::Result<()> {
+        for verdict in verdicts.iter() {
+            writeln!(self.writer, "{}", verdict.json_line())?;
+        }
+        self.num_written += verdicts.len() as u64;
+        self.writer.flush()
+    }
+}
+
+/// Outputs of the verdicts of a run: the verdict log and the SQLite result store (feature 'sqlite').
+#[derive(Debug, Default)]
+pub(crate) struct VerdictSinks {
+    log: Option<VerdictLog>,
+    #[cfg(feature = "sqlite")]
+    sqlite: Option<crate::results_store_sqlite::ResultsStoreSqlite>,
+}
+
+impl VerdictSinks {
+    /// Opens the configured outputs, see [VerdictLog::start_run].
+    pub(crate) fn start_run(
+        decider_configs: &[DeciderConfig],
+        result: &mut DeciderResultStats,
+    ) -> Self {
+        Self {
+            log: VerdictLog::start_run(decider_configs, result),
+            #[cfg(feature = "sqlite")]
+            sqlite: crate::results_store_sqlite::ResultsStoreSqlite::start_run(
+                decider_configs,
+                result,
+            ),
+        }
+    }
+}
+
+/// Takes the verdicts collected in the result and writes them into the outputs. If an output cannot be written,
+/// a warning is added to the result. Without outputs the verdicts are just dropped.
+pub(crate) fn write_verdicts_or_warn(sinks: &mut VerdictSinks, result: &mut DeciderResultStats) {
+    let verdicts = result.take_verdicts();
+    if let Some(log) = sinks.log.as_mut() {
+        if let Err(e) = log.write_verdicts(&verdicts) {
+            let file_path = log.file_path().to_path_buf();
+            result.add_warning(WarningKind::VerdictLog, || {
+                format!("{}: {e}", file_path.display())
+            });
+        }
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(store) = sinks.sqlite.as_mut() {
+        if let Err(e) = store.write_batch(&verdicts, result) {
+            let file_path = store.file_path().to_path_buf();
+            result.add_warning(WarningKind::ResultsSqlite, || {
+                format!("{}: {e}", file_path.display())
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data_provider::enumerator_binary::{EnumeratorBinary, EnumeratorType},
+        decider::{
+            decider_engine::batch_run_decider_chain_data_provider_single_thread_reporting,
+            DeciderStandard,
+        },
+        event_log::json_field,
+        output_layout::{OutputLayout, VERDICT_LOG_FILE_NAME},
+    };
+
+    #[test]
+    fn verdict_log_written_by_engine() {
+        let dir = std::env::temp_dir().join("bb_challenge_verdict_log_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = Config::builder(3)
+            .machine_limit(0)
+            .output_layout(OutputLayout::new(&dir))
+            .write_verdict_log(true)
+            .build();
+        let deciders = [
+            DeciderStandard::Cycler.decider_config(&config),
+            DeciderStandard::Hold.decider_config(&config),
+        ];
+        let data_provider =
+            EnumeratorBinary::new(EnumeratorType::EnumeratorReducedForward, &config);
+        let result = batch_run_decider_chain_data_provider_single_thread_reporting(
+            &deciders,
+            data_provider,
+            None,
+        );
+
+        let text = std::fs::read_to_string(dir.join(VERDICT_LOG_FILE_NAME)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(result.warnings().count(WarningKind::VerdictLog), 0);
+        let lines: Vec<&str> = text.lines().collect();
+        let count = |verdict: &str| {
+            lines
+                .iter()
+                .filter(|l| json_field(l, "verdict") == Some(verdict))
+                .count() as u64
+        };
+        assert_eq!(count("halt"), result.num_halt());
+        assert_eq!(count("undecided"), result.num_undecided());
+        assert!(count("non_halt") > 0);
+        // BB3 max is decided by the cycler, which also detects halting machines
+        assert!(lines.iter().any(|l| l.starts_with(
+            "{\"id\":null,\"machine\":\"1RB---_1LB0RC_1LC1LA\",\"decider\":\"Decider Cycler\",\
+            \"verdict\":\"halt\",\"code\":101,\"steps\":21}"
+        )));
+    }
+}